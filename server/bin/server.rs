@@ -16,16 +16,22 @@ use tracing::{debug, error, info, warn};
 use vixen_server::{
     api::{AppState, build_router},
     build_info,
+    cli::Cli,
     config::Config,
     database::{Database, Redis, ensure_watched_chats},
     jobs,
+    services::api_keys::ApiKeyCipher,
     services::captcha::{CaptchaService, CaptchaState, Fonts},
     services::cas_client::CasClient,
+    services::live_feed::LiveFeed,
+    services::llm::LlmProviders,
     services::moderation_service::ModerationService,
-    services::openai_client::OpenAiClient,
+    services::outbound::{Limits, OutboundQueue},
+    services::public_report::PublicReportService,
     services::report_service::ReportService,
     services::spam::service::SpamService,
     services::summary_service::SummaryService,
+    services::user_info::UserInfoCache,
     telegram::commands::Command,
     telegram::{WatchedChats, build_dispatcher},
     telemetry,
@@ -43,15 +49,25 @@ async fn main() -> anyhow::Result<()> {
         }
     }
 
-    let config = Arc::new(Config::parse());
+    let cli = Cli::parse();
+    let config = Arc::new(cli.config);
 
     if let Err(e) = config.validate() {
         eprintln!("configuration error: {e}");
         std::process::exit(2);
     }
 
-    // Hold the guard until main returns: dropping it flushes the JSON file writer.
-    let _telemetry_guard = telemetry::init(&config.log_level, &config.log_dir);
+    // Hold the guard until main returns: dropping it flushes buffered OTLP
+    // spans and the JSON file writer.
+    let _telemetry_guard = telemetry::init(
+        &config.log_level,
+        &config.log_dir,
+        config.otlp_endpoint.as_deref(),
+        config.otlp_sample_ratio,
+    );
+    // Recorder goes in before any service is built so no sample is lost.
+    // `chat_id` labels are bounded to the watched chats.
+    telemetry::metrics::install(&config.chats);
 
     info!(
         version = build_info::VERSION,
@@ -62,9 +78,14 @@ async fn main() -> anyhow::Result<()> {
         environment = %config.environment,
         chats = config.chats.len(),
         bot_token = %config.bot_token,
+        otlp = config.otlp_endpoint.is_some(),
         "vixen-server starting"
     );
 
+    if let Some(command) = cli.command {
+        return command.run(&config).await;
+    }
+
     let cancel = CancellationToken::new();
     spawn_signal_listener(cancel.clone());
 
@@ -107,13 +128,32 @@ async fn main() -> anyhow::Result<()> {
     // can capture a clone — Bot is cheap to clone.
     let bot = Bot::new(config.bot_token.expose());
 
+    // Shared by the summary and the opt-in LLM spam step.
+    let llm = Arc::new(LlmProviders::new(config.openai_base_url.clone()));
+    let api_keys = Arc::new(ApiKeyCipher::from_config(&config).context("secrets key")?);
+
     let cas = CasClient::new(redis.clone(), config.cas_base_url.clone());
-    let spam = Arc::new(SpamService::new(db.pool().clone(), cas));
-    let moderation = ModerationService::new(db.pool().clone(), bot.clone());
+    let spam = Arc::new(SpamService::new(
+        db.pool().clone(),
+        cas,
+        llm.clone(),
+        api_keys.clone(),
+    ));
+    // One queue for every chat-facing Bot API call, so the global cap holds
+    // across handlers, jobs and HTTP routes.
+    let outbound = OutboundQueue::new(Limits::default());
+    // Relays `live:*` from every replica to this one's SSE streams.
+    let (live, live_relay) = LiveFeed::start(redis.clone(), cancel.clone());
+    let moderation = ModerationService::new(
+        db.pool().clone(),
+        bot.clone(),
+        outbound.clone(),
+        live.clone(),
+    );
 
     let reports = Arc::new(ReportService::new(db.pool().clone()));
-    let openai = Arc::new(OpenAiClient::new(config.openai_base_url.clone()));
-    let summary = SummaryService::new(db.pool().clone(), openai);
+    let summary = SummaryService::new(db.pool().clone(), llm, api_keys.clone());
+    let public_reports = PublicReportService::new(reports.clone(), config.chats.clone());
 
     let state = AppState {
         config: config.clone(),
@@ -125,6 +165,12 @@ async fn main() -> anyhow::Result<()> {
         moderation: moderation.clone(),
         reports: reports.clone(),
         summary: summary.clone(),
+        api_keys,
+        public_reports,
+        bot: bot.clone(),
+        outbound,
+        live,
+        user_info: UserInfoCache::new(db.pool().clone()),
     };
 
     let http_handle = spawn_http(&config.address, state.clone(), cancel.clone())
@@ -137,6 +183,9 @@ async fn main() -> anyhow::Result<()> {
 
     let dispatcher_handle = spawn_dispatcher(bot.clone(), &config, state.clone(), cancel.clone());
     let job_handles = jobs::spawn_all(bot.clone(), state.clone(), cancel.clone());
+    let metrics_handle = telemetry::metrics::spawn_upkeep(cancel.clone());
+    // Marks Redis unavailable / available again; see `database::redis`.
+    let redis_probe = redis.spawn_probe(cancel.clone());
 
     // Surface JoinErrors (panics, abort) from each long-running task. Without
     // this, a job panic during shutdown is swallowed and the operator sees
@@ -156,6 +205,15 @@ async fn main() -> anyhow::Result<()> {
         if let Err(e) = pubsub_handle.await {
             error!(?e, "redis pubsub task join error");
         }
+        if let Err(e) = live_relay.await {
+            error!(?e, "live feed relay task join error");
+        }
+        if let Err(e) = metrics_handle.await {
+            error!(?e, "metrics upkeep task join error");
+        }
+        if let Err(e) = redis_probe.await {
+            error!(?e, "redis probe task join error");
+        }
     };
 
     cancel.cancelled().await;
@@ -0,0 +1,236 @@
+//! Spam corpus evaluation. Runs labelled samples through the pure steps of
+//! the cascade (`services::spam::eval`) and prints precision / recall, a
+//! confusion matrix per rule, a `spam_threshold` sweep and a per-phrase
+//! ablation for tuning `spam_weights`. No Postgres, Redis or CAS.
+//!
+//! ```text
+//! cargo run --example spam_eval -- [PATH…] [--threshold 1.0]
+//!     [--weights weights.json] [--near-dup 0.8] [--sweep 0.5,1,2]
+//!     [--show-misses]
+//! ```
+//!
+//! `PATH` is a corpus YAML or a directory of them (default
+//! `tests/spam_corpus`). Labels come from the corpus keys: `must_ban`,
+//! `must_delete` and `must_ban_after_first` are spam, `must_allow` is ham.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use clap::Parser;
+use serde::Deserialize;
+use vixen_server::services::spam::eval::{self, Confusion, Params, Rule, Sample};
+use vixen_server::services::spam::phrases::SpamWeights;
+
+const EXCERPT_CHARS: usize = 80;
+
+#[derive(Parser, Debug)]
+#[command(about = "Evaluate the spam cascade against a labelled corpus")]
+struct Args {
+    /// Corpus YAML files or directories of them.
+    paths: Vec<PathBuf>,
+    /// `spam_threshold` to evaluate at.
+    #[arg(long, default_value_t = 1.0)]
+    threshold: f32,
+    /// `spam_weights` overrides: a JSON object of phrase → weight.
+    #[arg(long)]
+    weights: Option<PathBuf>,
+    /// Enable the near-dup step at this Jaccard similarity (0..=1).
+    #[arg(long)]
+    near_dup: Option<f32>,
+    /// Thresholds for the sweep.
+    #[arg(long, value_delimiter = ',', default_value = "0.5,1,1.5,2,2.5,3")]
+    sweep: Vec<f32>,
+    /// List every misclassified sample.
+    #[arg(long)]
+    show_misses: bool,
+}
+
+/// Same schema as `tests/spam_pipeline.rs`.
+#[derive(Default, Debug, Deserialize)]
+#[serde(default)]
+struct CorpusFile {
+    must_ban: Vec<String>,
+    must_delete: Vec<String>,
+    must_allow: Vec<String>,
+    must_ban_after_first: Vec<String>,
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    let paths = if args.paths.is_empty() {
+        vec![Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/spam_corpus")]
+    } else {
+        args.paths.clone()
+    };
+    let files = corpus_files(&paths)?;
+    let mut samples = Vec::new();
+    for file in &files {
+        load(file, &mut samples)?;
+    }
+    if samples.is_empty() {
+        bail!("no samples under {paths:?}");
+    }
+
+    let weights = match &args.weights {
+        Some(path) => {
+            let raw = std::fs::read_to_string(path)
+                .with_context(|| format!("read {}", path.display()))?;
+            let json: serde_json::Value =
+                serde_json::from_str(&raw).with_context(|| format!("parse {}", path.display()))?;
+            SpamWeights::from_json(&json)
+        }
+        None => SpamWeights::default(),
+    };
+    let params = Params {
+        threshold: args.threshold,
+        weights,
+        near_dup: args.near_dup,
+    };
+
+    let spam = samples.iter().filter(|s| s.spam).count();
+    println!(
+        "corpus: {} samples ({spam} spam, {} ham) from {} file(s)",
+        samples.len(),
+        samples.len() - spam,
+        files.len()
+    );
+    println!(
+        "params: threshold={} weights={} near_dup={}",
+        params.threshold,
+        args.weights
+            .as_deref()
+            .map_or("default".to_owned(), |p| p.display().to_string()),
+        params.near_dup.map_or("off".to_owned(), |t| t.to_string())
+    );
+
+    let evaluation = eval::evaluate(&samples, &params);
+    println!();
+    println!("{}", header("rule"));
+    println!("{}", row("cascade", &evaluation.overall));
+    for (rule, confusion) in &evaluation.per_rule {
+        if *rule == Rule::NearDup && params.near_dup.is_none() {
+            continue;
+        }
+        println!("{}", row(rule.as_str(), confusion));
+    }
+
+    println!();
+    println!("{}", header("threshold"));
+    for (threshold, confusion) in eval::sweep_threshold(&samples, &params, &args.sweep) {
+        println!("{}", row(&threshold.to_string(), &confusion));
+    }
+
+    let phrases = eval::phrase_ablation(&samples, &params);
+    if !phrases.is_empty() {
+        println!();
+        // Precision / recall are for the run with that phrase's weight at 0.
+        println!(
+            "{}",
+            phrase_row("phrase", "weight", "spam", "ham", "precision", "recall")
+        );
+        for p in &phrases {
+            println!(
+                "{}",
+                phrase_row(
+                    p.phrase,
+                    &p.weight.to_string(),
+                    &p.spam_hits.to_string(),
+                    &p.ham_hits.to_string(),
+                    &pct(p.without.precision()),
+                    &pct(p.without.recall()),
+                )
+            );
+        }
+    }
+
+    if args.show_misses {
+        println!();
+        for (sample, outcome) in samples.iter().zip(&evaluation.outcomes) {
+            let flagged = outcome.verdict.is_some();
+            if flagged == sample.spam {
+                continue;
+            }
+            let label = if flagged { "FP" } else { "FN" };
+            let why = match outcome.verdict {
+                Some(rule) => rule.as_str().to_owned(),
+                None if outcome.short => "short".to_owned(),
+                None => format!("score {:.2} {:?}", outcome.score, outcome.matched),
+            };
+            let excerpt: String = sample.text.chars().take(EXCERPT_CHARS).collect();
+            println!("{label} {} ({why}): {excerpt}", sample.origin);
+        }
+    }
+    Ok(())
+}
+
+fn header(label: &str) -> String {
+    format!(
+        "{label:<12} {:>5} {:>5} {:>5} {:>5}  {:>9} {:>7} {:>7}",
+        "tp", "fp", "tn", "fn", "precision", "recall", "f1"
+    )
+}
+
+fn row(label: &str, c: &Confusion) -> String {
+    format!(
+        "{label:<12} {:>5} {:>5} {:>5} {:>5}  {:>9} {:>7} {:>7}",
+        c.tp,
+        c.fp,
+        c.tn,
+        c.fn_,
+        pct(c.precision()),
+        pct(c.recall()),
+        pct(c.f1())
+    )
+}
+
+fn phrase_row(phrase: &str, weight: &str, spam: &str, ham: &str, p: &str, r: &str) -> String {
+    format!("{phrase:<40} {weight:>6} {spam:>5} {ham:>5}  {p:>9} {r:>9}")
+}
+
+fn pct(value: Option<f64>) -> String {
+    value.map_or("n/a".to_owned(), |v| format!("{:.1}%", v * 100.0))
+}
+
+fn corpus_files(paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            let mut found: Vec<PathBuf> = std::fs::read_dir(path)
+                .with_context(|| format!("read {}", path.display()))?
+                .filter_map(|e| e.ok().map(|e| e.path()))
+                .filter(|p| p.extension().and_then(|s| s.to_str()) == Some("yaml"))
+                .collect();
+            found.sort();
+            files.extend(found);
+        } else {
+            files.push(path.clone());
+        }
+    }
+    Ok(files)
+}
+
+fn load(path: &Path, samples: &mut Vec<Sample>) -> Result<()> {
+    let raw = std::fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+    let corpus: CorpusFile =
+        serde_yaml::from_str(&raw).with_context(|| format!("parse {}", path.display()))?;
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let mut push = |key: &str, texts: &[String], spam: bool, known: bool| {
+        for (i, text) in texts.iter().enumerate() {
+            samples.push(Sample {
+                text: text.clone(),
+                spam,
+                known,
+                origin: format!("{name}:{key}[{i}]"),
+            });
+        }
+    };
+    push("must_ban", &corpus.must_ban, true, true);
+    push("must_delete", &corpus.must_delete, true, false);
+    push("must_allow", &corpus.must_allow, false, false);
+    // Fed twice, as in the pipeline test: the second copy should dedup.
+    for _ in 0..2 {
+        let texts = &corpus.must_ban_after_first;
+        push("must_ban_after_first", texts, true, false);
+    }
+    Ok(())
+}
@@ -0,0 +1,106 @@
+### Vixen message catalog — English.
+###
+### Every key here must exist in every other `locales/*.ftl` file; the
+### `i18n` unit tests fail otherwise. Arguments are `$snake_case`. Strings
+### that end up inside MarkdownV2 are escaped by the caller, never here.
+
+## Captcha captions (plain text, one sentence per line)
+
+captcha-welcome = 👋 { $mention }, welcome!
+captcha-solve-prompt = 🔐 Please solve the captcha to start chatting.
+captcha-attempts-left = 🎯 Attempts left: { $count }
+captcha-verification = 🔐 Captcha verification
+captcha-enter-digits = Enter the 4 digits from the image.
+captcha-wrong = ❌ Wrong code, try again.
+captcha-not-yours = This isn't your captcha.
+
+## Command replies
+
+cmd-help =
+    Vixen anti-spam bot — captcha + spam pipeline.
+    /help — this message
+    /status — bot status in this chat
+    /verify (reply or <user_id>) — moderator: manually verify a user
+    /ban (reply or <user_id> [reason]) — moderator: ban a user
+    /unban <user_id> — moderator: lift a ban
+    /mod grant|revoke (reply or <user_id>) [role] — admin: manage moderators
+cmd-forbidden = Only chat moderators or admins can run { $command }.
+cmd-forbidden-role = Your role ({ $role }) can't run { $command }.
+cmd-status = Vixen is watching this chat.
+cmd-status-appeal = Banned by mistake? Appeal here: { $link }
+cmd-cooldown = { $command }: wait { $seconds ->
+    [one] { $seconds } more second
+   *[other] { $seconds } more seconds
+    }.
+verify-usage = Reply to a user or pass /verify <user_id>.
+verify-done = Verified user { $user_id }.
+verify-already = User { $user_id } was already verified.
+verify-unexpected = Unexpected verify state.
+ban-usage = Reply to a user's message or pass /ban <user_id> [reason].
+ban-already = User { $user_id } is already banned.
+ban-failed = Ban failed; check bot permissions.
+unban-usage = Usage: /unban <user_id>
+unban-done = Unbanned { $user_id }.
+unban-not-banned = User { $user_id } is not currently banned.
+unban-failed = Unban failed; check bot permissions.
+mod-usage =
+    Usage: /mod grant (reply or <user_id>) [owner|admin|moderator|viewer]
+    /mod revoke (reply or <user_id>)
+mod-cannot-change = Your role ({ $role }) can't change a user with role { $target_role }.
+mod-cannot-grant = Your role ({ $role }) can't grant { $target_role }.
+mod-granted = User { $user_id } is now { $role }.
+mod-revoked = Revoked the role of user { $user_id }.
+mod-no-role = User { $user_id } has no role in this chat.
+no-permission = You don't have permission to do that in this chat.
+report-failed = Couldn't generate the report. Details are in the log.
+summary-unavailable = The summary is temporarily unavailable. Try again later.
+summary-skip-no-key = No OpenAI key is set for this chat. Set chat_config.openai_api_key from the dashboard.
+summary-skip-disabled = The AI summary is disabled for this chat (chat_config.summary_enabled = FALSE).
+summary-skip-no-messages = No messages to summarise. Turn on chat_config.log_allowed_messages so the bot keeps messages for the AI.
+summary-skip-budget = The daily token budget is spent ({ $used } of { $budget }).
+
+## Appeals (private chat with the bot)
+
+appeal-intro = Vixen is an anti-spam bot. If you were banned in a chat it protects, open the appeal link from that chat's rules to ask for a review.
+appeal-ban-notice = You were banned in { $chat }. If you think this was a mistake, you can appeal: { $link }
+appeal-prompt = Send one message explaining why the ban should be lifted. Moderators will review it. This request expires in { $minutes } minutes.
+appeal-not-banned = You are not banned in that chat.
+appeal-already-pending = Your appeal for that chat is already waiting for a moderator.
+appeal-cooldown = Your last appeal for that chat was denied. You can appeal again in { $hours ->
+    [one] { $hours } hour
+   *[other] { $hours } hours
+    }.
+appeal-sent = Appeal sent. You'll get a message here once it's reviewed.
+appeal-already-decided = This appeal was already decided.
+appeal-approved-toast = Appeal approved; user unbanned and verified.
+appeal-denied-toast = Appeal denied.
+appeal-approved-notice = Your appeal was approved. You can rejoin the chat.
+appeal-denied-notice = Your appeal was reviewed and denied.
+
+## Reports (rendered into MarkdownV2 / HTML by the caller)
+
+report-title-daily = Daily report
+report-title-weekly = Weekly report
+report-title-monthly = Monthly report
+report-title-today = Today's snapshot
+report-title-on-demand = On-demand report
+report-messages = Messages
+report-deleted = Deleted
+report-verified = Verified
+report-banned = Banned
+report-captcha = Captcha
+report-captcha-issued = Issued
+report-captcha-solved = Solved
+report-captcha-expired = Expired
+report-top-phrases = Top phrases
+report-ban-reasons = Top ban reasons
+report-captcha-trend = Captcha solve rate by week
+report-last-7-days = Last 7 days
+# One character per weekday, Monday first.
+report-weekday-initials = MTWTFSS
+
+## Public report page
+
+public-report-for = report for
+public-top-phrases = Top spam phrases
+public-chart-alt = Chat activity chart
@@ -0,0 +1,116 @@
+### Vixen message catalog — Russian.
+###
+### Every key here must exist in every other `locales/*.ftl` file; the
+### `i18n` unit tests fail otherwise. Plural selectors use CLDR categories:
+### `one` (1, 21, 31…), `few` (2–4, 22–24…), `many` (0, 5–20, 25…).
+
+## Captcha captions (plain text, one sentence per line)
+
+captcha-welcome = 👋 { $mention }, добро пожаловать!
+captcha-solve-prompt = 🔐 Решите капчу, чтобы писать в чат.
+captcha-attempts-left = 🎯 { $count ->
+    [one] Осталась { $count } попытка
+    [few] Осталось { $count } попытки
+   *[many] Осталось { $count } попыток
+    }
+captcha-verification = 🔐 Проверка капчи
+captcha-enter-digits = Введите 4 цифры с картинки.
+captcha-wrong = ❌ Неверный код, попробуйте ещё раз.
+captcha-not-yours = Это не ваша капча.
+
+## Command replies
+
+cmd-help =
+    Vixen — антиспам-бот: капча + спам-фильтр.
+    /help — это сообщение
+    /status — статус бота в этом чате
+    /verify (ответом или <user_id>) — модератор: верифицировать пользователя вручную
+    /ban (ответом или <user_id> [причина]) — модератор: забанить пользователя
+    /unban <user_id> — модератор: снять бан
+    /mod grant|revoke (ответом или <user_id>) [роль] — админ: управление модераторами
+cmd-forbidden = Команду { $command } могут запускать только модераторы и админы чата.
+cmd-forbidden-role = Ваша роль ({ $role }) не позволяет запускать { $command }.
+cmd-status = Vixen следит за этим чатом.
+cmd-status-appeal = Забанены по ошибке? Подайте апелляцию: { $link }
+cmd-cooldown = { $command }: подождите ещё { $seconds } { $seconds ->
+    [one] секунду
+    [few] секунды
+   *[many] секунд
+    }.
+verify-usage = Ответьте на сообщение пользователя или укажите /verify <user_id>.
+verify-done = Пользователь { $user_id } верифицирован.
+verify-already = Пользователь { $user_id } уже верифицирован.
+verify-unexpected = Неожиданное состояние верификации.
+ban-usage = Ответьте на сообщение пользователя или укажите /ban <user_id> [причина].
+ban-already = Пользователь { $user_id } уже забанен.
+ban-failed = Не удалось забанить; проверьте права бота.
+unban-usage = Использование: /unban <user_id>
+unban-done = Пользователь { $user_id } разбанен.
+unban-not-banned = Пользователь { $user_id } сейчас не забанен.
+unban-failed = Не удалось разбанить; проверьте права бота.
+mod-usage =
+    Использование: /mod grant (ответом или <user_id>) [owner|admin|moderator|viewer]
+    /mod revoke (ответом или <user_id>)
+mod-cannot-change = Ваша роль ({ $role }) не позволяет менять пользователя с ролью { $target_role }.
+mod-cannot-grant = Ваша роль ({ $role }) не позволяет выдать { $target_role }.
+mod-granted = Пользователь { $user_id } теперь { $role }.
+mod-revoked = Роль пользователя { $user_id } отозвана.
+mod-no-role = У пользователя { $user_id } нет роли в этом чате.
+no-permission = У вас нет прав на это действие в этом чате.
+report-failed = Не удалось сгенерировать отчёт. Подробности в логе.
+summary-unavailable = Сводка временно недоступна. Попробуйте позже.
+summary-skip-no-key = OpenAI ключ не задан для этого чата. Задайте chat_config.openai_api_key через дашборд.
+summary-skip-disabled = AI-сводка отключена для этого чата (chat_config.summary_enabled = FALSE).
+summary-skip-no-messages = Нет сообщений для сводки. Включите chat_config.log_allowed_messages, чтобы бот сохранял сообщения для AI.
+summary-skip-budget = Дневной лимит токенов исчерпан ({ $used } из { $budget }).
+
+## Appeals (private chat with the bot)
+
+appeal-intro = Vixen — антиспам-бот. Если вас забанили в чате, который он защищает, откройте ссылку для апелляции из правил этого чата.
+appeal-ban-notice = Вас забанили в { $chat }. Если это ошибка, подайте апелляцию: { $link }
+appeal-prompt = Отправьте одно сообщение с объяснением, почему бан стоит снять. Модераторы его рассмотрят. Запрос действует { $minutes } { $minutes ->
+    [one] минуту
+    [few] минуты
+   *[many] минут
+    }.
+appeal-not-banned = Вы не забанены в этом чате.
+appeal-already-pending = Ваша апелляция для этого чата уже ждёт модератора.
+appeal-cooldown = Ваша предыдущая апелляция для этого чата отклонена. Подать новую можно через { $hours } { $hours ->
+    [one] час
+    [few] часа
+   *[many] часов
+    }.
+appeal-sent = Апелляция отправлена. Ответ придёт сюда после рассмотрения.
+appeal-already-decided = По этой апелляции уже принято решение.
+appeal-approved-toast = Апелляция одобрена; пользователь разбанен и верифицирован.
+appeal-denied-toast = Апелляция отклонена.
+appeal-approved-notice = Ваша апелляция одобрена. Можете вернуться в чат.
+appeal-denied-notice = Ваша апелляция рассмотрена и отклонена.
+
+## Reports (rendered into MarkdownV2 / HTML by the caller)
+
+report-title-daily = Ежедневный отчёт
+report-title-weekly = Недельный отчёт
+report-title-monthly = Месячный отчёт
+report-title-today = Сводка за сегодня
+report-title-on-demand = Отчёт по запросу
+report-messages = Сообщений
+report-deleted = Удалено
+report-verified = Верифицировано
+report-banned = Забанено
+report-captcha = Капча
+report-captcha-issued = Выдано
+report-captcha-solved = Решено
+report-captcha-expired = Истекло
+report-top-phrases = Частые фразы
+report-ban-reasons = Причины банов
+report-captcha-trend = Решаемость капчи по неделям
+report-last-7-days = 7 дней
+# One character per weekday, Monday first.
+report-weekday-initials = ПВСЧПСВ
+
+## Public report page
+
+public-report-for = отчёт за
+public-top-phrases = Частые спам-фразы
+public-chart-alt = График активности чата
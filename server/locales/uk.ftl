@@ -0,0 +1,116 @@
+### Vixen message catalog — Ukrainian.
+###
+### Every key here must exist in every other `locales/*.ftl` file; the
+### `i18n` unit tests fail otherwise. Plural selectors use CLDR categories:
+### `one` (1, 21, 31…), `few` (2–4, 22–24…), `many` (0, 5–20, 25…).
+
+## Captcha captions (plain text, one sentence per line)
+
+captcha-welcome = 👋 { $mention }, ласкаво просимо!
+captcha-solve-prompt = 🔐 Розв'яжіть капчу, щоб писати в чат.
+captcha-attempts-left = 🎯 { $count ->
+    [one] Залишилася { $count } спроба
+    [few] Залишилося { $count } спроби
+   *[many] Залишилося { $count } спроб
+    }
+captcha-verification = 🔐 Перевірка капчі
+captcha-enter-digits = Введіть 4 цифри з картинки.
+captcha-wrong = ❌ Неправильний код, спробуйте ще раз.
+captcha-not-yours = Це не ваша капча.
+
+## Command replies
+
+cmd-help =
+    Vixen — антиспам-бот: капча + спам-фільтр.
+    /help — це повідомлення
+    /status — статус бота в цьому чаті
+    /verify (відповіддю або <user_id>) — модератор: верифікувати користувача вручну
+    /ban (відповіддю або <user_id> [причина]) — модератор: забанити користувача
+    /unban <user_id> — модератор: зняти бан
+    /mod grant|revoke (відповіддю або <user_id>) [роль] — адмін: керування модераторами
+cmd-forbidden = Команду { $command } можуть запускати лише модератори та адміни чату.
+cmd-forbidden-role = Ваша роль ({ $role }) не дозволяє запускати { $command }.
+cmd-status = Vixen стежить за цим чатом.
+cmd-status-appeal = Забанені помилково? Подайте апеляцію: { $link }
+cmd-cooldown = { $command }: зачекайте ще { $seconds } { $seconds ->
+    [one] секунду
+    [few] секунди
+   *[many] секунд
+    }.
+verify-usage = Дайте відповідь на повідомлення користувача або вкажіть /verify <user_id>.
+verify-done = Користувача { $user_id } верифіковано.
+verify-already = Користувач { $user_id } уже верифікований.
+verify-unexpected = Неочікуваний стан верифікації.
+ban-usage = Дайте відповідь на повідомлення користувача або вкажіть /ban <user_id> [причина].
+ban-already = Користувач { $user_id } уже забанений.
+ban-failed = Не вдалося забанити; перевірте права бота.
+unban-usage = Використання: /unban <user_id>
+unban-done = Користувача { $user_id } розбанено.
+unban-not-banned = Користувач { $user_id } зараз не забанений.
+unban-failed = Не вдалося розбанити; перевірте права бота.
+mod-usage =
+    Використання: /mod grant (відповіддю або <user_id>) [owner|admin|moderator|viewer]
+    /mod revoke (відповіддю або <user_id>)
+mod-cannot-change = Ваша роль ({ $role }) не дозволяє змінювати користувача з роллю { $target_role }.
+mod-cannot-grant = Ваша роль ({ $role }) не дозволяє видати { $target_role }.
+mod-granted = Користувач { $user_id } тепер { $role }.
+mod-revoked = Роль користувача { $user_id } відкликано.
+mod-no-role = Користувач { $user_id } не має ролі в цьому чаті.
+no-permission = У вас немає прав на цю дію в цьому чаті.
+report-failed = Не вдалося згенерувати звіт. Подробиці в лозі.
+summary-unavailable = Підсумок тимчасово недоступний. Спробуйте пізніше.
+summary-skip-no-key = Для цього чату не задано ключ OpenAI. Задайте chat_config.openai_api_key через дашборд.
+summary-skip-disabled = AI-підсумок вимкнено для цього чату (chat_config.summary_enabled = FALSE).
+summary-skip-no-messages = Немає повідомлень для підсумку. Увімкніть chat_config.log_allowed_messages, щоб бот зберігав повідомлення для AI.
+summary-skip-budget = Денний ліміт токенів вичерпано ({ $used } з { $budget }).
+
+## Appeals (private chat with the bot)
+
+appeal-intro = Vixen — антиспам-бот. Якщо вас забанили в чаті, який він захищає, відкрийте посилання для апеляції з правил цього чату.
+appeal-ban-notice = Вас забанено в { $chat }. Якщо це помилка, подайте апеляцію: { $link }
+appeal-prompt = Надішліть одне повідомлення з поясненням, чому бан варто зняти. Модератори його розглянуть. Запит діє { $minutes } { $minutes ->
+    [one] хвилину
+    [few] хвилини
+   *[many] хвилин
+    }.
+appeal-not-banned = Ви не забанені в цьому чаті.
+appeal-already-pending = Ваша апеляція для цього чату вже чекає на модератора.
+appeal-cooldown = Вашу попередню апеляцію для цього чату відхилено. Подати нову можна через { $hours } { $hours ->
+    [one] годину
+    [few] години
+   *[many] годин
+    }.
+appeal-sent = Апеляцію надіслано. Відповідь прийде сюди після розгляду.
+appeal-already-decided = Щодо цієї апеляції вже ухвалено рішення.
+appeal-approved-toast = Апеляцію схвалено; користувача розбанено й верифіковано.
+appeal-denied-toast = Апеляцію відхилено.
+appeal-approved-notice = Вашу апеляцію схвалено. Можете повернутися в чат.
+appeal-denied-notice = Вашу апеляцію розглянуто й відхилено.
+
+## Reports (rendered into MarkdownV2 / HTML by the caller)
+
+report-title-daily = Щоденний звіт
+report-title-weekly = Тижневий звіт
+report-title-monthly = Місячний звіт
+report-title-today = Зведення за сьогодні
+report-title-on-demand = Звіт на запит
+report-messages = Повідомлень
+report-deleted = Видалено
+report-verified = Верифіковано
+report-banned = Забанено
+report-captcha = Капча
+report-captcha-issued = Видано
+report-captcha-solved = Розв'язано
+report-captcha-expired = Минуло
+report-top-phrases = Часті фрази
+report-ban-reasons = Причини банів
+report-captcha-trend = Розв'язуваність капчі за тижнями
+report-last-7-days = 7 днів
+# One character per weekday, Monday first.
+report-weekday-initials = ПВСЧПСН
+
+## Public report page
+
+public-report-for = звіт за
+public-top-phrases = Часті спам-фрази
+public-chart-alt = Графік активності чату
@@ -0,0 +1,13 @@
+-- Reverts 20260504000000_moderator_roles.up.sql.
+--
+-- Every row keeps its membership; the role distinction is lost, so viewers
+-- regain full moderator rights after a rollback.
+
+BEGIN;
+
+DROP INDEX idx_chat_moderators_user_id;
+
+ALTER TABLE chat_moderators
+    DROP COLUMN role;
+
+COMMIT;
@@ -0,0 +1,25 @@
+-- Moderator roles.
+--
+-- chat_moderators was a flat allow-list: any row could ban, verify, edit
+-- config and spend the chat's OpenAI budget. Each row now carries a role that
+-- the permission matrix in `models::chat_moderator` maps to concrete
+-- capabilities:
+--
+--   owner     — everything, including AI config (the chat's OpenAI key);
+--   admin     — moderation + config + granting moderator/viewer;
+--   moderator — ban / unban / verify / reports / summary;
+--   viewer    — read-only: /stats and dashboard reads.
+--
+-- Existing rows become 'moderator', which keeps every capability they used in
+-- practice (no runtime path edited config before this migration).
+
+BEGIN;
+
+ALTER TABLE chat_moderators
+    ADD COLUMN role TEXT NOT NULL DEFAULT 'moderator'
+        CHECK (role IN ('owner', 'admin', 'moderator', 'viewer'));
+
+-- `chats_for(user_id)` at JWT mint time scans by user.
+CREATE INDEX idx_chat_moderators_user_id ON chat_moderators (user_id);
+
+COMMIT;
@@ -0,0 +1,23 @@
+-- Reverts 20260505000000_moderator_sync.up.sql.
+--
+-- Like the M1 CHECK revert, restoring the narrower action list fails while
+-- 'role_grant' / 'role_revoke' rows exist — delete them explicitly first if
+-- losing that audit trail is acceptable. Synced rows stay as plain
+-- moderators-by-role once the source column is gone.
+
+BEGIN;
+
+ALTER TABLE moderation_actions
+    DROP CONSTRAINT moderation_actions_action_check;
+
+ALTER TABLE moderation_actions
+    ADD CONSTRAINT moderation_actions_action_check
+    CHECK (action IN (
+        'ban', 'unban', 'mute', 'unmute', 'delete', 'verify', 'unverify',
+        'captcha_expired', 'captcha_failed', 'kick'
+    ));
+
+ALTER TABLE chat_moderators
+    DROP COLUMN source;
+
+COMMIT;
@@ -0,0 +1,34 @@
+-- Sync chat_moderators from Telegram chat administrators.
+--
+-- Two changes:
+--
+-- 1. chat_moderators.source tells who owns a row:
+--      * manual   — granted with `/mod grant` (or seeded by hand). The sync
+--                   never touches these rows, so a manual downgrade of a
+--                   Telegram admin to viewer sticks.
+--      * telegram — mirrored from `getChatAdministrators` / `chat_member`
+--                   updates (creator → owner, administrator → admin). The
+--                   sync re-roles or deletes them as Telegram changes.
+--    Existing rows predate the sync and are treated as manual.
+--
+-- 2. moderation_actions.action gains 'role_grant' and 'role_revoke' so role
+--    changes (sync and `/mod`) land in the same audit ledger as bans.
+
+BEGIN;
+
+ALTER TABLE chat_moderators
+    ADD COLUMN source TEXT NOT NULL DEFAULT 'manual'
+        CHECK (source IN ('manual', 'telegram'));
+
+ALTER TABLE moderation_actions
+    DROP CONSTRAINT moderation_actions_action_check;
+
+ALTER TABLE moderation_actions
+    ADD CONSTRAINT moderation_actions_action_check
+    CHECK (action IN (
+        'ban', 'unban', 'mute', 'unmute', 'delete', 'verify', 'unverify',
+        'captcha_expired', 'captcha_failed', 'kick',
+        'role_grant', 'role_revoke'
+    ));
+
+COMMIT;
@@ -0,0 +1,17 @@
+-- Reverts 20260506000000_mod_log.up.sql. Unposted log entries are dropped
+-- with the marker column; the ledger rows themselves are untouched.
+
+BEGIN;
+
+DROP FUNCTION try_jsonb(TEXT);
+
+DROP INDEX idx_moderation_actions_unlogged;
+
+ALTER TABLE moderation_actions
+    DROP COLUMN log_claimed_until,
+    DROP COLUMN logged_at;
+
+ALTER TABLE chat_config
+    DROP COLUMN log_chat_id;
+
+COMMIT;
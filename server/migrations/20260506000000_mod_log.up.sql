@@ -0,0 +1,51 @@
+-- Moderation log channel.
+--
+-- 1. chat_config.log_chat_id — optional Telegram chat (usually a private
+--    channel) that receives one entry per moderation_actions row for this
+--    chat. NULL disables the mirror.
+--
+-- 2. moderation_actions.logged_at — outbox marker. The `mod_log` job reads
+--    rows WHERE logged_at IS NULL, posts them, and stamps logged_at. Rows
+--    for chats without a log channel are stamped without posting, so the
+--    partial index below only ever holds the in-flight tail.
+--    moderation_actions.log_claimed_until is the job's lease on a pending
+--    row: a pass claims its batch by pushing it into the future, so a
+--    second replica skips those rows instead of posting them twice.
+--
+-- Existing rows are stamped with their created_at: turning the feature on
+-- must not replay the whole ledger into a fresh channel.
+--
+-- 3. try_jsonb — `value::jsonb`, but NULL instead of an error when `value`
+--    is not valid JSON. moderation_actions.reason holds either the spam
+--    verdict JSON or free moderator text, and free text may well start with
+--    `{` (`/ban 42 {spam}`); an unguarded cast turns one such row into a
+--    failed query. Callers keep the cheap `LIKE '{%'` check in front so the
+--    exception block only runs for JSON-looking reasons. The job uses it to
+--    strip the verdict's `excerpt` when stamping a row.
+--    pg_input_is_valid() would do the same but needs PostgreSQL 16.
+
+BEGIN;
+
+ALTER TABLE chat_config
+    ADD COLUMN log_chat_id BIGINT;
+
+ALTER TABLE moderation_actions
+    ADD COLUMN logged_at TIMESTAMPTZ,
+    ADD COLUMN log_claimed_until TIMESTAMPTZ;
+
+UPDATE moderation_actions SET logged_at = created_at;
+
+CREATE INDEX idx_moderation_actions_unlogged
+    ON moderation_actions (created_at)
+    WHERE logged_at IS NULL;
+
+CREATE FUNCTION try_jsonb(value TEXT)
+RETURNS JSONB AS $$
+BEGIN
+    RETURN value::jsonb;
+EXCEPTION WHEN invalid_text_representation THEN
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql IMMUTABLE;
+
+COMMIT;
@@ -0,0 +1,8 @@
+-- Reverts 20260507000000_appeals.up.sql. Appeal history is dropped; the
+-- ledger rows it pointed at are untouched.
+
+BEGIN;
+
+DROP TABLE appeals;
+
+COMMIT;
@@ -0,0 +1,38 @@
+-- Ban appeals submitted through the bot's private chat.
+--
+-- A row is opened as `draft` by the `/start appeal_<chat_id>` deep link,
+-- filled in and moved to `pending` by the user's next private message, and
+-- closed as `approved` / `denied` by a moderator's button press.
+--
+-- action_id links the ban the user contests (the latest `ban` ledger row at
+-- draft time). ON DELETE SET NULL keeps the appeal if the ledger is ever
+-- pruned.
+--
+-- The partial unique index allows one open (draft or pending) appeal per
+-- user per chat; decided appeals accumulate as history.
+
+BEGIN;
+
+CREATE TABLE appeals (
+    id           UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    chat_id      BIGINT NOT NULL REFERENCES chats(chat_id) ON DELETE CASCADE,
+    user_id      BIGINT NOT NULL,
+    action_id    UUID REFERENCES moderation_actions(id) ON DELETE SET NULL,
+    text         TEXT,
+    status       TEXT NOT NULL DEFAULT 'draft'
+                 CHECK (status IN ('draft', 'pending', 'approved', 'denied')),
+    submitted_at TIMESTAMPTZ,
+    decided_by   BIGINT,
+    decided_at   TIMESTAMPTZ,
+    created_at   TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX idx_appeals_open
+    ON appeals (chat_id, user_id)
+    WHERE status IN ('draft', 'pending');
+
+CREATE INDEX idx_appeals_user_draft
+    ON appeals (user_id, created_at DESC)
+    WHERE status = 'draft';
+
+COMMIT;
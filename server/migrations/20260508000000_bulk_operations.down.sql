@@ -0,0 +1,9 @@
+-- Reverts 20260508000000_bulk_operations.up.sql. The ledger rows written by
+-- bulk operations stay; only the grouping is lost.
+
+BEGIN;
+
+DROP TABLE bulk_operation_items;
+DROP TABLE bulk_operations;
+
+COMMIT;
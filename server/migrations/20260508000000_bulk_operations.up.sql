@@ -0,0 +1,46 @@
+-- Bulk undo over the moderation ledger.
+--
+-- bulk_operations is the parent record of one dashboard request: the filter
+-- it was started with, the inverse it applies, and running counters.
+-- bulk_operation_items has one row per matched target with its outcome and
+-- the ledger rows on both ends — source_action_id (the row being undone)
+-- and result_action_id (the row the inverse action wrote). Together they
+-- link every generated ledger row back to the operation. At most one
+-- operation per chat is running, enforced by a partial unique index rather
+-- than a check-then-insert that two concurrent requests could both pass.
+
+BEGIN;
+
+CREATE TABLE bulk_operations (
+    id           UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    chat_id      BIGINT NOT NULL REFERENCES chats(chat_id) ON DELETE CASCADE,
+    requested_by BIGINT NOT NULL,
+    inverse      TEXT NOT NULL CHECK (inverse IN ('unban', 'unverify', 'verify')),
+    filter       JSONB NOT NULL,
+    status       TEXT NOT NULL DEFAULT 'running'
+                 CHECK (status IN ('running', 'completed', 'failed')),
+    total        INTEGER NOT NULL CHECK (total >= 0),
+    applied      INTEGER NOT NULL DEFAULT 0,
+    skipped      INTEGER NOT NULL DEFAULT 0,
+    failed       INTEGER NOT NULL DEFAULT 0,
+    created_at   TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    finished_at  TIMESTAMPTZ
+);
+
+CREATE INDEX idx_bulk_operations_chat ON bulk_operations (chat_id, created_at DESC);
+CREATE UNIQUE INDEX uq_bulk_operations_running
+    ON bulk_operations (chat_id) WHERE status = 'running';
+
+CREATE TABLE bulk_operation_items (
+    operation_id     UUID NOT NULL REFERENCES bulk_operations(id) ON DELETE CASCADE,
+    target_user_id   BIGINT NOT NULL,
+    source_action_id UUID REFERENCES moderation_actions(id) ON DELETE SET NULL,
+    result_action_id UUID REFERENCES moderation_actions(id) ON DELETE SET NULL,
+    outcome          TEXT NOT NULL DEFAULT 'pending'
+                     CHECK (outcome IN ('pending', 'applied', 'skipped', 'failed')),
+    error            TEXT,
+    updated_at       TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (operation_id, target_user_id)
+);
+
+COMMIT;
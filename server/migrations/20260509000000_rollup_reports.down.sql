@@ -0,0 +1,15 @@
+-- Revert weekly / monthly rollup reports. Rollup rows in report_messages
+-- are dropped so the narrower CHECK can be restored.
+
+BEGIN;
+
+DELETE FROM report_messages WHERE kind NOT IN ('daily_text', 'daily_photo');
+ALTER TABLE report_messages DROP CONSTRAINT report_messages_kind_check;
+ALTER TABLE report_messages ADD CONSTRAINT report_messages_kind_check
+    CHECK (kind IN ('daily_text', 'daily_photo'));
+
+ALTER TABLE chat_config
+    DROP COLUMN monthly_report_day,
+    DROP COLUMN weekly_report_weekday;
+
+COMMIT;
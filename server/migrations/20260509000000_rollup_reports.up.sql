@@ -0,0 +1,28 @@
+-- Weekly and monthly rollup reports.
+--
+-- 1. chat_config gets two schedule tunables, both NULL (off) by default:
+--      * weekly_report_weekday — ISO weekday (1 = Monday … 7 = Sunday) on
+--        which the 7-day rollup is posted, at report_hour.
+--      * monthly_report_day    — day of month (1..28, so every month has
+--        it) on which the one-month rollup is posted, at report_hour.
+--
+-- 2. report_messages.kind widens from the daily pair to one text + photo
+--    pair per period. The (chat_id, report_date, kind) key is unchanged, so
+--    a weekly report posted on the same day as the daily one gets its own
+--    rows and its own replace-on-redo gate.
+
+BEGIN;
+
+ALTER TABLE chat_config
+    ADD COLUMN weekly_report_weekday SMALLINT
+        CHECK (weekly_report_weekday BETWEEN 1 AND 7),
+    ADD COLUMN monthly_report_day    SMALLINT
+        CHECK (monthly_report_day BETWEEN 1 AND 28);
+
+ALTER TABLE report_messages DROP CONSTRAINT report_messages_kind_check;
+ALTER TABLE report_messages ADD CONSTRAINT report_messages_kind_check
+    CHECK (kind IN ('daily_text', 'daily_photo',
+                    'weekly_text', 'weekly_photo',
+                    'monthly_text', 'monthly_photo'));
+
+COMMIT;
@@ -0,0 +1,9 @@
+-- Revert the public report opt-in and the slug format check.
+
+BEGIN;
+
+ALTER TABLE chats DROP CONSTRAINT chats_slug_format_check;
+
+ALTER TABLE chat_config DROP COLUMN public_report;
+
+COMMIT;
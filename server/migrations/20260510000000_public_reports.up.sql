@@ -0,0 +1,23 @@
+-- Public redacted report page (`GET /report/{slug}`).
+--
+-- 1. chat_config.public_report — per-chat opt-in, FALSE by default. A chat
+--    is only served publicly when it has a `chats.slug` AND this is TRUE.
+--
+-- 2. chats.slug gets the same format the HTTP layer validates
+--    (lowercase `[a-z0-9-]{3,64}`), so a slug stored by hand can always be
+--    reached through the public route. Slugs set before this migration
+--    that don't match are cleared first, so the constraint can't abort it:
+--    the route never served them anyway, and the chat can pick a valid one.
+
+BEGIN;
+
+ALTER TABLE chat_config
+    ADD COLUMN public_report BOOLEAN NOT NULL DEFAULT FALSE;
+
+UPDATE chats SET slug = NULL WHERE slug !~ '^[a-z0-9-]{3,64}$';
+
+ALTER TABLE chats
+    ADD CONSTRAINT chats_slug_format_check
+        CHECK (slug ~ '^[a-z0-9-]{3,64}$');
+
+COMMIT;
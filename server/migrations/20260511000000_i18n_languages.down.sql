@@ -0,0 +1,14 @@
+-- Revert to the RU / EN language set. Ukrainian chats fall back to the
+-- column default first so the narrower check can be added.
+
+BEGIN;
+
+UPDATE chat_config SET language = 'ru' WHERE language = 'uk';
+
+ALTER TABLE chat_config DROP CONSTRAINT chat_config_language_check;
+
+ALTER TABLE chat_config
+    ADD CONSTRAINT chat_config_language_check
+        CHECK (language IN ('ru', 'en'));
+
+COMMIT;
@@ -0,0 +1,16 @@
+-- Ukrainian locale.
+--
+-- chat_config.language now accepts every locale shipped under
+-- `server/locales/` ('ru', 'en', 'uk'). The column still drives reports and
+-- chat-wide notices; replies to one user may follow their Telegram client
+-- language instead (see `src/i18n.rs`).
+
+BEGIN;
+
+ALTER TABLE chat_config DROP CONSTRAINT chat_config_language_check;
+
+ALTER TABLE chat_config
+    ADD CONSTRAINT chat_config_language_check
+        CHECK (language IN ('ru', 'en', 'uk'));
+
+COMMIT;
@@ -0,0 +1,11 @@
+-- Drop per-chat LLM provider selection. Chats on a self-hosted provider fall
+-- back to hosted OpenAI, which skips them until an API key is set.
+
+BEGIN;
+
+ALTER TABLE chat_config
+    DROP CONSTRAINT chat_config_llm_base_url_check,
+    DROP COLUMN llm_base_url,
+    DROP COLUMN llm_provider;
+
+COMMIT;
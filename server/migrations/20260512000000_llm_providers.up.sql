@@ -0,0 +1,22 @@
+-- Pluggable LLM providers for AI summaries.
+--
+-- chat_config.llm_provider selects the backend (`src/services/llm/`):
+--   'openai'             hosted OpenAI at CONFIG_OPENAI_BASE_URL (default)
+--   'openai_compatible'  any /v1/chat/completions server at llm_base_url
+--   'ollama'             Ollama's native /api/chat at llm_base_url
+-- Self-hosted providers need a base URL; openai_api_key becomes optional
+-- for them.
+
+BEGIN;
+
+ALTER TABLE chat_config
+    ADD COLUMN llm_provider VARCHAR(32) NOT NULL DEFAULT 'openai'
+        CONSTRAINT chat_config_llm_provider_check
+            CHECK (llm_provider IN ('openai', 'openai_compatible', 'ollama')),
+    ADD COLUMN llm_base_url TEXT;
+
+ALTER TABLE chat_config
+    ADD CONSTRAINT chat_config_llm_base_url_check
+        CHECK (llm_provider = 'openai' OR llm_base_url IS NOT NULL);
+
+COMMIT;
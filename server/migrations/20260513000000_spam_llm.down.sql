@@ -0,0 +1,10 @@
+-- Drop the LLM spam step settings.
+
+BEGIN;
+
+ALTER TABLE chat_config
+    DROP COLUMN spam_llm_min_confidence,
+    DROP COLUMN spam_llm_min_score,
+    DROP COLUMN spam_llm_enabled;
+
+COMMIT;
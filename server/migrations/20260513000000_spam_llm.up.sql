@@ -0,0 +1,19 @@
+-- Opt-in LLM step at the end of the spam cascade.
+--
+-- Messages whose n-gram score lands in [spam_llm_min_score, spam_threshold)
+-- go to the chat's LLM provider (llm_provider / llm_base_url / openai_model).
+-- A spam verdict with confidence >= spam_llm_min_confidence deletes the
+-- message; the step never bans. Tokens count against summary_token_budget.
+
+BEGIN;
+
+ALTER TABLE chat_config
+    ADD COLUMN spam_llm_enabled BOOLEAN NOT NULL DEFAULT FALSE,
+    ADD COLUMN spam_llm_min_score REAL NOT NULL DEFAULT 0.5
+        CONSTRAINT chat_config_spam_llm_min_score_check
+            CHECK (spam_llm_min_score >= 0),
+    ADD COLUMN spam_llm_min_confidence REAL NOT NULL DEFAULT 0.8
+        CONSTRAINT chat_config_spam_llm_min_confidence_check
+            CHECK (spam_llm_min_confidence BETWEEN 0 AND 1);
+
+COMMIT;
@@ -0,0 +1,7 @@
+-- Drop the hourly summary cache.
+
+BEGIN;
+
+DROP TABLE summary_chunks;
+
+COMMIT;
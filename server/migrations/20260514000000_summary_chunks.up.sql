@@ -0,0 +1,24 @@
+-- Hourly map step of the AI summary.
+--
+-- SummaryService::summarize splits its window into whole UTC hours and
+-- summarises each one separately; finished hours are cached here so a
+-- repeated /summary, /report and the daily report reuse them and only pay
+-- for the combine call. tokens_used is what the provider reported for that
+-- chunk's call. The model is part of the key: switching
+-- chat_config.openai_model starts a fresh cache instead of mixing outputs.
+
+BEGIN;
+
+CREATE TABLE summary_chunks (
+    chat_id       BIGINT NOT NULL REFERENCES chats(chat_id) ON DELETE CASCADE,
+    hour_start    TIMESTAMPTZ NOT NULL,
+    language      VARCHAR(8) NOT NULL,
+    model         VARCHAR(64) NOT NULL,
+    message_count INTEGER NOT NULL CHECK (message_count > 0),
+    summary       TEXT NOT NULL,
+    tokens_used   INTEGER NOT NULL CHECK (tokens_used >= 0),
+    created_at    TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (chat_id, language, model, hour_start)
+);
+
+COMMIT;
@@ -0,0 +1,7 @@
+-- Drop the captcha digit buffer fallback.
+
+BEGIN;
+
+ALTER TABLE captcha_challenges DROP COLUMN input;
+
+COMMIT;
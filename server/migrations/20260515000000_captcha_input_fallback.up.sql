@@ -0,0 +1,13 @@
+-- Postgres fallback for the captcha digit buffer.
+--
+-- The digits a user has typed live in Redis (cap:input:{chat}:{user}).
+-- While Redis is unavailable the callback handler keeps them here instead,
+-- so a captcha can still be solved during an outage. Re-issuing a
+-- challenge resets the column with the rest of the row.
+
+BEGIN;
+
+ALTER TABLE captcha_challenges
+    ADD COLUMN input VARCHAR(8) NOT NULL DEFAULT '';
+
+COMMIT;
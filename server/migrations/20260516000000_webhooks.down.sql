@@ -0,0 +1,10 @@
+-- Reverts 20260516000000_webhooks.up.sql. Undelivered events and endpoint
+-- secrets are dropped; the moderation ledger is untouched.
+
+BEGIN;
+
+DROP TABLE webhook_deliveries;
+DROP TABLE outbox_events;
+DROP TABLE webhook_endpoints;
+
+COMMIT;
@@ -0,0 +1,62 @@
+-- Transactional outbox + signed webhooks.
+--
+-- 1. webhook_endpoints — per-chat consumer URLs. `secret` keys the
+--    HMAC-SHA256 signature on every delivery; it is generated by the server,
+--    only shown once, at creation, and stored sealed like
+--    chat_config.openai_api_key (services/api_keys.rs).
+--
+-- 2. outbox_events — one row per externally visible event (ban, unban,
+--    verify), inserted inside the same transaction as the ledger row it
+--    describes, so an event exists iff the action committed. `payload` is
+--    the exact JSON body consumers receive. The `webhooks` job fans pending
+--    rows out into deliveries and stamps fanned_out_at.
+--
+-- 3. webhook_deliveries — one row per (event, endpoint). `pending` rows are
+--    retried with exponential backoff until they succeed (`delivered`) or
+--    run out of attempts (`dead`). Dead rows stay until re-driven from the
+--    API or pruned with their event.
+
+BEGIN;
+
+CREATE TABLE webhook_endpoints (
+    id         UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    chat_id    BIGINT NOT NULL REFERENCES chats(chat_id) ON DELETE CASCADE,
+    url        TEXT NOT NULL CHECK (url ~ '^https?://'),
+    secret     TEXT NOT NULL,
+    created_by BIGINT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (chat_id, url)
+);
+
+CREATE TABLE outbox_events (
+    id            UUID PRIMARY KEY,
+    chat_id       BIGINT NOT NULL REFERENCES chats(chat_id) ON DELETE CASCADE,
+    event_type    TEXT NOT NULL,
+    payload       JSONB NOT NULL,
+    created_at    TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    fanned_out_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_outbox_events_created ON outbox_events (created_at);
+CREATE INDEX idx_outbox_events_pending
+    ON outbox_events (created_at)
+    WHERE fanned_out_at IS NULL;
+
+CREATE TABLE webhook_deliveries (
+    event_id        UUID NOT NULL REFERENCES outbox_events(id) ON DELETE CASCADE,
+    endpoint_id     UUID NOT NULL REFERENCES webhook_endpoints(id) ON DELETE CASCADE,
+    status          TEXT NOT NULL DEFAULT 'pending'
+                    CHECK (status IN ('pending', 'delivered', 'dead')),
+    attempts        INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_error      TEXT,
+    delivered_at    TIMESTAMPTZ,
+    PRIMARY KEY (event_id, endpoint_id)
+);
+
+CREATE INDEX idx_webhook_deliveries_due
+    ON webhook_deliveries (next_attempt_at)
+    WHERE status = 'pending';
+CREATE INDEX idx_webhook_deliveries_endpoint ON webhook_deliveries (endpoint_id, status);
+
+COMMIT;
@@ -0,0 +1,8 @@
+-- Reverts 20260517000000_user_info_cache.up.sql. Cached usernames are
+-- dropped; they refill from new updates once the migration is re-applied.
+
+BEGIN;
+
+DROP TABLE user_info_cache;
+
+COMMIT;
@@ -0,0 +1,17 @@
+-- user_info_cache — the last profile Telegram showed us for each user, so
+-- the dashboard's verified / banned lists can show @usernames next to ids.
+-- Upserted from messages and joins in watched chats. A cache, not a
+-- directory: renames made outside those chats are never seen, and users
+-- who have not posted or joined since this migration have no row.
+
+BEGIN;
+
+CREATE TABLE user_info_cache (
+    user_id    BIGINT      PRIMARY KEY,
+    username   VARCHAR(64),
+    first_name TEXT        NOT NULL,
+    last_name  TEXT,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+COMMIT;
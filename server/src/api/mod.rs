@@ -1,11 +1,23 @@
 //! HTTP API surface — Axum router, response envelope, route handlers.
 
+pub mod pagination;
 pub mod response;
 pub mod routes_about;
+pub mod routes_actions;
+pub mod routes_auth;
+pub mod routes_chats;
+pub mod routes_events;
 pub mod routes_health;
+pub mod routes_metrics;
+pub mod routes_moderation;
+pub mod routes_public;
+pub mod routes_reports;
+pub mod routes_webhooks;
 pub mod server;
 pub mod state;
+pub mod webapp_auth;
 
 pub use response::{ApiError, ApiResult};
 pub use server::build_router;
 pub use state::AppState;
+pub use webapp_auth::DashboardContext;
@@ -0,0 +1,56 @@
+//! Keyset cursors for list endpoints (`server/docs/api.md` § Pagination):
+//! the sort key of the last item sent — e.g. `(created_at, id)` — as
+//! URL-safe base64 of its JSON. Opaque to clients; a cursor that doesn't
+//! decode is a `400`.
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as B64;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+/// Page size when `?limit=` is omitted.
+pub const DEFAULT_LIMIT: i64 = 50;
+/// Larger `?limit=` values are clamped to this.
+pub const MAX_LIMIT: i64 = 200;
+
+pub fn encode<K: Serialize>(key: &K) -> String {
+    B64.encode(serde_json::to_vec(key).unwrap_or_default())
+}
+
+/// `None` for anything [`encode`] did not produce.
+pub fn decode<K: DeserializeOwned>(cursor: &str) -> Option<K> {
+    let bytes = B64.decode(cursor).ok()?;
+    serde_json::from_slice(&bytes).ok()
+}
+
+/// `?limit=` clamped to `1..=MAX_LIMIT`.
+pub fn limit(requested: Option<i64>) -> i64 {
+    requested.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursor_round_trip() {
+        let key = ("2026-05-16T12:00:00Z".to_owned(), 42_i64);
+        let cursor = encode(&key);
+        assert!(!cursor.contains(['+', '/', '=']), "URL-safe: {cursor}");
+        assert_eq!(decode::<(String, i64)>(&cursor), Some(key));
+    }
+
+    #[test]
+    fn garbage_cursor_is_none() {
+        assert_eq!(decode::<(String, i64)>("not a cursor!"), None);
+        assert_eq!(decode::<(String, i64)>(&encode(&"text")), None);
+    }
+
+    #[test]
+    fn limit_is_clamped() {
+        assert_eq!(limit(None), DEFAULT_LIMIT);
+        assert_eq!(limit(Some(0)), 1);
+        assert_eq!(limit(Some(10)), 10);
+        assert_eq!(limit(Some(10_000)), MAX_LIMIT);
+    }
+}
@@ -0,0 +1,378 @@
+//! `GET /api/v1/chats/{chat_id}/actions` — the audit log: the chat's
+//! `moderation_actions` ledger, filtered and keyset-paginated
+//! (`services::audit_log`). Viewer-level ([`Permission::ViewReports`]).
+//!
+//! `?format=csv` / `?format=ndjson` stream every matching row instead of a
+//! page, as a download for offline review; `cursor` still applies, `limit`
+//! does not.
+
+use axum::body::{Body, Bytes};
+use axum::extract::rejection::QueryRejection;
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::http::header::{CONTENT_DISPOSITION, CONTENT_TYPE};
+use axum::response::{IntoResponse, Response};
+use chrono::{DateTime, SecondsFormat, Utc};
+use futures::{StreamExt, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::api::pagination;
+use crate::api::response::{ApiError, ApiResult};
+use crate::api::state::AppState;
+use crate::api::webapp_auth::DashboardContext;
+use crate::models::chat_moderator::Permission;
+use crate::models::moderation_action::{ActorKind, ModerationActionKind};
+use crate::services::audit_log::{self, AuditCursor, AuditEntry, AuditFilter};
+use crate::{api_error, api_success};
+
+const CSV_HEADER: &str =
+    "id,created_at,action,target_user_id,actor_kind,actor_user_id,message_id,reason\r\n";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ActionsFormat {
+    Json,
+    Csv,
+    Ndjson,
+}
+
+#[derive(Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ActionsQuery {
+    /// Ledger action, e.g. `ban`, `verify`, `captcha_expired`.
+    pub action: Option<String>,
+    /// `bot` or `moderator`.
+    pub actor_kind: Option<String>,
+    pub actor_user_id: Option<i64>,
+    pub target_user_id: Option<i64>,
+    /// Case-insensitive substring of the reason.
+    pub reason: Option<String>,
+    /// Spam rule in the verdict's `matched_rules` (`xxh3_dedup`, `cas`,
+    /// `ngram`, `llm`).
+    pub rule: Option<String>,
+    /// Inclusive lower bound on `created_at` (RFC 3339).
+    pub from: Option<DateTime<Utc>>,
+    /// Exclusive upper bound on `created_at` (RFC 3339).
+    pub to: Option<DateTime<Utc>>,
+    /// `cursor` from the previous page.
+    pub cursor: Option<String>,
+    /// Page size, 1–200 (default 50). Ignored by exports.
+    pub limit: Option<i64>,
+    /// `json` (default, one page), `csv` or `ndjson` (every matching row).
+    #[param(value_type = Option<String>)]
+    pub format: Option<ActionsFormat>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ActionsPage {
+    /// Newest first.
+    pub items: Vec<AuditEntry>,
+    pub has_more: bool,
+    /// Pass as `?cursor=` for the next page; `None` on the last one.
+    pub cursor: Option<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/chats/{chat_id}/actions",
+    params(("chat_id" = i64, Path, description = "Telegram chat id"), ActionsQuery),
+    responses(
+        (status = 200, description = "A page of the ledger, or every matching row as a download",
+            content((ActionsPage = "application/json"), (String = "text/csv"), (String = "application/x-ndjson"))),
+        (status = 400, body = ApiError, description = "Unknown action / actor kind, bad time range or cursor"),
+        (status = 401, body = ApiError, description = "Missing or invalid token"),
+        (status = 403, body = ApiError, description = "No role in this chat"),
+    ),
+    security(("bearer" = [])),
+    tag = "chats"
+)]
+pub async fn actions(
+    State(state): State<AppState>,
+    ctx: DashboardContext,
+    Path(chat_id): Path<i64>,
+    query: Result<Query<ActionsQuery>, QueryRejection>,
+) -> Response {
+    let Ok(Query(query)) = query else {
+        return bad_request("malformed query string (timestamps are RFC 3339)");
+    };
+    let (filter, after) = match validate(&query) {
+        Ok(v) => v,
+        Err(message) => return bad_request(message),
+    };
+    if let Err(e) = ctx.require(&state, chat_id, Permission::ViewReports).await {
+        return e.into_response();
+    }
+
+    let format = query.format.unwrap_or(ActionsFormat::Json);
+    if format != ActionsFormat::Json {
+        info!(chat_id, user_id = ctx.user_id, ?format, "audit log export");
+        return export(&state, chat_id, filter, after, format);
+    }
+
+    let limit = pagination::limit(query.limit);
+    match audit_log::page(state.db.pool(), chat_id, &filter, after, limit + 1).await {
+        Ok(mut items) => {
+            let has_more = items.len() as i64 > limit;
+            items.truncate(limit as usize);
+            let cursor = if has_more {
+                items.last().map(|e| pagination::encode(&e.cursor()))
+            } else {
+                None
+            };
+            api_success!(ActionsPage {
+                items,
+                has_more,
+                cursor,
+            })
+            .into_response()
+        }
+        Err(e) => {
+            error!(error = ?e, chat_id, "audit log query failed");
+            let r: ApiResult<ActionsPage> =
+                api_error!("DATABASE_ERROR", "failed to read the audit log");
+            r.into_response()
+        }
+    }
+}
+
+/// Query → filter and resume point, or the message for a `400`.
+fn validate(query: &ActionsQuery) -> Result<(AuditFilter, Option<AuditCursor>), String> {
+    let unknown_action = query
+        .action
+        .as_deref()
+        .filter(|a| ModerationActionKind::from_db_str(a).is_none());
+    if let Some(action) = unknown_action {
+        return Err(format!("unknown action '{action}'"));
+    }
+    if query
+        .actor_kind
+        .as_deref()
+        .is_some_and(|k| ActorKind::from_db_str(k).is_none())
+    {
+        return Err("actor_kind must be 'bot' or 'moderator'".into());
+    }
+    if matches!((query.from, query.to), (Some(from), Some(to)) if from >= to) {
+        return Err("from must be before to".into());
+    }
+    let after = match &query.cursor {
+        Some(raw) => Some(pagination::decode(raw).ok_or("malformed cursor")?),
+        None => None,
+    };
+    fn non_empty(s: &Option<String>) -> Option<&str> {
+        s.as_deref().map(str::trim).filter(|s| !s.is_empty())
+    }
+    let filter = AuditFilter {
+        action: query.action.clone(),
+        actor_kind: query.actor_kind.clone(),
+        actor_user_id: query.actor_user_id,
+        target_user_id: query.target_user_id,
+        reason: non_empty(&query.reason).map(str::to_owned),
+        rule: non_empty(&query.rule).map(str::to_owned),
+        from: query.from,
+        to: query.to,
+    };
+    Ok((filter, after))
+}
+
+/// Stream the rows as a download. A database error mid-way aborts the
+/// body, so the client sees a truncated transfer rather than a short file.
+fn export(
+    state: &AppState,
+    chat_id: i64,
+    filter: AuditFilter,
+    after: Option<AuditCursor>,
+    format: ActionsFormat,
+) -> Response {
+    let (content_type, extension) = match format {
+        ActionsFormat::Csv => ("text/csv; charset=utf-8", "csv"),
+        _ => ("application/x-ndjson", "ndjson"),
+    };
+    let rows = audit_log::export(state.db.pool().clone(), chat_id, filter, after)
+        .map_ok(move |batch| {
+            let mut out = String::new();
+            for entry in &batch {
+                match format {
+                    ActionsFormat::Csv => push_csv_row(&mut out, entry),
+                    _ => push_ndjson_row(&mut out, entry),
+                }
+            }
+            Bytes::from(out)
+        })
+        .inspect_err(move |e| error!(error = ?e, chat_id, "audit log export failed"));
+    let header = match format {
+        ActionsFormat::Csv => Bytes::from_static(CSV_HEADER.as_bytes()),
+        _ => Bytes::new(),
+    };
+    let body = futures::stream::once(async { Ok(header) }).chain(rows);
+    let disposition = format!("attachment; filename=\"actions_{chat_id}.{extension}\"");
+    (
+        StatusCode::OK,
+        [
+            (CONTENT_TYPE, content_type.to_owned()),
+            (CONTENT_DISPOSITION, disposition),
+        ],
+        Body::from_stream(body),
+    )
+        .into_response()
+}
+
+fn push_ndjson_row(out: &mut String, entry: &AuditEntry) {
+    if let Ok(line) = serde_json::to_string(entry) {
+        out.push_str(&line);
+        out.push('\n');
+    }
+}
+
+/// RFC 4180 row. Text cells that a spreadsheet would evaluate as a formula
+/// get a leading `'`, since reasons are moderator- or spammer-supplied.
+fn push_csv_row(out: &mut String, entry: &AuditEntry) {
+    let opt = |v: Option<String>| v.unwrap_or_default();
+    let cells = [
+        entry.id.to_string(),
+        entry
+            .created_at
+            .to_rfc3339_opts(SecondsFormat::Millis, true),
+        csv_text(&entry.action),
+        entry.target_user_id.to_string(),
+        csv_text(&entry.actor_kind),
+        opt(entry.actor_user_id.map(|v| v.to_string())),
+        opt(entry.message_id.map(|v| v.to_string())),
+        opt(entry.reason.as_deref().map(csv_text)),
+    ];
+    out.push_str(&cells.join(","));
+    out.push_str("\r\n");
+}
+
+fn csv_text(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{value}")
+    } else {
+        value.to_owned()
+    };
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+fn bad_request(message: impl Into<String>) -> Response {
+    ApiError {
+        code: "VALIDATION_ERROR".into(),
+        message: message.into(),
+        status: StatusCode::BAD_REQUEST,
+    }
+    .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn entry(reason: Option<&str>) -> AuditEntry {
+        AuditEntry {
+            id: Uuid::nil(),
+            target_user_id: 42,
+            action: "ban".into(),
+            actor_kind: "moderator".into(),
+            actor_user_id: Some(7),
+            message_id: None,
+            reason: reason.map(str::to_owned),
+            created_at: "2026-05-16T12:00:00Z".parse().unwrap(),
+        }
+    }
+
+    #[test]
+    fn csv_row_quotes_and_escapes() {
+        let mut out = String::new();
+        push_csv_row(&mut out, &entry(Some(r#"{"matched_rules":["cas"]}"#)));
+        assert_eq!(
+            out,
+            "00000000-0000-0000-0000-000000000000,2026-05-16T12:00:00.000Z,ban,42,\
+             moderator,7,,\"{\"\"matched_rules\"\":[\"\"cas\"\"]}\"\r\n"
+        );
+    }
+
+    #[test]
+    fn csv_row_defuses_formulas() {
+        let mut out = String::new();
+        push_csv_row(&mut out, &entry(Some("=HYPERLINK(\"x\")")));
+        assert!(out.ends_with(",\"'=HYPERLINK(\"\"x\"\")\"\r\n"), "{out}");
+        out.clear();
+        push_csv_row(&mut out, &entry(None));
+        assert!(out.ends_with(",7,,\r\n"), "{out}");
+    }
+
+    #[test]
+    fn csv_header_matches_row_width() {
+        let mut out = String::new();
+        push_csv_row(&mut out, &entry(Some("spam")));
+        assert_eq!(CSV_HEADER.matches(',').count(), out.matches(',').count());
+    }
+
+    #[test]
+    fn ndjson_is_one_line_per_entry() {
+        let mut out = String::new();
+        push_ndjson_row(&mut out, &entry(Some("line one\nline two")));
+        push_ndjson_row(&mut out, &entry(None));
+        let lines: Vec<_> = out.lines().collect();
+        assert_eq!(lines.len(), 2);
+        let first: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(first["reason"], "line one\nline two");
+        assert_eq!(first["action"], "ban");
+    }
+
+    fn text(s: &str) -> Option<String> {
+        Some(s.to_owned())
+    }
+
+    #[test]
+    fn validate_rejects_unknown_values() {
+        let ok = ActionsQuery {
+            action: text("ban"),
+            actor_kind: text("bot"),
+            ..Default::default()
+        };
+        assert!(validate(&ok).is_ok());
+        let bad = [
+            ActionsQuery {
+                action: text("nuke"),
+                ..Default::default()
+            },
+            ActionsQuery {
+                actor_kind: text("admin"),
+                ..Default::default()
+            },
+            ActionsQuery {
+                cursor: text("garbage"),
+                ..Default::default()
+            },
+            ActionsQuery {
+                from: "2026-05-16T00:00:00Z".parse().ok(),
+                to: "2026-05-15T00:00:00Z".parse().ok(),
+                ..Default::default()
+            },
+        ];
+        for query in &bad {
+            assert!(validate(query).is_err());
+        }
+    }
+
+    #[test]
+    fn validate_round_trips_the_cursor_and_drops_blank_text() {
+        let cursor = entry(None).cursor();
+        let query = ActionsQuery {
+            cursor: Some(pagination::encode(&cursor)),
+            reason: text(" "),
+            rule: text("cas"),
+            ..Default::default()
+        };
+        let (filter, after) = validate(&query).unwrap();
+        assert_eq!(after, Some(cursor));
+        assert_eq!(filter.reason, None);
+        assert_eq!(filter.rule.as_deref(), Some("cas"));
+    }
+}
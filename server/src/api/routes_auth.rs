@@ -0,0 +1,149 @@
+//! `POST /api/v1/auth/telegram/login` and `GET /api/v1/auth/me` — dashboard
+//! sign-in via Telegram `initData`. See `server/docs/auth.md`.
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use chrono::Utc;
+use serde::Serialize;
+use tracing::{error, info, warn};
+use utoipa::ToSchema;
+
+use crate::api::response::{ApiError, ApiResult};
+use crate::api::state::AppState;
+use crate::api::webapp_auth::DashboardContext;
+use crate::models::chat_moderator::{self, ModeratorRole};
+use crate::services::auth_service::{self, AuthError, TgIdentity};
+use crate::{api_error, api_success};
+
+#[derive(Serialize, ToSchema)]
+pub struct LoginResponse {
+    /// HS256 JWT; send as `Authorization: Bearer <token>`.
+    pub token: String,
+    pub user: TgIdentity,
+    /// Watched chats where the user holds a role at mint time.
+    pub chat_ids: Vec<i64>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct MeResponse {
+    pub user: TgIdentity,
+    /// Live role per chat from the token's `chat_ids`; chats where the role
+    /// has since been revoked are omitted.
+    pub roles: Vec<ChatRole>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ChatRole {
+    pub chat_id: i64,
+    pub role: ModeratorRole,
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/telegram/login",
+    request_body(content = String, content_type = "text/plain", description = "Raw signed initData"),
+    responses(
+        (status = 200, body = LoginResponse, description = "JWT minted"),
+        (status = 401, body = ApiError, description = "initData invalid or expired"),
+        (status = 403, body = ApiError, description = "Not a moderator of any watched chat"),
+    ),
+    tag = "auth"
+)]
+pub async fn login(State(state): State<AppState>, body: String) -> ApiResult<LoginResponse> {
+    let Some(secret) = state.config.jwt_secret.as_ref() else {
+        return api_error!(
+            "UNAUTHORIZED",
+            "dashboard auth is not configured",
+            StatusCode::UNAUTHORIZED
+        );
+    };
+
+    let tg = match auth_service::validate_init_data(
+        body.trim(),
+        state.config.bot_token.expose(),
+        state.config.init_data_max_age_secs,
+        Utc::now().timestamp(),
+    ) {
+        Ok(tg) => tg,
+        Err(AuthError::InitDataExpired) => {
+            return api_error!(
+                "INIT_DATA_EXPIRED",
+                "initData is too old",
+                StatusCode::UNAUTHORIZED
+            );
+        }
+        Err(e) => {
+            warn!(error = %e, "initData rejected");
+            return api_error!(
+                "INVALID_INIT_DATA",
+                "initData signature check failed",
+                StatusCode::UNAUTHORIZED
+            );
+        }
+    };
+
+    let chat_ids: Vec<i64> = match chat_moderator::chats_for(state.db.pool(), tg.id).await {
+        Ok(ids) => ids
+            .into_iter()
+            .filter(|id| state.config.chats.contains(id))
+            .collect(),
+        Err(e) => {
+            error!(error = ?e, "chats_for failed");
+            return api_error!("DATABASE_ERROR", "failed to resolve chats");
+        }
+    };
+    if chat_ids.is_empty() {
+        return api_error!(
+            "MODERATOR_REQUIRED",
+            "not a moderator of any watched chat",
+            StatusCode::FORBIDDEN
+        );
+    }
+
+    let token = match auth_service::mint(
+        tg.clone(),
+        chat_ids.clone(),
+        secret.expose(),
+        state.config.jwt_ttl_secs,
+    ) {
+        Ok(t) => t,
+        Err(e) => {
+            error!(error = %e, "JWT mint failed");
+            return api_error!("INTERNAL_ERROR", "failed to mint token");
+        }
+    };
+    info!(user_id = tg.id, chats = chat_ids.len(), "dashboard login");
+    api_success!(LoginResponse {
+        token,
+        user: tg,
+        chat_ids,
+    })
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/auth/me",
+    responses(
+        (status = 200, body = MeResponse, description = "Caller identity and live roles"),
+        (status = 401, body = ApiError, description = "Missing or invalid token"),
+    ),
+    security(("bearer" = [])),
+    tag = "auth"
+)]
+pub async fn me(State(state): State<AppState>, ctx: DashboardContext) -> ApiResult<MeResponse> {
+    let mut roles = Vec::with_capacity(ctx.chat_ids.len());
+    for &chat_id in &ctx.chat_ids {
+        match state.moderation.role(chat_id, ctx.user_id).await {
+            Ok(Some(role)) => roles.push(ChatRole { chat_id, role }),
+            Ok(None) => {}
+            Err(e) => {
+                error!(error = ?e, chat_id, "moderation.role failed");
+                return api_error!("DATABASE_ERROR", "failed to resolve roles");
+            }
+        }
+    }
+    api_success!(MeResponse {
+        user: ctx.tg,
+        roles,
+    })
+}
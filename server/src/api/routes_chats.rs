@@ -0,0 +1,379 @@
+//! `/api/v1/chats/{chat_id}/*` — chat-scoped dashboard routes (moderators,
+//! bulk undo, the masked AI API key). Every handler takes a [`DashboardContext`] and gates on a
+//! [`Permission`] before touching the chat.
+
+use axum::Json;
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::api::response::{ApiError, ApiResult};
+use crate::api::state::AppState;
+use crate::api::webapp_auth::DashboardContext;
+use crate::models::chat_moderator::{self, ChatModerator, Permission};
+use crate::services::api_keys;
+use crate::services::bulk_service::{self, BulkFilter, BulkOperation, Executors, Inverse};
+use crate::{api_error, api_success};
+
+#[derive(Serialize, ToSchema)]
+pub struct ModeratorsResponse {
+    /// Highest role first.
+    pub items: Vec<ChatModerator>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/chats/{chat_id}/moderators",
+    params(("chat_id" = i64, Path, description = "Telegram chat id")),
+    responses(
+        (status = 200, body = ModeratorsResponse, description = "Moderators of the chat"),
+        (status = 401, body = ApiError, description = "Missing or invalid token"),
+        (status = 403, body = ApiError, description = "No role or insufficient role in this chat"),
+    ),
+    security(("bearer" = [])),
+    tag = "chats"
+)]
+pub async fn moderators(
+    State(state): State<AppState>,
+    ctx: DashboardContext,
+    Path(chat_id): Path<i64>,
+) -> ApiResult<ModeratorsResponse> {
+    if let Err(e) = ctx.require(&state, chat_id, Permission::ViewReports).await {
+        return ApiResult::Error(e);
+    }
+    match chat_moderator::list(state.db.pool(), chat_id).await {
+        Ok(items) => api_success!(ModeratorsResponse { items }),
+        Err(e) => {
+            error!(error = ?e, chat_id, "list moderators failed");
+            api_error!("DATABASE_ERROR", "failed to list moderators")
+        }
+    }
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct BulkUndoRequest {
+    pub filter: BulkFilter,
+    /// Only resolve the targets; nothing is written or applied.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct BulkUndoResponse {
+    /// `None` for a dry run. Poll `GET .../bulk-undo/{operation_id}` for
+    /// per-target outcomes.
+    pub operation_id: Option<Uuid>,
+    pub inverse: Inverse,
+    pub targets: Vec<i64>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/chats/{chat_id}/bulk-undo",
+    params(("chat_id" = i64, Path, description = "Telegram chat id")),
+    request_body = BulkUndoRequest,
+    responses(
+        (status = 200, body = BulkUndoResponse, description = "Dry run: matched targets"),
+        (status = 202, body = BulkUndoResponse, description = "Operation started"),
+        (status = 400, body = ApiError, description = "Invalid filter or too many targets"),
+        (status = 401, body = ApiError, description = "Missing or invalid token"),
+        (status = 403, body = ApiError, description = "No role or insufficient role in this chat"),
+        (status = 409, body = ApiError, description = "Another bulk operation is running"),
+    ),
+    security(("bearer" = [])),
+    tag = "chats"
+)]
+pub async fn bulk_undo(
+    State(state): State<AppState>,
+    ctx: DashboardContext,
+    Path(chat_id): Path<i64>,
+    body: Result<Json<BulkUndoRequest>, JsonRejection>,
+) -> ApiResult<BulkUndoResponse> {
+    let Ok(Json(req)) = body else {
+        return api_error!(
+            "VALIDATION_ERROR",
+            "expected a JSON bulk-undo request",
+            StatusCode::BAD_REQUEST
+        );
+    };
+    let Some(inverse) = Inverse::for_action(&req.filter.action) else {
+        return api_error!(
+            "VALIDATION_ERROR",
+            format!("action '{}' has no inverse", req.filter.action),
+            StatusCode::BAD_REQUEST
+        );
+    };
+    if req
+        .filter
+        .actor_kind
+        .as_deref()
+        .is_some_and(|k| !matches!(k, "bot" | "moderator"))
+    {
+        return api_error!(
+            "VALIDATION_ERROR",
+            "actor_kind must be 'bot' or 'moderator'",
+            StatusCode::BAD_REQUEST
+        );
+    }
+    if let Err(e) = ctx.require(&state, chat_id, inverse.permission()).await {
+        return ApiResult::Error(e);
+    }
+
+    let pool = state.db.pool();
+    let targets = match bulk_service::matching_targets(pool, chat_id, &req.filter).await {
+        Ok(t) => t,
+        Err(e) => {
+            error!(error = ?e, chat_id, "bulk-undo target lookup failed");
+            return api_error!("DATABASE_ERROR", "failed to resolve targets");
+        }
+    };
+    if targets.len() > bulk_service::MAX_TARGETS {
+        return api_error!(
+            "VALIDATION_ERROR",
+            format!(
+                "filter matches more than {} users; narrow it",
+                bulk_service::MAX_TARGETS
+            ),
+            StatusCode::BAD_REQUEST
+        );
+    }
+    let user_ids: Vec<i64> = targets.iter().map(|t| t.user_id).collect();
+    if req.dry_run || targets.is_empty() {
+        return api_success!(BulkUndoResponse {
+            operation_id: None,
+            inverse,
+            targets: user_ids,
+        });
+    }
+
+    match bulk_service::running_for(pool, chat_id).await {
+        Ok(None) => {}
+        Ok(Some(running)) => {
+            return api_error!(
+                "CONFLICT",
+                format!("bulk operation {running} is still running"),
+                StatusCode::CONFLICT
+            );
+        }
+        Err(e) => {
+            error!(error = ?e, chat_id, "bulk-undo running check failed");
+            return api_error!("DATABASE_ERROR", "failed to check running operations");
+        }
+    }
+    let id = match bulk_service::create(pool, chat_id, ctx.user_id, inverse, &req.filter, &targets)
+        .await
+    {
+        Ok(id) => id,
+        // Lost a race with a concurrent request past the check above.
+        Err(e) if e.is::<bulk_service::AlreadyRunning>() => {
+            return api_error!(
+                "CONFLICT",
+                "a bulk operation is already running",
+                StatusCode::CONFLICT
+            );
+        }
+        Err(e) => {
+            error!(error = ?e, chat_id, "bulk-undo create failed");
+            return api_error!("DATABASE_ERROR", "failed to start bulk operation");
+        }
+    };
+    let exec = Executors {
+        moderation: state.moderation.clone(),
+        captcha: state.captcha.clone(),
+        captcha_state: state.captcha_state.clone(),
+    };
+    tokio::spawn(bulk_service::run(pool.clone(), exec, id));
+    info!(chat_id, operation_id = %id, targets = user_ids.len(), inverse = inverse.as_db_str(), "bulk undo started");
+    api_success!(
+        BulkUndoResponse {
+            operation_id: Some(id),
+            inverse,
+            targets: user_ids,
+        },
+        StatusCode::ACCEPTED
+    )
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/chats/{chat_id}/bulk-undo/{operation_id}",
+    params(
+        ("chat_id" = i64, Path, description = "Telegram chat id"),
+        ("operation_id" = Uuid, Path, description = "Id returned by POST bulk-undo"),
+    ),
+    responses(
+        (status = 200, body = BulkOperation, description = "Operation with per-target outcomes"),
+        (status = 401, body = ApiError, description = "Missing or invalid token"),
+        (status = 403, body = ApiError, description = "No role or insufficient role in this chat"),
+        (status = 404, body = ApiError, description = "No such operation in this chat"),
+    ),
+    security(("bearer" = [])),
+    tag = "chats"
+)]
+pub async fn bulk_operation(
+    State(state): State<AppState>,
+    ctx: DashboardContext,
+    Path((chat_id, operation_id)): Path<(i64, Uuid)>,
+) -> ApiResult<BulkOperation> {
+    if let Err(e) = ctx.require(&state, chat_id, Permission::ViewReports).await {
+        return ApiResult::Error(e);
+    }
+    match bulk_service::get(state.db.pool(), chat_id, operation_id).await {
+        Ok(Some(op)) => api_success!(op),
+        Ok(None) => api_error!("NOT_FOUND", "no such bulk operation", StatusCode::NOT_FOUND),
+        Err(e) => {
+            error!(error = ?e, chat_id, "bulk operation lookup failed");
+            api_error!("DATABASE_ERROR", "failed to load bulk operation")
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ApiKeyResponse {
+    /// Masked: `****` plus the last 4 characters. `None` when no key is set.
+    #[schema(example = "****1a2b")]
+    pub openai_api_key: Option<String>,
+    /// `false` for a legacy plaintext row awaiting `rotate-secrets-key`.
+    pub encrypted: bool,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct ApiKeyRequest {
+    /// New key, or `null` to remove it.
+    pub openai_api_key: Option<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/chats/{chat_id}/api-key",
+    params(("chat_id" = i64, Path, description = "Telegram chat id")),
+    responses(
+        (status = 200, body = ApiKeyResponse, description = "Masked key"),
+        (status = 401, body = ApiError, description = "Missing or invalid token"),
+        (status = 403, body = ApiError, description = "No role or insufficient role in this chat"),
+        (status = 404, body = ApiError, description = "Chat not configured"),
+    ),
+    security(("bearer" = [])),
+    tag = "chats"
+)]
+pub async fn api_key(
+    State(state): State<AppState>,
+    ctx: DashboardContext,
+    Path(chat_id): Path<i64>,
+) -> ApiResult<ApiKeyResponse> {
+    if let Err(e) = ctx.require(&state, chat_id, Permission::EditAiConfig).await {
+        return ApiResult::Error(e);
+    }
+    let stored = match api_keys::load(state.db.pool(), chat_id).await {
+        Ok(Some(stored)) => stored,
+        Ok(None) => {
+            return api_error!("NOT_FOUND", "chat not configured", StatusCode::NOT_FOUND);
+        }
+        Err(e) => {
+            error!(error = ?e, chat_id, "api key lookup failed");
+            return api_error!("DATABASE_ERROR", "failed to load api key");
+        }
+    };
+    api_success!(masked(&state, chat_id, stored.as_deref()))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/chats/{chat_id}/api-key",
+    params(("chat_id" = i64, Path, description = "Telegram chat id")),
+    request_body = ApiKeyRequest,
+    responses(
+        (status = 200, body = ApiKeyResponse, description = "Key stored (masked)"),
+        (status = 400, body = ApiError, description = "Empty key"),
+        (status = 401, body = ApiError, description = "Missing or invalid token"),
+        (status = 403, body = ApiError, description = "No role or insufficient role in this chat"),
+        (status = 404, body = ApiError, description = "Chat not configured"),
+        (status = 503, body = ApiError, description = "CONFIG_SECRETS_KEY not set"),
+    ),
+    security(("bearer" = [])),
+    tag = "chats"
+)]
+pub async fn set_api_key(
+    State(state): State<AppState>,
+    ctx: DashboardContext,
+    Path(chat_id): Path<i64>,
+    body: Result<Json<ApiKeyRequest>, JsonRejection>,
+) -> ApiResult<ApiKeyResponse> {
+    let Ok(Json(req)) = body else {
+        return api_error!(
+            "VALIDATION_ERROR",
+            "expected a JSON api-key request",
+            StatusCode::BAD_REQUEST
+        );
+    };
+    if let Err(e) = ctx.require(&state, chat_id, Permission::EditAiConfig).await {
+        return ApiResult::Error(e);
+    }
+    let plaintext = req.openai_api_key.as_deref().map(str::trim);
+    if plaintext.is_some_and(str::is_empty) {
+        return api_error!(
+            "VALIDATION_ERROR",
+            "openai_api_key must not be empty; send null to remove it",
+            StatusCode::BAD_REQUEST
+        );
+    }
+    if plaintext.is_some() && !state.api_keys.can_seal() {
+        return api_error!(
+            "SECRETS_KEY_UNSET",
+            "server has no CONFIG_SECRETS_KEY; keys cannot be stored",
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+    }
+    let sealed = match plaintext
+        .map(|p| state.api_keys.seal(chat_id, p))
+        .transpose()
+    {
+        Ok(s) => s,
+        Err(e) => {
+            error!(error = %e, chat_id, "api key seal failed");
+            return api_error!("INTERNAL_ERROR", "failed to encrypt api key");
+        }
+    };
+    match api_keys::store(state.db.pool(), chat_id, sealed.as_deref()).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return api_error!("NOT_FOUND", "chat not configured", StatusCode::NOT_FOUND);
+        }
+        Err(e) => {
+            error!(error = ?e, chat_id, "api key store failed");
+            return api_error!("DATABASE_ERROR", "failed to store api key");
+        }
+    }
+    info!(
+        chat_id,
+        user_id = ctx.user_id,
+        set = sealed.is_some(),
+        "openai api key updated"
+    );
+    api_success!(masked(&state, chat_id, sealed.as_deref()))
+}
+
+fn masked(state: &AppState, chat_id: i64, stored: Option<&str>) -> ApiKeyResponse {
+    let Some(stored) = stored else {
+        return ApiKeyResponse {
+            openai_api_key: None,
+            encrypted: false,
+        };
+    };
+    let openai_api_key = match state.api_keys.open(chat_id, stored) {
+        Ok(plain) => api_keys::mask(&plain),
+        Err(e) => {
+            // Wrong / missing master key: still confirm a key exists.
+            error!(error = %format!("{e:#}"), chat_id, "api key cannot be opened");
+            api_keys::mask("")
+        }
+    };
+    ApiKeyResponse {
+        openai_api_key: Some(openai_api_key),
+        encrypted: api_keys::is_sealed(stored),
+    }
+}
@@ -0,0 +1,299 @@
+//! `GET /api/v1/chats/{chat_id}/events` — server-sent events feed of live
+//! chat activity (`services::live_feed`) for the dashboard. Viewer-level
+//! ([`Permission::ViewReports`]).
+//!
+//! The connection subscribes to the process-wide broadcast before reading
+//! the resume buffer, so an event published in between is not lost; ids
+//! at or below the last one sent are skipped, so it is not sent twice
+//! either. The stream ends on shutdown, when the caller loses the role
+//! (re-checked every [`ROLE_RECHECK`]), or when it falls more than the
+//! broadcast capacity behind — in each case the client reconnects with
+//! `Last-Event-ID` and resumes from the buffer.
+
+use std::collections::HashSet;
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::extract::rejection::QueryRejection;
+use axum::extract::{Path, Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use serde::Deserialize;
+use tokio::sync::broadcast::Receiver;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::{Instant, Interval};
+use tracing::{info, warn};
+use utoipa::IntoParams;
+
+use crate::api::response::ApiError;
+use crate::api::state::AppState;
+use crate::api::webapp_auth::DashboardContext;
+use crate::models::chat_moderator::Permission;
+use crate::services::live_feed::{LiveEvent, LiveEventKind, StreamId};
+use crate::telemetry::metrics;
+
+const LAST_EVENT_ID: &str = "last-event-id";
+/// How often an open stream re-checks the caller's role.
+const ROLE_RECHECK: Duration = Duration::from_secs(60);
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct EventsQuery {
+    /// Comma-separated event types to receive (`captcha.issued`,
+    /// `captcha.solved`, `captcha.expired`, `spam.verdict`,
+    /// `moderation.action`); every type when omitted.
+    pub types: Option<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/chats/{chat_id}/events",
+    params(
+        ("chat_id" = i64, Path, description = "Telegram chat id"),
+        EventsQuery,
+        ("Last-Event-ID" = Option<String>, Header, description = "Resume after this event id"),
+    ),
+    responses(
+        (status = 200, content_type = "text/event-stream", body = String,
+            description = "One SSE event per activity: `event` is the type, `data` the JSON event, `id` the resume id"),
+        (status = 400, body = ApiError, description = "Unknown event type or malformed Last-Event-ID"),
+        (status = 401, body = ApiError, description = "Missing or invalid token"),
+        (status = 403, body = ApiError, description = "No role in this chat"),
+    ),
+    security(("bearer" = [])),
+    tag = "chats"
+)]
+pub async fn events(
+    State(state): State<AppState>,
+    ctx: DashboardContext,
+    Path(chat_id): Path<i64>,
+    query: Result<Query<EventsQuery>, QueryRejection>,
+    headers: HeaderMap,
+) -> Response {
+    let Ok(Query(query)) = query else {
+        return bad_request("malformed query string".into());
+    };
+    let types = match parse_types(query.types.as_deref()) {
+        Ok(t) => t,
+        Err(unknown) => return bad_request(format!("unknown event type '{unknown}'")),
+    };
+    let resume = match headers.get(LAST_EVENT_ID) {
+        None => None,
+        Some(value) => match value.to_str().map(str::parse::<StreamId>) {
+            Ok(Ok(id)) => Some(id),
+            _ => return bad_request("malformed Last-Event-ID".into()),
+        },
+    };
+    if let Err(e) = ctx.require(&state, chat_id, Permission::ViewReports).await {
+        return e.into_response();
+    }
+
+    // Subscribe first: an event published while the backlog is read is
+    // then both in the backlog and on `live`, and deduped by id.
+    let live = state.live.subscribe();
+    let filter = Filter { chat_id, types };
+    let mut backlog = match resume {
+        Some(after) => match state.live.replay(chat_id, after).await {
+            Ok(events) => events,
+            Err(e) => {
+                warn!(error = %e, chat_id, "live feed replay failed; streaming live events only");
+                Vec::new()
+            }
+        },
+        None => Vec::new(),
+    };
+    backlog.retain(|event| filter.matches(event));
+    info!(
+        chat_id,
+        user_id = ctx.user_id,
+        resumed = resume.is_some(),
+        backlog = backlog.len(),
+        "live stream opened"
+    );
+
+    let connection = Connection {
+        state,
+        ctx,
+        filter,
+        backlog: backlog.into_iter(),
+        live,
+        last_id: resume,
+        recheck: tokio::time::interval_at(Instant::now() + ROLE_RECHECK, ROLE_RECHECK),
+        _open: OpenStream::new(),
+    };
+    let stream = futures::stream::unfold(connection, |mut conn| async move {
+        let frame = conn.next_frame().await?;
+        Some((Ok::<_, Infallible>(frame), conn))
+    });
+    Sse::new(stream)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+/// `None` for "every type"; `Err` carries the first unknown name.
+fn parse_types(raw: Option<&str>) -> Result<Option<HashSet<&'static str>>, String> {
+    let Some(raw) = raw else {
+        return Ok(None);
+    };
+    raw.split(',')
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(|t| {
+            LiveEventKind::TYPES
+                .into_iter()
+                .find(|known| *known == t)
+                .ok_or_else(|| t.to_owned())
+        })
+        .collect::<Result<_, _>>()
+        .map(Some)
+}
+
+fn bad_request(message: String) -> Response {
+    ApiError {
+        code: "VALIDATION_ERROR".into(),
+        message,
+        status: StatusCode::BAD_REQUEST,
+    }
+    .into_response()
+}
+
+/// Which events a connection receives.
+struct Filter {
+    chat_id: i64,
+    /// `None` for every type.
+    types: Option<HashSet<&'static str>>,
+}
+
+impl Filter {
+    fn matches(&self, event: &LiveEvent) -> bool {
+        event.chat_id == self.chat_id
+            && self
+                .types
+                .as_ref()
+                .is_none_or(|types| types.contains(event.kind.name()))
+    }
+}
+
+/// One open stream.
+struct Connection {
+    state: AppState,
+    ctx: DashboardContext,
+    filter: Filter,
+    /// Replayed events, already filtered.
+    backlog: std::vec::IntoIter<LiveEvent>,
+    live: Receiver<Arc<LiveEvent>>,
+    /// Highest id sent (or the `Last-Event-ID` resumed from).
+    last_id: Option<StreamId>,
+    recheck: Interval,
+    _open: OpenStream,
+}
+
+enum Wake {
+    Shutdown,
+    Recheck,
+    Live(Result<Arc<LiveEvent>, RecvError>),
+}
+
+impl Connection {
+    /// The next frame to send, or `None` to end the stream.
+    async fn next_frame(&mut self) -> Option<Event> {
+        if let Some(event) = self.backlog.next() {
+            self.last_id = event.id.or(self.last_id);
+            return Some(frame(&event));
+        }
+        loop {
+            let wake = tokio::select! {
+                biased;
+                _ = self.state.live.closed().cancelled() => Wake::Shutdown,
+                _ = self.recheck.tick() => Wake::Recheck,
+                received = self.live.recv() => Wake::Live(received),
+            };
+            match wake {
+                Wake::Shutdown => return None,
+                Wake::Recheck => {
+                    let allowed = self
+                        .ctx
+                        .require(&self.state, self.filter.chat_id, Permission::ViewReports)
+                        .await;
+                    if let Err(e) = allowed {
+                        info!(
+                            chat_id = self.filter.chat_id,
+                            user_id = self.ctx.user_id,
+                            code = %e.code,
+                            "live stream closed: access revoked"
+                        );
+                        return None;
+                    }
+                }
+                Wake::Live(Ok(event)) if self.filter.matches(&event) && self.is_new(&event) => {
+                    self.last_id = event.id.or(self.last_id);
+                    return Some(frame(&event));
+                }
+                Wake::Live(Ok(_)) => {}
+                Wake::Live(Err(RecvError::Lagged(missed))) => {
+                    warn!(
+                        chat_id = self.filter.chat_id,
+                        missed, "live stream lagging; closing so the client resumes"
+                    );
+                    return None;
+                }
+                Wake::Live(Err(RecvError::Closed)) => return None,
+            }
+        }
+    }
+
+    /// Id-less events (published while Redis was down) are always new.
+    fn is_new(&self, event: &LiveEvent) -> bool {
+        match (event.id, self.last_id) {
+            (Some(id), Some(last)) => id > last,
+            _ => true,
+        }
+    }
+}
+
+fn frame(event: &LiveEvent) -> Event {
+    let frame = Event::default()
+        .event(event.kind.name())
+        .data(serde_json::to_string(event).unwrap_or_default());
+    match event.id {
+        Some(id) => frame.id(id.to_string()),
+        None => frame,
+    }
+}
+
+/// Holds `vixen_live_streams` up for the stream's lifetime.
+struct OpenStream;
+
+impl OpenStream {
+    fn new() -> Self {
+        metrics::live_stream(true);
+        Self
+    }
+}
+
+impl Drop for OpenStream {
+    fn drop(&mut self) {
+        metrics::live_stream(false);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn types_filter_parses_known_names() {
+        assert_eq!(parse_types(None), Ok(None));
+        let types = parse_types(Some("captcha.solved, spam.verdict,")).unwrap();
+        assert_eq!(
+            types,
+            Some(HashSet::from(["captcha.solved", "spam.verdict"]))
+        );
+        assert_eq!(
+            parse_types(Some("captcha.solved,captcha")),
+            Err("captcha".to_owned())
+        );
+    }
+}
@@ -1,9 +1,10 @@
 //! `GET /health` — operational liveness probe.
 //!
 //! Returns `200` with `{"status":"ok","checks":{"db":"ok","redis":"ok"}}` when
-//! both pools respond, and `503` with `"degraded"` plus per-component status
-//! otherwise. Used by the load balancer / docker compose `condition:
-//! service_healthy`.
+//! both pools respond. Redis alone down is still `200`, with `"degraded"`:
+//! the bot keeps working on its fallbacks (see `database::redis`) and
+//! recovers by itself. Postgres down is `503` with `"down"`. Used by the load
+//! balancer / docker compose `condition: service_healthy`.
 
 use axum::Json;
 use axum::extract::State;
@@ -15,7 +16,8 @@ use crate::api::state::AppState;
 
 #[derive(Serialize, ToSchema)]
 pub struct HealthResponse {
-    /// Overall status — `ok` if every check is up, `degraded` otherwise.
+    /// Overall status — `ok` if every check is up, `degraded` while only
+    /// Redis is down, `down` while Postgres is.
     #[schema(example = "ok")]
     pub status: &'static str,
     pub checks: HealthChecks,
@@ -35,8 +37,8 @@ pub struct HealthChecks {
     get,
     path = "/health",
     responses(
-        (status = 200, body = HealthResponse, description = "All checks passing"),
-        (status = 503, body = HealthResponse, description = "One or more checks failing"),
+        (status = 200, body = HealthResponse, description = "Postgres up; `degraded` if Redis is down"),
+        (status = 503, body = HealthResponse, description = "Postgres down"),
     ),
     tag = "ops"
 )]
@@ -44,13 +46,17 @@ pub async fn health(State(state): State<AppState>) -> (StatusCode, Json<HealthRe
     let db_ok = state.db.health_check().await.is_ok();
     let redis_ok = state.redis.ping().await.is_ok();
     let body = HealthResponse {
-        status: if db_ok && redis_ok { "ok" } else { "degraded" },
+        status: match (db_ok, redis_ok) {
+            (false, _) => "down",
+            (true, false) => "degraded",
+            (true, true) => "ok",
+        },
         checks: HealthChecks {
             db: if db_ok { "ok" } else { "down" },
             redis: if redis_ok { "ok" } else { "down" },
         },
     };
-    let code = if db_ok && redis_ok {
+    let code = if db_ok {
         StatusCode::OK
     } else {
         StatusCode::SERVICE_UNAVAILABLE
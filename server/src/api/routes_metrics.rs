@@ -0,0 +1,80 @@
+//! `GET /metrics` — Prometheus exposition, plus the middleware that feeds the
+//! `vixen_http_*` series.
+//!
+//! Gated by `X-Admin-Secret: <CONFIG_ADMIN_SECRET>` (constant-time compare,
+//! see `docs/auth.md`). Without a configured secret the endpoint is disabled
+//! and answers `404`, so dev builds never expose it by accident.
+
+use std::time::Instant;
+
+use axum::extract::{MatchedPath, Request, State};
+use axum::http::header::CONTENT_TYPE;
+use axum::http::{HeaderMap, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use subtle::ConstantTimeEq;
+
+use crate::api::state::AppState;
+use crate::telemetry::metrics;
+
+const ADMIN_SECRET_HEADER: &str = "x-admin-secret";
+const EXPOSITION_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+/// `route` label for requests that matched no route (404s, scanners).
+const UNMATCHED_ROUTE: &str = "unmatched";
+
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    params(("X-Admin-Secret" = String, Header, description = "CONFIG_ADMIN_SECRET")),
+    responses(
+        (status = 200, content_type = "text/plain", description = "Prometheus text exposition"),
+        (status = 401, description = "Missing or wrong admin secret"),
+        (status = 404, description = "No admin secret configured — endpoint disabled"),
+    ),
+    tag = "ops"
+)]
+pub async fn metrics(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    let Some(secret) = state.config.admin_secret.as_ref() else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let given = headers
+        .get(ADMIN_SECRET_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
+    if !bool::from(given.as_bytes().ct_eq(secret.expose().as_bytes())) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+
+    let pool = state.db.pool();
+    metrics::db_pool(
+        pool.size(),
+        pool.num_idle(),
+        pool.options().get_max_connections(),
+    );
+
+    match metrics::render() {
+        Some(body) => ([(CONTENT_TYPE, EXPOSITION_CONTENT_TYPE)], body).into_response(),
+        None => StatusCode::SERVICE_UNAVAILABLE.into_response(),
+    }
+}
+
+/// Count and time every request by method, matched route template and
+/// status. The raw URI is never used as a label — `/report/{slug}` stays one
+/// series no matter how many slugs are scraped.
+pub async fn track(req: Request, next: Next) -> Response {
+    let method = req.method().as_str().to_owned();
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or(UNMATCHED_ROUTE, MatchedPath::as_str)
+        .to_owned();
+    let started = Instant::now();
+    let response = next.run(req).await;
+    metrics::http_request(
+        &method,
+        &route,
+        response.status().as_u16(),
+        started.elapsed(),
+    );
+    response
+}
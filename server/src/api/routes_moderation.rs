@@ -0,0 +1,542 @@
+//! `/api/v1/chats/{chat_id}/moderation/*` — the dashboard's verified and
+//! banned users lists (`services::user_lists`, viewer-level) and the
+//! ban / unban / verify / unverify buttons next to them.
+//!
+//! Mutations go through the same services as the `/ban`, `/unban` and
+//! `/verify` commands, with [`ActorKind::Moderator`] and the token's user
+//! id as the actor, so the ledger, webhooks and live feed can't tell a
+//! dashboard click from a command. Each is idempotent: `applied: false`
+//! means the user was already in the requested state.
+
+use axum::Json;
+use axum::extract::rejection::{JsonRejection, QueryRejection};
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, warn};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::api::pagination;
+use crate::api::response::{ApiError, ApiResult};
+use crate::api::state::AppState;
+use crate::api::webapp_auth::DashboardContext;
+use crate::models::chat_moderator::{self, Permission};
+use crate::models::moderation_action::ActorKind;
+use crate::services::captcha::Outcome as CaptchaOutcome;
+use crate::services::moderation_service::{Action, ApplyContext, Outcome as ModOutcome};
+use crate::services::outbound::OutboundError;
+use crate::services::user_info::UserProfile;
+use crate::services::user_lists::{self, BannedUserItem, UserCursor, VerifiedUserItem};
+use crate::{api_error, api_success};
+
+const DEFAULT_BAN_REASON: &str = "manual ban (no reason)";
+
+#[derive(Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct UsersQuery {
+    /// User id (exact), or a case-insensitive substring of the username
+    /// (leading `@` optional) or name.
+    pub q: Option<String>,
+    /// `cursor` from the previous page.
+    pub cursor: Option<String>,
+    /// Page size, 1–200 (default 50).
+    pub limit: Option<i64>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct VerifiedPage {
+    /// Most recently verified first.
+    pub items: Vec<VerifiedUserItem>,
+    pub has_more: bool,
+    /// Pass as `?cursor=` for the next page; `None` on the last one.
+    pub cursor: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct BannedPage {
+    /// Most recently banned first.
+    pub items: Vec<BannedUserItem>,
+    pub has_more: bool,
+    /// Pass as `?cursor=` for the next page; `None` on the last one.
+    pub cursor: Option<String>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct BanRequest {
+    pub user_id: i64,
+    /// Recorded in the ledger; defaults to `manual ban (no reason)`.
+    pub reason: Option<String>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct TargetRequest {
+    pub user_id: i64,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ModerationResponse {
+    pub user_id: i64,
+    /// `false` when the user already was in the requested state; nothing
+    /// was written or sent to Telegram.
+    pub applied: bool,
+    /// Cached Telegram profile; `None` for users the bot never saw.
+    pub user: Option<UserProfile>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/chats/{chat_id}/moderation/verified",
+    params(("chat_id" = i64, Path, description = "Telegram chat id"), UsersQuery),
+    responses(
+        (status = 200, body = VerifiedPage, description = "A page of verified users"),
+        (status = 400, body = ApiError, description = "Malformed query or cursor"),
+        (status = 401, body = ApiError, description = "Missing or invalid token"),
+        (status = 403, body = ApiError, description = "No role in this chat"),
+    ),
+    security(("bearer" = [])),
+    tag = "chats"
+)]
+pub async fn verified(
+    State(state): State<AppState>,
+    ctx: DashboardContext,
+    Path(chat_id): Path<i64>,
+    query: Result<Query<UsersQuery>, QueryRejection>,
+) -> ApiResult<VerifiedPage> {
+    let (q, after) = match parse_query(query) {
+        Ok(v) => v,
+        Err(e) => return ApiResult::Error(e),
+    };
+    if let Err(e) = ctx.require(&state, chat_id, Permission::ViewReports).await {
+        return ApiResult::Error(e);
+    }
+    let limit = pagination::limit(q.limit);
+    let pool = state.db.pool();
+    match user_lists::verified(pool, chat_id, q.q.as_deref(), after, limit + 1).await {
+        Ok(mut items) => {
+            let has_more = items.len() as i64 > limit;
+            items.truncate(limit as usize);
+            let cursor = next_cursor(has_more, items.last().map(VerifiedUserItem::cursor));
+            api_success!(VerifiedPage {
+                items,
+                has_more,
+                cursor,
+            })
+        }
+        Err(e) => {
+            error!(error = ?e, chat_id, "verified users query failed");
+            api_error!("DATABASE_ERROR", "failed to list verified users")
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/chats/{chat_id}/moderation/banned",
+    params(("chat_id" = i64, Path, description = "Telegram chat id"), UsersQuery),
+    responses(
+        (status = 200, body = BannedPage, description = "A page of currently banned users"),
+        (status = 400, body = ApiError, description = "Malformed query or cursor"),
+        (status = 401, body = ApiError, description = "Missing or invalid token"),
+        (status = 403, body = ApiError, description = "No role in this chat"),
+    ),
+    security(("bearer" = [])),
+    tag = "chats"
+)]
+pub async fn banned(
+    State(state): State<AppState>,
+    ctx: DashboardContext,
+    Path(chat_id): Path<i64>,
+    query: Result<Query<UsersQuery>, QueryRejection>,
+) -> ApiResult<BannedPage> {
+    let (q, after) = match parse_query(query) {
+        Ok(v) => v,
+        Err(e) => return ApiResult::Error(e),
+    };
+    if let Err(e) = ctx.require(&state, chat_id, Permission::ViewReports).await {
+        return ApiResult::Error(e);
+    }
+    let limit = pagination::limit(q.limit);
+    let pool = state.db.pool();
+    match user_lists::banned(pool, chat_id, q.q.as_deref(), after, limit + 1).await {
+        Ok(mut items) => {
+            let has_more = items.len() as i64 > limit;
+            items.truncate(limit as usize);
+            let cursor = next_cursor(has_more, items.last().map(BannedUserItem::cursor));
+            api_success!(BannedPage {
+                items,
+                has_more,
+                cursor,
+            })
+        }
+        Err(e) => {
+            error!(error = ?e, chat_id, "banned users query failed");
+            api_error!("DATABASE_ERROR", "failed to list banned users")
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/chats/{chat_id}/moderation/ban",
+    params(("chat_id" = i64, Path, description = "Telegram chat id")),
+    request_body = BanRequest,
+    responses(
+        (status = 200, body = ModerationResponse, description = "Banned, or already banned"),
+        (status = 400, body = ApiError, description = "Malformed body or user id"),
+        (status = 401, body = ApiError, description = "Missing or invalid token"),
+        (status = 403, body = ApiError, description = "No role, insufficient role, or a target the caller can't manage"),
+        (status = 502, body = ApiError, description = "Telegram rejected the ban; nothing was recorded"),
+    ),
+    security(("bearer" = [])),
+    tag = "chats"
+)]
+pub async fn ban(
+    State(state): State<AppState>,
+    ctx: DashboardContext,
+    Path(chat_id): Path<i64>,
+    body: Result<Json<BanRequest>, JsonRejection>,
+) -> ApiResult<ModerationResponse> {
+    let Ok(Json(req)) = body else {
+        return api_error!(
+            "VALIDATION_ERROR",
+            "expected a JSON ban request",
+            StatusCode::BAD_REQUEST
+        );
+    };
+    let reason = req
+        .reason
+        .as_deref()
+        .map(str::trim)
+        .filter(|r| !r.is_empty())
+        .unwrap_or(DEFAULT_BAN_REASON)
+        .to_owned();
+    let action = Action::Ban {
+        reason,
+        until: None,
+    };
+    apply(state, ctx, chat_id, req.user_id, action).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/chats/{chat_id}/moderation/unban",
+    params(("chat_id" = i64, Path, description = "Telegram chat id")),
+    request_body = TargetRequest,
+    responses(
+        (status = 200, body = ModerationResponse, description = "Unbanned, or not banned"),
+        (status = 400, body = ApiError, description = "Malformed body or user id"),
+        (status = 401, body = ApiError, description = "Missing or invalid token"),
+        (status = 403, body = ApiError, description = "No role, insufficient role, or a target the caller can't manage"),
+        (status = 502, body = ApiError, description = "Telegram rejected the unban; nothing was recorded"),
+    ),
+    security(("bearer" = [])),
+    tag = "chats"
+)]
+pub async fn unban(
+    State(state): State<AppState>,
+    ctx: DashboardContext,
+    Path(chat_id): Path<i64>,
+    body: Result<Json<TargetRequest>, JsonRejection>,
+) -> ApiResult<ModerationResponse> {
+    let Ok(Json(req)) = body else {
+        return api_error!(
+            "VALIDATION_ERROR",
+            "expected a JSON unban request",
+            StatusCode::BAD_REQUEST
+        );
+    };
+    apply(state, ctx, chat_id, req.user_id, Action::Unban).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/chats/{chat_id}/moderation/verify",
+    params(("chat_id" = i64, Path, description = "Telegram chat id")),
+    request_body = TargetRequest,
+    responses(
+        (status = 200, body = ModerationResponse, description = "Verified, or already verified"),
+        (status = 400, body = ApiError, description = "Malformed body or user id"),
+        (status = 401, body = ApiError, description = "Missing or invalid token"),
+        (status = 403, body = ApiError, description = "No role or insufficient role in this chat"),
+    ),
+    security(("bearer" = [])),
+    tag = "chats"
+)]
+pub async fn verify(
+    State(state): State<AppState>,
+    ctx: DashboardContext,
+    Path(chat_id): Path<i64>,
+    body: Result<Json<TargetRequest>, JsonRejection>,
+) -> ApiResult<ModerationResponse> {
+    let user_id = match target(body, "verify", &state, &ctx, chat_id, Permission::Verify).await {
+        Ok(id) => id,
+        Err(e) => return ApiResult::Error(e),
+    };
+    let outcome = match state
+        .captcha
+        .verify_manual(chat_id, user_id, Some(ctx.user_id))
+        .await
+    {
+        Ok((outcome, _)) => outcome,
+        Err(e) => {
+            error!(error = ?e, chat_id, user_id, "dashboard verify failed");
+            return api_error!("DATABASE_ERROR", "failed to verify the user");
+        }
+    };
+    // Same best-effort Redis fill as `/verify`.
+    if let Err(e) = state.captcha_state.mark_verified(chat_id, user_id).await {
+        warn!(error = ?e, "redis mark_verified (dashboard) failed");
+    }
+    let applied = outcome == CaptchaOutcome::Solved;
+    info!(
+        chat_id,
+        user_id,
+        actor = ctx.user_id,
+        applied,
+        "dashboard verify"
+    );
+    respond(&state, user_id, applied).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/chats/{chat_id}/moderation/unverify",
+    params(("chat_id" = i64, Path, description = "Telegram chat id")),
+    request_body = TargetRequest,
+    responses(
+        (status = 200, body = ModerationResponse, description = "Unverified, or not verified"),
+        (status = 400, body = ApiError, description = "Malformed body or user id"),
+        (status = 401, body = ApiError, description = "Missing or invalid token"),
+        (status = 403, body = ApiError, description = "No role or insufficient role in this chat"),
+    ),
+    security(("bearer" = [])),
+    tag = "chats"
+)]
+pub async fn unverify(
+    State(state): State<AppState>,
+    ctx: DashboardContext,
+    Path(chat_id): Path<i64>,
+    body: Result<Json<TargetRequest>, JsonRejection>,
+) -> ApiResult<ModerationResponse> {
+    let user_id = match target(body, "unverify", &state, &ctx, chat_id, Permission::Verify).await {
+        Ok(id) => id,
+        Err(e) => return ApiResult::Error(e),
+    };
+    let applied = match state
+        .captcha
+        .unverify_manual(chat_id, user_id, ctx.user_id)
+        .await
+    {
+        Ok(action_id) => action_id.is_some(),
+        Err(e) => {
+            error!(error = ?e, chat_id, user_id, "dashboard unverify failed");
+            return api_error!("DATABASE_ERROR", "failed to unverify the user");
+        }
+    };
+    if applied {
+        if let Err(e) = state.captcha_state.clear_verified(chat_id, user_id).await {
+            warn!(error = ?e, "redis clear_verified (dashboard) failed");
+        }
+    }
+    info!(
+        chat_id,
+        user_id,
+        actor = ctx.user_id,
+        applied,
+        "dashboard unverify"
+    );
+    respond(&state, user_id, applied).await
+}
+
+/// Shared tail of `ban` / `unban`: permission, then `ModerationService`.
+/// A target who holds a role in the chat needs the same standing as `/mod`
+/// does to change it (see `ModeratorRole::can_manage`), so a moderator
+/// can't ban a fellow moderator or an admin.
+async fn apply(
+    state: AppState,
+    ctx: DashboardContext,
+    chat_id: i64,
+    user_id: i64,
+    action: Action,
+) -> ApiResult<ModerationResponse> {
+    if let Err(e) = check_user_id(user_id) {
+        return ApiResult::Error(e);
+    }
+    let actor_role = match ctx.require(&state, chat_id, Permission::Ban).await {
+        Ok(role) => role,
+        Err(e) => return ApiResult::Error(e),
+    };
+    match chat_moderator::role_of(state.db.pool(), chat_id, user_id).await {
+        Ok(Some(target_role)) if !actor_role.can_manage(target_role) => {
+            warn!(
+                chat_id,
+                user_id,
+                actor = ctx.user_id,
+                ?actor_role,
+                ?target_role,
+                "dashboard moderation: target outranks actor"
+            );
+            return api_error!(
+                "FORBIDDEN",
+                format!(
+                    "role '{}' cannot act on a '{}'",
+                    actor_role.as_db_str(),
+                    target_role.as_db_str()
+                ),
+                StatusCode::FORBIDDEN
+            );
+        }
+        Ok(_) => {}
+        Err(e) => {
+            error!(error = ?e, chat_id, user_id, "dashboard moderation: target role lookup failed");
+            return api_error!("DATABASE_ERROR", "failed to resolve the target's role");
+        }
+    }
+    let apply_ctx = ApplyContext {
+        chat_id,
+        target_user_id: user_id,
+        message_id: None,
+        actor_kind: ActorKind::Moderator,
+        actor_user_id: Some(ctx.user_id),
+    };
+    let outcome = match state.moderation.apply(action.clone(), apply_ctx).await {
+        Ok(outcome) => outcome,
+        // Only fatal Telegram errors surface; the ledger row was rolled back.
+        Err(e) if e.downcast_ref::<OutboundError>().is_some() => {
+            warn!(error = ?e, chat_id, user_id, ?action, "dashboard moderation: bot API failed");
+            return api_error!(
+                "BOT_API_ERROR",
+                "Telegram rejected the request; try again",
+                StatusCode::BAD_GATEWAY
+            );
+        }
+        Err(e) => {
+            error!(error = ?e, chat_id, user_id, ?action, "dashboard moderation failed");
+            return api_error!("DATABASE_ERROR", "failed to record the action");
+        }
+    };
+    let applied = matches!(outcome, ModOutcome::Applied(_));
+    info!(
+        chat_id,
+        user_id,
+        actor = ctx.user_id,
+        ?action,
+        applied,
+        "dashboard moderation"
+    );
+    respond(&state, user_id, applied).await
+}
+
+/// Body → validated user id, after the permission check.
+async fn target(
+    body: Result<Json<TargetRequest>, JsonRejection>,
+    what: &str,
+    state: &AppState,
+    ctx: &DashboardContext,
+    chat_id: i64,
+    permission: Permission,
+) -> Result<i64, ApiError> {
+    let Ok(Json(req)) = body else {
+        return Err(validation(format!("expected a JSON {what} request")));
+    };
+    check_user_id(req.user_id)?;
+    ctx.require(state, chat_id, permission).await?;
+    Ok(req.user_id)
+}
+
+async fn respond(state: &AppState, user_id: i64, applied: bool) -> ApiResult<ModerationResponse> {
+    // The profile is decoration; a lookup failure doesn't undo the action.
+    let user = match state.user_info.get(user_id).await {
+        Ok(user) => user,
+        Err(e) => {
+            warn!(error = ?e, user_id, "user_info lookup failed");
+            None
+        }
+    };
+    api_success!(ModerationResponse {
+        user_id,
+        applied,
+        user,
+    })
+}
+
+/// Query → normalised search and resume point.
+fn parse_query(
+    query: Result<Query<UsersQuery>, QueryRejection>,
+) -> Result<(UsersQuery, Option<UserCursor>), ApiError> {
+    let Ok(Query(mut query)) = query else {
+        return Err(validation("malformed query string"));
+    };
+    let after = match &query.cursor {
+        Some(raw) => Some(pagination::decode(raw).ok_or_else(|| validation("malformed cursor"))?),
+        None => None,
+    };
+    query.q = normalize_search(query.q.as_deref());
+    Ok((query, after))
+}
+
+/// Trimmed search text; `None` when nothing is left to match on.
+fn normalize_search(q: Option<&str>) -> Option<String> {
+    q.map(str::trim)
+        .filter(|q| !q.is_empty() && *q != "@")
+        .map(str::to_owned)
+}
+
+fn next_cursor(has_more: bool, last: Option<UserCursor>) -> Option<String> {
+    if has_more {
+        last.map(|c| pagination::encode(&c))
+    } else {
+        None
+    }
+}
+
+fn check_user_id(user_id: i64) -> Result<(), ApiError> {
+    if user_id > 0 {
+        Ok(())
+    } else {
+        Err(validation("user_id must be a positive Telegram user id"))
+    }
+}
+
+fn validation(message: impl Into<String>) -> ApiError {
+    ApiError {
+        code: "VALIDATION_ERROR".into(),
+        message: message.into(),
+        status: StatusCode::BAD_REQUEST,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search_is_trimmed_and_blank_dropped() {
+        assert_eq!(normalize_search(None), None);
+        assert_eq!(normalize_search(Some("   ")), None);
+        assert_eq!(normalize_search(Some(" @ ")), None);
+        assert_eq!(
+            normalize_search(Some(" @alice ")).as_deref(),
+            Some("@alice")
+        );
+        assert_eq!(normalize_search(Some("42")).as_deref(), Some("42"));
+    }
+
+    #[test]
+    fn cursor_only_when_more() {
+        let last = UserCursor {
+            at: "2026-05-17T12:00:00Z".parse().unwrap(),
+            user_id: 7,
+        };
+        assert_eq!(next_cursor(false, Some(last)), None);
+        let encoded = next_cursor(true, Some(last)).unwrap();
+        assert_eq!(pagination::decode::<UserCursor>(&encoded), Some(last));
+    }
+
+    #[test]
+    fn user_id_must_be_positive() {
+        assert!(check_user_id(1).is_ok());
+        assert!(check_user_id(0).is_err());
+        assert!(check_user_id(-100_123).is_err());
+    }
+}
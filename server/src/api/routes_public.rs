@@ -0,0 +1,411 @@
+//! `/report/{slug}` — the public, indexable chat report. No auth.
+//!
+//! Serves [`PublicReportService`] output outside the `/api/v1` envelope:
+//! the redacted `ReportData` as JSON, or a small server-rendered HTML page
+//! with OpenGraph tags for link previews and crawlers (picked by
+//! `?format=` or, failing that, `Accept: text/html`), plus the WebP chart.
+//! Successful responses carry a content-hash `ETag` and long-lived public
+//! `Cache-Control` so a CDN in front absorbs nearly all traffic; misses are
+//! cached briefly too. Errors use the usual `ApiError` body.
+
+use std::fmt::Write as _;
+
+use axum::extract::rejection::QueryRejection;
+use axum::extract::{Path, Query, State};
+use axum::http::header::{ACCEPT, CACHE_CONTROL, CONTENT_TYPE, ETAG, IF_NONE_MATCH, VARY};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use serde::Deserialize;
+use tracing::error;
+use utoipa::IntoParams;
+
+use crate::api::response::ApiError;
+use crate::api::state::AppState;
+use crate::models::report::ReportData;
+use crate::services::public_report::{PublicReport, is_valid_slug};
+use crate::services::report_render::Lang;
+use crate::tr;
+
+/// The report only changes at chat-local midnight; let browsers and CDNs
+/// keep it for an hour and serve stale for a day while revalidating.
+const CACHE_OK: &str = "public, max-age=3600, stale-while-revalidate=86400";
+/// Unknown / opted-out slugs — short, so opting in shows up quickly.
+const CACHE_MISS: &str = "public, max-age=300";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PublicFormat {
+    Json,
+    Html,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PublicReportQuery {
+    /// `json` or `html`; defaults to HTML for `Accept: text/html`, JSON otherwise.
+    #[param(value_type = Option<String>)]
+    pub format: Option<PublicFormat>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/report/{slug}",
+    params(
+        ("slug" = String, Path, description = "Public chat slug, `[a-z0-9-]{3,64}`"),
+        PublicReportQuery,
+    ),
+    responses(
+        (status = 200, description = "Redacted report for the last complete chat-local day",
+            content((ReportData = "application/json"), (String = "text/html"))),
+        (status = 304, description = "Unchanged since the supplied `ETag`"),
+        (status = 400, body = ApiError, description = "Malformed slug or format"),
+        (status = 404, body = ApiError, description = "No public report under this slug"),
+    ),
+    tag = "public"
+)]
+pub async fn report(
+    State(state): State<AppState>,
+    Path(slug): Path<String>,
+    query: Result<Query<PublicReportQuery>, QueryRejection>,
+    headers: HeaderMap,
+) -> Response {
+    let Ok(Query(query)) = query else {
+        return bad_request("format must be 'json' or 'html'");
+    };
+    let report = match load(&state, &slug).await {
+        Ok(r) => r,
+        Err(resp) => return resp,
+    };
+    let format = query.format.unwrap_or_else(|| negotiate(&headers));
+    let (etag, content_type, body) = match format {
+        PublicFormat::Json => (
+            etag(&report, "json"),
+            "application/json",
+            serde_json::to_string(&report.data).unwrap_or_default(),
+        ),
+        PublicFormat::Html => (
+            etag(&report, "html"),
+            "text/html; charset=utf-8",
+            render_html(&report, state.config.public_url.as_deref()),
+        ),
+    };
+    cached(&headers, etag, content_type, body)
+}
+
+#[utoipa::path(
+    get,
+    path = "/report/{slug}/chart.webp",
+    params(("slug" = String, Path, description = "Public chat slug, `[a-z0-9-]{3,64}`")),
+    responses(
+        (status = 200, content_type = "image/webp", body = Vec<u8>, description = "480×270 report chart"),
+        (status = 304, description = "Unchanged since the supplied `ETag`"),
+        (status = 400, body = ApiError, description = "Malformed slug"),
+        (status = 404, body = ApiError, description = "No public report under this slug"),
+    ),
+    tag = "public"
+)]
+pub async fn chart(
+    State(state): State<AppState>,
+    Path(slug): Path<String>,
+    headers: HeaderMap,
+) -> Response {
+    match load(&state, &slug).await {
+        Ok(report) => cached(
+            &headers,
+            etag(&report, "webp"),
+            "image/webp",
+            report.chart_webp.clone(),
+        ),
+        Err(resp) => resp,
+    }
+}
+
+async fn load(state: &AppState, slug: &str) -> Result<std::sync::Arc<PublicReport>, Response> {
+    if !is_valid_slug(slug) {
+        return Err(bad_request("slug must match [a-z0-9-]{3,64}"));
+    }
+    match state.public_reports.get(slug).await {
+        Ok(Some(report)) => Ok(report),
+        Ok(None) => {
+            let err = ApiError {
+                code: "NOT_FOUND".into(),
+                message: "no public report under this slug".into(),
+                status: StatusCode::NOT_FOUND,
+            };
+            Err(([(CACHE_CONTROL, CACHE_MISS)], err).into_response())
+        }
+        Err(e) => {
+            error!(error = ?e, slug, "public report build failed");
+            let err = ApiError {
+                code: "DATABASE_ERROR".into(),
+                message: "failed to build report".into(),
+                status: StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            Err(([(CACHE_CONTROL, "no-store")], err).into_response())
+        }
+    }
+}
+
+fn bad_request(message: &str) -> Response {
+    ApiError {
+        code: "VALIDATION_ERROR".into(),
+        message: message.into(),
+        status: StatusCode::BAD_REQUEST,
+    }
+    .into_response()
+}
+
+/// HTML when the client lists `text/html` (browsers, crawlers, link
+/// unfurlers), JSON otherwise.
+fn negotiate(headers: &HeaderMap) -> PublicFormat {
+    let wants_html = headers
+        .get(ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.contains("text/html"));
+    if wants_html {
+        PublicFormat::Html
+    } else {
+        PublicFormat::Json
+    }
+}
+
+fn etag(report: &PublicReport, representation: &str) -> String {
+    format!("\"{}-{representation}\"", report.version)
+}
+
+fn not_modified(headers: &HeaderMap, etag: &str) -> bool {
+    headers
+        .get(IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| {
+            v.split(',')
+                .map(|t| t.trim().trim_start_matches("W/"))
+                .any(|t| t == etag || t == "*")
+        })
+}
+
+fn cached(
+    headers: &HeaderMap,
+    etag: String,
+    content_type: &'static str,
+    body: impl IntoResponse,
+) -> Response {
+    let common = [(CACHE_CONTROL, CACHE_OK), (VARY, "Accept")];
+    if not_modified(headers, &etag) {
+        return (StatusCode::NOT_MODIFIED, common, [(ETAG, etag)]).into_response();
+    }
+    (common, [(ETAG, etag)], [(CONTENT_TYPE, content_type)], body).into_response()
+}
+
+fn render_html(report: &PublicReport, public_url: Option<&str>) -> String {
+    let base = public_url.unwrap_or("").trim_end_matches('/');
+    let page_url = format!("{base}/report/{}", report.slug);
+    let chart_url = format!("{page_url}/chart.webp");
+    let data = &report.data;
+    let labels = Labels::for_lang(report.lang);
+    let chat = escape(data.chat_title.as_deref().unwrap_or(&report.slug));
+    let title = format!("{chat} — {} {}", labels.report_for, report.date);
+    let description = format!(
+        "{}: {} · {}: {} · {}: {} · {}: {}",
+        labels.messages,
+        data.messages_seen,
+        labels.deleted,
+        data.messages_deleted,
+        labels.verified,
+        data.users_verified,
+        labels.banned,
+        data.users_banned,
+    );
+    let lang = report.lang.as_db_str();
+
+    let mut html = String::with_capacity(4096);
+    let _ = write!(
+        html,
+        r#"<!doctype html>
+<html lang="{lang}">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{title}</title>
+<meta name="description" content="{description}">
+<meta property="og:type" content="website">
+<meta property="og:title" content="{title}">
+<meta property="og:description" content="{description}">
+<meta property="og:url" content="{page_url}">
+<meta property="og:image" content="{chart_url}">
+<meta property="og:image:type" content="image/webp">
+<meta property="og:image:width" content="480">
+<meta property="og:image:height" content="270">
+<meta name="twitter:card" content="summary_large_image">
+<link rel="canonical" href="{page_url}">
+<link rel="alternate" type="application/json" href="{page_url}?format=json">
+</head>
+<body>
+<main>
+<h1>{title}</h1>
+<img src="{chart_url}" width="480" height="270" alt="{chart_alt}">
+<table>
+<tr><th>{messages}</th><td>{seen}</td></tr>
+<tr><th>{deleted}</th><td>{deleted_n}</td></tr>
+<tr><th>{verified}</th><td>{verified_n}</td></tr>
+<tr><th>{banned}</th><td>{banned_n}</td></tr>
+</table>
+"#,
+        chart_alt = labels.chart_alt,
+        messages = labels.messages,
+        seen = data.messages_seen,
+        deleted = labels.deleted,
+        deleted_n = data.messages_deleted,
+        verified = labels.verified,
+        verified_n = data.users_verified,
+        banned = labels.banned,
+        banned_n = data.users_banned,
+    );
+    if !data.top_phrases.is_empty() {
+        let _ = writeln!(html, "<h2>{}</h2>\n<ol>", labels.top_phrases);
+        for phrase in &data.top_phrases {
+            let _ = writeln!(
+                html,
+                "<li>{} <small>×{}</small></li>",
+                escape(&phrase.text),
+                phrase.hits
+            );
+        }
+        html.push_str("</ol>\n");
+    }
+    html.push_str("</main>\n</body>\n</html>\n");
+    html
+}
+
+/// Catalog labels for the page, HTML-escaped.
+struct Labels {
+    report_for: String,
+    messages: String,
+    deleted: String,
+    verified: String,
+    banned: String,
+    top_phrases: String,
+    chart_alt: String,
+}
+
+impl Labels {
+    fn for_lang(lang: Lang) -> Self {
+        let label = |key| escape(&tr!(lang, key));
+        Self {
+            report_for: label("public-report-for"),
+            messages: label("report-messages"),
+            deleted: label("report-deleted"),
+            verified: label("report-verified"),
+            banned: label("report-banned"),
+            top_phrases: label("public-top-phrases"),
+            chart_alt: label("public-chart-alt"),
+        }
+    }
+}
+
+/// Minimal HTML escaping for text and double-quoted attribute values.
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::report::{CaptchaCounts, TopPhrase};
+    use axum::http::HeaderValue;
+    use chrono::{NaiveDate, Utc};
+
+    fn report(title: &str, phrase: &str) -> PublicReport {
+        let now = Utc::now();
+        PublicReport {
+            slug: "dart-community".into(),
+            date: NaiveDate::from_ymd_opt(2026, 5, 10).unwrap(),
+            lang: Lang::En,
+            data: ReportData {
+                chat_id: -100,
+                from: now,
+                to: now,
+                chat_title: Some(title.into()),
+                messages_seen: 120,
+                messages_deleted: 4,
+                users_verified: 3,
+                users_banned: 2,
+                captcha: CaptchaCounts::default(),
+                top_phrases: vec![TopPhrase {
+                    text: phrase.into(),
+                    hits: 5,
+                }],
+                last_7_days_messages: vec![],
+                rollup: None,
+            },
+            chart_webp: vec![],
+            version: "00000000deadbeef".into(),
+        }
+    }
+
+    #[test]
+    fn html_carries_opengraph_tags_with_absolute_urls() {
+        let html = render_html(
+            &report("Dart", "buy now"),
+            Some("https://vixen.example.org/"),
+        );
+        assert!(
+            html.contains(r#"<meta property="og:title" content="Dart — report for 2026-05-10">"#)
+        );
+        assert!(html.contains(
+            r#"<meta property="og:image" content="https://vixen.example.org/report/dart-community/chart.webp">"#
+        ));
+        assert!(html.contains(
+            r#"<meta property="og:url" content="https://vixen.example.org/report/dart-community">"#
+        ));
+        assert!(html.contains("Messages: 120 · Deleted: 4 · Verified: 3 · Banned: 2"));
+        assert!(html.contains("<li>buy now <small>×5</small></li>"));
+    }
+
+    #[test]
+    fn html_escapes_user_supplied_text() {
+        let html = render_html(&report("<b>\"Dart\"</b>", "<script>x</script>"), None);
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("&lt;b&gt;&quot;Dart&quot;&lt;/b&gt;"));
+        assert!(html.contains("&lt;script&gt;x&lt;/script&gt;"));
+        assert!(html.contains(r#"content="/report/dart-community/chart.webp""#));
+    }
+
+    #[test]
+    fn negotiate_prefers_html_for_browsers() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(negotiate(&headers), PublicFormat::Json);
+        headers.insert(
+            ACCEPT,
+            HeaderValue::from_static("text/html,application/xhtml+xml,*/*;q=0.8"),
+        );
+        assert_eq!(negotiate(&headers), PublicFormat::Html);
+    }
+
+    #[test]
+    fn if_none_match_handles_lists_and_weak_tags() {
+        let etag = "\"abc-json\"";
+        let mut headers = HeaderMap::new();
+        assert!(!not_modified(&headers, etag));
+        headers.insert(
+            IF_NONE_MATCH,
+            HeaderValue::from_static("\"x\", W/\"abc-json\""),
+        );
+        assert!(not_modified(&headers, etag));
+        headers.insert(IF_NONE_MATCH, HeaderValue::from_static("\"abc-html\""));
+        assert!(!not_modified(&headers, etag));
+        headers.insert(IF_NONE_MATCH, HeaderValue::from_static("*"));
+        assert!(not_modified(&headers, etag));
+    }
+}
@@ -0,0 +1,315 @@
+//! `/api/v1/chats/{chat_id}/reports/*` — ad-hoc reports over an arbitrary
+//! `[from, to)` window ("what happened during the raid last Tuesday").
+//!
+//! One request aggregates once via `ReportService::aggregate` and returns
+//! whichever outputs were asked for: the JSON `ReportData`, the MarkdownV2
+//! text, the WebP chart (base64) and/or a post into the chat. Posting needs
+//! [`Permission::PostReport`]; everything else [`Permission::ViewReports`].
+//! Unlike `/report`, a posted ad-hoc report is not recorded in
+//! `report_messages` — it never replaces the scheduled daily pair.
+//!
+//! Rate limited per (chat, moderator) with a fixed-window Redis counter:
+//! the chart render is CPU-bound and a post lands in the chat.
+
+use axum::Json;
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use teloxide::prelude::*;
+use teloxide::types::{ChatId, InputFile, ParseMode};
+use tracing::{error, info, warn};
+use utoipa::ToSchema;
+
+use crate::api::response::{ApiError, ApiResult};
+use crate::api::state::AppState;
+use crate::api::webapp_auth::DashboardContext;
+use crate::i18n;
+use crate::models::chat_moderator::Permission;
+use crate::models::report::ReportData;
+use crate::services::chart_service;
+use crate::services::outbound::{OutboundError, Priority};
+use crate::services::report_render::{self, HeaderKind};
+use crate::{api_error, api_success};
+
+/// Longest window one request may aggregate.
+pub const MAX_RANGE_DAYS: i64 = 366;
+
+/// Generate requests allowed per (chat, moderator) per [`RATE_WINDOW_SECS`].
+pub const RATE_LIMIT: i64 = 5;
+pub const RATE_WINDOW_SECS: i64 = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportOutput {
+    /// The aggregated `ReportData`.
+    Json,
+    /// The MarkdownV2 body `/report` would post.
+    Markdown,
+    /// The 480×270 WebP chart, base64-encoded.
+    Chart,
+    /// Post text + chart into the chat.
+    Post,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct GenerateReportRequest {
+    /// Inclusive lower bound (UTC).
+    pub from: DateTime<Utc>,
+    /// Exclusive upper bound (UTC).
+    pub to: DateTime<Utc>,
+    /// At least one; duplicates are ignored.
+    pub outputs: Vec<ReportOutput>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PostedReport {
+    pub text_message_id: i32,
+    pub photo_message_id: i32,
+}
+
+/// Only the requested outputs are set.
+#[derive(Serialize, ToSchema, Default)]
+pub struct GenerateReportResponse {
+    pub data: Option<ReportData>,
+    pub markdown: Option<String>,
+    /// Base64 (standard alphabet) lossless WebP.
+    pub chart_webp: Option<String>,
+    pub posted: Option<PostedReport>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/chats/{chat_id}/reports/generate",
+    params(("chat_id" = i64, Path, description = "Telegram chat id")),
+    request_body = GenerateReportRequest,
+    responses(
+        (status = 200, body = GenerateReportResponse, description = "Requested report outputs"),
+        (status = 400, body = ApiError, description = "Invalid range or no outputs"),
+        (status = 401, body = ApiError, description = "Missing or invalid token"),
+        (status = 403, body = ApiError, description = "No role or insufficient role in this chat"),
+        (status = 429, body = ApiError, description = "Too many generate requests"),
+        (status = 502, body = ApiError, description = "Posting into the chat failed"),
+    ),
+    security(("bearer" = [])),
+    tag = "reports"
+)]
+pub async fn generate(
+    State(state): State<AppState>,
+    ctx: DashboardContext,
+    Path(chat_id): Path<i64>,
+    body: Result<Json<GenerateReportRequest>, JsonRejection>,
+) -> ApiResult<GenerateReportResponse> {
+    let Ok(Json(req)) = body else {
+        return api_error!(
+            "VALIDATION_ERROR",
+            "expected a JSON report request",
+            StatusCode::BAD_REQUEST
+        );
+    };
+    if let Err(msg) = validate(&req) {
+        return api_error!("VALIDATION_ERROR", msg, StatusCode::BAD_REQUEST);
+    }
+    let wants = |o| req.outputs.contains(&o);
+    let permission = if wants(ReportOutput::Post) {
+        Permission::PostReport
+    } else {
+        Permission::ViewReports
+    };
+    if let Err(e) = ctx.require(&state, chat_id, permission).await {
+        return ApiResult::Error(e);
+    }
+    match check_rate_limit(&state, chat_id, ctx.user_id).await {
+        Ok(None) => {}
+        Ok(Some(retry_after)) => {
+            return api_error!(
+                "RATE_LIMITED",
+                format!("too many report requests; retry in {retry_after}s"),
+                StatusCode::TOO_MANY_REQUESTS
+            );
+        }
+        // Redis down: serve the request rather than locking moderators out.
+        Err(e) => warn!(error = ?e, chat_id, "report rate limit check failed"),
+    }
+
+    let report = match state.reports.aggregate(chat_id, req.from, req.to).await {
+        Ok(r) => r,
+        Err(e) => {
+            error!(error = ?e, chat_id, "ad-hoc report aggregate failed");
+            return api_error!("DATABASE_ERROR", "failed to aggregate report");
+        }
+    };
+    let mut out = GenerateReportResponse::default();
+
+    let markdown = if wants(ReportOutput::Markdown) || wants(ReportOutput::Post) {
+        let lang = i18n::chat_lang(state.db.pool(), chat_id).await;
+        Some(report_render::render(&report, lang, HeaderKind::OnDemand))
+    } else {
+        None
+    };
+    let chart = if wants(ReportOutput::Chart) || wants(ReportOutput::Post) {
+        let owned = report.clone();
+        match tokio::task::spawn_blocking(move || chart_service::render(&owned)).await {
+            Ok(Ok(bytes)) => Some(bytes),
+            Ok(Err(e)) => {
+                error!(error = ?e, chat_id, "ad-hoc report chart failed");
+                return api_error!("INTERNAL_ERROR", "failed to render chart");
+            }
+            Err(e) => {
+                error!(error = ?e, chat_id, "ad-hoc report chart task failed");
+                return api_error!("INTERNAL_ERROR", "failed to render chart");
+            }
+        }
+    } else {
+        None
+    };
+
+    if let (true, Some(text), Some(bytes)) =
+        (wants(ReportOutput::Post), markdown.clone(), chart.clone())
+    {
+        match post(&state, chat_id, text, bytes).await {
+            Ok(posted) => out.posted = Some(posted),
+            Err(e) => {
+                warn!(error = %e, chat_id, "ad-hoc report post failed");
+                return api_error!(
+                    "BOT_API_ERROR",
+                    "failed to post the report into the chat",
+                    StatusCode::BAD_GATEWAY
+                );
+            }
+        }
+    }
+    if wants(ReportOutput::Markdown) {
+        out.markdown = markdown;
+    }
+    if wants(ReportOutput::Chart) {
+        out.chart_webp = chart.map(|b| BASE64.encode(b));
+    }
+    if wants(ReportOutput::Json) {
+        out.data = Some(report);
+    }
+
+    info!(
+        chat_id,
+        moderator = ctx.user_id,
+        from = %req.from,
+        to = %req.to,
+        posted = out.posted.is_some(),
+        "ad-hoc report generated"
+    );
+    api_success!(out)
+}
+
+fn validate(req: &GenerateReportRequest) -> Result<(), String> {
+    if req.outputs.is_empty() {
+        return Err("outputs must name at least one of json, markdown, chart, post".into());
+    }
+    if req.from >= req.to {
+        return Err("from must be before to".into());
+    }
+    if req.to - req.from > Duration::days(MAX_RANGE_DAYS) {
+        return Err(format!("range is longer than {MAX_RANGE_DAYS} days"));
+    }
+    Ok(())
+}
+
+async fn post(
+    state: &AppState,
+    chat_id: i64,
+    text: String,
+    chart: Vec<u8>,
+) -> Result<PostedReport, OutboundError> {
+    let chat = ChatId(chat_id);
+    let request = state
+        .bot
+        .send_message(chat, text)
+        .parse_mode(ParseMode::MarkdownV2);
+    let text_msg = state
+        .outbound
+        .send(chat_id, Priority::Background, request)
+        .await?;
+    let photo = InputFile::memory(chart).file_name("report.webp");
+    let request = state.bot.send_photo(chat, photo);
+    let photo_msg = state
+        .outbound
+        .send(chat_id, Priority::Background, request)
+        .await?;
+    Ok(PostedReport {
+        text_message_id: text_msg.id.0,
+        photo_message_id: photo_msg.id.0,
+    })
+}
+
+/// `INCR`, plus `EXPIRE` on the first hit, in one script: run as two
+/// commands, a failure between them would leave a counter with no TTL that
+/// rate-limits the user forever.
+const RATE_LIMIT_SCRIPT: &str = r#"
+local hits = redis.call('INCR', KEYS[1])
+if hits == 1 then
+    redis.call('EXPIRE', KEYS[1], ARGV[1])
+end
+return hits
+"#;
+
+/// Fixed-window counter (see [`RATE_LIMIT_SCRIPT`]). `Some(secs)` until the
+/// window resets once [`RATE_LIMIT`] is exceeded.
+async fn check_rate_limit(
+    state: &AppState,
+    chat_id: i64,
+    user_id: i64,
+) -> anyhow::Result<Option<u64>> {
+    let key = format!("rl:report_generate:{chat_id}:{user_id}");
+    let mut incr = redis::cmd("EVAL");
+    incr.arg(RATE_LIMIT_SCRIPT)
+        .arg(1)
+        .arg(&key)
+        .arg(RATE_WINDOW_SECS);
+    let hits: i64 = state.redis.query(&incr).await?;
+    if hits <= RATE_LIMIT {
+        return Ok(None);
+    }
+    let ttl: i64 = state
+        .redis
+        .query(&redis::Cmd::ttl(&key))
+        .await
+        .unwrap_or(-1);
+    Ok(Some(ttl.max(1) as u64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(days: i64, outputs: Vec<ReportOutput>) -> GenerateReportRequest {
+        let to = Utc::now();
+        GenerateReportRequest {
+            from: to - Duration::days(days),
+            to,
+            outputs,
+        }
+    }
+
+    #[test]
+    fn validate_rejects_empty_outputs_and_bad_ranges() {
+        assert!(validate(&request(1, vec![ReportOutput::Json])).is_ok());
+        assert!(validate(&request(1, vec![])).is_err());
+        assert!(validate(&request(0, vec![ReportOutput::Json])).is_err());
+        assert!(validate(&request(-1, vec![ReportOutput::Json])).is_err());
+        assert!(validate(&request(MAX_RANGE_DAYS + 1, vec![ReportOutput::Json])).is_err());
+    }
+
+    #[test]
+    fn outputs_deserialize_snake_case() {
+        let req: GenerateReportRequest = serde_json::from_str(
+            r#"{"from":"2026-05-01T00:00:00Z","to":"2026-05-02T00:00:00Z","outputs":["json","chart","post"]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            req.outputs,
+            vec![ReportOutput::Json, ReportOutput::Chart, ReportOutput::Post]
+        );
+    }
+}
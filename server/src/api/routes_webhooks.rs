@@ -0,0 +1,223 @@
+//! `/api/v1/chats/{chat_id}/webhooks*` — the chat's outbound webhook
+//! endpoints (`services::webhooks`). Admin-only ([`Permission::EditConfig`]):
+//! an endpoint receives every ban, unban and verification in the chat.
+
+use axum::Json;
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::api::response::{ApiError, ApiResult};
+use crate::api::state::AppState;
+use crate::api::webapp_auth::DashboardContext;
+use crate::models::chat_moderator::Permission;
+use crate::services::webhooks::{self, CreateOutcome, CreatedEndpoint, WebhookEndpoint};
+use crate::{api_error, api_success};
+
+#[derive(Serialize, ToSchema)]
+pub struct WebhooksResponse {
+    /// Oldest first.
+    pub items: Vec<WebhookEndpoint>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct CreateWebhookRequest {
+    /// `http(s)://` URL that receives the signed POSTs.
+    #[schema(example = "https://crm.example.com/hooks/vixen")]
+    pub url: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct RedriveResponse {
+    /// Dead deliveries put back in the queue.
+    pub requeued: u64,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/chats/{chat_id}/webhooks",
+    params(("chat_id" = i64, Path, description = "Telegram chat id")),
+    responses(
+        (status = 200, body = WebhooksResponse, description = "Endpoints with their delivery backlog"),
+        (status = 401, body = ApiError, description = "Missing or invalid token"),
+        (status = 403, body = ApiError, description = "No role or insufficient role in this chat"),
+    ),
+    security(("bearer" = [])),
+    tag = "chats"
+)]
+pub async fn list(
+    State(state): State<AppState>,
+    ctx: DashboardContext,
+    Path(chat_id): Path<i64>,
+) -> ApiResult<WebhooksResponse> {
+    if let Err(e) = ctx.require(&state, chat_id, Permission::EditConfig).await {
+        return ApiResult::Error(e);
+    }
+    match webhooks::list_endpoints(state.db.pool(), chat_id).await {
+        Ok(items) => api_success!(WebhooksResponse { items }),
+        Err(e) => {
+            error!(error = ?e, chat_id, "list webhooks failed");
+            api_error!("DATABASE_ERROR", "failed to list webhooks")
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/chats/{chat_id}/webhooks",
+    params(("chat_id" = i64, Path, description = "Telegram chat id")),
+    request_body = CreateWebhookRequest,
+    responses(
+        (status = 201, body = CreatedEndpoint, description = "Endpoint created; the secret is only returned here"),
+        (status = 400, body = ApiError, description = "Invalid URL, or a private host without CONFIG_WEBHOOKS_ALLOW_PRIVATE"),
+        (status = 401, body = ApiError, description = "Missing or invalid token"),
+        (status = 403, body = ApiError, description = "No role or insufficient role in this chat"),
+        (status = 409, body = ApiError, description = "URL already registered, or endpoint limit reached"),
+        (status = 503, body = ApiError, description = "CONFIG_SECRETS_KEY not set"),
+    ),
+    security(("bearer" = [])),
+    tag = "chats"
+)]
+pub async fn create(
+    State(state): State<AppState>,
+    ctx: DashboardContext,
+    Path(chat_id): Path<i64>,
+    body: Result<Json<CreateWebhookRequest>, JsonRejection>,
+) -> ApiResult<CreatedEndpoint> {
+    let Ok(Json(req)) = body else {
+        return api_error!(
+            "VALIDATION_ERROR",
+            "expected a JSON webhook request",
+            StatusCode::BAD_REQUEST
+        );
+    };
+    let url = req.url.trim();
+    if let Err(reason) = webhooks::validate_url(url, state.config.webhooks_allow_private) {
+        return api_error!("VALIDATION_ERROR", reason, StatusCode::BAD_REQUEST);
+    }
+    if let Err(e) = ctx.require(&state, chat_id, Permission::EditConfig).await {
+        return ApiResult::Error(e);
+    }
+    if !state.api_keys.can_seal() {
+        return api_error!(
+            "SECRETS_KEY_UNSET",
+            "server has no CONFIG_SECRETS_KEY; webhook secrets cannot be stored",
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+    }
+    let pool = state.db.pool();
+    match webhooks::create_endpoint(pool, &state.api_keys, chat_id, url, ctx.user_id).await {
+        Ok(CreateOutcome::Created(created)) => {
+            info!(
+                chat_id,
+                user_id = ctx.user_id,
+                endpoint_id = %created.endpoint.id,
+                "webhook endpoint created"
+            );
+            api_success!(created, StatusCode::CREATED)
+        }
+        Ok(CreateOutcome::Duplicate) => api_error!(
+            "CONFLICT",
+            "this URL is already registered for the chat",
+            StatusCode::CONFLICT
+        ),
+        Ok(CreateOutcome::LimitReached) => api_error!(
+            "CONFLICT",
+            format!(
+                "a chat can have at most {} webhook endpoints",
+                webhooks::MAX_ENDPOINTS_PER_CHAT
+            ),
+            StatusCode::CONFLICT
+        ),
+        Err(e) => {
+            error!(error = ?e, chat_id, "create webhook failed");
+            api_error!("DATABASE_ERROR", "failed to create webhook")
+        }
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/chats/{chat_id}/webhooks/{endpoint_id}",
+    params(
+        ("chat_id" = i64, Path, description = "Telegram chat id"),
+        ("endpoint_id" = Uuid, Path, description = "Id returned by POST webhooks"),
+    ),
+    responses(
+        (status = 200, body = WebhooksResponse, description = "Endpoint removed; the remaining endpoints"),
+        (status = 401, body = ApiError, description = "Missing or invalid token"),
+        (status = 403, body = ApiError, description = "No role or insufficient role in this chat"),
+        (status = 404, body = ApiError, description = "No such endpoint in this chat"),
+    ),
+    security(("bearer" = [])),
+    tag = "chats"
+)]
+pub async fn delete(
+    State(state): State<AppState>,
+    ctx: DashboardContext,
+    Path((chat_id, endpoint_id)): Path<(i64, Uuid)>,
+) -> ApiResult<WebhooksResponse> {
+    if let Err(e) = ctx.require(&state, chat_id, Permission::EditConfig).await {
+        return ApiResult::Error(e);
+    }
+    let pool = state.db.pool();
+    match webhooks::delete_endpoint(pool, chat_id, endpoint_id).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return api_error!("NOT_FOUND", "no such webhook", StatusCode::NOT_FOUND);
+        }
+        Err(e) => {
+            error!(error = ?e, chat_id, "delete webhook failed");
+            return api_error!("DATABASE_ERROR", "failed to delete webhook");
+        }
+    }
+    info!(chat_id, user_id = ctx.user_id, endpoint_id = %endpoint_id, "webhook endpoint deleted");
+    match webhooks::list_endpoints(pool, chat_id).await {
+        Ok(items) => api_success!(WebhooksResponse { items }),
+        Err(e) => {
+            error!(error = ?e, chat_id, "list webhooks failed");
+            api_error!("DATABASE_ERROR", "failed to list webhooks")
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/chats/{chat_id}/webhooks/{endpoint_id}/redrive",
+    params(
+        ("chat_id" = i64, Path, description = "Telegram chat id"),
+        ("endpoint_id" = Uuid, Path, description = "Id returned by POST webhooks"),
+    ),
+    responses(
+        (status = 200, body = RedriveResponse, description = "Dead deliveries requeued"),
+        (status = 401, body = ApiError, description = "Missing or invalid token"),
+        (status = 403, body = ApiError, description = "No role or insufficient role in this chat"),
+        (status = 404, body = ApiError, description = "No such endpoint in this chat"),
+    ),
+    security(("bearer" = [])),
+    tag = "chats"
+)]
+pub async fn redrive(
+    State(state): State<AppState>,
+    ctx: DashboardContext,
+    Path((chat_id, endpoint_id)): Path<(i64, Uuid)>,
+) -> ApiResult<RedriveResponse> {
+    if let Err(e) = ctx.require(&state, chat_id, Permission::EditConfig).await {
+        return ApiResult::Error(e);
+    }
+    match webhooks::redrive(state.db.pool(), chat_id, endpoint_id).await {
+        Ok(Some(requeued)) => {
+            info!(chat_id, endpoint_id = %endpoint_id, requeued, "webhook deliveries redriven");
+            api_success!(RedriveResponse { requeued })
+        }
+        Ok(None) => api_error!("NOT_FOUND", "no such webhook", StatusCode::NOT_FOUND),
+        Err(e) => {
+            error!(error = ?e, chat_id, "redrive webhook failed");
+            api_error!("DATABASE_ERROR", "failed to redrive webhook")
+        }
+    }
+}
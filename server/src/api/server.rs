@@ -1,15 +1,20 @@
-//! HTTP router builder. Assembles `/health`, `/about`, the OpenAPI JSON spec
-//! and (optionally) the Scalar UI behind a CORS + request-id + tracing
-//! middleware stack.
+//! HTTP router builder. Assembles `/health`, `/about`, `/metrics`, the
+//! dashboard routes (`/api/v1/auth/*`, `/api/v1/chats/*`), the public
+//! `/report/{slug}` page, the OpenAPI JSON spec and (optionally) the Scalar
+//! UI behind a CORS + request-id + tracing + metrics middleware stack.
 
 use axum::Router;
+use axum::body::Body;
 use axum::http::header::{AUTHORIZATION, CONTENT_TYPE};
-use axum::http::{HeaderName, HeaderValue, Method};
+use axum::http::{HeaderName, HeaderValue, Method, Request};
 use axum::response::Html;
 use tower_http::cors::CorsLayer;
 use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
 use tower_http::trace::TraceLayer;
-use utoipa::OpenApi;
+use tracing::Span;
+use tracing::field::Empty;
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
 use utoipa_axum::router::OpenApiRouter;
 use utoipa_axum::routes;
 use utoipa_scalar::Scalar;
@@ -17,7 +22,11 @@ use utoipa_scalar::Scalar;
 use crate::api::routes_about::AboutResponse;
 use crate::api::routes_health::{HealthChecks, HealthResponse};
 use crate::api::state::AppState;
-use crate::api::{routes_about, routes_health};
+use crate::api::{
+    routes_about, routes_actions, routes_auth, routes_chats, routes_events, routes_health,
+    routes_metrics, routes_moderation, routes_public, routes_reports, routes_webhooks,
+};
+use crate::telemetry::otel;
 
 /// Top-level OpenAPI document. Schemas are picked up automatically via
 /// `utoipa-axum::routes!` ↦ `OpenApiRouter::routes`.
@@ -28,10 +37,35 @@ use crate::api::{routes_about, routes_health};
         description = "Telegram anti-spam bot — operational + dashboard API.",
     ),
     components(schemas(HealthResponse, HealthChecks, AboutResponse)),
-    tags((name = "ops", description = "Health + build metadata"))
+    modifiers(&BearerAuth),
+    tags(
+        (name = "ops", description = "Health, build metadata + Prometheus metrics"),
+        (name = "auth", description = "Dashboard sign-in via Telegram initData"),
+        (name = "chats", description = "Chat-scoped dashboard routes (role-gated)"),
+        (name = "reports", description = "Ad-hoc chat reports (role-gated)"),
+        (name = "public", description = "Redacted public chat reports (no auth)"),
+    )
 )]
 struct ApiDoc;
 
+/// Registers the `bearer` security scheme referenced by dashboard routes.
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .build(),
+            ),
+        );
+    }
+}
+
 const REQUEST_ID_HEADER: &str = "x-request-id";
 
 /// Build the application router with state, routes and middleware.
@@ -43,6 +77,27 @@ pub fn build_router(state: AppState) -> Router {
     let (api_router, mut openapi) = OpenApiRouter::with_openapi(ApiDoc::openapi())
         .routes(routes!(routes_health::health))
         .routes(routes!(routes_about::about))
+        .routes(routes!(routes_metrics::metrics))
+        .routes(routes!(routes_auth::login))
+        .routes(routes!(routes_auth::me))
+        .routes(routes!(routes_chats::moderators))
+        .routes(routes!(routes_actions::actions))
+        .routes(routes!(routes_moderation::verified))
+        .routes(routes!(routes_moderation::banned))
+        .routes(routes!(routes_moderation::ban))
+        .routes(routes!(routes_moderation::unban))
+        .routes(routes!(routes_moderation::verify))
+        .routes(routes!(routes_moderation::unverify))
+        .routes(routes!(routes_chats::bulk_undo))
+        .routes(routes!(routes_chats::bulk_operation))
+        .routes(routes!(routes_chats::api_key, routes_chats::set_api_key))
+        .routes(routes!(routes_events::events))
+        .routes(routes!(routes_webhooks::list, routes_webhooks::create))
+        .routes(routes!(routes_webhooks::delete))
+        .routes(routes!(routes_webhooks::redrive))
+        .routes(routes!(routes_reports::generate))
+        .routes(routes!(routes_public::report))
+        .routes(routes!(routes_public::chart))
         .split_for_parts();
 
     // Pin a stable version label on the spec so dashboards can detect it.
@@ -76,12 +131,36 @@ pub fn build_router(state: AppState) -> Router {
     let request_id = HeaderName::from_static(REQUEST_ID_HEADER);
     let cors = build_cors(&cors_origins);
 
-    app.layer(SetRequestIdLayer::new(request_id.clone(), MakeRequestUuid))
-        .layer(PropagateRequestIdLayer::new(request_id))
-        .layer(TraceLayer::new_for_http())
+    // `route_layer` runs after routing, so the middleware sees `MatchedPath`
+    // and labels by route template; unmatched 404s are not counted.
+    // Layers run outermost-last: the request id is set before `TraceLayer`
+    // opens the request span, so the span can carry it.
+    app.route_layer(axum::middleware::from_fn(routes_metrics::track))
+        .layer(TraceLayer::new_for_http().make_span_with(request_span))
+        .layer(PropagateRequestIdLayer::new(request_id.clone()))
+        .layer(SetRequestIdLayer::new(request_id, MakeRequestUuid))
         .layer(cors)
 }
 
+/// Root span for one HTTP request, tagged with the `x-request-id` set by
+/// `SetRequestIdLayer` and, when OTLP export is on, the trace id.
+fn request_span(req: &Request<Body>) -> Span {
+    let request_id = req
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    let span = tracing::info_span!(
+        "request",
+        method = %req.method(),
+        uri = %req.uri().path(),
+        request_id,
+        trace_id = Empty,
+    );
+    otel::record_trace_id(&span);
+    span
+}
+
 fn build_cors(origins: &[String]) -> CorsLayer {
     let layer = CorsLayer::new()
         .allow_methods([
@@ -91,7 +170,13 @@ fn build_cors(origins: &[String]) -> CorsLayer {
             Method::DELETE,
             Method::OPTIONS,
         ])
-        .allow_headers([CONTENT_TYPE, AUTHORIZATION]);
+        // `Last-Event-ID`: the dashboard resumes `/chats/{id}/events` itself
+        // (fetch-based SSE, since `EventSource` can't send the bearer token).
+        .allow_headers([
+            CONTENT_TYPE,
+            AUTHORIZATION,
+            HeaderName::from_static("last-event-id"),
+        ]);
 
     let parsed: Vec<HeaderValue> = origins
         .iter()
@@ -3,13 +3,20 @@
 
 use std::sync::Arc;
 
+use teloxide::Bot;
+
 use crate::config::Config;
 use crate::database::{Database, Redis};
+use crate::services::api_keys::ApiKeyCipher;
 use crate::services::captcha::{CaptchaService, CaptchaState};
+use crate::services::live_feed::LiveFeed;
 use crate::services::moderation_service::ModerationService;
+use crate::services::outbound::OutboundQueue;
+use crate::services::public_report::PublicReportService;
 use crate::services::report_service::ReportService;
 use crate::services::spam::service::SpamService;
 use crate::services::summary_service::SummaryService;
+use crate::services::user_info::UserInfoCache;
 
 #[derive(Clone)]
 pub struct AppState {
@@ -34,4 +41,21 @@ pub struct AppState {
     /// M3 AI summary: per-chat OpenAI key resolved at call time, daily
     /// token budget enforced via `daily_stats('openai_tokens_used')`.
     pub summary: Arc<SummaryService>,
+    /// Seals / opens `chat_config.openai_api_key` under `CONFIG_SECRETS_KEY`.
+    pub api_keys: Arc<ApiKeyCipher>,
+    /// Redacted, cached `/report/{slug}` builds for opted-in chats.
+    pub public_reports: Arc<PublicReportService>,
+    /// Bot API handle for HTTP routes that post into a chat (ad-hoc
+    /// reports). Telegram handlers and jobs get their own clone.
+    pub bot: Bot,
+    /// Rate-limit-aware scheduler every chat-facing Bot API call goes
+    /// through: per-chat and global buckets, `retry_after`, priorities.
+    pub outbound: Arc<OutboundQueue>,
+    /// Live activity (captchas, spam verdicts, moderation actions) behind
+    /// `GET /chats/{chat_id}/events`, fanned out across replicas through
+    /// Redis pub/sub.
+    pub live: LiveFeed,
+    /// Last-seen Telegram profiles (`user_info_cache`): fed by the message
+    /// gate and joins, read by the dashboard's user lists.
+    pub user_info: Arc<UserInfoCache>,
 }
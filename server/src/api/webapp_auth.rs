@@ -0,0 +1,118 @@
+//! Dashboard auth extractor. `DashboardContext` decodes the
+//! `Authorization: Bearer <jwt>` header minted by `POST /auth/telegram/login`
+//! and is taken as a handler argument on every dashboard route; a missing or
+//! invalid token rejects with a 401 envelope before the handler runs.
+//!
+//! Chat-scoped routes then call [`DashboardContext::require`] with the
+//! [`Permission`] they need. The JWT's `chat_ids` is only a UI hint, so
+//! `require` re-reads the live role from `chat_moderators` (through the
+//! `ModerationService` cache) — a revoke takes effect without waiting for the
+//! token to expire.
+
+use axum::async_trait;
+use axum::extract::FromRequestParts;
+use axum::http::StatusCode;
+use axum::http::header::AUTHORIZATION;
+use axum::http::request::Parts;
+use tracing::{error, warn};
+
+use crate::api::response::ApiError;
+use crate::api::state::AppState;
+use crate::models::chat_moderator::{ModeratorRole, Permission};
+use crate::services::auth_service::{self, TgIdentity};
+
+/// Authenticated dashboard caller.
+#[derive(Debug, Clone)]
+pub struct DashboardContext {
+    pub user_id: i64,
+    pub chat_ids: Vec<i64>,
+    pub tg: TgIdentity,
+}
+
+impl DashboardContext {
+    /// Gate a chat-scoped endpoint. Returns the caller's role in `chat_id`
+    /// when it grants `permission`; otherwise a ready-to-return 403
+    /// (`MODERATOR_REQUIRED` without a role, `FORBIDDEN` with an
+    /// insufficient one).
+    pub async fn require(
+        &self,
+        state: &AppState,
+        chat_id: i64,
+        permission: Permission,
+    ) -> Result<ModeratorRole, ApiError> {
+        if !self.chat_ids.contains(&chat_id) {
+            return Err(moderator_required());
+        }
+        let role = match state.moderation.role(chat_id, self.user_id).await {
+            Ok(Some(role)) => role,
+            Ok(None) => return Err(moderator_required()),
+            Err(e) => {
+                error!(error = ?e, chat_id, "moderation.role failed");
+                return Err(ApiError {
+                    code: "DATABASE_ERROR".into(),
+                    message: "failed to resolve moderator role".into(),
+                    status: StatusCode::INTERNAL_SERVER_ERROR,
+                });
+            }
+        };
+        if !role.allows(permission) {
+            warn!(
+                chat_id,
+                user_id = self.user_id,
+                ?role,
+                ?permission,
+                "permission denied"
+            );
+            return Err(ApiError {
+                code: "FORBIDDEN".into(),
+                message: format!("role '{}' lacks this permission", role.as_db_str()),
+                status: StatusCode::FORBIDDEN,
+            });
+        }
+        Ok(role)
+    }
+}
+
+fn moderator_required() -> ApiError {
+    ApiError {
+        code: "MODERATOR_REQUIRED".into(),
+        message: "not a moderator of this chat".into(),
+        status: StatusCode::FORBIDDEN,
+    }
+}
+
+fn unauthorized(code: &str, message: &str) -> ApiError {
+    ApiError {
+        code: code.into(),
+        message: message.into(),
+        status: StatusCode::UNAUTHORIZED,
+    }
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for DashboardContext {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, ApiError> {
+        let Some(secret) = state.config.jwt_secret.as_ref() else {
+            // Dev without CONFIG_JWT_SECRET: no token can ever be valid.
+            return Err(unauthorized(
+                "UNAUTHORIZED",
+                "dashboard auth is not configured",
+            ));
+        };
+        let token = parts
+            .headers
+            .get(AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .ok_or_else(|| unauthorized("UNAUTHORIZED", "missing bearer token"))?;
+        let claims = auth_service::decode(token, secret.expose())
+            .map_err(|_| unauthorized("INVALID_TOKEN", "invalid or expired token"))?;
+        Ok(Self {
+            user_id: claims.sub,
+            chat_ids: claims.chat_ids,
+            tg: claims.tg,
+        })
+    }
+}
@@ -0,0 +1,583 @@
+//! Command line: the server's [`Config`] flags plus one-shot maintenance
+//! subcommands. No subcommand = run the server.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::{Parser, Subcommand};
+use teloxide::Bot;
+use tokio_util::sync::CancellationToken;
+use tracing::warn;
+
+use crate::config::Config;
+use crate::database::{Database, Redis, ensure_watched_chats};
+use crate::i18n::Lang;
+use crate::jobs::{daily_report, spam_cleanup};
+use crate::models::chat_config;
+use crate::models::moderation_action::ActorKind;
+use crate::services::api_keys::{self, ApiKeyCipher};
+use crate::services::captcha::{CaptchaService, CaptchaState, Fonts, Outcome as CaptchaOutcome};
+use crate::services::live_feed::LiveFeed;
+use crate::services::moderation_service::{Action, ApplyContext, ModerationService, Outcome};
+use crate::services::outbound::{Limits, OutboundQueue};
+use crate::services::report_render::{self, HeaderKind};
+use crate::services::report_service::{self, ReportService};
+use crate::services::{chart_service, sqlite_import};
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "vixen-server",
+    version,
+    about = "Telegram anti-spam bot — captcha gating, spam pipeline, daily reports.",
+    long_about = "Single-tenant Telegram anti-spam service. \
+                  Watches a fixed set of chats configured via CONFIG_CHATS; \
+                  per-chat tunables live in the database, not here."
+)]
+pub struct Cli {
+    #[command(flatten)]
+    pub config: Config,
+
+    #[command(subcommand)]
+    pub command: Option<AdminCommand>,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum AdminCommand {
+    /// Re-seal every `chat_config.openai_api_key` under `CONFIG_SECRETS_KEY`:
+    /// re-wraps values sealed with `CONFIG_SECRETS_KEY_PREVIOUS` and
+    /// encrypts legacy plaintext. All-or-nothing.
+    RotateSecretsKey,
+
+    /// Import `verified_users` and the spam dictionary from the Dart
+    /// prototype's SQLite file. Idempotent; existing rows are left alone.
+    MigrateFromSqlite {
+        /// Path to the prototype's database file (e.g. `vixen.db`).
+        path: PathBuf,
+        /// Print what would be inserted without writing anything.
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Apply pending migrations and exit.
+    Migrate,
+
+    /// Mark a user verified in a chat, as `/verify` does.
+    Verify {
+        #[arg(allow_negative_numbers = true)]
+        chat_id: i64,
+        user_id: i64,
+        /// Telegram user id recorded as the moderator in the ledger.
+        #[arg(long)]
+        actor: Option<i64>,
+    },
+
+    /// Ban a user from a chat, as `/ban <user_id>` does.
+    Ban {
+        #[arg(allow_negative_numbers = true)]
+        chat_id: i64,
+        user_id: i64,
+        #[arg(long)]
+        reason: Option<String>,
+        /// End of the ban (RFC 3339); permanent when omitted.
+        #[arg(long)]
+        until: Option<DateTime<Utc>>,
+        /// Telegram user id recorded as the moderator in the ledger.
+        #[arg(long)]
+        actor: Option<i64>,
+    },
+
+    /// Lift a ban, as `/unban` does.
+    Unban {
+        #[arg(allow_negative_numbers = true)]
+        chat_id: i64,
+        user_id: i64,
+        /// Telegram user id recorded as the moderator in the ledger.
+        #[arg(long)]
+        actor: Option<i64>,
+    },
+
+    /// Print a chat's daily report (the MarkdownV2 body the bot would post)
+    /// without posting it. No AI summary.
+    Report {
+        #[arg(allow_negative_numbers = true)]
+        chat_id: i64,
+        /// Chat-local day; today in the chat's timezone when omitted.
+        #[arg(long)]
+        date: Option<NaiveDate>,
+        /// Also write the report chart (WebP) to this path.
+        #[arg(long)]
+        chart: Option<PathBuf>,
+    },
+
+    /// Run one `spam_cleanup` pass now: drop spam dictionary rows and cached
+    /// summary chunks older than `CONFIG_SPAM_RETENTION_DAYS`.
+    PruneSpam,
+
+    /// Read or write one per-chat `chat_config` field.
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+
+    /// Print the effective configuration (secrets redacted) and probe
+    /// Postgres and Redis. Does not migrate. Exits non-zero when a probe
+    /// fails.
+    CheckConfig,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum ConfigCommand {
+    /// Print a field's value (`openai_api_key` is masked).
+    Get {
+        #[arg(allow_negative_numbers = true)]
+        chat_id: i64,
+        key: String,
+    },
+    /// Set a field. The value is cast by Postgres to the column type, so
+    /// the table's constraints apply; `openai_api_key` is sealed first.
+    Set {
+        #[arg(allow_negative_numbers = true)]
+        chat_id: i64,
+        key: String,
+        #[arg(required_unless_present = "unset")]
+        value: Option<String>,
+        /// Set the field to NULL instead.
+        #[arg(long, conflicts_with = "value")]
+        unset: bool,
+    },
+}
+
+impl AdminCommand {
+    pub async fn run(&self, config: &Config) -> Result<()> {
+        // Reports on the backends itself, so it must not fail on connect
+        // and must not change the schema.
+        if let Self::CheckConfig = self {
+            return check_config(config).await;
+        }
+        let db = Database::connect(config)
+            .await
+            .context("postgres connect")?;
+        // Same as server startup: a fresh database (the M7 cutover) gets
+        // its schema before anything is written to it. A dry run writes
+        // nothing, schema included, so it refuses a schema that is behind.
+        if let Self::MigrateFromSqlite { dry_run: true, .. } = self {
+            let pending = db
+                .pending_migrations()
+                .await
+                .context("read applied migrations")?;
+            if !pending.is_empty() {
+                bail!(
+                    "schema is {} migration(s) behind; run `server migrate` first",
+                    pending.len()
+                );
+            }
+        } else {
+            db.migrate().await.context("apply pending migrations")?;
+        }
+        let result = match self {
+            Self::RotateSecretsKey => rotate_secrets_key(&db, config).await,
+            Self::MigrateFromSqlite { path, dry_run } => {
+                migrate_from_sqlite(&db, config, path, *dry_run).await
+            }
+            Self::Migrate => {
+                println!("migrate: schema is up to date");
+                Ok(())
+            }
+            Self::Verify {
+                chat_id,
+                user_id,
+                actor,
+            } => verify(&db, config, *chat_id, *user_id, *actor).await,
+            Self::Ban {
+                chat_id,
+                user_id,
+                reason,
+                until,
+                actor,
+            } => {
+                let action = Action::Ban {
+                    reason: reason
+                        .clone()
+                        .unwrap_or_else(|| "manual ban (cli)".to_string()),
+                    until: *until,
+                };
+                moderate(&db, config, action, *chat_id, *user_id, *actor).await
+            }
+            Self::Unban {
+                chat_id,
+                user_id,
+                actor,
+            } => moderate(&db, config, Action::Unban, *chat_id, *user_id, *actor).await,
+            Self::Report {
+                chat_id,
+                date,
+                chart,
+            } => report(&db, config, *chat_id, *date, chart.as_deref()).await,
+            Self::PruneSpam => prune_spam(&db, config).await,
+            Self::Config { command } => command.run(&db, config).await,
+            Self::CheckConfig => unreachable!("handled before connecting"),
+        };
+        db.close().await;
+        result
+    }
+}
+
+impl ConfigCommand {
+    async fn run(&self, db: &Database, config: &Config) -> Result<()> {
+        match self {
+            Self::Get { chat_id, key } => {
+                ensure_watched(config, *chat_id)?;
+                let value = if key == chat_config::SEALED_FIELD {
+                    let cipher = ApiKeyCipher::from_config(config)?;
+                    match api_keys::load(db.pool(), *chat_id).await? {
+                        Some(Some(stored)) => {
+                            Some(Some(api_keys::mask(&cipher.open(*chat_id, &stored)?)))
+                        }
+                        other => other,
+                    }
+                } else {
+                    chat_config::get(db.pool(), *chat_id, key).await?
+                };
+                match value {
+                    None => bail!("chat {chat_id} has no chat_config row"),
+                    Some(None) => println!("{key} = NULL"),
+                    Some(Some(v)) => println!("{key} = {v}"),
+                }
+                Ok(())
+            }
+            Self::Set {
+                chat_id,
+                key,
+                value,
+                ..
+            } => {
+                ensure_watched(config, *chat_id)?;
+                let value = value.as_deref();
+                let found = if key == chat_config::SEALED_FIELD {
+                    let cipher = ApiKeyCipher::from_config(config)?;
+                    let sealed = value.map(|v| cipher.seal(*chat_id, v)).transpose()?;
+                    api_keys::store(db.pool(), *chat_id, sealed.as_deref()).await?
+                } else {
+                    chat_config::set(db.pool(), *chat_id, key, value).await?
+                };
+                if !found {
+                    bail!("chat {chat_id} has no chat_config row");
+                }
+                println!("{key} updated for chat {chat_id}");
+                publish_config_change(config, *chat_id, key).await;
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Per-chat commands only act on chats this deployment watches.
+fn ensure_watched(config: &Config, chat_id: i64) -> Result<()> {
+    if !config.chats.contains(&chat_id) {
+        bail!("chat {chat_id} is not in CONFIG_CHATS");
+    }
+    Ok(())
+}
+
+/// Best-effort Redis for side caches; the command's Postgres write is the
+/// source of truth, so an unreachable Redis only costs a warning.
+async fn try_redis(config: &Config) -> Option<Redis> {
+    match Redis::connect(config.redis_url.clone()).await {
+        Ok(redis) => Some(redis),
+        Err(e) => {
+            warn!(error = %e, "redis unavailable; skipping cache update");
+            None
+        }
+    }
+}
+
+/// The `chat_config:{chat_id}` invalidation the server subscribes to.
+async fn publish_config_change(config: &Config, chat_id: i64, key: &str) {
+    let Some(redis) = try_redis(config).await else {
+        return;
+    };
+    if let Err(e) = redis.publish(&format!("chat_config:{chat_id}"), key).await {
+        warn!(error = %e, "chat_config invalidation publish failed");
+    }
+}
+
+async fn verify(
+    db: &Database,
+    config: &Config,
+    chat_id: i64,
+    user_id: i64,
+    actor: Option<i64>,
+) -> Result<()> {
+    ensure_watched(config, chat_id)?;
+    let fonts = Fonts::load().context("load captcha fonts")?;
+    let captcha = CaptchaService::new(db.pool().clone(), fonts);
+    let (outcome, _) = captcha.verify_manual(chat_id, user_id, actor).await?;
+    if let Some(redis) = try_redis(config).await {
+        let state = CaptchaState::new(Arc::new(redis));
+        if let Err(e) = state.mark_verified(chat_id, user_id).await {
+            warn!(error = ?e, "redis mark_verified failed");
+        }
+    }
+    match outcome {
+        CaptchaOutcome::AlreadyVerified => {
+            println!("verify: user {user_id} was already verified in chat {chat_id}")
+        }
+        _ => println!("verify: user {user_id} verified in chat {chat_id}"),
+    }
+    Ok(())
+}
+
+async fn moderate(
+    db: &Database,
+    config: &Config,
+    action: Action,
+    chat_id: i64,
+    user_id: i64,
+    actor: Option<i64>,
+) -> Result<()> {
+    ensure_watched(config, chat_id)?;
+    let verb = match action {
+        Action::Ban { .. } => "ban",
+        Action::Unban => "unban",
+        Action::Delete { .. } => "delete",
+    };
+    let bot = Bot::new(config.bot_token.expose());
+    // Running servers put the action on their dashboards' live feed.
+    let live = match try_redis(config).await {
+        Some(redis) => LiveFeed::new(Arc::new(redis), CancellationToken::new()),
+        None => LiveFeed::disabled(),
+    };
+    let moderation = ModerationService::new(
+        db.pool().clone(),
+        bot,
+        OutboundQueue::new(Limits::default()),
+        live,
+    );
+    let ctx = ApplyContext {
+        chat_id,
+        target_user_id: user_id,
+        message_id: None,
+        actor_kind: ActorKind::Moderator,
+        actor_user_id: actor,
+    };
+    match moderation.apply(action, ctx).await? {
+        Outcome::Applied(_) => println!("{verb}: applied to user {user_id} in chat {chat_id}"),
+        Outcome::AlreadyApplied => {
+            println!("{verb}: already in effect for user {user_id} in chat {chat_id}")
+        }
+    }
+    Ok(())
+}
+
+async fn report(
+    db: &Database,
+    config: &Config,
+    chat_id: i64,
+    date: Option<NaiveDate>,
+    chart: Option<&Path>,
+) -> Result<()> {
+    ensure_watched(config, chat_id)?;
+    let (today, tz) = daily_report::current_report_date_with_tz(db.pool(), chat_id).await?;
+    let (from, to) = report_service::day_window_local(date.unwrap_or(today), tz);
+    let language = chat_config::get(db.pool(), chat_id, "language")
+        .await?
+        .flatten()
+        .with_context(|| format!("chat {chat_id} has no chat_config row"))?;
+    let data = ReportService::new(db.pool().clone())
+        .aggregate(chat_id, from, to)
+        .await?;
+    println!(
+        "{}",
+        report_render::render(&data, Lang::from_db_str(&language), HeaderKind::OnDemand)
+    );
+    if let Some(path) = chart {
+        let bytes = tokio::task::spawn_blocking(move || chart_service::render(&data))
+            .await
+            .context("chart spawn_blocking join")??;
+        std::fs::write(path, bytes).with_context(|| format!("write {}", path.display()))?;
+    }
+    Ok(())
+}
+
+async fn prune_spam(db: &Database, config: &Config) -> Result<()> {
+    let retention_days =
+        i32::try_from(config.spam_retention_days).context("spam_retention_days exceeds i32")?;
+    let spam = spam_cleanup::prune_expired(db.pool(), retention_days).await?;
+    let chunks = spam_cleanup::prune_summary_chunks(db.pool(), retention_days).await?;
+    println!(
+        "prune-spam: {spam} spam_messages and {chunks} summary_chunks rows older than {retention_days} days removed"
+    );
+    Ok(())
+}
+
+async fn check_config(config: &Config) -> Result<()> {
+    let set = |present: bool| if present { "set" } else { "unset" };
+    println!("environment        {}", config.environment);
+    println!("address            {}", config.address);
+    println!("telegram_mode      {}", config.telegram_mode);
+    println!("chats              {:?}", config.chats);
+    println!("database_url       {}", redact_url(&config.database_url));
+    println!("redis_url          {}", redact_url(&config.redis_url));
+    println!("bot_token          {}", config.bot_token);
+    println!("jwt_secret         {}", set(config.jwt_secret.is_some()));
+    println!("admin_secret       {}", set(config.admin_secret.is_some()));
+    println!("secrets_key        {}", set(config.secrets_key.is_some()));
+    println!("spam_retention     {} days", config.spam_retention_days);
+    println!("openai_base_url    {}", config.openai_base_url);
+    println!("cas_base_url       {}", config.cas_base_url);
+    println!(
+        "public_url         {}",
+        config.public_url.as_deref().unwrap_or("unset")
+    );
+    println!(
+        "otlp_endpoint      {}",
+        config.otlp_endpoint.as_deref().unwrap_or("unset")
+    );
+
+    let mut failures = Vec::new();
+    if let Err(e) = ApiKeyCipher::from_config(config) {
+        failures.push(format!("secrets key: {e:#}"));
+    }
+    match Database::connect(config).await {
+        Ok(db) => {
+            if let Err(e) = db.health_check().await {
+                failures.push(format!("postgres: {e}"));
+            }
+            db.close().await;
+        }
+        Err(e) => failures.push(format!("postgres: {e}")),
+    }
+    if let Err(e) = Redis::connect(config.redis_url.clone()).await {
+        failures.push(format!("redis: {e}"));
+    }
+
+    if failures.is_empty() {
+        println!("check-config: ok");
+        return Ok(());
+    }
+    for failure in &failures {
+        eprintln!("check-config: {failure}");
+    }
+    bail!("{} check(s) failed", failures.len())
+}
+
+/// Connection URL with the password replaced; unparseable URLs are hidden
+/// entirely rather than risk printing a credential.
+fn redact_url(raw: &str) -> String {
+    match url::Url::parse(raw) {
+        Ok(mut url) => {
+            if url.password().is_some() {
+                let _ = url.set_password(Some("***"));
+            }
+            url.to_string()
+        }
+        Err(_) => "***unparseable***".to_string(),
+    }
+}
+
+async fn rotate_secrets_key(db: &Database, config: &Config) -> Result<()> {
+    let cipher = ApiKeyCipher::from_config(config)?;
+    anyhow::ensure!(cipher.can_seal(), "CONFIG_SECRETS_KEY is not set");
+    let report = api_keys::rotate(db.pool(), &cipher).await?;
+    println!(
+        "rotate-secrets-key: {} re-wrapped, {} encrypted from plaintext, {} already current",
+        report.rewrapped, report.encrypted, report.unchanged
+    );
+    Ok(())
+}
+
+async fn migrate_from_sqlite(
+    db: &Database,
+    config: &Config,
+    path: &Path,
+    dry_run: bool,
+) -> Result<()> {
+    let owned = path.to_path_buf();
+    let legacy = tokio::task::spawn_blocking(move || sqlite_import::read(&owned))
+        .await
+        .context("sqlite read join")??;
+    let plan = sqlite_import::plan(
+        &legacy,
+        &config.chats,
+        config.spam_retention_days,
+        chrono::Utc::now(),
+    );
+    if !dry_run {
+        // Imported verifications reference `chats(chat_id)`.
+        ensure_watched_chats(db.pool(), &config.chats)
+            .await
+            .context("seed watched chats")?;
+    }
+    let report = sqlite_import::apply(db.pool(), &plan, dry_run).await?;
+    println!("migrate-from-sqlite ({}):", path.display());
+    println!("{report}");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Parser, Debug)]
+    struct Harness {
+        #[command(subcommand)]
+        command: AdminCommand,
+    }
+
+    fn parse(args: &[&str]) -> Result<AdminCommand, clap::Error> {
+        Harness::try_parse_from(std::iter::once("server").chain(args.iter().copied()))
+            .map(|h| h.command)
+    }
+
+    #[test]
+    fn ban_accepts_negative_chat_id() {
+        let cmd = parse(&["ban", "-1001234567890", "42", "--reason", "spam"]).unwrap();
+        match cmd {
+            AdminCommand::Ban {
+                chat_id,
+                user_id,
+                reason,
+                until,
+                actor,
+            } => {
+                assert_eq!(chat_id, -1001234567890);
+                assert_eq!(user_id, 42);
+                assert_eq!(reason.as_deref(), Some("spam"));
+                assert!(until.is_none() && actor.is_none());
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+    }
+
+    #[test]
+    fn report_parses_date() {
+        let cmd = parse(&["report", "-1001", "--date", "2026-05-01"]).unwrap();
+        match cmd {
+            AdminCommand::Report { chat_id, date, .. } => {
+                assert_eq!(chat_id, -1001);
+                assert_eq!(date, NaiveDate::from_ymd_opt(2026, 5, 1));
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+    }
+
+    #[test]
+    fn config_set_needs_exactly_one_of_value_and_unset() {
+        assert!(parse(&["config", "set", "-1001", "report_hour", "9"]).is_ok());
+        assert!(parse(&["config", "set", "-1001", "log_chat_id", "--unset"]).is_ok());
+        assert!(parse(&["config", "set", "-1001", "report_hour"]).is_err());
+        assert!(parse(&["config", "set", "-1001", "report_hour", "9", "--unset"]).is_err());
+    }
+
+    #[test]
+    fn redact_url_hides_password_only() {
+        assert_eq!(
+            redact_url("postgres://vixen:hunter2@db:5432/vixen"),
+            "postgres://vixen:***@db:5432/vixen"
+        );
+        assert_eq!(redact_url("redis://cache:6379"), "redis://cache:6379");
+        assert_eq!(redact_url("not a url"), "***unparseable***");
+    }
+}
@@ -11,7 +11,7 @@ use std::path::PathBuf;
 use clap::Parser;
 
 pub mod secrets;
-pub use secrets::{AdminSecret, BotToken, JwtSecret, OpenAiKey};
+pub use secrets::{AdminSecret, BotToken, JwtSecret, OpenAiKey, SecretsKey};
 
 #[derive(Parser, Debug, Clone)]
 #[command(
@@ -101,6 +101,17 @@ pub struct Config {
     #[arg(long, env = "CONFIG_JWT_TTL_SECS", default_value_t = 3600)]
     pub jwt_ttl_secs: i64,
 
+    /// Master key for the per-chat API keys in `chat_config` (base64 of 32
+    /// random bytes, `openssl rand -base64 32`). Unset = new keys can't be
+    /// stored; legacy plaintext rows still work.
+    #[arg(long, env = "CONFIG_SECRETS_KEY")]
+    pub secrets_key: Option<SecretsKey>,
+
+    /// The master key being rotated away from. Set only while running
+    /// `rotate-secrets-key`; values wrapped with it stay readable meanwhile.
+    #[arg(long, env = "CONFIG_SECRETS_KEY_PREVIOUS")]
+    pub secrets_key_previous: Option<SecretsKey>,
+
     /// Reject Telegram WebApp `initData` whose `auth_date` is older than this.
     #[arg(long, env = "CONFIG_INIT_DATA_MAX_AGE_SECS", default_value_t = 86_400)]
     pub init_data_max_age_secs: u64,
@@ -147,6 +158,35 @@ pub struct Config {
         default_value = "https://api.openai.com"
     )]
     pub openai_base_url: String,
+
+    /// Public origin the server is reached at (`https://vixen.example.org`).
+    /// Makes the public report page's OpenGraph `og:url` / `og:image`
+    /// absolute; relative paths are emitted when unset.
+    #[arg(long, env = "CONFIG_PUBLIC_URL")]
+    pub public_url: Option<String>,
+
+    // ── Webhooks ──
+    /// Accept webhook endpoints on loopback, private and link-local hosts.
+    /// Off by default: a chat admin could otherwise point the server at its
+    /// own network. Turn on for an in-cluster consumer.
+    #[arg(
+        long,
+        env = "CONFIG_WEBHOOKS_ALLOW_PRIVATE",
+        default_value_t = false,
+        action = clap::ArgAction::Set
+    )]
+    pub webhooks_allow_private: bool,
+
+    // ── Observability ──
+    /// OTLP/HTTP collector base URL (`http://otel-collector:4318`). Spans are
+    /// POSTed to `{endpoint}/v1/traces`. Unset = no trace export.
+    #[arg(long, env = "CONFIG_OTLP_ENDPOINT")]
+    pub otlp_endpoint: Option<String>,
+
+    /// Fraction of root traces (updates, HTTP requests, job passes) exported,
+    /// `0.0..=1.0`. Child spans follow their root's decision.
+    #[arg(long, env = "CONFIG_OTLP_SAMPLE_RATIO", default_value_t = 1.0)]
+    pub otlp_sample_ratio: f64,
 }
 
 impl Config {
@@ -198,6 +238,22 @@ impl Config {
             });
         }
 
+        for (var, key) in [
+            ("CONFIG_SECRETS_KEY", &self.secrets_key),
+            ("CONFIG_SECRETS_KEY_PREVIOUS", &self.secrets_key_previous),
+        ] {
+            if key.as_ref().is_some_and(|k| k.decode().is_none()) {
+                return Err(ConfigError::BadSecretsKey(var));
+            }
+        }
+        if self.secrets_key_previous.is_some() && self.secrets_key.is_none() {
+            return Err(ConfigError::BadSecretsKey("CONFIG_SECRETS_KEY"));
+        }
+
+        if !(0.0..=1.0).contains(&self.otlp_sample_ratio) {
+            return Err(ConfigError::BadSampleRatio(self.otlp_sample_ratio));
+        }
+
         Ok(())
     }
 
@@ -225,6 +281,10 @@ pub enum ConfigError {
     WebhookMissing(&'static str),
     #[error("CONFIG_DB_MIN_CONNECTIONS ({min}) cannot exceed CONFIG_DB_MAX_CONNECTIONS ({max})")]
     DbPoolInverted { min: u32, max: u32 },
+    #[error("CONFIG_OTLP_SAMPLE_RATIO must be within 0.0..=1.0 (got {0})")]
+    BadSampleRatio(f64),
+    #[error("{0} must be set to base64 of exactly 32 bytes")]
+    BadSecretsKey(&'static str),
 }
 
 #[cfg(test)]
@@ -322,4 +382,29 @@ mod tests {
             Err(ConfigError::DbPoolInverted { .. })
         ));
     }
+
+    #[test]
+    fn rejects_malformed_secrets_key() {
+        let cfg = Config::try_parse_from(args(&[("secrets-key", "c2hvcnQ=")])).expect("parses");
+        assert!(matches!(
+            cfg.validate(),
+            Err(ConfigError::BadSecretsKey("CONFIG_SECRETS_KEY"))
+        ));
+
+        let cfg = Config::try_parse_from(args(&[(
+            "secrets-key",
+            "AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8=",
+        )]))
+        .expect("parses");
+        cfg.validate().expect("32-byte key passes");
+    }
+
+    #[test]
+    fn rejects_out_of_range_sample_ratio() {
+        let cfg = Config::try_parse_from(args(&[("otlp-sample-ratio", "1.5")])).expect("parses");
+        assert!(matches!(
+            cfg.validate(),
+            Err(ConfigError::BadSampleRatio(_))
+        ));
+    }
 }
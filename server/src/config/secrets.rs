@@ -66,6 +66,22 @@ secret_newtype!(
     OpenAiKey,
     "OpenAI API key for the daily-report summary feature."
 );
+secret_newtype!(
+    SecretsKey,
+    "Base64 256-bit master key wrapping the per-chat API key data keys."
+);
+
+impl SecretsKey {
+    /// The raw 32 key bytes, or `None` when the value isn't standard base64
+    /// of exactly 32 bytes.
+    pub fn decode(&self) -> Option<[u8; 32]> {
+        use base64::Engine;
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(self.0.trim())
+            .ok()?;
+        bytes.try_into().ok()
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -85,6 +101,14 @@ mod tests {
         assert!(s.len() >= 32);
     }
 
+    #[test]
+    fn secrets_key_decodes_32_bytes_only() {
+        let ok = SecretsKey::new("AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8=");
+        assert_eq!(ok.decode().map(|k| k[31]), Some(31));
+        assert_eq!(SecretsKey::new("c2hvcnQ=").decode(), None);
+        assert_eq!(SecretsKey::new("not base64!").decode(), None);
+    }
+
     #[test]
     fn parses_from_env_string() {
         let parsed: AdminSecret = "topsecret".parse().expect("infallible");
@@ -72,6 +72,29 @@ impl Database {
         sqlx::migrate!("./migrations").run(&self.pool).await
     }
 
+    /// Versions of the embedded migrations this database hasn't applied yet
+    /// (all of them on a fresh one). Read-only, unlike [`Self::migrate`]:
+    /// it doesn't even create `_sqlx_migrations`.
+    pub async fn pending_migrations(&self) -> Result<Vec<i64>, sqlx::Error> {
+        let tracked: bool =
+            sqlx::query_scalar("SELECT to_regclass('_sqlx_migrations') IS NOT NULL")
+                .fetch_one(&self.pool)
+                .await?;
+        let applied: Vec<i64> = if tracked {
+            sqlx::query_scalar("SELECT version FROM _sqlx_migrations WHERE success")
+                .fetch_all(&self.pool)
+                .await?
+        } else {
+            Vec::new()
+        };
+        Ok(sqlx::migrate!("./migrations")
+            .iter()
+            .filter(|m| !m.migration_type.is_down_migration())
+            .map(|m| m.version)
+            .filter(|v| !applied.contains(v))
+            .collect())
+    }
+
     /// Drain in-flight queries and close the pool. Call before process exit.
     pub async fn close(&self) {
         self.pool.close().await;
@@ -3,32 +3,82 @@
 //! Pooled connections are used for ordinary commands (PUBLISH, GET/SET, ...).
 //! A subscription needs a dedicated long-lived connection — `subscribe()` opens
 //! its own `redis::Client` to keep the pool clean.
+//!
+//! Redis only holds caches and ephemeral UI state, so an outage degrades the
+//! bot instead of stopping it. Commands go through [`Redis::query`], bounded
+//! by [`COMMAND_TIMEOUT`]; a connection failure or timeout marks Redis
+//! unavailable and later commands fail fast with [`RedisError::Unavailable`]
+//! while callers take their fallback path. The probe from
+//! [`Redis::spawn_probe`] marks it available again once PING answers, after
+//! deleting the keys [`Redis::invalidate`] could not reach in the meantime.
+//! See `server/docs/architecture.md#redis-outages`.
 
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
-use deadpool_redis::{Config as DpConfig, Pool, Runtime};
+use deadpool_redis::{Config as DpConfig, Pool, PoolConfig, Runtime, Timeouts};
 use futures::StreamExt;
-use redis::AsyncCommands;
+use redis::FromRedisValue;
 use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
 use tokio_util::sync::CancellationToken;
 use tracing::{debug, error, info, warn};
 
+use crate::telemetry::metrics;
+
+/// How often [`Redis::spawn_probe`] PINGs.
+pub const PROBE_INTERVAL: Duration = Duration::from_secs(5);
+/// Pause before a dropped pub/sub subscription reconnects.
+const RESUBSCRIBE_DELAY: Duration = Duration::from_secs(5);
+/// Keys remembered for deletion on recovery. Past this an invalidation is
+/// dropped and the key lives out its TTL.
+const MAX_DEFERRED_KEYS: usize = 10_000;
+/// Keys per `DEL` when replaying deferred invalidations.
+const DEL_CHUNK: usize = 500;
+
 #[derive(Clone)]
 pub struct Redis {
     pool: Pool,
     /// Captured at construction time so `subscribe()` can build a dedicated
     /// pubsub client without callers re-passing the URL.
     url: String,
+    /// Shared by every clone.
+    health: Arc<Health>,
+}
+
+#[derive(Default)]
+struct Health {
+    down: AtomicBool,
+    /// Keys to `DEL` before Redis is marked available again. Also guards
+    /// the `down → up` flip, so a key can't be deferred after the replay.
+    deferred: Mutex<HashSet<String>>,
 }
 
 impl Redis {
     /// Build the pool from a `redis://` URL and verify reachability with a
-    /// PING round-trip (subject to the pool's wait+create timeouts).
+    /// PING round-trip. Pool waits, connects and recycles are bounded by
+    /// [`COMMAND_TIMEOUT`].
     pub async fn connect(url: impl Into<String>) -> Result<Self, RedisError> {
         let url = url.into();
-        let pool = DpConfig::from_url(&url).create_pool(Some(Runtime::Tokio1))?;
-        let me = Self { pool, url };
+        let mut cfg = DpConfig::from_url(&url);
+        cfg.pool = Some(PoolConfig {
+            timeouts: Timeouts {
+                wait: Some(COMMAND_TIMEOUT),
+                create: Some(COMMAND_TIMEOUT),
+                recycle: Some(COMMAND_TIMEOUT),
+            },
+            ..PoolConfig::default()
+        });
+        let pool = cfg.create_pool(Some(Runtime::Tokio1))?;
+        let me = Self {
+            pool,
+            url,
+            health: Arc::default(),
+        };
         me.ping().await?;
+        metrics::redis_available(true);
         Ok(me)
     }
 
@@ -36,21 +86,163 @@ impl Redis {
         &self.pool
     }
 
-    /// Cheap liveness probe used by `/health`.
+    /// Liveness probe used by `/health` and [`Self::probe`]. Ignores (and
+    /// doesn't change) the availability flag.
     pub async fn ping(&self) -> Result<(), RedisError> {
-        let mut conn = self.pool.get().await?;
-        let pong: String = redis::cmd("PING").query_async(&mut *conn).await?;
+        let pong: String = self.exec(&redis::cmd("PING")).await?;
         if pong != "PONG" {
             return Err(RedisError::UnexpectedPong(pong));
         }
         Ok(())
     }
 
+    /// `false` between a connection failure and the probe's next PONG.
+    pub fn is_available(&self) -> bool {
+        !self.health.down.load(Ordering::Acquire)
+    }
+
+    /// Run one command on a pooled connection, bounded by
+    /// [`COMMAND_TIMEOUT`]. Fails fast with [`RedisError::Unavailable`]
+    /// while Redis is marked down; a connection failure or timeout marks it
+    /// down.
+    pub async fn query<T: FromRedisValue>(&self, cmd: &redis::Cmd) -> Result<T, RedisError> {
+        if !self.is_available() {
+            return Err(RedisError::Unavailable);
+        }
+        let result = self.exec(cmd).await;
+        match &result {
+            Err(e) if e.is_connection_failure() => self.mark_down(e),
+            _ => {}
+        }
+        result
+    }
+
+    /// `DEL key`. While Redis is unavailable the key is remembered instead
+    /// and deleted by the probe before Redis is marked available again, so
+    /// an entry invalidated during an outage (an un-verify, an admin
+    /// demotion) can't be read back afterwards.
+    pub async fn invalidate(&self, key: &str) -> Result<(), RedisError> {
+        let del = redis::Cmd::del(key);
+        match self.query::<i64>(&del).await {
+            Err(e) if e.is_connection_failure() => {
+                if self.defer(key) {
+                    return Ok(());
+                }
+                // The probe brought Redis back in between.
+                self.query::<i64>(&del).await.map(drop)
+            }
+            other => other.map(drop),
+        }
+    }
+
     /// Publish a payload on a channel.
     pub async fn publish(&self, channel: &str, payload: &str) -> Result<u64, RedisError> {
-        let mut conn = self.pool.get().await?;
-        let receivers: u64 = conn.publish(channel, payload).await?;
-        Ok(receivers)
+        self.query(&redis::Cmd::publish(channel, payload)).await
+    }
+
+    /// Spawn the availability probe: every [`PROBE_INTERVAL`] it runs
+    /// [`Self::probe`]. Exits on `shutdown`.
+    pub fn spawn_probe(&self, shutdown: CancellationToken) -> JoinHandle<()> {
+        let me = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(PROBE_INTERVAL);
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            loop {
+                tokio::select! {
+                    biased;
+                    _ = shutdown.cancelled() => return,
+                    _ = interval.tick() => {
+                        me.probe().await;
+                    }
+                }
+            }
+        })
+    }
+
+    /// One probe round: PING, and on the first PONG after an outage replay
+    /// the deferred invalidations, then mark Redis available. Returns
+    /// whether Redis is available afterwards.
+    pub async fn probe(&self) -> bool {
+        if let Err(e) = self.ping().await {
+            self.mark_down(&e);
+            return false;
+        }
+        if self.is_available() {
+            return true;
+        }
+        match self.recover().await {
+            Ok(()) => true,
+            Err(e) => {
+                warn!(error = %e, "redis replay of deferred invalidations failed");
+                false
+            }
+        }
+    }
+
+    async fn recover(&self) -> Result<(), RedisError> {
+        loop {
+            let keys: Vec<String> = {
+                let mut deferred = self.deferred();
+                if deferred.is_empty() {
+                    self.health.down.store(false, Ordering::Release);
+                    metrics::redis_available(true);
+                    info!("redis available again");
+                    return Ok(());
+                }
+                deferred.drain().collect()
+            };
+            for (i, chunk) in keys.chunks(DEL_CHUNK).enumerate() {
+                if let Err(e) = self.exec::<i64>(&redis::Cmd::del(chunk)).await {
+                    self.deferred()
+                        .extend(keys[i * DEL_CHUNK..].iter().cloned());
+                    return Err(e);
+                }
+            }
+            info!(keys = keys.len(), "redis deferred invalidations replayed");
+        }
+    }
+
+    /// Queue `key` for deletion on recovery. `false` if Redis is available
+    /// again, in which case the caller deletes it itself.
+    fn defer(&self, key: &str) -> bool {
+        let mut deferred = self.deferred();
+        if self.is_available() {
+            return false;
+        }
+        if deferred.len() < MAX_DEFERRED_KEYS {
+            deferred.insert(key.to_owned());
+        } else {
+            warn!(
+                key,
+                "too many deferred redis invalidations; key kept until its TTL"
+            );
+        }
+        true
+    }
+
+    fn deferred(&self) -> std::sync::MutexGuard<'_, HashSet<String>> {
+        self.health
+            .deferred
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn mark_down(&self, cause: &RedisError) {
+        if !self.health.down.swap(true, Ordering::AcqRel) {
+            metrics::redis_available(false);
+            warn!(error = %cause, "redis unavailable; degrading until it answers again");
+        }
+    }
+
+    /// [`Self::query`] without the availability check.
+    async fn exec<T: FromRedisValue>(&self, cmd: &redis::Cmd) -> Result<T, RedisError> {
+        let run = async {
+            let mut conn = self.pool.get().await?;
+            Ok::<T, RedisError>(cmd.query_async(&mut *conn).await?)
+        };
+        tokio::time::timeout(COMMAND_TIMEOUT, run)
+            .await
+            .unwrap_or(Err(RedisError::Timeout))
     }
 
     /// Spawn a task that PSUBSCRIBEs to `pattern` and invokes `on_message` for
@@ -58,8 +250,9 @@ impl Redis {
     ///
     /// `on_message` is async-free: it runs synchronously within the message
     /// loop. Heavy work belongs in a separate spawned task so the loop keeps
-    /// reading. Failures during connect / psubscribe are logged at error and
-    /// the task returns; consumers can re-subscribe by calling this again.
+    /// reading. A failed connect / psubscribe or a dropped connection is
+    /// logged and retried every [`RESUBSCRIBE_DELAY`]; messages published
+    /// while disconnected are lost.
     pub fn subscribe<F>(
         &self,
         pattern: impl Into<String>,
@@ -72,49 +265,16 @@ impl Redis {
         let url = self.url.clone();
         let pattern = pattern.into();
         tokio::spawn(async move {
-            let client = match redis::Client::open(url) {
-                Ok(c) => c,
-                Err(e) => {
-                    error!(error = %e, pattern, "redis pubsub: open failed");
-                    return;
-                }
-            };
-            let mut pubsub = match client.get_async_pubsub().await {
-                Ok(c) => c,
-                Err(e) => {
-                    error!(error = %e, pattern, "redis pubsub: connect failed");
+            loop {
+                if listen(&url, &pattern, &cancel, &mut on_message).await {
+                    info!(pattern, "redis pubsub: shutting down");
                     return;
                 }
-            };
-            if let Err(e) = pubsub.psubscribe(&pattern).await {
-                error!(error = %e, pattern, "redis pubsub: psubscribe failed");
-                return;
-            }
-            info!(pattern, "redis pubsub: subscribed");
-
-            let mut stream = pubsub.on_message();
-            loop {
                 tokio::select! {
                     biased;
-                    _ = cancel.cancelled() => {
-                        info!(pattern, "redis pubsub: shutting down");
-                        return;
-                    }
-                    next = stream.next() => {
-                        let Some(msg) = next else {
-                            warn!(pattern, "redis pubsub: stream ended");
-                            return;
-                        };
-                        let channel = msg.get_channel_name().to_string();
-                        let payload: String = match msg.get_payload() {
-                            Ok(p) => p,
-                            Err(e) => {
-                                warn!(error = %e, channel, "redis pubsub: bad payload");
-                                continue;
-                            }
-                        };
-                        debug!(channel, "redis pubsub: message");
-                        on_message(channel, payload);
+                    _ = cancel.cancelled() => return,
+                    _ = tokio::time::sleep(RESUBSCRIBE_DELAY) => {
+                        info!(pattern, "redis pubsub: resubscribing");
                     }
                 }
             }
@@ -122,8 +282,59 @@ impl Redis {
     }
 }
 
-/// Default for ad-hoc command timeouts. Not used internally; exported for
-/// callers that want a sensible bound.
+/// One subscription, until the connection drops (`false`) or `cancel`
+/// fires (`true`).
+async fn listen<F>(url: &str, pattern: &str, cancel: &CancellationToken, on_message: &mut F) -> bool
+where
+    F: FnMut(String, String),
+{
+    let client = match redis::Client::open(url) {
+        Ok(c) => c,
+        Err(e) => {
+            error!(error = %e, pattern, "redis pubsub: open failed");
+            return false;
+        }
+    };
+    let mut pubsub = match client.get_async_pubsub().await {
+        Ok(c) => c,
+        Err(e) => {
+            error!(error = %e, pattern, "redis pubsub: connect failed");
+            return false;
+        }
+    };
+    if let Err(e) = pubsub.psubscribe(pattern).await {
+        error!(error = %e, pattern, "redis pubsub: psubscribe failed");
+        return false;
+    }
+    info!(pattern, "redis pubsub: subscribed");
+
+    let mut stream = pubsub.on_message();
+    loop {
+        tokio::select! {
+            biased;
+            _ = cancel.cancelled() => return true,
+            next = stream.next() => {
+                let Some(msg) = next else {
+                    warn!(pattern, "redis pubsub: stream ended");
+                    return false;
+                };
+                let channel = msg.get_channel_name().to_string();
+                let payload: String = match msg.get_payload() {
+                    Ok(p) => p,
+                    Err(e) => {
+                        warn!(error = %e, channel, "redis pubsub: bad payload");
+                        continue;
+                    }
+                };
+                debug!(channel, "redis pubsub: message");
+                on_message(channel, payload);
+            }
+        }
+    }
+}
+
+/// Bound on one command, including the pool wait. Past it Redis counts as
+/// unavailable.
 pub const COMMAND_TIMEOUT: Duration = Duration::from_secs(2);
 
 #[derive(thiserror::Error, Debug)]
@@ -136,4 +347,24 @@ pub enum RedisError {
     Redis(#[from] redis::RedisError),
     #[error("unexpected PING response: {0:?}")]
     UnexpectedPong(String),
+    #[error("redis command timed out")]
+    Timeout,
+    #[error("redis unavailable")]
+    Unavailable,
+}
+
+impl RedisError {
+    /// The server is unreachable, as opposed to a bad command or reply.
+    fn is_connection_failure(&self) -> bool {
+        match self {
+            Self::PoolAcquire(_) | Self::Timeout | Self::Unavailable => true,
+            Self::Redis(e) => {
+                e.is_io_error()
+                    || e.is_connection_dropped()
+                    || e.is_connection_refusal()
+                    || e.is_timeout()
+            }
+            Self::PoolCreate(_) | Self::UnexpectedPong(_) => false,
+        }
+    }
 }
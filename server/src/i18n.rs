@@ -0,0 +1,307 @@
+//! Message catalog. Bot replies, captcha captions and report labels are
+//! looked up by key in the Fluent files under `server/locales/` (embedded
+//! at compile time, one file per [`Lang`]) rather than hardcoded at each
+//! call site.
+//!
+//! Which language a string is rendered in:
+//!
+//!   * Reports and chat-wide notices (cooldowns, `/summary`) use the chat's
+//!     `chat_config.language` — see [`chat_lang`].
+//!   * Replies aimed at one user (command replies, captcha captions,
+//!     callback toasts, appeal DMs) prefer that user's Telegram
+//!     `language_code` when it names a shipped locale — [`Lang::for_user`].
+//!
+//! Plural selectors follow CLDR rules for the bundle's locale, so Russian
+//! and Ukrainian get their `one` / `few` / `many` forms. A key missing from
+//! a locale falls back to English, then to the key itself; the tests below
+//! keep every shipped locale complete.
+
+use std::sync::LazyLock;
+
+pub use fluent_bundle::FluentArgs;
+use fluent_bundle::FluentResource;
+use fluent_bundle::concurrent::FluentBundle;
+use sqlx::PgPool;
+use tracing::warn;
+use unic_langid::LanguageIdentifier;
+
+/// A shipped locale. Stored in `chat_config.language` as [`Lang::as_db_str`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Lang {
+    Ru,
+    En,
+    Uk,
+}
+
+impl Lang {
+    pub const ALL: [Lang; 3] = [Lang::Ru, Lang::En, Lang::Uk];
+
+    /// `chat_config.language`. Unknown values fall back to Russian, the
+    /// column default.
+    pub fn from_db_str(s: &str) -> Self {
+        match s {
+            "en" => Self::En,
+            "uk" => Self::Uk,
+            _ => Self::Ru,
+        }
+    }
+
+    pub fn as_db_str(self) -> &'static str {
+        match self {
+            Self::Ru => "ru",
+            Self::En => "en",
+            Self::Uk => "uk",
+        }
+    }
+
+    /// Telegram's `User.language_code` is an IETF tag (`en`, `en-US`,
+    /// `uk`); only the primary subtag matters. `None` for anything we don't
+    /// ship.
+    pub fn from_language_code(code: &str) -> Option<Self> {
+        let primary = code.split(['-', '_']).next().unwrap_or_default();
+        match primary.to_ascii_lowercase().as_str() {
+            "ru" => Some(Self::Ru),
+            "en" => Some(Self::En),
+            "uk" => Some(Self::Uk),
+            _ => None,
+        }
+    }
+
+    /// Language for a reply aimed at one user: their client language when
+    /// we ship it, otherwise `self` (the chat's).
+    pub fn for_user(self, language_code: Option<&str>) -> Self {
+        language_code
+            .and_then(Self::from_language_code)
+            .unwrap_or(self)
+    }
+
+    fn source(self) -> &'static str {
+        match self {
+            Self::Ru => include_str!("../locales/ru.ftl"),
+            Self::En => include_str!("../locales/en.ftl"),
+            Self::Uk => include_str!("../locales/uk.ftl"),
+        }
+    }
+
+    fn index(self) -> usize {
+        match self {
+            Self::Ru => 0,
+            Self::En => 1,
+            Self::Uk => 2,
+        }
+    }
+}
+
+/// One bundle per [`Lang::ALL`] entry, same order. A syntax error in a
+/// shipped `.ftl` file panics on first use; the tests parse every locale.
+static CATALOG: LazyLock<Vec<FluentBundle<FluentResource>>> =
+    LazyLock::new(|| Lang::ALL.iter().map(|&lang| bundle(lang)).collect());
+
+fn bundle(lang: Lang) -> FluentBundle<FluentResource> {
+    let id: LanguageIdentifier = lang.as_db_str().parse().expect("valid language id");
+    let resource = FluentResource::try_new(lang.source().to_owned())
+        .unwrap_or_else(|(_, errors)| panic!("locales/{}.ftl: {errors:?}", lang.as_db_str()));
+    let mut bundle = FluentBundle::new_concurrent(vec![id]);
+    // Unicode isolation marks around placeables render as visible boxes in
+    // some Telegram clients.
+    bundle.set_use_isolating(false);
+    bundle
+        .add_resource(resource)
+        .unwrap_or_else(|errors| panic!("locales/{}.ftl: {errors:?}", lang.as_db_str()));
+    bundle
+}
+
+/// Format `key` without arguments. Prefer the [`tr!`](crate::tr) macro.
+pub fn tr(lang: Lang, key: &str) -> String {
+    format(lang, key, None)
+}
+
+/// Format `key` with `args`. Prefer the [`tr!`](crate::tr) macro.
+pub fn tr_args(lang: Lang, key: &str, args: &FluentArgs) -> String {
+    format(lang, key, Some(args))
+}
+
+fn format(lang: Lang, key: &str, args: Option<&FluentArgs>) -> String {
+    for lang in [lang, Lang::En] {
+        let bundle = &CATALOG[lang.index()];
+        let Some(pattern) = bundle.get_message(key).and_then(|m| m.value()) else {
+            continue;
+        };
+        let mut errors = Vec::new();
+        let out = bundle.format_pattern(pattern, args, &mut errors);
+        if !errors.is_empty() {
+            warn!(key, lang = lang.as_db_str(), ?errors, "i18n format errors");
+        }
+        return out.into_owned();
+    }
+    warn!(key, "i18n key missing from every locale");
+    key.to_owned()
+}
+
+/// `tr!(lang, "key")` or `tr!(lang, "key", name = value, ...)` — the
+/// catalog string for `key` in `lang`. Argument names match the `$name`
+/// placeables in `locales/*.ftl`; values are anything `Into<FluentValue>`
+/// (strings and numbers — numbers drive plural selection).
+#[macro_export]
+macro_rules! tr {
+    ($lang:expr, $key:expr) => {
+        $crate::i18n::tr($lang, $key)
+    };
+    ($lang:expr, $key:expr, $($name:ident = $value:expr),+ $(,)?) => {{
+        let mut args = $crate::i18n::FluentArgs::new();
+        $(args.set(stringify!($name), $value);)+
+        $crate::i18n::tr_args($lang, $key, &args)
+    }};
+}
+
+/// `chat_config.language` for `chat_id`. Russian (the column default) when
+/// the row is missing or the lookup fails, so a transient DB hiccup doesn't
+/// surface as user-visible noise.
+pub async fn chat_lang(pool: &PgPool, chat_id: i64) -> Lang {
+    let lang = sqlx::query_scalar!(
+        r#"SELECT language FROM chat_config WHERE chat_id = $1"#,
+        chat_id,
+    )
+    .fetch_optional(pool)
+    .await;
+    match lang {
+        Ok(Some(s)) => Lang::from_db_str(&s),
+        Ok(None) => Lang::Ru,
+        Err(e) => {
+            warn!(error = ?e, chat_id, "chat language lookup failed");
+            Lang::Ru
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    /// Top-level message ids: `key = ...` at column 0.
+    fn keys(source: &str) -> BTreeSet<&str> {
+        source
+            .lines()
+            .filter(|l| l.starts_with(|c: char| c.is_ascii_lowercase()))
+            .filter_map(|l| l.split_once('=').map(|(k, _)| k.trim()))
+            .collect()
+    }
+
+    #[test]
+    fn every_key_exists_in_every_locale() {
+        let reference = keys(Lang::En.source());
+        assert!(reference.len() > 50, "parsed only {} keys", reference.len());
+        for lang in Lang::ALL {
+            let got = keys(lang.source());
+            let missing: Vec<_> = reference.difference(&got).collect();
+            let extra: Vec<_> = got.difference(&reference).collect();
+            assert!(
+                missing.is_empty() && extra.is_empty(),
+                "locales/{}.ftl: missing {missing:?}, extra {extra:?}",
+                lang.as_db_str()
+            );
+            for key in &reference {
+                assert!(
+                    CATALOG[lang.index()].has_message(key),
+                    "locales/{}.ftl: {key} did not load",
+                    lang.as_db_str()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn every_message_formats_without_errors() {
+        let mut args = FluentArgs::new();
+        for name in [
+            "mention",
+            "count",
+            "command",
+            "role",
+            "target_role",
+            "link",
+            "seconds",
+            "minutes",
+            "user_id",
+            "used",
+            "budget",
+            "hours",
+            "chat",
+        ] {
+            args.set(name, 2);
+        }
+        for lang in Lang::ALL {
+            let bundle = &CATALOG[lang.index()];
+            for key in keys(Lang::En.source()) {
+                let pattern = bundle.get_message(key).and_then(|m| m.value()).unwrap();
+                let mut errors = Vec::new();
+                let out = bundle.format_pattern(pattern, Some(&args), &mut errors);
+                assert!(errors.is_empty(), "{}/{key}: {errors:?}", lang.as_db_str());
+                assert!(
+                    !out.trim().is_empty(),
+                    "{}/{key} is empty",
+                    lang.as_db_str()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn russian_plurals() {
+        let attempts = |n: i64| tr!(Lang::Ru, "captcha-attempts-left", count = n);
+        assert_eq!(attempts(1), "🎯 Осталась 1 попытка");
+        assert_eq!(attempts(3), "🎯 Осталось 3 попытки");
+        assert_eq!(attempts(5), "🎯 Осталось 5 попыток");
+        assert_eq!(attempts(11), "🎯 Осталось 11 попыток");
+        assert_eq!(attempts(21), "🎯 Осталась 21 попытка");
+        assert_eq!(attempts(22), "🎯 Осталось 22 попытки");
+        assert_eq!(
+            tr!(Lang::Ru, "cmd-cooldown", command = "/stats", seconds = 41),
+            "/stats: подождите ещё 41 секунду."
+        );
+    }
+
+    #[test]
+    fn ukrainian_plurals() {
+        let attempts = |n: i64| tr!(Lang::Uk, "captcha-attempts-left", count = n);
+        assert_eq!(attempts(1), "🎯 Залишилася 1 спроба");
+        assert_eq!(attempts(4), "🎯 Залишилося 4 спроби");
+        assert_eq!(attempts(12), "🎯 Залишилося 12 спроб");
+        assert_eq!(
+            tr!(Lang::Uk, "cmd-cooldown", command = "/summary", seconds = 2),
+            "/summary: зачекайте ще 2 секунди."
+        );
+    }
+
+    #[test]
+    fn english_plurals_and_no_isolation_marks() {
+        assert_eq!(
+            tr!(Lang::En, "cmd-cooldown", command = "/stats", seconds = 1),
+            "/stats: wait 1 more second."
+        );
+        let s = tr!(Lang::En, "captcha-welcome", mention = "@alice");
+        assert_eq!(s, "👋 @alice, welcome!");
+        assert!(!s.contains('\u{2068}'));
+    }
+
+    #[test]
+    fn missing_key_falls_back_to_the_key() {
+        assert_eq!(tr!(Lang::Uk, "no-such-key"), "no-such-key");
+    }
+
+    #[test]
+    fn language_code_resolution() {
+        assert_eq!(Lang::from_language_code("en-US"), Some(Lang::En));
+        assert_eq!(Lang::from_language_code("UK"), Some(Lang::Uk));
+        assert_eq!(Lang::from_language_code("ru"), Some(Lang::Ru));
+        assert_eq!(Lang::from_language_code("de"), None);
+        assert_eq!(Lang::Ru.for_user(Some("uk")), Lang::Uk);
+        assert_eq!(Lang::Uk.for_user(Some("de")), Lang::Uk);
+        assert_eq!(Lang::En.for_user(None), Lang::En);
+        for lang in Lang::ALL {
+            assert_eq!(Lang::from_db_str(lang.as_db_str()), lang);
+        }
+        assert_eq!(Lang::from_db_str("fr"), Lang::Ru);
+    }
+}
@@ -3,8 +3,8 @@
 //! Per docs/captcha.md the M1 policy is "delete the message, give them a
 //! fresh captcha next time they speak". The user is NEVER kicked for failing
 //! a captcha — kicks are reserved for the M2 spam pipeline. So a sweep here
-//! just deletes the captcha photo (best-effort) and writes a `captcha_expired`
-//! audit row.
+//! just deletes the captcha photo (best-effort), writes a `captcha_expired`
+//! audit row and puts `captcha.expired` on the dashboard's live feed.
 //!
 //! The DELETE … RETURNING below is the single source of truth for "what's
 //! expired"; running the loop twice in a row finds zero rows on the second
@@ -27,6 +27,8 @@ use tracing::{info, instrument, warn};
 
 use crate::api::AppState;
 use crate::models::daily_stats::{self, Metric};
+use crate::services::live_feed::LiveEventKind;
+use crate::services::outbound::Priority;
 
 pub const NAME: &str = "captcha_expiry";
 pub const INTERVAL: Duration = Duration::from_secs(60);
@@ -44,7 +46,8 @@ pub async fn run(bot: Bot, state: AppState, shutdown: CancellationToken) -> Resu
                 return Ok(());
             }
             _ = interval.tick() => {
-                if let Err(e) = do_one_pass(&bot, &state, &shutdown).await {
+                let pass = do_one_pass(&bot, &state, &shutdown);
+                if let Err(e) = super::timed_pass(NAME, pass).await {
                     warn!(job = NAME, ?e, "iteration failed");
                 }
             }
@@ -70,7 +73,7 @@ async fn do_one_pass(bot: &Bot, state: &AppState, shutdown: &CancellationToken)
                 info!(swept = total, "shutdown mid-batch");
                 return Ok(());
             }
-            process_expired(bot, state.db.pool(), row).await;
+            process_expired(bot, state, row).await;
         }
     }
     if total > 0 {
@@ -117,16 +120,25 @@ async fn sweep_expired_batch(pool: &PgPool, limit: i64) -> Result<Vec<ExpiredRow
         .collect())
 }
 
-async fn process_expired(bot: &Bot, pool: &PgPool, row: ExpiredRow) {
+async fn process_expired(bot: &Bot, state: &AppState, row: ExpiredRow) {
     let chat_id = ChatId(row.chat_id);
 
     if let Some(mid) = row.telegram_message_id {
-        let _ = bot.delete_message(chat_id, MessageId(mid)).await;
+        // Cleanup of our own photo: behind spam deletes and fresh captchas.
+        let request = bot.delete_message(chat_id, MessageId(mid));
+        let _ = state
+            .outbound
+            .send(row.chat_id, Priority::Background, request)
+            .await;
     }
 
-    if let Err(e) = ledger_expired(pool, &row).await {
+    if let Err(e) = ledger_expired(state.db.pool(), &row).await {
         warn!(?e, "ledger insert (captcha_expired) failed");
     }
+    let event = LiveEventKind::CaptchaExpired {
+        user_id: row.user_id,
+    };
+    state.live.publish(row.chat_id, event).await;
 }
 
 async fn ledger_expired(pool: &PgPool, row: &ExpiredRow) -> Result<()> {
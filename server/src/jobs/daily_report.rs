@@ -1,18 +1,24 @@
-//! `daily_report` job — fires a per-chat report at the chat-local hour.
+//! `daily_report` job — fires the per-chat daily, weekly and monthly
+//! reports at the chat-local hour.
 //!
 //! The scheduler ticks every 5 minutes. On each tick:
 //!
 //! 1. Iterate over every watched chat (`Config::chats`).
 //! 2. Read `chat_config.{report_hour, timezone, report_min_activity,
-//!    summary_enabled, language}`.
-//! 3. Compute current chat-local time. Fire iff:
-//!      * `chat_local.hour() == report_hour`,
-//!      * `chat_local.minute() < TICK_INTERVAL.minutes()`, and
-//!      * no `report_messages` row exists for `(chat_id, chat_local.date())`.
-//! 4. Aggregate via `ReportService`. Skip silently if `messages_seen <
-//!    report_min_activity` (low-activity day).
-//! 5. Render text + chart, optionally generate AI summary, post both
-//!    messages, record in `report_messages`.
+//!    summary_enabled, language, weekly_report_weekday,
+//!    monthly_report_day}`.
+//! 3. Compute current chat-local time. Continue iff
+//!    `chat_local.hour() == report_hour` and
+//!    `chat_local.minute() < TICK_INTERVAL.minutes()`.
+//! 4. For each period due today — daily always, weekly on
+//!    `weekly_report_weekday` (ISO, 1 = Monday), monthly on
+//!    `monthly_report_day` — skip it when both of its `report_messages`
+//!    rows exist for `(chat_id, chat_local.date())`.
+//! 5. Aggregate via `ReportService`. Skip silently if `messages_seen <
+//!    report_min_activity` over the period (low-activity day / week /
+//!    month).
+//! 6. Render text + chart, optionally generate AI summary (daily only),
+//!    post both messages, record in `report_messages`.
 //!
 //! This loop is idempotent on re-fire (the `report_messages` lookup gates
 //! re-sends), so the 5-min cadence + ±5-min fire window safely covers
@@ -23,7 +29,7 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use anyhow::{Context, Result};
-use chrono::{NaiveDate, Timelike, Utc};
+use chrono::{Datelike, NaiveDate, Timelike, Utc};
 use chrono_tz::Tz;
 use sqlx::PgPool;
 use teloxide::prelude::*;
@@ -32,9 +38,11 @@ use tokio_util::sync::CancellationToken;
 use tracing::{debug, info, instrument, warn};
 
 use crate::api::AppState;
+use crate::models::report::ReportPeriod;
 use crate::models::report_message::{self, ReportKind};
+use crate::services::outbound::Priority;
 use crate::services::report_render::{HeaderKind, Lang};
-use crate::services::report_service::{ReportService, day_window_local};
+use crate::services::report_service::{ReportService, day_window_local, period_window_local};
 use crate::services::summary_service::SummaryOutcome;
 use crate::services::{chart_service, report_render};
 
@@ -53,7 +61,7 @@ pub async fn run(bot: Bot, state: AppState, shutdown: CancellationToken) -> Resu
                 return Ok(());
             }
             _ = interval.tick() => {
-                if let Err(e) = do_one_pass(&bot, &state).await {
+                if let Err(e) = super::timed_pass(NAME, do_one_pass(&bot, &state)).await {
                     warn!(job = NAME, ?e, "iteration failed");
                 }
             }
@@ -80,12 +88,31 @@ struct ScheduleConfig {
     min_activity: i16,
     language: String,
     summary_enabled: bool,
+    /// ISO weekday (1 = Monday) of the weekly rollup; `None` = off.
+    weekly_weekday: Option<i16>,
+    /// Day of month (1..=28) of the monthly rollup; `None` = off.
+    monthly_day: Option<i16>,
+}
+
+impl ScheduleConfig {
+    /// Periods whose report is due on the chat-local `date`, daily first.
+    fn due_on(&self, date: NaiveDate) -> Vec<ReportPeriod> {
+        let mut due = vec![ReportPeriod::Daily];
+        if self.weekly_weekday == Some(date.weekday().number_from_monday() as i16) {
+            due.push(ReportPeriod::Weekly);
+        }
+        if self.monthly_day == Some(date.day() as i16) {
+            due.push(ReportPeriod::Monthly);
+        }
+        due
+    }
 }
 
 async fn fetch_schedule_config(pool: &PgPool, chat_id: i64) -> Result<Option<ScheduleConfig>> {
     let row = sqlx::query!(
         r#"
-        SELECT report_hour, timezone, report_min_activity, language, summary_enabled
+        SELECT report_hour, timezone, report_min_activity, language, summary_enabled,
+               weekly_report_weekday, monthly_report_day
         FROM chat_config
         WHERE chat_id = $1
         "#,
@@ -100,6 +127,8 @@ async fn fetch_schedule_config(pool: &PgPool, chat_id: i64) -> Result<Option<Sch
         min_activity: r.report_min_activity,
         language: r.language,
         summary_enabled: r.summary_enabled,
+        weekly_weekday: r.weekly_report_weekday,
+        monthly_day: r.monthly_report_day,
     }))
 }
 
@@ -133,16 +162,59 @@ async fn maybe_fire(
     }
 
     let report_date: NaiveDate = now_local.date_naive();
-    if report_message::already_posted_today(state.db.pool(), chat_id, report_date).await? {
-        debug!(chat_id, %report_date, "already posted, skipping");
+    for period in cfg.due_on(report_date) {
+        // One failing period (say, the monthly chart) must not hold back the
+        // others; the next tick retries it.
+        if let Err(e) =
+            fire_period(bot, state, reports, chat_id, &cfg, report_date, tz, period).await
+        {
+            warn!(chat_id, ?period, ?e, "report for period failed");
+        }
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn fire_period(
+    bot: &Bot,
+    state: &AppState,
+    reports: &Arc<ReportService>,
+    chat_id: i64,
+    cfg: &ScheduleConfig,
+    report_date: NaiveDate,
+    tz: Tz,
+    period: ReportPeriod,
+) -> Result<()> {
+    if report_message::already_posted_today(state.db.pool(), chat_id, report_date, period).await? {
+        debug!(chat_id, %report_date, ?period, "already posted, skipping");
         return Ok(());
     }
 
-    let (from, to) = day_window_local(report_date, tz);
-    let report = reports.aggregate(chat_id, from, to).await?;
+    let (report, header) = match period {
+        ReportPeriod::Daily => {
+            let (from, to) = day_window_local(report_date, tz);
+            (
+                reports.aggregate(chat_id, from, to).await?,
+                HeaderKind::Daily,
+            )
+        }
+        ReportPeriod::Weekly | ReportPeriod::Monthly => {
+            let window = period_window_local(period, report_date, tz);
+            let header = if period == ReportPeriod::Weekly {
+                HeaderKind::Weekly
+            } else {
+                HeaderKind::Monthly
+            };
+            (
+                reports.aggregate_rollup(chat_id, period, window).await?,
+                header,
+            )
+        }
+    };
     if report.messages_seen < cfg.min_activity as i64 {
         info!(
             chat_id,
+            ?period,
             messages_seen = report.messages_seen,
             min = cfg.min_activity,
             "below activity threshold, skipping"
@@ -158,16 +230,18 @@ async fn maybe_fire(
         &cfg.language,
         cfg.summary_enabled,
         &report,
-        HeaderKind::Daily,
+        header,
     )
     .await?;
     Ok(())
 }
 
 /// Common send / record logic, shared by the scheduler and the on-demand
-/// `/report` command. `summary_enabled` is the chat-config flag; the
-/// summary is also gated by the per-chat OpenAI key resolved inside
-/// `summary_service`.
+/// `/report` command. The `report_messages` rows are keyed by
+/// [`ReportData::period`](crate::models::report::ReportData::period).
+/// `summary_enabled` is the chat-config flag; the summary is also gated by
+/// the per-chat OpenAI key resolved inside `summary_service`, and rollups
+/// never get one — the summary prompt is sized for a single day.
 #[allow(clippy::too_many_arguments)]
 pub async fn deliver(
     bot: &Bot,
@@ -179,32 +253,39 @@ pub async fn deliver(
     report: &crate::models::report::ReportData,
     header: HeaderKind,
 ) -> Result<()> {
+    let period = report.period();
     // Replace-on-redo: best-effort delete prior pair, then drop ledger rows.
-    let prior = report_message::prior_today(state.db.pool(), chat_id, report_date).await?;
+    let prior = report_message::prior_today(state.db.pool(), chat_id, report_date, period).await?;
     let chat = ChatId(chat_id);
     for m in &prior {
-        if let Err(e) = bot
-            .delete_message(chat, MessageId(m.telegram_message_id))
+        let request = bot.delete_message(chat, MessageId(m.telegram_message_id));
+        if let Err(e) = state
+            .outbound
+            .send(chat_id, Priority::Background, request)
             .await
         {
             warn!(error = %e, kind = ?m.kind, "delete prior report message failed");
         }
     }
     if !prior.is_empty() {
-        report_message::delete_for_day(state.db.pool(), chat_id, report_date).await?;
+        report_message::delete_for_day(state.db.pool(), chat_id, report_date, period).await?;
     }
 
     let lang = Lang::from_db_str(language);
     let body = report_render::render(report, lang, header);
-    let text_msg = bot
+    let request = bot
         .send_message(chat, body)
-        .parse_mode(ParseMode::MarkdownV2)
+        .parse_mode(ParseMode::MarkdownV2);
+    let text_msg = state
+        .outbound
+        .send(chat_id, Priority::Background, request)
         .await
         .context("send_message (report text)")?;
     report_message::record(
         state.db.pool(),
         chat_id,
         report_date,
+        period,
         ReportKind::Text,
         text_msg.id.0,
     )
@@ -217,7 +298,7 @@ pub async fn deliver(
             .context("chart spawn_blocking join")??
     };
 
-    let caption = if summary_enabled {
+    let caption = if summary_enabled && period == ReportPeriod::Daily {
         let outcome = state
             .summary
             .summarize(chat_id, report.from, report.to, language)
@@ -238,17 +319,22 @@ pub async fn deliver(
     if let Some(c) = caption {
         req = req.caption(c).parse_mode(ParseMode::MarkdownV2);
     }
-    let photo_msg = req.await.context("send_photo (report chart)")?;
+    let photo_msg = state
+        .outbound
+        .send(chat_id, Priority::Background, req)
+        .await
+        .context("send_photo (report chart)")?;
     report_message::record(
         state.db.pool(),
         chat_id,
         report_date,
+        period,
         ReportKind::Photo,
         photo_msg.id.0,
     )
     .await?;
 
-    info!(chat_id, %report_date, "daily report delivered");
+    info!(chat_id, %report_date, ?period, "report delivered");
     Ok(())
 }
 
@@ -281,6 +367,38 @@ mod tests {
         assert_eq!(INTERVAL.as_secs(), 300);
     }
 
+    #[test]
+    fn rollups_due_on_configured_days() {
+        let cfg = ScheduleConfig {
+            report_hour: 17,
+            timezone: "UTC".into(),
+            min_activity: 0,
+            language: "en".into(),
+            summary_enabled: false,
+            weekly_weekday: Some(1),
+            monthly_day: Some(4),
+        };
+        // 2026-05-04 is a Monday.
+        let monday = NaiveDate::from_ymd_opt(2026, 5, 4).unwrap();
+        assert_eq!(
+            cfg.due_on(monday),
+            vec![
+                ReportPeriod::Daily,
+                ReportPeriod::Weekly,
+                ReportPeriod::Monthly
+            ]
+        );
+        let tuesday = monday.succ_opt().unwrap();
+        assert_eq!(cfg.due_on(tuesday), vec![ReportPeriod::Daily]);
+
+        let off = ScheduleConfig {
+            weekly_weekday: None,
+            monthly_day: None,
+            ..cfg
+        };
+        assert_eq!(off.due_on(monday), vec![ReportPeriod::Daily]);
+    }
+
     #[test]
     fn timezone_parses_known_iana() {
         let _: Tz = "Europe/Berlin".parse().unwrap();
@@ -1,9 +1,16 @@
-//! Background jobs (captcha expiry, daily report, spam cleanup, chat-info refresh,
-//! summary generation). See `server/docs/rules/background-jobs.md`.
+//! Background jobs (captcha expiry, daily report, spam cleanup, moderator
+//! sync, moderation log, webhook delivery, chat-info refresh, summary
+//! generation). See `server/docs/rules/background-jobs.md`.
 
 pub mod captcha_expiry;
 pub mod daily_report;
+pub mod mod_log;
+pub mod moderator_sync;
 pub mod spam_cleanup;
+pub mod webhooks;
+
+use std::future::Future;
+use std::time::Instant;
 
 use teloxide::prelude::*;
 use tokio::task::JoinHandle;
@@ -11,6 +18,7 @@ use tokio_util::sync::CancellationToken;
 use tracing::{error, info};
 
 use crate::api::AppState;
+use crate::telemetry::metrics;
 
 /// Spawn every registered job and return their join handles. Each task logs
 /// a clean exit (`Ok(())`) or a job-level error returned by `run`. **Panics
@@ -27,13 +35,25 @@ pub fn spawn_all(bot: Bot, state: AppState, shutdown: CancellationToken) -> Vec<
             daily_report::NAME,
             daily_report::run(bot.clone(), state.clone(), shutdown.clone()),
         ),
-        spawn_named(spam_cleanup::NAME, spam_cleanup::run(bot, state, shutdown)),
+        spawn_named(
+            mod_log::NAME,
+            mod_log::run(bot.clone(), state.clone(), shutdown.clone()),
+        ),
+        spawn_named(
+            moderator_sync::NAME,
+            moderator_sync::run(bot.clone(), state.clone(), shutdown.clone()),
+        ),
+        spawn_named(
+            spam_cleanup::NAME,
+            spam_cleanup::run(bot.clone(), state.clone(), shutdown.clone()),
+        ),
+        spawn_named(webhooks::NAME, webhooks::run(bot, state, shutdown)),
     ]
 }
 
 fn spawn_named<F>(name: &'static str, fut: F) -> JoinHandle<()>
 where
-    F: std::future::Future<Output = anyhow::Result<()>> + Send + 'static,
+    F: Future<Output = anyhow::Result<()>> + Send + 'static,
 {
     info!(job = name, "spawning");
     tokio::spawn(async move {
@@ -43,3 +63,16 @@ where
         }
     })
 }
+
+/// Run one job iteration and record its outcome and duration
+/// (`vixen_job_runs_total`, `vixen_job_run_duration_seconds`). Every job's
+/// tick arm wraps its `do_one_pass` in this.
+pub(crate) async fn timed_pass<F>(name: &'static str, pass: F) -> anyhow::Result<()>
+where
+    F: Future<Output = anyhow::Result<()>>,
+{
+    let started = Instant::now();
+    let result = pass.await;
+    metrics::job_run(name, result.is_ok(), started.elapsed());
+    result
+}
@@ -0,0 +1,131 @@
+//! `mod_log` job — mirrors new `moderation_actions` rows into each chat's
+//! moderation log channel (`chat_config.log_chat_id`) every 5 seconds.
+//!
+//! The ledger is the outbox: rows with `logged_at IS NULL` are pending. Each
+//! pass first stamps rows of chats without a log channel, then claims up to
+//! [`BATCH`] pending rows oldest-first (a lease on `log_claimed_until`, taken
+//! with `FOR UPDATE SKIP LOCKED`, so replicas never post the same row) and
+//! stamps each one after its send.
+//!
+//! Delivery is at-least-once on the happy path and best-effort otherwise:
+//!
+//!   * a `RetryAfter` that outlasts the outbound queue's retries, or a drop
+//!     from its full backlog, ends the pass and releases the rest of the
+//!     batch for the next tick;
+//!   * any other send error (bot removed from the channel, bad chat id) is
+//!     logged and the row is stamped anyway — a misconfigured channel must
+//!     not wedge the outbox.
+
+use std::time::Duration;
+
+use anyhow::Result;
+use sqlx::PgPool;
+use teloxide::RequestError;
+use teloxide::prelude::*;
+use teloxide::types::{ChatId, ParseMode};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, instrument, warn};
+use uuid::Uuid;
+
+use crate::api::AppState;
+use crate::services::mod_log::{self, LogEntry};
+use crate::services::outbound::{OutboundError, Priority};
+
+pub const NAME: &str = "mod_log";
+pub const INTERVAL: Duration = Duration::from_secs(5);
+
+/// Max entries posted per pass. Telegram allows ~20 messages/minute into one
+/// group, so a burst larger than this drains over several ticks anyway.
+const BATCH: i64 = 50;
+
+pub async fn run(bot: Bot, state: AppState, shutdown: CancellationToken) -> Result<()> {
+    let mut interval = tokio::time::interval(INTERVAL);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    info!(job = NAME, interval_secs = INTERVAL.as_secs(), "starting");
+    loop {
+        tokio::select! {
+            biased;
+            _ = shutdown.cancelled() => {
+                info!(job = NAME, "shutdown");
+                return Ok(());
+            }
+            _ = interval.tick() => {
+                let pass = do_one_pass(&bot, &state, &shutdown);
+                if let Err(e) = super::timed_pass(NAME, pass).await {
+                    warn!(job = NAME, ?e, "iteration failed");
+                }
+            }
+        }
+    }
+}
+
+#[instrument(skip(bot, state, shutdown), fields(job = NAME))]
+async fn do_one_pass(bot: &Bot, state: &AppState, shutdown: &CancellationToken) -> Result<()> {
+    let pool = state.db.pool();
+    let skipped = mod_log::skip_unconfigured(pool).await?;
+    if skipped > 0 {
+        debug!(skipped, "stamped entries of chats without a log channel");
+    }
+
+    let entries = mod_log::claim_pending(pool, BATCH).await?;
+    for (i, entry) in entries.iter().enumerate() {
+        if shutdown.is_cancelled() {
+            info!("shutdown mid-pass");
+            return release(pool, &entries[i..]).await;
+        }
+        let mut req = bot
+            .send_message(ChatId(entry.log_chat_id), mod_log::render(entry))
+            .parse_mode(ParseMode::MarkdownV2);
+        if let Some(kb) = mod_log::keyboard(entry) {
+            req = req.reply_markup(kb);
+        }
+        match state
+            .outbound
+            .send(entry.log_chat_id, Priority::Background, req)
+            .await
+        {
+            Ok(_) => {}
+            Err(OutboundError::Dropped(_)) => {
+                debug!(
+                    log_chat_id = entry.log_chat_id,
+                    "outbound backlog full; resuming next tick"
+                );
+                return release(pool, &entries[i..]).await;
+            }
+            Err(OutboundError::Request(RequestError::RetryAfter(after))) => {
+                warn!(
+                    log_chat_id = entry.log_chat_id,
+                    retry_after_secs = after.seconds(),
+                    "rate limited; resuming next tick"
+                );
+                return release(pool, &entries[i..]).await;
+            }
+            Err(e) => {
+                warn!(
+                    chat_id = entry.chat_id,
+                    log_chat_id = entry.log_chat_id,
+                    error = %e,
+                    "mod log send failed; dropping entry"
+                );
+            }
+        }
+        mod_log::mark_logged(pool, entry.id).await?;
+    }
+    Ok(())
+}
+
+/// Hand the unposted tail of a batch back before its lease runs out.
+async fn release(pool: &PgPool, rest: &[LogEntry]) -> Result<()> {
+    let ids: Vec<Uuid> = rest.iter().map(|e| e.id).collect();
+    mod_log::release(pool, &ids).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interval_is_5_seconds() {
+        assert_eq!(INTERVAL.as_secs(), 5);
+    }
+}
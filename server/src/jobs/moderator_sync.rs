@@ -0,0 +1,89 @@
+//! `moderator_sync` job — reconciles `chat_moderators` with each watched
+//! chat's Telegram administrators every 30 minutes.
+//!
+//! The `chat_member` handler already applies promotions and demotions as they
+//! happen; this pass is the safety net for updates the bot never saw
+//! (downtime, admins that predate the bot joining the chat). See
+//! `services::moderator_sync` for the mapping and the manual-grant rule.
+//!
+//! The fresh administrator list also refreshes the message gate's
+//! `cap:admins` cache, so a promoted admin stops hitting the captcha without
+//! waiting for the 6h TTL.
+
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use teloxide::prelude::*;
+use teloxide::types::ChatId;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, instrument, warn};
+
+use crate::api::AppState;
+use crate::services::moderator_sync;
+
+pub const NAME: &str = "moderator_sync";
+pub const INTERVAL: Duration = Duration::from_secs(30 * 60);
+
+pub async fn run(bot: Bot, state: AppState, shutdown: CancellationToken) -> Result<()> {
+    let mut interval = tokio::time::interval(INTERVAL);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    info!(job = NAME, interval_secs = INTERVAL.as_secs(), "starting");
+    loop {
+        tokio::select! {
+            biased;
+            _ = shutdown.cancelled() => {
+                info!(job = NAME, "shutdown");
+                return Ok(());
+            }
+            _ = interval.tick() => {
+                let pass = do_one_pass(&bot, &state, &shutdown);
+                if let Err(e) = super::timed_pass(NAME, pass).await {
+                    warn!(job = NAME, ?e, "iteration failed");
+                }
+            }
+        }
+    }
+}
+
+#[instrument(skip(bot, state, shutdown), fields(job = NAME))]
+async fn do_one_pass(bot: &Bot, state: &AppState, shutdown: &CancellationToken) -> Result<()> {
+    for &chat_id in &state.config.chats {
+        if shutdown.is_cancelled() {
+            info!("shutdown mid-pass");
+            return Ok(());
+        }
+        if let Err(e) = sync_one(bot, state, chat_id).await {
+            warn!(chat_id, ?e, "moderator sync for chat failed");
+        }
+    }
+    Ok(())
+}
+
+async fn sync_one(bot: &Bot, state: &AppState, chat_id: i64) -> Result<()> {
+    let admins = bot
+        .get_chat_administrators(ChatId(chat_id))
+        .await
+        .context("get_chat_administrators")?;
+
+    let ids: Vec<i64> = admins.iter().map(|a| a.user.id.0 as i64).collect();
+    if let Err(e) = state.captcha_state.set_admins(chat_id, &ids).await {
+        warn!(chat_id, error = ?e, "redis set_admins failed");
+    }
+
+    let changes =
+        moderator_sync::sync_chat(state.db.pool(), &state.moderation, chat_id, &admins).await?;
+    if !changes.is_empty() {
+        info!(chat_id, count = changes.len(), "moderator roles synced");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interval_is_30_minutes() {
+        assert_eq!(INTERVAL.as_secs(), 1800);
+    }
+}
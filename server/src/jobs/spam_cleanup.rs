@@ -4,6 +4,10 @@
 //! long-tail recurrence after the retention window starts fresh (`hit_count
 //! = 1`), which is acceptable.
 //!
+//! The same pass drops `summary_chunks` hours older than the window; a
+//! report that far back is never regenerated. Webhook events past their own
+//! fixed retention (`webhooks::RETENTION_DAYS`) go too.
+//!
 //! Tick: every 24h, cancel-aware. The DELETE is a single statement; spam
 //! tables are small (millions of rows worst-case) so we don't need batching
 //! the way captcha_expiry does.
@@ -17,6 +21,7 @@ use tokio_util::sync::CancellationToken;
 use tracing::{info, instrument, warn};
 
 use crate::api::AppState;
+use crate::services::webhooks;
 
 pub const NAME: &str = "spam_cleanup";
 pub const INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
@@ -40,7 +45,8 @@ pub async fn run(_bot: Bot, state: AppState, shutdown: CancellationToken) -> Res
                 return Ok(());
             }
             _ = interval.tick() => {
-                if let Err(e) = do_one_pass(state.db.pool(), retention_days).await {
+                let pass = do_one_pass(state.db.pool(), retention_days);
+                if let Err(e) = super::timed_pass(NAME, pass).await {
                     warn!(job = NAME, ?e, "iteration failed");
                 }
             }
@@ -54,6 +60,14 @@ async fn do_one_pass(pool: &PgPool, retention_days: i32) -> Result<()> {
     if pruned > 0 {
         info!(pruned, "spam_messages rows pruned");
     }
+    let pruned = prune_summary_chunks(pool, retention_days).await?;
+    if pruned > 0 {
+        info!(pruned, "summary_chunks rows pruned");
+    }
+    let pruned = webhooks::prune(pool).await?;
+    if pruned > 0 {
+        info!(pruned, "outbox_events rows pruned");
+    }
     Ok(())
 }
 
@@ -72,3 +86,19 @@ pub async fn prune_expired(pool: &PgPool, retention_days: i32) -> Result<u64> {
     .context("DELETE spam_messages (expired)")?;
     Ok(res.rows_affected())
 }
+
+/// Delete cached `summary_chunks` hours older than `now - retention_days
+/// days`. Returns the count.
+pub async fn prune_summary_chunks(pool: &PgPool, retention_days: i32) -> Result<u64> {
+    let res = sqlx::query!(
+        r#"
+        DELETE FROM summary_chunks
+        WHERE hour_start < NOW() - make_interval(days => $1::int)
+        "#,
+        retention_days,
+    )
+    .execute(pool)
+    .await
+    .context("DELETE summary_chunks (expired)")?;
+    Ok(res.rows_affected())
+}
@@ -0,0 +1,137 @@
+//! `webhooks` job — drains the `outbox_events` outbox into signed HTTP POSTs
+//! to each chat's webhook endpoints every 5 seconds.
+//!
+//! Each pass fans pending events out into `webhook_deliveries`, then claims
+//! up to [`BATCH`] due deliveries and POSTs them one by one. Failures are
+//! retried with backoff and eventually dead-lettered by
+//! [`webhooks::deliver`]; see `services::webhooks` for the contract. A
+//! shutdown mid-pass leaves the rest of the batch claimed: the lease expires
+//! and the next run picks it up without spending an attempt.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use teloxide::prelude::*;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, instrument, warn};
+
+use crate::api::AppState;
+use crate::services::webhooks::{self, DeliveryOutcome};
+
+pub const NAME: &str = "webhooks";
+pub const INTERVAL: Duration = Duration::from_secs(5);
+
+/// Max events fanned out and deliveries attempted per pass.
+const BATCH: i64 = 50;
+/// Per-POST bound. Well under `webhooks::CLAIM_LEASE`.
+const HTTP_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub async fn run(_bot: Bot, state: AppState, shutdown: CancellationToken) -> Result<()> {
+    let http = http_client(state.config.webhooks_allow_private)?;
+    let mut interval = tokio::time::interval(INTERVAL);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    info!(job = NAME, interval_secs = INTERVAL.as_secs(), "starting");
+    loop {
+        tokio::select! {
+            biased;
+            _ = shutdown.cancelled() => {
+                info!(job = NAME, "shutdown");
+                return Ok(());
+            }
+            _ = interval.tick() => {
+                let pass = do_one_pass(&http, &state, &shutdown);
+                if let Err(e) = super::timed_pass(NAME, pass).await {
+                    warn!(job = NAME, ?e, "iteration failed");
+                }
+            }
+        }
+    }
+}
+
+/// Client for webhook POSTs. Redirects are not followed: a 3xx counts as a
+/// failed attempt, so an endpoint cannot bounce signed events elsewhere.
+/// Unless `allow_private`, host names resolve through [`PublicOnly`], so a
+/// name that passed `validate_url` cannot later point into the network.
+pub fn http_client(allow_private: bool) -> Result<reqwest::Client> {
+    let mut builder = reqwest::Client::builder()
+        .timeout(HTTP_TIMEOUT)
+        .redirect(reqwest::redirect::Policy::none())
+        .user_agent(format!("vixen-server/{}", crate::build_info::VERSION));
+    if !allow_private {
+        builder = builder.dns_resolver(Arc::new(PublicOnly));
+    }
+    builder.build().context("build webhook HTTP client")
+}
+
+/// System resolver minus [`webhooks::is_private_ip`] answers. A name with
+/// nothing left fails the lookup, which the delivery records as a failed
+/// attempt.
+struct PublicOnly;
+
+impl Resolve for PublicOnly {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((name.as_str(), 0))
+                .await?
+                .filter(|a| !webhooks::is_private_ip(a.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(format!("{} resolves only to private addresses", name.as_str()).into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+#[instrument(skip(http, state, shutdown), fields(job = NAME))]
+async fn do_one_pass(
+    http: &reqwest::Client,
+    state: &AppState,
+    shutdown: &CancellationToken,
+) -> Result<()> {
+    let pool = state.db.pool();
+    let fanned = webhooks::fan_out(pool, BATCH).await?;
+    if fanned > 0 {
+        debug!(fanned, "outbox events fanned out");
+    }
+
+    for delivery in webhooks::claim_due(pool, BATCH).await? {
+        if shutdown.is_cancelled() {
+            info!("shutdown mid-pass");
+            return Ok(());
+        }
+        match webhooks::deliver(pool, http, &state.api_keys, &delivery).await? {
+            DeliveryOutcome::Delivered => {}
+            DeliveryOutcome::Retry(after) => debug!(
+                event_id = %delivery.event_id,
+                endpoint_id = %delivery.endpoint_id,
+                retry_in_secs = after.as_secs(),
+                "webhook delivery failed; retrying"
+            ),
+            DeliveryOutcome::Dead => warn!(
+                event_id = %delivery.event_id,
+                endpoint_id = %delivery.endpoint_id,
+                "webhook delivery out of attempts; dead-lettered"
+            ),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interval_is_5_seconds() {
+        assert_eq!(INTERVAL.as_secs(), 5);
+    }
+
+    #[test]
+    fn http_timeout_fits_in_the_claim_lease() {
+        assert!(HTTP_TIMEOUT < webhooks::CLAIM_LEASE);
+    }
+}
@@ -9,11 +9,15 @@
 //! - `api`        Axum router + `/health` + `/about` + OpenAPI (#24)
 //! - `telegram`   teloxide dispatcher + watched-chats filter (#25)
 //! - `services`, `jobs`, `models`, `utils` populated from M1 onwards.
+//! - `i18n`       Fluent message catalog for bot replies and reports
+//! - `cli`        `Config` flags + one-shot maintenance subcommands
 
 pub mod api;
 pub mod build_info;
+pub mod cli;
 pub mod config;
 pub mod database;
+pub mod i18n;
 pub mod jobs;
 pub mod models;
 pub mod services;
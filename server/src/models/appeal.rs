@@ -0,0 +1,266 @@
+//! `appeals` row + the status enum, and the queries behind the private-chat
+//! appeal flow (see `telegram::handlers::appeal`).
+//!
+//! Schema (migration 20260507000000_appeals): one row per submission, linked
+//! to the `moderation_actions` ban it contests. A row starts as `draft` when
+//! the user opens the deep link, becomes `pending` once they send the appeal
+//! text, and ends `approved` or `denied`. At most one open (`draft` or
+//! `pending`) appeal per (chat, user).
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// How long a `draft` waits for the appeal text before a new deep-link press
+/// replaces it.
+pub const DRAFT_TTL_MINUTES: i64 = 60;
+
+/// After a denial, how long before the user may appeal the same ban again.
+pub const DENIAL_COOLDOWN_HOURS: i64 = 24;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppealStatus {
+    Draft,
+    Pending,
+    Approved,
+    Denied,
+}
+
+impl AppealStatus {
+    pub fn as_db_str(self) -> &'static str {
+        match self {
+            Self::Draft => "draft",
+            Self::Pending => "pending",
+            Self::Approved => "approved",
+            Self::Denied => "denied",
+        }
+    }
+
+    pub fn from_db_str(s: &str) -> Option<Self> {
+        match s {
+            "draft" => Some(Self::Draft),
+            "pending" => Some(Self::Pending),
+            "approved" => Some(Self::Approved),
+            "denied" => Some(Self::Denied),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Appeal {
+    pub id: Uuid,
+    pub chat_id: i64,
+    pub user_id: i64,
+    pub action_id: Option<Uuid>,
+    pub text: Option<String>,
+    pub status: AppealStatus,
+    /// When the user sent the appeal text; `None` while a draft.
+    pub submitted_at: Option<DateTime<Utc>>,
+    pub decided_by: Option<i64>,
+    pub decided_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Result of [`open_draft`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OpenDraft {
+    /// A fresh draft, waiting for the appeal text.
+    Opened(Uuid),
+    /// The ledger shows no standing ban for the user in this chat.
+    NotBanned,
+    /// An appeal is already waiting for a moderator.
+    AlreadyPending,
+    /// An appeal against this ban was denied less than
+    /// [`DENIAL_COOLDOWN_HOURS`] ago; carries the hours left, rounded up.
+    CoolingDown(i64),
+}
+
+/// Start an appeal against the user's standing ban in `chat_id`: the latest
+/// `ban` / `unban` ledger row must be a `ban`, which the draft links to. A
+/// stale or abandoned draft is replaced; a recent denial of the same ban
+/// blocks a new one.
+pub async fn open_draft(pool: &PgPool, chat_id: i64, user_id: i64) -> Result<OpenDraft> {
+    let mut tx = pool.begin().await.context("BEGIN open appeal tx")?;
+
+    let last = sqlx::query!(
+        r#"
+        SELECT id, action FROM moderation_actions
+        WHERE chat_id = $1 AND target_user_id = $2 AND action IN ('ban', 'unban')
+        ORDER BY created_at DESC
+        LIMIT 1
+        "#,
+        chat_id,
+        user_id,
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .context("SELECT last ban for appeal")?;
+    let Some(ban) = last.filter(|r| r.action == "ban") else {
+        return Ok(OpenDraft::NotBanned);
+    };
+
+    let pending: bool = sqlx::query_scalar!(
+        r#"SELECT EXISTS(
+            SELECT 1 FROM appeals
+            WHERE chat_id = $1 AND user_id = $2 AND status = 'pending'
+        ) AS "exists!""#,
+        chat_id,
+        user_id,
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    if pending {
+        return Ok(OpenDraft::AlreadyPending);
+    }
+
+    let cooldown_until = sqlx::query_scalar!(
+        r#"
+        SELECT decided_at + make_interval(hours => $4::INT) AS "until!"
+        FROM appeals
+        WHERE chat_id = $1 AND user_id = $2 AND action_id = $3 AND status = 'denied'
+          AND decided_at > NOW() - make_interval(hours => $4::INT)
+        ORDER BY decided_at DESC
+        LIMIT 1
+        "#,
+        chat_id,
+        user_id,
+        ban.id,
+        DENIAL_COOLDOWN_HOURS as i32,
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .context("SELECT recent appeal denial")?;
+    if let Some(until) = cooldown_until {
+        let minutes = (until - Utc::now()).num_minutes().max(1);
+        return Ok(OpenDraft::CoolingDown((minutes + 59) / 60));
+    }
+
+    sqlx::query!(
+        r#"DELETE FROM appeals WHERE chat_id = $1 AND user_id = $2 AND status = 'draft'"#,
+        chat_id,
+        user_id,
+    )
+    .execute(&mut *tx)
+    .await
+    .context("DELETE stale appeal draft")?;
+    let id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO appeals (chat_id, user_id, action_id, status)
+        VALUES ($1, $2, $3, 'draft')
+        RETURNING id
+        "#,
+        chat_id,
+        user_id,
+        ban.id,
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .context("INSERT appeals draft")?;
+
+    tx.commit().await.context("COMMIT open appeal tx")?;
+    Ok(OpenDraft::Opened(id))
+}
+
+/// Attach `text` to the user's live draft and move it to `pending`. `None`
+/// when the user has no draft younger than [`DRAFT_TTL_MINUTES`].
+pub async fn submit(pool: &PgPool, user_id: i64, text: &str) -> Result<Option<Appeal>> {
+    let row = sqlx::query!(
+        r#"
+        UPDATE appeals
+        SET text = $2, status = 'pending', submitted_at = NOW()
+        WHERE id = (
+            SELECT id FROM appeals
+            WHERE user_id = $1 AND status = 'draft'
+              AND created_at > NOW() - make_interval(mins => $3::INT)
+            ORDER BY created_at DESC
+            LIMIT 1
+        )
+        RETURNING id, chat_id, user_id, action_id, text, status, submitted_at,
+                  decided_by, decided_at, created_at
+        "#,
+        user_id,
+        text,
+        DRAFT_TTL_MINUTES as i32,
+    )
+    .fetch_optional(pool)
+    .await
+    .context("UPDATE appeals (submit)")?;
+    Ok(row.map(|r| Appeal {
+        id: r.id,
+        chat_id: r.chat_id,
+        user_id: r.user_id,
+        action_id: r.action_id,
+        text: r.text,
+        status: AppealStatus::from_db_str(&r.status).unwrap_or(AppealStatus::Pending),
+        submitted_at: r.submitted_at,
+        decided_by: r.decided_by,
+        decided_at: r.decided_at,
+        created_at: r.created_at,
+    }))
+}
+
+/// Close a `pending` appeal. Returns the row as it was decided, or `None`
+/// when it was not pending (already decided by another moderator, or gone).
+/// The `status = 'pending'` guard makes concurrent presses race-free.
+pub async fn decide(
+    pool: &PgPool,
+    id: Uuid,
+    approve: bool,
+    decided_by: i64,
+) -> Result<Option<Appeal>> {
+    let status = if approve {
+        AppealStatus::Approved
+    } else {
+        AppealStatus::Denied
+    };
+    let row = sqlx::query!(
+        r#"
+        UPDATE appeals
+        SET status = $2, decided_by = $3, decided_at = NOW()
+        WHERE id = $1 AND status = 'pending'
+        RETURNING id, chat_id, user_id, action_id, text, status, submitted_at,
+                  decided_by, decided_at, created_at
+        "#,
+        id,
+        status.as_db_str(),
+        decided_by,
+    )
+    .fetch_optional(pool)
+    .await
+    .context("UPDATE appeals (decide)")?;
+    Ok(row.map(|r| Appeal {
+        id: r.id,
+        chat_id: r.chat_id,
+        user_id: r.user_id,
+        action_id: r.action_id,
+        text: r.text,
+        status,
+        submitted_at: r.submitted_at,
+        decided_by: r.decided_by,
+        decided_at: r.decided_at,
+        created_at: r.created_at,
+    }))
+}
+
+/// `chat_id` of an appeal, for the permission check before [`decide`].
+pub async fn chat_of(pool: &PgPool, id: Uuid) -> Result<Option<i64>> {
+    sqlx::query_scalar!(r#"SELECT chat_id FROM appeals WHERE id = $1"#, id)
+        .fetch_optional(pool)
+        .await
+        .context("SELECT appeals.chat_id")
+}
+
+/// The contested ledger row's `reason`, shown to moderators next to the
+/// appeal text.
+pub async fn ban_reason(pool: &PgPool, action_id: Uuid) -> Result<Option<String>> {
+    let row = sqlx::query_scalar!(
+        r#"SELECT reason FROM moderation_actions WHERE id = $1"#,
+        action_id,
+    )
+    .fetch_optional(pool)
+    .await
+    .context("SELECT moderation_actions.reason (appeal)")?;
+    Ok(row.flatten())
+}
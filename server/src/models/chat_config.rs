@@ -0,0 +1,97 @@
+//! Single-column reads and writes of `chat_config`, for `server config
+//! get|set`. Editable columns are exactly those with a
+//! [`Permission::for_config_field`]; values travel as text and Postgres
+//! casts them to the column's type, so the table's CHECK constraints are
+//! the validation. `openai_api_key` is excluded — it is sealed, see
+//! [`crate::services::api_keys`].
+
+use anyhow::{Context, Result, bail};
+use chrono_tz::Tz;
+use sqlx::PgPool;
+
+use crate::models::chat_moderator::Permission;
+
+/// The column that must never be read or written as plain text here.
+pub const SEALED_FIELD: &str = "openai_api_key";
+
+fn check_field(field: &str) -> Result<()> {
+    if field == SEALED_FIELD {
+        bail!("`{SEALED_FIELD}` is sealed; it cannot be read or written as text");
+    }
+    if Permission::for_config_field(field).is_none() {
+        bail!("`{field}` is not an editable chat_config field");
+    }
+    Ok(())
+}
+
+/// Text form of one column. Outer `None` = no `chat_config` row, inner
+/// `None` = the column is NULL.
+pub async fn get(pool: &PgPool, chat_id: i64, field: &str) -> Result<Option<Option<String>>> {
+    check_field(field)?;
+    // `field` is whitelisted above, so interpolating it is safe.
+    let sql = format!("SELECT {field}::text FROM chat_config WHERE chat_id = $1");
+    let row: Option<Option<String>> = sqlx::query_scalar(&sql)
+        .bind(chat_id)
+        .fetch_optional(pool)
+        .await
+        .with_context(|| format!("SELECT chat_config.{field}"))?;
+    Ok(row)
+}
+
+/// Write one column from its text form (`None` = NULL). `false` when the
+/// chat has no `chat_config` row. Out-of-range values surface as the
+/// constraint violation Postgres reports.
+pub async fn set(pool: &PgPool, chat_id: i64, field: &str, value: Option<&str>) -> Result<bool> {
+    check_field(field)?;
+    if field == "timezone" {
+        // Not a CHECK constraint: the report job falls back to UTC on a bad
+        // name, which would silently shift the schedule.
+        let tz = value.unwrap_or_default();
+        if tz.parse::<Tz>().is_err() {
+            bail!("`{tz}` is not an IANA timezone");
+        }
+    }
+
+    // Base type without the modifier: `character varying`, not `(64)`. The
+    // assignment to the column then applies the length limit as an error
+    // instead of the silent truncation an explicit `::varchar(64)` does.
+    let ty: String = sqlx::query_scalar(
+        r#"
+        SELECT format_type(atttypid, NULL)
+        FROM pg_attribute
+        WHERE attrelid = 'chat_config'::regclass AND attname = $1 AND NOT attisdropped
+        "#,
+    )
+    .bind(field)
+    .fetch_one(pool)
+    .await
+    .with_context(|| format!("resolve type of chat_config.{field}"))?;
+
+    let sql = format!("UPDATE chat_config SET {field} = $2::text::{ty} WHERE chat_id = $1");
+    let result = sqlx::query(&sql)
+        .bind(chat_id)
+        .bind(value)
+        .execute(pool)
+        .await
+        .with_context(|| format!("UPDATE chat_config.{field}"))?;
+    Ok(result.rows_affected() > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_field_accepts_whitelisted_columns() {
+        assert!(check_field("captcha_attempts").is_ok());
+        assert!(check_field("summary_token_budget").is_ok());
+    }
+
+    #[test]
+    fn check_field_rejects_keys_and_unknown_names() {
+        assert!(check_field("openai_api_key").is_err());
+        assert!(check_field("chat_id").is_err());
+        assert!(check_field("updated_at").is_err());
+        assert!(check_field("captcha_enabled; DROP TABLE chats").is_err());
+    }
+}
@@ -0,0 +1,427 @@
+//! `chat_moderators` row, the role enum, and the permission matrix shared by
+//! the bot commands and the dashboard API.
+//!
+//! Schema (migrations 20260504000000_moderator_roles and
+//! 20260505000000_moderator_sync):
+//!
+//! ```text
+//! chat_moderators (chat_id, user_id, granted_at, granted_by, role, source)
+//!   PRIMARY KEY (chat_id, user_id)
+//!   role   ∈ {'owner', 'admin', 'moderator', 'viewer'}
+//!   source ∈ {'manual', 'telegram'}
+//! ```
+//!
+//! Roles are strictly ordered (`owner > admin > moderator > viewer`); every
+//! permission is granted to a role and everything above it. Granting and
+//! revoking is further limited to roles strictly below the actor's own, so an
+//! admin can never mint another admin or touch an owner.
+//!
+//! `source = 'telegram'` rows are owned by `services::moderator_sync`, which
+//! mirrors the chat's Telegram administrators. The `sync_*` writers only ever
+//! touch those rows, so a manual grant always wins over the mirror.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{Executor, PgPool, Postgres};
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ModeratorRole {
+    // Declaration order drives `Ord`: lowest privilege first.
+    Viewer,
+    Moderator,
+    Admin,
+    Owner,
+}
+
+impl ModeratorRole {
+    pub fn as_db_str(self) -> &'static str {
+        match self {
+            Self::Owner => "owner",
+            Self::Admin => "admin",
+            Self::Moderator => "moderator",
+            Self::Viewer => "viewer",
+        }
+    }
+
+    pub fn from_db_str(s: &str) -> Option<Self> {
+        match s {
+            "owner" => Some(Self::Owner),
+            "admin" => Some(Self::Admin),
+            "moderator" => Some(Self::Moderator),
+            "viewer" => Some(Self::Viewer),
+            _ => None,
+        }
+    }
+
+    /// The permission matrix. Each permission names the lowest role that
+    /// holds it; higher roles inherit.
+    pub fn allows(self, permission: Permission) -> bool {
+        self >= permission.min_role()
+    }
+
+    /// Whether a holder of `self` may grant or revoke `target`. Requires
+    /// [`Permission::ManageModerators`] and a target strictly below `self`.
+    pub fn can_manage(self, target: ModeratorRole) -> bool {
+        self.allows(Permission::ManageModerators) && target < self
+    }
+}
+
+/// Who owns a `chat_moderators` row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum RoleSource {
+    /// `/mod grant` or a hand-written row. Never touched by the sync.
+    Manual,
+    /// Mirrored from the chat's Telegram administrators.
+    Telegram,
+}
+
+impl RoleSource {
+    pub fn as_db_str(self) -> &'static str {
+        match self {
+            Self::Manual => "manual",
+            Self::Telegram => "telegram",
+        }
+    }
+
+    pub fn from_db_str(s: &str) -> Option<Self> {
+        match s {
+            "manual" => Some(Self::Manual),
+            "telegram" => Some(Self::Telegram),
+            _ => None,
+        }
+    }
+}
+
+/// A capability checked by `/commands` and dashboard endpoints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Permission {
+    /// `/stats`, dashboard reads (reports, ledger, moderator list).
+    ViewReports,
+    /// `/report` — posts the full report into the chat.
+    PostReport,
+    /// `/summary` — spends the chat's OpenAI token budget.
+    RequestSummary,
+    /// `/verify`.
+    Verify,
+    /// `/ban`, `/unban`.
+    Ban,
+    /// Non-sensitive `chat_config` fields (captcha, spam, report schedule).
+    EditConfig,
+    /// AI-related `chat_config` fields: the OpenAI key, model, budget and the
+    /// message logging that feeds the summary.
+    EditAiConfig,
+    /// `/mod grant|revoke` for roles strictly below the actor's own.
+    ManageModerators,
+}
+
+impl Permission {
+    pub fn min_role(self) -> ModeratorRole {
+        match self {
+            Self::ViewReports => ModeratorRole::Viewer,
+            Self::PostReport | Self::RequestSummary | Self::Verify | Self::Ban => {
+                ModeratorRole::Moderator
+            }
+            Self::EditConfig | Self::ManageModerators => ModeratorRole::Admin,
+            Self::EditAiConfig => ModeratorRole::Owner,
+        }
+    }
+
+    /// Permission required to write a `chat_config` column. `None` for
+    /// columns that are not editable at all (keys, timestamps, unknown names).
+    pub fn for_config_field(field: &str) -> Option<Self> {
+        match field {
+            "captcha_enabled"
+            | "captcha_lifetime_secs"
+            | "captcha_attempts"
+            | "spam_enabled"
+            | "spam_threshold"
+            | "spam_weights"
+            | "cas_enabled"
+            | "clown_chance"
+            | "report_hour"
+            | "report_min_activity"
+            | "weekly_report_weekday"
+            | "monthly_report_day"
+            | "public_report"
+            | "timezone"
+            | "log_chat_id"
+            | "language" => Some(Self::EditConfig),
+            "openai_api_key"
+            | "openai_model"
+            | "llm_provider"
+            | "llm_base_url"
+            | "spam_llm_enabled"
+            | "spam_llm_min_score"
+            | "spam_llm_min_confidence"
+            | "summary_enabled"
+            | "summary_token_budget"
+            | "log_allowed_messages" => Some(Self::EditAiConfig),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ChatModerator {
+    pub chat_id: i64,
+    pub user_id: i64,
+    pub role: ModeratorRole,
+    pub granted_at: DateTime<Utc>,
+    /// `None` for rows written by the Telegram admin sync.
+    pub granted_by: Option<i64>,
+    pub source: RoleSource,
+}
+
+/// Role of `user_id` in `chat_id`, or `None` when there is no row.
+pub async fn role_of(pool: &PgPool, chat_id: i64, user_id: i64) -> Result<Option<ModeratorRole>> {
+    let role = sqlx::query_scalar!(
+        r#"
+        SELECT role FROM chat_moderators
+        WHERE chat_id = $1 AND user_id = $2
+        "#,
+        chat_id,
+        user_id,
+    )
+    .fetch_optional(pool)
+    .await
+    .context("SELECT chat_moderators.role")?;
+    Ok(role.as_deref().and_then(ModeratorRole::from_db_str))
+}
+
+/// Insert or re-role a moderator. `granted_by` / `granted_at` are refreshed
+/// on every call so the row always names whoever last set the role. The row
+/// becomes `manual`, taking it over from the Telegram sync if it was synced.
+pub async fn grant<'e, E>(
+    executor: E,
+    chat_id: i64,
+    user_id: i64,
+    role: ModeratorRole,
+    granted_by: i64,
+) -> Result<()>
+where
+    E: Executor<'e, Database = Postgres>,
+{
+    sqlx::query!(
+        r#"
+        INSERT INTO chat_moderators (chat_id, user_id, role, granted_by, source)
+        VALUES ($1, $2, $3, $4, 'manual')
+        ON CONFLICT (chat_id, user_id) DO UPDATE
+            SET role       = EXCLUDED.role,
+                granted_by = EXCLUDED.granted_by,
+                granted_at = NOW(),
+                source     = 'manual'
+        "#,
+        chat_id,
+        user_id,
+        role.as_db_str(),
+        granted_by,
+    )
+    .execute(executor)
+    .await
+    .context("UPSERT chat_moderators")?;
+    Ok(())
+}
+
+/// Remove a moderator. Returns `false` when there was no row.
+pub async fn revoke<'e, E>(executor: E, chat_id: i64, user_id: i64) -> Result<bool>
+where
+    E: Executor<'e, Database = Postgres>,
+{
+    let res = sqlx::query!(
+        r#"
+        DELETE FROM chat_moderators
+        WHERE chat_id = $1 AND user_id = $2
+        "#,
+        chat_id,
+        user_id,
+    )
+    .execute(executor)
+    .await
+    .context("DELETE chat_moderators")?;
+    Ok(res.rows_affected() > 0)
+}
+
+/// Mirror a Telegram administrator. Inserts a `telegram` row or re-roles an
+/// existing one; `manual` rows are left alone. Returns `true` when a row was
+/// written (so the caller knows whether to ledger and invalidate).
+pub async fn sync_upsert<'e, E>(
+    executor: E,
+    chat_id: i64,
+    user_id: i64,
+    role: ModeratorRole,
+) -> Result<bool>
+where
+    E: Executor<'e, Database = Postgres>,
+{
+    let res = sqlx::query!(
+        r#"
+        INSERT INTO chat_moderators (chat_id, user_id, role, source)
+        VALUES ($1, $2, $3, 'telegram')
+        ON CONFLICT (chat_id, user_id) DO UPDATE
+            SET role       = EXCLUDED.role,
+                granted_at = NOW()
+            WHERE chat_moderators.source = 'telegram'
+              AND chat_moderators.role <> EXCLUDED.role
+        "#,
+        chat_id,
+        user_id,
+        role.as_db_str(),
+    )
+    .execute(executor)
+    .await
+    .context("UPSERT chat_moderators (sync)")?;
+    Ok(res.rows_affected() > 0)
+}
+
+/// Drop a mirrored row after a Telegram demotion. `manual` rows survive.
+/// Returns `true` when a row was deleted.
+pub async fn sync_remove<'e, E>(executor: E, chat_id: i64, user_id: i64) -> Result<bool>
+where
+    E: Executor<'e, Database = Postgres>,
+{
+    let res = sqlx::query!(
+        r#"
+        DELETE FROM chat_moderators
+        WHERE chat_id = $1 AND user_id = $2 AND source = 'telegram'
+        "#,
+        chat_id,
+        user_id,
+    )
+    .execute(executor)
+    .await
+    .context("DELETE chat_moderators (sync)")?;
+    Ok(res.rows_affected() > 0)
+}
+
+/// Every moderator of `chat_id`, highest role first.
+pub async fn list(pool: &PgPool, chat_id: i64) -> Result<Vec<ChatModerator>> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT chat_id, user_id, role, granted_at, granted_by, source
+        FROM chat_moderators
+        WHERE chat_id = $1
+        ORDER BY granted_at
+        "#,
+        chat_id,
+    )
+    .fetch_all(pool)
+    .await
+    .context("SELECT chat_moderators")?;
+
+    let mut out: Vec<ChatModerator> = rows
+        .into_iter()
+        .filter_map(|r| {
+            let Some(role) = ModeratorRole::from_db_str(&r.role) else {
+                tracing::warn!(role = %r.role, "unknown chat_moderators.role, ignoring");
+                return None;
+            };
+            let Some(source) = RoleSource::from_db_str(&r.source) else {
+                tracing::warn!(source = %r.source, "unknown chat_moderators.source, ignoring");
+                return None;
+            };
+            Some(ChatModerator {
+                chat_id: r.chat_id,
+                user_id: r.user_id,
+                role,
+                granted_at: r.granted_at,
+                granted_by: r.granted_by,
+                source,
+            })
+        })
+        .collect();
+    out.sort_by(|a, b| b.role.cmp(&a.role));
+    Ok(out)
+}
+
+/// Chats where `user_id` holds any role. Feeds the JWT `chat_ids` claim.
+pub async fn chats_for(pool: &PgPool, user_id: i64) -> Result<Vec<i64>> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT chat_id FROM chat_moderators
+        WHERE user_id = $1
+        ORDER BY chat_id
+        "#,
+        user_id,
+    )
+    .fetch_all(pool)
+    .await
+    .context("SELECT chat_moderators by user")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn role_db_round_trip() {
+        for role in [
+            ModeratorRole::Owner,
+            ModeratorRole::Admin,
+            ModeratorRole::Moderator,
+            ModeratorRole::Viewer,
+        ] {
+            assert_eq!(ModeratorRole::from_db_str(role.as_db_str()), Some(role));
+        }
+        assert_eq!(ModeratorRole::from_db_str("root"), None);
+        for source in [RoleSource::Manual, RoleSource::Telegram] {
+            assert_eq!(RoleSource::from_db_str(source.as_db_str()), Some(source));
+        }
+    }
+
+    #[test]
+    fn permission_matrix() {
+        use ModeratorRole::*;
+        use Permission::*;
+
+        assert!(Viewer.allows(ViewReports));
+        assert!(!Viewer.allows(PostReport));
+        assert!(!Viewer.allows(RequestSummary));
+        assert!(!Viewer.allows(Ban));
+
+        assert!(Moderator.allows(Ban));
+        assert!(Moderator.allows(Verify));
+        assert!(Moderator.allows(RequestSummary));
+        assert!(!Moderator.allows(EditConfig));
+        assert!(!Moderator.allows(ManageModerators));
+
+        assert!(Admin.allows(EditConfig));
+        assert!(Admin.allows(ManageModerators));
+        assert!(!Admin.allows(EditAiConfig));
+
+        assert!(Owner.allows(EditAiConfig));
+    }
+
+    #[test]
+    fn manage_only_strictly_lower_roles() {
+        use ModeratorRole::*;
+
+        assert!(Owner.can_manage(Admin));
+        assert!(!Owner.can_manage(Owner));
+        assert!(Admin.can_manage(Moderator));
+        assert!(Admin.can_manage(Viewer));
+        assert!(!Admin.can_manage(Admin));
+        assert!(!Moderator.can_manage(Viewer));
+    }
+
+    #[test]
+    fn config_field_permissions() {
+        assert_eq!(
+            Permission::for_config_field("report_hour"),
+            Some(Permission::EditConfig)
+        );
+        assert_eq!(
+            Permission::for_config_field("openai_api_key"),
+            Some(Permission::EditAiConfig)
+        );
+        assert_eq!(
+            Permission::for_config_field("llm_base_url"),
+            Some(Permission::EditAiConfig)
+        );
+        assert_eq!(Permission::for_config_field("chat_id"), None);
+        assert_eq!(Permission::for_config_field("updated_at"), None);
+    }
+}
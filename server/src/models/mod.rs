@@ -1,16 +1,24 @@
 //! Database models (SQLx) and API DTOs (Serde). Populated alongside the services
 //! that own each table — see `server/docs/database.md` for the schema.
 
+pub mod appeal;
 pub mod captcha_challenge;
+pub mod chat_config;
+pub mod chat_moderator;
 pub mod daily_stats;
 pub mod moderation_action;
 pub mod report;
 pub mod report_message;
 pub mod verified_user;
 
+pub use appeal::{Appeal, AppealStatus};
 pub use captcha_challenge::CaptchaChallenge;
+pub use chat_moderator::{ChatModerator, ModeratorRole, Permission, RoleSource};
 pub use daily_stats::Metric;
 pub use moderation_action::{ActorKind, ModerationAction, ModerationActionKind};
-pub use report::{CaptchaCounts, DailyPoint, ReportData, TopPhrase};
+pub use report::{
+    BanReason, CaptchaCounts, DailyPoint, PeriodTotals, ReportData, ReportPeriod, RollupData,
+    SolveRatePoint, TopPhrase,
+};
 pub use report_message::{ReportKind, ReportMessage};
 pub use verified_user::VerifiedUser;
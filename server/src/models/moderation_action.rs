@@ -3,7 +3,8 @@
 //! `action` and `actor_kind` are stored as `TEXT` with CHECK constraints — we
 //! map them to Rust enums and round-trip via `as_db_str` / `from_db_str`. The
 //! list is the full M1 set (initial seven plus `captcha_expired`,
-//! `captcha_failed`, `kick`).
+//! `captcha_failed`, `kick`) plus `role_grant` / `role_revoke` for
+//! `chat_moderators` changes.
 
 use chrono::{DateTime, Utc};
 use sqlx::FromRow;
@@ -21,6 +22,10 @@ pub enum ModerationActionKind {
     CaptchaExpired,
     CaptchaFailed,
     Kick,
+    /// A `chat_moderators` row was created or re-roled; `reason` names the
+    /// new role.
+    RoleGrant,
+    RoleRevoke,
 }
 
 impl ModerationActionKind {
@@ -36,6 +41,26 @@ impl ModerationActionKind {
             Self::CaptchaExpired => "captcha_expired",
             Self::CaptchaFailed => "captcha_failed",
             Self::Kick => "kick",
+            Self::RoleGrant => "role_grant",
+            Self::RoleRevoke => "role_revoke",
+        }
+    }
+
+    pub fn from_db_str(s: &str) -> Option<Self> {
+        match s {
+            "ban" => Some(Self::Ban),
+            "unban" => Some(Self::Unban),
+            "mute" => Some(Self::Mute),
+            "unmute" => Some(Self::Unmute),
+            "delete" => Some(Self::Delete),
+            "verify" => Some(Self::Verify),
+            "unverify" => Some(Self::Unverify),
+            "captcha_expired" => Some(Self::CaptchaExpired),
+            "captcha_failed" => Some(Self::CaptchaFailed),
+            "kick" => Some(Self::Kick),
+            "role_grant" => Some(Self::RoleGrant),
+            "role_revoke" => Some(Self::RoleRevoke),
+            _ => None,
         }
     }
 }
@@ -53,6 +78,14 @@ impl ActorKind {
             Self::Moderator => "moderator",
         }
     }
+
+    pub fn from_db_str(s: &str) -> Option<Self> {
+        match s {
+            "bot" => Some(Self::Bot),
+            "moderator" => Some(Self::Moderator),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, FromRow)]
@@ -67,3 +100,96 @@ pub struct ModerationAction {
     pub reason: Option<String>,
     pub created_at: DateTime<Utc>,
 }
+
+/// What of a ledger `reason` may leave the server — to webhook consumers or
+/// the dashboard's live feed. Free text passes as-is; a spam verdict's
+/// `reason_json` is cut down to its `matched_rules` and `score`, so the
+/// `excerpt` of the user's message never does.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PublicReason {
+    pub reason: Option<String>,
+    pub matched_rules: Option<Vec<String>>,
+    pub score: Option<f64>,
+}
+
+impl PublicReason {
+    pub fn from_ledger(reason: Option<&str>) -> Self {
+        let verdict = reason
+            .filter(|r| r.starts_with('{'))
+            .and_then(|r| serde_json::from_str::<serde_json::Value>(r).ok());
+        match verdict {
+            Some(v) => Self::from_verdict(&v),
+            None => Self {
+                reason: reason.map(str::to_owned),
+                ..Self::default()
+            },
+        }
+    }
+
+    pub fn from_verdict(verdict: &serde_json::Value) -> Self {
+        Self {
+            reason: None,
+            matched_rules: verdict["matched_rules"].as_array().map(|rules| {
+                rules
+                    .iter()
+                    .filter_map(|r| r.as_str().map(str::to_owned))
+                    .collect()
+            }),
+            score: verdict["score"].as_f64(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn db_strings_round_trip() {
+        use ModerationActionKind::*;
+        for kind in [
+            Ban,
+            Unban,
+            Mute,
+            Unmute,
+            Delete,
+            Verify,
+            Unverify,
+            CaptchaExpired,
+            CaptchaFailed,
+            Kick,
+            RoleGrant,
+            RoleRevoke,
+        ] {
+            assert_eq!(
+                ModerationActionKind::from_db_str(kind.as_db_str()),
+                Some(kind)
+            );
+        }
+        for kind in [ActorKind::Bot, ActorKind::Moderator] {
+            assert_eq!(ActorKind::from_db_str(kind.as_db_str()), Some(kind));
+        }
+        assert_eq!(ModerationActionKind::from_db_str("Ban"), None);
+        assert_eq!(ActorKind::from_db_str("admin"), None);
+    }
+
+    #[test]
+    fn public_reason_drops_the_excerpt() {
+        let verdict = r#"{"matched_rules":["ngram"],"score":3.5,"excerpt":"buy now"}"#;
+        assert_eq!(
+            PublicReason::from_ledger(Some(verdict)),
+            PublicReason {
+                reason: None,
+                matched_rules: Some(vec!["ngram".into()]),
+                score: Some(3.5),
+            }
+        );
+        // Free text, including text that only looks like JSON, is kept.
+        for text in ["raid", "{spam} flood"] {
+            let public = PublicReason::from_ledger(Some(text));
+            assert_eq!(public.reason.as_deref(), Some(text));
+            assert_eq!(public.matched_rules, None);
+        }
+        assert_eq!(PublicReason::from_ledger(None), PublicReason::default());
+    }
+}
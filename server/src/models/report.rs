@@ -3,10 +3,15 @@
 //! Built by [`crate::services::report_service::ReportService::aggregate`]
 //! from `daily_stats`, `moderation_actions` and `spam_messages`. No I/O once
 //! constructed — the renderer and chart are pure functions of this struct.
+//! Weekly / monthly rollups carry the extra comparison data in
+//! [`ReportData::rollup`]. Serialises as-is for the dashboard's
+//! `reports/generate` JSON output.
 
 use chrono::{DateTime, NaiveDate, Utc};
+use serde::Serialize;
+use utoipa::ToSchema;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, ToSchema)]
 pub struct ReportData {
     pub chat_id: i64,
     /// Inclusive lower bound of the aggregation window (UTC).
@@ -26,18 +31,116 @@ pub struct ReportData {
     /// Top-N spam phrases by `hit_count`, descending. Source is the global
     /// `spam_messages` table (xxh3-keyed, not chat-scoped). The renderer
     /// MUST escape `text` for MarkdownV2 — phrase samples are user-supplied.
-    /// TODO(M6): redact via the to-be-introduced `redact::phrase` helper
-    /// before exposing in any public-facing surface; for now the in-chat
-    /// report shows the raw match.
+    /// The in-chat report shows the raw match; the public `/report/{slug}`
+    /// page runs it through `public_report::redact` first.
     pub top_phrases: Vec<TopPhrase>,
 
     /// `messages_seen` for the last 7 calendar days (server-UTC), oldest
     /// first. Used by the renderer for the sparkline. Length is always 7;
     /// missing days are zero.
     pub last_7_days_messages: Vec<DailyPoint>,
+
+    /// Period-over-period data for weekly / monthly reports. `None` for the
+    /// daily report and the on-demand / `/stats` windows.
+    pub rollup: Option<RollupData>,
+}
+
+impl ReportData {
+    /// Which scheduled report this is; keys the `report_messages` rows.
+    pub fn period(&self) -> ReportPeriod {
+        self.rollup
+            .as_ref()
+            .map_or(ReportPeriod::Daily, |r| r.period)
+    }
+
+    pub fn totals(&self) -> PeriodTotals {
+        PeriodTotals {
+            messages_seen: self.messages_seen,
+            messages_deleted: self.messages_deleted,
+            users_verified: self.users_verified,
+            users_banned: self.users_banned,
+            captcha: self.captcha,
+        }
+    }
+}
+
+/// Report cadence. `Daily` covers one chat-local day; the rollups cover the
+/// 7 days / one month ending with the report day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportPeriod {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+impl ReportPeriod {
+    pub fn as_db_str(self) -> &'static str {
+        match self {
+            Self::Daily => "daily",
+            Self::Weekly => "weekly",
+            Self::Monthly => "monthly",
+        }
+    }
+
+    /// Length of the `messages_seen` history the chart plots for a rollup.
+    pub fn history_days(self) -> i64 {
+        match self {
+            Self::Daily => 7,
+            Self::Weekly => 30,
+            Self::Monthly => 90,
+        }
+    }
 }
 
-#[derive(Debug, Clone, Copy, Default)]
+/// The rollup-only half of a weekly / monthly [`ReportData`].
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct RollupData {
+    pub period: ReportPeriod,
+    /// Same counters over the equally long window right before this one.
+    pub previous: PeriodTotals,
+    /// `messages_seen` per day for [`ReportPeriod::history_days`] days
+    /// ending with the report window, oldest first, zero-padded.
+    pub history: Vec<DailyPoint>,
+    /// Most frequent ban reasons in the window: spam rules from
+    /// `reason.matched_rules`, or the moderator's free-text reason.
+    pub top_ban_reasons: Vec<BanReason>,
+    /// Captcha issued / solved per 7-day bucket over the history window,
+    /// oldest first.
+    pub captcha_trend: Vec<SolveRatePoint>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, ToSchema)]
+pub struct PeriodTotals {
+    pub messages_seen: i64,
+    pub messages_deleted: i64,
+    pub users_verified: i64,
+    pub users_banned: i64,
+    pub captcha: CaptchaCounts,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct BanReason {
+    /// User-supplied for moderator bans — the renderer MUST escape it.
+    pub reason: String,
+    pub count: i64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, ToSchema)]
+pub struct SolveRatePoint {
+    /// First day of the 7-day bucket.
+    pub week_start: NaiveDate,
+    pub issued: i64,
+    pub solved: i64,
+}
+
+impl SolveRatePoint {
+    pub fn solve_rate(&self) -> Option<f64> {
+        solve_rate(self.issued, self.solved)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, ToSchema)]
 pub struct CaptchaCounts {
     pub issued: i64,
     pub solved: i64,
@@ -48,15 +151,24 @@ impl CaptchaCounts {
     pub fn total(&self) -> i64 {
         self.issued + self.solved + self.expired
     }
+
+    /// `solved / issued`, `None` when nothing was issued.
+    pub fn solve_rate(&self) -> Option<f64> {
+        solve_rate(self.issued, self.solved)
+    }
+}
+
+fn solve_rate(issued: i64, solved: i64) -> Option<f64> {
+    (issued > 0).then(|| (solved as f64 / issued as f64).min(1.0))
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, ToSchema)]
 pub struct TopPhrase {
     pub text: String,
     pub hits: i64,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, ToSchema)]
 pub struct DailyPoint {
     pub date: NaiveDate,
     pub messages: i64,
@@ -5,11 +5,13 @@
 //! ```text
 //! report_messages (chat_id, report_date, kind, telegram_message_id, generated_at)
 //!   PRIMARY KEY (chat_id, report_date, kind)
-//!   kind ∈ {'daily_text', 'daily_photo'}
+//!   kind ∈ {'{daily,weekly,monthly}_text', '{daily,weekly,monthly}_photo'}
 //! ```
 //!
-//! `kind` discriminates the two messages a daily report posts (the MarkdownV2
-//! text block and the WebP chart photo). Replace-on-redo: when the same chat
+//! `kind` discriminates the two messages a report posts (the MarkdownV2 text
+//! block and the WebP chart photo) per [`ReportPeriod`]. Every reader and
+//! writer is scoped to one period, so a weekly rollup posted on the same day
+//! as the daily report has its own pair. Replace-on-redo: when the same chat
 //! re-runs on the same `report_date`, [`prior_today`] returns both rows, the
 //! caller `delete_message`s them via the bot, [`delete_for_day`] clears the
 //! ledger, and [`record`] inserts the fresh pair.
@@ -18,6 +20,8 @@ use anyhow::{Context, Result};
 use chrono::NaiveDate;
 use sqlx::PgPool;
 
+use crate::models::report::ReportPeriod;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ReportKind {
     Text,
@@ -25,10 +29,14 @@ pub enum ReportKind {
 }
 
 impl ReportKind {
-    pub fn as_db_str(self) -> &'static str {
-        match self {
-            Self::Text => "daily_text",
-            Self::Photo => "daily_photo",
+    pub fn as_db_str(self, period: ReportPeriod) -> &'static str {
+        match (period, self) {
+            (ReportPeriod::Daily, Self::Text) => "daily_text",
+            (ReportPeriod::Daily, Self::Photo) => "daily_photo",
+            (ReportPeriod::Weekly, Self::Text) => "weekly_text",
+            (ReportPeriod::Weekly, Self::Photo) => "weekly_photo",
+            (ReportPeriod::Monthly, Self::Text) => "monthly_text",
+            (ReportPeriod::Monthly, Self::Photo) => "monthly_photo",
         }
     }
 }
@@ -48,6 +56,7 @@ pub async fn record(
     pool: &PgPool,
     chat_id: i64,
     report_date: NaiveDate,
+    period: ReportPeriod,
     kind: ReportKind,
     telegram_message_id: i32,
 ) -> Result<()> {
@@ -61,7 +70,7 @@ pub async fn record(
         "#,
         chat_id,
         report_date,
-        kind.as_db_str(),
+        kind.as_db_str(period),
         telegram_message_id,
     )
     .execute(pool)
@@ -70,21 +79,25 @@ pub async fn record(
     Ok(())
 }
 
-/// All report rows for `(chat_id, report_date)`. Returns at most two entries
-/// (text + photo). Empty Vec when no report has been posted for that day.
+/// All `period` report rows for `(chat_id, report_date)`. Returns at most
+/// two entries (text + photo). Empty Vec when no report of that period has
+/// been posted for that day.
 pub async fn prior_today(
     pool: &PgPool,
     chat_id: i64,
     report_date: NaiveDate,
+    period: ReportPeriod,
 ) -> Result<Vec<ReportMessage>> {
     let rows = sqlx::query!(
         r#"
         SELECT kind, telegram_message_id
         FROM report_messages
-        WHERE chat_id = $1 AND report_date = $2
+        WHERE chat_id = $1 AND report_date = $2 AND kind IN ($3, $4)
         "#,
         chat_id,
         report_date,
+        ReportKind::Text.as_db_str(period),
+        ReportKind::Photo.as_db_str(period),
     )
     .fetch_all(pool)
     .await
@@ -93,8 +106,8 @@ pub async fn prior_today(
     let mut out = Vec::with_capacity(rows.len());
     for r in rows {
         let kind = match r.kind.as_str() {
-            "daily_text" => ReportKind::Text,
-            "daily_photo" => ReportKind::Photo,
+            k if k == ReportKind::Text.as_db_str(period) => ReportKind::Text,
+            k if k == ReportKind::Photo.as_db_str(period) => ReportKind::Photo,
             other => {
                 tracing::warn!(kind = %other, "unknown report_messages.kind, ignoring");
                 continue;
@@ -108,17 +121,24 @@ pub async fn prior_today(
     Ok(out)
 }
 
-/// Drop every row for `(chat_id, report_date)`. Called after the bot has
-/// (best-effort) deleted the corresponding Telegram messages, just before
-/// re-inserting the new pair.
-pub async fn delete_for_day(pool: &PgPool, chat_id: i64, report_date: NaiveDate) -> Result<()> {
+/// Drop every `period` row for `(chat_id, report_date)`. Called after the
+/// bot has (best-effort) deleted the corresponding Telegram messages, just
+/// before re-inserting the new pair.
+pub async fn delete_for_day(
+    pool: &PgPool,
+    chat_id: i64,
+    report_date: NaiveDate,
+    period: ReportPeriod,
+) -> Result<()> {
     sqlx::query!(
         r#"
         DELETE FROM report_messages
-        WHERE chat_id = $1 AND report_date = $2
+        WHERE chat_id = $1 AND report_date = $2 AND kind IN ($3, $4)
         "#,
         chat_id,
         report_date,
+        ReportKind::Text.as_db_str(period),
+        ReportKind::Photo.as_db_str(period),
     )
     .execute(pool)
     .await
@@ -126,7 +146,7 @@ pub async fn delete_for_day(pool: &PgPool, chat_id: i64, report_date: NaiveDate)
     Ok(())
 }
 
-/// True iff **both** the text and the photo rows are present for
+/// True iff **both** the `period` text and photo rows are present for
 /// `(chat_id, report_date)`. The scheduler uses this to skip days that have
 /// already been fully posted; if `deliver()` succeeded on the text message
 /// but failed on the chart (e.g. spawn-blocking error, send_photo timeout),
@@ -137,16 +157,19 @@ pub async fn already_posted_today(
     pool: &PgPool,
     chat_id: i64,
     report_date: NaiveDate,
+    period: ReportPeriod,
 ) -> Result<bool> {
     let count = sqlx::query_scalar!(
         r#"
         SELECT COUNT(*)::BIGINT AS "n!"
         FROM report_messages
         WHERE chat_id = $1 AND report_date = $2
-          AND kind IN ('daily_text', 'daily_photo')
+          AND kind IN ($3, $4)
         "#,
         chat_id,
         report_date,
+        ReportKind::Text.as_db_str(period),
+        ReportKind::Photo.as_db_str(period),
     )
     .fetch_one(pool)
     .await
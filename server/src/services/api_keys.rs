@@ -0,0 +1,500 @@
+//! Envelope encryption for `chat_config.openai_api_key` and
+//! `webhook_endpoints.secret`.
+//!
+//! Each value is sealed with its own random 256-bit data key (AES-256-GCM,
+//! AAD binds the ciphertext to its column and row — the `chat_id`, or the
+//! endpoint id); the data key is in turn wrapped with the master key from
+//! `CONFIG_SECRETS_KEY`. The columns hold
+//!
+//! ```text
+//! v1.<kid>.<b64url(nonce ‖ wrapped data key ‖ tag)>.<b64url(nonce ‖ ciphertext ‖ tag)>
+//! ```
+//!
+//! where `kid` is the first 4 bytes of SHA-256(master key), hex — enough to
+//! pick the right master key during rotation, useless to an attacker.
+//!
+//! Rotation only re-wraps data keys: set the new key as `CONFIG_SECRETS_KEY`,
+//! the old one as `CONFIG_SECRETS_KEY_PREVIOUS`, run `vixen-server
+//! rotate-secrets-key` ([`rotate`]), then drop the previous key. The running
+//! server decrypts with either key in the meantime.
+//!
+//! Values without the `v1.` prefix are legacy plaintext: [`ApiKeyCipher::open`]
+//! passes them through (with a warning) and [`rotate`] encrypts them.
+
+use anyhow::{Context, Result, anyhow, bail};
+use aws_lc_rs::aead::{AES_256_GCM, Aad, LessSafeKey, NONCE_LEN, Nonce, UnboundKey};
+use aws_lc_rs::rand::{SecureRandom, SystemRandom};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as B64;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::config::{Config, SecretsKey};
+
+const PREFIX: &str = "v1";
+const KEY_LEN: usize = 32;
+/// AAD for the wrapped data key.
+const DEK_AAD: &[u8] = b"vixen:dek:v1";
+/// Characters of the plaintext revealed by [`mask`].
+const MASK_SUFFIX_CHARS: usize = 4;
+/// Keys shorter than this are masked completely.
+const MASK_MIN_CHARS: usize = 12;
+
+struct MasterKey {
+    kid: String,
+    key: LessSafeKey,
+}
+
+impl MasterKey {
+    fn new(secret: &SecretsKey) -> Result<Self> {
+        let bytes = secret
+            .decode()
+            .context("secrets key must be 32 bytes, base64-encoded")?;
+        let digest = Sha256::digest(bytes);
+        let kid = digest[..4].iter().map(|b| format!("{b:02x}")).collect();
+        Ok(Self {
+            kid,
+            key: aead_key(&bytes)?,
+        })
+    }
+}
+
+/// The column and row a sealed value belongs to. Part of the AAD, so a
+/// value copied into another column or row fails to open.
+#[derive(Debug, Clone, Copy)]
+enum Slot {
+    OpenAiKey(i64),
+    WebhookSecret(Uuid),
+}
+
+impl Slot {
+    fn column(self) -> &'static str {
+        match self {
+            Self::OpenAiKey(_) => "chat_config.openai_api_key",
+            Self::WebhookSecret(_) => "webhook_endpoints.secret",
+        }
+    }
+
+    fn aad(self) -> Vec<u8> {
+        match self {
+            Self::OpenAiKey(chat_id) => format!("{}:{chat_id}", self.column()),
+            Self::WebhookSecret(id) => format!("{}:{id}", self.column()),
+        }
+        .into_bytes()
+    }
+}
+
+/// Seals and opens per-chat API keys and webhook secrets. Built once from [`Config`]; cheap to
+/// share behind an `Arc`.
+pub struct ApiKeyCipher {
+    current: Option<MasterKey>,
+    previous: Option<MasterKey>,
+    rng: SystemRandom,
+}
+
+/// What [`ApiKeyCipher::reseal`] did to one stored value.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Reseal {
+    /// Already wrapped with the current master key.
+    Current,
+    /// Data key re-wrapped with the current master key.
+    Rewrapped(String),
+    /// Legacy plaintext, now sealed.
+    Encrypted(String),
+}
+
+impl ApiKeyCipher {
+    pub fn new(current: Option<&SecretsKey>, previous: Option<&SecretsKey>) -> Result<Self> {
+        Ok(Self {
+            current: current
+                .map(MasterKey::new)
+                .transpose()
+                .context("CONFIG_SECRETS_KEY")?,
+            previous: previous
+                .map(MasterKey::new)
+                .transpose()
+                .context("CONFIG_SECRETS_KEY_PREVIOUS")?,
+            rng: SystemRandom::new(),
+        })
+    }
+
+    pub fn from_config(config: &Config) -> Result<Self> {
+        Self::new(
+            config.secrets_key.as_ref(),
+            config.secrets_key_previous.as_ref(),
+        )
+    }
+
+    /// `true` when new keys can be stored.
+    pub fn can_seal(&self) -> bool {
+        self.current.is_some()
+    }
+
+    /// Encrypt `plaintext` for `chat_id` under the current master key.
+    pub fn seal(&self, chat_id: i64, plaintext: &str) -> Result<String> {
+        self.seal_slot(Slot::OpenAiKey(chat_id), plaintext)
+    }
+
+    /// Decrypt a stored value. Legacy plaintext passes through unchanged.
+    pub fn open(&self, chat_id: i64, stored: &str) -> Result<String> {
+        self.open_slot(Slot::OpenAiKey(chat_id), stored)
+    }
+
+    /// Bring one stored value onto the current master key.
+    pub fn reseal(&self, chat_id: i64, stored: &str) -> Result<Reseal> {
+        self.reseal_slot(Slot::OpenAiKey(chat_id), stored)
+    }
+
+    /// [`Self::seal`] for the signing secret of webhook endpoint `endpoint_id`.
+    pub fn seal_webhook_secret(&self, endpoint_id: Uuid, plaintext: &str) -> Result<String> {
+        self.seal_slot(Slot::WebhookSecret(endpoint_id), plaintext)
+    }
+
+    /// [`Self::open`] for the signing secret of webhook endpoint `endpoint_id`.
+    pub fn open_webhook_secret(&self, endpoint_id: Uuid, stored: &str) -> Result<String> {
+        self.open_slot(Slot::WebhookSecret(endpoint_id), stored)
+    }
+
+    fn seal_slot(&self, slot: Slot, plaintext: &str) -> Result<String> {
+        let master = self
+            .current
+            .as_ref()
+            .ok_or_else(|| anyhow!("CONFIG_SECRETS_KEY is not set"))?;
+        let mut dek = [0u8; KEY_LEN];
+        self.rng
+            .fill(&mut dek)
+            .map_err(|_| anyhow!("system RNG failed"))?;
+        let data = self.encrypt(&aead_key(&dek)?, &slot.aad(), plaintext.as_bytes())?;
+        let wrapped = self.encrypt(&master.key, DEK_AAD, &dek)?;
+        Ok(format!(
+            "{PREFIX}.{}.{}.{}",
+            master.kid,
+            B64.encode(wrapped),
+            B64.encode(data)
+        ))
+    }
+
+    fn open_slot(&self, slot: Slot, stored: &str) -> Result<String> {
+        let Some(envelope) = Envelope::parse(stored)? else {
+            warn!(
+                ?slot,
+                "{} stored as plaintext; run rotate-secrets-key",
+                slot.column()
+            );
+            return Ok(stored.to_owned());
+        };
+        let dek = self.unwrap_dek(&envelope)?;
+        let plain = decrypt(&aead_key(&dek)?, &slot.aad(), &envelope.data)
+            .with_context(|| format!("decrypt {}", slot.column()))?;
+        String::from_utf8(plain).with_context(|| format!("{} is not UTF-8", slot.column()))
+    }
+
+    fn reseal_slot(&self, slot: Slot, stored: &str) -> Result<Reseal> {
+        let master = self
+            .current
+            .as_ref()
+            .ok_or_else(|| anyhow!("CONFIG_SECRETS_KEY is not set"))?;
+        let Some(envelope) = Envelope::parse(stored)? else {
+            return Ok(Reseal::Encrypted(self.seal_slot(slot, stored)?));
+        };
+        if envelope.kid == master.kid {
+            return Ok(Reseal::Current);
+        }
+        let dek = self.unwrap_dek(&envelope)?;
+        let wrapped = self.encrypt(&master.key, DEK_AAD, &dek)?;
+        Ok(Reseal::Rewrapped(format!(
+            "{PREFIX}.{}.{}.{}",
+            master.kid,
+            B64.encode(wrapped),
+            B64.encode(&envelope.data)
+        )))
+    }
+
+    fn unwrap_dek(&self, envelope: &Envelope) -> Result<Vec<u8>> {
+        let master = [self.current.as_ref(), self.previous.as_ref()]
+            .into_iter()
+            .flatten()
+            .find(|m| m.kid == envelope.kid)
+            .ok_or_else(|| anyhow!("no secrets key with id {} configured", envelope.kid))?;
+        decrypt(&master.key, DEK_AAD, &envelope.wrapped_dek).context("unwrap data key")
+    }
+
+    /// `nonce ‖ ciphertext ‖ tag`.
+    fn encrypt(&self, key: &LessSafeKey, aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>> {
+        let mut nonce = [0u8; NONCE_LEN];
+        self.rng
+            .fill(&mut nonce)
+            .map_err(|_| anyhow!("system RNG failed"))?;
+        let mut in_out = plaintext.to_vec();
+        key.seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(aad),
+            &mut in_out,
+        )
+        .map_err(|_| anyhow!("AES-GCM seal failed"))?;
+        let mut out = nonce.to_vec();
+        out.extend_from_slice(&in_out);
+        Ok(out)
+    }
+}
+
+struct Envelope {
+    kid: String,
+    wrapped_dek: Vec<u8>,
+    data: Vec<u8>,
+}
+
+impl Envelope {
+    /// `Ok(None)` for legacy plaintext; `Err` for a malformed envelope.
+    fn parse(stored: &str) -> Result<Option<Self>> {
+        let Some(rest) = stored
+            .strip_prefix(PREFIX)
+            .and_then(|r| r.strip_prefix('.'))
+        else {
+            return Ok(None);
+        };
+        let mut parts = rest.split('.');
+        let (Some(kid), Some(wrapped), Some(data), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            bail!("malformed secret envelope");
+        };
+        Ok(Some(Self {
+            kid: kid.to_owned(),
+            wrapped_dek: B64.decode(wrapped).context("envelope data key")?,
+            data: B64.decode(data).context("envelope ciphertext")?,
+        }))
+    }
+}
+
+fn aead_key(bytes: &[u8]) -> Result<LessSafeKey> {
+    let unbound = UnboundKey::new(&AES_256_GCM, bytes).map_err(|_| anyhow!("bad AES-256 key"))?;
+    Ok(LessSafeKey::new(unbound))
+}
+
+fn decrypt(key: &LessSafeKey, aad: &[u8], sealed: &[u8]) -> Result<Vec<u8>> {
+    if sealed.len() < NONCE_LEN {
+        bail!("ciphertext too short");
+    }
+    let (nonce, body) = sealed.split_at(NONCE_LEN);
+    let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| anyhow!("bad nonce"))?;
+    let mut in_out = body.to_vec();
+    let plain = key
+        .open_in_place(nonce, Aad::from(aad), &mut in_out)
+        .map_err(|_| anyhow!("authentication failed (wrong key or tampered value)"))?;
+    Ok(plain.to_vec())
+}
+
+/// The only form an API key ever leaves the server in: `****` plus the last
+/// few characters, or just `****` for short values.
+pub fn mask(plaintext: &str) -> String {
+    let chars = plaintext.chars().count();
+    if chars < MASK_MIN_CHARS {
+        return "****".to_owned();
+    }
+    let suffix: String = plaintext.chars().skip(chars - MASK_SUFFIX_CHARS).collect();
+    format!("****{suffix}")
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct RotationReport {
+    pub rewrapped: u64,
+    pub encrypted: u64,
+    pub unchanged: u64,
+}
+
+/// Re-seal every stored key and webhook secret under the current master
+/// key, in one transaction: a single undecryptable row aborts the whole run
+/// and leaves both tables untouched.
+pub async fn rotate(pool: &PgPool, cipher: &ApiKeyCipher) -> Result<RotationReport> {
+    let mut tx = pool.begin().await.context("begin rotation tx")?;
+    let rows = sqlx::query!(
+        r#"
+        SELECT chat_id, openai_api_key AS "openai_api_key!"
+        FROM chat_config
+        WHERE openai_api_key IS NOT NULL
+        ORDER BY chat_id
+        FOR UPDATE
+        "#
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .context("SELECT chat_config keys")?;
+
+    let mut report = RotationReport::default();
+    for row in rows {
+        let resealed = cipher
+            .reseal(row.chat_id, &row.openai_api_key)
+            .with_context(|| format!("chat {}", row.chat_id))?;
+        if let Some(value) = report.count(resealed) {
+            store(&mut *tx, row.chat_id, Some(&value)).await?;
+        }
+    }
+
+    let endpoints = sqlx::query!(
+        r#"
+        SELECT id, secret
+        FROM webhook_endpoints
+        ORDER BY id
+        FOR UPDATE
+        "#
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .context("SELECT webhook_endpoints secrets")?;
+    for endpoint in endpoints {
+        let resealed = cipher
+            .reseal_slot(Slot::WebhookSecret(endpoint.id), &endpoint.secret)
+            .with_context(|| format!("webhook endpoint {}", endpoint.id))?;
+        if let Some(value) = report.count(resealed) {
+            sqlx::query!(
+                "UPDATE webhook_endpoints SET secret = $2 WHERE id = $1",
+                endpoint.id,
+                value,
+            )
+            .execute(&mut *tx)
+            .await
+            .context("UPDATE webhook_endpoints.secret")?;
+        }
+    }
+    tx.commit().await.context("commit rotation tx")?;
+    info!(?report, "secrets key rotation complete");
+    Ok(report)
+}
+
+impl RotationReport {
+    /// Tally one [`Reseal`]; the value to write back, if any.
+    fn count(&mut self, resealed: Reseal) -> Option<String> {
+        match resealed {
+            Reseal::Current => {
+                self.unchanged += 1;
+                None
+            }
+            Reseal::Rewrapped(v) => {
+                self.rewrapped += 1;
+                Some(v)
+            }
+            Reseal::Encrypted(v) => {
+                self.encrypted += 1;
+                Some(v)
+            }
+        }
+    }
+}
+
+/// The stored (sealed or legacy plaintext) value. Outer `None` = no such
+/// chat.
+pub async fn load(pool: &PgPool, chat_id: i64) -> Result<Option<Option<String>>> {
+    let row = sqlx::query_scalar!(
+        "SELECT openai_api_key FROM chat_config WHERE chat_id = $1",
+        chat_id,
+    )
+    .fetch_optional(pool)
+    .await
+    .context("SELECT chat_config.openai_api_key")?;
+    Ok(row)
+}
+
+/// Write an already-sealed value (or clear it with `None`). `false` when
+/// the chat has no `chat_config` row.
+pub async fn store<'e, E>(executor: E, chat_id: i64, sealed: Option<&str>) -> Result<bool>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let result = sqlx::query!(
+        "UPDATE chat_config SET openai_api_key = $2 WHERE chat_id = $1",
+        chat_id,
+        sealed,
+    )
+    .execute(executor)
+    .await
+    .context("UPDATE chat_config.openai_api_key")?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// `true` when `stored` is an envelope rather than legacy plaintext.
+pub fn is_sealed(stored: &str) -> bool {
+    matches!(Envelope::parse(stored), Ok(Some(_)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(byte: u8) -> SecretsKey {
+        SecretsKey::new(base64::engine::general_purpose::STANDARD.encode([byte; KEY_LEN]))
+    }
+
+    #[test]
+    fn seal_then_open_round_trips() {
+        let cipher = ApiKeyCipher::new(Some(&key(1)), None).unwrap();
+        let sealed = cipher.seal(-1001, "sk-live-abcdef123456").unwrap();
+        assert!(sealed.starts_with("v1."));
+        assert!(!sealed.contains("sk-live"));
+        assert_eq!(cipher.open(-1001, &sealed).unwrap(), "sk-live-abcdef123456");
+    }
+
+    #[test]
+    fn ciphertext_is_bound_to_chat() {
+        let cipher = ApiKeyCipher::new(Some(&key(1)), None).unwrap();
+        let sealed = cipher.seal(-1001, "sk-live-abcdef123456").unwrap();
+        assert!(cipher.open(-1002, &sealed).is_err());
+    }
+
+    #[test]
+    fn webhook_secret_is_bound_to_its_endpoint() {
+        let cipher = ApiKeyCipher::new(Some(&key(1)), None).unwrap();
+        let endpoint = Uuid::new_v4();
+        let sealed = cipher.seal_webhook_secret(endpoint, "whsec_abc").unwrap();
+        assert_eq!(
+            cipher.open_webhook_secret(endpoint, &sealed).unwrap(),
+            "whsec_abc"
+        );
+        assert!(cipher.open_webhook_secret(Uuid::new_v4(), &sealed).is_err());
+        // Nor can it be passed off as a chat's API key.
+        assert!(cipher.open(-1001, &sealed).is_err());
+    }
+
+    #[test]
+    fn legacy_plaintext_passes_through() {
+        let cipher = ApiKeyCipher::new(None, None).unwrap();
+        assert_eq!(cipher.open(-1001, "sk-test").unwrap(), "sk-test");
+        assert!(cipher.seal(-1001, "sk-test").is_err());
+    }
+
+    #[test]
+    fn reseal_rewraps_under_the_new_key() {
+        let old = ApiKeyCipher::new(Some(&key(1)), None).unwrap();
+        let sealed = old.seal(-1001, "sk-live-abcdef123456").unwrap();
+
+        let rotating = ApiKeyCipher::new(Some(&key(2)), Some(&key(1))).unwrap();
+        let Reseal::Rewrapped(rewrapped) = rotating.reseal(-1001, &sealed).unwrap() else {
+            panic!("expected a re-wrap");
+        };
+        assert_eq!(rotating.reseal(-1001, &rewrapped).unwrap(), Reseal::Current);
+
+        let new_only = ApiKeyCipher::new(Some(&key(2)), None).unwrap();
+        assert_eq!(
+            new_only.open(-1001, &rewrapped).unwrap(),
+            "sk-live-abcdef123456"
+        );
+        assert!(new_only.open(-1001, &sealed).is_err());
+    }
+
+    #[test]
+    fn reseal_encrypts_plaintext() {
+        let cipher = ApiKeyCipher::new(Some(&key(1)), None).unwrap();
+        let Reseal::Encrypted(sealed) = cipher.reseal(-1001, "sk-test").unwrap() else {
+            panic!("expected encryption");
+        };
+        assert_eq!(cipher.open(-1001, &sealed).unwrap(), "sk-test");
+    }
+
+    #[test]
+    fn mask_reveals_only_a_suffix() {
+        assert_eq!(mask("sk-proj-abcdefghijkl1234"), "****1234");
+        assert_eq!(mask("short"), "****");
+    }
+}
@@ -0,0 +1,214 @@
+//! Ban appeals: the deep-link payload and the DM that hands it to a banned
+//! user, the moderator notification, and the `va:` callback scheme of its
+//! approve / deny buttons. Storage lives in
+//! `models::appeal`; the Telegram side in `telegram::handlers::appeal`.
+//!
+//! Deep link: `https://t.me/<bot>?start=appeal_<chat_id>`. Telegram only
+//! allows `[A-Za-z0-9_-]` in a start payload, which a negative chat id fits.
+//!
+//! Callback data scheme: `va:{op}:{appeal_id}` where `op` is `ok` (approve)
+//! or `no` (deny) and `appeal_id` is the full UUID (45 bytes total, under
+//! Telegram's 64-byte cap).
+
+use anyhow::{Context, Result};
+use sqlx::PgPool;
+use teloxide::prelude::*;
+use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup, UserId};
+use tracing::debug;
+use uuid::Uuid;
+
+use crate::i18n;
+use crate::models::appeal::Appeal;
+use crate::services::mod_log::parse_reason;
+use crate::services::outbound::{OutboundQueue, Priority};
+use crate::services::report_render::escape;
+use crate::tr;
+
+pub const CALLBACK_PREFIX: &str = "va";
+/// `CALLBACK_PREFIX` plus the separator, for the dispatcher's per-update
+/// filter.
+pub const CALLBACK_PREFIX_WITH_COLON: &str = "va:";
+
+/// `/start` payload prefix of the appeal deep link.
+pub const START_PREFIX: &str = "appeal_";
+
+/// Appeal text longer than this is cut before it reaches moderators.
+pub const MAX_TEXT_CHARS: usize = 1000;
+
+pub fn deep_link(bot_username: &str, chat_id: i64) -> String {
+    format!("https://t.me/{bot_username}?start={START_PREFIX}{chat_id}")
+}
+
+/// DM a just-banned user the appeal link, in the chat's language. A banned
+/// user can no longer read the chat, so this is where they learn the link.
+/// Best-effort: Telegram refuses (403) users who never started the bot.
+pub async fn send_link(
+    bot: &Bot,
+    outbound: &OutboundQueue,
+    pool: &PgPool,
+    bot_username: &str,
+    chat_id: i64,
+    user_id: i64,
+) {
+    let lang = i18n::chat_lang(pool, chat_id).await;
+    let chat = match chat_title(pool, chat_id).await {
+        Ok(Some(title)) => title,
+        _ => chat_id.to_string(),
+    };
+    let text = tr!(
+        lang,
+        "appeal-ban-notice",
+        chat = chat,
+        link = deep_link(bot_username, chat_id)
+    );
+    let request = bot.send_message(UserId(user_id as u64), text);
+    if let Err(e) = outbound.send(user_id, Priority::Background, request).await {
+        debug!(user_id, error = %e, "appeal link DM not delivered");
+    }
+}
+
+/// Chat id from a `/start` payload, `None` for anything that isn't an appeal
+/// link.
+pub fn parse_start(payload: &str) -> Option<i64> {
+    payload.trim().strip_prefix(START_PREFIX)?.parse().ok()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AppealCallback {
+    pub approve: bool,
+    pub appeal_id: Uuid,
+}
+
+pub fn data_for(approve: bool, appeal_id: Uuid) -> String {
+    let op = if approve { "ok" } else { "no" };
+    format!("{CALLBACK_PREFIX}:{op}:{appeal_id}")
+}
+
+pub fn parse_callback(data: &str) -> Option<AppealCallback> {
+    let mut it = data.splitn(3, ':');
+    if it.next()? != CALLBACK_PREFIX {
+        return None;
+    }
+    let approve = match it.next()? {
+        "ok" => true,
+        "no" => false,
+        _ => return None,
+    };
+    let appeal_id = Uuid::parse_str(it.next()?).ok()?;
+    Some(AppealCallback { approve, appeal_id })
+}
+
+pub fn keyboard(appeal_id: Uuid) -> InlineKeyboardMarkup {
+    InlineKeyboardMarkup::new(vec![vec![
+        InlineKeyboardButton::callback("Approve", data_for(true, appeal_id)),
+        InlineKeyboardButton::callback("Deny", data_for(false, appeal_id)),
+    ]])
+}
+
+/// `chat_info_cache.title`, for the notification header.
+pub async fn chat_title(pool: &PgPool, chat_id: i64) -> Result<Option<String>> {
+    sqlx::query_scalar!(
+        r#"SELECT title FROM chat_info_cache WHERE chat_id = $1"#,
+        chat_id,
+    )
+    .fetch_optional(pool)
+    .await
+    .context("SELECT chat_info_cache.title")
+}
+
+/// MarkdownV2 notification for moderators: who appeals which ban, the
+/// original verdict, and the appeal text as a quote.
+pub fn render(appeal: &Appeal, chat_title: Option<&str>, ban_reason: Option<&str>) -> String {
+    let chat = chat_title
+        .map(str::to_string)
+        .unwrap_or_else(|| appeal.chat_id.to_string());
+    let mut out = format!("📨 *Appeal* · {}\n", escape(&chat));
+    out.push_str(&format!(
+        "User: [{id}](tg://user?id={id})\n",
+        id = appeal.user_id
+    ));
+    let reason = ban_reason.map(parse_reason).unwrap_or_default();
+    if !reason.rules.is_empty() {
+        out.push_str(&format!(
+            "Banned by: {}\n",
+            escape(&reason.rules.join(", "))
+        ));
+    } else if let Some(text) = reason.text {
+        out.push_str(&format!("Ban reason: {}\n", escape(&text)));
+    }
+    let text: String = appeal
+        .text
+        .as_deref()
+        .unwrap_or_default()
+        .chars()
+        .take(MAX_TEXT_CHARS)
+        .collect();
+    for line in text.lines() {
+        out.push('>');
+        out.push_str(&escape(line));
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+    use crate::models::appeal::AppealStatus;
+
+    #[test]
+    fn parses_start_payload() {
+        assert_eq!(parse_start("appeal_-1001234567890"), Some(-1001234567890));
+        assert_eq!(parse_start("appeal_"), None);
+        assert_eq!(parse_start("ref_42"), None);
+        assert_eq!(parse_start(""), None);
+        assert_eq!(
+            deep_link("vixen_bot", -10042),
+            "https://t.me/vixen_bot?start=appeal_-10042"
+        );
+    }
+
+    #[test]
+    fn callback_round_trip() {
+        let id = Uuid::new_v4();
+        let data = data_for(true, id);
+        assert!(data.len() <= 64, "Telegram caps callback_data at 64 bytes");
+        assert_eq!(
+            parse_callback(&data),
+            Some(AppealCallback {
+                approve: true,
+                appeal_id: id,
+            })
+        );
+        assert_eq!(
+            parse_callback(&data_for(false, id)).map(|c| c.approve),
+            Some(false)
+        );
+        assert!(parse_callback("va:maybe:00000000-0000-0000-0000-000000000000").is_none());
+        assert!(parse_callback("vl:ub:-100:1").is_none());
+        assert!(parse_callback("va:ok:not-a-uuid").is_none());
+    }
+
+    #[test]
+    fn renders_rules_and_quoted_text() {
+        let appeal = Appeal {
+            id: Uuid::nil(),
+            chat_id: -100,
+            user_id: 4242,
+            action_id: None,
+            text: Some("I'm not a bot.\nPlease!".into()),
+            status: AppealStatus::Pending,
+            submitted_at: Some(Utc::now()),
+            decided_by: None,
+            decided_at: None,
+            created_at: Utc::now(),
+        };
+        let s = render(&appeal, Some("acme"), Some(r#"{"matched_rules":["cas"]}"#));
+        assert!(s.starts_with("📨 *Appeal* · acme\n"));
+        assert!(s.contains("User: [4242](tg://user?id=4242)\n"));
+        assert!(s.contains("Banned by: cas\n"));
+        assert!(s.ends_with(">I'm not a bot\\.\n>Please\\!\n"));
+    }
+}
@@ -0,0 +1,175 @@
+//! Read side of the `moderation_actions` ledger: the dashboard's audit log
+//! (`GET /chats/{chat_id}/actions`) and its CSV / NDJSON export.
+//!
+//! Rows come newest first, ordered by `(created_at, id)` so actions written
+//! in one transaction (same `created_at`) still page deterministically.
+//! Pages are keyset, not offset: [`page`] continues strictly after an
+//! [`AuditCursor`], which stays valid while new actions are appended at the
+//! head. [`export`] walks the same order in [`EXPORT_BATCH`]-row pages, so
+//! a large export never holds a transaction or the whole result in memory.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use futures::Stream;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Rows fetched per round trip while exporting.
+pub const EXPORT_BATCH: i64 = 500;
+/// An export stops after this many rows; narrow the filter (e.g. by time
+/// range) or continue from the last row's cursor for more.
+pub const MAX_EXPORT_ROWS: usize = 100_000;
+
+/// Ledger rows to return. `chat_id` comes from the route; every field is
+/// optional and they combine with AND.
+#[derive(Debug, Clone, Default)]
+pub struct AuditFilter {
+    /// `moderation_actions.action`, e.g. `ban` or `captcha_expired`.
+    pub action: Option<String>,
+    /// `bot` or `moderator`.
+    pub actor_kind: Option<String>,
+    pub actor_user_id: Option<i64>,
+    pub target_user_id: Option<i64>,
+    /// Case-insensitive substring of `reason` (free text or the spam
+    /// verdict JSON).
+    pub reason: Option<String>,
+    /// Spam rule that must appear in `reason.matched_rules` (`xxh3_dedup`,
+    /// `cas`, `ngram`, `llm`).
+    pub rule: Option<String>,
+    /// Inclusive lower bound on `created_at`.
+    pub from: Option<DateTime<Utc>>,
+    /// Exclusive upper bound on `created_at`.
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// Sort key of the last row a client has seen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditCursor {
+    pub created_at: DateTime<Utc>,
+    pub id: Uuid,
+}
+
+/// One ledger row as the audit log shows it.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AuditEntry {
+    pub id: Uuid,
+    pub target_user_id: i64,
+    pub action: String,
+    /// `bot` or `moderator`.
+    pub actor_kind: String,
+    /// `None` for the bot and for `server verify`.
+    pub actor_user_id: Option<i64>,
+    pub message_id: Option<i32>,
+    /// Free text for moderator actions; the verdict JSON (`matched_rules`,
+    /// score; the excerpt only until the mod log handled the row) for
+    /// spam-pipeline ones.
+    pub reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl AuditEntry {
+    pub fn cursor(&self) -> AuditCursor {
+        AuditCursor {
+            created_at: self.created_at,
+            id: self.id,
+        }
+    }
+}
+
+/// Up to `limit` rows matching `filter`, newest first, strictly after
+/// `after` when given. Callers ask for one more than they show to learn
+/// whether another page exists.
+pub async fn page(
+    pool: &PgPool,
+    chat_id: i64,
+    filter: &AuditFilter,
+    after: Option<AuditCursor>,
+    limit: i64,
+) -> Result<Vec<AuditEntry>> {
+    let rows = sqlx::query_as!(
+        AuditEntry,
+        r#"
+        SELECT id, target_user_id, action, actor_kind, actor_user_id,
+               message_id, reason, created_at
+        FROM moderation_actions
+        WHERE chat_id = $1
+          AND ($2::TEXT IS NULL OR action = $2)
+          AND ($3::TEXT IS NULL OR actor_kind = $3)
+          AND ($4::BIGINT IS NULL OR actor_user_id = $4)
+          AND ($5::BIGINT IS NULL OR target_user_id = $5)
+          AND ($6::TEXT IS NULL OR strpos(lower(reason), lower($6)) > 0)
+          AND ($7::TEXT IS NULL
+               OR (CASE WHEN reason LIKE '{%' THEN try_jsonb(reason) END)
+                  -> 'matched_rules' ? $7)
+          AND ($8::TIMESTAMPTZ IS NULL OR created_at >= $8)
+          AND ($9::TIMESTAMPTZ IS NULL OR created_at < $9)
+          AND ($10::TIMESTAMPTZ IS NULL OR (created_at, id) < ($10, $11::UUID))
+        ORDER BY created_at DESC, id DESC
+        LIMIT $12
+        "#,
+        chat_id,
+        filter.action,
+        filter.actor_kind,
+        filter.actor_user_id,
+        filter.target_user_id,
+        filter.reason,
+        filter.rule,
+        filter.from,
+        filter.to,
+        after.map(|c| c.created_at),
+        after.map(|c| c.id),
+        limit,
+    )
+    .fetch_all(pool)
+    .await
+    .context("SELECT audit log page")?;
+    Ok(rows)
+}
+
+/// Every row matching `filter` after `after`, newest first, as batches of
+/// at most [`EXPORT_BATCH`], ending after [`MAX_EXPORT_ROWS`]. A database
+/// error is yielded once and ends the stream.
+pub fn export(
+    pool: PgPool,
+    chat_id: i64,
+    filter: AuditFilter,
+    after: Option<AuditCursor>,
+) -> impl Stream<Item = Result<Vec<AuditEntry>>> + Send + 'static {
+    struct Walk {
+        pool: PgPool,
+        filter: AuditFilter,
+        after: Option<AuditCursor>,
+        sent: usize,
+        done: bool,
+    }
+
+    let walk = Walk {
+        pool,
+        filter,
+        after,
+        sent: 0,
+        done: false,
+    };
+    futures::stream::unfold(walk, move |mut walk| async move {
+        let remaining = MAX_EXPORT_ROWS.saturating_sub(walk.sent);
+        if walk.done || remaining == 0 {
+            return None;
+        }
+        let limit = EXPORT_BATCH.min(remaining as i64);
+        match page(&walk.pool, chat_id, &walk.filter, walk.after, limit).await {
+            Ok(rows) if rows.is_empty() => None,
+            Ok(rows) => {
+                walk.done = (rows.len() as i64) < limit;
+                walk.sent += rows.len();
+                walk.after = rows.last().map(AuditEntry::cursor);
+                Some((Ok(rows), walk))
+            }
+            Err(e) => {
+                walk.done = true;
+                Some((Err(e), walk))
+            }
+        }
+    })
+}
@@ -0,0 +1,278 @@
+//! Dashboard authentication: Telegram `initData` validation and the internal
+//! HS256 JWT the dashboard carries afterwards.
+//!
+//! Two payload shapes are accepted (see `server/docs/auth.md`):
+//!
+//!   * WebApp `initData` — has a JSON `user` field; the HMAC key is
+//!     `HMAC_SHA256("WebAppData", bot_token)`.
+//!   * Login Widget — flat `id` / `first_name` / ... fields; the HMAC key is
+//!     `SHA256(bot_token)`.
+//!
+//! Both end up as the same [`TgIdentity`] and mint the same [`Claims`]. Pure
+//! functions only — the route handler resolves `chat_ids` and the secrets.
+
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+use utoipa::ToSchema;
+
+type HmacSha256 = Hmac<Sha256>;
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum AuthError {
+    #[error("initData signature check failed")]
+    InvalidInitData,
+    #[error("initData auth_date is too old")]
+    InitDataExpired,
+    #[error("token is invalid or expired")]
+    InvalidToken,
+}
+
+/// Telegram identity carried in the JWT for UI display.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct TgIdentity {
+    pub id: i64,
+    pub first_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+}
+
+/// Internal JWT claims. `chat_ids` is a UI hint captured at mint time —
+/// chat-scoped endpoints re-check the live role on every request.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Claims {
+    pub sub: i64,
+    pub exp: i64,
+    pub chat_ids: Vec<i64>,
+    pub tg: TgIdentity,
+}
+
+/// Validate a raw `initData` (or Login Widget) query string against the bot
+/// token and return the signed identity. `now` is unix seconds, injected so
+/// tests can pin the clock.
+pub fn validate_init_data(
+    raw: &str,
+    bot_token: &str,
+    max_age_secs: u64,
+    now: i64,
+) -> Result<TgIdentity, AuthError> {
+    let mut pairs: Vec<(String, String)> = url::form_urlencoded::parse(raw.as_bytes())
+        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        .collect();
+    let hash_idx = pairs
+        .iter()
+        .position(|(k, _)| k == "hash")
+        .ok_or(AuthError::InvalidInitData)?;
+    let (_, hash) = pairs.swap_remove(hash_idx);
+    pairs.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let data_check_string = pairs
+        .iter()
+        .map(|(k, v)| format!("{k}={v}"))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let user_json = pairs.iter().find(|(k, _)| k == "user").map(|(_, v)| v);
+    let secret_key: Vec<u8> = if user_json.is_some() {
+        hmac_sha256(b"WebAppData", bot_token.as_bytes())?
+    } else {
+        Sha256::digest(bot_token.as_bytes()).to_vec()
+    };
+    let expected = to_hex(&hmac_sha256(&secret_key, data_check_string.as_bytes())?);
+    if !bool::from(
+        expected
+            .as_bytes()
+            .ct_eq(hash.to_ascii_lowercase().as_bytes()),
+    ) {
+        return Err(AuthError::InvalidInitData);
+    }
+
+    let field = |name: &str| {
+        pairs
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    };
+    let auth_date: i64 = field("auth_date")
+        .and_then(|v| v.parse().ok())
+        .ok_or(AuthError::InvalidInitData)?;
+    if now.saturating_sub(auth_date) > max_age_secs as i64 {
+        return Err(AuthError::InitDataExpired);
+    }
+
+    let identity = match user_json {
+        Some(json) => serde_json::from_str(json).map_err(|_| AuthError::InvalidInitData)?,
+        None => TgIdentity {
+            id: field("id")
+                .and_then(|v| v.parse().ok())
+                .ok_or(AuthError::InvalidInitData)?,
+            first_name: field("first_name").unwrap_or_default().to_string(),
+            last_name: field("last_name").map(str::to_string),
+            username: field("username").map(str::to_string),
+        },
+    };
+    if identity.id <= 0 {
+        return Err(AuthError::InvalidInitData);
+    }
+    Ok(identity)
+}
+
+/// Mint a dashboard JWT for `tg` valid for `ttl_secs`.
+pub fn mint(
+    tg: TgIdentity,
+    chat_ids: Vec<i64>,
+    secret: &str,
+    ttl_secs: i64,
+) -> Result<String, jsonwebtoken::errors::Error> {
+    let claims = Claims {
+        sub: tg.id,
+        exp: Utc::now().timestamp() + ttl_secs,
+        chat_ids,
+        tg,
+    };
+    jsonwebtoken::encode(
+        &Header::new(Algorithm::HS256),
+        &claims,
+        &EncodingKey::from_secret(secret.as_bytes()),
+    )
+}
+
+/// Verify signature + expiry and return the claims.
+pub fn decode(token: &str, secret: &str) -> Result<Claims, AuthError> {
+    let validation = Validation::new(Algorithm::HS256);
+    jsonwebtoken::decode::<Claims>(
+        token,
+        &DecodingKey::from_secret(secret.as_bytes()),
+        &validation,
+    )
+    .map(|data| data.claims)
+    .map_err(|_| AuthError::InvalidToken)
+}
+
+fn hmac_sha256(key: &[u8], message: &[u8]) -> Result<Vec<u8>, AuthError> {
+    // HMAC accepts keys of any length; the error arm is unreachable in
+    // practice but kept typed rather than unwrapped.
+    let mut mac = HmacSha256::new_from_slice(key).map_err(|_| AuthError::InvalidInitData)?;
+    mac.update(message);
+    Ok(mac.finalize().into_bytes().to_vec())
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    use std::fmt::Write;
+    bytes
+        .iter()
+        .fold(String::with_capacity(bytes.len() * 2), |mut s, b| {
+            let _ = write!(s, "{b:02x}");
+            s
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOKEN: &str = "1234567890:QWERTYUIOPASDFGHJKLZXCVBNMQWERTYUIO";
+    const NOW: i64 = 1_790_000_000;
+
+    /// Sign `fields` the way Telegram does and return the query string.
+    fn sign(fields: &[(&str, String)], secret_key: &[u8]) -> String {
+        let mut sorted = fields.to_vec();
+        sorted.sort_by(|a, b| a.0.cmp(b.0));
+        let dcs = sorted
+            .iter()
+            .map(|(k, v)| format!("{k}={v}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let hash = to_hex(&hmac_sha256(secret_key, dcs.as_bytes()).unwrap());
+        let mut ser = url::form_urlencoded::Serializer::new(String::new());
+        for (k, v) in &sorted {
+            ser.append_pair(k, v);
+        }
+        ser.append_pair("hash", &hash).finish()
+    }
+
+    fn mock_init_data(user_id: i64, bot_token: &str, auth_date: i64) -> String {
+        let secret_key = hmac_sha256(b"WebAppData", bot_token.as_bytes()).unwrap();
+        let user = format!(r#"{{"id":{user_id},"first_name":"Test"}}"#);
+        sign(
+            &[("auth_date", auth_date.to_string()), ("user", user)],
+            &secret_key,
+        )
+    }
+
+    #[test]
+    fn test_init_data_validation_accepts_signed_payload() {
+        let raw = mock_init_data(4242, TOKEN, NOW - 10);
+        let id = validate_init_data(&raw, TOKEN, 86_400, NOW).expect("valid");
+        assert_eq!(id.id, 4242);
+        assert_eq!(id.first_name, "Test");
+    }
+
+    #[test]
+    fn test_init_data_validation_rejects_other_bot_token() {
+        let raw = mock_init_data(4242, TOKEN, NOW - 10);
+        let other = "9999999999:QWERTYUIOPASDFGHJKLZXCVBNMQWERTYUIO";
+        assert_eq!(
+            validate_init_data(&raw, other, 86_400, NOW),
+            Err(AuthError::InvalidInitData)
+        );
+    }
+
+    #[test]
+    fn test_init_data_validation_rejects_tampered_user() {
+        let raw = mock_init_data(4242, TOKEN, NOW - 10).replace("4242", "4243");
+        assert_eq!(
+            validate_init_data(&raw, TOKEN, 86_400, NOW),
+            Err(AuthError::InvalidInitData)
+        );
+    }
+
+    #[test]
+    fn test_init_data_validation_rejects_expired_auth_date() {
+        let raw = mock_init_data(4242, TOKEN, NOW - 86_401);
+        assert_eq!(
+            validate_init_data(&raw, TOKEN, 86_400, NOW),
+            Err(AuthError::InitDataExpired)
+        );
+    }
+
+    #[test]
+    fn test_init_data_validation_accepts_login_widget_shape() {
+        let fields = [
+            ("auth_date", (NOW - 5).to_string()),
+            ("first_name", "Widget".to_string()),
+            ("id", "77".to_string()),
+            ("username", "widget_user".to_string()),
+        ];
+        let raw = sign(&fields, &Sha256::digest(TOKEN.as_bytes()));
+
+        let id = validate_init_data(&raw, TOKEN, 86_400, NOW).expect("valid");
+        assert_eq!(id.id, 77);
+        assert_eq!(id.username.as_deref(), Some("widget_user"));
+    }
+
+    #[test]
+    fn test_jwt_round_trip_and_wrong_secret() {
+        let secret = "abcdefghij1234567890ABCDEFGHIJ12";
+        let tg = TgIdentity {
+            id: 4242,
+            first_name: "Test".into(),
+            last_name: None,
+            username: None,
+        };
+        let token = mint(tg.clone(), vec![-100], secret, 60).expect("mint");
+        let claims = decode(&token, secret).expect("decode");
+        assert_eq!(claims.sub, 4242);
+        assert_eq!(claims.chat_ids, vec![-100]);
+        assert_eq!(claims.tg, tg);
+        assert_eq!(
+            decode(&token, "another-secret-another-secret-32").map(|c| c.sub),
+            Err(AuthError::InvalidToken)
+        );
+    }
+}
@@ -0,0 +1,578 @@
+//! Bulk undo over the moderation ledger.
+//!
+//! A [`BulkFilter`] selects `moderation_actions` rows of one chat and one
+//! action kind (optionally narrowed by a spam rule in `reason`, a time range
+//! and the actor). Every distinct target gets the inverse action:
+//!
+//! | Matched action | Inverse |
+//! |---|---|
+//! | `ban` | `unban` via `ModerationService::apply` |
+//! | `verify` | `unverify` via `CaptchaService::unverify_manual` |
+//! | `captcha_failed`, `captcha_expired`, `kick`, `unverify` | `verify` via `CaptchaService::verify_manual` |
+//!
+//! [`create`] writes the parent `bulk_operations` row plus one
+//! `bulk_operation_items` row per target and returns; [`run`] then works
+//! through the items in batches of [`BATCH_SIZE`] with a [`BATCH_PAUSE`]
+//! between them, so a 1000-target undo stays well under Telegram's ~30 req/s
+//! bot limit. A `RetryAfter` that outlasts the outbound queue's own retries
+//! sleeps the requested time and retries the target once more. Each item
+//! ends `applied`, `skipped` (already in effect) or `failed`, with
+//! `result_action_id` pointing at the ledger row it produced.
+//!
+//! A chat has at most one `running` operation (a partial unique index;
+//! [`create`] fails with [`AlreadyRunning`]). Operations run on a detached
+//! task, so a restart mid-run leaves the parent `running` with `pending`
+//! items; once no item has moved for [`STALE_AFTER`], [`running_for`] marks
+//! it `failed` so the chat isn't locked out. Re-submitting the same filter
+//! is safe — targets already undone come back as `skipped`.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use teloxide::RequestError;
+use tracing::{info, instrument, warn};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::models::chat_moderator::Permission;
+use crate::models::moderation_action::ActorKind;
+use crate::services::captcha::{CaptchaService, CaptchaState, Outcome as CaptchaOutcome};
+use crate::services::moderation_service::{
+    Action, ApplyContext, ModerationService, Outcome as ModOutcome,
+};
+use crate::services::outbound::OutboundError;
+
+/// Targets handled per batch before pausing.
+pub const BATCH_SIZE: usize = 20;
+/// Pause between batches of Telegram-calling inverses (unban). 20 calls per
+/// second leaves headroom under the bot-wide limit for live traffic.
+pub const BATCH_PAUSE: Duration = Duration::from_secs(1);
+/// Upper bound on distinct targets per operation; larger filters must be
+/// narrowed (e.g. by time range).
+pub const MAX_TARGETS: usize = 1000;
+/// A `running` operation with no item progress for this long is taken to
+/// have died with its process. Far above one batch plus the longest
+/// `RetryAfter` Telegram hands out.
+pub const STALE_AFTER: Duration = Duration::from_secs(10 * 60);
+
+/// [`create`] lost to another operation already running in the chat.
+#[derive(Debug, thiserror::Error)]
+#[error("a bulk operation is already running in this chat")]
+pub struct AlreadyRunning;
+
+/// Ledger rows to undo. `chat_id` comes from the route.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct BulkFilter {
+    /// Ledger action to undo: `ban`, `verify`, `captcha_failed`,
+    /// `captcha_expired`, `kick` or `unverify`.
+    pub action: String,
+    /// Spam rule that must appear in the row's `reason.matched_rules`
+    /// (`xxh3_dedup`, `cas`, `ngram`).
+    pub rule: Option<String>,
+    /// Inclusive lower bound on `created_at`.
+    pub from: Option<DateTime<Utc>>,
+    /// Exclusive upper bound on `created_at`.
+    pub to: Option<DateTime<Utc>>,
+    /// `bot` or `moderator`.
+    pub actor_kind: Option<String>,
+    pub actor_user_id: Option<i64>,
+}
+
+/// The inverse applied to every matched target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Inverse {
+    Unban,
+    Unverify,
+    /// Stored as `verify`: re-adds the user to `verified_users`.
+    #[serde(rename = "verify")]
+    Reverify,
+}
+
+impl Inverse {
+    pub fn for_action(action: &str) -> Option<Self> {
+        match action {
+            "ban" => Some(Self::Unban),
+            "verify" => Some(Self::Unverify),
+            "captcha_failed" | "captcha_expired" | "kick" | "unverify" => Some(Self::Reverify),
+            _ => None,
+        }
+    }
+
+    pub fn as_db_str(self) -> &'static str {
+        match self {
+            Self::Unban => "unban",
+            Self::Unverify => "unverify",
+            Self::Reverify => "verify",
+        }
+    }
+
+    fn from_db_str(s: &str) -> Option<Self> {
+        match s {
+            "unban" => Some(Self::Unban),
+            "unverify" => Some(Self::Unverify),
+            "verify" => Some(Self::Reverify),
+            _ => None,
+        }
+    }
+
+    /// Permission the requester needs in the chat.
+    pub fn permission(self) -> Permission {
+        match self {
+            Self::Unban => Permission::Ban,
+            Self::Unverify | Self::Reverify => Permission::Verify,
+        }
+    }
+
+    fn calls_telegram(self) -> bool {
+        matches!(self, Self::Unban)
+    }
+}
+
+/// One matched target and the newest ledger row that matched it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Target {
+    pub user_id: i64,
+    pub source_action_id: Uuid,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct BulkItem {
+    pub target_user_id: i64,
+    pub source_action_id: Option<Uuid>,
+    /// Ledger row written by the inverse; `None` unless `applied`.
+    pub result_action_id: Option<Uuid>,
+    /// `pending`, `applied`, `skipped` (already in effect) or `failed`.
+    pub outcome: String,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct BulkOperation {
+    pub id: Uuid,
+    pub chat_id: i64,
+    pub requested_by: i64,
+    pub inverse: Inverse,
+    pub filter: BulkFilter,
+    /// `running`, `completed` or `failed`.
+    pub status: String,
+    pub total: i32,
+    pub applied: i32,
+    pub skipped: i32,
+    pub failed: i32,
+    pub created_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub items: Vec<BulkItem>,
+}
+
+/// Distinct targets matched by `filter` in `chat_id`, at most
+/// `MAX_TARGETS + 1` so the caller can tell "exactly the cap" from "over".
+pub async fn matching_targets(
+    pool: &PgPool,
+    chat_id: i64,
+    filter: &BulkFilter,
+) -> Result<Vec<Target>> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT DISTINCT ON (target_user_id) id, target_user_id
+        FROM moderation_actions
+        WHERE chat_id = $1
+          AND action = $2
+          AND ($3::TEXT IS NULL
+               OR (CASE WHEN reason LIKE '{%' THEN try_jsonb(reason) END)
+                  -> 'matched_rules' ? $3)
+          AND ($4::TIMESTAMPTZ IS NULL OR created_at >= $4)
+          AND ($5::TIMESTAMPTZ IS NULL OR created_at < $5)
+          AND ($6::TEXT IS NULL OR actor_kind = $6)
+          AND ($7::BIGINT IS NULL OR actor_user_id = $7)
+        ORDER BY target_user_id, created_at DESC
+        LIMIT $8
+        "#,
+        chat_id,
+        filter.action,
+        filter.rule,
+        filter.from,
+        filter.to,
+        filter.actor_kind,
+        filter.actor_user_id,
+        (MAX_TARGETS + 1) as i64,
+    )
+    .fetch_all(pool)
+    .await
+    .context("SELECT bulk targets")?;
+    Ok(rows
+        .into_iter()
+        .map(|r| Target {
+            user_id: r.target_user_id,
+            source_action_id: r.id,
+        })
+        .collect())
+}
+
+/// The chat's running operation, if any. One that has made no progress for
+/// [`STALE_AFTER`] (its process died) is marked `failed` first and not
+/// returned.
+pub async fn running_for(pool: &PgPool, chat_id: i64) -> Result<Option<Uuid>> {
+    let reaped = sqlx::query_scalar!(
+        r#"
+        UPDATE bulk_operations b
+        SET status = 'failed', finished_at = NOW()
+        WHERE b.chat_id = $1
+          AND b.status = 'running'
+          AND b.created_at < NOW() - make_interval(secs => $2)
+          AND NOT EXISTS (
+              SELECT 1 FROM bulk_operation_items i
+              WHERE i.operation_id = b.id
+                AND i.updated_at >= NOW() - make_interval(secs => $2)
+          )
+        RETURNING b.id
+        "#,
+        chat_id,
+        STALE_AFTER.as_secs_f64(),
+    )
+    .fetch_optional(pool)
+    .await
+    .context("UPDATE stale bulk operation")?;
+    if let Some(id) = reaped {
+        warn!(chat_id, operation_id = %id, "stale bulk operation marked failed");
+    }
+    sqlx::query_scalar!(
+        r#"SELECT id FROM bulk_operations WHERE chat_id = $1 AND status = 'running' LIMIT 1"#,
+        chat_id,
+    )
+    .fetch_optional(pool)
+    .await
+    .context("SELECT running bulk operation")
+}
+
+/// Write the parent row and one `pending` item per target. Fails with
+/// [`AlreadyRunning`] if the chat already has a running operation.
+pub async fn create(
+    pool: &PgPool,
+    chat_id: i64,
+    requested_by: i64,
+    inverse: Inverse,
+    filter: &BulkFilter,
+    targets: &[Target],
+) -> Result<Uuid> {
+    let mut tx = pool.begin().await.context("BEGIN bulk create tx")?;
+    let id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO bulk_operations (chat_id, requested_by, inverse, filter, total)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING id
+        "#,
+        chat_id,
+        requested_by,
+        inverse.as_db_str(),
+        serde_json::to_value(filter).context("serialize bulk filter")?,
+        targets.len() as i32,
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| match &e {
+        sqlx::Error::Database(d) if d.constraint() == Some("uq_bulk_operations_running") => {
+            anyhow::Error::new(AlreadyRunning)
+        }
+        _ => anyhow::Error::new(e).context("INSERT bulk_operations"),
+    })?;
+
+    let users: Vec<i64> = targets.iter().map(|t| t.user_id).collect();
+    let sources: Vec<Uuid> = targets.iter().map(|t| t.source_action_id).collect();
+    sqlx::query!(
+        r#"
+        INSERT INTO bulk_operation_items (operation_id, target_user_id, source_action_id)
+        SELECT $1, t.user_id, t.source_id
+        FROM UNNEST($2::BIGINT[], $3::UUID[]) AS t(user_id, source_id)
+        "#,
+        id,
+        &users,
+        &sources,
+    )
+    .execute(&mut *tx)
+    .await
+    .context("INSERT bulk_operation_items")?;
+
+    tx.commit().await.context("COMMIT bulk create tx")?;
+    Ok(id)
+}
+
+/// Everything [`run`] needs besides the pool; cloned out of `AppState`.
+#[derive(Clone)]
+pub struct Executors {
+    pub moderation: Arc<ModerationService>,
+    pub captcha: Arc<CaptchaService>,
+    pub captcha_state: Arc<CaptchaState>,
+}
+
+/// Work through the operation's pending items. Never returns an error: a
+/// failure outside a single target marks the operation `failed`.
+#[instrument(skip(pool, exec), fields(operation_id = %id))]
+pub async fn run(pool: PgPool, exec: Executors, id: Uuid) {
+    let status = match run_items(&pool, &exec, id).await {
+        Ok(()) => "completed",
+        Err(e) => {
+            warn!(error = ?e, "bulk operation aborted");
+            "failed"
+        }
+    };
+    if let Err(e) = sqlx::query!(
+        r#"UPDATE bulk_operations SET status = $2, finished_at = NOW() WHERE id = $1"#,
+        id,
+        status,
+    )
+    .execute(&pool)
+    .await
+    {
+        warn!(error = ?e, "bulk operation status update failed");
+    }
+    info!(status, "bulk operation finished");
+}
+
+async fn run_items(pool: &PgPool, exec: &Executors, id: Uuid) -> Result<()> {
+    let op = sqlx::query!(
+        r#"SELECT chat_id, requested_by, inverse FROM bulk_operations WHERE id = $1"#,
+        id,
+    )
+    .fetch_one(pool)
+    .await
+    .context("SELECT bulk_operations")?;
+    let inverse = Inverse::from_db_str(&op.inverse).context("unknown bulk inverse")?;
+    let targets: Vec<i64> = sqlx::query_scalar!(
+        r#"
+        SELECT target_user_id FROM bulk_operation_items
+        WHERE operation_id = $1 AND outcome = 'pending'
+        ORDER BY target_user_id
+        "#,
+        id,
+    )
+    .fetch_all(pool)
+    .await
+    .context("SELECT pending bulk items")?;
+
+    for (i, batch) in targets.chunks(BATCH_SIZE).enumerate() {
+        if i > 0 && inverse.calls_telegram() {
+            tokio::time::sleep(BATCH_PAUSE).await;
+        }
+        for &user_id in batch {
+            let result =
+                apply_with_retry(exec, inverse, op.chat_id, user_id, op.requested_by).await;
+            let (outcome, error, result_action_id) = match result {
+                Ok(Applied::Yes(action_id)) => ("applied", None, action_id),
+                Ok(Applied::AlreadyInEffect) => ("skipped", None, None),
+                Err(e) => {
+                    warn!(user_id, error = ?e, "bulk inverse failed");
+                    ("failed", Some(format!("{e:#}")), None)
+                }
+            };
+            record(
+                pool,
+                id,
+                user_id,
+                outcome,
+                error.as_deref(),
+                result_action_id,
+            )
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+/// What one inverse action did to its target.
+enum Applied {
+    /// Newly applied, with the ledger row it wrote. `None` only when a
+    /// re-verify's `verify` row already existed for the same captcha message.
+    Yes(Option<Uuid>),
+    /// Already in effect; nothing written.
+    AlreadyInEffect,
+}
+
+/// One inverse action. A Telegram `RetryAfter` is honoured once.
+async fn apply_with_retry(
+    exec: &Executors,
+    inverse: Inverse,
+    chat_id: i64,
+    user_id: i64,
+    actor: i64,
+) -> Result<Applied> {
+    match apply_one(exec, inverse, chat_id, user_id, actor).await {
+        Err(e) => match e.downcast_ref::<OutboundError>() {
+            Some(OutboundError::Request(RequestError::RetryAfter(after))) => {
+                let wait = Duration::from_secs(u64::from(after.seconds()));
+                warn!(
+                    user_id,
+                    wait_secs = wait.as_secs(),
+                    "rate limited; retrying"
+                );
+                tokio::time::sleep(wait).await;
+                apply_one(exec, inverse, chat_id, user_id, actor).await
+            }
+            _ => Err(e),
+        },
+        ok => ok,
+    }
+}
+
+async fn apply_one(
+    exec: &Executors,
+    inverse: Inverse,
+    chat_id: i64,
+    user_id: i64,
+    actor: i64,
+) -> Result<Applied> {
+    match inverse {
+        Inverse::Unban => {
+            let ctx = ApplyContext {
+                chat_id,
+                target_user_id: user_id,
+                message_id: None,
+                actor_kind: ActorKind::Moderator,
+                actor_user_id: Some(actor),
+            };
+            match exec.moderation.apply(Action::Unban, ctx).await? {
+                ModOutcome::Applied(id) => Ok(Applied::Yes(Some(id))),
+                ModOutcome::AlreadyApplied => Ok(Applied::AlreadyInEffect),
+            }
+        }
+        Inverse::Unverify => {
+            let Some(id) = exec
+                .captcha
+                .unverify_manual(chat_id, user_id, actor)
+                .await?
+            else {
+                return Ok(Applied::AlreadyInEffect);
+            };
+            if let Err(e) = exec.captcha_state.clear_verified(chat_id, user_id).await {
+                warn!(error = ?e, "redis clear_verified (bulk) failed");
+            }
+            Ok(Applied::Yes(Some(id)))
+        }
+        Inverse::Reverify => {
+            let (outcome, id) = exec
+                .captcha
+                .verify_manual(chat_id, user_id, Some(actor))
+                .await?;
+            if let Err(e) = exec.captcha_state.mark_verified(chat_id, user_id).await {
+                warn!(error = ?e, "redis mark_verified (bulk) failed");
+            }
+            Ok(match outcome {
+                CaptchaOutcome::Solved => Applied::Yes(id),
+                _ => Applied::AlreadyInEffect,
+            })
+        }
+    }
+}
+
+async fn record(
+    pool: &PgPool,
+    id: Uuid,
+    user_id: i64,
+    outcome: &str,
+    error: Option<&str>,
+    result_action_id: Option<Uuid>,
+) -> Result<()> {
+    let mut tx = pool.begin().await.context("BEGIN bulk record tx")?;
+    sqlx::query!(
+        r#"
+        UPDATE bulk_operation_items
+        SET outcome = $3, error = $4, result_action_id = $5, updated_at = NOW()
+        WHERE operation_id = $1 AND target_user_id = $2
+        "#,
+        id,
+        user_id,
+        outcome,
+        error,
+        result_action_id,
+    )
+    .execute(&mut *tx)
+    .await
+    .context("UPDATE bulk_operation_items")?;
+    sqlx::query!(
+        r#"
+        UPDATE bulk_operations
+        SET applied = applied + (CASE WHEN $2 = 'applied' THEN 1 ELSE 0 END),
+            skipped = skipped + (CASE WHEN $2 = 'skipped' THEN 1 ELSE 0 END),
+            failed  = failed  + (CASE WHEN $2 = 'failed'  THEN 1 ELSE 0 END)
+        WHERE id = $1
+        "#,
+        id,
+        outcome,
+    )
+    .execute(&mut *tx)
+    .await
+    .context("UPDATE bulk_operations counters")?;
+    tx.commit().await.context("COMMIT bulk record tx")?;
+    Ok(())
+}
+
+/// Parent row plus items, scoped to `chat_id`.
+pub async fn get(pool: &PgPool, chat_id: i64, id: Uuid) -> Result<Option<BulkOperation>> {
+    let Some(op) = sqlx::query!(
+        r#"
+        SELECT id, chat_id, requested_by, inverse, filter, status, total, applied,
+               skipped, failed, created_at, finished_at
+        FROM bulk_operations
+        WHERE id = $1 AND chat_id = $2
+        "#,
+        id,
+        chat_id,
+    )
+    .fetch_optional(pool)
+    .await
+    .context("SELECT bulk_operations")?
+    else {
+        return Ok(None);
+    };
+    let items = sqlx::query_as!(
+        BulkItem,
+        r#"
+        SELECT target_user_id, source_action_id, result_action_id, outcome, error
+        FROM bulk_operation_items
+        WHERE operation_id = $1
+        ORDER BY target_user_id
+        "#,
+        id,
+    )
+    .fetch_all(pool)
+    .await
+    .context("SELECT bulk_operation_items")?;
+    Ok(Some(BulkOperation {
+        id: op.id,
+        chat_id: op.chat_id,
+        requested_by: op.requested_by,
+        inverse: Inverse::from_db_str(&op.inverse).context("unknown bulk inverse")?,
+        filter: serde_json::from_value(op.filter).unwrap_or_default(),
+        status: op.status,
+        total: op.total,
+        applied: op.applied,
+        skipped: op.skipped,
+        failed: op.failed,
+        created_at: op.created_at,
+        finished_at: op.finished_at,
+        items,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inverse_mapping() {
+        assert_eq!(Inverse::for_action("ban"), Some(Inverse::Unban));
+        assert_eq!(Inverse::for_action("verify"), Some(Inverse::Unverify));
+        for kind in ["captcha_failed", "captcha_expired", "kick", "unverify"] {
+            assert_eq!(Inverse::for_action(kind), Some(Inverse::Reverify), "{kind}");
+        }
+        assert_eq!(Inverse::for_action("delete"), None);
+        assert_eq!(Inverse::for_action("unban"), None);
+        assert_eq!(Inverse::Reverify.as_db_str(), "verify");
+        assert_eq!(Inverse::Unban.permission(), Permission::Ban);
+        assert_eq!(Inverse::Unverify.permission(), Permission::Verify);
+    }
+}
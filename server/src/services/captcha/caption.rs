@@ -6,7 +6,10 @@
 //!
 //! Filled slot = keycap digit (`1️⃣`), empty slot = white square (`⬜`).
 //! Captions are plain text (no `parse_mode`) so user mentions don't need
-//! MarkdownV2 escaping.
+//! MarkdownV2 escaping. Text comes from the `captcha-*` catalog keys.
+
+use crate::i18n::Lang;
+use crate::tr;
 
 const SOLUTION_LEN: usize = 4;
 const EMPTY_SLOT: &str = "⬜";
@@ -35,44 +38,37 @@ pub fn render_slots(input: &str) -> String {
 
 /// Initial caption posted alongside the captcha image when a fresh user joins
 /// (or when an unverified user trips the message gate).
-pub fn caption_initial(mention: &str, attempts_left: i16) -> String {
-    format!(
-        "👋 {mention}, welcome!\n\
-         \n\
-         🔐 Please solve the captcha to start chatting.\n\
-         \n\
-         {slots}\n\
-         \n\
-         🎯 Attempts left: {attempts_left}",
-        slots = render_slots(""),
-    )
+pub fn caption_initial(lang: Lang, mention: &str, attempts_left: i16) -> String {
+    [
+        tr!(lang, "captcha-welcome", mention = mention),
+        tr!(lang, "captcha-solve-prompt"),
+        render_slots(""),
+        tr!(lang, "captcha-attempts-left", count = attempts_left),
+    ]
+    .join("\n\n")
 }
 
 /// Caption shown while the user is typing — also used after backspace and
 /// after refresh (both reset to whatever buffer is current; refresh always
 /// passes an empty string).
-pub fn caption_progress(input: &str) -> String {
-    format!(
-        "🔐 Captcha verification\n\
-         \n\
-         Enter the 4 digits from the image.\n\
-         \n\
-         {slots}",
-        slots = render_slots(input),
-    )
+pub fn caption_progress(lang: Lang, input: &str) -> String {
+    [
+        tr!(lang, "captcha-verification"),
+        tr!(lang, "captcha-enter-digits"),
+        render_slots(input),
+    ]
+    .join("\n\n")
 }
 
 /// Caption shown after a wrong (non-final) attempt. The buffer is reset to
 /// empty server-side, so we always render four empty slots here.
-pub fn caption_wrong(attempts_left: i16) -> String {
-    format!(
-        "❌ Wrong code, try again.\n\
-         \n\
-         🎯 Attempts left: {attempts_left}\n\
-         \n\
-         {slots}",
-        slots = render_slots(""),
-    )
+pub fn caption_wrong(lang: Lang, attempts_left: i16) -> String {
+    [
+        tr!(lang, "captcha-wrong"),
+        tr!(lang, "captcha-attempts-left", count = attempts_left),
+        render_slots(""),
+    ]
+    .join("\n\n")
 }
 
 #[cfg(test)]
@@ -117,7 +113,7 @@ mod tests {
 
     #[test]
     fn caption_initial_has_mention_and_attempts() {
-        let c = caption_initial("@alice", 5);
+        let c = caption_initial(Lang::En, "@alice", 5);
         assert!(c.starts_with("👋 @alice, welcome!"));
         assert!(c.contains("Attempts left: 5"));
         assert!(c.contains("⬜ ⬜ ⬜ ⬜"));
@@ -125,7 +121,7 @@ mod tests {
 
     #[test]
     fn caption_progress_shows_typed_digits() {
-        let c = caption_progress("12");
+        let c = caption_progress(Lang::En, "12");
         assert!(c.contains(KEYCAP_1));
         assert!(c.contains(KEYCAP_2));
         assert!(c.contains("Enter the 4 digits"));
@@ -133,21 +129,31 @@ mod tests {
 
     #[test]
     fn caption_wrong_has_attempts_and_empty_slots() {
-        let c = caption_wrong(3);
+        let c = caption_wrong(Lang::En, 3);
         assert!(c.starts_with("❌ Wrong code, try again."));
         assert!(c.contains("Attempts left: 3"));
         assert!(c.contains("⬜ ⬜ ⬜ ⬜"));
     }
 
+    #[test]
+    fn russian_caption_uses_plural_forms() {
+        let c = caption_initial(Lang::Ru, "@alice", 3);
+        assert!(c.starts_with("👋 @alice, добро пожаловать!"));
+        assert!(c.ends_with("🎯 Осталось 3 попытки"));
+        assert!(caption_wrong(Lang::Ru, 1).contains("Осталась 1 попытка"));
+    }
+
     #[test]
     fn captions_split_sentences_on_separate_lines() {
         // Each emoji-prefixed sentence sits on its own line — the user asked
         // for no run-on lines.
-        for c in [
-            caption_initial("@alice", 5),
-            caption_progress(""),
-            caption_wrong(2),
-        ] {
+        for c in Lang::ALL.into_iter().flat_map(|lang| {
+            [
+                caption_initial(lang, "@alice", 5),
+                caption_progress(lang, ""),
+                caption_wrong(lang, 2),
+            ]
+        }) {
             for line in c.lines() {
                 // Every non-empty line that contains a sentence-ending period
                 // must contain at most one period.
@@ -3,6 +3,8 @@
 //! The service is Telegram-free: it talks to Postgres only. Bot side-effects
 //! (restrict / send_photo / delete_message / kick) live in the handlers and
 //! the expiry job. This keeps the service trivially testable with `sqlx::test`.
+//! A verification (solve or manual) queues a `user.verified` webhook event
+//! in its transaction (`services::webhooks`).
 
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
@@ -12,9 +14,13 @@ use uuid::Uuid;
 use xxhash_rust::xxh3::xxh3_64;
 
 use super::fonts::Fonts;
-use super::keyboard::digit_pad;
+use super::keyboard::{digit_pad, short_id};
 use super::render::render_webp;
+use super::state::MetaPayload;
 use crate::models::daily_stats::{self, Metric};
+use crate::models::moderation_action::ActorKind;
+use crate::services::webhooks::{self, EventType, WebhookEvent};
+use crate::telemetry::metrics;
 
 const SOLUTION_LEN: usize = 4;
 const DEFAULT_ATTEMPTS: i16 = 5;
@@ -58,6 +64,20 @@ pub enum Outcome {
     NotFound,
 }
 
+impl Outcome {
+    /// `outcome` label on `vixen_captcha_attempts_total`.
+    fn label(&self) -> &'static str {
+        match self {
+            Outcome::Solved => "solved",
+            Outcome::AlreadyVerified => "already_verified",
+            Outcome::WrongLeft(_) => "wrong",
+            Outcome::WrongFinal => "wrong_final",
+            Outcome::Expired => "expired",
+            Outcome::NotFound => "not_found",
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct CaptchaService {
     pool: PgPool,
@@ -110,6 +130,7 @@ impl CaptchaService {
                 solution            = EXCLUDED.solution,
                 attempts_left       = EXCLUDED.attempts_left,
                 telegram_message_id = NULL,
+                input               = '',
                 expires_at          = EXCLUDED.expires_at,
                 created_at          = NOW()
             RETURNING id, attempts_left, expires_at
@@ -200,15 +221,90 @@ impl CaptchaService {
         Ok(())
     }
 
+    /// Digits typed so far from the `input` column, which stands in for
+    /// Redis `cap:input` while Redis is unavailable. Empty without a row.
+    pub async fn input(&self, chat_id: i64, user_id: i64) -> Result<String> {
+        let input = sqlx::query_scalar!(
+            r#"SELECT input FROM captcha_challenges WHERE chat_id = $1 AND user_id = $2"#,
+            chat_id,
+            user_id,
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .context("SELECT captcha_challenges.input")?;
+        Ok(input.unwrap_or_default())
+    }
+
+    /// Counterpart of [`Self::input`]. A wrong answer and a re-issue reset
+    /// the column themselves.
+    pub async fn set_input(&self, chat_id: i64, user_id: i64, input: &str) -> Result<()> {
+        sqlx::query!(
+            r#"UPDATE captcha_challenges SET input = $3 WHERE chat_id = $1 AND user_id = $2"#,
+            chat_id,
+            user_id,
+            input,
+        )
+        .execute(&self.pool)
+        .await
+        .context("UPDATE captcha_challenges.input")?;
+        Ok(())
+    }
+
+    /// Callback meta rebuilt from the challenge whose photo is `message_id`,
+    /// for a callback whose Redis `cap:meta` key is missing or unreadable.
+    /// `None` once the challenge is gone.
+    pub async fn meta_for_message(
+        &self,
+        chat_id: i64,
+        message_id: i32,
+    ) -> Result<Option<MetaPayload>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT id, user_id,
+                   EXTRACT(EPOCH FROM expires_at - created_at)::BIGINT AS "lifetime_secs!"
+            FROM captcha_challenges
+            WHERE chat_id = $1 AND telegram_message_id = $2
+            "#,
+            chat_id,
+            message_id,
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .context("SELECT captcha_challenges by message")?;
+        Ok(row.map(|r| MetaPayload {
+            owner_user_id: r.user_id,
+            uuid_short: short_id(r.id),
+            lifetime_secs: r.lifetime_secs.max(1) as u64,
+        }))
+    }
+
     /// Process a candidate solution. All transitions happen in one transaction
     /// guarded by `SELECT ... FOR UPDATE` so two concurrent solvers can't both
     /// win.
     pub async fn solve(&self, chat_id: i64, user_id: i64, attempt: &str) -> Result<Outcome> {
+        let result = self.solve_tx(chat_id, user_id, attempt).await;
+        match &result {
+            Ok((outcome, solved_after)) => {
+                metrics::captcha_solve(chat_id, outcome.label(), *solved_after)
+            }
+            Err(_) => metrics::captcha_solve(chat_id, "error", None),
+        }
+        result.map(|(outcome, _)| outcome)
+    }
+
+    /// Body of [`Self::solve`]. Also returns the issue → solve latency on a
+    /// correct answer, for the solve-latency histogram.
+    async fn solve_tx(
+        &self,
+        chat_id: i64,
+        user_id: i64,
+        attempt: &str,
+    ) -> Result<(Outcome, Option<std::time::Duration>)> {
         let mut tx = self.pool.begin().await.context("begin solve tx")?;
 
         let row = sqlx::query!(
             r#"
-            SELECT id, solution, attempts_left, expires_at, telegram_message_id
+            SELECT id, solution, attempts_left, expires_at, telegram_message_id, created_at
             FROM captcha_challenges
             WHERE chat_id = $1 AND user_id = $2
             FOR UPDATE
@@ -234,11 +330,12 @@ impl CaptchaService {
             .fetch_one(&mut *tx)
             .await?;
             tx.commit().await?;
-            return Ok(if just_verified {
+            let outcome = if just_verified {
                 Outcome::AlreadyVerified
             } else {
                 Outcome::NotFound
-            });
+            };
+            return Ok((outcome, None));
         };
 
         if row.expires_at < Utc::now() {
@@ -269,7 +366,7 @@ impl CaptchaService {
             .await?;
             daily_stats::increment(&mut *tx, chat_id, Metric::CaptchaExpired, 1).await?;
             tx.commit().await?;
-            return Ok(Outcome::Expired);
+            return Ok((Outcome::Expired, None));
         }
 
         if row.solution == attempt {
@@ -291,23 +388,34 @@ impl CaptchaService {
             )
             .execute(&mut *tx)
             .await?;
-            sqlx::query!(
+            let action_id = sqlx::query_scalar!(
                 r#"
                 INSERT INTO moderation_actions
                     (chat_id, target_user_id, action, actor_kind, message_id)
                 VALUES ($1, $2, 'verify', 'bot', $3)
                 ON CONFLICT DO NOTHING
+                RETURNING id
                 "#,
                 chat_id,
                 user_id,
                 row.telegram_message_id,
             )
-            .execute(&mut *tx)
+            .fetch_optional(&mut *tx)
             .await?;
+            // A replayed solve hits the ledger's ON CONFLICT and inserts
+            // nothing; it must not emit a second `user.verified` either.
+            if action_id.is_some() {
+                let event = WebhookEvent {
+                    action_id,
+                    ..WebhookEvent::new(EventType::UserVerified, chat_id, user_id, ActorKind::Bot)
+                };
+                webhooks::record(&mut *tx, &event).await?;
+            }
             daily_stats::increment(&mut *tx, chat_id, Metric::CaptchaSolved, 1).await?;
             daily_stats::increment(&mut *tx, chat_id, Metric::UsersVerified, 1).await?;
             tx.commit().await?;
-            return Ok(Outcome::Solved);
+            let solved_after = (Utc::now() - row.created_at).to_std().ok();
+            return Ok((Outcome::Solved, solved_after));
         }
 
         let new_attempts = row.attempts_left - 1;
@@ -333,12 +441,12 @@ impl CaptchaService {
             .execute(&mut *tx)
             .await?;
             tx.commit().await?;
-            Ok(Outcome::WrongFinal)
+            Ok((Outcome::WrongFinal, None))
         } else {
             sqlx::query!(
                 r#"
                 UPDATE captcha_challenges
-                SET attempts_left = $3
+                SET attempts_left = $3, input = ''
                 WHERE chat_id = $1 AND user_id = $2
                 "#,
                 chat_id,
@@ -348,18 +456,21 @@ impl CaptchaService {
             .execute(&mut *tx)
             .await?;
             tx.commit().await?;
-            Ok(Outcome::WrongLeft(new_attempts))
+            Ok((Outcome::WrongLeft(new_attempts), None))
         }
     }
 
     /// Manual verification (used by `/verify`). Idempotent — verifying an
     /// already-verified user is a no-op that returns `AlreadyVerified`.
+    /// `actor_user_id` is `None` for `server verify`, which has no Telegram
+    /// user behind it. Also returns the id of the `verify` ledger row written,
+    /// if any.
     pub async fn verify_manual(
         &self,
         chat_id: i64,
         target_user_id: i64,
-        actor_user_id: i64,
-    ) -> Result<Outcome> {
+        actor_user_id: Option<i64>,
+    ) -> Result<(Outcome, Option<Uuid>)> {
         let mut tx = self.pool.begin().await.context("begin verify tx")?;
 
         let already: bool = sqlx::query_scalar!(
@@ -373,7 +484,7 @@ impl CaptchaService {
         .await?;
         if already {
             tx.commit().await?;
-            return Ok(Outcome::AlreadyVerified);
+            return Ok((Outcome::AlreadyVerified, None));
         }
 
         let pending = sqlx::query!(
@@ -401,24 +512,83 @@ impl CaptchaService {
         .execute(&mut *tx)
         .await?;
 
-        sqlx::query!(
+        let action_id = sqlx::query_scalar!(
             r#"
             INSERT INTO moderation_actions
                 (chat_id, target_user_id, action, actor_kind, actor_user_id, message_id, reason)
             VALUES ($1, $2, 'verify', 'moderator', $3, $4, 'manual')
             ON CONFLICT DO NOTHING
+            RETURNING id
             "#,
             chat_id,
             target_user_id,
             actor_user_id,
             pending_msg,
         )
-        .execute(&mut *tx)
+        .fetch_optional(&mut *tx)
         .await?;
+        if action_id.is_some() {
+            let event = WebhookEvent {
+                actor_user_id,
+                action_id,
+                reason: Some("manual".to_owned()),
+                ..WebhookEvent::new(
+                    EventType::UserVerified,
+                    chat_id,
+                    target_user_id,
+                    ActorKind::Moderator,
+                )
+            };
+            webhooks::record(&mut *tx, &event).await?;
+        }
         daily_stats::increment(&mut *tx, chat_id, Metric::UsersVerified, 1).await?;
 
         tx.commit().await?;
-        Ok(Outcome::Solved)
+        Ok((Outcome::Solved, action_id))
+    }
+
+    /// Manual un-verification (bulk undo of `verify`). Removes the
+    /// `verified_users` row and ledgers `unverify`, returning the ledger row's
+    /// id; `None` when the user was not verified, writing nothing. The user gets a fresh captcha
+    /// on their next message.
+    pub async fn unverify_manual(
+        &self,
+        chat_id: i64,
+        target_user_id: i64,
+        actor_user_id: i64,
+    ) -> Result<Option<Uuid>> {
+        let mut tx = self.pool.begin().await.context("begin unverify tx")?;
+
+        let removed = sqlx::query!(
+            r#"DELETE FROM verified_users WHERE chat_id = $1 AND user_id = $2"#,
+            chat_id,
+            target_user_id,
+        )
+        .execute(&mut *tx)
+        .await?
+        .rows_affected()
+            > 0;
+        if !removed {
+            tx.commit().await?;
+            return Ok(None);
+        }
+
+        let action_id = sqlx::query_scalar!(
+            r#"
+            INSERT INTO moderation_actions
+                (chat_id, target_user_id, action, actor_kind, actor_user_id, reason)
+            VALUES ($1, $2, 'unverify', 'moderator', $3, 'manual')
+            RETURNING id
+            "#,
+            chat_id,
+            target_user_id,
+            actor_user_id,
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(Some(action_id))
     }
 
     // ── Internal helpers ──────────────────────────────────────────────────
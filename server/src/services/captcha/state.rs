@@ -14,12 +14,19 @@
 //! repopulated lazily on PG hit.
 //!
 //! Cache misses return empty/false, never errors: a flaky Redis must degrade
-//! the captcha UI, never break it.
+//! the captcha UI, never break it. While Redis is unavailable every call
+//! fails fast (`RedisError::Unavailable`) and callers fall back: verified
+//! checks to Postgres, the admin list to an in-process Moka copy (refilled
+//! from `get_chat_administrators` on a miss), digit input and meta to the
+//! `captcha_challenges` row. The `clear_*` calls
+//! never fail for an outage — the key is deleted once Redis is back.
 
 use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::{Context, Result};
-use redis::AsyncCommands;
+use moka::future::Cache;
+use tracing::debug;
 
 use crate::database::Redis;
 
@@ -34,9 +41,15 @@ pub const VERIFIED_CACHE_TTL_SECS: u64 = 604_800;
 /// the message-gate decision path.
 pub const ADMINS_CACHE_TTL_SECS: u64 = 21_600;
 
+/// Watched chats are few; this bounds the in-process admin lists anyway.
+const LOCAL_ADMINS_CAPACITY: u64 = 10_000;
+
 #[derive(Clone)]
 pub struct CaptchaState {
     redis: Arc<Redis>,
+    /// Per-replica copy of `cap:admins`, written alongside it and read only
+    /// while Redis is unavailable.
+    local_admins: Cache<i64, Vec<i64>>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -80,7 +93,13 @@ impl MetaPayload {
 
 impl CaptchaState {
     pub fn new(redis: Arc<Redis>) -> Self {
-        Self { redis }
+        Self {
+            redis,
+            local_admins: Cache::builder()
+                .max_capacity(LOCAL_ADMINS_CAPACITY)
+                .time_to_live(Duration::from_secs(ADMINS_CACHE_TTL_SECS))
+                .build(),
+        }
     }
 
     // ── Input buffer ──────────────────────────────────────────────────────
@@ -93,14 +112,9 @@ impl CaptchaState {
         ttl_secs: u64,
     ) -> Result<()> {
         let key = input_key(chat_id, user_id);
-        let mut conn = self
+        let _: () = self
             .redis
-            .pool()
-            .get()
-            .await
-            .context("redis pool acquire (set_input)")?;
-        let _: () = conn
-            .set_ex(&key, input, ttl_secs)
+            .query(&redis::Cmd::set_ex(&key, input, ttl_secs))
             .await
             .context("SETEX cap:input")?;
         Ok(())
@@ -108,26 +122,17 @@ impl CaptchaState {
 
     pub async fn get_input(&self, chat_id: i64, user_id: i64) -> Result<String> {
         let key = input_key(chat_id, user_id);
-        let mut conn = self
+        let value: Option<String> = self
             .redis
-            .pool()
-            .get()
+            .query(&redis::Cmd::get(&key))
             .await
-            .context("redis pool acquire (get_input)")?;
-        let value: Option<String> = conn.get(&key).await.context("GET cap:input")?;
+            .context("GET cap:input")?;
         Ok(value.unwrap_or_default())
     }
 
     pub async fn clear_input(&self, chat_id: i64, user_id: i64) -> Result<()> {
         let key = input_key(chat_id, user_id);
-        let mut conn = self
-            .redis
-            .pool()
-            .get()
-            .await
-            .context("redis pool acquire (clear_input)")?;
-        let _: i64 = conn.del(&key).await.context("DEL cap:input")?;
-        Ok(())
+        self.redis.invalidate(&key).await.context("DEL cap:input")
     }
 
     // ── Callback meta ─────────────────────────────────────────────────────
@@ -147,14 +152,9 @@ impl CaptchaState {
             lifetime_secs: ttl_secs,
         }
         .to_redis_string();
-        let mut conn = self
+        let _: () = self
             .redis
-            .pool()
-            .get()
-            .await
-            .context("redis pool acquire (set_meta)")?;
-        let _: () = conn
-            .set_ex(&key, payload, ttl_secs)
+            .query(&redis::Cmd::set_ex(&key, payload, ttl_secs))
             .await
             .context("SETEX cap:meta")?;
         Ok(())
@@ -162,54 +162,48 @@ impl CaptchaState {
 
     pub async fn get_meta(&self, chat_id: i64, message_id: i32) -> Result<Option<MetaPayload>> {
         let key = meta_key(chat_id, message_id);
-        let mut conn = self
+        let raw: Option<String> = self
             .redis
-            .pool()
-            .get()
+            .query(&redis::Cmd::get(&key))
             .await
-            .context("redis pool acquire (get_meta)")?;
-        let raw: Option<String> = conn.get(&key).await.context("GET cap:meta")?;
+            .context("GET cap:meta")?;
         Ok(raw.and_then(|s| MetaPayload::from_redis_string(&s)))
     }
 
     pub async fn clear_meta(&self, chat_id: i64, message_id: i32) -> Result<()> {
         let key = meta_key(chat_id, message_id);
-        let mut conn = self
-            .redis
-            .pool()
-            .get()
-            .await
-            .context("redis pool acquire (clear_meta)")?;
-        let _: i64 = conn.del(&key).await.context("DEL cap:meta")?;
-        Ok(())
+        self.redis.invalidate(&key).await.context("DEL cap:meta")
     }
 
     // ── Verified cache ────────────────────────────────────────────────────
 
     pub async fn mark_verified(&self, chat_id: i64, user_id: i64) -> Result<()> {
         let key = verified_key(chat_id, user_id);
-        let mut conn = self
+        let _: () = self
             .redis
-            .pool()
-            .get()
-            .await
-            .context("redis pool acquire (mark_verified)")?;
-        let _: () = conn
-            .set_ex(&key, "1", VERIFIED_CACHE_TTL_SECS)
+            .query(&redis::Cmd::set_ex(&key, "1", VERIFIED_CACHE_TTL_SECS))
             .await
             .context("SETEX cap:verified")?;
         Ok(())
     }
 
+    /// Drop the `is_verified` cache entry after an un-verify, so the gate
+    /// re-checks Postgres on the user's next message.
+    pub async fn clear_verified(&self, chat_id: i64, user_id: i64) -> Result<()> {
+        let key = verified_key(chat_id, user_id);
+        self.redis
+            .invalidate(&key)
+            .await
+            .context("DEL cap:verified")
+    }
+
     pub async fn is_verified_cached(&self, chat_id: i64, user_id: i64) -> Result<bool> {
         let key = verified_key(chat_id, user_id);
-        let mut conn = self
+        let v: Option<String> = self
             .redis
-            .pool()
-            .get()
+            .query(&redis::Cmd::get(&key))
             .await
-            .context("redis pool acquire (is_verified_cached)")?;
-        let v: Option<String> = conn.get(&key).await.context("GET cap:verified")?;
+            .context("GET cap:verified")?;
         Ok(v.is_some())
     }
 
@@ -219,35 +213,42 @@ impl CaptchaState {
     /// instead of a Redis SET so that "missing key" stays distinguishable
     /// from "empty set" with a single round-trip — the message gate uses
     /// that to decide whether to repopulate from `get_chat_administrators`.
+    /// The local copy is written first, so it is set even when Redis is not.
     pub async fn set_admins(&self, chat_id: i64, admins: &[i64]) -> Result<()> {
+        self.local_admins.insert(chat_id, admins.to_vec()).await;
         let key = admins_key(chat_id);
         let payload = serde_json::to_string(admins).context("serialize admin list")?;
-        let mut conn = self
+        let _: () = self
             .redis
-            .pool()
-            .get()
-            .await
-            .context("redis pool acquire (set_admins)")?;
-        let _: () = conn
-            .set_ex(&key, payload, ADMINS_CACHE_TTL_SECS)
+            .query(&redis::Cmd::set_ex(&key, payload, ADMINS_CACHE_TTL_SECS))
             .await
             .context("SETEX cap:admins")?;
         Ok(())
     }
 
     /// Returns `Some(list)` on cache hit, `None` on miss (key absent or
-    /// malformed payload — both treated as "needs repopulation").
+    /// malformed payload — both treated as "needs repopulation"). A Redis
+    /// error answers from the local copy instead, so an outage costs one
+    /// `get_chat_administrators` per chat and TTL, not one per message.
     pub async fn get_admins(&self, chat_id: i64) -> Result<Option<Vec<i64>>> {
         let key = admins_key(chat_id);
-        let mut conn = self
-            .redis
-            .pool()
-            .get()
-            .await
-            .context("redis pool acquire (get_admins)")?;
-        let raw: Option<String> = conn.get(&key).await.context("GET cap:admins")?;
+        let raw: Option<String> = match self.redis.query(&redis::Cmd::get(&key)).await {
+            Ok(raw) => raw,
+            Err(e) => {
+                debug!(error = %e, "GET cap:admins failed; using the local copy");
+                return Ok(self.local_admins.get(&chat_id).await);
+            }
+        };
         Ok(raw.and_then(|s| serde_json::from_str::<Vec<i64>>(&s).ok()))
     }
+
+    /// Drop the cached admin list so the next gate check re-fetches it. Called
+    /// on `chat_member` promotions / demotions.
+    pub async fn clear_admins(&self, chat_id: i64) -> Result<()> {
+        self.local_admins.invalidate(&chat_id).await;
+        let key = admins_key(chat_id);
+        self.redis.invalidate(&key).await.context("DEL cap:admins")
+    }
 }
 
 fn input_key(chat_id: i64, user_id: i64) -> String {
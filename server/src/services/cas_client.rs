@@ -1,18 +1,19 @@
 //! CAS (Combot Anti-Spam) lookup client. Two-tier cache: Moka 1h front,
 //! Redis 24h back, HTTP `{base_url}/check?user_id={id}` on miss.
 //! Fail-open on any error — falsely flagging a real user is worse than
-//! missing a spammer.
+//! missing a spammer. While Redis is unavailable the back tier is skipped
+//! and Moka is the only cache.
 //!
 //! See `server/docs/spam-detection.md` §"CAS integration".
 
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use moka::future::Cache;
-use redis::AsyncCommands;
 use tracing::{debug, instrument, warn};
 
 use crate::database::Redis;
+use crate::telemetry::metrics;
 
 /// Production base URL for cas.chat.
 pub const PRODUCTION_BASE_URL: &str = "https://api.cas.chat";
@@ -82,32 +83,37 @@ impl CasClient {
     pub async fn lookup(&self, user_id: i64) -> Verdict {
         if let Some(v) = self.moka.get(&user_id).await {
             debug!(verdict = ?v, "moka hit");
+            metrics::cas_lookup("moka", v.as_redis_str());
             return v;
         }
         if let Some(v) = self.redis_get(user_id).await {
             debug!(verdict = ?v, "redis hit");
             self.moka.insert(user_id, v).await;
+            metrics::cas_lookup("redis", v.as_redis_str());
             return v;
         }
-        match self.http_check(user_id).await {
+        let started = Instant::now();
+        let checked = self.http_check(user_id).await;
+        metrics::cas_http(started.elapsed());
+        match checked {
             Some(verdict) => {
                 self.write_through(user_id, verdict).await;
+                metrics::cas_lookup("http", verdict.as_redis_str());
                 verdict
             }
-            None => Verdict::Clean,
+            None => {
+                metrics::cas_lookup("fail_open", Verdict::Clean.as_redis_str());
+                Verdict::Clean
+            }
         }
     }
 
     async fn redis_get(&self, user_id: i64) -> Option<Verdict> {
-        let mut conn = match self.redis.pool().get().await {
-            Ok(c) => c,
-            Err(e) => {
-                warn!(error = %e, "redis pool acquire failed; skipping redis tier");
-                return None;
-            }
-        };
+        if !self.redis.is_available() {
+            return None;
+        }
         let key = redis_key(user_id);
-        let raw: Option<String> = match conn.get(&key).await {
+        let raw: Option<String> = match self.redis.query(&redis::Cmd::get(&key)).await {
             Ok(v) => v,
             Err(e) => {
                 warn!(error = %e, key, "redis GET failed; skipping redis tier");
@@ -122,18 +128,12 @@ impl CasClient {
         // every clean message. Recovery from a misclassified ban happens via
         // moderator unban, which is a separate flow.
         self.moka.insert(user_id, verdict).await;
-        let mut conn = match self.redis.pool().get().await {
-            Ok(c) => c,
-            Err(e) => {
-                warn!(error = %e, "redis pool acquire failed; skipping redis write");
-                return;
-            }
-        };
+        if !self.redis.is_available() {
+            return;
+        }
         let key = redis_key(user_id);
-        if let Err(e) = conn
-            .set_ex::<_, _, ()>(&key, verdict.as_redis_str(), REDIS_TTL_SECS)
-            .await
-        {
+        let set = redis::Cmd::set_ex(&key, verdict.as_redis_str(), REDIS_TTL_SECS);
+        if let Err(e) = self.redis.query::<()>(&set).await {
             warn!(error = %e, key, "redis SET EX failed");
         }
     }
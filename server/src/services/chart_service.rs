@@ -6,7 +6,9 @@
 //!
 //! 1. Counts — bars for `messages_seen`, `messages_deleted`, `users_verified`,
 //!    `users_banned`, `captcha.solved`, `captcha.expired`.
-//! 2. Last-7-days — bar per calendar day of `messages_seen`, oldest first.
+//! 2. History — bar per calendar day of `messages_seen`, oldest first: the
+//!    last 7 days, or the 30 / 90-day `rollup.history` for weekly / monthly
+//!    reports.
 //!
 //! Pure plotters → RGB pixel buffer → `image::codecs::webp::WebPEncoder`. No
 //! standalone `webp` crate dependency, matches the captcha pipeline. CPU work
@@ -134,13 +136,22 @@ fn draw_sparkline_panel<DB: DrawingBackend>(
 where
     DB::ErrorType: 'static,
 {
-    let points = &report.last_7_days_messages;
+    let points = report
+        .rollup
+        .as_ref()
+        .map_or(&report.last_7_days_messages, |r| &r.history);
     let max_value = points.iter().map(|p| p.messages).max().unwrap_or(0).max(1);
     let n = points.len();
+    // Per-bar side margin: 4px reads well for a week, but 90 bars in ~440px
+    // leave no room for gaps.
+    let bar_margin = if n > 14 { 0 } else { 4 };
 
     let mut chart = ChartBuilder::on(area)
         .margin(6)
-        .caption("Last 7 days · messages", ("sans-serif", 14).into_font())
+        .caption(
+            format!("Last {n} days · messages"),
+            ("sans-serif", 14).into_font(),
+        )
         .x_label_area_size(18)
         .y_label_area_size(28)
         .build_cartesian_2d(0..n, 0i64..(max_value + max_value / 5 + 1))
@@ -150,7 +161,7 @@ where
         .configure_mesh()
         .disable_x_mesh()
         .disable_y_mesh()
-        .x_labels(n)
+        .x_labels(n.min(8))
         .x_label_formatter(&|idx| {
             points
                 .get(*idx)
@@ -168,7 +179,7 @@ where
                 [(i, 0i64), (i + 1, p.messages)],
                 RGBColor(46, 134, 222).filled(),
             );
-            bar.set_margin(0, 0, 4, 4);
+            bar.set_margin(0, 0, bar_margin, bar_margin);
             bar
         }))
         .map_err(|e| anyhow::anyhow!("sparkline draw_series: {e}"))?;
@@ -179,7 +190,9 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::report::{CaptchaCounts, DailyPoint, ReportData};
+    use crate::models::report::{
+        CaptchaCounts, DailyPoint, PeriodTotals, ReportData, ReportPeriod, RollupData,
+    };
     use chrono::{Duration, NaiveDate, TimeZone, Utc};
 
     fn fixture() -> ReportData {
@@ -208,6 +221,7 @@ mod tests {
             },
             top_phrases: vec![],
             last_7_days_messages: last_7,
+            rollup: None,
         }
     }
 
@@ -230,6 +244,26 @@ mod tests {
         assert_eq!(&bytes[8..12], b"WEBP");
     }
 
+    #[test]
+    fn renders_90_day_history_under_max_bytes() {
+        let mut r = fixture();
+        let start = NaiveDate::from_ymd_opt(2026, 2, 2).unwrap();
+        r.rollup = Some(RollupData {
+            period: ReportPeriod::Monthly,
+            previous: PeriodTotals::default(),
+            history: (0..90)
+                .map(|i: i64| DailyPoint {
+                    date: start + Duration::days(i),
+                    messages: (i * 37) % 50,
+                })
+                .collect(),
+            top_ban_reasons: vec![],
+            captcha_trend: vec![],
+        });
+        let bytes = render(&r).expect("render");
+        assert!(bytes.len() <= MAX_BYTES, "WebP bytes={}", bytes.len());
+    }
+
     #[test]
     fn renders_zero_data_without_panic() {
         let mut r = fixture();
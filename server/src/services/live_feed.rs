@@ -0,0 +1,399 @@
+//! Live chat activity for the dashboard's `GET /chats/{chat_id}/events`
+//! stream: captchas issued, solved and expired, spam verdicts and
+//! moderation actions, as they happen.
+//!
+//! [`LiveFeed::publish`] appends an event to a capped per-chat Redis stream
+//! (`live:log:{chat_id}`, the resume buffer) and PUBLISHes it on
+//! `live:{chat_id}` in one script, so the stream entry id doubles as the
+//! SSE `id` and every replica sees the same order. Each process relays
+//! `live:*` into a local broadcast channel; an SSE connection subscribes to
+//! that and, after a reconnect, replays the stream past its
+//! `Last-Event-ID` ([`LiveFeed::replay`]).
+//!
+//! Best-effort by design: a failed publish is logged and dropped, never
+//! surfaced to the captcha / spam / moderation path that produced it. While
+//! Redis is unavailable an event reaches this replica's connections only,
+//! without an id. See `server/docs/live-events.md`.
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+use crate::database::{Redis, RedisError};
+
+/// Entries kept per chat for `Last-Event-ID` resumes (approximate:
+/// `XADD MAXLEN ~`).
+pub const BUFFER_LEN: usize = 1000;
+/// The resume buffer of a chat that has gone quiet expires after this.
+pub const BUFFER_TTL: Duration = Duration::from_secs(60 * 60);
+/// Events a slow connection may fall behind by before it is closed (and
+/// resumes from the buffer on reconnect).
+const LOCAL_CAPACITY: usize = 1024;
+const CHANNEL_PATTERN: &str = "live:*";
+
+/// XADD to the buffer, refresh its TTL and PUBLISH `"{id}\n{json}"`.
+/// Atomic, so the pub/sub order matches the stream order.
+const PUBLISH_SCRIPT: &str = r#"
+local id = redis.call('XADD', KEYS[1], 'MAXLEN', '~', ARGV[1], '*', 'event', ARGV[2])
+redis.call('EXPIRE', KEYS[1], ARGV[3])
+redis.call('PUBLISH', ARGV[4], id .. '\n' .. ARGV[2])
+return id
+"#;
+
+/// A Redis stream entry id, `{ms}-{seq}`. Orders like Redis does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StreamId {
+    pub ms: u64,
+    pub seq: u64,
+}
+
+impl FromStr for StreamId {
+    type Err = InvalidStreamId;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (ms, seq) = s.split_once('-').ok_or(InvalidStreamId)?;
+        Ok(Self {
+            ms: ms.parse().map_err(|_| InvalidStreamId)?,
+            seq: seq.parse().map_err(|_| InvalidStreamId)?,
+        })
+    }
+}
+
+impl fmt::Display for StreamId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.ms, self.seq)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("expected a stream id like 1715860000000-0")]
+pub struct InvalidStreamId;
+
+/// One event on the feed. Serialized as the SSE `data` (and the buffered
+/// entry); `type` doubles as the SSE `event` name.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LiveEvent {
+    /// Resume-buffer id; `None` for an event published while Redis was
+    /// unavailable.
+    #[serde(skip)]
+    pub id: Option<StreamId>,
+    pub chat_id: i64,
+    pub at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub kind: LiveEventKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum LiveEventKind {
+    #[serde(rename = "captcha.issued")]
+    CaptchaIssued {
+        user_id: i64,
+        expires_at: DateTime<Utc>,
+    },
+    #[serde(rename = "captcha.solved")]
+    CaptchaSolved { user_id: i64 },
+    #[serde(rename = "captcha.expired")]
+    CaptchaExpired { user_id: i64 },
+    /// A non-`Allow` spam pipeline verdict. The action it leads to follows
+    /// as `moderation.action`.
+    #[serde(rename = "spam.verdict")]
+    SpamVerdict {
+        user_id: i64,
+        message_id: i32,
+        /// `delete` or `ban`.
+        verdict: String,
+        /// The verdict's `reason_json` cut down by
+        /// [`PublicReason`](crate::models::moderation_action::PublicReason);
+        /// the excerpt of the message is not for the feed.
+        matched_rules: Option<Vec<String>>,
+        score: Option<f64>,
+    },
+    /// A ban, unban or delete that newly applied (`ModerationService`).
+    #[serde(rename = "moderation.action")]
+    ModerationAction {
+        action_id: Uuid,
+        user_id: i64,
+        action: String,
+        actor_kind: String,
+        actor_user_id: Option<i64>,
+        message_id: Option<i32>,
+        /// The ledger reason, through
+        /// [`PublicReason`](crate::models::moderation_action::PublicReason).
+        reason: Option<String>,
+        matched_rules: Option<Vec<String>>,
+        score: Option<f64>,
+    },
+}
+
+impl LiveEventKind {
+    /// Every `type`, for `?types=` validation.
+    pub const TYPES: [&'static str; 5] = [
+        "captcha.issued",
+        "captcha.solved",
+        "captcha.expired",
+        "spam.verdict",
+        "moderation.action",
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::CaptchaIssued { .. } => "captcha.issued",
+            Self::CaptchaSolved { .. } => "captcha.solved",
+            Self::CaptchaExpired { .. } => "captcha.expired",
+            Self::SpamVerdict { .. } => "spam.verdict",
+            Self::ModerationAction { .. } => "moderation.action",
+        }
+    }
+}
+
+/// Cheap to clone; every clone shares the local broadcast channel.
+#[derive(Clone)]
+pub struct LiveFeed {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    redis: Option<Arc<Redis>>,
+    local: broadcast::Sender<Arc<LiveEvent>>,
+    closed: CancellationToken,
+}
+
+impl LiveFeed {
+    /// Feed backed by Redis, plus the task relaying `live:*` into it. Both
+    /// stop on `shutdown`, which also ends every open SSE stream so the
+    /// HTTP server can drain.
+    pub fn start(redis: Arc<Redis>, shutdown: CancellationToken) -> (Self, JoinHandle<()>) {
+        let me = Self::new(redis.clone(), shutdown.clone());
+        let relay = me.clone();
+        let on_message = move |channel: String, payload: String| match parse_message(&payload) {
+            Some(event) => relay.deliver(event),
+            None => warn!(channel, "live feed: malformed message"),
+        };
+        let handle = redis.subscribe(CHANNEL_PATTERN, shutdown, on_message);
+        (me, handle)
+    }
+
+    /// Publish-only feed, for one-shot processes (the CLI): events reach
+    /// the servers' streams, nothing is relayed back here.
+    pub fn new(redis: Arc<Redis>, shutdown: CancellationToken) -> Self {
+        Self::with_redis(Some(redis), shutdown)
+    }
+
+    /// Feed without Redis, for tests and a CLI run without it: events reach
+    /// only this process's subscribers, without ids.
+    pub fn disabled() -> Self {
+        Self::with_redis(None, CancellationToken::new())
+    }
+
+    fn with_redis(redis: Option<Arc<Redis>>, closed: CancellationToken) -> Self {
+        let (local, _) = broadcast::channel(LOCAL_CAPACITY);
+        Self {
+            inner: Arc::new(Inner {
+                redis,
+                local,
+                closed,
+            }),
+        }
+    }
+
+    /// Publish an event for `chat_id`. Never fails: without Redis the event
+    /// is delivered locally, without an id.
+    pub async fn publish(&self, chat_id: i64, kind: LiveEventKind) {
+        let event = LiveEvent {
+            id: None,
+            chat_id,
+            at: Utc::now(),
+            kind,
+        };
+        let Some(redis) = &self.inner.redis else {
+            self.deliver(event);
+            return;
+        };
+        let json = match serde_json::to_string(&event) {
+            Ok(j) => j,
+            Err(e) => {
+                warn!(error = %e, chat_id, "live event serialize failed");
+                return;
+            }
+        };
+        let mut cmd = redis::cmd("EVAL");
+        cmd.arg(PUBLISH_SCRIPT)
+            .arg(1)
+            .arg(buffer_key(chat_id))
+            .arg(BUFFER_LEN)
+            .arg(&json)
+            .arg(BUFFER_TTL.as_secs())
+            .arg(channel(chat_id));
+        if let Err(e) = redis.query::<String>(&cmd).await {
+            debug!(error = %e, chat_id, kind = event.kind.name(), "live event not buffered; delivering locally");
+            self.deliver(event);
+        }
+    }
+
+    /// Events published from now on, for every chat. A receiver that lags
+    /// more than [`LOCAL_CAPACITY`] events behind gets `Lagged`.
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<LiveEvent>> {
+        self.inner.local.subscribe()
+    }
+
+    /// Buffered events of `chat_id` after `after`, oldest first. Empty when
+    /// the buffer has expired; starts at the oldest retained entry when
+    /// `after` has been trimmed.
+    pub async fn replay(
+        &self,
+        chat_id: i64,
+        after: StreamId,
+    ) -> Result<Vec<LiveEvent>, RedisError> {
+        let Some(redis) = &self.inner.redis else {
+            return Ok(Vec::new());
+        };
+        let mut cmd = redis::cmd("XRANGE");
+        cmd.arg(buffer_key(chat_id))
+            .arg(after.to_string())
+            .arg("+")
+            .arg("COUNT")
+            .arg(BUFFER_LEN + 1);
+        let entries: Vec<(String, Vec<String>)> = redis.query(&cmd).await?;
+        Ok(entries
+            .into_iter()
+            .filter_map(|(id, fields)| {
+                let id = id.parse::<StreamId>().ok().filter(|id| *id > after)?;
+                let json = fields.chunks(2).find(|f| f[0] == "event")?.get(1)?;
+                let mut event: LiveEvent = serde_json::from_str(json).ok()?;
+                event.id = Some(id);
+                Some(event)
+            })
+            .collect())
+    }
+
+    /// Fires on shutdown.
+    pub fn closed(&self) -> &CancellationToken {
+        &self.inner.closed
+    }
+
+    fn deliver(&self, event: LiveEvent) {
+        // `Err` only means nobody is listening.
+        let _ = self.inner.local.send(Arc::new(event));
+    }
+}
+
+fn buffer_key(chat_id: i64) -> String {
+    format!("live:log:{chat_id}")
+}
+
+fn channel(chat_id: i64) -> String {
+    format!("live:{chat_id}")
+}
+
+/// `"{stream id}\n{json}"`, as PUBLISHed by [`PUBLISH_SCRIPT`].
+fn parse_message(payload: &str) -> Option<LiveEvent> {
+    let (id, json) = payload.split_once('\n')?;
+    let mut event: LiveEvent = serde_json::from_str(json).ok()?;
+    event.id = Some(id.parse().ok()?);
+    Some(event)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solved() -> LiveEvent {
+        LiveEvent {
+            id: None,
+            chat_id: -1001234567890,
+            at: "2026-05-16T12:00:00Z".parse().unwrap(),
+            kind: LiveEventKind::CaptchaSolved { user_id: 42 },
+        }
+    }
+
+    #[test]
+    fn stream_ids_order_numerically() {
+        let a: StreamId = "1715860000000-9".parse().unwrap();
+        let b: StreamId = "1715860000000-10".parse().unwrap();
+        let c: StreamId = "1715860000001-0".parse().unwrap();
+        assert!(a < b && b < c);
+        assert_eq!(b.to_string(), "1715860000000-10");
+        for bad in ["", "17158", "1-x", "-1-0", "1-2-3"] {
+            assert_eq!(bad.parse::<StreamId>(), Err(InvalidStreamId), "{bad}");
+        }
+    }
+
+    #[test]
+    fn event_json_is_flat_and_typed() {
+        let json = serde_json::to_value(solved()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "chat_id": -1001234567890_i64,
+                "at": "2026-05-16T12:00:00Z",
+                "type": "captcha.solved",
+                "user_id": 42,
+            })
+        );
+    }
+
+    #[test]
+    fn type_names_match_serde_tags() {
+        let kinds = [
+            LiveEventKind::CaptchaIssued {
+                user_id: 1,
+                expires_at: Utc::now(),
+            },
+            LiveEventKind::CaptchaSolved { user_id: 1 },
+            LiveEventKind::CaptchaExpired { user_id: 1 },
+            LiveEventKind::SpamVerdict {
+                user_id: 1,
+                message_id: 2,
+                verdict: "delete".into(),
+                matched_rules: Some(vec!["ngram".into()]),
+                score: Some(3.5),
+            },
+            LiveEventKind::ModerationAction {
+                action_id: Uuid::nil(),
+                user_id: 1,
+                action: "ban".into(),
+                actor_kind: "bot".into(),
+                actor_user_id: None,
+                message_id: None,
+                reason: None,
+                matched_rules: None,
+                score: None,
+            },
+        ];
+        for (kind, name) in kinds.iter().zip(LiveEventKind::TYPES) {
+            assert_eq!(kind.name(), name);
+            assert_eq!(serde_json::to_value(kind).unwrap()["type"], name);
+        }
+    }
+
+    #[test]
+    fn published_message_round_trips() {
+        let json = serde_json::to_string(&solved()).unwrap();
+        let event = parse_message(&format!("1715860000000-3\n{json}")).unwrap();
+        assert_eq!(event.id, Some("1715860000000-3".parse().unwrap()));
+        assert_eq!(LiveEvent { id: None, ..event }, solved());
+        assert!(parse_message(&json).is_none());
+        assert!(parse_message("nope\n{}").is_none());
+    }
+
+    #[tokio::test]
+    async fn disabled_feed_delivers_locally_without_ids() {
+        let feed = LiveFeed::disabled();
+        let mut rx = feed.subscribe();
+        feed.publish(-1, LiveEventKind::CaptchaExpired { user_id: 7 })
+            .await;
+        let event = rx.recv().await.unwrap();
+        assert_eq!(event.id, None);
+        assert_eq!(event.kind, LiveEventKind::CaptchaExpired { user_id: 7 });
+    }
+}
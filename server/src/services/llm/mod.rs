@@ -0,0 +1,332 @@
+//! LLM providers behind one trait, selected per chat.
+//!
+//! `chat_config.llm_provider` picks the implementation:
+//!
+//! | value               | impl                         | endpoint                              |
+//! |---------------------|------------------------------|---------------------------------------|
+//! | `openai`            | [`OpenAiCompatible`]         | `CONFIG_OPENAI_BASE_URL`              |
+//! | `openai_compatible` | [`OpenAiCompatible`]         | `chat_config.llm_base_url` (llama.cpp, vLLM, Ollama's `/v1`) |
+//! | `ollama`            | [`Ollama`]                   | `chat_config.llm_base_url` (`/api/chat`) |
+//!
+//! A provider makes exactly one HTTP attempt per [`LlmProvider::complete`]
+//! call and classifies the failure; [`chat`] owns the retry-with-backoff loop
+//! and the metrics, so every provider retries the same way.
+//!
+//! Token accounting is provider-specific — each impl reports what its API
+//! counts (see [`ChatCompletion::total_tokens`]). Callers charge that number
+//! to `daily_stats('openai_tokens_used')` regardless of provider, so the
+//! per-chat `summary_token_budget` caps local models too.
+
+pub mod ollama;
+pub mod openai;
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{Result, bail};
+use async_trait::async_trait;
+use serde::Serialize;
+use tracing::{debug, warn};
+
+pub use ollama::Ollama;
+pub use openai::OpenAiCompatible;
+
+use crate::telemetry::metrics;
+
+const MAX_RETRIES: u32 = 3;
+const INITIAL_BACKOFF_MS: u64 = 500;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+/// Cap on a server-supplied `Retry-After`.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChatMessage {
+    pub role: ChatRole,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChatRole {
+    System,
+    User,
+    Assistant,
+}
+
+#[derive(Debug, Clone)]
+pub struct ChatRequest {
+    pub model: String,
+    pub messages: Vec<ChatMessage>,
+    pub max_tokens: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct ChatCompletion {
+    pub content: String,
+    /// Prompt + completion tokens as the provider counts them. Falls back to
+    /// [`estimate_tokens`] when the server reports no usage.
+    pub total_tokens: u32,
+}
+
+/// `chat_config.llm_provider`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProviderKind {
+    OpenAi,
+    OpenAiCompatible,
+    Ollama,
+}
+
+impl ProviderKind {
+    pub fn as_db_str(self) -> &'static str {
+        match self {
+            Self::OpenAi => "openai",
+            Self::OpenAiCompatible => "openai_compatible",
+            Self::Ollama => "ollama",
+        }
+    }
+
+    pub fn from_db_str(s: &str) -> Option<Self> {
+        match s {
+            "openai" => Some(Self::OpenAi),
+            "openai_compatible" => Some(Self::OpenAiCompatible),
+            "ollama" => Some(Self::Ollama),
+            _ => None,
+        }
+    }
+
+    /// Hosted OpenAI bills per token and needs a key; self-hosted servers
+    /// usually run without one.
+    pub fn requires_api_key(self) -> bool {
+        matches!(self, Self::OpenAi)
+    }
+}
+
+/// Why one attempt failed. [`chat`] retries `Retryable` and surfaces `Fatal`.
+#[derive(Debug)]
+pub enum AttemptError {
+    /// 429 / 5xx / transport error. `retry_after` overrides the backoff.
+    Retryable {
+        error: anyhow::Error,
+        retry_after: Option<Duration>,
+        transport: bool,
+    },
+    /// Any other non-2xx, or an undecodable body.
+    Fatal(anyhow::Error),
+}
+
+#[async_trait]
+pub trait LlmProvider: Send + Sync {
+    fn kind(&self) -> ProviderKind;
+
+    /// One HTTP attempt. No retries — see [`chat`].
+    async fn complete(&self, request: &ChatRequest) -> Result<ChatCompletion, AttemptError>;
+}
+
+/// Per-chat provider selection, as stored in `chat_config`.
+#[derive(Debug, Clone)]
+pub struct ProviderSettings {
+    pub kind: ProviderKind,
+    /// Required for every provider except `openai`.
+    pub base_url: Option<String>,
+    pub api_key: Option<String>,
+}
+
+/// Builds the provider for a chat. Holds the shared HTTP client and the
+/// global OpenAI base URL; per-chat settings are applied per call, so a
+/// moderator's config edit takes effect on the next request.
+#[derive(Clone)]
+pub struct LlmProviders {
+    http: reqwest::Client,
+    openai_base_url: String,
+}
+
+impl LlmProviders {
+    pub fn new(openai_base_url: String) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .expect("reqwest client builds");
+        Self {
+            http,
+            openai_base_url,
+        }
+    }
+
+    pub fn for_chat(&self, settings: &ProviderSettings) -> Result<Arc<dyn LlmProvider>> {
+        let base_url = match (settings.kind, settings.base_url.as_deref()) {
+            (ProviderKind::OpenAi, _) => self.openai_base_url.clone(),
+            (_, Some(url)) => url.to_string(),
+            (kind, None) => bail!("llm_provider {} needs llm_base_url", kind.as_db_str()),
+        };
+        let http = self.http.clone();
+        let api_key = settings.api_key.clone();
+        Ok(match settings.kind {
+            kind @ (ProviderKind::OpenAi | ProviderKind::OpenAiCompatible) => {
+                Arc::new(OpenAiCompatible::new(kind, http, base_url, api_key))
+            }
+            ProviderKind::Ollama => Arc::new(Ollama::new(http, base_url, api_key)),
+        })
+    }
+}
+
+/// Run `request` against `provider`, retrying 429 / 5xx / transport errors
+/// up to [`MAX_RETRIES`] times with exponential backoff (or `Retry-After`).
+pub async fn chat(provider: &dyn LlmProvider, request: &ChatRequest) -> Result<ChatCompletion> {
+    let kind = provider.kind().as_db_str();
+    let mut last_err: Option<anyhow::Error> = None;
+    for attempt in 0..MAX_RETRIES {
+        let started = Instant::now();
+        match provider.complete(request).await {
+            Ok(completion) => {
+                metrics::llm_request(kind, "ok", started.elapsed());
+                metrics::llm_tokens(kind, completion.total_tokens);
+                return Ok(completion);
+            }
+            Err(AttemptError::Fatal(e)) => {
+                metrics::llm_request(kind, "error", started.elapsed());
+                return Err(e.context(format!("{kind} request failed")));
+            }
+            Err(AttemptError::Retryable {
+                error,
+                retry_after,
+                transport,
+            }) => {
+                let outcome = if transport { "transport" } else { "retryable" };
+                metrics::llm_request(kind, outcome, started.elapsed());
+                // Nothing left to wait for after the last attempt.
+                if attempt + 1 < MAX_RETRIES {
+                    warn!(provider = kind, attempt, error = %error, "llm attempt failed; retrying");
+                    sleep_with_backoff(attempt, retry_after).await;
+                }
+                last_err = Some(error);
+            }
+        }
+    }
+    Err(last_err
+        .unwrap_or_else(|| anyhow::anyhow!("{kind} exhausted retries"))
+        .context(format!("{kind} gave up after {MAX_RETRIES} attempts")))
+}
+
+async fn sleep_with_backoff(attempt: u32, retry_after: Option<Duration>) {
+    let backoff = retry_after
+        .unwrap_or_else(|| Duration::from_millis(INITIAL_BACKOFF_MS * (1u64 << attempt)));
+    debug!(attempt, ?backoff, "sleeping before llm retry");
+    tokio::time::sleep(backoff).await;
+}
+
+/// Classify a non-2xx status: 429 and 5xx are worth retrying.
+pub(crate) fn is_retryable(status: reqwest::StatusCode) -> bool {
+    status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+pub(crate) fn parse_retry_after(value: Option<&reqwest::header::HeaderValue>) -> Option<Duration> {
+    let v = value?.to_str().ok()?;
+    // RFC 9110: Retry-After is either delta-seconds or HTTP-date. We accept
+    // delta-seconds only; HTTP-date is rare for LLM APIs and not worth a parser.
+    let secs: u64 = v.trim().parse().ok()?;
+    Some(Duration::from_secs(secs).min(MAX_RETRY_AFTER))
+}
+
+/// Turn a failed response into an [`AttemptError`], reading the body for
+/// the error message.
+pub(crate) async fn status_error(provider: &str, resp: reqwest::Response) -> AttemptError {
+    let status = resp.status();
+    let retry_after = parse_retry_after(resp.headers().get("retry-after"));
+    let body_text = resp.text().await.unwrap_or_default();
+    let preview: String = body_text.chars().take(200).collect();
+    let error = anyhow::anyhow!("{provider} status {status}: {preview}");
+    if is_retryable(status) {
+        AttemptError::Retryable {
+            error,
+            retry_after,
+            transport: false,
+        }
+    } else {
+        AttemptError::Fatal(error)
+    }
+}
+
+/// Rough token count (≈4 chars per token) for servers that report no usage,
+/// so the daily budget still moves.
+pub fn estimate_tokens(messages: &[ChatMessage], output: &str) -> u32 {
+    let chars: usize = messages
+        .iter()
+        .map(|m| m.content.chars().count())
+        .sum::<usize>()
+        + output.chars().count();
+    u32::try_from(chars.div_ceil(4)).unwrap_or(u32::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retry_after_parses_delta_seconds() {
+        let v = reqwest::header::HeaderValue::from_static("3");
+        let d = parse_retry_after(Some(&v));
+        assert_eq!(d, Some(Duration::from_secs(3)));
+    }
+
+    #[test]
+    fn retry_after_caps_at_60s() {
+        let v = reqwest::header::HeaderValue::from_static("3600");
+        let d = parse_retry_after(Some(&v));
+        assert_eq!(d, Some(Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn retry_after_rejects_http_date() {
+        let v = reqwest::header::HeaderValue::from_static("Wed, 21 Oct 2026 07:28:00 GMT");
+        assert_eq!(parse_retry_after(Some(&v)), None);
+    }
+
+    #[test]
+    fn is_retryable_matches_429_and_5xx() {
+        use reqwest::StatusCode;
+        assert!(is_retryable(StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_retryable(StatusCode::INTERNAL_SERVER_ERROR));
+        assert!(is_retryable(StatusCode::BAD_GATEWAY));
+        assert!(!is_retryable(StatusCode::UNAUTHORIZED));
+        assert!(!is_retryable(StatusCode::BAD_REQUEST));
+    }
+
+    #[test]
+    fn provider_kind_round_trips() {
+        for kind in [
+            ProviderKind::OpenAi,
+            ProviderKind::OpenAiCompatible,
+            ProviderKind::Ollama,
+        ] {
+            assert_eq!(ProviderKind::from_db_str(kind.as_db_str()), Some(kind));
+        }
+        assert_eq!(ProviderKind::from_db_str("anthropic"), None);
+    }
+
+    #[test]
+    fn self_hosted_providers_need_a_base_url() {
+        let providers = LlmProviders::new("https://api.openai.com".into());
+        let settings = ProviderSettings {
+            kind: ProviderKind::Ollama,
+            base_url: None,
+            api_key: None,
+        };
+        assert!(providers.for_chat(&settings).is_err());
+
+        let settings = ProviderSettings {
+            kind: ProviderKind::OpenAi,
+            base_url: None,
+            api_key: Some("sk-test".into()),
+        };
+        assert!(providers.for_chat(&settings).is_ok());
+    }
+
+    #[test]
+    fn estimate_counts_four_chars_per_token() {
+        let messages = vec![ChatMessage {
+            role: ChatRole::User,
+            content: "abcdefgh".into(),
+        }];
+        assert_eq!(estimate_tokens(&messages, "abcd"), 3);
+    }
+}
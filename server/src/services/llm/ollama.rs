@@ -0,0 +1,111 @@
+//! Ollama's native chat API (`POST {base}/api/chat`, `stream: false`).
+//!
+//! Preferred over the `/v1` shim because it reports exact token counts:
+//! `prompt_eval_count + eval_count`. Ollama omits `prompt_eval_count` when
+//! the prompt was served from its KV cache, so a missing count is treated as
+//! zero rather than estimated — the model really did no prompt work.
+
+use anyhow::Context;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    AttemptError, ChatCompletion, ChatMessage, ChatRequest, LlmProvider, ProviderKind,
+    estimate_tokens, status_error,
+};
+
+const TEMPERATURE: f32 = 0.4;
+
+pub struct Ollama {
+    http: reqwest::Client,
+    base_url: String,
+    api_key: Option<String>,
+}
+
+impl Ollama {
+    /// `api_key` is sent as a bearer token for Ollama instances behind an
+    /// authenticating reverse proxy; plain Ollama ignores it.
+    pub fn new(http: reqwest::Client, base_url: String, api_key: Option<String>) -> Self {
+        Self {
+            http,
+            base_url,
+            api_key,
+        }
+    }
+}
+
+#[async_trait]
+impl LlmProvider for Ollama {
+    fn kind(&self) -> ProviderKind {
+        ProviderKind::Ollama
+    }
+
+    async fn complete(&self, request: &ChatRequest) -> Result<ChatCompletion, AttemptError> {
+        let url = format!("{}/api/chat", self.base_url.trim_end_matches('/'));
+        let body = OllamaChatRequest {
+            model: &request.model,
+            messages: &request.messages,
+            stream: false,
+            options: OllamaOptions {
+                num_predict: request.max_tokens,
+                temperature: TEMPERATURE,
+            },
+        };
+        let mut builder = self.http.post(&url).json(&body);
+        if let Some(key) = self.api_key.as_deref() {
+            builder = builder.bearer_auth(key);
+        }
+
+        let resp = builder.send().await.map_err(|e| AttemptError::Retryable {
+            error: anyhow::Error::from(e).context("ollama POST"),
+            retry_after: None,
+            transport: true,
+        })?;
+        if !resp.status().is_success() {
+            return Err(status_error("ollama", resp).await);
+        }
+
+        let parsed: OllamaChatResponse = resp
+            .json()
+            .await
+            .context("decode ollama response")
+            .map_err(AttemptError::Fatal)?;
+        let content = parsed.message.content;
+        let total_tokens = match (parsed.prompt_eval_count, parsed.eval_count) {
+            (None, None) => estimate_tokens(&request.messages, &content),
+            (p, e) => p.unwrap_or(0).saturating_add(e.unwrap_or(0)),
+        };
+        Ok(ChatCompletion {
+            content,
+            total_tokens,
+        })
+    }
+}
+
+// ── wire types ─────────────────────────────────────────────────────────────
+
+#[derive(Debug, Serialize)]
+struct OllamaChatRequest<'a> {
+    model: &'a str,
+    messages: &'a [ChatMessage],
+    stream: bool,
+    options: OllamaOptions,
+}
+
+#[derive(Debug, Serialize)]
+struct OllamaOptions {
+    num_predict: u32,
+    temperature: f32,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaChatResponse {
+    message: OllamaMessage,
+    prompt_eval_count: Option<u32>,
+    eval_count: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaMessage {
+    content: String,
+}
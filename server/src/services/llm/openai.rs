@@ -0,0 +1,155 @@
+//! OpenAI Chat Completions (`POST {base}/v1/chat/completions`). Serves both
+//! hosted OpenAI and self-hosted servers that speak the same wire format
+//! (llama.cpp `server`, vLLM, Ollama's `/v1` shim).
+//!
+//! Tokens: `usage.total_tokens`, else `prompt_tokens + completion_tokens`
+//! (older llama.cpp builds), else [`estimate_tokens`](super::estimate_tokens).
+
+use anyhow::Context;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    AttemptError, ChatCompletion, ChatMessage, ChatRequest, LlmProvider, ProviderKind,
+    estimate_tokens, status_error,
+};
+
+const TEMPERATURE: f32 = 0.4;
+
+pub struct OpenAiCompatible {
+    kind: ProviderKind,
+    http: reqwest::Client,
+    base_url: String,
+    api_key: Option<String>,
+}
+
+impl OpenAiCompatible {
+    pub fn new(
+        kind: ProviderKind,
+        http: reqwest::Client,
+        base_url: String,
+        api_key: Option<String>,
+    ) -> Self {
+        Self {
+            kind,
+            http,
+            base_url,
+            api_key,
+        }
+    }
+}
+
+#[async_trait]
+impl LlmProvider for OpenAiCompatible {
+    fn kind(&self) -> ProviderKind {
+        self.kind
+    }
+
+    async fn complete(&self, request: &ChatRequest) -> Result<ChatCompletion, AttemptError> {
+        let provider = self.kind.as_db_str();
+        let url = format!(
+            "{}/v1/chat/completions",
+            self.base_url.trim_end_matches('/')
+        );
+        let body = ChatCompletionsRequest {
+            model: &request.model,
+            messages: &request.messages,
+            max_tokens: request.max_tokens,
+            temperature: TEMPERATURE,
+        };
+        let mut builder = self.http.post(&url).json(&body);
+        if let Some(key) = self.api_key.as_deref() {
+            builder = builder.bearer_auth(key);
+        }
+
+        let resp = builder.send().await.map_err(|e| AttemptError::Retryable {
+            error: anyhow::Error::from(e).context(format!("{provider} POST")),
+            retry_after: None,
+            transport: true,
+        })?;
+        if !resp.status().is_success() {
+            return Err(status_error(provider, resp).await);
+        }
+
+        let parsed: ChatCompletionsResponse = resp
+            .json()
+            .await
+            .with_context(|| format!("decode {provider} response"))
+            .map_err(AttemptError::Fatal)?;
+        let content = parsed
+            .choices
+            .into_iter()
+            .next()
+            .map(|c| c.message.content)
+            .unwrap_or_default();
+        let total_tokens = parsed
+            .usage
+            .and_then(ChatUsage::total)
+            .unwrap_or_else(|| estimate_tokens(&request.messages, &content));
+        Ok(ChatCompletion {
+            content,
+            total_tokens,
+        })
+    }
+}
+
+// ── wire types ─────────────────────────────────────────────────────────────
+
+#[derive(Debug, Serialize)]
+struct ChatCompletionsRequest<'a> {
+    model: &'a str,
+    messages: &'a [ChatMessage],
+    max_tokens: u32,
+    temperature: f32,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatCompletionsResponse {
+    choices: Vec<ChatChoice>,
+    usage: Option<ChatUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatChoice {
+    message: ChatChoiceMessage,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatChoiceMessage {
+    content: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatUsage {
+    total_tokens: Option<u32>,
+    prompt_tokens: Option<u32>,
+    completion_tokens: Option<u32>,
+}
+
+impl ChatUsage {
+    fn total(self) -> Option<u32> {
+        self.total_tokens
+            .or(match (self.prompt_tokens, self.completion_tokens) {
+                (None, None) => None,
+                (p, c) => Some(p.unwrap_or(0).saturating_add(c.unwrap_or(0))),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn usage_prefers_total_then_sums_parts() {
+        let usage: ChatUsage = serde_json::from_str(r#"{"total_tokens": 42}"#).unwrap();
+        assert_eq!(usage.total(), Some(42));
+
+        let usage: ChatUsage =
+            serde_json::from_str(r#"{"prompt_tokens": 30, "completion_tokens": 12}"#).unwrap();
+        assert_eq!(usage.total(), Some(42));
+
+        let usage: ChatUsage = serde_json::from_str("{}").unwrap();
+        assert_eq!(usage.total(), None);
+    }
+}
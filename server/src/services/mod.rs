@@ -1,12 +1,26 @@
-//! Business-logic services (captcha, spam, moderation, reports, summary).
+//! Business-logic services (auth, captcha, spam, moderation, reports, summary).
 //! Populated from M1 onwards — see `server/docs/architecture.md`.
 
+pub mod api_keys;
+pub mod appeal;
+pub mod audit_log;
+pub mod auth_service;
+pub mod bulk_service;
 pub mod captcha;
 pub mod cas_client;
 pub mod chart_service;
+pub mod live_feed;
+pub mod llm;
+pub mod mod_log;
 pub mod moderation_service;
-pub mod openai_client;
+pub mod moderator_sync;
+pub mod outbound;
+pub mod public_report;
 pub mod report_render;
 pub mod report_service;
 pub mod spam;
+pub mod sqlite_import;
 pub mod summary_service;
+pub mod user_info;
+pub mod user_lists;
+pub mod webhooks;
@@ -0,0 +1,469 @@
+//! Moderation log channel: renders `moderation_actions` rows into MarkdownV2
+//! entries for `chat_config.log_chat_id`, plus the `vl:` callback scheme of
+//! the one-tap buttons attached to them.
+//!
+//! `moderation_actions` doubles as the outbox: `logged_at IS NULL` marks an
+//! entry the `mod_log` job has not posted yet. Every writer (the moderation
+//! service, captcha pipeline, expiry job, role sync) already inserts there,
+//! so nothing upstream has to know the log channel exists.
+//!
+//! The spam verdict's `excerpt` of the removed message is only there for the
+//! log entry: stamping a row `logged` (posted or skipped) also strips it from
+//! `reason`, so the permanent ledger keeps the rules and score but none of
+//! the user's text.
+//!
+//! Callback data scheme: `vl:{op}:{chat_id}:{user_id}` where `op` is
+//!
+//!   * `ub` — unban (`Action::Unban` through `ModerationService`);
+//!   * `rv` — restore verification (`CaptchaService::verify_manual`).
+//!
+//! `chat_id` is the moderated chat, not the log channel. The handler checks
+//! that the button was pressed inside that chat's configured log channel and
+//! that the presser holds the matching permission there.
+
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup};
+use uuid::Uuid;
+
+use crate::models::chat_moderator::Permission;
+use crate::services::report_render::escape;
+
+pub const CALLBACK_PREFIX: &str = "vl";
+/// `CALLBACK_PREFIX` plus the separator, for the dispatcher's per-update
+/// filter (same trick as the captcha `vc:` prefix).
+pub const CALLBACK_PREFIX_WITH_COLON: &str = "vl:";
+
+/// Offending-text excerpt length in a log entry. The spam pipeline already
+/// caps what it stores in `reason`; this is a second bound for hand-written
+/// reasons.
+const EXCERPT_MAX_CHARS: usize = 300;
+
+/// How long a claimed entry stays invisible to other passes. Covers a full
+/// batch queued behind one channel's ~20 messages/minute.
+pub const CLAIM_LEASE: Duration = Duration::from_secs(300);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogOp {
+    Unban,
+    RestoreVerification,
+}
+
+impl LogOp {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Unban => "ub",
+            Self::RestoreVerification => "rv",
+        }
+    }
+
+    fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "ub" => Some(Self::Unban),
+            "rv" => Some(Self::RestoreVerification),
+            _ => None,
+        }
+    }
+
+    /// Permission the presser needs in the moderated chat.
+    pub fn permission(self) -> Permission {
+        match self {
+            Self::Unban => Permission::Ban,
+            Self::RestoreVerification => Permission::Verify,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Unban => "Unban",
+            Self::RestoreVerification => "Restore verification",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LogCallback {
+    pub op: LogOp,
+    pub chat_id: i64,
+    pub user_id: i64,
+}
+
+pub fn data_for(op: LogOp, chat_id: i64, user_id: i64) -> String {
+    format!("{CALLBACK_PREFIX}:{}:{chat_id}:{user_id}", op.as_str())
+}
+
+pub fn parse_callback(data: &str) -> Option<LogCallback> {
+    let mut it = data.splitn(4, ':');
+    if it.next()? != CALLBACK_PREFIX {
+        return None;
+    }
+    let op = LogOp::from_str(it.next()?)?;
+    let chat_id = it.next()?.parse().ok()?;
+    let user_id = it.next()?.parse().ok().filter(|id: &i64| *id > 0)?;
+    Some(LogCallback {
+        op,
+        chat_id,
+        user_id,
+    })
+}
+
+/// One unposted ledger row joined with its chat's log channel and title.
+#[derive(Debug, Clone)]
+pub struct LogEntry {
+    pub id: Uuid,
+    pub chat_id: i64,
+    pub log_chat_id: i64,
+    pub chat_title: Option<String>,
+    pub target_user_id: i64,
+    pub action: String,
+    pub actor_kind: String,
+    pub actor_user_id: Option<i64>,
+    pub reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Claim up to `limit` unposted rows of chats that have a log channel,
+/// oldest first, by pushing their `log_claimed_until` out by [`CLAIM_LEASE`].
+/// Concurrent passes (other replicas) skip them until they are stamped,
+/// [`release`]d, or the lease runs out.
+pub async fn claim_pending(pool: &PgPool, limit: i64) -> Result<Vec<LogEntry>> {
+    let rows = sqlx::query!(
+        r#"
+        WITH due AS (
+            SELECT m.id
+            FROM moderation_actions m
+            JOIN chat_config c ON c.chat_id = m.chat_id
+            WHERE m.logged_at IS NULL AND c.log_chat_id IS NOT NULL
+              AND (m.log_claimed_until IS NULL OR m.log_claimed_until <= NOW())
+            ORDER BY m.created_at
+            LIMIT $1
+            FOR UPDATE OF m SKIP LOCKED
+        )
+        UPDATE moderation_actions m
+        SET log_claimed_until = NOW() + make_interval(secs => $2::DOUBLE PRECISION)
+        FROM due, chat_config c
+        LEFT JOIN chat_info_cache i ON i.chat_id = c.chat_id
+        WHERE m.id = due.id AND c.chat_id = m.chat_id
+        RETURNING m.id, m.chat_id, c.log_chat_id AS "log_chat_id!", i.title AS "chat_title?",
+                  m.target_user_id, m.action, m.actor_kind, m.actor_user_id, m.reason,
+                  m.created_at
+        "#,
+        limit,
+        CLAIM_LEASE.as_secs_f64(),
+    )
+    .fetch_all(pool)
+    .await
+    .context("claim pending mod log entries")?;
+    // UPDATE … RETURNING has no order of its own.
+    let mut entries: Vec<LogEntry> = rows
+        .into_iter()
+        .map(|r| LogEntry {
+            id: r.id,
+            chat_id: r.chat_id,
+            log_chat_id: r.log_chat_id,
+            chat_title: r.chat_title,
+            target_user_id: r.target_user_id,
+            action: r.action,
+            actor_kind: r.actor_kind,
+            actor_user_id: r.actor_user_id,
+            reason: r.reason,
+            created_at: r.created_at,
+        })
+        .collect();
+    entries.sort_by_key(|e| e.created_at);
+    Ok(entries)
+}
+
+/// Stamp unposted rows of chats without a log channel so they leave the
+/// outbox index. Returns the number of rows stamped.
+pub async fn skip_unconfigured(pool: &PgPool) -> Result<u64> {
+    let res = sqlx::query!(
+        r#"
+        UPDATE moderation_actions m
+        SET logged_at = NOW(),
+            reason = CASE WHEN reason LIKE '{%'
+                          THEN COALESCE((try_jsonb(reason) - 'excerpt')::TEXT, reason)
+                          ELSE reason END
+        WHERE m.logged_at IS NULL
+          AND NOT EXISTS (
+              SELECT 1 FROM chat_config c
+              WHERE c.chat_id = m.chat_id AND c.log_chat_id IS NOT NULL
+          )
+        "#,
+    )
+    .execute(pool)
+    .await
+    .context("UPDATE moderation_actions (skip unlogged)")?;
+    Ok(res.rows_affected())
+}
+
+/// Stamp a posted (or undeliverable) entry and drop its `excerpt`.
+pub async fn mark_logged(pool: &PgPool, id: Uuid) -> Result<()> {
+    sqlx::query!(
+        r#"
+        UPDATE moderation_actions
+        SET logged_at = NOW(),
+            reason = CASE WHEN reason LIKE '{%'
+                          THEN COALESCE((try_jsonb(reason) - 'excerpt')::TEXT, reason)
+                          ELSE reason END
+        WHERE id = $1
+        "#,
+        id,
+    )
+    .execute(pool)
+    .await
+    .context("UPDATE moderation_actions.logged_at")?;
+    Ok(())
+}
+
+/// Drop the claim on entries a pass took but did not get to post, so the
+/// next pass picks them up without waiting out the lease.
+pub async fn release(pool: &PgPool, ids: &[Uuid]) -> Result<()> {
+    sqlx::query!(
+        r#"UPDATE moderation_actions SET log_claimed_until = NULL WHERE id = ANY($1)"#,
+        ids,
+    )
+    .execute(pool)
+    .await
+    .context("release mod log claims")?;
+    Ok(())
+}
+
+/// `chat_config.log_chat_id` for `chat_id`, `None` when unset.
+pub async fn log_chat_for(pool: &PgPool, chat_id: i64) -> Result<Option<i64>> {
+    let row = sqlx::query_scalar!(
+        r#"SELECT log_chat_id FROM chat_config WHERE chat_id = $1"#,
+        chat_id,
+    )
+    .fetch_optional(pool)
+    .await
+    .context("SELECT chat_config.log_chat_id")?;
+    Ok(row.flatten())
+}
+
+/// MarkdownV2 body for one entry:
+///
+/// ```text
+/// 🔨 Ban · Chat title
+/// User: 4242 (link)
+/// By: bot
+/// Rules: ngram
+/// > offending text
+/// ```
+pub fn render(entry: &LogEntry) -> String {
+    let chat = entry
+        .chat_title
+        .clone()
+        .unwrap_or_else(|| entry.chat_id.to_string());
+    let mut out = format!(
+        "{} *{}* · {}\n",
+        emoji_for(&entry.action),
+        escape(&action_label(&entry.action)),
+        escape(&chat)
+    );
+    out.push_str(&format!("User: {}\n", user_link(entry.target_user_id)));
+    match entry.actor_user_id {
+        Some(id) if entry.actor_kind == "moderator" => {
+            out.push_str(&format!("By: {}\n", user_link(id)));
+        }
+        _ => out.push_str(&format!("By: {}\n", escape(&entry.actor_kind))),
+    }
+
+    let reason = entry
+        .reason
+        .as_deref()
+        .map(parse_reason)
+        .unwrap_or_default();
+    if !reason.rules.is_empty() {
+        out.push_str(&format!("Rules: {}\n", escape(&reason.rules.join(", "))));
+    }
+    if let Some(text) = reason.text {
+        out.push_str(&format!("Reason: {}\n", escape(&text)));
+    }
+    if let Some(excerpt) = reason.excerpt {
+        for line in truncate(&excerpt, EXCERPT_MAX_CHARS).lines() {
+            out.push('>');
+            out.push_str(&escape(line));
+            out.push('\n');
+        }
+    }
+    out.push_str(&escape(
+        &entry.created_at.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+    ));
+    out
+}
+
+/// One-tap undo for the entry, if the action has one.
+pub fn keyboard(entry: &LogEntry) -> Option<InlineKeyboardMarkup> {
+    let op = match entry.action.as_str() {
+        "ban" => LogOp::Unban,
+        "captcha_failed" | "captcha_expired" | "unverify" => LogOp::RestoreVerification,
+        _ => return None,
+    };
+    Some(InlineKeyboardMarkup::new(vec![vec![
+        InlineKeyboardButton::callback(
+            op.label(),
+            data_for(op, entry.chat_id, entry.target_user_id),
+        ),
+    ]]))
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct ParsedReason {
+    pub rules: Vec<String>,
+    pub text: Option<String>,
+    pub excerpt: Option<String>,
+}
+
+/// Spam-pipeline reasons are JSON (`matched_rules`, optional `excerpt`);
+/// everything else (`/ban` reasons, `lifetime`, role names) is shown as is.
+pub(crate) fn parse_reason(raw: &str) -> ParsedReason {
+    let Ok(serde_json::Value::Object(obj)) = serde_json::from_str::<serde_json::Value>(raw) else {
+        return ParsedReason {
+            text: Some(raw.to_string()).filter(|s| !s.is_empty()),
+            ..Default::default()
+        };
+    };
+    ParsedReason {
+        rules: obj
+            .get("matched_rules")
+            .and_then(|v| v.as_array())
+            .map(|a| {
+                a.iter()
+                    .filter_map(|r| r.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        text: None,
+        excerpt: obj
+            .get("excerpt")
+            .and_then(|v| v.as_str())
+            .map(str::to_string),
+    }
+}
+
+fn action_label(action: &str) -> String {
+    let spaced = action.replace('_', " ");
+    let mut chars = spaced.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn emoji_for(action: &str) -> &'static str {
+    match action {
+        "ban" => "🔨",
+        "unban" => "♻️",
+        "delete" => "🗑",
+        "verify" => "✅",
+        "unverify" => "❎",
+        "captcha_failed" => "❌",
+        "captcha_expired" => "⌛",
+        "kick" => "👢",
+        "mute" => "🔇",
+        "unmute" => "🔊",
+        "role_grant" => "🛡",
+        "role_revoke" => "🚫",
+        _ => "•",
+    }
+}
+
+fn user_link(user_id: i64) -> String {
+    format!("[{user_id}](tg://user?id={user_id})")
+}
+
+fn truncate(s: &str, max_chars: usize) -> String {
+    if s.chars().count() <= max_chars {
+        return s.to_string();
+    }
+    let mut out: String = s.chars().take(max_chars).collect();
+    out.push('…');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn entry(action: &str, reason: Option<&str>) -> LogEntry {
+        LogEntry {
+            id: Uuid::nil(),
+            chat_id: -1001234567890,
+            log_chat_id: -1009999999999,
+            chat_title: Some("acme.chat".into()),
+            target_user_id: 4242,
+            action: action.into(),
+            actor_kind: "bot".into(),
+            actor_user_id: None,
+            reason: reason.map(str::to_string),
+            created_at: Utc.with_ymd_and_hms(2026, 5, 6, 12, 0, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn callback_round_trip() {
+        let data = data_for(LogOp::Unban, -1001234567890, 4242);
+        assert_eq!(data, "vl:ub:-1001234567890:4242");
+        assert!(data.len() <= 64, "Telegram caps callback_data at 64 bytes");
+        assert_eq!(
+            parse_callback(&data),
+            Some(LogCallback {
+                op: LogOp::Unban,
+                chat_id: -1001234567890,
+                user_id: 4242,
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_foreign_or_malformed_callbacks() {
+        assert!(parse_callback("vc:0123abcd:1").is_none());
+        assert!(parse_callback("vl:xx:-100:1").is_none());
+        assert!(parse_callback("vl:ub:-100").is_none());
+        assert!(parse_callback("vl:ub:-100:-5").is_none());
+    }
+
+    #[test]
+    fn renders_spam_ban_with_rules_and_escaped_excerpt() {
+        let e = entry(
+            "ban",
+            Some(
+                r#"{"matched_rules":["ngram"],"score":2.0,"excerpt":"buy now! (50% off)\nlink.ru"}"#,
+            ),
+        );
+        let s = render(&e);
+        assert!(s.starts_with("🔨 *Ban* · acme\\.chat\n"));
+        assert!(s.contains("User: [4242](tg://user?id=4242)\n"));
+        assert!(s.contains("By: bot\n"));
+        assert!(s.contains("Rules: ngram\n"));
+        assert!(s.contains(">buy now\\! \\(50% off\\)\n>link\\.ru\n"));
+        assert!(s.ends_with("2026\\-05\\-06 12:00:00 UTC"));
+    }
+
+    #[test]
+    fn renders_plain_reason_and_moderator_actor() {
+        let mut e = entry("captcha_expired", Some("lifetime"));
+        e.actor_kind = "moderator".into();
+        e.actor_user_id = Some(77);
+        let s = render(&e);
+        assert!(s.starts_with("⌛ *Captcha expired*"));
+        assert!(s.contains("By: [77](tg://user?id=77)\n"));
+        assert!(s.contains("Reason: lifetime\n"));
+    }
+
+    #[test]
+    fn keyboard_only_for_undoable_actions() {
+        let kb = keyboard(&entry("ban", None)).expect("ban has unban");
+        assert_eq!(kb.inline_keyboard[0][0].text, "Unban");
+        let kb = keyboard(&entry("captcha_failed", None)).expect("restore");
+        assert_eq!(kb.inline_keyboard[0][0].text, "Restore verification");
+        assert!(keyboard(&entry("delete", None)).is_none());
+        assert!(keyboard(&entry("unban", None)).is_none());
+    }
+}
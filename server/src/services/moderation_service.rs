@@ -3,7 +3,11 @@
 //! inside the same transaction as the bot side-effect. Re-running the same
 //! action is a no-op via the `(chat_id, target_user_id, action, message_id)`
 //! uniqueness key (plus a behaviour check for id-mode bans where
-//! `message_id IS NULL` and the unique constraint doesn't help).
+//! `message_id IS NULL` and the unique constraint doesn't help). Bans and
+//! unbans also queue a webhook event (`services::webhooks`) in that
+//! transaction; every newly applied action goes on the dashboard's live
+//! feed (`services::live_feed`) after the commit, and a newly banned user is
+//! sent the appeal link by DM (`services::appeal`).
 //!
 //! See `server/docs/moderation.md`.
 
@@ -18,11 +22,18 @@ use teloxide::ApiError;
 use teloxide::RequestError;
 use teloxide::prelude::*;
 use teloxide::types::{ChatId, MessageId, UserId};
+use tokio::sync::OnceCell;
 use tracing::{info, instrument, warn};
 use uuid::Uuid;
 
+use crate::models::chat_moderator::{self, ModeratorRole};
 use crate::models::daily_stats::{self, Metric};
-use crate::models::moderation_action::{ActorKind, ModerationActionKind};
+use crate::models::moderation_action::{ActorKind, ModerationActionKind, PublicReason};
+use crate::services::appeal;
+use crate::services::live_feed::{LiveEventKind, LiveFeed};
+use crate::services::outbound::{OutboundError, OutboundQueue, Priority};
+use crate::services::webhooks::{self, EventType, WebhookEvent};
+use crate::telemetry::metrics;
 
 const MODERATOR_CACHE_TTL: Duration = Duration::from_secs(5 * 60);
 const MODERATOR_CACHE_CAPACITY: u64 = 10_000;
@@ -73,8 +84,8 @@ pub struct ApplyContext {
 pub enum Outcome {
     /// The action was newly applied. Bot side-effect was attempted; non-fatal
     /// Telegram errors (403/400) still produce `Applied` because the ledger
-    /// records the *intent*.
-    Applied,
+    /// records the *intent*. Carries the id of the ledger row it wrote.
+    Applied(Uuid),
     /// An equivalent ledger row already existed. The bot side-effect was
     /// skipped — this is the whole point of the idempotency contract.
     AlreadyApplied,
@@ -84,18 +95,25 @@ pub enum Outcome {
 pub struct ModerationService {
     db: PgPool,
     bot: Bot,
-    moderator_cache: Cache<(i64, i64), bool>,
+    outbound: Arc<OutboundQueue>,
+    live: LiveFeed,
+    moderator_cache: Cache<(i64, i64), Option<ModeratorRole>>,
+    /// `getMe` username for the appeal deep link, fetched on the first ban.
+    bot_username: Arc<OnceCell<String>>,
 }
 
 impl ModerationService {
-    pub fn new(db: PgPool, bot: Bot) -> Arc<Self> {
+    pub fn new(db: PgPool, bot: Bot, outbound: Arc<OutboundQueue>, live: LiveFeed) -> Arc<Self> {
         Arc::new(Self {
             db,
             bot,
+            outbound,
+            live,
             moderator_cache: Cache::builder()
                 .max_capacity(MODERATOR_CACHE_CAPACITY)
                 .time_to_live(MODERATOR_CACHE_TTL)
                 .build(),
+            bot_username: Arc::new(OnceCell::new()),
         })
     }
 
@@ -103,7 +121,8 @@ impl ModerationService {
     /// inside a single transaction so a fatal bot failure rolls back the
     /// ledger row atomically — a concurrent caller cannot observe the row
     /// before commit, so it cannot get a misleading `AlreadyApplied` for
-    /// an action that ultimately did not apply.
+    /// an action that ultimately did not apply. The webhook event for a ban
+    /// or unban rides the same transaction and rolls back with it.
     ///
     /// **Concurrency:** for id-mode bans/unbans where `message_id` is `NULL`
     /// the unique constraint can't dedup (PG treats NULLs as distinct), so
@@ -118,6 +137,8 @@ impl ModerationService {
     /// Holding a PG connection across the bot call is acceptable here:
     /// `/ban` / `/unban` are infrequent moderator commands, and the spam
     /// pipeline always sets `message_id` (so it never holds the chat lock).
+    /// The bot call waits in the outbound queue, at the top priority; only a
+    /// 429 makes that wait long, and then for `retry_after` at most twice.
     #[instrument(
         skip(self),
         fields(
@@ -127,6 +148,18 @@ impl ModerationService {
         )
     )]
     pub async fn apply(&self, action: Action, ctx: ApplyContext) -> Result<Outcome> {
+        let kind = action.kind().as_db_str();
+        let result = self.apply_tx(action, ctx).await;
+        let outcome = match &result {
+            Ok(Outcome::Applied(_)) => "applied",
+            Ok(Outcome::AlreadyApplied) => "already_applied",
+            Err(_) => "error",
+        };
+        metrics::moderation_action(ctx.chat_id, kind, ctx.actor_kind.as_db_str(), outcome);
+        result
+    }
+
+    async fn apply_tx(&self, action: Action, ctx: ApplyContext) -> Result<Outcome> {
         let needs_lock =
             ctx.message_id.is_none() && matches!(action, Action::Ban { .. } | Action::Unban);
 
@@ -195,12 +228,24 @@ impl ModerationService {
             return Ok(Outcome::AlreadyApplied);
         };
 
+        if let Some(event_type) = EventType::for_action(action.kind()) {
+            let event = WebhookEvent {
+                actor_user_id: ctx.actor_user_id,
+                action_id: Some(id),
+                ..WebhookEvent::new(event_type, ctx.chat_id, ctx.target_user_id, ctx.actor_kind)
+            }
+            .with_reason(action.reason());
+            webhooks::record(&mut *tx, &event).await?;
+        }
+
         match self.dispatch(&action, &ctx).await {
             Ok(()) => {
                 tx.commit().await.context("COMMIT apply tx")?;
                 info!(action_id = %id, "moderation applied");
                 self.bump_daily_stats(action.kind(), ctx.chat_id).await;
-                Ok(Outcome::Applied)
+                self.publish_live(id, &action, &ctx).await;
+                self.send_appeal_link(&action, &ctx);
+                Ok(Outcome::Applied(id))
             }
             Err(BotCallOutcome::NonFatal(e)) => {
                 tx.commit()
@@ -208,7 +253,9 @@ impl ModerationService {
                     .context("COMMIT apply tx (non-fatal bot error)")?;
                 warn!(error = %e, "bot call non-fatal; ledger row kept");
                 self.bump_daily_stats(action.kind(), ctx.chat_id).await;
-                Ok(Outcome::Applied)
+                self.publish_live(id, &action, &ctx).await;
+                self.send_appeal_link(&action, &ctx);
+                Ok(Outcome::Applied(id))
             }
             Err(BotCallOutcome::Fatal(e)) => {
                 // Tx drops here without commit → rolls back automatically.
@@ -220,27 +267,22 @@ impl ModerationService {
         }
     }
 
-    /// Membership in `chat_moderators` (Moka 5min cache). Chat admins are
-    /// gated separately via the existing M1 admin cache (`CaptchaState`).
-    pub async fn is_moderator(&self, chat_id: i64, user_id: i64) -> Result<bool> {
+    /// Role in `chat_moderators` (Moka 5min cache), `None` when the user has
+    /// no row. Telegram admins get a row from `moderator_sync`; until the
+    /// first sync the bot commands fall back to the M1 admin cache
+    /// (`CaptchaState`).
+    pub async fn role(&self, chat_id: i64, user_id: i64) -> Result<Option<ModeratorRole>> {
         if let Some(cached) = self.moderator_cache.get(&(chat_id, user_id)).await {
             return Ok(cached);
         }
-        let row = sqlx::query_scalar!(
-            r#"
-            SELECT EXISTS(
-                SELECT 1 FROM chat_moderators
-                WHERE chat_id = $1 AND user_id = $2
-            ) AS "exists!"
-            "#,
-            chat_id,
-            user_id,
-        )
-        .fetch_one(&self.db)
-        .await
-        .context("SELECT chat_moderators")?;
-        self.moderator_cache.insert((chat_id, user_id), row).await;
-        Ok(row)
+        let role = chat_moderator::role_of(&self.db, chat_id, user_id).await?;
+        self.moderator_cache.insert((chat_id, user_id), role).await;
+        Ok(role)
+    }
+
+    /// Whether the user holds any role in `chat_moderators`.
+    pub async fn is_moderator(&self, chat_id: i64, user_id: i64) -> Result<bool> {
+        Ok(self.role(chat_id, user_id).await?.is_some())
     }
 
     /// Invalidate a single (chat, user) entry — call this after writing to
@@ -268,6 +310,60 @@ impl ModerationService {
         }
     }
 
+    /// Put a committed action on the dashboard's live feed. Best-effort,
+    /// like the counters above.
+    async fn publish_live(&self, action_id: Uuid, action: &Action, ctx: &ApplyContext) {
+        let PublicReason {
+            reason,
+            matched_rules,
+            score,
+        } = PublicReason::from_ledger(action.reason());
+        let event = LiveEventKind::ModerationAction {
+            action_id,
+            user_id: ctx.target_user_id,
+            action: action.kind().as_db_str().to_owned(),
+            actor_kind: ctx.actor_kind.as_db_str().to_owned(),
+            actor_user_id: ctx.actor_user_id,
+            message_id: ctx.message_id,
+            reason,
+            matched_rules,
+            score,
+        };
+        self.live.publish(ctx.chat_id, event).await;
+    }
+
+    /// DM a newly banned user the appeal link. Spawned so the spam pipeline
+    /// never waits on it; a failure only costs the user the shortcut.
+    fn send_appeal_link(&self, action: &Action, ctx: &ApplyContext) {
+        if !matches!(action, Action::Ban { .. }) {
+            return;
+        }
+        let this = self.clone();
+        let (chat_id, user_id) = (ctx.chat_id, ctx.target_user_id);
+        tokio::spawn(async move {
+            let username = this
+                .bot_username
+                .get_or_try_init(|| async {
+                    this.bot.get_me().await.map(|me| me.username().to_owned())
+                })
+                .await;
+            match username {
+                Ok(username) => {
+                    appeal::send_link(
+                        &this.bot,
+                        &this.outbound,
+                        &this.db,
+                        username,
+                        chat_id,
+                        user_id,
+                    )
+                    .await
+                }
+                Err(e) => warn!(error = %e, "getMe for the appeal link failed"),
+            }
+        });
+    }
+
     async fn dispatch(
         &self,
         action: &Action,
@@ -276,34 +372,53 @@ impl ModerationService {
         let chat = ChatId(ctx.chat_id);
         let user = UserId(ctx.target_user_id as u64);
 
+        let method = match action {
+            Action::Ban { .. } => "banChatMember",
+            Action::Unban => "unbanChatMember",
+            Action::Delete { .. } => "deleteMessage",
+        };
         let result = match action {
             Action::Ban { until, .. } => {
                 let mut req = self.bot.ban_chat_member(chat, user);
                 if let Some(t) = until {
                     req = req.until_date(*t);
                 }
-                req.await.map(|_| ())
+                self.send(ctx.chat_id, req).await
+            }
+            Action::Unban => {
+                self.send(ctx.chat_id, self.bot.unban_chat_member(chat, user))
+                    .await
             }
-            Action::Unban => self.bot.unban_chat_member(chat, user).await.map(|_| ()),
             Action::Delete { .. } => {
                 let Some(mid) = ctx.message_id else {
                     return Err(BotCallOutcome::Fatal(anyhow::anyhow!(
                         "Delete action requires message_id"
                     )));
                 };
-                self.bot
-                    .delete_message(chat, MessageId(mid))
+                self.send(ctx.chat_id, self.bot.delete_message(chat, MessageId(mid)))
                     .await
-                    .map(|_| ())
             }
         };
 
+        metrics::telegram_request(method, request_outcome(&result));
         match result {
             Ok(()) => Ok(()),
-            Err(e) if is_non_fatal(&e) => Err(BotCallOutcome::NonFatal(e)),
+            Err(OutboundError::Request(e)) if is_non_fatal(&e) => Err(BotCallOutcome::NonFatal(e)),
             Err(e) => Err(BotCallOutcome::Fatal(anyhow::Error::from(e))),
         }
     }
+
+    /// Moderation calls jump the outbound queue ahead of captcha photos and
+    /// reports; a 429 is waited out there before it becomes fatal here.
+    async fn send<R>(&self, chat_id: i64, request: R) -> std::result::Result<(), OutboundError>
+    where
+        R: teloxide::requests::Request<Err = RequestError>,
+    {
+        self.outbound
+            .send(chat_id, Priority::Moderation, request)
+            .await
+            .map(|_| ())
+    }
 }
 
 enum BotCallOutcome {
@@ -330,6 +445,22 @@ fn is_non_fatal(e: &RequestError) -> bool {
     )
 }
 
+/// `outcome` label on `vixen_telegram_api_requests_total`.
+fn request_outcome(result: &std::result::Result<(), OutboundError>) -> &'static str {
+    let e = match result {
+        Ok(()) => return "ok",
+        Err(OutboundError::Dropped(_)) => return "dropped",
+        Err(OutboundError::Request(e)) => e,
+    };
+    match e {
+        e if is_non_fatal(e) => "non_fatal",
+        RequestError::Api(_) => "api_error",
+        RequestError::RetryAfter(_) => "retry_after",
+        RequestError::Network(_) => "network",
+        _ => "other",
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
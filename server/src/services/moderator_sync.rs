@@ -0,0 +1,279 @@
+//! Mirror Telegram chat administrators into `chat_moderators`.
+//!
+//! The message gate already treats Telegram admins as trusted (the
+//! `cap:admins` cache), but the role matrix reads `chat_moderators`, so
+//! without this mirror a Telegram admin had no Vixen role and a demoted
+//! admin kept theirs. Two entry points keep the table in step:
+//!
+//!   * [`sync_chat`] — full reconcile against a `getChatAdministrators`
+//!     snapshot, run by the `moderator_sync` job.
+//!   * [`sync_member`] — a single `chat_member` update (promotion or
+//!     demotion), run from the dispatcher so changes land immediately.
+//!
+//! Mapping: creator → `owner`, administrator → `admin`. Bots are skipped.
+//! Only `source = 'telegram'` rows are written or deleted; a `manual` row
+//! (from `/mod grant`) is authoritative and survives any Telegram change.
+//! Every write lands in the audit ledger as `role_grant` / `role_revoke`
+//! with `actor_kind = 'bot'` and the role in `reason`, and invalidates the
+//! `ModerationService` role cache for that user.
+
+use anyhow::{Context, Result};
+use sqlx::{Executor, PgPool, Postgres};
+use teloxide::types::{ChatMember, ChatMemberKind};
+use tracing::info;
+
+use crate::models::chat_moderator::{self, ChatModerator, ModeratorRole, RoleSource};
+use crate::models::moderation_action::{ActorKind, ModerationActionKind};
+use crate::services::moderation_service::ModerationService;
+
+/// One planned change to a `telegram` row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoleChange {
+    /// Insert, or re-role an existing mirrored row.
+    Grant { user_id: i64, role: ModeratorRole },
+    /// The user is no longer a Telegram admin; `role` is the one removed.
+    Revoke { user_id: i64, role: ModeratorRole },
+}
+
+impl RoleChange {
+    pub fn user_id(self) -> i64 {
+        match self {
+            Self::Grant { user_id, .. } | Self::Revoke { user_id, .. } => user_id,
+        }
+    }
+}
+
+/// Vixen role for a Telegram membership, `None` for everything below admin.
+pub fn telegram_role(kind: &ChatMemberKind) -> Option<ModeratorRole> {
+    match kind {
+        ChatMemberKind::Owner(_) => Some(ModeratorRole::Owner),
+        ChatMemberKind::Administrator(_) => Some(ModeratorRole::Admin),
+        _ => None,
+    }
+}
+
+/// Diff the current rows against the Telegram admin list. Pure — the caller
+/// applies the result. `manual` rows never produce a change.
+pub fn plan(existing: &[ChatModerator], admins: &[(i64, ModeratorRole)]) -> Vec<RoleChange> {
+    let mut changes: Vec<RoleChange> = admins
+        .iter()
+        .filter_map(|&(user_id, role)| {
+            let row = existing.iter().find(|m| m.user_id == user_id);
+            plan_user(row, user_id, Some(role))
+        })
+        .collect();
+    changes.extend(
+        existing
+            .iter()
+            .filter(|m| !admins.iter().any(|&(id, _)| id == m.user_id))
+            .filter_map(|m| plan_user(Some(m), m.user_id, None)),
+    );
+    changes
+}
+
+fn plan_user(
+    row: Option<&ChatModerator>,
+    user_id: i64,
+    desired: Option<ModeratorRole>,
+) -> Option<RoleChange> {
+    match (row, desired) {
+        (Some(m), _) if m.source == RoleSource::Manual => None,
+        (Some(m), Some(role)) if m.role == role => None,
+        (_, Some(role)) => Some(RoleChange::Grant { user_id, role }),
+        (Some(m), None) => Some(RoleChange::Revoke {
+            user_id,
+            role: m.role,
+        }),
+        (None, None) => None,
+    }
+}
+
+/// Reconcile `chat_id` against a full administrator snapshot. Returns the
+/// changes that were actually written.
+pub async fn sync_chat(
+    pool: &PgPool,
+    moderation: &ModerationService,
+    chat_id: i64,
+    admins: &[ChatMember],
+) -> Result<Vec<RoleChange>> {
+    let admins: Vec<(i64, ModeratorRole)> = admins
+        .iter()
+        .filter(|a| !a.user.is_bot)
+        .filter_map(|a| telegram_role(&a.kind).map(|role| (a.user.id.0 as i64, role)))
+        .collect();
+    let existing = chat_moderator::list(pool, chat_id).await?;
+    let changes = plan(&existing, &admins);
+    apply(pool, moderation, chat_id, changes).await
+}
+
+/// Reconcile one user after a `chat_member` update. `kind` is the new
+/// membership; anything below admin removes a mirrored row.
+pub async fn sync_member(
+    pool: &PgPool,
+    moderation: &ModerationService,
+    chat_id: i64,
+    user_id: i64,
+    kind: &ChatMemberKind,
+) -> Result<Option<RoleChange>> {
+    let existing = chat_moderator::list(pool, chat_id).await?;
+    let row = existing.iter().find(|m| m.user_id == user_id);
+    let Some(change) = plan_user(row, user_id, telegram_role(kind)) else {
+        return Ok(None);
+    };
+    Ok(apply(pool, moderation, chat_id, vec![change])
+        .await?
+        .into_iter()
+        .next())
+}
+
+/// Write `changes` in one transaction. The `sync_*` writers re-check
+/// `source = 'telegram'` in SQL, so a `/mod grant` racing the plan is never
+/// overwritten — such a change is dropped and not ledgered.
+async fn apply(
+    pool: &PgPool,
+    moderation: &ModerationService,
+    chat_id: i64,
+    changes: Vec<RoleChange>,
+) -> Result<Vec<RoleChange>> {
+    if changes.is_empty() {
+        return Ok(changes);
+    }
+    let mut tx = pool.begin().await.context("BEGIN moderator sync")?;
+    let mut applied = Vec::with_capacity(changes.len());
+    for change in changes {
+        let written = match change {
+            RoleChange::Grant { user_id, role } => {
+                chat_moderator::sync_upsert(&mut *tx, chat_id, user_id, role).await?
+            }
+            RoleChange::Revoke { user_id, .. } => {
+                chat_moderator::sync_remove(&mut *tx, chat_id, user_id).await?
+            }
+        };
+        if !written {
+            continue;
+        }
+        let (kind, role) = match change {
+            RoleChange::Grant { role, .. } => (ModerationActionKind::RoleGrant, role),
+            RoleChange::Revoke { role, .. } => (ModerationActionKind::RoleRevoke, role),
+        };
+        record_role_change(
+            &mut *tx,
+            chat_id,
+            change.user_id(),
+            kind,
+            ActorKind::Bot,
+            None,
+            role,
+        )
+        .await?;
+        applied.push(change);
+    }
+    tx.commit().await.context("COMMIT moderator sync")?;
+
+    for change in &applied {
+        moderation
+            .invalidate_moderator(chat_id, change.user_id())
+            .await;
+        info!(chat_id, ?change, "moderator role synced from telegram");
+    }
+    Ok(applied)
+}
+
+/// Ledger a `chat_moderators` change. `role` is the granted role for
+/// `role_grant` and the removed one for `role_revoke`. Shared with `/mod`,
+/// which passes `ActorKind::Moderator` and the caller's id.
+pub async fn record_role_change<'e, E>(
+    executor: E,
+    chat_id: i64,
+    target_user_id: i64,
+    kind: ModerationActionKind,
+    actor_kind: ActorKind,
+    actor_user_id: Option<i64>,
+    role: ModeratorRole,
+) -> Result<()>
+where
+    E: Executor<'e, Database = Postgres>,
+{
+    sqlx::query!(
+        r#"
+        INSERT INTO moderation_actions
+            (chat_id, target_user_id, action, actor_kind, actor_user_id, reason)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        "#,
+        chat_id,
+        target_user_id,
+        kind.as_db_str(),
+        actor_kind.as_db_str(),
+        actor_user_id,
+        role.as_db_str(),
+    )
+    .execute(executor)
+    .await
+    .context("INSERT moderation_actions (role change)")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+
+    fn row(user_id: i64, role: ModeratorRole, source: RoleSource) -> ChatModerator {
+        ChatModerator {
+            chat_id: -100,
+            user_id,
+            role,
+            granted_at: Utc::now(),
+            granted_by: None,
+            source,
+        }
+    }
+
+    #[test]
+    fn plan_grants_new_admins_and_revokes_demoted() {
+        let existing = [row(2, ModeratorRole::Admin, RoleSource::Telegram)];
+        let admins = [(1, ModeratorRole::Owner)];
+        assert_eq!(
+            plan(&existing, &admins),
+            vec![
+                RoleChange::Grant {
+                    user_id: 1,
+                    role: ModeratorRole::Owner,
+                },
+                RoleChange::Revoke {
+                    user_id: 2,
+                    role: ModeratorRole::Admin,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn plan_rerole_only_when_role_differs() {
+        let existing = [
+            row(1, ModeratorRole::Admin, RoleSource::Telegram),
+            row(2, ModeratorRole::Admin, RoleSource::Telegram),
+        ];
+        let admins = [(1, ModeratorRole::Admin), (2, ModeratorRole::Owner)];
+        assert_eq!(
+            plan(&existing, &admins),
+            vec![RoleChange::Grant {
+                user_id: 2,
+                role: ModeratorRole::Owner,
+            }]
+        );
+    }
+
+    #[test]
+    fn plan_leaves_manual_rows_alone() {
+        let existing = [
+            // Telegram admin deliberately downgraded with /mod.
+            row(1, ModeratorRole::Viewer, RoleSource::Manual),
+            // Manual moderator who is not a Telegram admin.
+            row(2, ModeratorRole::Moderator, RoleSource::Manual),
+        ];
+        let admins = [(1, ModeratorRole::Admin)];
+        assert!(plan(&existing, &admins).is_empty());
+    }
+}
@@ -0,0 +1,522 @@
+//! Outbound Bot API scheduler. Every request that posts into, edits in,
+//! deletes from or bans in a chat goes through [`OutboundQueue::send`],
+//! which waits for a permit before making the HTTP call:
+//!
+//! - a global token bucket (Telegram allows roughly 30 requests/s per bot);
+//! - a per-chat bucket for messages the bot posts or edits (roughly 20 per
+//!   minute in a group). Deletes and bans are not messages and skip it;
+//! - a per-chat block after a 429, for the `retry_after` Telegram sent,
+//!   after which the request is retried.
+//!
+//! Permits go out in [`Priority`] order, so during a raid deletes and bans
+//! don't wait behind captcha photos and reports. A priority whose backlog
+//! is full drops new requests instead of queueing them.
+//!
+//! Reads (`getMe`, `getChatAdministrators`) and `answerCallbackQuery` don't
+//! post into a chat and call the `Bot` directly.
+//!
+//! See `server/docs/bot.md#outbound-rate-limits`.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+
+use teloxide::RequestError;
+use teloxide::requests::{Output, Request};
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::Instant;
+use tracing::warn;
+
+use crate::telemetry::metrics;
+
+/// Attempts per request, counting the first. Each 429 in between waits out
+/// its `retry_after`.
+const MAX_ATTEMPTS: u32 = 3;
+/// Idle per-chat state is pruned once this many chats are tracked.
+const CHAT_STATE_PRUNE_AT: usize = 1024;
+/// Slack for float drift when a bucket is refilled to exactly one token.
+const TOKEN_EPSILON: f64 = 1e-9;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Priority {
+    /// Deletes, bans and unbans. Not metered per chat.
+    Moderation,
+    /// Captcha photos and their edits, command replies, appeal messages.
+    Interactive,
+    /// Reports, mod-log entries and their cleanup.
+    Background,
+}
+
+impl Priority {
+    /// Grant order, highest first.
+    pub const ALL: [Self; 3] = [Self::Moderation, Self::Interactive, Self::Background];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Moderation => "moderation",
+            Self::Interactive => "interactive",
+            Self::Background => "background",
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    /// Whether the request draws from the chat's message bucket.
+    fn metered(self) -> bool {
+        !matches!(self, Self::Moderation)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Limits {
+    /// Requests per second across all chats (also the global burst).
+    pub global_per_sec: f64,
+    /// Posted / edited messages a chat can take in one burst.
+    pub chat_burst: f64,
+    /// Sustained posted / edited messages per second per chat.
+    pub chat_per_sec: f64,
+    /// Waiting requests per priority (in [`Priority::ALL`] order) before new
+    /// ones are dropped.
+    pub backlog: [usize; 3],
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            global_per_sec: 30.0,
+            chat_burst: 20.0,
+            chat_per_sec: 20.0 / 60.0,
+            backlog: [10_000, 1_000, 100],
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum OutboundError {
+    /// The priority's backlog was full; the request was never sent.
+    #[error("outbound queue full, {0} request dropped")]
+    Dropped(&'static str),
+    #[error(transparent)]
+    Request(#[from] RequestError),
+}
+
+pub struct OutboundQueue {
+    tx: mpsc::UnboundedSender<Msg>,
+}
+
+impl OutboundQueue {
+    /// Spawns the scheduler task. It exits when the queue is dropped.
+    pub fn new(limits: Limits) -> Arc<Self> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        tokio::spawn(schedule(rx, limits));
+        Arc::new(Self { tx })
+    }
+
+    /// Wait for a permit for `chat_id`, then send `request`. A 429 blocks the
+    /// chat for `retry_after` and the request is retried, up to
+    /// [`MAX_ATTEMPTS`] in total.
+    pub async fn send<R>(
+        &self,
+        chat_id: i64,
+        priority: Priority,
+        request: R,
+    ) -> Result<Output<R>, OutboundError>
+    where
+        R: Request<Err = RequestError>,
+    {
+        let mut attempt = 1;
+        loop {
+            self.acquire(chat_id, priority).await?;
+            match request.send_ref().await {
+                Err(RequestError::RetryAfter(after)) if attempt < MAX_ATTEMPTS => {
+                    warn!(
+                        chat_id,
+                        retry_after = after.seconds(),
+                        attempt,
+                        "Telegram 429, retrying"
+                    );
+                    metrics::outbound_retry_after(priority.as_str());
+                    let until = Instant::now() + after.duration();
+                    let _ = self.tx.send(Msg::Block { chat_id, until });
+                    attempt += 1;
+                }
+                result => return result.map_err(OutboundError::Request),
+            }
+        }
+    }
+
+    async fn acquire(&self, chat_id: i64, priority: Priority) -> Result<(), OutboundError> {
+        let (grant, granted) = oneshot::channel();
+        let ticket = Ticket {
+            chat_id,
+            priority,
+            grant,
+        };
+        // Both ends fail only when the ticket was dropped: backlog full.
+        self.tx
+            .send(Msg::Acquire(ticket))
+            .map_err(|_| OutboundError::Dropped(priority.as_str()))?;
+        granted
+            .await
+            .map_err(|_| OutboundError::Dropped(priority.as_str()))
+    }
+}
+
+enum Msg {
+    Acquire(Ticket),
+    Block { chat_id: i64, until: Instant },
+}
+
+struct Ticket {
+    chat_id: i64,
+    priority: Priority,
+    grant: oneshot::Sender<()>,
+}
+
+async fn schedule(mut rx: mpsc::UnboundedReceiver<Msg>, limits: Limits) {
+    let mut scheduler = Scheduler::new(limits, Instant::now());
+    loop {
+        let now = Instant::now();
+        scheduler.grant(now);
+        scheduler.report_depth();
+        let msg = match scheduler.next_wake() {
+            Some(at) => tokio::select! {
+                msg = rx.recv() => msg,
+                () = tokio::time::sleep_until(at) => continue,
+            },
+            None => rx.recv().await,
+        };
+        match msg {
+            Some(Msg::Acquire(ticket)) => scheduler.enqueue(ticket, Instant::now()),
+            Some(Msg::Block { chat_id, until }) => scheduler.block(chat_id, until, Instant::now()),
+            None => return,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    capacity: f64,
+    per_sec: f64,
+    refilled_at: Instant,
+}
+
+impl Bucket {
+    fn full(capacity: f64, per_sec: f64, now: Instant) -> Self {
+        Self {
+            tokens: capacity,
+            capacity,
+            per_sec,
+            refilled_at: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now
+            .saturating_duration_since(self.refilled_at)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.per_sec).min(self.capacity);
+        self.refilled_at = now;
+    }
+
+    fn ready(&mut self, now: Instant) -> bool {
+        self.refill(now);
+        self.tokens + TOKEN_EPSILON >= 1.0
+    }
+
+    fn take(&mut self) {
+        self.tokens -= 1.0;
+    }
+
+    /// When the next whole token is in. Independent of when we last refilled.
+    fn next_token_at(&self) -> Instant {
+        let missing = (1.0 - self.tokens).max(0.0);
+        self.refilled_at + Duration::from_secs_f64(missing / self.per_sec)
+    }
+
+    fn is_full(&self) -> bool {
+        self.tokens + TOKEN_EPSILON >= self.capacity
+    }
+}
+
+#[derive(Debug)]
+struct ChatState {
+    bucket: Bucket,
+    blocked_until: Option<Instant>,
+}
+
+/// The permit bookkeeping, separate from the channel plumbing so it can be
+/// driven with explicit instants.
+struct Scheduler {
+    limits: Limits,
+    global: Bucket,
+    chats: HashMap<i64, ChatState>,
+    pending: [VecDeque<Ticket>; 3],
+}
+
+impl Scheduler {
+    fn new(limits: Limits, now: Instant) -> Self {
+        Self {
+            limits,
+            global: Bucket::full(limits.global_per_sec, limits.global_per_sec, now),
+            chats: HashMap::new(),
+            pending: Default::default(),
+        }
+    }
+
+    /// Queue `ticket`, or drop it (the caller sees `Dropped`) when its
+    /// priority's backlog is full.
+    fn enqueue(&mut self, ticket: Ticket, now: Instant) {
+        let priority = ticket.priority;
+        let queue = &mut self.pending[priority.index()];
+        if queue.len() >= self.limits.backlog[priority.index()] {
+            warn!(
+                chat_id = ticket.chat_id,
+                priority = priority.as_str(),
+                "outbound backlog full, dropping request"
+            );
+            metrics::outbound_dropped(priority.as_str());
+            return;
+        }
+        queue.push_back(ticket);
+        self.prune(now);
+    }
+
+    fn block(&mut self, chat_id: i64, until: Instant, now: Instant) {
+        let limits = self.limits;
+        let chat = self
+            .chats
+            .entry(chat_id)
+            .or_insert_with(|| ChatState::new(&limits, now));
+        chat.blocked_until = Some(chat.blocked_until.map_or(until, |t| t.max(until)));
+    }
+
+    /// Grant every permit that is due at `now`, highest priority first and
+    /// FIFO within a priority. Tickets whose caller went away are discarded.
+    fn grant(&mut self, now: Instant) {
+        let Self {
+            limits,
+            global,
+            chats,
+            pending,
+        } = self;
+        for queue in pending.iter_mut() {
+            let mut i = 0;
+            while i < queue.len() {
+                let ticket = &queue[i];
+                if ticket.grant.is_closed() {
+                    queue.remove(i);
+                    continue;
+                }
+                if !global.ready(now) {
+                    return;
+                }
+                let chat = chats
+                    .entry(ticket.chat_id)
+                    .or_insert_with(|| ChatState::new(limits, now));
+                let metered = ticket.priority.metered();
+                let blocked = chat.blocked_until.is_some_and(|t| t > now);
+                if blocked || (metered && !chat.bucket.ready(now)) {
+                    i += 1;
+                    continue;
+                }
+                global.take();
+                if metered {
+                    chat.bucket.take();
+                }
+                if let Some(ticket) = queue.remove(i) {
+                    let _ = ticket.grant.send(());
+                }
+            }
+        }
+    }
+
+    /// Earliest instant a waiting ticket could be granted; `None` when
+    /// nothing waits. Call after [`Self::grant`].
+    fn next_wake(&self) -> Option<Instant> {
+        let earliest = self
+            .pending
+            .iter()
+            .flatten()
+            .map(|ticket| match self.chats.get(&ticket.chat_id) {
+                Some(chat) => {
+                    let unblocked = chat.blocked_until.unwrap_or(chat.bucket.refilled_at);
+                    if ticket.priority.metered() {
+                        unblocked.max(chat.bucket.next_token_at())
+                    } else {
+                        unblocked
+                    }
+                }
+                None => self.global.refilled_at,
+            })
+            .min()?;
+        Some(earliest.max(self.global.next_token_at()))
+    }
+
+    fn report_depth(&self) {
+        for priority in Priority::ALL {
+            metrics::outbound_queue_depth(priority.as_str(), self.pending[priority.index()].len());
+        }
+    }
+
+    /// A full, unblocked chat is indistinguishable from an untracked one, so
+    /// dropping it loses nothing.
+    fn prune(&mut self, now: Instant) {
+        if self.chats.len() <= CHAT_STATE_PRUNE_AT {
+            return;
+        }
+        self.chats.retain(|_, chat| {
+            chat.bucket.refill(now);
+            !chat.bucket.is_full() || chat.blocked_until.is_some_and(|t| t > now)
+        });
+    }
+}
+
+impl ChatState {
+    fn new(limits: &Limits, now: Instant) -> Self {
+        Self {
+            bucket: Bucket::full(limits.chat_burst, limits.chat_per_sec, now),
+            blocked_until: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHAT: i64 = -1001;
+    const OTHER: i64 = -1002;
+
+    fn limits() -> Limits {
+        Limits {
+            global_per_sec: 2.0,
+            chat_burst: 1.0,
+            chat_per_sec: 1.0,
+            backlog: [10, 10, 1],
+        }
+    }
+
+    fn ticket(chat_id: i64, priority: Priority) -> (Ticket, oneshot::Receiver<()>) {
+        let (grant, rx) = oneshot::channel();
+        (
+            Ticket {
+                chat_id,
+                priority,
+                grant,
+            },
+            rx,
+        )
+    }
+
+    fn granted(rx: &mut oneshot::Receiver<()>) -> bool {
+        rx.try_recv().is_ok()
+    }
+
+    #[test]
+    fn higher_priority_is_granted_first() {
+        let now = Instant::now();
+        let mut s = Scheduler::new(limits(), now);
+        let (report, mut report_rx) = ticket(CHAT, Priority::Background);
+        let (photo, mut photo_rx) = ticket(CHAT, Priority::Interactive);
+        let (delete, mut delete_rx) = ticket(CHAT, Priority::Moderation);
+        s.enqueue(report, now);
+        s.enqueue(photo, now);
+        s.enqueue(delete, now);
+
+        // Global burst 2: the delete plus the photo, which also takes the
+        // chat's only message token. The report waits on both buckets.
+        s.grant(now);
+        assert!(granted(&mut delete_rx));
+        assert!(granted(&mut photo_rx));
+        assert!(!granted(&mut report_rx));
+
+        let wake = s.next_wake().expect("report still waits");
+        assert_eq!(wake, now + Duration::from_secs(1));
+        s.grant(wake);
+        assert!(granted(&mut report_rx));
+        assert!(s.next_wake().is_none());
+    }
+
+    #[test]
+    fn chat_bucket_does_not_hold_back_other_chats_or_deletes() {
+        let now = Instant::now();
+        let mut s = Scheduler::new(limits(), now);
+        let (first, mut first_rx) = ticket(CHAT, Priority::Interactive);
+        let (second, mut second_rx) = ticket(CHAT, Priority::Interactive);
+        s.enqueue(first, now);
+        s.enqueue(second, now);
+        s.grant(now);
+        assert!(granted(&mut first_rx));
+        assert!(!granted(&mut second_rx));
+
+        let later = now + Duration::from_millis(600);
+        let (other, mut other_rx) = ticket(OTHER, Priority::Interactive);
+        let (delete, mut delete_rx) = ticket(CHAT, Priority::Moderation);
+        s.enqueue(other, later);
+        s.enqueue(delete, later);
+        s.grant(later);
+        assert!(granted(&mut delete_rx), "deletes skip the chat bucket");
+        assert!(granted(&mut other_rx), "another chat has its own bucket");
+        assert!(!granted(&mut second_rx));
+    }
+
+    #[test]
+    fn retry_after_blocks_every_priority_in_the_chat() {
+        let now = Instant::now();
+        let mut s = Scheduler::new(limits(), now);
+        s.block(CHAT, now + Duration::from_secs(5), now);
+        let (delete, mut delete_rx) = ticket(CHAT, Priority::Moderation);
+        let (other, mut other_rx) = ticket(OTHER, Priority::Moderation);
+        s.enqueue(delete, now);
+        s.enqueue(other, now);
+        s.grant(now);
+        assert!(!granted(&mut delete_rx));
+        assert!(granted(&mut other_rx));
+
+        assert_eq!(s.next_wake(), Some(now + Duration::from_secs(5)));
+        s.grant(now + Duration::from_secs(5));
+        assert!(granted(&mut delete_rx));
+    }
+
+    #[test]
+    fn full_backlog_drops_and_closed_tickets_are_skipped() {
+        let now = Instant::now();
+        let mut s = Scheduler::new(limits(), now);
+        // Drain the chat bucket so Background tickets have to wait.
+        let (photo, _photo_rx) = ticket(CHAT, Priority::Interactive);
+        s.enqueue(photo, now);
+        s.grant(now);
+
+        let (kept, kept_rx) = ticket(CHAT, Priority::Background);
+        let (dropped, mut dropped_rx) = ticket(CHAT, Priority::Background);
+        s.enqueue(kept, now);
+        s.enqueue(dropped, now);
+        assert!(matches!(
+            dropped_rx.try_recv(),
+            Err(oneshot::error::TryRecvError::Closed)
+        ));
+
+        // The caller gave up: its ticket is discarded rather than granted.
+        drop(kept_rx);
+        s.grant(now + Duration::from_secs(1));
+        assert!(s.pending.iter().all(VecDeque::is_empty));
+        assert!(s.next_wake().is_none());
+    }
+
+    #[test]
+    fn bucket_refills_up_to_capacity() {
+        let now = Instant::now();
+        let mut bucket = Bucket::full(2.0, 1.0, now);
+        bucket.take();
+        bucket.take();
+        assert!(!bucket.ready(now));
+        assert_eq!(bucket.next_token_at(), now + Duration::from_secs(1));
+        assert!(bucket.ready(now + Duration::from_secs(1)));
+        bucket.refill(now + Duration::from_secs(60));
+        assert!(bucket.is_full());
+    }
+}
@@ -0,0 +1,206 @@
+//! Public, unauthenticated chat report behind `/report/{slug}`.
+//!
+//! A chat is served only when it is watched, has a `chats.slug` and has
+//! opted in via `chat_config.public_report`. The page covers the last
+//! complete chat-local day, so it only changes at local midnight — which is
+//! what makes the long HTTP cache lifetimes safe. Everything user-supplied
+//! (spam samples, moderator ban reasons) goes through [`redact`] first:
+//! URLs, e-mails, phone numbers and @mentions are replaced by the same
+//! sanitiser the AI summary uses.
+//!
+//! Built reports (data + chart) are memoised per slug — misses included —
+//! so a crawler hammering one page costs one aggregate and one chart render
+//! per [`CACHE_TTL`], and concurrent first requests share a single build.
+
+use std::sync::{Arc, LazyLock};
+use std::time::Duration as StdDuration;
+
+use anyhow::{Context, Result, anyhow};
+use chrono::{Duration, NaiveDate, Utc};
+use chrono_tz::Tz;
+use moka::future::Cache;
+use regex::Regex;
+use xxhash_rust::xxh3::xxh3_64;
+
+use crate::models::report::ReportData;
+use crate::services::chart_service;
+use crate::services::report_render::Lang;
+use crate::services::report_service::{ReportService, day_window_local};
+use crate::services::summary_service::sanitize;
+
+/// How long a built report (or a miss) is reused before re-aggregating.
+/// Also bounds how long an opt-out takes to apply on this server.
+pub const CACHE_TTL: StdDuration = StdDuration::from_secs(600);
+const CACHE_CAPACITY: u64 = 1_000;
+
+static SLUG_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[a-z0-9-]{3,64}$").expect("slug regex compiles"));
+
+/// Lowercase `[a-z0-9-]{3,64}` — the same shape `chats.slug` is checked
+/// against, so an invalid slug can be rejected without a lookup.
+pub fn is_valid_slug(slug: &str) -> bool {
+    SLUG_RE.is_match(slug)
+}
+
+/// Scrub user-supplied text out of a report before it leaves the dashboard:
+/// spam phrase samples and rollup ban reasons go through the summary
+/// sanitiser. Counters and the chat title (already public) are untouched.
+pub fn redact(mut report: ReportData) -> ReportData {
+    for phrase in &mut report.top_phrases {
+        phrase.text = sanitize(&phrase.text);
+    }
+    if let Some(rollup) = report.rollup.as_mut() {
+        for reason in &mut rollup.top_ban_reasons {
+            reason.reason = sanitize(&reason.reason);
+        }
+    }
+    report
+}
+
+/// One built public report. Immutable once cached.
+#[derive(Debug)]
+pub struct PublicReport {
+    pub slug: String,
+    /// The chat-local day the report covers.
+    pub date: NaiveDate,
+    pub lang: Lang,
+    /// Already passed through [`redact`].
+    pub data: ReportData,
+    /// 480×270 lossless WebP of `data`.
+    pub chart_webp: Vec<u8>,
+    /// xxh3 of the serialised `data`, hex. The chart is a pure function of
+    /// it, so one version covers every representation.
+    pub version: String,
+}
+
+pub struct PublicReportService {
+    reports: Arc<ReportService>,
+    watched: Vec<i64>,
+    cache: Cache<String, Option<Arc<PublicReport>>>,
+}
+
+impl PublicReportService {
+    pub fn new(reports: Arc<ReportService>, watched: Vec<i64>) -> Arc<Self> {
+        Arc::new(Self {
+            reports,
+            watched,
+            cache: Cache::builder()
+                .max_capacity(CACHE_CAPACITY)
+                .time_to_live(CACHE_TTL)
+                .build(),
+        })
+    }
+
+    /// The public report for `slug`, or `None` when no watched, opted-in
+    /// chat has it. Callers validate the slug with [`is_valid_slug`] first.
+    pub async fn get(&self, slug: &str) -> Result<Option<Arc<PublicReport>>> {
+        self.cache
+            .try_get_with(slug.to_owned(), self.build(slug))
+            .await
+            .map_err(|e| anyhow!("{e:#}"))
+    }
+
+    async fn build(&self, slug: &str) -> Result<Option<Arc<PublicReport>>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT c.chat_id, cc.timezone, cc.language
+            FROM chats c
+            JOIN chat_config cc USING (chat_id)
+            WHERE c.slug = $1 AND cc.public_report
+            "#,
+            slug,
+        )
+        .fetch_optional(self.reports.pool())
+        .await
+        .context("SELECT chats by slug")?;
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        if !self.watched.contains(&row.chat_id) {
+            return Ok(None);
+        }
+
+        let tz = row.timezone.parse::<Tz>().unwrap_or(chrono_tz::UTC);
+        let date = Utc::now().with_timezone(&tz).date_naive() - Duration::days(1);
+        let (from, to) = day_window_local(date, tz);
+        let data = redact(self.reports.aggregate(row.chat_id, from, to).await?);
+
+        let owned = data.clone();
+        let chart_webp = tokio::task::spawn_blocking(move || chart_service::render(&owned))
+            .await
+            .context("public report chart task")??;
+        let json = serde_json::to_vec(&data).context("serialise public report")?;
+
+        Ok(Some(Arc::new(PublicReport {
+            slug: slug.to_owned(),
+            date,
+            lang: Lang::from_db_str(&row.language),
+            data,
+            chart_webp,
+            version: format!("{:016x}", xxh3_64(&json)),
+        })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::report::{
+        BanReason, CaptchaCounts, PeriodTotals, ReportPeriod, RollupData, TopPhrase,
+    };
+
+    #[test]
+    fn slug_validation() {
+        assert!(is_valid_slug("dart-community"));
+        assert!(is_valid_slug("abc"));
+        assert!(is_valid_slug(&"a".repeat(64)));
+        assert!(!is_valid_slug("ab"));
+        assert!(!is_valid_slug(&"a".repeat(65)));
+        assert!(!is_valid_slug("Dart"));
+        assert!(!is_valid_slug("dart_community"));
+        assert!(!is_valid_slug("../etc"));
+        assert!(!is_valid_slug("dart community"));
+    }
+
+    #[test]
+    fn redact_scrubs_phrases_and_ban_reasons() {
+        let now = Utc::now();
+        let report = ReportData {
+            chat_id: -100,
+            from: now - Duration::days(1),
+            to: now,
+            chat_title: Some("Dart".into()),
+            messages_seen: 10,
+            messages_deleted: 2,
+            users_verified: 1,
+            users_banned: 1,
+            captcha: CaptchaCounts::default(),
+            top_phrases: vec![TopPhrase {
+                text: "earn $$$ https://scam.example write @scammer or +1 (555) 123-4567".into(),
+                hits: 3,
+            }],
+            last_7_days_messages: vec![],
+            rollup: Some(RollupData {
+                period: ReportPeriod::Weekly,
+                previous: PeriodTotals::default(),
+                history: vec![],
+                top_ban_reasons: vec![BanReason {
+                    reason: "spam from @someone".into(),
+                    count: 1,
+                }],
+                captcha_trend: vec![],
+            }),
+        };
+        let redacted = redact(report);
+        assert_eq!(
+            redacted.top_phrases[0].text,
+            "earn $$$ [link] write [user] or [phone]"
+        );
+        assert_eq!(
+            redacted.rollup.unwrap().top_ban_reasons[0].reason,
+            "spam from [user]"
+        );
+        assert_eq!(redacted.chat_title.as_deref(), Some("Dart"));
+        assert_eq!(redacted.messages_seen, 10);
+    }
+}
@@ -10,6 +10,10 @@
 //!   * Top phrases — escaped, truncated to 60 chars + `…`.
 //!   * Sparkline — last-7-days messages, one row of block characters.
 //!
+//! Weekly / monthly rollups additionally get a period-over-period delta on
+//! every counts line, the top ban reasons and a per-week captcha solve-rate
+//! block.
+//!
 //! Every section that produces zero data is omitted entirely so a quiet day
 //! shrinks gracefully instead of emitting "0 / 0 / 0" rows.
 //!
@@ -21,7 +25,9 @@
 
 use chrono::{DateTime, Datelike, Utc};
 
-use crate::models::report::{DailyPoint, ReportData, TopPhrase};
+pub use crate::i18n::Lang;
+use crate::models::report::{BanReason, DailyPoint, ReportData, RollupData, TopPhrase};
+use crate::tr;
 
 /// Maximum top-phrase sample length in the rendered message. Long enough to
 /// be informative, short enough that ten of them plus the rest of the report
@@ -35,27 +41,14 @@ const COUNTS_BAR_WIDTH: usize = 7;
 
 const BLOCK_CHARS: [char; 9] = [' ', '▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum Lang {
-    Ru,
-    En,
-}
-
-impl Lang {
-    pub fn from_db_str(s: &str) -> Self {
-        match s {
-            "en" => Self::En,
-            _ => Self::Ru,
-        }
-    }
-}
-
-/// Drives the header banner. `Daily` for the scheduled report, `Today`
-/// for `/stats` (chat-local day so far), `OnDemand` for `/report`. Affects
-/// the title line only.
+/// Drives the header banner. `Daily` / `Weekly` / `Monthly` for the
+/// scheduled reports, `Today` for `/stats` (chat-local day so far),
+/// `OnDemand` for `/report`. Affects the title line only.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum HeaderKind {
     Daily,
+    Weekly,
+    Monthly,
     Today,
     OnDemand,
 }
@@ -70,6 +63,10 @@ pub fn render(report: &ReportData, lang: Lang, header: HeaderKind) -> String {
     push_captcha(&mut out, report, lang);
     push_moderation(&mut out, report, lang);
     push_top_phrases(&mut out, report, lang);
+    if let Some(rollup) = &report.rollup {
+        push_ban_reasons(&mut out, rollup, lang);
+        push_captcha_trend(&mut out, rollup, lang);
+    }
     push_sparkline(&mut out, report, lang);
     // Trim a trailing newline if push_* left one — Telegram strips them but
     // it makes the snapshot tests slightly cleaner.
@@ -80,16 +77,14 @@ pub fn render(report: &ReportData, lang: Lang, header: HeaderKind) -> String {
 }
 
 fn push_header(out: &mut String, report: &ReportData, lang: Lang, header: HeaderKind) {
-    let title = match (lang, header) {
-        (Lang::Ru, HeaderKind::Daily) => "📊 *Ежедневный отчёт*",
-        (Lang::Ru, HeaderKind::Today) => "📊 *Сводка за сегодня*",
-        (Lang::Ru, HeaderKind::OnDemand) => "📊 *Отчёт по запросу*",
-        (Lang::En, HeaderKind::Daily) => "📊 *Daily report*",
-        (Lang::En, HeaderKind::Today) => "📊 *Today's snapshot*",
-        (Lang::En, HeaderKind::OnDemand) => "📊 *On-demand report*",
+    let key = match header {
+        HeaderKind::Daily => "report-title-daily",
+        HeaderKind::Weekly => "report-title-weekly",
+        HeaderKind::Monthly => "report-title-monthly",
+        HeaderKind::Today => "report-title-today",
+        HeaderKind::OnDemand => "report-title-on-demand",
     };
-    out.push_str(title);
-    out.push('\n');
+    out.push_str(&format!("📊 *{}*\n", escape(&tr!(lang, key))));
 
     if let Some(t) = &report.chat_title {
         out.push_str(&escape(t));
@@ -101,27 +96,49 @@ fn push_header(out: &mut String, report: &ReportData, lang: Lang, header: Header
 }
 
 fn push_counts(out: &mut String, report: &ReportData, lang: Lang) {
-    let labels = match lang {
-        Lang::Ru => ["Сообщений", "Удалено", "Верифицировано", "Забанено"],
-        Lang::En => ["Messages", "Deleted", "Verified", "Banned"],
-    };
+    let labels = [
+        "report-messages",
+        "report-deleted",
+        "report-verified",
+        "report-banned",
+    ]
+    .map(|key| tr!(lang, key));
     let values = [
         report.messages_seen,
         report.messages_deleted,
         report.users_verified,
         report.users_banned,
     ];
+    let previous = report.rollup.as_ref().map(|r| {
+        [
+            r.previous.messages_seen,
+            r.previous.messages_deleted,
+            r.previous.users_verified,
+            r.previous.users_banned,
+        ]
+    });
     let max = values.iter().copied().max().unwrap_or(0).max(1);
 
     let label_pad = labels.iter().map(|l| visual_width(l)).max().unwrap_or(0);
+    let value_pad = values
+        .iter()
+        .map(|v| v.to_string().len())
+        .max()
+        .unwrap_or(0);
 
     out.push_str("```\n");
-    for (label, &value) in labels.iter().zip(values.iter()) {
+    for (i, (label, &value)) in labels.iter().zip(values.iter()).enumerate() {
         let bar = ascii_bar(value, max, COUNTS_BAR_WIDTH);
         let pad = " ".repeat(label_pad.saturating_sub(visual_width(label)));
         // Inside a fenced ```code block``` MarkdownV2 only requires escaping
         // backticks and backslashes. Labels are static; values are integers.
-        out.push_str(&format!("{label}{pad}  {bar}  {value}\n"));
+        match previous {
+            Some(prev) => out.push_str(&format!(
+                "{label}{pad}  {bar}  {value:<value_pad$}  {}\n",
+                delta(value, prev[i])
+            )),
+            None => out.push_str(&format!("{label}{pad}  {bar}  {value}\n")),
+        }
     }
     out.push_str("```\n");
 }
@@ -130,16 +147,13 @@ fn push_captcha(out: &mut String, report: &ReportData, lang: Lang) {
     if report.captcha.total() == 0 {
         return;
     }
-    let header = match lang {
-        Lang::Ru => "*Капча*",
-        Lang::En => "*Captcha*",
-    };
-    let labels = match lang {
-        Lang::Ru => ["Выдано", "Решено", "Истекло"],
-        Lang::En => ["Issued", "Solved", "Expired"],
-    };
-    out.push_str(header);
-    out.push('\n');
+    let labels = [
+        "report-captcha-issued",
+        "report-captcha-solved",
+        "report-captcha-expired",
+    ]
+    .map(|key| tr!(lang, key));
+    push_section_header(out, lang, "report-captcha");
     out.push_str("```\n");
     let values = [
         report.captcha.issued,
@@ -170,12 +184,7 @@ fn push_top_phrases(out: &mut String, report: &ReportData, lang: Lang) {
     if report.top_phrases.is_empty() {
         return;
     }
-    let header = match lang {
-        Lang::Ru => "*Частые фразы*",
-        Lang::En => "*Top phrases*",
-    };
-    out.push_str(header);
-    out.push('\n');
+    push_section_header(out, lang, "report-top-phrases");
     for TopPhrase { text, hits } in &report.top_phrases {
         let truncated = truncate_chars(text, TOP_PHRASE_MAX_CHARS);
         let single_line = truncated.replace('\n', " ");
@@ -188,11 +197,45 @@ fn push_top_phrases(out: &mut String, report: &ReportData, lang: Lang) {
     out.push('\n');
 }
 
+fn push_ban_reasons(out: &mut String, rollup: &RollupData, lang: Lang) {
+    if rollup.top_ban_reasons.is_empty() {
+        return;
+    }
+    push_section_header(out, lang, "report-ban-reasons");
+    for BanReason { reason, count } in &rollup.top_ban_reasons {
+        let truncated = truncate_chars(reason, TOP_PHRASE_MAX_CHARS);
+        out.push_str(&format!(
+            "▌  {count}× {reason}\n",
+            count = escape(&count.to_string()),
+            reason = escape(&truncated.replace('\n', " ")),
+        ));
+    }
+    out.push('\n');
+}
+
+/// One line per 7-day bucket: week start, solve-rate bar, percentage. The
+/// header carries the whole-period rate against the previous period.
+fn push_captcha_trend(out: &mut String, rollup: &RollupData, lang: Lang) {
+    if rollup.captcha_trend.iter().all(|p| p.issued == 0) {
+        return;
+    }
+    push_section_header(out, lang, "report-captcha-trend");
+    out.push_str("```\n");
+    for point in &rollup.captcha_trend {
+        let label = point.week_start.format("%m-%d");
+        match point.solve_rate() {
+            Some(rate) => {
+                let pct = (rate * 100.0).round() as i64;
+                let bar = ascii_bar(pct, 100, COUNTS_BAR_WIDTH);
+                out.push_str(&format!("{label}  {bar}  {pct}%\n"));
+            }
+            None => out.push_str(&format!("{label}  {}  —\n", " ".repeat(COUNTS_BAR_WIDTH))),
+        }
+    }
+    out.push_str("```\n");
+}
+
 fn push_sparkline(out: &mut String, report: &ReportData, lang: Lang) {
-    let header = match lang {
-        Lang::Ru => "*7 дней*",
-        Lang::En => "*Last 7 days*",
-    };
     let max = report
         .last_7_days_messages
         .iter()
@@ -202,8 +245,7 @@ fn push_sparkline(out: &mut String, report: &ReportData, lang: Lang) {
     if max == 0 {
         return;
     }
-    out.push_str(header);
-    out.push('\n');
+    push_section_header(out, lang, "report-last-7-days");
     out.push_str("```\n");
     let line: String = report
         .last_7_days_messages
@@ -213,21 +255,15 @@ fn push_sparkline(out: &mut String, report: &ReportData, lang: Lang) {
     out.push_str(&line);
     out.push('\n');
     // Day labels under the bars so a flat sparkline still anchors to dates.
+    // 1-char weekday codes keep the label row the same width as the
+    // sparkline.
+    let initials: Vec<char> = tr!(lang, "report-weekday-initials").chars().collect();
     let labels: String = report
         .last_7_days_messages
         .iter()
         .map(|DailyPoint { date, .. }| {
-            // 1-char weekday code (Mon=M, Tue=T, etc.) keeps the label row
-            // the same width as the sparkline.
-            match date.weekday().num_days_from_monday() {
-                0 => 'M',
-                1 => 'T',
-                2 => 'W',
-                3 => 'T',
-                4 => 'F',
-                5 => 'S',
-                _ => 'S',
-            }
+            let day = date.weekday().num_days_from_monday() as usize;
+            initials.get(day).copied().unwrap_or('?')
         })
         .collect();
     out.push_str(&labels);
@@ -237,6 +273,12 @@ fn push_sparkline(out: &mut String, report: &ReportData, lang: Lang) {
 
 // ── helpers ───────────────────────────────────────────────────────────────
 
+/// `*Label*` line above a section. Catalog strings may contain MarkdownV2
+/// specials (`'`, `-`), so they are escaped like user input.
+fn push_section_header(out: &mut String, lang: Lang, key: &str) {
+    out.push_str(&format!("*{}*\n", escape(&tr!(lang, key))));
+}
+
 fn ascii_bar(value: i64, max: i64, width: usize) -> String {
     let max = max.max(1) as f64;
     let v = value.max(0) as f64;
@@ -256,6 +298,24 @@ fn ascii_bar(value: i64, max: i64, width: usize) -> String {
     s
 }
 
+/// Period-over-period change: `+12%`, `-5%`, `±0%`; `new` when the previous
+/// period was zero and this one isn't.
+fn delta(value: i64, previous: i64) -> String {
+    if previous == 0 {
+        return if value == 0 {
+            "±0%".into()
+        } else {
+            "new".into()
+        };
+    }
+    let pct = ((value - previous) as f64 * 100.0 / previous as f64).round() as i64;
+    match pct {
+        0 => "±0%".into(),
+        p if p > 0 => format!("+{p}%"),
+        p => format!("{p}%"),
+    }
+}
+
 fn spark_char(value: i64, max: i64) -> char {
     if max <= 0 {
         return BLOCK_CHARS[0];
@@ -322,7 +382,9 @@ pub fn escape(s: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::report::{CaptchaCounts, DailyPoint, TopPhrase};
+    use crate::models::report::{
+        CaptchaCounts, DailyPoint, PeriodTotals, ReportPeriod, SolveRatePoint, TopPhrase,
+    };
     use chrono::{Duration, NaiveDate, TimeZone};
 
     fn fixture() -> ReportData {
@@ -360,9 +422,48 @@ mod tests {
                 },
             ],
             last_7_days_messages: last_7,
+            rollup: None,
         }
     }
 
+    fn rollup_fixture() -> ReportData {
+        let mut r = fixture();
+        let start = NaiveDate::from_ymd_opt(2026, 4, 5).unwrap();
+        r.rollup = Some(RollupData {
+            period: ReportPeriod::Weekly,
+            previous: PeriodTotals {
+                messages_seen: 100,
+                messages_deleted: 4,
+                users_verified: 0,
+                users_banned: 2,
+                captcha: CaptchaCounts::default(),
+            },
+            history: (0..30)
+                .map(|i| DailyPoint {
+                    date: start + Duration::days(i),
+                    messages: i,
+                })
+                .collect(),
+            top_ban_reasons: vec![BanReason {
+                reason: "cas".into(),
+                count: 3,
+            }],
+            captcha_trend: vec![
+                SolveRatePoint {
+                    week_start: NaiveDate::from_ymd_opt(2026, 4, 19).unwrap(),
+                    issued: 0,
+                    solved: 0,
+                },
+                SolveRatePoint {
+                    week_start: NaiveDate::from_ymd_opt(2026, 4, 26).unwrap(),
+                    issued: 4,
+                    solved: 3,
+                },
+            ],
+        });
+        r
+    }
+
     #[test]
     fn escape_all_special_chars() {
         let s = escape("a.b_c*d[e]f(g)h~i`j>k#l+m-n=o|p{q}r!s\\t");
@@ -442,6 +543,18 @@ mod tests {
         assert!(s.contains("Top phrases"));
     }
 
+    #[test]
+    fn ukrainian_locale_renders_ukrainian_strings() {
+        let s = render(&fixture(), Lang::Uk, HeaderKind::Today);
+        assert!(s.contains("Зведення за сьогодні"));
+        assert!(s.contains("Повідомлень"));
+        let (_, labels) = sparkline_rows(&s);
+        assert!(
+            labels.chars().all(|c| "ПВСЧН".contains(c)),
+            "got {labels:?}"
+        );
+    }
+
     #[test]
     fn truncates_long_phrases() {
         let mut r = fixture();
@@ -516,7 +629,10 @@ mod tests {
     fn sparkline_rows(rendered: &str) -> (String, String) {
         let mut iter = rendered.lines();
         for line in iter.by_ref() {
-            if line.contains("*7 дней*") || line.contains("*Last 7 days*") {
+            if line.contains("*7 дней*")
+                || line.contains("*7 днів*")
+                || line.contains("*Last 7 days*")
+            {
                 break;
             }
         }
@@ -555,6 +671,7 @@ mod tests {
     fn lang_from_db_str_falls_back_to_ru() {
         assert!(matches!(Lang::from_db_str("ru"), Lang::Ru));
         assert!(matches!(Lang::from_db_str("en"), Lang::En));
+        assert!(matches!(Lang::from_db_str("uk"), Lang::Uk));
         // Unknown / mistyped value collapses to RU rather than panicking.
         assert!(matches!(Lang::from_db_str(""), Lang::Ru));
         assert!(matches!(Lang::from_db_str("fr"), Lang::Ru));
@@ -588,4 +705,34 @@ mod tests {
         // Header → date line → counts: no orphan blank.
         assert!(s.contains("UTC"));
     }
+
+    #[test]
+    fn delta_formats_sign_and_new() {
+        assert_eq!(delta(120, 100), "+20%");
+        assert_eq!(delta(90, 100), "-10%");
+        assert_eq!(delta(100, 100), "±0%");
+        assert_eq!(delta(0, 0), "±0%");
+        assert_eq!(delta(5, 0), "new");
+    }
+
+    #[test]
+    fn rollup_renders_deltas_reasons_and_trend() {
+        let s = render(&rollup_fixture(), Lang::En, HeaderKind::Weekly);
+        assert!(s.starts_with("📊 *Weekly report*"));
+        assert!(s.contains("Messages"));
+        assert!(s.contains("+20%"), "messages delta: {s}");
+        assert!(s.contains("new"), "verified delta: {s}");
+        assert!(s.contains("*Top ban reasons*"));
+        assert!(s.contains("3× cas"));
+        assert!(s.contains("*Captcha solve rate by week*"));
+        assert!(s.contains("04-26"));
+        assert!(s.contains("75%"));
+    }
+
+    #[test]
+    fn daily_has_no_rollup_sections() {
+        let s = render(&fixture(), Lang::En, HeaderKind::Daily);
+        assert!(!s.contains("%"));
+        assert!(!s.contains("*Top ban reasons*"));
+    }
 }
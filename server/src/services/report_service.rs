@@ -6,13 +6,20 @@
 //! kind)` index for cheap re-aggregation. No N+1 — top phrases come from a
 //! single ORDER BY on `spam_messages`. The renderer + chart are pure
 //! functions of the returned struct, so unit tests don't need a bot.
+//!
+//! Weekly / monthly rollups ([`ReportService::aggregate_rollup`]) run the
+//! same aggregate twice — this window and the one before it — and add the
+//! 30/90-day history, ban reasons and the captcha solve-rate trend.
 
 use anyhow::{Context, Result};
-use chrono::{DateTime, Duration, LocalResult, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono::{DateTime, Duration, LocalResult, Months, NaiveDate, NaiveDateTime, TimeZone, Utc};
 use chrono_tz::Tz;
 use sqlx::PgPool;
 
-use crate::models::report::{CaptchaCounts, DailyPoint, ReportData, TopPhrase};
+use crate::models::report::{
+    BanReason, CaptchaCounts, DailyPoint, PeriodTotals, ReportData, ReportPeriod, RollupData,
+    SolveRatePoint, TopPhrase,
+};
 
 /// How many spam-phrase samples the renderer can fit in one MarkdownV2
 /// message. Ten is the upper bound the issue spec mentions.
@@ -22,6 +29,9 @@ const TOP_PHRASES_LIMIT: i64 = 10;
 /// length so the renderer doesn't have to handle ragged input.
 const SPARKLINE_DAYS: i64 = 7;
 
+/// How many ban reasons a rollup lists.
+const TOP_BAN_REASONS_LIMIT: i64 = 5;
+
 #[derive(Clone)]
 pub struct ReportService {
     db: PgPool,
@@ -49,7 +59,6 @@ impl ReportService {
         from: DateTime<Utc>,
         to: DateTime<Utc>,
     ) -> Result<ReportData> {
-        let from_date: NaiveDate = from.date_naive();
         let to_date: NaiveDate = to.date_naive();
 
         let chat_title = sqlx::query_scalar!(
@@ -60,29 +69,7 @@ impl ReportService {
         .await
         .context("SELECT chat_info_cache.title")?;
 
-        let messages_seen = self
-            .sum_metric(chat_id, "messages_seen", from_date, to_date)
-            .await?;
-
-        // moderation_actions counters use created_at — the daily_stats
-        // mirror exists for cheap reads but the source-of-truth count for
-        // the report is the ledger (so retroactive bans counted on the
-        // right day even if the daily_stats UPSERT raced something).
-        let messages_deleted = count_actions(&self.db, chat_id, "delete", from, to).await?;
-        let users_verified = count_actions(&self.db, chat_id, "verify", from, to).await?;
-        let users_banned = count_actions(&self.db, chat_id, "ban", from, to).await?;
-
-        let captcha = CaptchaCounts {
-            issued: self
-                .sum_metric(chat_id, "captcha_issued", from_date, to_date)
-                .await?,
-            solved: self
-                .sum_metric(chat_id, "captcha_solved", from_date, to_date)
-                .await?,
-            expired: self
-                .sum_metric(chat_id, "captcha_expired", from_date, to_date)
-                .await?,
-        };
+        let totals = self.totals(chat_id, from, to).await?;
 
         let top_phrases = sqlx::query!(
             r#"
@@ -109,20 +96,97 @@ impl ReportService {
         })
         .collect();
 
-        let last_7_days_messages = self.sparkline(chat_id, to_date).await?;
+        let last_7_days_messages = self.daily_series(chat_id, to_date, SPARKLINE_DAYS).await?;
 
         Ok(ReportData {
             chat_id,
             from,
             to,
             chat_title,
+            messages_seen: totals.messages_seen,
+            messages_deleted: totals.messages_deleted,
+            users_verified: totals.users_verified,
+            users_banned: totals.users_banned,
+            captcha: totals.captcha,
+            top_phrases,
+            last_7_days_messages,
+            rollup: None,
+        })
+    }
+
+    /// [`Self::aggregate`] for a weekly / monthly window, plus the
+    /// [`RollupData`] comparing it with `[prev_from, from)` — the equally
+    /// long window right before it (see [`period_window_local`]).
+    pub async fn aggregate_rollup(
+        &self,
+        chat_id: i64,
+        period: ReportPeriod,
+        (prev_from, from, to): (DateTime<Utc>, DateTime<Utc>, DateTime<Utc>),
+    ) -> Result<ReportData> {
+        let mut report = self.aggregate(chat_id, from, to).await?;
+        let previous = self.totals(chat_id, prev_from, from).await?;
+
+        // `to` is the midnight after the report day; the history ends on the
+        // last day inside the window, not on the (empty) day after it.
+        let last_day = (to - Duration::seconds(1)).date_naive();
+        let history = self
+            .daily_series(chat_id, last_day, period.history_days())
+            .await?;
+        let top_ban_reasons = self.top_ban_reasons(chat_id, from, to).await?;
+        let captcha_trend = self
+            .captcha_trend(chat_id, last_day, period.history_days() / 7)
+            .await?;
+
+        report.rollup = Some(RollupData {
+            period,
+            previous,
+            history,
+            top_ban_reasons,
+            captcha_trend,
+        });
+        Ok(report)
+    }
+
+    /// The headline counters for `[from, to)`.
+    async fn totals(
+        &self,
+        chat_id: i64,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<PeriodTotals> {
+        let from_date: NaiveDate = from.date_naive();
+        let to_date: NaiveDate = to.date_naive();
+
+        let messages_seen = self
+            .sum_metric(chat_id, "messages_seen", from_date, to_date)
+            .await?;
+
+        // moderation_actions counters use created_at — the daily_stats
+        // mirror exists for cheap reads but the source-of-truth count for
+        // the report is the ledger (so retroactive bans counted on the
+        // right day even if the daily_stats UPSERT raced something).
+        let messages_deleted = count_actions(&self.db, chat_id, "delete", from, to).await?;
+        let users_verified = count_actions(&self.db, chat_id, "verify", from, to).await?;
+        let users_banned = count_actions(&self.db, chat_id, "ban", from, to).await?;
+
+        let captcha = CaptchaCounts {
+            issued: self
+                .sum_metric(chat_id, "captcha_issued", from_date, to_date)
+                .await?,
+            solved: self
+                .sum_metric(chat_id, "captcha_solved", from_date, to_date)
+                .await?,
+            expired: self
+                .sum_metric(chat_id, "captcha_expired", from_date, to_date)
+                .await?,
+        };
+
+        Ok(PeriodTotals {
             messages_seen,
             messages_deleted,
             users_verified,
             users_banned,
             captcha,
-            top_phrases,
-            last_7_days_messages,
         })
     }
 
@@ -153,11 +217,16 @@ impl ReportService {
         Ok(row)
     }
 
-    /// Last 7 days (inclusive of `to_date`) of `messages_seen`, oldest
-    /// first. Missing days are zero — the result is always exactly
-    /// [`SPARKLINE_DAYS`] long so the renderer can index it directly.
-    async fn sparkline(&self, chat_id: i64, to_date: NaiveDate) -> Result<Vec<DailyPoint>> {
-        let from_date = to_date - Duration::days(SPARKLINE_DAYS - 1);
+    /// Last `days` days (inclusive of `to_date`) of `messages_seen`, oldest
+    /// first. Missing days are zero — the result is always exactly `days`
+    /// long so the renderer can index it directly.
+    async fn daily_series(
+        &self,
+        chat_id: i64,
+        to_date: NaiveDate,
+        days: i64,
+    ) -> Result<Vec<DailyPoint>> {
+        let from_date = to_date - Duration::days(days - 1);
         let rows = sqlx::query!(
             r#"
             SELECT date, value
@@ -171,15 +240,15 @@ impl ReportService {
         )
         .fetch_all(&self.db)
         .await
-        .context("SELECT daily_stats (sparkline)")?;
+        .context("SELECT daily_stats (daily series)")?;
 
         let mut by_date = std::collections::BTreeMap::new();
         for r in rows {
             by_date.insert(r.date, r.value);
         }
 
-        let mut out = Vec::with_capacity(SPARKLINE_DAYS as usize);
-        for offset in 0..SPARKLINE_DAYS {
+        let mut out = Vec::with_capacity(days as usize);
+        for offset in 0..days {
             let day = from_date + Duration::days(offset);
             out.push(DailyPoint {
                 date: day,
@@ -188,6 +257,114 @@ impl ReportService {
         }
         Ok(out)
     }
+
+    /// Ban counts per reason in `[from, to)`. Spam bans count once per rule
+    /// in `reason.matched_rules`; moderator bans count under their free-text
+    /// reason (`manual` when empty), even when that text starts with `{`.
+    async fn top_ban_reasons(
+        &self,
+        chat_id: i64,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<BanReason>> {
+        let rows = sqlx::query!(
+            r#"
+            WITH bans AS (
+                SELECT reason,
+                       CASE WHEN reason LIKE '{%' THEN try_jsonb(reason) END AS verdict
+                FROM moderation_actions
+                WHERE chat_id = $1 AND action = 'ban'
+                  AND created_at >= $2 AND created_at < $3
+            )
+            SELECT reason AS "reason!", COUNT(*)::BIGINT AS "n!"
+            FROM (
+                SELECT jsonb_array_elements_text(verdict -> 'matched_rules') AS reason
+                FROM bans
+                WHERE jsonb_typeof(verdict -> 'matched_rules') = 'array'
+                UNION ALL
+                SELECT COALESCE(NULLIF(reason, ''), 'manual')
+                FROM bans
+                WHERE jsonb_typeof(verdict -> 'matched_rules') IS DISTINCT FROM 'array'
+            ) r
+            GROUP BY reason
+            ORDER BY 2 DESC, 1
+            LIMIT $4
+            "#,
+            chat_id,
+            from,
+            to,
+            TOP_BAN_REASONS_LIMIT,
+        )
+        .fetch_all(&self.db)
+        .await
+        .context("SELECT moderation_actions (ban reasons)")?;
+        Ok(rows
+            .into_iter()
+            .map(|r| BanReason {
+                reason: r.reason,
+                count: r.n,
+            })
+            .collect())
+    }
+
+    /// Captcha issued / solved in `weeks` 7-day buckets ending with
+    /// `to_date`, oldest first.
+    async fn captcha_trend(
+        &self,
+        chat_id: i64,
+        to_date: NaiveDate,
+        weeks: i64,
+    ) -> Result<Vec<SolveRatePoint>> {
+        let from_date = to_date - Duration::days(weeks * 7 - 1);
+        let rows = sqlx::query!(
+            r#"
+            SELECT date, kind, value
+            FROM daily_stats
+            WHERE chat_id = $1 AND kind IN ('captcha_issued', 'captcha_solved')
+              AND date >= $2 AND date <= $3
+            "#,
+            chat_id,
+            from_date,
+            to_date,
+        )
+        .fetch_all(&self.db)
+        .await
+        .context("SELECT daily_stats (captcha trend)")?;
+        Ok(weekly_buckets(
+            to_date,
+            weeks,
+            rows.into_iter().map(|r| (r.date, r.kind, r.value)),
+        ))
+    }
+}
+
+/// Fold `(date, kind, value)` captcha rows into `weeks` 7-day buckets ending
+/// with `to_date`, oldest first. Rows outside the buckets are dropped.
+fn weekly_buckets(
+    to_date: NaiveDate,
+    weeks: i64,
+    rows: impl IntoIterator<Item = (NaiveDate, String, i64)>,
+) -> Vec<SolveRatePoint> {
+    let mut out: Vec<SolveRatePoint> = (0..weeks)
+        .map(|i| SolveRatePoint {
+            week_start: to_date - Duration::days((weeks - i) * 7 - 1),
+            issued: 0,
+            solved: 0,
+        })
+        .collect();
+    for (date, kind, value) in rows {
+        let back = (to_date - date).num_days();
+        if !(0..weeks * 7).contains(&back) {
+            continue;
+        }
+        let bucket = &mut out[(weeks - 1 - back / 7) as usize];
+        match kind.as_str() {
+            "captcha_issued" => bucket.issued += value,
+            "captcha_solved" => bucket.solved += value,
+            _ => {}
+        }
+    }
+    out
 }
 
 /// COUNT moderation_actions rows for a `(chat_id, action, [from, to))` window.
@@ -256,6 +433,38 @@ pub fn day_window_local(date: NaiveDate, tz: Tz) -> (DateTime<Utc>, DateTime<Utc
     (resolve_local(start_local, tz), resolve_local(end_local, tz))
 }
 
+/// First chat-local day of the `period` report posted on `date`: the day
+/// itself, the 7 days ending with it, or the month since the same day of
+/// the previous month.
+pub fn period_first_day(period: ReportPeriod, date: NaiveDate) -> NaiveDate {
+    match period {
+        ReportPeriod::Daily => date,
+        ReportPeriod::Weekly => date - Duration::days(6),
+        ReportPeriod::Monthly => {
+            date.checked_sub_months(Months::new(1))
+                .expect("one month back is always representable")
+                + Duration::days(1)
+        }
+    }
+}
+
+/// UTC `(prev_from, from, to)` for the `period` report posted on the
+/// chat-local `date`: `[from, to)` covers [`period_first_day`] through
+/// `date`, `[prev_from, from)` is the same period ending the day before.
+pub fn period_window_local(
+    period: ReportPeriod,
+    date: NaiveDate,
+    tz: Tz,
+) -> (DateTime<Utc>, DateTime<Utc>, DateTime<Utc>) {
+    let first = period_first_day(period, date);
+    let prev_first = period_first_day(period, first - Duration::days(1));
+    (
+        day_window_local(prev_first, tz).0,
+        day_window_local(first, tz).0,
+        day_window_local(date, tz).1,
+    )
+}
+
 fn resolve_local(dt: NaiveDateTime, tz: Tz) -> DateTime<Utc> {
     match tz.from_local_datetime(&dt) {
         LocalResult::Single(t) => t.with_timezone(&Utc),
@@ -305,6 +514,45 @@ mod tests {
         assert_eq!(to.hour(), 21);
     }
 
+    #[test]
+    fn period_windows_cover_the_report_day() {
+        let date = NaiveDate::from_ymd_opt(2026, 5, 4).unwrap();
+        let d = |m, d| NaiveDate::from_ymd_opt(2026, m, d).unwrap();
+        assert_eq!(period_first_day(ReportPeriod::Daily, date), date);
+        assert_eq!(period_first_day(ReportPeriod::Weekly, date), d(4, 28));
+        assert_eq!(period_first_day(ReportPeriod::Monthly, date), d(4, 5));
+
+        let (prev_from, from, to) = period_window_local(ReportPeriod::Weekly, date, chrono_tz::UTC);
+        assert_eq!(to - from, Duration::days(7));
+        assert_eq!(from - prev_from, Duration::days(7));
+        assert_eq!(to.date_naive(), d(5, 5));
+
+        // 5 Apr → 4 May (30 days), previous 5 Mar → 4 Apr (31 days).
+        let (prev_from, from, to) =
+            period_window_local(ReportPeriod::Monthly, date, chrono_tz::UTC);
+        assert_eq!(to - from, Duration::days(30));
+        assert_eq!(from - prev_from, Duration::days(31));
+    }
+
+    #[test]
+    fn weekly_buckets_end_with_to_date() {
+        let to = NaiveDate::from_ymd_opt(2026, 5, 4).unwrap();
+        let rows = vec![
+            (to, "captcha_issued".to_string(), 4),
+            (to, "captcha_solved".to_string(), 3),
+            (to - Duration::days(6), "captcha_issued".to_string(), 1),
+            (to - Duration::days(7), "captcha_issued".to_string(), 10),
+            (to - Duration::days(14), "captcha_issued".to_string(), 99),
+        ];
+        let buckets = weekly_buckets(to, 2, rows);
+        assert_eq!(buckets.len(), 2);
+        assert_eq!(buckets[1].week_start, to - Duration::days(6));
+        assert_eq!((buckets[1].issued, buckets[1].solved), (5, 3));
+        assert_eq!(buckets[0].week_start, to - Duration::days(13));
+        assert_eq!((buckets[0].issued, buckets[0].solved), (10, 0));
+        assert_eq!(buckets[0].solve_rate(), Some(0.0));
+    }
+
     #[test]
     fn day_window_local_with_utc_matches_day_window_utc() {
         let date = NaiveDate::from_ymd_opt(2026, 5, 3).unwrap();
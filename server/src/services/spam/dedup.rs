@@ -9,7 +9,7 @@
 use anyhow::{Context, Result};
 use sqlx::PgPool;
 
-const SAMPLE_BODY_MAX: usize = 4096;
+pub(crate) const SAMPLE_BODY_MAX: usize = 4096;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DedupOutcome {
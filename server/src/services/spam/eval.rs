@@ -0,0 +1,415 @@
+//! Offline evaluation of the spam cascade's pure steps over a labelled
+//! corpus: normalize → dedup → optional near-dup → n-gram score. No CAS, no
+//! LLM, no database — dedup is an in-memory set that fills the way
+//! `spam_messages` would over the run.
+//!
+//! Drives `examples/spam_eval.rs`; see `server/docs/spam-detection.md`
+//! §"Tuning with the corpus".
+
+use std::collections::{BTreeMap, HashSet};
+
+use xxhash_rust::xxh3::xxh3_64;
+
+use crate::services::spam::normalize::normalize;
+use crate::services::spam::phrases::{PHRASES, SpamWeights};
+use crate::services::spam::service::MIN_NORMALIZED_LEN;
+
+/// Shingle width for the near-dup similarity, in chars.
+const SHINGLE_CHARS: usize = 3;
+
+#[derive(Debug, Clone)]
+pub struct Sample {
+    pub text: String,
+    /// Ground truth: `true` = should be deleted or banned.
+    pub spam: bool,
+    /// The hash is in the dictionary before the run starts (the corpus
+    /// `must_ban` samples, which `tests/spam_pipeline.rs` pre-seeds).
+    pub known: bool,
+    /// Where the sample came from, for the misclassification listing.
+    pub origin: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Params {
+    /// `chat_config.spam_threshold`.
+    pub threshold: f32,
+    /// `chat_config.spam_weights`.
+    pub weights: SpamWeights,
+    /// Jaccard similarity (0..=1) over char shingles at which a sample
+    /// counts as a near-duplicate of one already recorded. `None` = step
+    /// off. The live pipeline has no such step; this measures whether one
+    /// would pay off.
+    pub near_dup: Option<f32>,
+}
+
+impl Default for Params {
+    /// The `chat_config` column defaults.
+    fn default() -> Self {
+        Self {
+            threshold: 1.0,
+            weights: SpamWeights::default(),
+            near_dup: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Rule {
+    Dedup,
+    NearDup,
+    Ngram,
+}
+
+impl Rule {
+    pub const ALL: [Rule; 3] = [Rule::Dedup, Rule::NearDup, Rule::Ngram];
+
+    /// Same names as `reason_json["matched_rules"]` where one exists.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Dedup => "xxh3_dedup",
+            Self::NearDup => "near_dup",
+            Self::Ngram => "ngram",
+        }
+    }
+}
+
+/// Binary confusion matrix; "positive" = flagged as spam.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Confusion {
+    pub tp: u32,
+    pub fp: u32,
+    pub tn: u32,
+    pub fn_: u32,
+}
+
+impl Confusion {
+    fn add(&mut self, flagged: bool, spam: bool) {
+        match (flagged, spam) {
+            (true, true) => self.tp += 1,
+            (true, false) => self.fp += 1,
+            (false, false) => self.tn += 1,
+            (false, true) => self.fn_ += 1,
+        }
+    }
+
+    /// `None` when nothing was flagged.
+    pub fn precision(&self) -> Option<f64> {
+        ratio(self.tp, self.tp + self.fp)
+    }
+
+    /// `None` when the corpus has no spam.
+    pub fn recall(&self) -> Option<f64> {
+        ratio(self.tp, self.tp + self.fn_)
+    }
+
+    pub fn f1(&self) -> Option<f64> {
+        let (p, r) = (self.precision()?, self.recall()?);
+        if p + r == 0.0 {
+            return Some(0.0);
+        }
+        Some(2.0 * p * r / (p + r))
+    }
+}
+
+fn ratio(num: u32, den: u32) -> Option<f64> {
+    (den > 0).then(|| f64::from(num) / f64::from(den))
+}
+
+/// What the cascade did with one sample.
+#[derive(Debug, Clone)]
+pub struct SampleOutcome {
+    /// The rule that decided, `None` = allowed.
+    pub verdict: Option<Rule>,
+    /// Every rule that would have fired, in cascade order.
+    pub fired: Vec<Rule>,
+    /// Below `MIN_NORMALIZED_LEN`; no rule ran.
+    pub short: bool,
+    pub score: f32,
+    pub matched: Vec<&'static str>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Evaluation {
+    /// The cascade's final verdict against the labels.
+    pub overall: Confusion,
+    /// Each rule on its own: "fired" against the labels, regardless of
+    /// whether an earlier rule already decided.
+    pub per_rule: BTreeMap<Rule, Confusion>,
+    /// Parallel to the input samples.
+    pub outcomes: Vec<SampleOutcome>,
+}
+
+/// Run every sample through the cascade, in order. Samples flagged by a
+/// content rule are recorded for dedup and near-dup, like
+/// `dedup::record` in the live pipeline, so later copies in the corpus hit.
+pub fn evaluate(samples: &[Sample], params: &Params) -> Evaluation {
+    let mut hashes: HashSet<i64> = samples
+        .iter()
+        .filter(|s| s.known)
+        .map(|s| hash(&normalize(&s.text)))
+        .collect();
+    let mut recorded: Vec<HashSet<String>> = Vec::new();
+
+    let mut overall = Confusion::default();
+    let mut per_rule: BTreeMap<Rule, Confusion> = Rule::ALL
+        .iter()
+        .map(|r| (*r, Confusion::default()))
+        .collect();
+    let mut outcomes = Vec::with_capacity(samples.len());
+
+    for sample in samples {
+        let normalized = normalize(&sample.text);
+        let outcome = if normalized.chars().count() < MIN_NORMALIZED_LEN {
+            SampleOutcome {
+                verdict: None,
+                fired: Vec::new(),
+                short: true,
+                score: 0.0,
+                matched: Vec::new(),
+            }
+        } else {
+            let h = hash(&normalized);
+            let shingled = shingles(&normalized);
+            let (score, matched) = PHRASES.score(&normalized, &params.weights);
+
+            let mut fired = Vec::new();
+            if hashes.contains(&h) {
+                fired.push(Rule::Dedup);
+            }
+            if let Some(min) = params.near_dup {
+                if recorded
+                    .iter()
+                    .any(|r| jaccard(r, &shingled) >= f64::from(min))
+                {
+                    fired.push(Rule::NearDup);
+                }
+            }
+            if score >= params.threshold && !matched.is_empty() {
+                fired.push(Rule::Ngram);
+            }
+
+            let verdict = fired.first().copied();
+            if matches!(verdict, Some(Rule::NearDup | Rule::Ngram)) {
+                hashes.insert(h);
+                recorded.push(shingled);
+            }
+            SampleOutcome {
+                verdict,
+                fired,
+                short: false,
+                score,
+                matched,
+            }
+        };
+
+        overall.add(outcome.verdict.is_some(), sample.spam);
+        for (rule, confusion) in per_rule.iter_mut() {
+            confusion.add(outcome.fired.contains(rule), sample.spam);
+        }
+        outcomes.push(outcome);
+    }
+
+    Evaluation {
+        overall,
+        per_rule,
+        outcomes,
+    }
+}
+
+/// The overall matrix at each `spam_threshold`, other params unchanged.
+pub fn sweep_threshold(
+    samples: &[Sample],
+    params: &Params,
+    thresholds: &[f32],
+) -> Vec<(f32, Confusion)> {
+    thresholds
+        .iter()
+        .map(|&threshold| {
+            let params = Params {
+                threshold,
+                ..params.clone()
+            };
+            (threshold, evaluate(samples, &params).overall)
+        })
+        .collect()
+}
+
+/// How one phrase behaves on the corpus, for tuning `spam_weights`.
+#[derive(Debug, Clone)]
+pub struct PhraseStats {
+    pub phrase: &'static str,
+    pub weight: f32,
+    /// Samples (spam / ham) whose normalized text contains the phrase.
+    pub spam_hits: u32,
+    pub ham_hits: u32,
+    /// The overall matrix with this phrase's weight set to 0.
+    pub without: Confusion,
+}
+
+/// [`PhraseStats`] for every phrase that matches at least one sample,
+/// ordered by ham hits (the false-positive drivers) first.
+pub fn phrase_ablation(samples: &[Sample], params: &Params) -> Vec<PhraseStats> {
+    let mut hits: BTreeMap<&'static str, (u32, u32)> = BTreeMap::new();
+    for sample in samples {
+        let normalized = normalize(&sample.text);
+        if normalized.chars().count() < MIN_NORMALIZED_LEN {
+            continue;
+        }
+        for phrase in PHRASES.matches(&normalized) {
+            let entry = hits.entry(phrase).or_default();
+            if sample.spam {
+                entry.0 += 1;
+            } else {
+                entry.1 += 1;
+            }
+        }
+    }
+
+    let mut stats: Vec<PhraseStats> = hits
+        .into_iter()
+        .map(|(phrase, (spam_hits, ham_hits))| {
+            let ablated = Params {
+                weights: params.weights.clone().with_weight(phrase, 0.0),
+                ..params.clone()
+            };
+            PhraseStats {
+                phrase,
+                weight: params.weights.weight_for(phrase),
+                spam_hits,
+                ham_hits,
+                without: evaluate(samples, &ablated).overall,
+            }
+        })
+        .collect();
+    stats.sort_by(|a, b| b.ham_hits.cmp(&a.ham_hits).then(a.phrase.cmp(b.phrase)));
+    stats
+}
+
+fn hash(normalized: &str) -> i64 {
+    xxh3_64(normalized.as_bytes()) as i64
+}
+
+fn shingles(normalized: &str) -> HashSet<String> {
+    let chars: Vec<char> = normalized.chars().collect();
+    chars
+        .windows(SHINGLE_CHARS)
+        .map(|w| w.iter().collect())
+        .collect()
+}
+
+fn jaccard(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPAM: &str = "Привет всем, предлагаю быстрый заработок без вложений в нашей команде";
+    const HAM: &str = "Сегодня встреча перенесена на 18:00, кому удобно подключиться по zoom";
+
+    fn sample(text: &str, spam: bool) -> Sample {
+        Sample {
+            text: text.to_owned(),
+            spam,
+            known: false,
+            origin: "test".to_owned(),
+        }
+    }
+
+    #[test]
+    fn ngram_flags_spam_and_allows_ham() {
+        let eval = evaluate(
+            &[sample(SPAM, true), sample(HAM, false)],
+            &Params::default(),
+        );
+        assert_eq!(
+            eval.overall,
+            Confusion {
+                tp: 1,
+                fp: 0,
+                tn: 1,
+                fn_: 0
+            }
+        );
+        assert_eq!(eval.outcomes[0].verdict, Some(Rule::Ngram));
+        assert_eq!(eval.outcomes[1].verdict, None);
+    }
+
+    #[test]
+    fn repeat_hits_dedup_first() {
+        let eval = evaluate(
+            &[sample(SPAM, true), sample(SPAM, true)],
+            &Params::default(),
+        );
+        assert_eq!(eval.outcomes[1].verdict, Some(Rule::Dedup));
+        assert_eq!(eval.outcomes[1].fired, vec![Rule::Dedup, Rule::Ngram]);
+        assert_eq!(eval.per_rule[&Rule::Dedup].tp, 1);
+        assert_eq!(eval.per_rule[&Rule::Ngram].tp, 2);
+    }
+
+    #[test]
+    fn known_samples_are_preseeded() {
+        let text = "a perfectly ordinary sentence that is long enough to be inspected";
+        let eval = evaluate(
+            &[Sample {
+                known: true,
+                ..sample(text, true)
+            }],
+            &Params::default(),
+        );
+        assert_eq!(eval.outcomes[0].verdict, Some(Rule::Dedup));
+    }
+
+    #[test]
+    fn short_samples_are_allowed() {
+        let eval = evaluate(&[sample("быстрый заработок", true)], &Params::default());
+        assert!(eval.outcomes[0].short);
+        assert_eq!(eval.overall.fn_, 1);
+    }
+
+    #[test]
+    fn near_dup_catches_an_edited_copy() {
+        let edited = "Привет всем, предлагаю быстрый заработок без вложений в нашей команде!!";
+        let params = Params {
+            near_dup: Some(0.8),
+            ..Params::default()
+        };
+        let eval = evaluate(&[sample(SPAM, true), sample(edited, true)], &params);
+        assert_eq!(eval.outcomes[1].verdict, Some(Rule::NearDup));
+    }
+
+    #[test]
+    fn threshold_sweep_trades_recall() {
+        let samples = [sample(SPAM, true), sample(HAM, false)];
+        let sweep = sweep_threshold(&samples, &Params::default(), &[1.0, 100.0]);
+        assert_eq!(sweep[0].1.recall(), Some(1.0));
+        assert_eq!(sweep[1].1.recall(), Some(0.0));
+        assert_eq!(sweep[1].1.precision(), None);
+    }
+
+    #[test]
+    fn ablation_reports_matched_phrases() {
+        let stats = phrase_ablation(&[sample(SPAM, true)], &Params::default());
+        let phrase = stats
+            .iter()
+            .find(|s| s.phrase == "быстрый заработок")
+            .expect("phrase matched");
+        assert_eq!((phrase.spam_hits, phrase.ham_hits), (1, 0));
+    }
+
+    #[test]
+    fn f1_of_perfect_run_is_one() {
+        let c = Confusion {
+            tp: 3,
+            fp: 0,
+            tn: 2,
+            fn_: 0,
+        };
+        assert_eq!(c.f1(), Some(1.0));
+    }
+}
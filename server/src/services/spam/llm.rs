@@ -0,0 +1,242 @@
+//! Opt-in LLM step at the end of the cascade (`chat_config.spam_llm_enabled`).
+//!
+//! Only messages the n-gram step found suspicious but not conclusive —
+//! `spam_llm_min_score <= score < spam_threshold` — are sent to the chat's
+//! LLM provider (see [`crate::services::llm`]), which answers with a
+//! structured `{"spam": bool, "confidence": 0..1}` verdict.
+//!
+//! Guard rails:
+//!   * The step can only ever produce `Verdict::Delete`, never a ban, and it
+//!     does **not** record the hash in `spam_messages` — a dedup hit bans,
+//!     so an LLM false positive must not escalate on the next copy.
+//!   * Fail-open: provider errors, timeouts, an unparseable reply or an
+//!     exhausted budget all leave the message alone.
+//!   * Tokens are charged to `daily_stats('openai_tokens_used')` through
+//!     [`daily_stats::try_reserve`], sharing `summary_token_budget` with the
+//!     AI summary.
+//!   * Verdicts are cached by the message's xxh3 hash, so a repeated text
+//!     costs one call per [`CACHE_TTL`] across all chats.
+//!   * The message is run through the summary sanitiser first, so links,
+//!     phones, emails and mentions leave the bot only as placeholders.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use moka::future::Cache;
+use serde::Deserialize;
+use tracing::{debug, warn};
+
+use crate::models::daily_stats::{self, Metric, ReserveOutcome};
+use crate::services::llm::{
+    self, ChatMessage, ChatRequest, ChatRole, LlmProviders, ProviderSettings, estimate_tokens,
+};
+use crate::services::summary_service::sanitize;
+use crate::telemetry::metrics;
+
+/// The reply is a one-line JSON object.
+const MAX_OUTPUT_TOKENS: u32 = 32;
+/// The step runs inline in the update handler; a slow or retrying provider
+/// must not hold the chat's update queue for long.
+const CLASSIFY_TIMEOUT: Duration = Duration::from_secs(10);
+/// Spam bodies are short; anything past this adds tokens, not signal.
+const MAX_INPUT_CHARS: usize = 1500;
+const CACHE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+const CACHE_CAPACITY: u64 = 10_000;
+
+const SYSTEM_PROMPT: &str = "You classify messages from a Telegram group chat as spam or not. \
+    Spam means unsolicited advertising, scams, easy-money or crypto offers, \
+    recruitment into other chats or channels, and adult-content promotion. \
+    Ordinary discussion, questions, jokes and off-topic chatter are not spam. \
+    Placeholders like [link], [phone], [email] and [user] stand for removed details. \
+    Reply with only a JSON object: {\"spam\": true or false, \"confidence\": number from 0 to 1}.";
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct Classification {
+    pub spam: bool,
+    pub confidence: f32,
+}
+
+#[derive(Clone)]
+pub struct LlmClassifier {
+    db: sqlx::PgPool,
+    providers: Arc<LlmProviders>,
+    cache: Cache<i64, Classification>,
+}
+
+impl LlmClassifier {
+    pub fn new(db: sqlx::PgPool, providers: Arc<LlmProviders>) -> Self {
+        let cache = Cache::builder()
+            .max_capacity(CACHE_CAPACITY)
+            .time_to_live(CACHE_TTL)
+            .build();
+        Self {
+            db,
+            providers,
+            cache,
+        }
+    }
+
+    /// Classify `text` (raw message body; `hash` is its normalized xxh3).
+    /// `None` means "no verdict" — the caller allows the message.
+    pub async fn classify(
+        &self,
+        chat_id: i64,
+        hash: i64,
+        text: &str,
+        settings: &ProviderSettings,
+        model: &str,
+        budget: i64,
+    ) -> Option<Classification> {
+        if let Some(hit) = self.cache.get(&hash).await {
+            debug!(?hit, "llm verdict cache hit");
+            metrics::spam_llm_check("cache_hit");
+            return Some(hit);
+        }
+        if settings.kind.requires_api_key() && settings.api_key.is_none() {
+            metrics::spam_llm_check("no_api_key");
+            return None;
+        }
+        let provider = match self.providers.for_chat(settings) {
+            Ok(p) => p,
+            Err(e) => {
+                warn!(error = %e, "llm provider unavailable; skipping llm step");
+                metrics::spam_llm_check("error");
+                return None;
+            }
+        };
+
+        let body: String = sanitize(text).chars().take(MAX_INPUT_CHARS).collect();
+        let request = ChatRequest {
+            model: model.to_owned(),
+            messages: vec![
+                ChatMessage {
+                    role: ChatRole::System,
+                    content: SYSTEM_PROMPT.to_owned(),
+                },
+                ChatMessage {
+                    role: ChatRole::User,
+                    content: body,
+                },
+            ],
+            max_tokens: MAX_OUTPUT_TOKENS,
+        };
+
+        // Reserve prompt estimate + worst-case output up front, reconcile to
+        // the provider's count afterwards — same scheme as the summary.
+        let reserve = i64::from(estimate_tokens(&request.messages, "") + MAX_OUTPUT_TOKENS);
+        match daily_stats::try_reserve(&self.db, chat_id, Metric::OpenaiTokensUsed, reserve, budget)
+            .await
+        {
+            Ok(ReserveOutcome::Reserved { .. }) => {}
+            Ok(ReserveOutcome::Rejected { used }) => {
+                debug!(used, budget, "token budget exhausted; skipping llm step");
+                metrics::spam_llm_check("budget_exhausted");
+                return None;
+            }
+            Err(e) => {
+                warn!(error = ?e, "token reservation failed; skipping llm step");
+                metrics::spam_llm_check("error");
+                return None;
+            }
+        }
+
+        let completion =
+            match tokio::time::timeout(CLASSIFY_TIMEOUT, llm::chat(provider.as_ref(), &request))
+                .await
+            {
+                Ok(Ok(c)) => c,
+                Ok(Err(e)) => {
+                    warn!(error = %format!("{e:#}"), "llm classification failed; allowing");
+                    self.settle(chat_id, -reserve).await;
+                    metrics::spam_llm_check("error");
+                    return None;
+                }
+                Err(_) => {
+                    warn!(timeout = ?CLASSIFY_TIMEOUT, "llm classification timed out; allowing");
+                    self.settle(chat_id, -reserve).await;
+                    metrics::spam_llm_check("error");
+                    return None;
+                }
+            };
+        self.settle(chat_id, i64::from(completion.total_tokens) - reserve)
+            .await;
+
+        let Some(verdict) = parse_verdict(&completion.content) else {
+            warn!(reply = %completion.content.chars().take(200).collect::<String>(), "unparseable llm verdict; allowing");
+            metrics::spam_llm_check("unparsed");
+            return None;
+        };
+        metrics::spam_llm_check("classified");
+        self.cache.insert(hash, verdict).await;
+        Some(verdict)
+    }
+
+    /// Adjust the reservation by `delta` tokens (negative = refund).
+    async fn settle(&self, chat_id: i64, delta: i64) {
+        if delta == 0 {
+            return;
+        }
+        if let Err(e) =
+            daily_stats::increment(&self.db, chat_id, Metric::OpenaiTokensUsed, delta).await
+        {
+            warn!(error = ?e, "failed to reconcile openai_tokens_used");
+        }
+    }
+}
+
+/// Pull the first `{...}` object out of the reply — local models like to
+/// wrap JSON in prose or code fences — and clamp the confidence.
+fn parse_verdict(reply: &str) -> Option<Classification> {
+    let start = reply.find('{')?;
+    let end = reply.rfind('}')?;
+    if end < start {
+        return None;
+    }
+    let mut verdict: Classification = serde_json::from_str(&reply[start..=end]).ok()?;
+    if !verdict.confidence.is_finite() {
+        return None;
+    }
+    verdict.confidence = verdict.confidence.clamp(0.0, 1.0);
+    Some(verdict)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_bare_json() {
+        assert_eq!(
+            parse_verdict(r#"{"spam": true, "confidence": 0.93}"#),
+            Some(Classification {
+                spam: true,
+                confidence: 0.93
+            })
+        );
+    }
+
+    #[test]
+    fn parses_json_wrapped_in_prose() {
+        let reply = "Sure!\n```json\n{\"spam\": false, \"confidence\": 0.7}\n```";
+        assert_eq!(
+            parse_verdict(reply),
+            Some(Classification {
+                spam: false,
+                confidence: 0.7
+            })
+        );
+    }
+
+    #[test]
+    fn clamps_confidence() {
+        let v = parse_verdict(r#"{"spam": true, "confidence": 7}"#).unwrap();
+        assert_eq!(v.confidence, 1.0);
+    }
+
+    #[test]
+    fn rejects_garbage() {
+        assert_eq!(parse_verdict("spam"), None);
+        assert_eq!(parse_verdict(r#"{"verdict": "spam"}"#), None);
+        assert_eq!(parse_verdict("} {"), None);
+    }
+}
@@ -174,6 +174,12 @@ impl SpamWeights {
         Self { overrides }
     }
 
+    /// Same overrides with `phrase` set to `weight`.
+    pub fn with_weight(mut self, phrase: &str, weight: f32) -> Self {
+        self.overrides.insert(phrase.to_owned(), weight);
+        self
+    }
+
     pub fn weight_for(&self, phrase: &str) -> f32 {
         self.overrides
             .get(phrase)
@@ -10,24 +10,34 @@
 //! 4. CAS lookup (when `cas_enabled`). Flagged → `Verdict::Ban` + record
 //!    the message so future copies dedup.
 //! 5. n-gram score = Σ phrase_weight. `score >= threshold` →
-//!    `Verdict::Delete` + record. Otherwise `Verdict::Allow`.
+//!    `Verdict::Delete` + record.
+//! 6. Opt-in LLM step (`spam_llm_enabled`) for `spam_llm_min_score <= score
+//!    < threshold`: a confident spam verdict → `Verdict::Delete` (never a
+//!    ban, never recorded — see [`super::llm`]). Otherwise `Verdict::Allow`.
 //!
 //! `inspect()` does not invoke moderation_service — the caller (handler)
 //! routes the verdict through `ModerationService::apply` so the ledger
 //! write and the bot side-effect stay in one place.
 
+use std::sync::Arc;
+use std::time::Instant;
+
 use anyhow::{Context, Result};
 use chrono::{Duration, Utc};
 use serde_json::json;
 use sqlx::PgPool;
 use teloxide::types::Message;
-use tracing::{debug, instrument};
+use tracing::{debug, instrument, warn};
 use xxhash_rust::xxh3::xxh3_64;
 
+use crate::services::api_keys::ApiKeyCipher;
 use crate::services::cas_client::{CasClient, Verdict as CasVerdict};
+use crate::services::llm::{LlmProviders, ProviderKind, ProviderSettings};
 use crate::services::spam::dedup::{self, DedupOutcome};
+use crate::services::spam::llm::LlmClassifier;
 use crate::services::spam::normalize;
 use crate::services::spam::phrases::{PHRASES, SpamWeights};
+use crate::telemetry::metrics;
 
 /// Min normalized length before dedup/CAS/n-gram apply. Below this we Allow
 /// — short text aliases too easily and bans become indiscriminate.
@@ -38,6 +48,11 @@ pub const MIN_NORMALIZED_LEN: usize = 48;
 /// waiting (or via `/unban`).
 const DEDUP_BAN_DURATION_MIN: i64 = 10;
 
+/// Leading chars of the raw message kept in `reason_json["excerpt"]`, so the
+/// moderation log can quote what was removed after the message is gone. The
+/// `mod_log` job strips it from the ledger once the entry is handled.
+const REASON_EXCERPT_CHARS: usize = 200;
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Verdict {
     Allow,
@@ -57,17 +72,50 @@ impl Verdict {
     pub fn is_action(&self) -> bool {
         !matches!(self, Verdict::Allow)
     }
+
+    /// First entry of `reason_json["matched_rules"]`, or `"none"` for
+    /// `Allow`. Used as the `rule` metric label.
+    fn rule(&self) -> &str {
+        let reason = match self {
+            Verdict::Allow => return "none",
+            Verdict::Delete { reason_json } | Verdict::Ban { reason_json, .. } => reason_json,
+        };
+        reason["matched_rules"][0].as_str().unwrap_or("none")
+    }
+
+    /// `allow`, `delete` or `ban`: the `verdict` metric label and the live
+    /// feed's `spam.verdict`.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Verdict::Allow => "allow",
+            Verdict::Delete { .. } => "delete",
+            Verdict::Ban { .. } => "ban",
+        }
+    }
 }
 
 #[derive(Clone)]
 pub struct SpamService {
     db: PgPool,
     cas: CasClient,
+    llm: LlmClassifier,
+    api_keys: Arc<ApiKeyCipher>,
 }
 
 impl SpamService {
-    pub fn new(db: PgPool, cas: CasClient) -> Self {
-        Self { db, cas }
+    pub fn new(
+        db: PgPool,
+        cas: CasClient,
+        providers: Arc<LlmProviders>,
+        api_keys: Arc<ApiKeyCipher>,
+    ) -> Self {
+        let llm = LlmClassifier::new(db.clone(), providers);
+        Self {
+            db,
+            cas,
+            llm,
+            api_keys,
+        }
     }
 
     #[instrument(
@@ -79,6 +127,17 @@ impl SpamService {
         )
     )]
     pub async fn inspect(&self, msg: &Message) -> Result<Verdict> {
+        let started = Instant::now();
+        let result = self.run_cascade(msg).await;
+        let (verdict, rule) = match &result {
+            Ok(v) => (v.label(), v.rule()),
+            Err(_) => ("error", "none"),
+        };
+        metrics::spam_inspect(msg.chat.id.0, verdict, rule, started.elapsed());
+        result
+    }
+
+    async fn run_cascade(&self, msg: &Message) -> Result<Verdict> {
         let Some(text) = msg.text() else {
             return Ok(Verdict::Allow);
         };
@@ -107,6 +166,7 @@ impl SpamService {
         // pattern round-trips losslessly — what matters is that the same
         // input always maps to the same DB key.
         let hash = xxh3_64(normalized.as_bytes()) as i64;
+        let excerpt: String = text.chars().take(REASON_EXCERPT_CHARS).collect();
 
         // Step 1 — spam_messages dedup.
         if let DedupOutcome::Hit { hit_count } = dedup::lookup(&self.db, hash).await? {
@@ -116,6 +176,7 @@ impl SpamService {
                     "matched_rules": ["xxh3_dedup"],
                     "hash": hash,
                     "hit_count": hit_count + 1,
+                    "excerpt": excerpt,
                 }),
                 until: Some(Utc::now() + Duration::minutes(DEDUP_BAN_DURATION_MIN)),
             });
@@ -130,6 +191,7 @@ impl SpamService {
                     reason_json: json!({
                         "matched_rules": ["cas"],
                         "user_id": user_id,
+                        "excerpt": excerpt,
                     }),
                     until: None,
                 });
@@ -147,13 +209,78 @@ impl SpamService {
                     "ngram_phrases": matched,
                     "score": score,
                     "threshold": cfg.spam_threshold,
+                    "excerpt": excerpt,
                 }),
             });
         }
 
+        // Step 4 — LLM second opinion on the grey zone.
+        if cfg.spam_llm_enabled && score >= cfg.spam_llm_min_score {
+            return Ok(self
+                .llm_step(chat_id, hash, text, &cfg, score, matched, excerpt)
+                .await);
+        }
+
         Ok(Verdict::Allow)
     }
 
+    #[allow(clippy::too_many_arguments)]
+    async fn llm_step(
+        &self,
+        chat_id: i64,
+        hash: i64,
+        text: &str,
+        cfg: &ChatSpamConfig,
+        score: f32,
+        matched: Vec<&'static str>,
+        excerpt: String,
+    ) -> Verdict {
+        let Some(kind) = ProviderKind::from_db_str(&cfg.llm_provider) else {
+            return Verdict::Allow;
+        };
+        let api_key = match cfg
+            .openai_api_key
+            .as_deref()
+            .map(|stored| self.api_keys.open(chat_id, stored))
+            .transpose()
+        {
+            Ok(k) => k,
+            Err(e) => {
+                warn!(error = %format!("{e:#}"), "cannot open openai_api_key; skipping llm step");
+                return Verdict::Allow;
+            }
+        };
+        let settings = ProviderSettings {
+            kind,
+            base_url: cfg.llm_base_url.clone(),
+            api_key,
+        };
+        let budget = i64::from(cfg.summary_token_budget);
+        let Some(c) = self
+            .llm
+            .classify(chat_id, hash, text, &settings, &cfg.openai_model, budget)
+            .await
+        else {
+            return Verdict::Allow;
+        };
+        debug!(spam = c.spam, confidence = c.confidence, "llm verdict");
+        if !c.spam || c.confidence < cfg.spam_llm_min_confidence {
+            return Verdict::Allow;
+        }
+        Verdict::Delete {
+            reason_json: json!({
+                "matched_rules": ["llm"],
+                "ngram_phrases": matched,
+                "score": score,
+                "threshold": cfg.spam_threshold,
+                "llm_provider": kind.as_db_str(),
+                "llm_model": cfg.openai_model,
+                "confidence": c.confidence,
+                "excerpt": excerpt,
+            }),
+        }
+    }
+
     async fn fetch_config(&self, chat_id: i64) -> Result<Option<ChatSpamConfig>> {
         let row = sqlx::query_as!(
             ChatSpamConfig,
@@ -162,7 +289,15 @@ impl SpamService {
                 spam_enabled    AS "spam_enabled!",
                 spam_threshold  AS "spam_threshold!",
                 spam_weights    AS "spam_weights!: serde_json::Value",
-                cas_enabled     AS "cas_enabled!"
+                cas_enabled     AS "cas_enabled!",
+                spam_llm_enabled        AS "spam_llm_enabled!",
+                spam_llm_min_score      AS "spam_llm_min_score!",
+                spam_llm_min_confidence AS "spam_llm_min_confidence!",
+                summary_token_budget    AS "summary_token_budget!",
+                llm_provider,
+                llm_base_url,
+                openai_api_key,
+                openai_model            AS "openai_model!"
             FROM chat_config
             WHERE chat_id = $1
             "#,
@@ -180,4 +315,12 @@ struct ChatSpamConfig {
     spam_threshold: f32,
     spam_weights: serde_json::Value,
     cas_enabled: bool,
+    spam_llm_enabled: bool,
+    spam_llm_min_score: f32,
+    spam_llm_min_confidence: f32,
+    summary_token_budget: i32,
+    llm_provider: String,
+    llm_base_url: Option<String>,
+    openai_api_key: Option<String>,
+    openai_model: String,
 }
@@ -0,0 +1,460 @@
+//! One-shot import of the Dart prototype's SQLite database (M7).
+//!
+//! Two legacy tables carry over; everything else (captcha state, reports,
+//! per-chat settings) is rebuilt by the Rust server on its own.
+//!
+//! | legacy table | columns read | target |
+//! |---|---|---|
+//! | `verified` | `chat_id`, `user_id`, `verified_at` (unix s) | `verified_users` |
+//! | `deleted_message_hash` | `message`, `count`, `update_at` (unix s) | `spam_messages` |
+//!
+//! The prototype's own `hash` column is ignored: it was computed over a
+//! differently normalised body, so it would never match a lookup here. Each
+//! sample is re-keyed through [`normalize::normalize`] + xxh3-64 exactly like
+//! `SpamService::inspect` step 1; legacy rows that collapse onto one key are
+//! merged (summed `count`, latest `update_at`).
+//!
+//! Rows are dropped, and counted in [`Skipped`], when they could never be
+//! used: verifications for chats outside `CONFIG_CHATS`, samples shorter than
+//! the pipeline's dedup minimum, and samples already past
+//! `CONFIG_SPAM_RETENTION_DAYS` (the cleanup job would prune them on its next
+//! pass). Writes are `ON CONFLICT DO NOTHING` in one transaction, so a re-run
+//! only counts everything as already present.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, TimeDelta, Utc};
+use rusqlite::{Connection, OpenFlags};
+use sqlx::PgPool;
+use xxhash_rust::xxh3::xxh3_64;
+
+use crate::services::spam::dedup::SAMPLE_BODY_MAX;
+use crate::services::spam::normalize;
+use crate::services::spam::service::MIN_NORMALIZED_LEN;
+
+/// `verified` row as the prototype stored it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LegacyVerified {
+    pub chat_id: i64,
+    pub user_id: i64,
+    pub verified_at: i64,
+}
+
+/// `deleted_message_hash` row as the prototype stored it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LegacySpam {
+    pub message: Option<String>,
+    pub count: i64,
+    pub update_at: i64,
+}
+
+#[derive(Debug, Default)]
+pub struct LegacyData {
+    pub verified: Vec<LegacyVerified>,
+    pub spam: Vec<LegacySpam>,
+}
+
+/// Read both legacy tables, oldest spam first so a merged key keeps its
+/// first-seen sample. The file is opened read-only; a missing table is
+/// an error rather than an empty import, so a wrong path can't look like a
+/// successful no-op.
+pub fn read(path: &Path) -> Result<LegacyData> {
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .with_context(|| format!("open {}", path.display()))?;
+
+    let mut stmt = conn
+        .prepare("SELECT chat_id, user_id, verified_at FROM verified ORDER BY chat_id, user_id")
+        .context("prepare SELECT verified")?;
+    let verified = stmt
+        .query_map([], |row| {
+            Ok(LegacyVerified {
+                chat_id: row.get(0)?,
+                user_id: row.get(1)?,
+                verified_at: row.get(2)?,
+            })
+        })
+        .context("SELECT verified")?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("read verified row")?;
+
+    let mut stmt = conn
+        .prepare("SELECT message, count, update_at FROM deleted_message_hash ORDER BY update_at")
+        .context("prepare SELECT deleted_message_hash")?;
+    let spam = stmt
+        .query_map([], |row| {
+            Ok(LegacySpam {
+                message: row.get(0)?,
+                count: row.get::<_, Option<i64>>(1)?.unwrap_or(1),
+                update_at: row.get(2)?,
+            })
+        })
+        .context("SELECT deleted_message_hash")?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("read deleted_message_hash row")?;
+
+    Ok(LegacyData { verified, spam })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerifiedRow {
+    pub chat_id: i64,
+    pub user_id: i64,
+    pub verified_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpamRow {
+    pub xxh3_hash: i64,
+    pub sample_body: String,
+    pub hit_count: i64,
+    pub last_seen: DateTime<Utc>,
+}
+
+/// Legacy rows left out of the import, by reason.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Skipped {
+    /// `verified` rows for a chat outside `CONFIG_CHATS`.
+    pub unwatched_chat: usize,
+    /// Duplicate `(chat_id, user_id)` in `verified`.
+    pub duplicate_verified: usize,
+    /// Samples that are NULL or normalise below the dedup minimum.
+    pub short_sample: usize,
+    /// Samples whose `update_at` is past the spam retention window.
+    pub expired: usize,
+    /// Samples merged into another row with the same xxh3 key.
+    pub merged: usize,
+}
+
+/// What the import would write, before touching Postgres.
+#[derive(Debug, Default)]
+pub struct ImportPlan {
+    pub verified: Vec<VerifiedRow>,
+    pub spam: Vec<SpamRow>,
+    pub skipped: Skipped,
+}
+
+/// Map legacy rows onto the Rust schema. Pure — `now` and the config are
+/// passed in so tests can pin them.
+pub fn plan(
+    data: &LegacyData,
+    watched: &[i64],
+    retention_days: u32,
+    now: DateTime<Utc>,
+) -> ImportPlan {
+    let watched: HashSet<i64> = watched.iter().copied().collect();
+    let mut skipped = Skipped::default();
+
+    let mut seen = HashSet::new();
+    let mut verified = Vec::new();
+    for row in &data.verified {
+        if !watched.contains(&row.chat_id) {
+            skipped.unwatched_chat += 1;
+            continue;
+        }
+        if !seen.insert((row.chat_id, row.user_id)) {
+            skipped.duplicate_verified += 1;
+            continue;
+        }
+        verified.push(VerifiedRow {
+            chat_id: row.chat_id,
+            user_id: row.user_id,
+            verified_at: unix_or_now(row.verified_at, now),
+        });
+    }
+
+    let cutoff = now - TimeDelta::days(i64::from(retention_days));
+    let mut by_hash: HashMap<i64, SpamRow> = HashMap::new();
+    let mut order = Vec::new();
+    for row in &data.spam {
+        let Some(message) = row.message.as_deref() else {
+            skipped.short_sample += 1;
+            continue;
+        };
+        let normalized = normalize::normalize(message);
+        if normalized.chars().count() < MIN_NORMALIZED_LEN {
+            skipped.short_sample += 1;
+            continue;
+        }
+        let last_seen = unix_or_now(row.update_at, now);
+        if last_seen < cutoff {
+            skipped.expired += 1;
+            continue;
+        }
+        // Same cast as `SpamService::inspect`: the u64 bit pattern is the key.
+        let hash = xxh3_64(normalized.as_bytes()) as i64;
+        let hit_count = row.count.max(1);
+        match by_hash.get_mut(&hash) {
+            Some(existing) => {
+                skipped.merged += 1;
+                existing.hit_count = existing.hit_count.saturating_add(hit_count);
+                existing.last_seen = existing.last_seen.max(last_seen);
+            }
+            None => {
+                order.push(hash);
+                by_hash.insert(
+                    hash,
+                    SpamRow {
+                        xxh3_hash: hash,
+                        sample_body: truncate(message, SAMPLE_BODY_MAX).to_string(),
+                        hit_count,
+                        last_seen,
+                    },
+                );
+            }
+        }
+    }
+    let spam = order
+        .into_iter()
+        .filter_map(|h| by_hash.remove(&h))
+        .collect();
+
+    ImportPlan {
+        verified,
+        spam,
+        skipped,
+    }
+}
+
+/// Per-table outcome of [`apply`]. With `dry_run`, `inserted` is what a real
+/// run would insert.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TableReport {
+    pub inserted: u64,
+    pub existing: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub verified_users: TableReport,
+    pub spam_messages: TableReport,
+    pub skipped: Skipped,
+}
+
+impl fmt::Display for ImportReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let verb = if self.dry_run {
+            "would insert"
+        } else {
+            "inserted"
+        };
+        let s = self.skipped;
+        writeln!(
+            f,
+            "verified_users: {verb} {}, already present {}, skipped {} (unwatched chat {}, duplicate {})",
+            self.verified_users.inserted,
+            self.verified_users.existing,
+            s.unwatched_chat + s.duplicate_verified,
+            s.unwatched_chat,
+            s.duplicate_verified,
+        )?;
+        write!(
+            f,
+            "spam_messages: {verb} {}, already present {}, skipped {} (short {}, expired {}, merged {})",
+            self.spam_messages.inserted,
+            self.spam_messages.existing,
+            s.short_sample + s.expired + s.merged,
+            s.short_sample,
+            s.expired,
+            s.merged,
+        )
+    }
+}
+
+/// Write `plan` in one transaction, or with `dry_run` only count which rows
+/// already exist. Existing rows are never touched.
+pub async fn apply(pool: &PgPool, plan: &ImportPlan, dry_run: bool) -> Result<ImportReport> {
+    let chat_ids: Vec<i64> = plan.verified.iter().map(|r| r.chat_id).collect();
+    let user_ids: Vec<i64> = plan.verified.iter().map(|r| r.user_id).collect();
+    let verified_at: Vec<DateTime<Utc>> = plan.verified.iter().map(|r| r.verified_at).collect();
+    let hashes: Vec<i64> = plan.spam.iter().map(|r| r.xxh3_hash).collect();
+    let samples: Vec<String> = plan.spam.iter().map(|r| r.sample_body.clone()).collect();
+    let hit_counts: Vec<i64> = plan.spam.iter().map(|r| r.hit_count).collect();
+    let last_seen: Vec<DateTime<Utc>> = plan.spam.iter().map(|r| r.last_seen).collect();
+
+    let mut tx = pool.begin().await.context("BEGIN sqlite import")?;
+    let (verified_inserted, spam_inserted) = if dry_run {
+        let verified_existing = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) AS "count!"
+            FROM verified_users v
+            JOIN UNNEST($1::bigint[], $2::bigint[]) AS t(chat_id, user_id)
+              USING (chat_id, user_id)
+            "#,
+            &chat_ids,
+            &user_ids,
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .context("COUNT existing verified_users")?;
+        let spam_existing = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) AS "count!" FROM spam_messages WHERE xxh3_hash = ANY($1)"#,
+            &hashes,
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .context("COUNT existing spam_messages")?;
+        (
+            chat_ids.len() as u64 - verified_existing as u64,
+            hashes.len() as u64 - spam_existing as u64,
+        )
+    } else {
+        let verified = sqlx::query!(
+            r#"
+            INSERT INTO verified_users (chat_id, user_id, verified_at)
+            SELECT * FROM UNNEST($1::bigint[], $2::bigint[], $3::timestamptz[])
+            ON CONFLICT (chat_id, user_id) DO NOTHING
+            "#,
+            &chat_ids,
+            &user_ids,
+            &verified_at,
+        )
+        .execute(&mut *tx)
+        .await
+        .context("INSERT verified_users")?;
+        let spam = sqlx::query!(
+            r#"
+            INSERT INTO spam_messages (xxh3_hash, sample_body, hit_count, first_seen, last_seen)
+            SELECT h, s, c, l, l
+            FROM UNNEST($1::bigint[], $2::text[], $3::bigint[], $4::timestamptz[]) AS t(h, s, c, l)
+            ON CONFLICT (xxh3_hash) DO NOTHING
+            "#,
+            &hashes,
+            &samples,
+            &hit_counts,
+            &last_seen,
+        )
+        .execute(&mut *tx)
+        .await
+        .context("INSERT spam_messages")?;
+        (verified.rows_affected(), spam.rows_affected())
+    };
+    tx.commit().await.context("COMMIT sqlite import")?;
+
+    Ok(ImportReport {
+        dry_run,
+        verified_users: TableReport {
+            inserted: verified_inserted,
+            existing: chat_ids.len() as u64 - verified_inserted,
+        },
+        spam_messages: TableReport {
+            inserted: spam_inserted,
+            existing: hashes.len() as u64 - spam_inserted,
+        },
+        skipped: plan.skipped,
+    })
+}
+
+fn unix_or_now(secs: i64, now: DateTime<Utc>) -> DateTime<Utc> {
+    DateTime::from_timestamp(secs, 0).unwrap_or(now)
+}
+
+fn truncate(s: &str, max: usize) -> &str {
+    if s.len() <= max {
+        return s;
+    }
+    let mut end = max;
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    &s[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPAM: &str =
+        "Earn $500 a day from home, message me for the details of this amazing offer";
+
+    fn now() -> DateTime<Utc> {
+        DateTime::from_timestamp(1_780_000_000, 0).unwrap()
+    }
+
+    fn spam(message: &str, count: i64, days_ago: i64) -> LegacySpam {
+        LegacySpam {
+            message: Some(message.into()),
+            count,
+            update_at: (now() - TimeDelta::days(days_ago)).timestamp(),
+        }
+    }
+
+    #[test]
+    fn verified_keeps_watched_chats_once() {
+        let data = LegacyData {
+            verified: vec![
+                LegacyVerified {
+                    chat_id: -100,
+                    user_id: 1,
+                    verified_at: 1_700_000_000,
+                },
+                LegacyVerified {
+                    chat_id: -100,
+                    user_id: 1,
+                    verified_at: 1_700_000_500,
+                },
+                LegacyVerified {
+                    chat_id: -200,
+                    user_id: 2,
+                    verified_at: 1_700_000_000,
+                },
+            ],
+            spam: vec![],
+        };
+        let plan = plan(&data, &[-100], 14, now());
+        assert_eq!(plan.verified.len(), 1);
+        assert_eq!(plan.verified[0].verified_at.timestamp(), 1_700_000_000);
+        assert_eq!(plan.skipped.unwatched_chat, 1);
+        assert_eq!(plan.skipped.duplicate_verified, 1);
+    }
+
+    #[test]
+    fn spam_is_rekeyed_like_the_pipeline() {
+        let data = LegacyData {
+            verified: vec![],
+            spam: vec![spam(SPAM, 3, 1)],
+        };
+        let plan = plan(&data, &[], 14, now());
+        let expected = xxh3_64(normalize::normalize(SPAM).as_bytes()) as i64;
+        assert_eq!(plan.spam.len(), 1);
+        assert_eq!(plan.spam[0].xxh3_hash, expected);
+        assert_eq!(plan.spam[0].hit_count, 3);
+        assert_eq!(plan.spam[0].sample_body, SPAM);
+    }
+
+    #[test]
+    fn spam_variants_merge_onto_one_key() {
+        let data = LegacyData {
+            verified: vec![],
+            spam: vec![spam(SPAM, 2, 5), spam(&SPAM.to_uppercase(), 4, 1)],
+        };
+        let plan = plan(&data, &[], 14, now());
+        assert_eq!(plan.spam.len(), 1);
+        assert_eq!(plan.spam[0].hit_count, 6);
+        assert_eq!(plan.spam[0].last_seen, now() - TimeDelta::days(1));
+        assert_eq!(plan.skipped.merged, 1);
+    }
+
+    #[test]
+    fn spam_skips_short_and_expired() {
+        let data = LegacyData {
+            verified: vec![],
+            spam: vec![
+                spam("buy now", 1, 1),
+                spam(SPAM, 1, 30),
+                LegacySpam {
+                    message: None,
+                    count: 1,
+                    update_at: now().timestamp(),
+                },
+            ],
+        };
+        let plan = plan(&data, &[], 14, now());
+        assert!(plan.spam.is_empty());
+        assert_eq!(plan.skipped.short_sample, 2);
+        assert_eq!(plan.skipped.expired, 1);
+    }
+}
@@ -1,17 +1,30 @@
-//! AI summary of recent chat activity. Per-chat: each chat has its own
-//! `chat_config.openai_api_key` (or NULL = no summary for this chat) and
+//! AI summary of recent chat activity. Per-chat: each chat picks its LLM
+//! backend (`chat_config.llm_provider` + `llm_base_url`, see
+//! [`crate::services::llm`]), its own `chat_config.openai_api_key` (required
+//! for hosted OpenAI only; NULL there = no summary for this chat; stored
+//! encrypted and opened here, see [`crate::services::api_keys`]) and
 //! `chat_config.openai_model`. Token usage accumulates per chat-day in
-//! `daily_stats('openai_tokens_used')` and is hard-capped at
-//! `chat_config.summary_token_budget`.
+//! `daily_stats('openai_tokens_used')` — whatever the provider — and is
+//! hard-capped at `chat_config.summary_token_budget`.
+//!
+//! Summaries are map-reduce over whole UTC hours. The requested window is
+//! widened to hour boundaries; each hour with messages is summarised on its
+//! own (map), and the hourly summaries are combined into the final bullet
+//! list (reduce, [`REDUCE_FANOUT`] at a time). Finished hours are cached in
+//! `summary_chunks` per language and model together with the tokens their
+//! call used, so a repeated `/summary`, `/report` and the daily report only
+//! pay for the hours nobody has summarised yet plus the combine call. The
+//! current, still-open hour is never cached. A window with a single active
+//! hour skips the reduce call.
 //!
 //! Inputs come from `allowed_messages` (filled by the spam pipeline when
 //! `chat_config.log_allowed_messages = TRUE`). With message logging off,
-//! the service has nothing to send to OpenAI and returns
+//! the service has nothing to send to the model and returns
 //! [`SummaryOutcome::Skipped`] with [`SkipReason::NoMessages`] — that's a
 //! fail-quiet design choice: a moderator who hasn't enabled logging
 //! shouldn't be surprised by an extra "logging is off" reminder every day.
 //!
-//! Sanitisation runs on every message body before we hit OpenAI:
+//! Sanitisation runs on every message body before it leaves the bot:
 //!   * URLs collapse to `[link]`.
 //!   * Phone-like sequences (≥7 digits, optional leading `+`) → `[phone]`.
 //!   * Emails → `[email]`.
@@ -19,19 +32,24 @@
 //!
 //! See `server/docs/reports.md` for the moderator-facing contract.
 
+use std::collections::BTreeMap;
 use std::sync::{Arc, LazyLock};
 
 use anyhow::{Context, Result};
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, TimeDelta, Utc};
 use regex::Regex;
 use sqlx::PgPool;
 use tracing::{debug, info, instrument, warn};
 
 use crate::models::daily_stats::{self, Metric, ReserveOutcome};
-use crate::services::openai_client::{ChatMessage, ChatRole, OpenAiClient};
-
-/// Hard limit on how many `allowed_messages` rows to feed into one summary
-/// request. Roughly equivalent to ~20 KB of text — well inside the
+use crate::services::api_keys::ApiKeyCipher;
+use crate::services::llm::{
+    self, ChatCompletion, ChatMessage, ChatRequest, ChatRole, LlmProvider, LlmProviders,
+    ProviderKind, ProviderSettings,
+};
+
+/// Hard limit on how many `allowed_messages` rows to feed into one hourly
+/// chunk request. Roughly equivalent to ~20 KB of text — well inside the
 /// 128k-token context of `gpt-4o-mini` even before any sanitisation.
 const MAX_MESSAGES: usize = 2000;
 
@@ -39,19 +57,35 @@ const MAX_MESSAGES: usize = 2000;
 /// a treatise. Caps the per-call token usage tightly.
 const MAX_OUTPUT_TOKENS: u32 = 500;
 
+/// Width of one map chunk, in seconds.
+const CHUNK_SECS: i64 = 60 * 60;
+
+/// How many summaries one reduce call combines. A day of hourly chunks fits
+/// in one call; longer windows reduce in rounds.
+const REDUCE_FANOUT: usize = 24;
+
 /// Min content length per message — single-character "+1" / "ok" replies
 /// add nothing to a summary and just drag the prompt budget down.
 const MIN_MESSAGE_CHARS: usize = 4;
 
 #[derive(Debug, Clone)]
 pub enum SummaryOutcome {
-    Generated { text: String, tokens_used: u32 },
-    Skipped { reason: SkipReason },
+    /// `tokens_used` is what this call spent; hours served from
+    /// `summary_chunks` cost nothing, so a fully cached single-hour window
+    /// reports 0.
+    Generated {
+        text: String,
+        tokens_used: u32,
+    },
+    Skipped {
+        reason: SkipReason,
+    },
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SkipReason {
-    /// `chat_config.openai_api_key` is NULL.
+    /// `chat_config.openai_api_key` is NULL and the provider is hosted
+    /// OpenAI.
     NoApiKey,
     /// `chat_config.summary_enabled = FALSE`.
     Disabled,
@@ -64,19 +98,24 @@ pub enum SkipReason {
 #[derive(Clone)]
 pub struct SummaryService {
     db: PgPool,
-    client: Arc<OpenAiClient>,
+    providers: Arc<LlmProviders>,
+    api_keys: Arc<ApiKeyCipher>,
 }
 
 impl SummaryService {
-    pub fn new(db: PgPool, client: Arc<OpenAiClient>) -> Arc<Self> {
-        Arc::new(Self { db, client })
+    pub fn new(db: PgPool, providers: Arc<LlmProviders>, api_keys: Arc<ApiKeyCipher>) -> Arc<Self> {
+        Arc::new(Self {
+            db,
+            providers,
+            api_keys,
+        })
     }
 
-    /// Resolve the per-chat config + budget, build a sanitised prompt, call
-    /// OpenAI, and (on success) increment the per-day token counter. The
-    /// caller decides what to do with `Generated` / `Skipped`; the renderer
-    /// uses the `text` directly, the `/summary` command formats the skip
-    /// reason for the user.
+    /// Resolve the per-chat config + budget, summarise every uncached hour of
+    /// the window with the chat's LLM provider, combine the hourly summaries,
+    /// and charge each call to the per-day token counter. The caller decides
+    /// what to do with `Generated` / `Skipped`; the renderer uses the `text`
+    /// directly, the `/summary` command formats the skip reason for the user.
     #[instrument(skip(self), fields(chat_id))]
     pub async fn summarize(
         &self,
@@ -99,16 +138,28 @@ impl SummaryService {
                 reason: SkipReason::Disabled,
             });
         }
-        let Some(api_key) = cfg.openai_api_key.as_ref() else {
+        let kind = ProviderKind::from_db_str(&cfg.llm_provider)
+            .with_context(|| format!("unknown llm_provider {:?}", cfg.llm_provider))?;
+        if kind.requires_api_key() && cfg.openai_api_key.is_none() {
             return Ok(SummaryOutcome::Skipped {
                 reason: SkipReason::NoApiKey,
             });
-        };
+        }
+        let api_key = cfg
+            .openai_api_key
+            .as_deref()
+            .map(|stored| self.api_keys.open(chat_id, stored))
+            .transpose()?;
+        let provider = self.providers.for_chat(&ProviderSettings {
+            kind,
+            base_url: cfg.llm_base_url.clone(),
+            api_key,
+        })?;
 
         // If allowed-message logging is disabled, the chat has not opted in
         // to having its raw text leave the bot. Don't reach for stale rows
         // captured before the moderator turned the flag off — short-circuit
-        // before either reading allowed_messages or hitting OpenAI.
+        // before either reading allowed_messages or calling the model.
         if !cfg.log_allowed_messages {
             return Ok(SummaryOutcome::Skipped {
                 reason: SkipReason::NoMessages,
@@ -117,8 +168,8 @@ impl SummaryService {
 
         // Cheap pre-flight: if today's counter already meets/exceeds the
         // configured budget there's nothing to reserve, and we want to
-        // surface BudgetExhausted before we go fetch any messages or hit
-        // OpenAI. The atomic check below is the actual race-safe gate.
+        // surface BudgetExhausted before we go fetch any messages or call
+        // the model. The reservation in `charged_chat` is the race-safe gate.
         let budget = cfg.summary_token_budget as i64;
         let used_today = daily_stats::get(
             &self.db,
@@ -136,77 +187,151 @@ impl SummaryService {
             });
         }
 
-        let messages = self.fetch_messages(chat_id, from, to).await?;
-        if messages.is_empty() {
+        let now = Utc::now();
+        let (from, to) = (hour_floor(from), hour_ceil(to));
+        let model = cfg.openai_model.as_str();
+        let mut chunks = self
+            .fetch_chunks(chat_id, language, model, from, to)
+            .await?;
+        let cached = chunks.len();
+        let pending = self
+            .fetch_messages(chat_id, language, model, from, to)
+            .await?;
+        if chunks.is_empty() && pending.is_empty() {
             return Ok(SummaryOutcome::Skipped {
                 reason: SkipReason::NoMessages,
             });
         }
 
+        debug!(
+            chat_id,
+            provider = kind.as_db_str(),
+            cached,
+            pending = pending.len(),
+            "calling llm for chat summary"
+        );
+
+        // Map: one call per uncached hour. A chunk that was paid for stays
+        // cached even if a later call in this run hits the budget.
+        let mut tokens_used = 0u32;
+        for (hour, messages) in pending {
+            let request =
+                build_request(model, system_prompt(language), build_user_prompt(&messages));
+            let completion = match self
+                .charged_chat(chat_id, provider.as_ref(), &request, budget)
+                .await?
+            {
+                Charged::Done(c) => c,
+                Charged::OverBudget { used } => {
+                    return Ok(SummaryOutcome::Skipped {
+                        reason: SkipReason::BudgetExhausted { used, budget },
+                    });
+                }
+            };
+            tokens_used = tokens_used.saturating_add(completion.total_tokens);
+            if hour + TimeDelta::seconds(CHUNK_SECS) <= now {
+                self.store_chunk(chat_id, hour, language, model, messages.len(), &completion)
+                    .await;
+            }
+            chunks.insert(hour, completion.content);
+        }
+
+        // Reduce: combine in rounds of REDUCE_FANOUT until one summary is left.
+        let mut parts: Vec<Part> = chunks
+            .into_iter()
+            .map(|(hour, text)| Part {
+                first: hour,
+                last: hour,
+                text,
+            })
+            .collect();
+        while parts.len() > 1 {
+            let mut next = Vec::with_capacity(parts.len().div_ceil(REDUCE_FANOUT));
+            for group in parts.chunks(REDUCE_FANOUT) {
+                if let [single] = group {
+                    next.push(single.clone());
+                    continue;
+                }
+                let request =
+                    build_request(model, reduce_prompt(language), build_reduce_prompt(group));
+                let completion = match self
+                    .charged_chat(chat_id, provider.as_ref(), &request, budget)
+                    .await?
+                {
+                    Charged::Done(c) => c,
+                    Charged::OverBudget { used } => {
+                        return Ok(SummaryOutcome::Skipped {
+                            reason: SkipReason::BudgetExhausted { used, budget },
+                        });
+                    }
+                };
+                tokens_used = tokens_used.saturating_add(completion.total_tokens);
+                next.push(Part {
+                    first: group[0].first,
+                    last: group[group.len() - 1].last,
+                    text: completion.content,
+                });
+            }
+            parts = next;
+        }
+        let text = parts.pop().map(|p| p.text).unwrap_or_default();
+
+        info!(chat_id, tokens = tokens_used, cached, "summary generated");
+        Ok(SummaryOutcome::Generated { text, tokens_used })
+    }
+
+    /// One LLM call charged to today's `openai_tokens_used`.
+    async fn charged_chat(
+        &self,
+        chat_id: i64,
+        provider: &dyn LlmProvider,
+        request: &ChatRequest,
+        budget: i64,
+    ) -> Result<Charged> {
         // Pre-charge the worst-case output token count atomically. Two
         // concurrent /summary calls can't both observe the same remaining
         // budget — the second one trips the SQL-side WHERE gate and gets
-        // Rejected. After the OpenAI call returns, we adjust by
+        // Rejected. After the model call returns, we adjust by
         // `actual - reserve` (positive = overshoot, negative = refund).
         let reserve = MAX_OUTPUT_TOKENS as i64;
         match daily_stats::try_reserve(&self.db, chat_id, Metric::OpenaiTokensUsed, reserve, budget)
             .await?
         {
             ReserveOutcome::Reserved { .. } => {}
-            ReserveOutcome::Rejected { used } => {
-                return Ok(SummaryOutcome::Skipped {
-                    reason: SkipReason::BudgetExhausted { used, budget },
-                });
-            }
+            ReserveOutcome::Rejected { used } => return Ok(Charged::OverBudget { used }),
         }
 
-        let prompt = build_user_prompt(&messages);
-        let chat_messages = vec![
-            ChatMessage {
-                role: ChatRole::System,
-                content: system_prompt(language),
-            },
-            ChatMessage {
-                role: ChatRole::User,
-                content: prompt,
-            },
-        ];
-
-        debug!(
-            chat_id,
-            inputs = messages.len(),
-            "calling openai for chat summary"
-        );
-
-        let completion = self
-            .client
-            .chat(api_key, &cfg.openai_model, chat_messages, MAX_OUTPUT_TOKENS)
-            .await
-            .context("openai chat call")?;
+        let completion = match llm::chat(provider, request).await {
+            Ok(completion) => completion,
+            Err(e) => {
+                // The failed call spent nothing; hand the reservation back.
+                self.settle(chat_id, -reserve).await;
+                return Err(e).context("llm chat call");
+            }
+        };
 
         // Reconcile the pre-charge against actual usage. A full-output
         // response with a small prompt may settle below `reserve` (refund);
         // a long-prompt response may push past it (overshoot, but still
         // bounded — the next call's reservation will see the higher counter
         // and reject).
-        let delta = completion.total_tokens as i64 - reserve;
-        if delta != 0 {
-            if let Err(e) =
-                daily_stats::increment(&self.db, chat_id, Metric::OpenaiTokensUsed, delta).await
-            {
-                warn!(error = ?e, "failed to reconcile openai_tokens_used");
-            }
-        }
+        self.settle(chat_id, completion.total_tokens as i64 - reserve)
+            .await;
+        Ok(Charged::Done(completion))
+    }
 
-        info!(
-            chat_id,
-            tokens = completion.total_tokens,
-            "summary generated"
-        );
-        Ok(SummaryOutcome::Generated {
-            text: completion.content,
-            tokens_used: completion.total_tokens,
-        })
+    /// Adjust today's `openai_tokens_used` by `delta`. Best-effort, like the
+    /// spam classifier's `settle`: a lost adjustment only skews today's
+    /// counter.
+    async fn settle(&self, chat_id: i64, delta: i64) {
+        if delta == 0 {
+            return;
+        }
+        if let Err(e) =
+            daily_stats::increment(&self.db, chat_id, Metric::OpenaiTokensUsed, delta).await
+        {
+            warn!(error = ?e, "failed to reconcile openai_tokens_used");
+        }
     }
 
     async fn fetch_config(&self, chat_id: i64) -> Result<Option<SummaryConfig>> {
@@ -218,7 +343,9 @@ impl SummaryService {
                 summary_token_budget  AS "summary_token_budget!",
                 openai_api_key,
                 openai_model          AS "openai_model!",
-                log_allowed_messages  AS "log_allowed_messages!"
+                log_allowed_messages  AS "log_allowed_messages!",
+                llm_provider,
+                llm_base_url
             FROM chat_config
             WHERE chat_id = $1
             "#,
@@ -230,36 +357,141 @@ impl SummaryService {
         Ok(row)
     }
 
-    async fn fetch_messages(
+    async fn fetch_chunks(
         &self,
         chat_id: i64,
+        language: &str,
+        model: &str,
         from: DateTime<Utc>,
         to: DateTime<Utc>,
-    ) -> Result<Vec<String>> {
+    ) -> Result<BTreeMap<DateTime<Utc>, String>> {
         let rows = sqlx::query!(
             r#"
-            SELECT content
-            FROM allowed_messages
-            WHERE chat_id = $1 AND created_at >= $2 AND created_at < $3
-              AND content IS NOT NULL
-            ORDER BY created_at ASC
-            LIMIT $4
+            SELECT hour_start, summary
+            FROM summary_chunks
+            WHERE chat_id = $1 AND language = $2 AND model = $3
+              AND hour_start >= $4 AND hour_start < $5
             "#,
             chat_id,
+            language,
+            model,
             from,
             to,
-            MAX_MESSAGES as i64,
         )
         .fetch_all(&self.db)
         .await
-        .context("SELECT allowed_messages")?;
+        .context("SELECT summary_chunks")?;
         Ok(rows
             .into_iter()
-            .filter_map(|r| r.content)
-            .filter(|c| c.chars().count() >= MIN_MESSAGE_CHARS)
-            .map(|c| sanitize(&c))
+            .map(|r| (r.hour_start, r.summary))
             .collect())
     }
+
+    /// Sanitised messages of every hour in `[from, to)` that has no cached
+    /// chunk, grouped by hour. Hours left empty by the length filter are
+    /// dropped.
+    async fn fetch_messages(
+        &self,
+        chat_id: i64,
+        language: &str,
+        model: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<BTreeMap<DateTime<Utc>, Vec<String>>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT m.created_at, m.content AS "content!"
+            FROM allowed_messages m
+            WHERE m.chat_id = $1 AND m.created_at >= $2 AND m.created_at < $3
+              AND m.content IS NOT NULL
+              AND NOT EXISTS (
+                  SELECT 1 FROM summary_chunks s
+                  WHERE s.chat_id = m.chat_id AND s.language = $4 AND s.model = $5
+                    AND s.hour_start = date_trunc('hour', m.created_at, 'UTC')
+              )
+            ORDER BY m.created_at ASC
+            "#,
+            chat_id,
+            from,
+            to,
+            language,
+            model,
+        )
+        .fetch_all(&self.db)
+        .await
+        .context("SELECT allowed_messages")?;
+        let mut hours: BTreeMap<DateTime<Utc>, Vec<String>> = BTreeMap::new();
+        for row in rows {
+            if row.content.chars().count() < MIN_MESSAGE_CHARS {
+                continue;
+            }
+            let hour = hours.entry(hour_floor(row.created_at)).or_default();
+            if hour.len() < MAX_MESSAGES {
+                hour.push(sanitize(&row.content));
+            }
+        }
+        Ok(hours)
+    }
+
+    /// Cache a finished hour. Best-effort: a failed write only means the
+    /// hour gets summarised again next time.
+    async fn store_chunk(
+        &self,
+        chat_id: i64,
+        hour_start: DateTime<Utc>,
+        language: &str,
+        model: &str,
+        message_count: usize,
+        completion: &ChatCompletion,
+    ) {
+        let res = sqlx::query!(
+            r#"
+            INSERT INTO summary_chunks
+                (chat_id, hour_start, language, model, message_count, summary, tokens_used)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (chat_id, language, model, hour_start) DO NOTHING
+            "#,
+            chat_id,
+            hour_start,
+            language,
+            model,
+            i32::try_from(message_count).unwrap_or(i32::MAX),
+            completion.content,
+            i32::try_from(completion.total_tokens).unwrap_or(i32::MAX),
+        )
+        .execute(&self.db)
+        .await;
+        if let Err(e) = res {
+            warn!(error = ?e, %hour_start, "failed to cache summary chunk");
+        }
+    }
+}
+
+enum Charged {
+    Done(ChatCompletion),
+    OverBudget { used: i64 },
+}
+
+/// One summary being reduced, covering the hours `first..=last`.
+#[derive(Debug, Clone)]
+struct Part {
+    first: DateTime<Utc>,
+    last: DateTime<Utc>,
+    text: String,
+}
+
+fn hour_floor(t: DateTime<Utc>) -> DateTime<Utc> {
+    let secs = t.timestamp();
+    DateTime::from_timestamp(secs - secs.rem_euclid(CHUNK_SECS), 0).unwrap_or(t)
+}
+
+fn hour_ceil(t: DateTime<Utc>) -> DateTime<Utc> {
+    let floor = hour_floor(t);
+    if floor == t {
+        t
+    } else {
+        floor + TimeDelta::seconds(CHUNK_SECS)
+    }
 }
 
 #[derive(Debug)]
@@ -269,11 +501,31 @@ struct SummaryConfig {
     openai_api_key: Option<String>,
     openai_model: String,
     log_allowed_messages: bool,
+    llm_provider: String,
+    llm_base_url: Option<String>,
+}
+
+fn build_request(model: &str, system: String, user: String) -> ChatRequest {
+    ChatRequest {
+        model: model.to_string(),
+        messages: vec![
+            ChatMessage {
+                role: ChatRole::System,
+                content: system,
+            },
+            ChatMessage {
+                role: ChatRole::User,
+                content: user,
+            },
+        ],
+        max_tokens: MAX_OUTPUT_TOKENS,
+    }
 }
 
 fn system_prompt(language: &str) -> String {
     let lang = match language {
         "en" => "English",
+        "uk" => "Ukrainian",
         _ => "Russian",
     };
     format!(
@@ -284,6 +536,36 @@ fn system_prompt(language: &str) -> String {
     )
 }
 
+fn reduce_prompt(language: &str) -> String {
+    let lang = match language {
+        "en" => "English",
+        "uk" => "Ukrainian",
+        _ => "Russian",
+    };
+    format!(
+        "You are combining summaries of consecutive periods of one Telegram \
+         chat into a single summary. Output 3-5 short bullet points in \
+         {lang} covering the whole span. Merge repeated topics, focus on \
+         topics and decisions, NOT individuals. Do not invent facts."
+    )
+}
+
+fn build_reduce_prompt(parts: &[Part]) -> String {
+    let mut s = String::new();
+    for p in parts {
+        let first = p.first.format("%Y-%m-%d %H:00");
+        if p.first == p.last {
+            s.push_str(&format!("## {first} UTC\n"));
+        } else {
+            let last = (p.last + TimeDelta::seconds(CHUNK_SECS)).format("%Y-%m-%d %H:00");
+            s.push_str(&format!("## {first} – {last} UTC\n"));
+        }
+        s.push_str(p.text.trim());
+        s.push_str("\n\n");
+    }
+    s
+}
+
 fn build_user_prompt(messages: &[String]) -> String {
     let mut s = String::with_capacity(messages.iter().map(|m| m.len() + 2).sum());
     for m in messages {
@@ -356,10 +638,45 @@ mod tests {
         assert_eq!(p, "- a\n- b\n");
     }
 
+    #[test]
+    fn hours_round_to_utc_boundaries() {
+        let t = DateTime::parse_from_rfc3339("2026-05-01T17:42:10+05:30")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(hour_floor(t).to_rfc3339(), "2026-05-01T12:00:00+00:00");
+        assert_eq!(hour_ceil(t).to_rfc3339(), "2026-05-01T13:00:00+00:00");
+        let aligned = hour_floor(t);
+        assert_eq!(hour_ceil(aligned), aligned);
+    }
+
+    #[test]
+    fn reduce_prompt_labels_each_span() {
+        let h = DateTime::parse_from_rfc3339("2026-05-01T10:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let parts = [
+            Part {
+                first: h,
+                last: h,
+                text: "- a\n".into(),
+            },
+            Part {
+                first: h + TimeDelta::hours(1),
+                last: h + TimeDelta::hours(3),
+                text: "- b".into(),
+            },
+        ];
+        assert_eq!(
+            build_reduce_prompt(&parts),
+            "## 2026-05-01 10:00 UTC\n- a\n\n## 2026-05-01 11:00 – 2026-05-01 14:00 UTC\n- b\n\n"
+        );
+    }
+
     #[test]
     fn system_prompt_picks_language() {
         assert!(system_prompt("ru").contains("Russian"));
         assert!(system_prompt("en").contains("English"));
+        assert!(system_prompt("uk").contains("Ukrainian"));
         assert!(system_prompt("fr").contains("Russian"));
     }
 }
@@ -0,0 +1,104 @@
+//! Cached Telegram profiles (`user_info_cache`) for the dashboard's verified
+//! and banned users lists, which otherwise only know user ids.
+//!
+//! The message gate and join handler call [`UserInfoCache::observe`] with
+//! every sender / new member. A Moka map of the profile this process last
+//! wrote keeps that to one upsert per user per [`WRITTEN_TTL`] unless the
+//! profile changes, so a busy chat doesn't turn every message into a write.
+//! Readers join `user_info_cache` in SQL; [`UserInfoCache::get`] is for
+//! single-user responses.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use moka::future::Cache;
+use serde::Serialize;
+use sqlx::PgPool;
+use teloxide::types::User;
+use utoipa::ToSchema;
+
+/// How long a written profile suppresses identical upserts. Bounds how
+/// stale `updated_at` gets for an active user.
+pub const WRITTEN_TTL: Duration = Duration::from_secs(60 * 60);
+const WRITTEN_CAPACITY: u64 = 100_000;
+
+/// What Telegram last showed us of a user.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct UserProfile {
+    /// Without the leading `@`.
+    pub username: Option<String>,
+    pub first_name: String,
+    pub last_name: Option<String>,
+}
+
+impl From<&User> for UserProfile {
+    fn from(user: &User) -> Self {
+        Self {
+            username: user.username.clone(),
+            first_name: user.first_name.clone(),
+            last_name: user.last_name.clone(),
+        }
+    }
+}
+
+pub struct UserInfoCache {
+    db: PgPool,
+    written: Cache<i64, UserProfile>,
+}
+
+impl UserInfoCache {
+    pub fn new(db: PgPool) -> Arc<Self> {
+        Arc::new(Self {
+            db,
+            written: Cache::builder()
+                .max_capacity(WRITTEN_CAPACITY)
+                .time_to_live(WRITTEN_TTL)
+                .build(),
+        })
+    }
+
+    /// Record `user`'s current profile. Bots are skipped.
+    pub async fn observe(&self, user: &User) -> Result<()> {
+        if user.is_bot {
+            return Ok(());
+        }
+        let user_id = user.id.0 as i64;
+        let profile = UserProfile::from(user);
+        if self.written.get(&user_id).await.as_ref() == Some(&profile) {
+            return Ok(());
+        }
+        sqlx::query!(
+            r#"
+            INSERT INTO user_info_cache (user_id, username, first_name, last_name)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (user_id) DO UPDATE
+            SET username = EXCLUDED.username,
+                first_name = EXCLUDED.first_name,
+                last_name = EXCLUDED.last_name,
+                updated_at = NOW()
+            "#,
+            user_id,
+            profile.username,
+            profile.first_name,
+            profile.last_name,
+        )
+        .execute(&self.db)
+        .await
+        .context("UPSERT user_info_cache")?;
+        self.written.insert(user_id, profile).await;
+        Ok(())
+    }
+
+    /// The cached profile, `None` when the user was never observed.
+    pub async fn get(&self, user_id: i64) -> Result<Option<UserProfile>> {
+        sqlx::query_as!(
+            UserProfile,
+            r#"SELECT username, first_name, last_name FROM user_info_cache WHERE user_id = $1"#,
+            user_id,
+        )
+        .fetch_optional(&self.db)
+        .await
+        .context("SELECT user_info_cache")
+    }
+}
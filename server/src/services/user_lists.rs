@@ -0,0 +1,149 @@
+//! The dashboard's per-chat verified and banned users lists, with the
+//! usernames from `user_info_cache` joined in.
+//!
+//! Verified means a `verified_users` row. Banned has no table of its own: a
+//! user is banned when their latest `ban` / `unban` ledger row in the chat
+//! is a `ban` — the same rule `ModerationService::apply` and appeals use.
+//! Both lists are newest first with keyset cursors on `(timestamp,
+//! user_id)`, and take one search string `q` that matches the user id
+//! exactly or the username / name as a case-insensitive substring.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct VerifiedUserItem {
+    pub user_id: i64,
+    /// From `user_info_cache`; `None` for users never seen since it exists.
+    pub username: Option<String>,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    pub verified_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct BannedUserItem {
+    pub user_id: i64,
+    /// From `user_info_cache`; `None` for users never seen since it exists.
+    pub username: Option<String>,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    /// When the standing ban was applied.
+    pub banned_at: DateTime<Utc>,
+    /// The `ban` ledger row.
+    pub action_id: Uuid,
+    /// `bot` or `moderator`.
+    pub actor_kind: String,
+    pub actor_user_id: Option<i64>,
+    /// Free text, or the spam verdict JSON for bot bans.
+    pub reason: Option<String>,
+}
+
+/// Sort key of the last row a client has seen: `verified_at` or
+/// `banned_at`, then the user id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UserCursor {
+    pub at: DateTime<Utc>,
+    pub user_id: i64,
+}
+
+impl VerifiedUserItem {
+    pub fn cursor(&self) -> UserCursor {
+        UserCursor {
+            at: self.verified_at,
+            user_id: self.user_id,
+        }
+    }
+}
+
+impl BannedUserItem {
+    pub fn cursor(&self) -> UserCursor {
+        UserCursor {
+            at: self.banned_at,
+            user_id: self.user_id,
+        }
+    }
+}
+
+/// Up to `limit` verified users of `chat_id`, most recently verified first.
+pub async fn verified(
+    pool: &PgPool,
+    chat_id: i64,
+    q: Option<&str>,
+    after: Option<UserCursor>,
+    limit: i64,
+) -> Result<Vec<VerifiedUserItem>> {
+    sqlx::query_as!(
+        VerifiedUserItem,
+        r#"
+        SELECT v.user_id, u.username, u.first_name AS "first_name?", u.last_name,
+               v.verified_at
+        FROM verified_users v
+        LEFT JOIN user_info_cache u ON u.user_id = v.user_id
+        WHERE v.chat_id = $1
+          AND ($2::TEXT IS NULL
+               OR v.user_id::TEXT = $2
+               OR strpos(lower(u.username), lower(ltrim($2, '@'))) > 0
+               OR strpos(lower(concat_ws(' ', u.first_name, u.last_name)), lower($2)) > 0)
+          AND ($3::TIMESTAMPTZ IS NULL OR (v.verified_at, v.user_id) < ($3, $4::BIGINT))
+        ORDER BY v.verified_at DESC, v.user_id DESC
+        LIMIT $5
+        "#,
+        chat_id,
+        q,
+        after.map(|c| c.at),
+        after.map(|c| c.user_id),
+        limit,
+    )
+    .fetch_all(pool)
+    .await
+    .context("SELECT verified users page")
+}
+
+/// Up to `limit` currently banned users of `chat_id`, most recently banned
+/// first.
+pub async fn banned(
+    pool: &PgPool,
+    chat_id: i64,
+    q: Option<&str>,
+    after: Option<UserCursor>,
+    limit: i64,
+) -> Result<Vec<BannedUserItem>> {
+    sqlx::query_as!(
+        BannedUserItem,
+        r#"
+        WITH latest AS (
+            SELECT DISTINCT ON (target_user_id)
+                   id, target_user_id, action, actor_kind, actor_user_id, reason, created_at
+            FROM moderation_actions
+            WHERE chat_id = $1 AND action IN ('ban', 'unban')
+            ORDER BY target_user_id, created_at DESC
+        )
+        SELECT l.target_user_id AS "user_id!", u.username, u.first_name AS "first_name?",
+               u.last_name, l.created_at AS "banned_at!", l.id AS "action_id!",
+               l.actor_kind AS "actor_kind!", l.actor_user_id, l.reason
+        FROM latest l
+        LEFT JOIN user_info_cache u ON u.user_id = l.target_user_id
+        WHERE l.action = 'ban'
+          AND ($2::TEXT IS NULL
+               OR l.target_user_id::TEXT = $2
+               OR strpos(lower(u.username), lower(ltrim($2, '@'))) > 0
+               OR strpos(lower(concat_ws(' ', u.first_name, u.last_name)), lower($2)) > 0)
+          AND ($3::TIMESTAMPTZ IS NULL OR (l.created_at, l.target_user_id) < ($3, $4::BIGINT))
+        ORDER BY l.created_at DESC, l.target_user_id DESC
+        LIMIT $5
+        "#,
+        chat_id,
+        q,
+        after.map(|c| c.at),
+        after.map(|c| c.user_id),
+        limit,
+    )
+    .fetch_all(pool)
+    .await
+    .context("SELECT banned users page")
+}
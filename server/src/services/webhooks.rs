@@ -0,0 +1,753 @@
+//! Outbound webhooks over a transactional outbox.
+//!
+//! Writers call [`record`] with the same transaction as the ledger row the
+//! event describes (`ModerationService::apply`, `CaptchaService::solve`,
+//! `verify_manual`), so `outbox_events` holds an event iff the action
+//! committed. The `webhooks` job then:
+//!
+//!   1. [`fan_out`] — copies pending events into one `webhook_deliveries`
+//!      row per endpoint of the event's chat, and stamps the event;
+//!   2. [`claim_due`] + [`deliver`] — POSTs each due delivery, signed with
+//!      the endpoint's secret, and records the outcome;
+//!   3. [`prune`] — drops events older than [`RETENTION_DAYS`] that have
+//!      nothing left to deliver.
+//!
+//! Delivery is at-least-once: a consumer dedups on [`DELIVERY_HEADER`] (the
+//! event id, stable across retries). A failed POST (transport error, timeout
+//! or any non-2xx, redirects included) is retried with exponential backoff;
+//! after [`MAX_ATTEMPTS`] the delivery goes `dead` until re-driven.
+//!
+//! Signature: `X-Vixen-Signature: t=<unix secs>,v1=<hex>` where `v1` is
+//! HMAC-SHA256 over `"{t}.{body}"` keyed with the endpoint secret — see
+//! [`sign`] and `server/docs/webhooks.md`.
+
+use std::net::IpAddr;
+use std::time::Duration;
+
+use anyhow::{Context, Result, anyhow};
+use aws_lc_rs::rand::{SecureRandom, SystemRandom};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as B64;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::Sha256;
+use sqlx::{Executor, PgPool, Postgres};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::models::moderation_action::{ActorKind, ModerationActionKind, PublicReason};
+use crate::services::api_keys::ApiKeyCipher;
+use crate::telemetry::metrics;
+
+pub const SIGNATURE_HEADER: &str = "X-Vixen-Signature";
+pub const EVENT_HEADER: &str = "X-Vixen-Event";
+/// Event id; the same on every retry of one event to one endpoint.
+pub const DELIVERY_HEADER: &str = "X-Vixen-Delivery";
+
+/// Attempts before a delivery goes `dead`. With [`backoff`] that spans
+/// roughly three hours.
+pub const MAX_ATTEMPTS: i32 = 10;
+const BACKOFF_BASE: Duration = Duration::from_secs(30);
+const BACKOFF_MAX: Duration = Duration::from_secs(60 * 60);
+/// How long a claimed delivery is hidden from other passes. Longer than any
+/// one POST (the job's client times out well before), so a replica that dies
+/// mid-POST only delays the retry.
+pub const CLAIM_LEASE: Duration = Duration::from_secs(60);
+pub const MAX_ENDPOINTS_PER_CHAT: i64 = 5;
+/// Events (and their deliveries, dead ones included) are kept this long.
+pub const RETENTION_DAYS: i32 = 30;
+/// `last_error` is a response status or a transport error, not a body.
+const ERROR_MAX_CHARS: usize = 300;
+const SECRET_PREFIX: &str = "whsec_";
+
+type HmacSha256 = Hmac<Sha256>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum EventType {
+    #[serde(rename = "user.banned")]
+    UserBanned,
+    #[serde(rename = "user.unbanned")]
+    UserUnbanned,
+    #[serde(rename = "user.verified")]
+    UserVerified,
+}
+
+impl EventType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::UserBanned => "user.banned",
+            Self::UserUnbanned => "user.unbanned",
+            Self::UserVerified => "user.verified",
+        }
+    }
+
+    /// The event a committed ledger row of `kind` produces. Deletes, captcha
+    /// outcomes and role changes stay internal.
+    pub fn for_action(kind: ModerationActionKind) -> Option<Self> {
+        match kind {
+            ModerationActionKind::Ban => Some(Self::UserBanned),
+            ModerationActionKind::Unban => Some(Self::UserUnbanned),
+            ModerationActionKind::Verify => Some(Self::UserVerified),
+            _ => None,
+        }
+    }
+}
+
+/// Webhook body, serialized once into `outbox_events.payload`.
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookEvent {
+    pub id: Uuid,
+    #[serde(rename = "type")]
+    pub event_type: EventType,
+    pub chat_id: i64,
+    pub user_id: i64,
+    /// `bot` or `moderator`.
+    pub actor_kind: &'static str,
+    pub actor_user_id: Option<i64>,
+    /// The `moderation_actions` row behind the event.
+    pub action_id: Option<Uuid>,
+    /// Free-text reason (`raid`, `manual`). Spam verdicts fill
+    /// `matched_rules` and `score` instead — see [`PublicReason`].
+    pub reason: Option<String>,
+    pub matched_rules: Option<Vec<String>>,
+    pub score: Option<f64>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+impl WebhookEvent {
+    /// Fresh id and timestamp; the optional fields start empty.
+    pub fn new(event_type: EventType, chat_id: i64, user_id: i64, actor: ActorKind) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            event_type,
+            chat_id,
+            user_id,
+            actor_kind: actor.as_db_str(),
+            actor_user_id: None,
+            action_id: None,
+            reason: None,
+            matched_rules: None,
+            score: None,
+            occurred_at: Utc::now(),
+        }
+    }
+
+    /// Set the reason fields from a ledger `reason`, through
+    /// [`PublicReason`] so a spam verdict's excerpt stays out of the payload.
+    pub fn with_reason(mut self, reason: Option<&str>) -> Self {
+        let public = PublicReason::from_ledger(reason);
+        self.reason = public.reason;
+        self.matched_rules = public.matched_rules;
+        self.score = public.score;
+        self
+    }
+}
+
+/// Queue `event` for every endpoint of its chat. Call it on the transaction
+/// that writes the action, so a rollback drops the event too.
+pub async fn record<'e, E>(executor: E, event: &WebhookEvent) -> Result<()>
+where
+    E: Executor<'e, Database = Postgres>,
+{
+    let payload = serde_json::to_value(event).context("serialize webhook event")?;
+    sqlx::query!(
+        r#"
+        INSERT INTO outbox_events (id, chat_id, event_type, payload)
+        VALUES ($1, $2, $3, $4)
+        "#,
+        event.id,
+        event.chat_id,
+        event.event_type.as_str(),
+        payload,
+    )
+    .execute(executor)
+    .await
+    .context("INSERT outbox_events")?;
+    Ok(())
+}
+
+/// Expand up to `limit` pending events into deliveries, oldest first.
+/// Events of chats without endpoints are stamped with no delivery. Returns
+/// the number of events stamped.
+pub async fn fan_out(pool: &PgPool, limit: i64) -> Result<u64> {
+    let stamped = sqlx::query!(
+        r#"
+        WITH batch AS (
+            SELECT id, chat_id
+            FROM outbox_events
+            WHERE fanned_out_at IS NULL
+            ORDER BY created_at
+            LIMIT $1
+            FOR UPDATE SKIP LOCKED
+        ), fanned AS (
+            INSERT INTO webhook_deliveries (event_id, endpoint_id)
+            SELECT b.id, w.id
+            FROM batch b
+            JOIN webhook_endpoints w ON w.chat_id = b.chat_id
+            ON CONFLICT DO NOTHING
+        )
+        UPDATE outbox_events
+        SET fanned_out_at = NOW()
+        WHERE id IN (SELECT id FROM batch)
+        "#,
+        limit,
+    )
+    .execute(pool)
+    .await
+    .context("fan out outbox_events")?
+    .rows_affected();
+    Ok(stamped)
+}
+
+/// A delivery claimed by [`claim_due`].
+#[derive(Debug, Clone)]
+pub struct Delivery {
+    pub event_id: Uuid,
+    pub endpoint_id: Uuid,
+    /// Failed attempts so far.
+    pub attempts: i32,
+    pub event_type: String,
+    pub payload: serde_json::Value,
+    pub url: String,
+    /// As stored: sealed, or legacy plaintext.
+    pub secret: String,
+}
+
+/// Claim up to `limit` due deliveries, oldest first, by pushing their
+/// `next_attempt_at` out by [`CLAIM_LEASE`]. Concurrent passes (other
+/// replicas) skip them until [`deliver`] records an outcome or the lease
+/// runs out.
+pub async fn claim_due(pool: &PgPool, limit: i64) -> Result<Vec<Delivery>> {
+    let rows = sqlx::query!(
+        r#"
+        WITH due AS (
+            SELECT event_id, endpoint_id
+            FROM webhook_deliveries
+            WHERE status = 'pending' AND next_attempt_at <= NOW()
+            ORDER BY next_attempt_at
+            LIMIT $1
+            FOR UPDATE SKIP LOCKED
+        )
+        UPDATE webhook_deliveries d
+        SET next_attempt_at = NOW() + make_interval(secs => $2::DOUBLE PRECISION)
+        FROM due, outbox_events e, webhook_endpoints w
+        WHERE d.event_id = due.event_id
+          AND d.endpoint_id = due.endpoint_id
+          AND e.id = d.event_id
+          AND w.id = d.endpoint_id
+        RETURNING d.event_id, d.endpoint_id, d.attempts, e.event_type, e.payload, w.url, w.secret
+        "#,
+        limit,
+        CLAIM_LEASE.as_secs_f64(),
+    )
+    .fetch_all(pool)
+    .await
+    .context("claim due webhook_deliveries")?;
+    Ok(rows
+        .into_iter()
+        .map(|r| Delivery {
+            event_id: r.event_id,
+            endpoint_id: r.endpoint_id,
+            attempts: r.attempts,
+            event_type: r.event_type,
+            payload: r.payload,
+            url: r.url,
+            secret: r.secret,
+        })
+        .collect())
+}
+
+/// What [`deliver`] recorded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeliveryOutcome {
+    Delivered,
+    /// Failed; retried after the returned backoff.
+    Retry(Duration),
+    /// Failed for the [`MAX_ATTEMPTS`]th time.
+    Dead,
+}
+
+impl DeliveryOutcome {
+    /// `outcome` label on `vixen_webhook_deliveries_total`.
+    fn label(self) -> &'static str {
+        match self {
+            Self::Delivered => "delivered",
+            Self::Retry(_) => "retry",
+            Self::Dead => "dead",
+        }
+    }
+}
+
+/// POST one claimed delivery and record the outcome. A secret that does not
+/// open (missing or wrong `CONFIG_SECRETS_KEY`) is a failed attempt. `Err`
+/// only when the outcome cannot be written; the lease then retries it.
+pub async fn deliver(
+    pool: &PgPool,
+    http: &reqwest::Client,
+    cipher: &ApiKeyCipher,
+    delivery: &Delivery,
+) -> Result<DeliveryOutcome> {
+    let outcome = match post(http, cipher, delivery).await {
+        Ok(()) => {
+            sqlx::query!(
+                r#"
+                UPDATE webhook_deliveries
+                SET status = 'delivered', attempts = attempts + 1,
+                    delivered_at = NOW(), last_error = NULL
+                WHERE event_id = $1 AND endpoint_id = $2
+                "#,
+                delivery.event_id,
+                delivery.endpoint_id,
+            )
+            .execute(pool)
+            .await
+            .context("UPDATE webhook_deliveries (delivered)")?;
+            DeliveryOutcome::Delivered
+        }
+        Err(error) => {
+            let attempts = delivery.attempts + 1;
+            let outcome = if attempts >= MAX_ATTEMPTS {
+                DeliveryOutcome::Dead
+            } else {
+                DeliveryOutcome::Retry(backoff(attempts))
+            };
+            let retry_in = match outcome {
+                DeliveryOutcome::Retry(after) => after,
+                _ => Duration::ZERO,
+            };
+            let error: String = error.chars().take(ERROR_MAX_CHARS).collect();
+            sqlx::query!(
+                r#"
+                UPDATE webhook_deliveries
+                SET status = CASE WHEN $3 THEN 'dead' ELSE 'pending' END,
+                    attempts = attempts + 1,
+                    last_error = $4,
+                    next_attempt_at = NOW() + make_interval(secs => $5::DOUBLE PRECISION)
+                WHERE event_id = $1 AND endpoint_id = $2
+                "#,
+                delivery.event_id,
+                delivery.endpoint_id,
+                outcome == DeliveryOutcome::Dead,
+                error,
+                retry_in.as_secs_f64(),
+            )
+            .execute(pool)
+            .await
+            .context("UPDATE webhook_deliveries (failed)")?;
+            outcome
+        }
+    };
+    metrics::webhook_delivery(outcome.label());
+    Ok(outcome)
+}
+
+/// One signed POST. `Err` carries the text stored in `last_error`.
+async fn post(
+    http: &reqwest::Client,
+    cipher: &ApiKeyCipher,
+    delivery: &Delivery,
+) -> std::result::Result<(), String> {
+    let secret = cipher
+        .open_webhook_secret(delivery.endpoint_id, &delivery.secret)
+        .map_err(|e| format!("{e:#}"))?;
+    let body = delivery.payload.to_string();
+    let signature = sign(&secret, Utc::now().timestamp(), body.as_bytes());
+    let response = http
+        .post(&delivery.url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header(EVENT_HEADER, &delivery.event_type)
+        .header(DELIVERY_HEADER, delivery.event_id.to_string())
+        .header(SIGNATURE_HEADER, signature)
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| e.without_url().to_string())?;
+    let status = response.status();
+    if status.is_success() {
+        Ok(())
+    } else {
+        Err(format!("HTTP {}", status.as_u16()))
+    }
+}
+
+/// `X-Vixen-Signature` value for `body` sent at `timestamp` (unix seconds).
+pub fn sign(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    let hex: String = mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect();
+    format!("t={timestamp},v1={hex}")
+}
+
+/// Delay before the next attempt, after `attempts` failures: 30 s doubling
+/// up to an hour.
+pub fn backoff(attempts: i32) -> Duration {
+    let doublings = attempts.saturating_sub(1).clamp(0, 16) as u32;
+    BACKOFF_BASE.saturating_mul(1 << doublings).min(BACKOFF_MAX)
+}
+
+/// Drop events past [`RETENTION_DAYS`] with no pending delivery; their
+/// delivered and dead deliveries cascade. Returns the events removed.
+pub async fn prune(pool: &PgPool) -> Result<u64> {
+    let removed = sqlx::query!(
+        r#"
+        DELETE FROM outbox_events e
+        WHERE e.created_at < NOW() - make_interval(days => $1)
+          AND e.fanned_out_at IS NOT NULL
+          AND NOT EXISTS (
+              SELECT 1 FROM webhook_deliveries d
+              WHERE d.event_id = e.id AND d.status = 'pending'
+          )
+        "#,
+        RETENTION_DAYS,
+    )
+    .execute(pool)
+    .await
+    .context("DELETE old outbox_events")?
+    .rows_affected();
+    Ok(removed)
+}
+
+// ── Endpoints ─────────────────────────────────────────────────────────────
+
+/// A configured endpoint. The secret is never read back.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct WebhookEndpoint {
+    pub id: Uuid,
+    pub chat_id: i64,
+    pub url: String,
+    pub created_by: i64,
+    pub created_at: DateTime<Utc>,
+    /// Deliveries still being attempted.
+    pub pending: i64,
+    /// Deliveries that ran out of attempts; see the redrive route.
+    pub dead: i64,
+}
+
+/// A freshly created endpoint with its signing secret — the only time the
+/// secret leaves the server.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct CreatedEndpoint {
+    #[serde(flatten)]
+    pub endpoint: WebhookEndpoint,
+    #[schema(example = "whsec_3q2-7wFh0kN4...")]
+    pub secret: String,
+}
+
+#[derive(Debug)]
+pub enum CreateOutcome {
+    Created(CreatedEndpoint),
+    /// The chat already has an endpoint with this URL.
+    Duplicate,
+    /// The chat already has [`MAX_ENDPOINTS_PER_CHAT`] endpoints.
+    LimitReached,
+}
+
+/// `http(s)://host[...]`, as the endpoint column's CHECK expects. Unless
+/// `allow_private` (`CONFIG_WEBHOOKS_ALLOW_PRIVATE`), the host must not be
+/// `localhost` or an [`is_private_ip`] literal; names resolving to such
+/// addresses are refused at delivery by `jobs::webhooks::http_client`.
+pub fn validate_url(raw: &str, allow_private: bool) -> std::result::Result<(), &'static str> {
+    let url = url::Url::parse(raw).map_err(|_| "url is not a valid URL")?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err("url must be http or https");
+    }
+    let private = match url.host() {
+        None => return Err("url must have a host"),
+        Some(url::Host::Domain("")) => return Err("url must have a host"),
+        Some(url::Host::Domain(name)) => {
+            let name = name.trim_end_matches('.').to_ascii_lowercase();
+            name == "localhost" || name.ends_with(".localhost")
+        }
+        Some(url::Host::Ipv4(ip)) => is_private_ip(ip.into()),
+        Some(url::Host::Ipv6(ip)) => is_private_ip(ip.into()),
+    };
+    if private && !allow_private {
+        return Err("url must not point at a loopback, private or link-local address");
+    }
+    Ok(())
+}
+
+/// Loopback, private (RFC 1918, CGNAT, IPv6 ULA), link-local (cloud
+/// metadata lives at `169.254.169.254`) or unspecified: somewhere on the
+/// server's own network rather than the internet.
+pub fn is_private_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, ..] = v4.octets();
+            v4.is_loopback()
+                || v4.is_private()
+                || v4.is_link_local()
+                || v4.is_unspecified()
+                || v4.is_broadcast()
+                || (a == 100 && (64..128).contains(&b))
+        }
+        IpAddr::V6(v6) => match v6.to_ipv4_mapped() {
+            Some(v4) => is_private_ip(v4.into()),
+            None => {
+                v6.is_loopback()
+                    || v6.is_unspecified()
+                    || v6.is_unique_local()
+                    || v6.is_unicast_link_local()
+            }
+        },
+    }
+}
+
+/// Endpoints of the chat with their delivery backlog, oldest first.
+pub async fn list_endpoints(pool: &PgPool, chat_id: i64) -> Result<Vec<WebhookEndpoint>> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT w.id, w.chat_id, w.url, w.created_by, w.created_at,
+               COUNT(d.event_id) FILTER (WHERE d.status = 'pending') AS "pending!",
+               COUNT(d.event_id) FILTER (WHERE d.status = 'dead') AS "dead!"
+        FROM webhook_endpoints w
+        LEFT JOIN webhook_deliveries d ON d.endpoint_id = w.id
+        WHERE w.chat_id = $1
+        GROUP BY w.id
+        ORDER BY w.created_at
+        "#,
+        chat_id,
+    )
+    .fetch_all(pool)
+    .await
+    .context("SELECT webhook_endpoints")?;
+    Ok(rows
+        .into_iter()
+        .map(|r| WebhookEndpoint {
+            id: r.id,
+            chat_id: r.chat_id,
+            url: r.url,
+            created_by: r.created_by,
+            created_at: r.created_at,
+            pending: r.pending,
+            dead: r.dead,
+        })
+        .collect())
+}
+
+/// Register `url` for the chat with a new random secret, stored sealed with
+/// `cipher` (which must [`ApiKeyCipher::can_seal`]). Only events that commit
+/// afterwards are delivered to it. `url` must pass [`validate_url`].
+pub async fn create_endpoint(
+    pool: &PgPool,
+    cipher: &ApiKeyCipher,
+    chat_id: i64,
+    url: &str,
+    created_by: i64,
+) -> Result<CreateOutcome> {
+    let mut tx = pool.begin().await.context("begin create endpoint tx")?;
+    // Serialise concurrent creates on the chat so the limit holds.
+    sqlx::query("SELECT 1 FROM chats WHERE chat_id = $1 FOR UPDATE")
+        .bind(chat_id)
+        .execute(&mut *tx)
+        .await
+        .context("SELECT FOR UPDATE chats")?;
+    let count = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "count!" FROM webhook_endpoints WHERE chat_id = $1"#,
+        chat_id,
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .context("COUNT webhook_endpoints")?;
+    if count >= MAX_ENDPOINTS_PER_CHAT {
+        return Ok(CreateOutcome::LimitReached);
+    }
+
+    let id = Uuid::new_v4();
+    let secret = new_secret()?;
+    let sealed = cipher.seal_webhook_secret(id, &secret)?;
+    let row = sqlx::query!(
+        r#"
+        INSERT INTO webhook_endpoints (id, chat_id, url, secret, created_by)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (chat_id, url) DO NOTHING
+        RETURNING created_at
+        "#,
+        id,
+        chat_id,
+        url,
+        sealed,
+        created_by,
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .context("INSERT webhook_endpoints")?;
+    let Some(row) = row else {
+        return Ok(CreateOutcome::Duplicate);
+    };
+    tx.commit().await.context("commit create endpoint tx")?;
+    Ok(CreateOutcome::Created(CreatedEndpoint {
+        endpoint: WebhookEndpoint {
+            id,
+            chat_id,
+            url: url.to_owned(),
+            created_by,
+            created_at: row.created_at,
+            pending: 0,
+            dead: 0,
+        },
+        secret,
+    }))
+}
+
+/// Remove an endpoint with its deliveries. `false` when the chat has no
+/// such endpoint.
+pub async fn delete_endpoint(pool: &PgPool, chat_id: i64, endpoint_id: Uuid) -> Result<bool> {
+    let deleted = sqlx::query!(
+        r#"DELETE FROM webhook_endpoints WHERE id = $1 AND chat_id = $2"#,
+        endpoint_id,
+        chat_id,
+    )
+    .execute(pool)
+    .await
+    .context("DELETE webhook_endpoints")?
+    .rows_affected();
+    Ok(deleted > 0)
+}
+
+/// Put the endpoint's dead deliveries back in the queue with a fresh
+/// attempt budget. `None` when the chat has no such endpoint.
+pub async fn redrive(pool: &PgPool, chat_id: i64, endpoint_id: Uuid) -> Result<Option<u64>> {
+    let exists = sqlx::query_scalar!(
+        r#"SELECT EXISTS(
+            SELECT 1 FROM webhook_endpoints WHERE id = $1 AND chat_id = $2
+        ) AS "exists!""#,
+        endpoint_id,
+        chat_id,
+    )
+    .fetch_one(pool)
+    .await
+    .context("SELECT webhook_endpoints")?;
+    if !exists {
+        return Ok(None);
+    }
+    let requeued = sqlx::query!(
+        r#"
+        UPDATE webhook_deliveries
+        SET status = 'pending', attempts = 0, next_attempt_at = NOW()
+        WHERE endpoint_id = $1 AND status = 'dead'
+        "#,
+        endpoint_id,
+    )
+    .execute(pool)
+    .await
+    .context("UPDATE webhook_deliveries (redrive)")?
+    .rows_affected();
+    Ok(Some(requeued))
+}
+
+fn new_secret() -> Result<String> {
+    let mut bytes = [0u8; 32];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .map_err(|_| anyhow!("system RNG failed"))?;
+    Ok(format!("{SECRET_PREFIX}{}", B64.encode(bytes)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_up_to_an_hour() {
+        assert_eq!(backoff(1), Duration::from_secs(30));
+        assert_eq!(backoff(2), Duration::from_secs(60));
+        assert_eq!(backoff(4), Duration::from_secs(240));
+        assert_eq!(backoff(8), BACKOFF_MAX);
+        assert_eq!(backoff(1_000), BACKOFF_MAX);
+    }
+
+    #[test]
+    fn signature_covers_timestamp_and_body() {
+        let sig = sign("whsec_test", 1_700_000_000, br#"{"a":1}"#);
+        assert!(sig.starts_with("t=1700000000,v1="), "{sig}");
+        assert_eq!(sig.len(), "t=1700000000,v1=".len() + 64);
+        assert_ne!(sig, sign("whsec_test", 1_700_000_001, br#"{"a":1}"#));
+        assert_ne!(sig, sign("whsec_test", 1_700_000_000, br#"{"a":2}"#));
+        assert_ne!(sig, sign("whsec_other", 1_700_000_000, br#"{"a":1}"#));
+    }
+
+    #[test]
+    fn only_bans_and_verifications_are_exported() {
+        assert_eq!(
+            EventType::for_action(ModerationActionKind::Ban),
+            Some(EventType::UserBanned)
+        );
+        assert_eq!(
+            EventType::for_action(ModerationActionKind::Verify),
+            Some(EventType::UserVerified)
+        );
+        assert_eq!(EventType::for_action(ModerationActionKind::Delete), None);
+        assert_eq!(
+            EventType::for_action(ModerationActionKind::CaptchaFailed),
+            None
+        );
+    }
+
+    #[test]
+    fn event_serializes_with_dotted_type() {
+        let event = WebhookEvent::new(EventType::UserBanned, -100, 42, ActorKind::Bot);
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["type"], "user.banned");
+        assert_eq!(json["actor_kind"], "bot");
+        assert_eq!(json["user_id"], 42);
+    }
+
+    #[test]
+    fn validate_url_requires_http_and_a_host() {
+        assert!(validate_url("https://crm.example.com/hooks/vixen", false).is_ok());
+        assert!(validate_url("http://8.8.8.8/hook", false).is_ok());
+        assert!(validate_url("ftp://example.com", false).is_err());
+        assert!(validate_url("not a url", false).is_err());
+        assert!(validate_url("file:///etc/passwd", false).is_err());
+    }
+
+    #[test]
+    fn validate_url_refuses_private_hosts_unless_allowed() {
+        for url in [
+            "http://127.0.0.1:8080/hook",
+            "http://2130706433/hook",
+            "http://localhost/hook",
+            "http://api.LOCALHOST./hook",
+            "http://10.1.2.3/hook",
+            "http://192.168.0.10/hook",
+            "http://100.64.0.1/hook",
+            "http://169.254.169.254/latest/meta-data",
+            "http://0.0.0.0/hook",
+            "http://[::1]/hook",
+            "http://[fd00::1]/hook",
+            "http://[fe80::1]/hook",
+            "http://[::ffff:10.0.0.1]/hook",
+        ] {
+            assert!(validate_url(url, false).is_err(), "{url}");
+            assert!(validate_url(url, true).is_ok(), "{url}");
+        }
+        assert!(validate_url("http://[2606:4700::1111]/hook", false).is_ok());
+    }
+
+    #[test]
+    fn spam_reason_is_sent_without_the_excerpt() {
+        let reason = r#"{"matched_rules":["ngram"],"score":3.5,"excerpt":"buy now"}"#;
+        let event = WebhookEvent::new(EventType::UserBanned, -100, 42, ActorKind::Bot)
+            .with_reason(Some(reason));
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["reason"], serde_json::Value::Null);
+        assert_eq!(json["matched_rules"], serde_json::json!(["ngram"]));
+        assert_eq!(json["score"], 3.5);
+        assert!(!json.to_string().contains("buy now"));
+
+        let event = WebhookEvent::new(EventType::UserBanned, -100, 42, ActorKind::Moderator)
+            .with_reason(Some("raid"));
+        assert_eq!(event.reason.as_deref(), Some("raid"));
+        assert_eq!(event.matched_rules, None);
+    }
+}
@@ -1,9 +1,14 @@
-//! Slash commands. `/help` and `/status` are stub replies; `/verify`, `/ban`
-//! and `/unban` are moderator-gated and routed through their respective
-//! services.
+//! Slash commands. `/help` and `/status` are stub replies; every other command
+//! is gated on a [`Permission`] from the moderator role matrix (see
+//! [`Command::required_permission`]) and routed through its service.
+//!
+//! [`PrivateCommand`] is the separate, much smaller set parsed in private
+//! chats with the bot (today only the `/start` deep link for appeals).
 
 use teloxide::utils::command::BotCommands;
 
+use crate::models::chat_moderator::Permission;
+
 #[derive(BotCommands, Clone, Debug)]
 #[command(rename_rule = "lowercase", description = "Vixen bot commands")]
 pub enum Command {
@@ -33,4 +38,50 @@ pub enum Command {
     /// Requires `chat_config.openai_api_key` set for this chat.
     #[command(description = "AI summary of recent chat (moderator)")]
     Summary,
+    /// `/mod grant <user_id|reply> [role]`, `/mod revoke <user_id|reply>`.
+    /// Role defaults to `moderator`; only roles below the actor's own.
+    #[command(description = "grant or revoke a moderator role (admin)")]
+    Mod(String),
+}
+
+impl Command {
+    /// Permission the sender needs before the command runs. `None` for the
+    /// public stub commands.
+    pub fn required_permission(&self) -> Option<Permission> {
+        match self {
+            Self::Help | Self::Status => None,
+            Self::Verify(_) => Some(Permission::Verify),
+            Self::Ban(_) | Self::Unban(_) => Some(Permission::Ban),
+            Self::Stats => Some(Permission::ViewReports),
+            Self::Report => Some(Permission::PostReport),
+            Self::Summary => Some(Permission::RequestSummary),
+            Self::Mod(_) => Some(Permission::ManageModerators),
+        }
+    }
+
+    /// Slash name for user-facing replies (`"/ban"`).
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Help => "/help",
+            Self::Status => "/status",
+            Self::Verify(_) => "/verify",
+            Self::Ban(_) => "/ban",
+            Self::Unban(_) => "/unban",
+            Self::Stats => "/stats",
+            Self::Report => "/report",
+            Self::Summary => "/summary",
+            Self::Mod(_) => "/mod",
+        }
+    }
+}
+
+/// Commands understood in a private chat with the bot. Kept apart from
+/// [`Command`] so group-only commands never parse in DMs and vice versa.
+#[derive(BotCommands, Clone, Debug)]
+#[command(rename_rule = "lowercase", description = "Vixen private-chat commands")]
+pub enum PrivateCommand {
+    /// `/start appeal_<chat_id>` opens a ban appeal; a bare `/start` replies
+    /// with a short intro.
+    #[command(description = "start the bot or open an appeal")]
+    Start(String),
 }
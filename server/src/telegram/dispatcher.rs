@@ -4,27 +4,38 @@
 //!
 //!   * `Update::filter_chat_member()`    — captcha issuance on join
 //!   * `Update::filter_callback_query()` — captcha digit-pad solve / refresh
+//!   * `Update::filter_callback_query()` — moderation log buttons (`vl:`);
+//!     pressed in a log channel, so scoped by the handler instead
+//!   * `Update::filter_callback_query()` — appeal approve / deny (`va:`);
+//!     pressed in a log channel or a moderator's DM, scoped by the handler
 //!   * `Update::filter_message()`        — slash commands first, then the
 //!     captcha message gate (delete + (re)issue captcha for unverified
 //!     non-admin users); the M2 spam pipeline will hang off the same gate.
+//!   * `Update::filter_message()` in private chats — `/start appeal_<chat>`
+//!     and the appeal text that follows it
 //!
-//! The watched-chats filter sits at the trunk so non-watched chats never reach
-//! a handler.
+//! The watched-chats filter sits at the trunk of every other branch so
+//! non-watched chats never reach a handler. Above all of it, [`traced`]
+//! opens the per-update root span every handler span nests under.
 
 use std::collections::HashSet;
 use std::sync::Arc;
 
-use teloxide::dispatching::{DefaultKey, Dispatcher};
-use teloxide::dptree;
+use teloxide::dispatching::{DefaultKey, Dispatcher, DpHandlerDescription, UpdateHandler};
+use teloxide::dptree::di::{DependencyMap, DependencySupplier};
+use teloxide::dptree::{self, HandlerDescription};
 use teloxide::prelude::*;
-use tracing::info;
+use tracing::{Instrument, info};
 
 use crate::api::AppState;
 use crate::services::captcha::keyboard::CALLBACK_PREFIX_WITH_COLON;
-use crate::telegram::commands::Command;
+use crate::services::{appeal, mod_log};
+use crate::telegram::commands::{Command, PrivateCommand};
 use crate::telegram::handlers::{
-    captcha as captcha_handler, commands as command_handler, member_update, message_gate,
+    appeal as appeal_handler, captcha as captcha_handler, commands as command_handler,
+    member_update, message_gate, mod_log as mod_log_handler,
 };
+use crate::telemetry::otel;
 
 /// Set of chat IDs the bot is allowed to react to. Constructed once at startup
 /// from `Config::chats` and cloned cheaply via `Arc`.
@@ -49,6 +60,21 @@ impl WatchedChats {
     }
 }
 
+/// Pass-through entry that runs the rest of the tree inside
+/// [`otel::update_span`], so one update is one trace. Described as `entry()`
+/// rather than user-defined code, which would widen the `allowed_updates`
+/// teloxide derives from the tree to every update kind.
+fn traced() -> UpdateHandler<anyhow::Error> {
+    dptree::from_fn_with_description(
+        DpHandlerDescription::entry(),
+        |deps: DependencyMap, cont| {
+            let update: Arc<Update> = deps.get();
+            let span = otel::update_span(&update);
+            cont(deps).instrument(span)
+        },
+    )
+}
+
 /// Build a teloxide `Dispatcher` with the M1 handler tree. The dispatcher
 /// drives polling itself; the caller stops it via `Dispatcher::shutdown_token()`.
 /// teloxide 0.13's `Dispatcher::dispatch` introspects the handler tree to
@@ -80,6 +106,24 @@ pub fn build_dispatcher(
         })
         .endpoint(captcha_handler::handle);
 
+    // Log channels are not watched chats; `mod_log_handler` checks that the
+    // press came from the log channel configured for the encoded chat.
+    let log_callback_branch = Update::filter_callback_query()
+        .filter(|q: CallbackQuery| {
+            q.data
+                .as_deref()
+                .is_some_and(|d| d.starts_with(mod_log::CALLBACK_PREFIX_WITH_COLON))
+        })
+        .endpoint(mod_log_handler::handle);
+
+    let appeal_callback_branch = Update::filter_callback_query()
+        .filter(|q: CallbackQuery| {
+            q.data
+                .as_deref()
+                .is_some_and(|d| d.starts_with(appeal::CALLBACK_PREFIX_WITH_COLON))
+        })
+        .endpoint(appeal_handler::callback);
+
     let message_branch = Update::filter_message()
         .filter(|msg: Message, watched: WatchedChats| watched.contains(msg.chat.id.0))
         .branch(
@@ -89,10 +133,24 @@ pub fn build_dispatcher(
         )
         .endpoint(message_gate::handle);
 
-    let handler = dptree::entry()
+    // Private chats are never watched; the appeal handlers scope themselves
+    // to the chat named in the deep link.
+    let private_branch = Update::filter_message()
+        .filter(|msg: Message| msg.chat.is_private())
+        .branch(
+            dptree::entry()
+                .filter_command::<PrivateCommand>()
+                .endpoint(appeal_handler::start),
+        )
+        .endpoint(appeal_handler::message);
+
+    let handler = traced()
         .branch(chat_member_branch)
         .branch(callback_branch)
-        .branch(message_branch);
+        .branch(log_callback_branch)
+        .branch(appeal_callback_branch)
+        .branch(message_branch)
+        .branch(private_branch);
 
     info!("telegram dispatcher: M1 handler tree ready");
 
@@ -0,0 +1,250 @@
+//! Private-chat appeal flow and the moderators' approve / deny buttons.
+//!
+//!   1. A banned user opens `t.me/<bot>?start=appeal_<chat_id>` →
+//!      [`start`] opens a `draft` appeal linked to their standing ban.
+//!   2. Their next private text message → [`message`] submits it and posts
+//!      the appeal to the chat's log channel, or (no log channel) DMs every
+//!      moderator who can ban and has started the bot.
+//!   3. A moderator presses Approve / Deny (`va:` callback) → [`callback`]
+//!      closes the appeal; approval runs `Action::Unban` plus
+//!      `verify_manual` so the user can rejoin without a captcha.
+//!
+//! Private chats are never watched chats, so the dispatcher routes these
+//! updates outside the watched-chats filter; every entry point here scopes
+//! itself to `Config::chats`.
+//!
+//! Replies go out in the user's client language when we ship it, else the
+//! appealed chat's; the decision DM only has the chat's.
+
+use anyhow::Result;
+use teloxide::prelude::*;
+use teloxide::types::{ChatId, MaybeInaccessibleMessage, ParseMode, UserId};
+use tracing::{info, instrument, warn};
+
+use crate::api::AppState;
+use crate::i18n::{self, Lang};
+use crate::models::appeal::{self, Appeal, OpenDraft};
+use crate::models::chat_moderator::{self, Permission};
+use crate::models::moderation_action::ActorKind;
+use crate::services::appeal::{self as appeal_service, parse_callback, parse_start};
+use crate::services::mod_log;
+use crate::services::moderation_service::{Action, ApplyContext};
+use crate::services::outbound::Priority;
+use crate::telegram::commands::PrivateCommand;
+use crate::tr;
+
+/// `/start [payload]` in a private chat.
+#[instrument(skip(bot, msg, state, cmd), fields(user_id = msg.chat.id.0))]
+pub async fn start(bot: Bot, msg: Message, state: AppState, cmd: PrivateCommand) -> Result<()> {
+    let PrivateCommand::Start(payload) = cmd;
+    let Some(user) = msg.from.as_ref() else {
+        return Ok(());
+    };
+    let user_lang = user.language_code.as_deref();
+    let Some(chat_id) = parse_start(&payload).filter(|c| state.config.chats.contains(c)) else {
+        let lang = Lang::En.for_user(user_lang);
+        send_dm(&bot, &state, msg.chat.id, tr!(lang, "appeal-intro")).await;
+        return Ok(());
+    };
+    let lang = i18n::chat_lang(state.db.pool(), chat_id)
+        .await
+        .for_user(user_lang);
+
+    let reply = match appeal::open_draft(state.db.pool(), chat_id, user.id.0 as i64).await? {
+        OpenDraft::Opened(_) => tr!(lang, "appeal-prompt", minutes = appeal::DRAFT_TTL_MINUTES),
+        OpenDraft::NotBanned => tr!(lang, "appeal-not-banned"),
+        OpenDraft::AlreadyPending => tr!(lang, "appeal-already-pending"),
+        OpenDraft::CoolingDown(hours) => tr!(lang, "appeal-cooldown", hours = hours),
+    };
+    send_dm(&bot, &state, msg.chat.id, reply).await;
+    Ok(())
+}
+
+/// Any other private text: the appeal body when the user has an open draft.
+#[instrument(skip(bot, msg, state), fields(user_id = msg.chat.id.0))]
+pub async fn message(bot: Bot, msg: Message, state: AppState) -> Result<()> {
+    let (Some(user), Some(text)) = (msg.from.as_ref(), msg.text()) else {
+        return Ok(());
+    };
+    let Some(appeal) = appeal::submit(state.db.pool(), user.id.0 as i64, text).await? else {
+        return Ok(());
+    };
+    let lang = i18n::chat_lang(state.db.pool(), appeal.chat_id)
+        .await
+        .for_user(user.language_code.as_deref());
+    send_dm(&bot, &state, msg.chat.id, tr!(lang, "appeal-sent")).await;
+    notify_moderators(&bot, &state, &appeal).await?;
+    info!(chat_id = appeal.chat_id, appeal_id = %appeal.id, "appeal submitted");
+    Ok(())
+}
+
+async fn notify_moderators(bot: &Bot, state: &AppState, appeal: &Appeal) -> Result<()> {
+    let pool = state.db.pool();
+    let title = appeal_service::chat_title(pool, appeal.chat_id).await?;
+    let ban_reason = match appeal.action_id {
+        Some(id) => appeal::ban_reason(pool, id).await?,
+        None => None,
+    };
+    let text = appeal_service::render(appeal, title.as_deref(), ban_reason.as_deref());
+
+    let targets: Vec<i64> = match mod_log::log_chat_for(pool, appeal.chat_id).await? {
+        Some(log_chat) => vec![log_chat],
+        None => chat_moderator::list(pool, appeal.chat_id)
+            .await?
+            .into_iter()
+            .filter(|m| m.role.allows(Permission::Ban))
+            .map(|m| m.user_id)
+            .collect(),
+    };
+    if targets.is_empty() {
+        warn!(chat_id = appeal.chat_id, "appeal has nobody to notify");
+    }
+    for target in targets {
+        // DMs fail with 403 for moderators who never started the bot; the
+        // remaining ones still get the appeal.
+        let request = bot
+            .send_message(ChatId(target), text.clone())
+            .parse_mode(ParseMode::MarkdownV2)
+            .reply_markup(appeal_service::keyboard(appeal.id));
+        if let Err(e) = state
+            .outbound
+            .send(target, Priority::Interactive, request)
+            .await
+        {
+            warn!(target, error = %e, "appeal notification failed");
+        }
+    }
+    Ok(())
+}
+
+/// Approve / Deny press (`va:` callback), from the log channel or a DM.
+#[instrument(
+    skip(bot, q, state),
+    fields(
+        callback_id = %q.id,
+        from_user = q.from.id.0,
+    )
+)]
+pub async fn callback(bot: Bot, q: CallbackQuery, state: AppState) -> Result<()> {
+    let Some(parsed) = q.data.as_deref().and_then(parse_callback) else {
+        let _ = bot.answer_callback_query(&q.id).await;
+        return Ok(());
+    };
+    let pool = state.db.pool();
+    let presser_id = q.from.id.0 as i64;
+
+    let Some(chat_id) = appeal::chat_of(pool, parsed.appeal_id)
+        .await?
+        .filter(|c| state.config.chats.contains(c))
+    else {
+        let _ = bot.answer_callback_query(&q.id).await;
+        return Ok(());
+    };
+    let chat_lang = i18n::chat_lang(pool, chat_id).await;
+    let lang = chat_lang.for_user(q.from.language_code.as_deref());
+    let allowed = state
+        .moderation
+        .role(chat_id, presser_id)
+        .await?
+        .is_some_and(|role| role.allows(Permission::Ban));
+    if !allowed {
+        let _ = bot
+            .answer_callback_query(&q.id)
+            .text(tr!(lang, "no-permission"))
+            .show_alert(false)
+            .await;
+        return Ok(());
+    }
+
+    let Some(decided) = appeal::decide(pool, parsed.appeal_id, parsed.approve, presser_id).await?
+    else {
+        let _ = bot
+            .answer_callback_query(&q.id)
+            .text(tr!(lang, "appeal-already-decided"))
+            .await;
+        remove_keyboard(&bot, &state, &q).await;
+        return Ok(());
+    };
+
+    let (toast, notice) = if parsed.approve {
+        approve(&state, &decided, presser_id).await;
+        (
+            tr!(lang, "appeal-approved-toast"),
+            tr!(chat_lang, "appeal-approved-notice"),
+        )
+    } else {
+        (
+            tr!(lang, "appeal-denied-toast"),
+            tr!(chat_lang, "appeal-denied-notice"),
+        )
+    };
+    let _ = bot.answer_callback_query(&q.id).text(toast).await;
+    remove_keyboard(&bot, &state, &q).await;
+    let request = bot.send_message(UserId(decided.user_id as u64), notice);
+    if let Err(e) = state
+        .outbound
+        .send(decided.user_id, Priority::Interactive, request)
+        .await
+    {
+        warn!(error = %e, "appeal decision DM failed");
+    }
+    info!(
+        chat_id,
+        target_user_id = decided.user_id,
+        approved = parsed.approve,
+        "appeal decided"
+    );
+    Ok(())
+}
+
+/// Unban + verify. Failures are logged, not surfaced: the appeal stays
+/// approved and a moderator can still `/unban` / `/verify` by hand.
+async fn approve(state: &AppState, appeal: &Appeal, moderator_id: i64) {
+    let ctx = ApplyContext {
+        chat_id: appeal.chat_id,
+        target_user_id: appeal.user_id,
+        message_id: None,
+        actor_kind: ActorKind::Moderator,
+        actor_user_id: Some(moderator_id),
+    };
+    if let Err(e) = state.moderation.apply(Action::Unban, ctx).await {
+        warn!(error = ?e, "moderation.apply (Unban) for appeal failed");
+    }
+    if let Err(e) = state
+        .captcha
+        .verify_manual(appeal.chat_id, appeal.user_id, Some(moderator_id))
+        .await
+    {
+        warn!(error = ?e, "verify_manual for appeal failed");
+    }
+    if let Err(e) = state
+        .captcha_state
+        .mark_verified(appeal.chat_id, appeal.user_id)
+        .await
+    {
+        warn!(error = ?e, "redis mark_verified (appeal) failed");
+    }
+}
+
+async fn remove_keyboard(bot: &Bot, state: &AppState, q: &CallbackQuery) {
+    let Some(MaybeInaccessibleMessage::Regular(msg)) = q.message.as_ref() else {
+        return;
+    };
+    let request = bot.edit_message_reply_markup(msg.chat.id, msg.id);
+    if let Err(e) = state
+        .outbound
+        .send(msg.chat.id.0, Priority::Interactive, request)
+        .await
+    {
+        warn!(error = %e, "remove appeal keyboard failed");
+    }
+}
+
+/// Reply in the user's private chat. Best-effort.
+async fn send_dm(bot: &Bot, state: &AppState, chat_id: ChatId, text: String) {
+    let request = bot.send_message(chat_id, text);
+    let _ = state
+        .outbound
+        .send(chat_id.0, Priority::Interactive, request)
+        .await;
+}
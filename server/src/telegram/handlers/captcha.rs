@@ -12,21 +12,31 @@
 //! a target's challenge by mashing buttons. The meta row is the source of
 //! truth for the press's identity, so we look it up *before* acking the
 //! callback (so non-owners actually see the toast).
+//!
+//! While Redis is unavailable (or the meta key is missing) both fall back to
+//! the `captcha_challenges` row: the meta is rebuilt from the challenge whose
+//! photo was pressed, and the digits go to its `input` column.
 
 use anyhow::Result;
+use teloxide::RequestError;
 use teloxide::payloads::EditMessageMediaSetters;
 use teloxide::prelude::*;
+use teloxide::requests::{Output, Request};
 use teloxide::types::{
     ChatId, InputFile, InputMedia, InputMediaPhoto, MaybeInaccessibleMessage, UserId,
 };
-use tracing::{info, instrument, warn};
+use tracing::{debug, info, instrument, warn};
 
 use crate::api::AppState;
-use crate::services::captcha::Outcome;
+use crate::i18n::{self, Lang};
 use crate::services::captcha::caption::{caption_progress, caption_wrong};
 use crate::services::captcha::keyboard::{
     OP_BACKSPACE, OP_REFRESH, digit_pad_from_short, parse_callback, short_id,
 };
+use crate::services::captcha::{MetaPayload, Outcome};
+use crate::services::live_feed::LiveEventKind;
+use crate::services::outbound::{OutboundError, Priority};
+use crate::tr;
 
 const SOLUTION_LEN: usize = 4;
 
@@ -56,28 +66,27 @@ pub async fn handle(bot: Bot, q: CallbackQuery, state: AppState) -> Result<()> {
     let presser_id = q.from.id;
     let message_id = msg.id;
 
-    // Look up meta BEFORE acking. If the meta is gone (TTL expired or restart
-    // wiped Redis), there's nothing meaningful to do; ack and bail.
-    let meta = match state.captcha_state.get_meta(chat_id.0, message_id.0).await {
-        Ok(Some(m)) => m,
-        Ok(None) => {
-            let _ = bot.answer_callback_query(&q.id).await;
-            return Ok(());
-        }
-        Err(e) => {
-            warn!(error = ?e, "redis get_meta failed");
-            let _ = bot.answer_callback_query(&q.id).await;
-            return Ok(());
-        }
+    // Look up meta BEFORE acking. If neither Redis nor the challenge row
+    // knows this message (challenge expired or solved), there's nothing
+    // meaningful to do; ack and bail.
+    let Some(meta) = load_meta(&state, chat_id.0, message_id.0).await else {
+        let _ = bot.answer_callback_query(&q.id).await;
+        return Ok(());
     };
 
+    // Captions and toasts go to the presser, who past the ownership check
+    // is also the captcha's owner.
+    let lang = i18n::chat_lang(state.db.pool(), chat_id.0)
+        .await
+        .for_user(q.from.language_code.as_deref());
+
     // Ownership check. A non-owner gets a toast (visible only to them) and we
     // do NOT touch the captcha. Telegram requires answer_callback_query within
     // ~15s; the check itself is two Redis ops + a string compare.
     if (presser_id.0 as i64) != meta.owner_user_id {
         let _ = bot
             .answer_callback_query(&q.id)
-            .text("This isn't your captcha.")
+            .text(tr!(lang, "captcha-not-yours"))
             .show_alert(false)
             .await;
         return Ok(());
@@ -97,11 +106,12 @@ pub async fn handle(bot: Bot, q: CallbackQuery, state: AppState) -> Result<()> {
     let lifetime = meta.lifetime_secs;
 
     match parsed.op.as_str() {
-        OP_REFRESH => refresh(&bot, &state, chat_id, message_id, owner_id).await,
+        OP_REFRESH => refresh(&bot, &state, lang, chat_id, message_id, owner_id).await,
         OP_BACKSPACE => {
             backspace(
                 &bot,
                 &state,
+                lang,
                 chat_id,
                 message_id,
                 owner_id,
@@ -114,6 +124,7 @@ pub async fn handle(bot: Bot, q: CallbackQuery, state: AppState) -> Result<()> {
             digit_pressed(
                 &bot,
                 &state,
+                lang,
                 chat_id,
                 presser_id,
                 message_id,
@@ -132,6 +143,7 @@ pub async fn handle(bot: Bot, q: CallbackQuery, state: AppState) -> Result<()> {
 async fn digit_pressed(
     bot: &Bot,
     state: &AppState,
+    lang: Lang,
     chat_id: ChatId,
     presser_id: UserId,
     message_id: teloxide::types::MessageId,
@@ -140,13 +152,7 @@ async fn digit_pressed(
     digit: &str,
     short: &str,
 ) -> Result<()> {
-    let mut input = match state.captcha_state.get_input(chat_id.0, owner_id).await {
-        Ok(s) => s,
-        Err(e) => {
-            warn!(error = ?e, "redis get_input failed; treating as empty buffer");
-            String::new()
-        }
-    };
+    let mut input = load_input(state, chat_id.0, owner_id).await;
 
     if input.chars().count() >= SOLUTION_LEN {
         // Cap reached, ignore. (User has to backspace first.)
@@ -155,30 +161,27 @@ async fn digit_pressed(
     input.push_str(digit);
 
     if input.chars().count() < SOLUTION_LEN {
-        if let Err(e) = state
-            .captcha_state
-            .set_input(chat_id.0, owner_id, &input, lifetime_secs)
-            .await
-        {
-            warn!(error = ?e, "redis set_input failed");
-        }
-        let _ = bot
+        store_input(state, chat_id.0, owner_id, &input, lifetime_secs).await;
+        let request = bot
             .edit_message_caption(chat_id, message_id)
-            .caption(caption_progress(&input))
-            .reply_markup(digit_pad_from_short(short))
+            .caption(caption_progress(lang, &input))
+            .reply_markup(digit_pad_from_short(short));
+        let _ = interactive(state, chat_id, request)
             .await
             .inspect_err(|e| warn!(error = %e, "edit_message_caption failed"));
         return Ok(());
     }
 
     // Length == SOLUTION_LEN — try to solve.
-    match state.captcha.solve(chat_id.0, owner_id, &input).await? {
+    let outcome = state.captcha.solve(chat_id.0, owner_id, &input).await?;
+    publish_outcome(state, chat_id.0, owner_id, &outcome).await;
+    match outcome {
         Outcome::Solved | Outcome::AlreadyVerified => {
             clear_state(state, chat_id.0, owner_id, message_id.0).await;
             if let Err(e) = state.captcha_state.mark_verified(chat_id.0, owner_id).await {
                 warn!(error = ?e, "redis mark_verified failed");
             }
-            on_solved(bot, chat_id, presser_id, message_id).await;
+            on_solved(bot, state, chat_id, presser_id, message_id).await;
         }
         Outcome::WrongLeft(left) => {
             // Reset the input buffer so the user can immediately retry — the
@@ -190,22 +193,22 @@ async fn digit_pressed(
             if let Err(e) = state.captcha_state.clear_input(chat_id.0, owner_id).await {
                 warn!(error = ?e, "redis clear_input (WrongLeft) failed");
             }
-            let _ = bot
+            let request = bot
                 .edit_message_caption(chat_id, message_id)
-                .caption(caption_wrong(left))
-                .reply_markup(digit_pad_from_short(short))
-                .await;
+                .caption(caption_wrong(lang, left))
+                .reply_markup(digit_pad_from_short(short));
+            let _ = interactive(state, chat_id, request).await;
         }
         Outcome::WrongFinal | Outcome::Expired => {
             clear_state(state, chat_id.0, owner_id, message_id.0).await;
-            on_failed(bot, chat_id, presser_id, message_id).await;
+            on_failed(bot, state, chat_id, presser_id, message_id).await;
         }
         Outcome::NotFound => {
             // Ownership was already verified above, so this is a true vanish:
             // the challenge row was already cleaned up by the expiry job or a
             // parallel solver. Drop the message + scrub Redis.
             clear_state(state, chat_id.0, owner_id, message_id.0).await;
-            let _ = bot.delete_message(chat_id, message_id).await;
+            let _ = interactive(state, chat_id, bot.delete_message(chat_id, message_id)).await;
         }
     }
     Ok(())
@@ -215,38 +218,28 @@ async fn digit_pressed(
 async fn backspace(
     bot: &Bot,
     state: &AppState,
+    lang: Lang,
     chat_id: ChatId,
     message_id: teloxide::types::MessageId,
     owner_id: i64,
     lifetime_secs: u64,
     short: &str,
 ) -> Result<()> {
-    let mut input = match state.captcha_state.get_input(chat_id.0, owner_id).await {
-        Ok(s) => s,
-        Err(e) => {
-            warn!(error = ?e, "redis get_input failed; backspace on empty buffer");
-            String::new()
-        }
-    };
+    let mut input = load_input(state, chat_id.0, owner_id).await;
     input.pop();
-    if let Err(e) = state
-        .captcha_state
-        .set_input(chat_id.0, owner_id, &input, lifetime_secs)
-        .await
-    {
-        warn!(error = ?e, "redis set_input failed");
-    }
-    let _ = bot
+    store_input(state, chat_id.0, owner_id, &input, lifetime_secs).await;
+    let request = bot
         .edit_message_caption(chat_id, message_id)
-        .caption(caption_progress(&input))
-        .reply_markup(digit_pad_from_short(short))
-        .await;
+        .caption(caption_progress(lang, &input))
+        .reply_markup(digit_pad_from_short(short));
+    let _ = interactive(state, chat_id, request).await;
     Ok(())
 }
 
 async fn refresh(
     bot: &Bot,
     state: &AppState,
+    lang: Lang,
     chat_id: ChatId,
     message_id: teloxide::types::MessageId,
     owner_id: i64,
@@ -272,13 +265,24 @@ async fn refresh(
             return Ok(());
         }
     };
+    state
+        .live
+        .publish(
+            chat_id.0,
+            LiveEventKind::CaptchaIssued {
+                user_id: owner_id,
+                expires_at: issued.expires_at,
+            },
+        )
+        .await;
     let media = InputMedia::Photo(
         InputMediaPhoto::new(InputFile::memory(issued.image_webp).file_name("captcha.webp"))
-            .caption(caption_progress("")),
+            .caption(caption_progress(lang, "")),
     );
-    let _ = bot
+    let request = bot
         .edit_message_media(chat_id, message_id, media)
-        .reply_markup(issued.keyboard)
+        .reply_markup(issued.keyboard);
+    let _ = interactive(state, chat_id, request)
         .await
         .inspect_err(|e| warn!(error = %e, "edit_message_media failed"));
     if let Err(e) = state
@@ -310,7 +314,87 @@ async fn refresh(
     Ok(())
 }
 
+/// Captcha edits and the photo's removal are part of the user's interaction:
+/// queued behind spam deletes, ahead of reports.
+async fn interactive<R>(
+    state: &AppState,
+    chat_id: ChatId,
+    request: R,
+) -> Result<Output<R>, OutboundError>
+where
+    R: Request<Err = RequestError>,
+{
+    state
+        .outbound
+        .send(chat_id.0, Priority::Interactive, request)
+        .await
+}
+
+/// Callback meta from Redis, else rebuilt from the challenge row.
+async fn load_meta(state: &AppState, chat_id: i64, message_id: i32) -> Option<MetaPayload> {
+    match state.captcha_state.get_meta(chat_id, message_id).await {
+        Ok(Some(meta)) => return Some(meta),
+        Ok(None) => {}
+        Err(e) => debug!(error = ?e, "redis get_meta failed; reading the challenge row"),
+    }
+    state
+        .captcha
+        .meta_for_message(chat_id, message_id)
+        .await
+        .unwrap_or_else(|e| {
+            warn!(error = ?e, "meta_for_message failed");
+            None
+        })
+}
+
+/// Digits typed so far: Redis, or the challenge row while Redis is
+/// unavailable. Digits typed during an outage aren't carried back into
+/// Redis; the buffer restarts empty once it recovers.
+async fn load_input(state: &AppState, chat_id: i64, owner_id: i64) -> String {
+    if state.redis.is_available() {
+        match state.captcha_state.get_input(chat_id, owner_id).await {
+            Ok(input) => return input,
+            Err(e) => warn!(error = ?e, "redis get_input failed; reading the challenge row"),
+        }
+    }
+    state
+        .captcha
+        .input(chat_id, owner_id)
+        .await
+        .unwrap_or_else(|e| {
+            warn!(error = ?e, "captcha input fallback read failed; treating as empty buffer");
+            String::new()
+        })
+}
+
+/// Counterpart of [`load_input`].
+async fn store_input(state: &AppState, chat_id: i64, owner_id: i64, input: &str, ttl_secs: u64) {
+    if state.redis.is_available() {
+        match state
+            .captcha_state
+            .set_input(chat_id, owner_id, input, ttl_secs)
+            .await
+        {
+            Ok(()) => return,
+            Err(e) => warn!(error = ?e, "redis set_input failed; writing the challenge row"),
+        }
+    }
+    if let Err(e) = state.captcha.set_input(chat_id, owner_id, input).await {
+        warn!(error = ?e, "captcha input fallback write failed");
+    }
+}
+
 /// Best-effort scrub of both Redis keys for a finished interaction.
+/// `captcha.solved` / `captcha.expired` on the dashboard's live feed.
+async fn publish_outcome(state: &AppState, chat_id: i64, user_id: i64, outcome: &Outcome) {
+    let kind = match outcome {
+        Outcome::Solved => LiveEventKind::CaptchaSolved { user_id },
+        Outcome::Expired => LiveEventKind::CaptchaExpired { user_id },
+        _ => return,
+    };
+    state.live.publish(chat_id, kind).await;
+}
+
 async fn clear_state(state: &AppState, chat_id: i64, owner_id: i64, message_id: i32) {
     if let Err(e) = state.captcha_state.clear_input(chat_id, owner_id).await {
         warn!(error = ?e, "redis clear_input failed");
@@ -322,11 +406,12 @@ async fn clear_state(state: &AppState, chat_id: i64, owner_id: i64, message_id:
 
 async fn on_solved(
     bot: &Bot,
+    state: &AppState,
     chat_id: ChatId,
     user_id: UserId,
     message_id: teloxide::types::MessageId,
 ) {
-    let _ = bot.delete_message(chat_id, message_id).await;
+    let _ = interactive(state, chat_id, bot.delete_message(chat_id, message_id)).await;
     info!(
         chat_id = chat_id.0,
         user_id = user_id.0 as i64,
@@ -340,11 +425,12 @@ async fn on_solved(
 /// will be issued.
 async fn on_failed(
     bot: &Bot,
+    state: &AppState,
     chat_id: ChatId,
     user_id: UserId,
     message_id: teloxide::types::MessageId,
 ) {
-    let _ = bot.delete_message(chat_id, message_id).await;
+    let _ = interactive(state, chat_id, bot.delete_message(chat_id, message_id)).await;
     info!(
         chat_id = chat_id.0,
         user_id = user_id.0 as i64,
@@ -1,25 +1,35 @@
 //! Slash-command handlers. `/verify`, `/ban`, `/unban` go through
 //! `ModerationService` for ledger + idempotent bot side-effect; `/help` and
-//! `/status` are stub replies. `/stats`, `/report`, `/summary` are
-//! moderator-only and built on the M3 report + summary services.
+//! `/status` are stub replies. `/stats`, `/report`, `/summary` are built on
+//! the M3 report + summary services; `/mod` edits `chat_moderators`.
+//!
+//! Permissions are enforced once, in [`dispatch`], against the sender's
+//! [`ModeratorRole`] — handlers below only run for an allowed sender.
+//!
+//! Replies to the sender use their client language when we ship it; chat
+//! output (`/stats`, `/report`, `/summary`, cooldown notices) uses the chat's
+//! `chat_config.language`. See [`crate::i18n`].
 
 use anyhow::{Context, Result};
 use chrono::Utc;
-use redis::AsyncCommands;
 use teloxide::prelude::*;
 use teloxide::types::{ChatId, ChatMemberKind, ParseMode};
 use tracing::{info, instrument, warn};
 
 use crate::api::AppState;
+use crate::i18n::{self, Lang};
 use crate::jobs::daily_report;
-use crate::models::moderation_action::ActorKind;
+use crate::models::chat_moderator::{self, ModeratorRole};
+use crate::models::moderation_action::{ActorKind, ModerationActionKind};
 use crate::services::captcha::Outcome;
 use crate::services::moderation_service::{Action, ApplyContext, Outcome as ModOutcome};
-use crate::services::report_render::{HeaderKind, Lang};
+use crate::services::outbound::{OutboundError, Priority};
+use crate::services::report_render::HeaderKind;
 use crate::services::report_service::last_24h_window;
 use crate::services::summary_service::{SkipReason, SummaryOutcome};
-use crate::services::{report_render, report_service};
+use crate::services::{appeal, moderator_sync, report_render, report_service};
 use crate::telegram::commands::Command;
+use crate::tr;
 
 /// Per-chat cooldown for `/stats` and `/summary`. Prevents rapid-fire
 /// invocations from burning OpenAI tokens or producing noise.
@@ -27,37 +37,67 @@ const COMMAND_COOLDOWN_SECS: u64 = 60;
 
 #[instrument(skip(bot, msg, state, cmd), fields(chat_id = msg.chat.id.0))]
 pub async fn dispatch(bot: Bot, msg: Message, state: AppState, cmd: Command) -> Result<()> {
+    let chat_lang = i18n::chat_lang(state.db.pool(), msg.chat.id.0).await;
+    let lang = chat_lang.for_user(msg.from.as_ref().and_then(|u| u.language_code.as_deref()));
+
+    // Sender's role, resolved only for gated commands. `Some` past this
+    // block means the gate passed.
+    let actor_role = match cmd.required_permission() {
+        None => None,
+        Some(permission) => {
+            let Some(actor) = msg.from.as_ref() else {
+                return Ok(());
+            };
+            let role = effective_role(&bot, &state, msg.chat.id, actor).await;
+            match role {
+                Some(r) if r.allows(permission) => Some(r),
+                _ => {
+                    let reply = match role {
+                        Some(r) => tr!(
+                            lang,
+                            "cmd-forbidden-role",
+                            role = r.as_db_str(),
+                            command = cmd.name()
+                        ),
+                        None => tr!(lang, "cmd-forbidden", command = cmd.name()),
+                    };
+                    send_reply(&bot, &state, msg.chat.id, reply).await;
+                    info!(?permission, ?role, "command rejected");
+                    return Ok(());
+                }
+            }
+        }
+    };
+
     match cmd {
         Command::Help => {
-            let _ = bot
-                .send_message(
-                    msg.chat.id,
-                    "Vixen anti-spam bot — captcha + spam pipeline.\n\
-                     /help — this message\n\
-                     /status — bot status in this chat\n\
-                     /verify (reply or <user_id>) — moderator: manually verify a user\n\
-                     /ban (reply or <user_id> [reason]) — moderator: ban a user\n\
-                     /unban <user_id> — moderator: lift a ban",
-                )
-                .await;
+            send_reply(&bot, &state, msg.chat.id, tr!(lang, "cmd-help")).await;
             Ok(())
         }
         Command::Status => {
-            let _ = bot
-                .send_message(msg.chat.id, "Vixen is watching this chat.")
-                .await;
+            let mut reply = tr!(lang, "cmd-status");
+            if let Ok(me) = bot.get_me().await {
+                let link = appeal::deep_link(me.username(), msg.chat.id.0);
+                reply.push('\n');
+                reply.push_str(&tr!(lang, "cmd-status-appeal", link = link));
+            }
+            send_reply(&bot, &state, msg.chat.id, reply).await;
             Ok(())
         }
-        Command::Verify(arg) => verify(bot, msg, state, arg.trim()).await,
-        Command::Ban(arg) => ban(bot, msg, state, arg.trim()).await,
-        Command::Unban(arg) => unban(bot, msg, state, arg.trim()).await,
-        Command::Stats => stats(bot, msg, state).await,
-        Command::Report => report(bot, msg, state).await,
-        Command::Summary => summary(bot, msg, state).await,
+        Command::Verify(arg) => verify(bot, msg, state, lang, arg.trim()).await,
+        Command::Ban(arg) => ban(bot, msg, state, lang, arg.trim()).await,
+        Command::Unban(arg) => unban(bot, msg, state, lang, arg.trim()).await,
+        Command::Stats => stats(bot, msg, state, chat_lang).await,
+        Command::Report => report(bot, msg, state, chat_lang).await,
+        Command::Summary => summary(bot, msg, state, chat_lang).await,
+        Command::Mod(arg) => match actor_role {
+            Some(role) => moderators(bot, msg, state, lang, role, arg.trim()).await,
+            None => Ok(()),
+        },
     }
 }
 
-async fn verify(bot: Bot, msg: Message, state: AppState, arg: &str) -> Result<()> {
+async fn verify(bot: Bot, msg: Message, state: AppState, lang: Lang, arg: &str) -> Result<()> {
     let actor = match msg.from.as_ref() {
         Some(u) => u,
         None => {
@@ -65,29 +105,17 @@ async fn verify(bot: Bot, msg: Message, state: AppState, arg: &str) -> Result<()
         }
     };
 
-    if !is_moderator_or_admin(&bot, &state, msg.chat.id, actor).await {
-        let _ = bot
-            .send_message(
-                msg.chat.id,
-                "Only chat moderators or admins can run /verify.",
-            )
-            .await;
-        return Ok(());
-    }
-
     let target_user_id = match resolve_target(&msg, arg) {
         Some(id) => id,
         None => {
-            let _ = bot
-                .send_message(msg.chat.id, "Reply to a user or pass /verify <user_id>.")
-                .await;
+            send_reply(&bot, &state, msg.chat.id, tr!(lang, "verify-usage")).await;
             return Ok(());
         }
     };
 
-    let outcome = state
+    let (outcome, _) = state
         .captcha
-        .verify_manual(msg.chat.id.0, target_user_id, actor.id.0 as i64)
+        .verify_manual(msg.chat.id.0, target_user_id, Some(actor.id.0 as i64))
         .await?;
 
     // Populate the Redis verified cache so the next join skips a PG round-trip.
@@ -101,11 +129,11 @@ async fn verify(bot: Bot, msg: Message, state: AppState, arg: &str) -> Result<()
     }
 
     let reply = match outcome {
-        Outcome::Solved => format!("Verified user {target_user_id}."),
-        Outcome::AlreadyVerified => format!("User {target_user_id} was already verified."),
-        _ => "Unexpected verify state.".to_string(),
+        Outcome::Solved => tr!(lang, "verify-done", user_id = target_user_id),
+        Outcome::AlreadyVerified => tr!(lang, "verify-already", user_id = target_user_id),
+        _ => tr!(lang, "verify-unexpected"),
     };
-    let _ = bot.send_message(msg.chat.id, reply).await;
+    send_reply(&bot, &state, msg.chat.id, reply).await;
 
     info!(target_user_id, ?outcome, "/verify completed");
     Ok(())
@@ -122,75 +150,67 @@ fn resolve_target(msg: &Message, arg: &str) -> Option<i64> {
     Some(reply.from.as_ref()?.id.0 as i64)
 }
 
-/// Permission gate shared by `/verify`, `/ban`, `/unban`: moderator (DB row in
-/// `chat_moderators`, Moka 5min cache) **OR** chat admin (existing M1 admin
-/// cache, 6h Redis TTL, falls back to a live `getChatAdministrators`).
+/// Sender's effective role: their `chat_moderators` row (Moka 5min cache)
+/// when present, otherwise [`ModeratorRole::Admin`] for a chat admin
+/// (existing M1 admin cache, 6h Redis TTL, falls back to a live
+/// `getChatAdministrators`), otherwise `None`. An explicit row wins over chat
+/// admin status so an owner can deliberately narrow an admin to `viewer`.
 ///
 /// On every cache repopulation we filter out `Banned` / `Left` admins — the
 /// same rule message_gate uses — so a stale ex-admin id can't sneak into the
 /// cache via this path.
-async fn is_moderator_or_admin(
+async fn effective_role(
     bot: &Bot,
     state: &AppState,
     chat_id: ChatId,
     user: &teloxide::types::User,
-) -> bool {
+) -> Option<ModeratorRole> {
     let uid = user.id.0 as i64;
 
-    // 1. chat_moderators (DB allow-list).
-    match state.moderation.is_moderator(chat_id.0, uid).await {
-        Ok(true) => return true,
-        Ok(false) => {}
+    // 1. chat_moderators (DB roles).
+    match state.moderation.role(chat_id.0, uid).await {
+        Ok(Some(role)) => return Some(role),
+        Ok(None) => {}
         Err(e) => {
-            warn!(error = ?e, "moderation.is_moderator failed; falling back to admin check");
+            warn!(error = ?e, "moderation.role failed; falling back to admin check");
         }
     }
 
     // 2. Existing M1 admin cache → live API.
-    if let Ok(Some(admins)) = state.captcha_state.get_admins(chat_id.0).await {
-        return admins.contains(&uid);
-    }
-    match bot.get_chat_administrators(chat_id).await {
-        Ok(admins) => {
-            let ids: Vec<i64> = admins
-                .iter()
-                .filter(|a| !matches!(a.kind, ChatMemberKind::Banned(_) | ChatMemberKind::Left))
-                .map(|a| a.user.id.0 as i64)
-                .collect();
-            if let Err(e) = state.captcha_state.set_admins(chat_id.0, &ids).await {
-                warn!(error = ?e, "redis set_admins failed");
+    let is_admin = if let Ok(Some(admins)) = state.captcha_state.get_admins(chat_id.0).await {
+        admins.contains(&uid)
+    } else {
+        match bot.get_chat_administrators(chat_id).await {
+            Ok(admins) => {
+                let ids: Vec<i64> = admins
+                    .iter()
+                    .filter(|a| !matches!(a.kind, ChatMemberKind::Banned(_) | ChatMemberKind::Left))
+                    .map(|a| a.user.id.0 as i64)
+                    .collect();
+                if let Err(e) = state.captcha_state.set_admins(chat_id.0, &ids).await {
+                    warn!(error = ?e, "redis set_admins failed");
+                }
+                ids.contains(&uid)
+            }
+            Err(e) => {
+                warn!(error = %e, "get_chat_administrators failed");
+                false
             }
-            ids.contains(&uid)
-        }
-        Err(e) => {
-            warn!(error = %e, "get_chat_administrators failed");
-            false
         }
-    }
+    };
+    is_admin.then_some(ModeratorRole::Admin)
 }
 
-async fn ban(bot: Bot, msg: Message, state: AppState, arg: &str) -> Result<()> {
+async fn ban(bot: Bot, msg: Message, state: AppState, lang: Lang, arg: &str) -> Result<()> {
     let Some(actor) = msg.from.as_ref() else {
         return Ok(());
     };
 
-    if !is_moderator_or_admin(&bot, &state, msg.chat.id, actor).await {
-        let _ = bot
-            .send_message(msg.chat.id, "Only chat moderators or admins can run /ban.")
-            .await;
-        return Ok(());
-    }
-
     // Resolve target + optional reason. Reply-mode wins when both are present.
     let (target_user_id, message_id, reason) = match parse_ban_target(&msg, arg) {
         Some(t) => t,
         None => {
-            let _ = bot
-                .send_message(
-                    msg.chat.id,
-                    "Reply to a user's message or pass /ban <user_id> [reason].",
-                )
-                .await;
+            send_reply(&bot, &state, msg.chat.id, tr!(lang, "ban-usage")).await;
             return Ok(());
         }
     };
@@ -208,47 +228,36 @@ async fn ban(bot: Bot, msg: Message, state: AppState, arg: &str) -> Result<()> {
     };
 
     match state.moderation.apply(action, ctx).await {
-        Ok(ModOutcome::Applied) => {
+        Ok(ModOutcome::Applied(_)) => {
             info!(target_user_id, "/ban applied");
             // Remove the moderator's command message to keep the chat clean.
             // Best-effort: bot may not be admin, in which case the line stays.
-            if let Err(e) = bot.delete_message(msg.chat.id, msg.id).await {
+            if let Err(e) = cleanup(&bot, &state, &msg).await {
                 warn!(error = %e, "delete /ban command message failed");
             }
         }
         Ok(ModOutcome::AlreadyApplied) => {
-            let _ = bot
-                .send_message(
-                    msg.chat.id,
-                    format!("User {target_user_id} is already banned."),
-                )
-                .await;
+            send_reply(
+                &bot,
+                &state,
+                msg.chat.id,
+                tr!(lang, "ban-already", user_id = target_user_id),
+            )
+            .await;
         }
         Err(e) => {
             warn!(error = ?e, "moderation.apply (Ban) failed");
-            let _ = bot
-                .send_message(msg.chat.id, "Ban failed; check bot permissions.")
-                .await;
+            send_reply(&bot, &state, msg.chat.id, tr!(lang, "ban-failed")).await;
         }
     }
     Ok(())
 }
 
-async fn unban(bot: Bot, msg: Message, state: AppState, arg: &str) -> Result<()> {
+async fn unban(bot: Bot, msg: Message, state: AppState, lang: Lang, arg: &str) -> Result<()> {
     let Some(actor) = msg.from.as_ref() else {
         return Ok(());
     };
 
-    if !is_moderator_or_admin(&bot, &state, msg.chat.id, actor).await {
-        let _ = bot
-            .send_message(
-                msg.chat.id,
-                "Only chat moderators or admins can run /unban.",
-            )
-            .await;
-        return Ok(());
-    }
-
     // /unban is id-only by design — replying to a banned user's old message
     // doesn't help (their messages are deleted on ban) and the moderator
     // already needs the user_id from the dashboard / audit log to find them.
@@ -256,16 +265,12 @@ async fn unban(bot: Bot, msg: Message, state: AppState, arg: &str) -> Result<()>
         Some(s) => match s.parse::<i64>() {
             Ok(id) if id > 0 => id,
             _ => {
-                let _ = bot
-                    .send_message(msg.chat.id, "Usage: /unban <user_id>")
-                    .await;
+                send_reply(&bot, &state, msg.chat.id, tr!(lang, "unban-usage")).await;
                 return Ok(());
             }
         },
         None => {
-            let _ = bot
-                .send_message(msg.chat.id, "Usage: /unban <user_id>")
-                .await;
+            send_reply(&bot, &state, msg.chat.id, tr!(lang, "unban-usage")).await;
             return Ok(());
         }
     };
@@ -279,53 +284,188 @@ async fn unban(bot: Bot, msg: Message, state: AppState, arg: &str) -> Result<()>
     };
 
     match state.moderation.apply(Action::Unban, ctx).await {
-        Ok(ModOutcome::Applied) => {
+        Ok(ModOutcome::Applied(_)) => {
             info!(target_user_id, "/unban applied");
-            if let Err(e) = bot.delete_message(msg.chat.id, msg.id).await {
+            if let Err(e) = cleanup(&bot, &state, &msg).await {
                 warn!(error = %e, "delete /unban command message failed");
             }
         }
         Ok(ModOutcome::AlreadyApplied) => {
-            let _ = bot
-                .send_message(
-                    msg.chat.id,
-                    format!("User {target_user_id} is not currently banned."),
-                )
-                .await;
+            send_reply(
+                &bot,
+                &state,
+                msg.chat.id,
+                tr!(lang, "unban-not-banned", user_id = target_user_id),
+            )
+            .await;
         }
         Err(e) => {
             warn!(error = ?e, "moderation.apply (Unban) failed");
-            let _ = bot
-                .send_message(msg.chat.id, "Unban failed; check bot permissions.")
-                .await;
+            send_reply(&bot, &state, msg.chat.id, tr!(lang, "unban-failed")).await;
         }
     }
     Ok(())
 }
 
-// ── M3: /stats /report /summary ─────────────────────────────────────────
+// ── /mod grant|revoke ───────────────────────────────────────────────────
 
+/// `/mod grant` upserts a `chat_moderators` row with `granted_by` = the
+/// sender; `/mod revoke` deletes it. The sender may only touch roles strictly
+/// below their own (see [`ModeratorRole::can_manage`]), both for the role
+/// being granted and for the role the target already holds.
 #[instrument(skip(bot, msg, state), fields(chat_id = msg.chat.id.0))]
-async fn stats(bot: Bot, msg: Message, state: AppState) -> Result<()> {
+async fn moderators(
+    bot: Bot,
+    msg: Message,
+    state: AppState,
+    lang: Lang,
+    actor_role: ModeratorRole,
+    arg: &str,
+) -> Result<()> {
     let Some(actor) = msg.from.as_ref() else {
         return Ok(());
     };
-    if !is_moderator_or_admin(&bot, &state, msg.chat.id, actor).await {
-        let _ = bot
-            .send_message(
-                msg.chat.id,
-                "Only chat moderators or admins can run /stats.",
-            )
-            .await;
+    let chat_id = msg.chat.id.0;
+    let actor_id = actor.id.0 as i64;
+    let (sub, rest) = arg
+        .split_once(char::is_whitespace)
+        .map_or((arg, ""), |(s, r)| (s, r.trim()));
+
+    let Some((target_user_id, role_arg)) = parse_mod_target(&msg, rest) else {
+        send_reply(&bot, &state, msg.chat.id, tr!(lang, "mod-usage")).await;
+        return Ok(());
+    };
+    let current = chat_moderator::role_of(state.db.pool(), chat_id, target_user_id).await?;
+    if let Some(current) = current.filter(|r| !actor_role.can_manage(*r)) {
+        send_reply(
+            &bot,
+            &state,
+            msg.chat.id,
+            tr!(
+                lang,
+                "mod-cannot-change",
+                role = actor_role.as_db_str(),
+                target_role = current.as_db_str()
+            ),
+        )
+        .await;
         return Ok(());
     }
-    if let Some(remaining) = check_cooldown(&state, msg.chat.id.0, "stats").await? {
-        let _ = bot
-            .send_message(
-                msg.chat.id,
-                format!("/stats: подождите ещё {remaining} секунд."),
-            )
-            .await;
+
+    let reply = match sub {
+        "grant" => {
+            let role = match role_arg {
+                None => ModeratorRole::Moderator,
+                Some(s) => match ModeratorRole::from_db_str(s) {
+                    Some(r) => r,
+                    None => {
+                        send_reply(&bot, &state, msg.chat.id, tr!(lang, "mod-usage")).await;
+                        return Ok(());
+                    }
+                },
+            };
+            if !actor_role.can_manage(role) {
+                tr!(
+                    lang,
+                    "mod-cannot-grant",
+                    role = actor_role.as_db_str(),
+                    target_role = role.as_db_str()
+                )
+            } else {
+                // The role and its ledger row land together or not at all.
+                let mut tx = state.db.pool().begin().await.context("BEGIN /mod tx")?;
+                chat_moderator::grant(&mut *tx, chat_id, target_user_id, role, actor_id).await?;
+                moderator_sync::record_role_change(
+                    &mut *tx,
+                    chat_id,
+                    target_user_id,
+                    ModerationActionKind::RoleGrant,
+                    ActorKind::Moderator,
+                    Some(actor_id),
+                    role,
+                )
+                .await?;
+                tx.commit().await.context("COMMIT /mod tx")?;
+                state
+                    .moderation
+                    .invalidate_moderator(chat_id, target_user_id)
+                    .await;
+                info!(
+                    target_user_id,
+                    role = role.as_db_str(),
+                    "/mod grant applied"
+                );
+                tr!(
+                    lang,
+                    "mod-granted",
+                    user_id = target_user_id,
+                    role = role.as_db_str()
+                )
+            }
+        }
+        "revoke" => {
+            let mut tx = state.db.pool().begin().await.context("BEGIN /mod tx")?;
+            let removed = chat_moderator::revoke(&mut *tx, chat_id, target_user_id).await?;
+            if let (true, Some(role)) = (removed, current) {
+                moderator_sync::record_role_change(
+                    &mut *tx,
+                    chat_id,
+                    target_user_id,
+                    ModerationActionKind::RoleRevoke,
+                    ActorKind::Moderator,
+                    Some(actor_id),
+                    role,
+                )
+                .await?;
+            }
+            tx.commit().await.context("COMMIT /mod tx")?;
+            if removed {
+                state
+                    .moderation
+                    .invalidate_moderator(chat_id, target_user_id)
+                    .await;
+                info!(target_user_id, "/mod revoke applied");
+                tr!(lang, "mod-revoked", user_id = target_user_id)
+            } else {
+                tr!(lang, "mod-no-role", user_id = target_user_id)
+            }
+        }
+        _ => tr!(lang, "mod-usage"),
+    };
+    send_reply(&bot, &state, msg.chat.id, reply).await;
+    Ok(())
+}
+
+/// Returns `(target_user_id, optional role word)`. Reply-mode wins, same as
+/// `/ban`: with a reply, `rest` is just the role; otherwise it is
+/// `<user_id> [role]`.
+fn parse_mod_target<'a>(msg: &Message, rest: &'a str) -> Option<(i64, Option<&'a str>)> {
+    let mut words = rest.split_whitespace();
+    if let Some(reply) = msg.reply_to_message() {
+        let target = reply.from.as_ref()?.id.0 as i64;
+        return Some((target, words.next()));
+    }
+    let id = words.next()?.parse::<i64>().ok().filter(|n| *n > 0)?;
+    Some((id, words.next()))
+}
+
+// ── M3: /stats /report /summary ─────────────────────────────────────────
+
+#[instrument(skip(bot, msg, state), fields(chat_id = msg.chat.id.0))]
+async fn stats(bot: Bot, msg: Message, state: AppState, lang: Lang) -> Result<()> {
+    if let Some(remaining) = check_cooldown(&state, msg.chat.id.0, "stats").await {
+        send_reply(
+            &bot,
+            &state,
+            msg.chat.id,
+            tr!(
+                lang,
+                "cmd-cooldown",
+                command = "/stats",
+                seconds = remaining
+            ),
+        )
+        .await;
         return Ok(());
     }
 
@@ -339,49 +479,35 @@ async fn stats(bot: Bot, msg: Message, state: AppState) -> Result<()> {
     let day_start = report_service::day_window_local(today, tz).0;
     let to = Utc::now();
     let report = state.reports.aggregate(chat_id, day_start, to).await?;
-    let lang = chat_language(&state, chat_id).await;
     let body = report_render::render(&report, lang, HeaderKind::Today);
 
-    let _ = bot
+    let request = bot
         .send_message(msg.chat.id, body)
-        .parse_mode(ParseMode::MarkdownV2)
+        .parse_mode(ParseMode::MarkdownV2);
+    let _ = state
+        .outbound
+        .send(chat_id, Priority::Interactive, request)
         .await;
     info!("/stats delivered");
     Ok(())
 }
 
 #[instrument(skip(bot, msg, state), fields(chat_id = msg.chat.id.0))]
-async fn report(bot: Bot, msg: Message, state: AppState) -> Result<()> {
-    let Some(actor) = msg.from.as_ref() else {
-        return Ok(());
-    };
-    if !is_moderator_or_admin(&bot, &state, msg.chat.id, actor).await {
-        let _ = bot
-            .send_message(
-                msg.chat.id,
-                "Only chat moderators or admins can run /report.",
-            )
-            .await;
-        return Ok(());
-    }
-
+async fn report(bot: Bot, msg: Message, state: AppState, lang: Lang) -> Result<()> {
     let chat_id = msg.chat.id.0;
     let (report_date, tz) =
         daily_report::current_report_date_with_tz(state.db.pool(), chat_id).await?;
     let (from, to) = report_service::day_window_local(report_date, tz);
     let aggregated = state.reports.aggregate(chat_id, from, to).await?;
 
-    let (lang_str, summary_enabled) = match fetch_lang_and_summary(&state, chat_id).await? {
-        Some(p) => p,
-        None => ("ru".to_string(), false),
-    };
+    let summary_enabled = fetch_summary_enabled(&state, chat_id).await?;
 
     if let Err(e) = daily_report::deliver(
         &bot,
         &state,
         chat_id,
         report_date,
-        &lang_str,
+        lang.as_db_str(),
         summary_enabled,
         &aggregated,
         HeaderKind::OnDemand,
@@ -389,16 +515,11 @@ async fn report(bot: Bot, msg: Message, state: AppState) -> Result<()> {
     .await
     {
         warn!(error = ?e, "/report deliver failed");
-        let _ = bot
-            .send_message(
-                msg.chat.id,
-                "Не удалось сгенерировать отчёт. Подробности в логе.",
-            )
-            .await;
+        send_reply(&bot, &state, msg.chat.id, tr!(lang, "report-failed")).await;
     } else {
         info!("/report delivered");
         // Best-effort: drop the moderator's command message to keep the chat clean.
-        if let Err(e) = bot.delete_message(msg.chat.id, msg.id).await {
+        if let Err(e) = cleanup(&bot, &state, &msg).await {
             warn!(error = %e, "delete /report command message failed");
         }
     }
@@ -406,129 +527,120 @@ async fn report(bot: Bot, msg: Message, state: AppState) -> Result<()> {
 }
 
 #[instrument(skip(bot, msg, state), fields(chat_id = msg.chat.id.0))]
-async fn summary(bot: Bot, msg: Message, state: AppState) -> Result<()> {
-    let Some(actor) = msg.from.as_ref() else {
-        return Ok(());
-    };
-    if !is_moderator_or_admin(&bot, &state, msg.chat.id, actor).await {
-        let _ = bot
-            .send_message(
-                msg.chat.id,
-                "Only chat moderators or admins can run /summary.",
-            )
-            .await;
-        return Ok(());
-    }
-    if let Some(remaining) = check_cooldown(&state, msg.chat.id.0, "summary").await? {
-        let _ = bot
-            .send_message(
-                msg.chat.id,
-                format!("/summary: подождите ещё {remaining} секунд."),
-            )
-            .await;
+async fn summary(bot: Bot, msg: Message, state: AppState, lang: Lang) -> Result<()> {
+    if let Some(remaining) = check_cooldown(&state, msg.chat.id.0, "summary").await {
+        send_reply(
+            &bot,
+            &state,
+            msg.chat.id,
+            tr!(
+                lang,
+                "cmd-cooldown",
+                command = "/summary",
+                seconds = remaining
+            ),
+        )
+        .await;
         return Ok(());
     }
 
     let chat_id = msg.chat.id.0;
     let now = Utc::now();
     let (from, to) = last_24h_window(now);
-    let lang = chat_language_str(&state, chat_id).await;
 
-    let outcome = match state.summary.summarize(chat_id, from, to, &lang).await {
+    let outcome = match state
+        .summary
+        .summarize(chat_id, from, to, lang.as_db_str())
+        .await
+    {
         Ok(o) => o,
         Err(e) => {
             warn!(error = ?e, "/summary failed");
-            let _ = bot
-                .send_message(msg.chat.id, "Сводка временно недоступна. Попробуйте позже.")
-                .await;
+            send_reply(&bot, &state, msg.chat.id, tr!(lang, "summary-unavailable")).await;
             return Ok(());
         }
     };
 
     let reply = match outcome {
         SummaryOutcome::Generated { text, .. } => text,
-        SummaryOutcome::Skipped { reason } => format_skip_reason(reason),
+        SummaryOutcome::Skipped { reason } => format_skip_reason(lang, reason),
     };
-    let _ = bot.send_message(msg.chat.id, reply).await;
+    send_reply(&bot, &state, msg.chat.id, reply).await;
     info!("/summary delivered");
     Ok(())
 }
 
-fn format_skip_reason(reason: SkipReason) -> String {
-    match reason {
-        SkipReason::NoApiKey => "OpenAI ключ не задан для этого чата. \
-             Задайте chat_config.openai_api_key через дашборд."
-            .to_string(),
-        SkipReason::Disabled => {
-            "AI-сводка отключена для этого чата (chat_config.summary_enabled = FALSE).".to_string()
-        }
-        SkipReason::NoMessages => "Нет сообщений для сводки. \
-             Включите chat_config.log_allowed_messages, чтобы бот сохранял сообщения для AI."
-            .to_string(),
-        SkipReason::BudgetExhausted { used, budget } => {
-            format!("Дневной лимит токенов исчерпан ({used} из {budget}).")
-        }
-    }
+/// Post a command reply. Best-effort, like every reply here: a failure is
+/// not worth failing the command over.
+async fn send_reply(bot: &Bot, state: &AppState, chat_id: ChatId, text: String) {
+    let request = bot.send_message(chat_id, text);
+    let _ = state
+        .outbound
+        .send(chat_id.0, Priority::Interactive, request)
+        .await;
 }
 
-/// Returns the chat's `language` column ("ru" / "en") as a [`Lang`] enum.
-/// Falls back to RU on any DB error so a transient hiccup doesn't surface
-/// as user-visible noise.
-async fn chat_language(state: &AppState, chat_id: i64) -> Lang {
-    Lang::from_db_str(&chat_language_str(state, chat_id).await)
+/// Delete the moderator's command message to keep the chat clean.
+async fn cleanup(bot: &Bot, state: &AppState, msg: &Message) -> Result<(), OutboundError> {
+    let request = bot.delete_message(msg.chat.id, msg.id);
+    state
+        .outbound
+        .send(msg.chat.id.0, Priority::Background, request)
+        .await
+        .map(|_| ())
 }
 
-async fn chat_language_str(state: &AppState, chat_id: i64) -> String {
-    match sqlx::query_scalar!(
-        r#"SELECT language FROM chat_config WHERE chat_id = $1"#,
-        chat_id,
-    )
-    .fetch_optional(state.db.pool())
-    .await
-    {
-        Ok(Some(s)) => s,
-        _ => "ru".to_string(),
+fn format_skip_reason(lang: Lang, reason: SkipReason) -> String {
+    match reason {
+        SkipReason::NoApiKey => tr!(lang, "summary-skip-no-key"),
+        SkipReason::Disabled => tr!(lang, "summary-skip-disabled"),
+        SkipReason::NoMessages => tr!(lang, "summary-skip-no-messages"),
+        SkipReason::BudgetExhausted { used, budget } => {
+            tr!(lang, "summary-skip-budget", used = used, budget = budget)
+        }
     }
 }
 
-async fn fetch_lang_and_summary(state: &AppState, chat_id: i64) -> Result<Option<(String, bool)>> {
-    let row = sqlx::query!(
-        r#"SELECT language, summary_enabled FROM chat_config WHERE chat_id = $1"#,
+async fn fetch_summary_enabled(state: &AppState, chat_id: i64) -> Result<bool> {
+    let enabled = sqlx::query_scalar!(
+        r#"SELECT summary_enabled FROM chat_config WHERE chat_id = $1"#,
         chat_id,
     )
     .fetch_optional(state.db.pool())
     .await
-    .context("SELECT chat_config (lang+summary)")?;
-    Ok(row.map(|r| (r.language, r.summary_enabled)))
+    .context("SELECT chat_config.summary_enabled")?;
+    Ok(enabled.unwrap_or(false))
 }
 
 /// Returns `Some(remaining_secs)` if a cooldown is active, `None` if the
 /// caller may proceed. Sets the cooldown key on a clear path. Implemented
 /// via `SET NX EX` so the check + set is one round-trip and stays correct
-/// under concurrency.
-async fn check_cooldown(state: &AppState, chat_id: i64, cmd: &str) -> Result<Option<u64>> {
+/// under concurrency. Without Redis there is no cooldown: the command runs,
+/// as `POST /reports/generate` does without its rate limit.
+async fn check_cooldown(state: &AppState, chat_id: i64, cmd: &str) -> Option<u64> {
     let key = format!("cmd:{cmd}:{chat_id}");
-    let mut conn = state
-        .redis
-        .pool()
-        .get()
-        .await
-        .context("redis pool acquire (cooldown)")?;
-    let acquired: Option<String> = redis::cmd("SET")
-        .arg(&key)
+    let mut set = redis::cmd("SET");
+    set.arg(&key)
         .arg("1")
         .arg("NX")
         .arg("EX")
-        .arg(COMMAND_COOLDOWN_SECS)
-        .query_async(&mut *conn)
-        .await
-        .context("SET NX EX cooldown")?;
+        .arg(COMMAND_COOLDOWN_SECS);
+    let acquired: Option<String> = match state.redis.query(&set).await {
+        Ok(v) => v,
+        Err(e) => {
+            warn!(error = %e, cmd, "cooldown check failed; running without it");
+            return None;
+        }
+    };
     if acquired.is_some() {
-        Ok(None)
-    } else {
-        let ttl: i64 = conn.ttl(&key).await.unwrap_or(-1);
-        Ok(Some(ttl.max(1) as u64))
+        return None;
     }
+    let ttl: i64 = state
+        .redis
+        .query(&redis::Cmd::ttl(&key))
+        .await
+        .unwrap_or(-1);
+    Some(ttl.max(1) as u64)
 }
 
 /// Returns `(target_user_id, message_id_for_ledger, reason)`. Reply-mode
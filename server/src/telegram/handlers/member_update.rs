@@ -1,9 +1,15 @@
-//! `chat_member` updates — issue a captcha to every fresh joiner.
+//! `chat_member` updates — issue a captcha to every fresh joiner, and mirror
+//! admin promotions / demotions into `chat_moderators`.
 //!
 //! M1 policy: we do **not** restrict or kick the user. The captcha is purely
 //! a gate — if they fail or ignore it, their messages keep getting deleted by
 //! `message_gate` until they pass. So this handler only sends the photo and
 //! anchors the Redis meta; no `restrict_chat_member` call.
+//!
+//! Any transition that changes the Telegram-derived role (member → admin,
+//! admin → owner, admin → member/left/banned) goes through
+//! `moderator_sync::sync_member` first and drops the `cap:admins` cache; the
+//! `moderator_sync` job catches whatever this branch misses.
 
 use anyhow::Result;
 use teloxide::prelude::*;
@@ -11,8 +17,12 @@ use teloxide::types::{ChatMemberKind, ChatMemberUpdated, InputFile};
 use tracing::{info, instrument, warn};
 
 use crate::api::AppState;
+use crate::i18n;
 use crate::services::captcha::caption::caption_initial;
 use crate::services::captcha::short_id;
+use crate::services::live_feed::LiveEventKind;
+use crate::services::moderator_sync;
+use crate::services::outbound::Priority;
 
 #[instrument(
     skip(bot, event, state),
@@ -22,9 +32,15 @@ use crate::services::captcha::short_id;
     )
 )]
 pub async fn handle(bot: Bot, event: ChatMemberUpdated, state: AppState) -> Result<()> {
+    if is_admin_change(&event) {
+        sync_admin_change(&event, &state).await;
+    }
     if !is_fresh_join(&event) {
         return Ok(());
     }
+    if let Err(e) = state.user_info.observe(&event.new_chat_member.user).await {
+        warn!(error = ?e, "user_info observe failed");
+    }
     if matches!(
         event.new_chat_member.kind,
         ChatMemberKind::Owner(_) | ChatMemberKind::Administrator(_)
@@ -62,15 +78,32 @@ pub async fn handle(bot: Bot, event: ChatMemberUpdated, state: AppState) -> Resu
             return Ok(());
         }
     };
+    state
+        .live
+        .publish(
+            chat_id.0,
+            LiveEventKind::CaptchaIssued {
+                user_id: uid,
+                expires_at: issued.expires_at,
+            },
+        )
+        .await;
 
-    let caption = caption_initial(&mention(&event.new_chat_member.user), issued.attempts_left);
+    let user = &event.new_chat_member.user;
+    let lang = i18n::chat_lang(state.db.pool(), chat_id.0)
+        .await
+        .for_user(user.language_code.as_deref());
+    let caption = caption_initial(lang, &mention(user), issued.attempts_left);
 
     let photo = InputFile::memory(issued.image_webp).file_name("captcha.webp");
-    let send_result = bot
+    let request = bot
         .send_photo(chat_id, photo)
         .caption(caption)
         .reply_markup(issued.keyboard)
-        .protect_content(true)
+        .protect_content(true);
+    let send_result = state
+        .outbound
+        .send(chat_id.0, Priority::Interactive, request)
         .await;
 
     match send_result {
@@ -112,6 +145,36 @@ pub async fn handle(bot: Bot, event: ChatMemberUpdated, state: AppState) -> Resu
     Ok(())
 }
 
+/// Best-effort: a failure leaves the row for the periodic sync to fix.
+async fn sync_admin_change(event: &ChatMemberUpdated, state: &AppState) {
+    let chat_id = event.chat.id.0;
+    let user = &event.new_chat_member.user;
+    if let Err(e) = state.captcha_state.clear_admins(chat_id).await {
+        warn!(error = ?e, "redis clear_admins failed");
+    }
+    if user.is_bot {
+        return;
+    }
+    if let Err(e) = moderator_sync::sync_member(
+        state.db.pool(),
+        &state.moderation,
+        chat_id,
+        user.id.0 as i64,
+        &event.new_chat_member.kind,
+    )
+    .await
+    {
+        warn!(error = ?e, "moderator sync_member failed");
+    }
+}
+
+/// True when the update moves the user across the admin / owner boundary or
+/// between admin and owner.
+fn is_admin_change(event: &ChatMemberUpdated) -> bool {
+    moderator_sync::telegram_role(&event.old_chat_member.kind)
+        != moderator_sync::telegram_role(&event.new_chat_member.kind)
+}
+
 /// True for transitions Left/Kicked → present-in-chat. "Present" includes
 /// `Restricted { is_member: true }` because chats with default-restricted
 /// permissions deliver fresh joins in that state — without this branch the
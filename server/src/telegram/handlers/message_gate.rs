@@ -16,8 +16,11 @@
 //!    would be a worse outcome than missing one. Doing the admin check
 //!    BEFORE the verified check costs one Redis lookup per message but
 //!    guarantees a verified admin can't accidentally trip `spam.inspect`.
-//! 2. `verified_users` — Redis cache, then PG. Most healthy-chat messages
-//!    hit this; on hit we run the spam pipeline and return.
+//!    While Redis is unavailable the `chat_moderators` mirror (owner /
+//!    admin rows) answers instead.
+//! 2. `verified_users` — Redis cache, then PG (PG only while Redis is
+//!    unavailable). Most healthy-chat messages hit this; on hit we run the
+//!    spam pipeline and return.
 //! 3. Otherwise: delete the message; if no live challenge → issue + send;
 //!    if a live challenge already exists → just delete (don't spam the chat
 //!    with multiple captcha photos for one user).
@@ -33,11 +36,14 @@ use teloxide::types::{ChatId, ChatMemberKind, InputFile};
 use tracing::{info, instrument, warn};
 
 use crate::api::AppState;
+use crate::i18n;
 use crate::models::daily_stats::{self, Metric};
-use crate::models::moderation_action::ActorKind;
+use crate::models::moderation_action::{ActorKind, PublicReason};
 use crate::services::captcha::caption::caption_initial;
 use crate::services::captcha::short_id;
+use crate::services::live_feed::LiveEventKind;
 use crate::services::moderation_service::{Action, ApplyContext};
+use crate::services::outbound::Priority;
 use crate::services::spam::service::Verdict;
 
 #[instrument(
@@ -62,6 +68,10 @@ pub async fn handle(bot: Bot, msg: Message, state: AppState) -> Result<()> {
     {
         warn!(error = ?e, "daily_stats messages_seen bump failed");
     }
+    // Username for the dashboard's user lists. Same best-effort rule.
+    if let Err(e) = state.user_info.observe(user).await {
+        warn!(error = ?e, "user_info observe failed");
+    }
 
     if is_chat_admin(&bot, &state, chat_id.0, uid).await {
         return Ok(());
@@ -74,7 +84,15 @@ pub async fn handle(bot: Bot, msg: Message, state: AppState) -> Result<()> {
     // Unverified, non-admin → delete the message. Best-effort: if delete fails
     // (bot not admin, message already gone), the captcha still gets posted so
     // at least the user has a path to verification.
-    if let Err(e) = bot.delete_message(chat_id, msg.id).await {
+    if let Err(e) = state
+        .outbound
+        .send(
+            chat_id.0,
+            Priority::Moderation,
+            bot.delete_message(chat_id, msg.id),
+        )
+        .await
+    {
         warn!(error = %e, "delete_message failed (bot likely not admin)");
     }
 
@@ -115,14 +133,30 @@ async fn issue_and_post(
             return;
         }
     };
+    state
+        .live
+        .publish(
+            chat_id.0,
+            LiveEventKind::CaptchaIssued {
+                user_id: uid,
+                expires_at: issued.expires_at,
+            },
+        )
+        .await;
 
-    let caption = caption_initial(&mention(user), issued.attempts_left);
+    let lang = i18n::chat_lang(state.db.pool(), chat_id.0)
+        .await
+        .for_user(user.language_code.as_deref());
+    let caption = caption_initial(lang, &mention(user), issued.attempts_left);
     let photo = InputFile::memory(issued.image_webp).file_name("captcha.webp");
-    let sent = match bot
+    let request = bot
         .send_photo(chat_id, photo)
         .caption(caption)
         .reply_markup(issued.keyboard)
-        .protect_content(true)
+        .protect_content(true);
+    let sent = match state
+        .outbound
+        .send(chat_id.0, Priority::Interactive, request)
         .await
     {
         Ok(m) => m,
@@ -180,6 +214,8 @@ async fn is_verified(state: &AppState, chat_id: i64, user_id: i64) -> bool {
 }
 
 async fn is_chat_admin(bot: &Bot, state: &AppState, chat_id: i64, user_id: i64) -> bool {
+    // Redis down: `get_admins` answers from its in-process copy, and a miss
+    // there falls through to Telegram like a Redis miss.
     if let Ok(Some(list)) = state.captcha_state.get_admins(chat_id).await {
         return list.contains(&user_id);
     }
@@ -229,6 +265,22 @@ async fn run_spam_pipeline(state: &AppState, msg: &Message, chat_id: i64, user_i
         }
     };
 
+    if let Verdict::Delete { reason_json } | Verdict::Ban { reason_json, .. } = &verdict {
+        let PublicReason {
+            matched_rules,
+            score,
+            ..
+        } = PublicReason::from_verdict(reason_json);
+        let event = LiveEventKind::SpamVerdict {
+            user_id,
+            message_id: msg.id.0,
+            verdict: verdict.label().to_owned(),
+            matched_rules,
+            score,
+        };
+        state.live.publish(chat_id, event).await;
+    }
+
     let (action, ctx) = match verdict {
         Verdict::Allow => {
             // Optionally log the message body for the AI-summary feature.
@@ -1,7 +1,9 @@
 //! Telegram update handlers. Each module owns one update kind. Common rules
 //! live in `server/docs/rules/telegram-handlers.md`.
 
+pub mod appeal;
 pub mod captcha;
 pub mod commands;
 pub mod member_update;
 pub mod message_gate;
+pub mod mod_log;
@@ -0,0 +1,142 @@
+//! Moderation log callback handler — the "Unban" / "Restore verification"
+//! buttons on `mod_log` entries land here.
+//!
+//! Callback data scheme is `vl:{op}:{chat_id}:{user_id}` (see
+//! `services::mod_log`). These presses arrive from the log channel, not from
+//! a watched chat, so the dispatcher routes them outside the watched-chats
+//! filter and this handler does the scoping itself:
+//!
+//!   * `chat_id` must be a watched chat;
+//!   * the press must come from that chat's `chat_config.log_chat_id`;
+//!   * the presser must hold the op's [`Permission`] in `chat_id`.
+//!
+//! A successful press removes the keyboard so the entry can't be undone twice.
+
+use anyhow::Result;
+use teloxide::prelude::*;
+use teloxide::types::MaybeInaccessibleMessage;
+use tracing::{info, instrument, warn};
+
+use crate::api::AppState;
+use crate::i18n;
+use crate::models::moderation_action::ActorKind;
+use crate::services::captcha::Outcome as CaptchaOutcome;
+use crate::services::mod_log::{self, LogOp, parse_callback};
+use crate::services::moderation_service::{Action, ApplyContext, Outcome as ModOutcome};
+use crate::services::outbound::Priority;
+use crate::tr;
+
+#[instrument(
+    skip(bot, q, state),
+    fields(
+        callback_id = %q.id,
+        from_user = q.from.id.0,
+    )
+)]
+pub async fn handle(bot: Bot, q: CallbackQuery, state: AppState) -> Result<()> {
+    let Some(data) = q.data.clone() else {
+        return Ok(());
+    };
+    let Some(parsed) = parse_callback(&data) else {
+        warn!(data = %data, "malformed mod log callback");
+        let _ = bot.answer_callback_query(&q.id).await;
+        return Ok(());
+    };
+    let Some(MaybeInaccessibleMessage::Regular(msg)) = q.message.as_ref() else {
+        let _ = bot.answer_callback_query(&q.id).await;
+        return Ok(());
+    };
+
+    // Scope: a watched chat, pressed inside its own log channel.
+    let log_chat = if state.config.chats.contains(&parsed.chat_id) {
+        mod_log::log_chat_for(state.db.pool(), parsed.chat_id).await?
+    } else {
+        None
+    };
+    if log_chat != Some(msg.chat.id.0) {
+        warn!(
+            chat_id = parsed.chat_id,
+            pressed_in = msg.chat.id.0,
+            "mod log callback outside the configured log chat"
+        );
+        let _ = bot.answer_callback_query(&q.id).await;
+        return Ok(());
+    }
+
+    let presser_id = q.from.id.0 as i64;
+    let lang = i18n::chat_lang(state.db.pool(), parsed.chat_id)
+        .await
+        .for_user(q.from.language_code.as_deref());
+    let allowed = state
+        .moderation
+        .role(parsed.chat_id, presser_id)
+        .await?
+        .is_some_and(|role| role.allows(parsed.op.permission()));
+    if !allowed {
+        let _ = bot
+            .answer_callback_query(&q.id)
+            .text(tr!(lang, "no-permission"))
+            .show_alert(false)
+            .await;
+        return Ok(());
+    }
+
+    let toast = match parsed.op {
+        LogOp::Unban => {
+            let ctx = ApplyContext {
+                chat_id: parsed.chat_id,
+                target_user_id: parsed.user_id,
+                message_id: None,
+                actor_kind: ActorKind::Moderator,
+                actor_user_id: Some(presser_id),
+            };
+            match state.moderation.apply(Action::Unban, ctx).await {
+                Ok(ModOutcome::Applied(_)) => tr!(lang, "unban-done", user_id = parsed.user_id),
+                Ok(ModOutcome::AlreadyApplied) => {
+                    tr!(lang, "unban-not-banned", user_id = parsed.user_id)
+                }
+                Err(e) => {
+                    warn!(error = ?e, "moderation.apply (Unban) failed");
+                    tr!(lang, "unban-failed")
+                }
+            }
+        }
+        LogOp::RestoreVerification => {
+            let (outcome, _) = state
+                .captcha
+                .verify_manual(parsed.chat_id, parsed.user_id, Some(presser_id))
+                .await?;
+            if let Err(e) = state
+                .captcha_state
+                .mark_verified(parsed.chat_id, parsed.user_id)
+                .await
+            {
+                warn!(error = ?e, "redis mark_verified (mod log) failed");
+            }
+            match outcome {
+                CaptchaOutcome::Solved => tr!(lang, "verify-done", user_id = parsed.user_id),
+                CaptchaOutcome::AlreadyVerified => {
+                    tr!(lang, "verify-already", user_id = parsed.user_id)
+                }
+                _ => tr!(lang, "verify-unexpected"),
+            }
+        }
+    };
+
+    let _ = bot.answer_callback_query(&q.id).text(toast).await;
+    let request = bot.edit_message_reply_markup(msg.chat.id, msg.id);
+    if let Err(e) = state
+        .outbound
+        .send(msg.chat.id.0, Priority::Interactive, request)
+        .await
+    {
+        warn!(error = %e, "remove mod log keyboard failed");
+    }
+    info!(
+        chat_id = parsed.chat_id,
+        target_user_id = parsed.user_id,
+        op = ?parsed.op,
+        "mod log action applied"
+    );
+    Ok(())
+}
@@ -0,0 +1,314 @@
+//! Prometheus metrics, exposed at `GET /metrics` (admin secret) — see
+//! `server/docs/observability.md#metrics` for the full catalogue.
+//!
+//! Instrumented code calls the typed helpers below instead of the `metrics`
+//! macros directly, so metric names, label keys and the `chat_id` bound live
+//! in one place. Every helper is a cheap no-op until [`install`] has run
+//! (unit tests, tools).
+//!
+//! Cardinality: `chat_id` labels only ever carry a watched chat
+//! (`CONFIG_CHATS`); anything else is folded into `"other"`. Routes use the
+//! matched path template, never the raw URI.
+
+use std::collections::HashSet;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use metrics::{counter, gauge, histogram};
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tracing::warn;
+
+/// Latency buckets (seconds) for request / call / job durations.
+const DURATION_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0,
+];
+/// Issue → solve buckets (seconds). People take seconds to minutes.
+const CAPTCHA_SOLVE_BUCKETS: &[f64] = &[2.0, 5.0, 10.0, 20.0, 30.0, 60.0, 120.0, 300.0, 600.0];
+/// Histogram maintenance cadence when nobody scrapes.
+const UPKEEP_INTERVAL: Duration = Duration::from_secs(60);
+/// Label value for a `chat_id` outside the watched set.
+pub const OTHER_CHAT: &str = "other";
+
+const CAPTCHA_SOLVE_SECONDS: &str = "vixen_captcha_solve_latency_seconds";
+
+static HANDLE: OnceLock<PrometheusHandle> = OnceLock::new();
+static WATCHED: OnceLock<HashSet<i64>> = OnceLock::new();
+
+/// Install the global Prometheus recorder. Idempotent — the first call wins,
+/// later calls return the same handle. `watched` bounds `chat_id` labels.
+pub fn install(watched: &[i64]) -> &'static PrometheusHandle {
+    HANDLE.get_or_init(|| {
+        let _ = WATCHED.set(watched.iter().copied().collect());
+        let recorder = PrometheusBuilder::new()
+            .set_buckets(DURATION_BUCKETS)
+            .and_then(|b| {
+                b.set_buckets_for_metric(
+                    Matcher::Full(CAPTCHA_SOLVE_SECONDS.into()),
+                    CAPTCHA_SOLVE_BUCKETS,
+                )
+            })
+            .expect("non-empty bucket lists")
+            .build_recorder();
+        let handle = recorder.handle();
+        if let Err(e) = metrics::set_global_recorder(recorder) {
+            warn!(error = %e, "metrics recorder already installed; /metrics will be empty");
+        }
+        handle
+    })
+}
+
+/// Render the exposition text, or `None` before [`install`].
+pub fn render() -> Option<String> {
+    HANDLE.get().map(PrometheusHandle::render)
+}
+
+/// Drain histogram buckets periodically so memory stays bounded between
+/// scrapes (rendering drains too). Exits on `shutdown`.
+pub fn spawn_upkeep(shutdown: CancellationToken) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(UPKEEP_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            tokio::select! {
+                biased;
+                _ = shutdown.cancelled() => return,
+                _ = interval.tick() => {
+                    if let Some(handle) = HANDLE.get() {
+                        handle.run_upkeep();
+                    }
+                }
+            }
+        }
+    })
+}
+
+/// `chat_id` label value: the id for a watched chat, [`OTHER_CHAT`]
+/// otherwise (including before [`install`]).
+pub fn chat_label(chat_id: i64) -> String {
+    match WATCHED.get() {
+        Some(watched) if watched.contains(&chat_id) => chat_id.to_string(),
+        _ => OTHER_CHAT.to_string(),
+    }
+}
+
+// ── HTTP ──────────────────────────────────────────────────────────────────
+
+pub fn http_request(method: &str, route: &str, status: u16, elapsed: Duration) {
+    let (method, route) = (method.to_owned(), route.to_owned());
+    counter!(
+        "vixen_http_requests_total",
+        "method" => method.clone(),
+        "route" => route.clone(),
+        "status" => status.to_string(),
+    )
+    .increment(1);
+    histogram!(
+        "vixen_http_request_duration_seconds",
+        "method" => method,
+        "route" => route,
+    )
+    .record(elapsed);
+}
+
+// ── Spam / captcha / moderation ───────────────────────────────────────────
+
+/// One `SpamService::inspect` call. `verdict` is `allow` / `delete` / `ban`
+/// / `error`; `rule` the first matched rule (`xxh3_dedup`, `cas`, `ngram`,
+/// `llm`) or `none`.
+pub fn spam_inspect(chat_id: i64, verdict: &'static str, rule: &str, elapsed: Duration) {
+    counter!(
+        "vixen_spam_verdicts_total",
+        "chat_id" => chat_label(chat_id),
+        "verdict" => verdict,
+        "rule" => rule.to_owned(),
+    )
+    .increment(1);
+    histogram!("vixen_spam_inspect_duration_seconds").record(elapsed);
+}
+
+/// One pass through the opt-in LLM cascade step. `outcome` is `cache_hit`,
+/// `classified`, `budget_exhausted`, `no_api_key`, `unparsed` or `error`.
+pub fn spam_llm_check(outcome: &'static str) {
+    counter!("vixen_spam_llm_checks_total", "outcome" => outcome).increment(1);
+}
+
+/// One `CaptchaService::solve` call; `solved_after` is the time since the
+/// challenge was issued, present only on a correct answer.
+pub fn captcha_solve(chat_id: i64, outcome: &'static str, solved_after: Option<Duration>) {
+    let chat = chat_label(chat_id);
+    counter!(
+        "vixen_captcha_attempts_total",
+        "chat_id" => chat.clone(),
+        "outcome" => outcome,
+    )
+    .increment(1);
+    if let Some(latency) = solved_after {
+        histogram!(CAPTCHA_SOLVE_SECONDS, "chat_id" => chat).record(latency);
+    }
+}
+
+/// One `ModerationService::apply` call. `outcome` is `applied` /
+/// `already_applied` / `error`.
+pub fn moderation_action(
+    chat_id: i64,
+    action: &'static str,
+    actor: &'static str,
+    outcome: &'static str,
+) {
+    counter!(
+        "vixen_moderation_actions_total",
+        "chat_id" => chat_label(chat_id),
+        "action" => action,
+        "actor" => actor,
+        "outcome" => outcome,
+    )
+    .increment(1);
+}
+
+/// One Bot API call made on behalf of a moderation action. `outcome` is
+/// `ok`, `dropped` (outbound backlog full) or an error class (`non_fatal`,
+/// `api_error`, `retry_after`, `network`, `other`).
+pub fn telegram_request(method: &'static str, outcome: &'static str) {
+    counter!(
+        "vixen_telegram_api_requests_total",
+        "method" => method,
+        "outcome" => outcome,
+    )
+    .increment(1);
+}
+
+/// Requests waiting for an `OutboundQueue` permit, per priority. Set on
+/// every scheduler pass.
+pub fn outbound_queue_depth(priority: &'static str, depth: usize) {
+    gauge!("vixen_outbound_queue_depth", "priority" => priority).set(depth as f64);
+}
+
+/// A request the `OutboundQueue` refused because its priority's backlog was
+/// full.
+pub fn outbound_dropped(priority: &'static str) {
+    counter!("vixen_outbound_dropped_total", "priority" => priority).increment(1);
+}
+
+/// A 429 the `OutboundQueue` waited out before retrying.
+pub fn outbound_retry_after(priority: &'static str) {
+    counter!("vixen_outbound_retry_after_total", "priority" => priority).increment(1);
+}
+
+// ── External APIs ─────────────────────────────────────────────────────────
+
+/// One `CasClient::lookup`. `source` is the tier that answered (`moka`,
+/// `redis`, `http`) or `fail_open`.
+pub fn cas_lookup(source: &'static str, verdict: &'static str) {
+    counter!(
+        "vixen_cas_lookups_total",
+        "source" => source,
+        "verdict" => verdict,
+    )
+    .increment(1);
+}
+
+pub fn cas_http(elapsed: Duration) {
+    histogram!("vixen_cas_http_duration_seconds").record(elapsed);
+}
+
+/// One HTTP attempt inside `llm::chat`. `provider` is the
+/// `chat_config.llm_provider` value; `outcome` is `ok`, `retryable`, `error`
+/// or `transport`. No `model` label: it is free text per chat, so it would
+/// grow the series without bound.
+pub fn llm_request(provider: &'static str, outcome: &'static str, elapsed: Duration) {
+    counter!(
+        "vixen_llm_requests_total",
+        "provider" => provider,
+        "outcome" => outcome,
+    )
+    .increment(1);
+    histogram!("vixen_llm_request_duration_seconds", "provider" => provider).record(elapsed);
+}
+
+/// Tokens as the provider counts them (see `llm::ChatCompletion`).
+pub fn llm_tokens(provider: &'static str, tokens: u32) {
+    counter!("vixen_llm_tokens_total", "provider" => provider).increment(u64::from(tokens));
+}
+
+/// One webhook POST by the `webhooks` job. `outcome` is `delivered`,
+/// `retry` or `dead` (out of attempts).
+pub fn webhook_delivery(outcome: &'static str) {
+    counter!("vixen_webhook_deliveries_total", "outcome" => outcome).increment(1);
+}
+
+/// A `GET /chats/{chat_id}/events` stream opened (`true`) or closed.
+pub fn live_stream(open: bool) {
+    let streams = gauge!("vixen_live_streams");
+    if open {
+        streams.increment(1.0);
+    } else {
+        streams.decrement(1.0);
+    }
+}
+
+// ── Jobs / DB ─────────────────────────────────────────────────────────────
+
+/// One background-job iteration.
+pub fn job_run(job: &'static str, ok: bool, elapsed: Duration) {
+    let outcome = if ok { "ok" } else { "error" };
+    counter!("vixen_job_runs_total", "job" => job, "outcome" => outcome).increment(1);
+    histogram!("vixen_job_run_duration_seconds", "job" => job).record(elapsed);
+    if ok {
+        let now = chrono::Utc::now().timestamp_millis() as f64 / 1000.0;
+        gauge!("vixen_job_last_success_timestamp_seconds", "job" => job).set(now);
+    }
+}
+
+/// `1` while Redis answers, `0` while the bot runs degraded without it
+/// (`database::Redis`).
+pub fn redis_available(up: bool) {
+    gauge!("vixen_redis_up").set(if up { 1.0 } else { 0.0 });
+}
+
+/// Postgres pool snapshot, sampled on every scrape.
+pub fn db_pool(size: u32, idle: usize, max: u32) {
+    let idle = idle as f64;
+    gauge!("vixen_db_pool_connections", "state" => "idle").set(idle);
+    gauge!("vixen_db_pool_connections", "state" => "in_use").set((f64::from(size) - idle).max(0.0));
+    gauge!("vixen_db_pool_max_connections").set(f64::from(max));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chat_label_is_bounded_to_watched_chats() {
+        install(&[-1001, -1002]);
+        assert_eq!(chat_label(-1001), "-1001");
+        assert_eq!(chat_label(-1002), "-1002");
+        assert_eq!(chat_label(-999), OTHER_CHAT);
+    }
+
+    #[test]
+    fn render_exposes_recorded_series() {
+        install(&[-1001]);
+        spam_inspect(-1001, "delete", "ngram", Duration::from_millis(3));
+        spam_inspect(42, "allow", "none", Duration::from_millis(1));
+        captcha_solve(-1001, "solved", Some(Duration::from_secs(7)));
+        job_run("spam_cleanup", true, Duration::from_millis(20));
+
+        let text = render().expect("installed");
+        assert!(text.contains(
+            r#"vixen_spam_verdicts_total{chat_id="-1001",verdict="delete",rule="ngram"}"#
+        ));
+        assert!(text.contains(r#"chat_id="other""#), "{text}");
+        assert!(!text.contains(r#"chat_id="42""#));
+        // Captcha latency uses its own minute-scale buckets.
+        assert!(
+            text.contains(
+                r#"vixen_captcha_solve_latency_seconds_bucket{chat_id="-1001",le="600"}"#
+            ),
+            "{text}"
+        );
+        assert!(text.contains(r#"vixen_job_runs_total{job="spam_cleanup",outcome="ok"}"#));
+        assert!(text.contains("vixen_spam_inspect_duration_seconds_bucket"));
+    }
+}
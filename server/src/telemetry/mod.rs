@@ -1,34 +1,62 @@
-//! Tracing setup. Two sinks:
+//! Tracing setup. Two sinks, plus an optional exporter:
 //!
 //! 1. **Console** — human-readable layer at `RUST_LOG`/`CONFIG_LOG_LEVEL`.
 //! 2. **File** — rolling JSON appender, daily rotation, 7-day retention.
+//! 3. **OTLP** — span export to `CONFIG_OTLP_ENDPOINT` when set; see [`otel`].
+//!
+//! Prometheus metrics live in [`metrics`] (`GET /metrics`).
 //!
 //! See `server/docs/observability.md` for span conventions and the redaction
 //! policy (raw bot tokens / `initData` / JWTs are never logged at info+; use
 //! `crate::utils::RedactedToken` or the `crate::config::secrets` newtypes).
 
+pub mod metrics;
+pub mod otel;
+
 use std::path::Path;
 
+use opentelemetry_sdk::trace::SdkTracerProvider;
 use tracing_appender::non_blocking::WorkerGuard;
 use tracing_appender::rolling::{RollingFileAppender, Rotation};
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
-use tracing_subscriber::{EnvFilter, fmt};
+use tracing_subscriber::{EnvFilter, Layer, fmt};
+
+/// Extra directive for the OTLP layer only: sqlx logs each statement at
+/// `debug` under `sqlx::query`, which turns into a span event on the span
+/// that ran it. Console and file output keep the configured level.
+const OTLP_SQLX_DIRECTIVE: &str = "sqlx::query=debug";
+
+/// Held for the lifetime of the process. Dropping it flushes buffered spans
+/// to the collector first, then the non-blocking JSON file writer.
+pub struct TelemetryGuard {
+    tracer_provider: Option<SdkTracerProvider>,
+    _file: WorkerGuard,
+}
+
+impl Drop for TelemetryGuard {
+    fn drop(&mut self) {
+        if let Some(Err(e)) = self.tracer_provider.take().map(|p| p.shutdown()) {
+            eprintln!("OTLP tracer shutdown failed: {e}");
+        }
+    }
+}
 
 /// Initialise the global tracing subscriber.
 ///
 /// Filter precedence: `RUST_LOG` env > `default_level` arg > `info`.
-///
-/// Returns a `WorkerGuard` that MUST be held for the lifetime of the process —
-/// dropping it flushes and closes the non-blocking JSON file writer.
-pub fn init(default_level: &str, log_dir: impl AsRef<Path>) -> WorkerGuard {
-    let filter = EnvFilter::try_from_default_env()
-        .or_else(|_| EnvFilter::try_new(default_level))
-        .unwrap_or_else(|_| EnvFilter::new("info"));
-
+/// `otlp_endpoint` enables span export; a collector that can't be configured
+/// is reported on stderr and the server runs without export.
+pub fn init(
+    default_level: &str,
+    log_dir: impl AsRef<Path>,
+    otlp_endpoint: Option<&str>,
+    otlp_sample_ratio: f64,
+) -> TelemetryGuard {
     let console = fmt::layer()
         .with_target(false)
-        .with_ansi(crate::build_info::IS_DEV);
+        .with_ansi(crate::build_info::IS_DEV)
+        .with_filter(env_filter(default_level));
 
     let appender = RollingFileAppender::builder()
         .rotation(Rotation::DAILY)
@@ -39,13 +67,38 @@ pub fn init(default_level: &str, log_dir: impl AsRef<Path>) -> WorkerGuard {
         .expect("rolling file appender");
 
     let (writer, guard) = tracing_appender::non_blocking(appender);
-    let json = fmt::layer().json().with_writer(writer);
+    let json = fmt::layer()
+        .json()
+        .with_writer(writer)
+        .with_filter(env_filter(default_level));
+
+    let (otlp, tracer_provider) = match otlp_endpoint.map(|e| otel::layer(e, otlp_sample_ratio)) {
+        Some(Ok((layer, provider))) => {
+            let filter = env_filter(default_level)
+                .add_directive(OTLP_SQLX_DIRECTIVE.parse().expect("static directive"));
+            (Some(layer.with_filter(filter)), Some(provider))
+        }
+        Some(Err(e)) => {
+            eprintln!("OTLP export disabled: {e:#}");
+            (None, None)
+        }
+        None => (None, None),
+    };
 
     tracing_subscriber::registry()
-        .with(filter)
         .with(console)
         .with(json)
+        .with(otlp)
         .init();
 
-    guard
+    TelemetryGuard {
+        tracer_provider,
+        _file: guard,
+    }
+}
+
+fn env_filter(default_level: &str) -> EnvFilter {
+    EnvFilter::try_from_default_env()
+        .or_else(|_| EnvFilter::try_new(default_level))
+        .unwrap_or_else(|_| EnvFilter::new("info"))
 }
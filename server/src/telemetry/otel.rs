@@ -0,0 +1,107 @@
+//! Optional OTLP trace export, enabled by `CONFIG_OTLP_ENDPOINT`.
+//!
+//! Every `tracing` span (the `#[instrument]`ed handlers, services and jobs)
+//! becomes an OpenTelemetry span; sqlx's per-statement `sqlx::query` events
+//! ride along as span events on whichever span issued the query. Root spans
+//! are opened per Telegram update ([`update_span`]) and per HTTP request
+//! (`api::server`), so "message arrived → spam inspect → CAS → ban" is one
+//! trace.
+//!
+//! Export is OTLP/HTTP protobuf from a batch processor on its own thread.
+//! Collector outages only drop spans — nothing on the request path blocks.
+
+use anyhow::{Context, Result};
+use opentelemetry::KeyValue;
+use opentelemetry::trace::{TraceContextExt, TracerProvider as _};
+use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::trace::{Sampler, SdkTracer, SdkTracerProvider};
+use teloxide::types::Update;
+use tracing::Span;
+use tracing::field::{Empty, display};
+use tracing_opentelemetry::{OpenTelemetryLayer, OpenTelemetrySpanExt};
+use tracing_subscriber::registry::LookupSpan;
+
+use crate::build_info;
+
+/// OTLP/HTTP traces path appended to `CONFIG_OTLP_ENDPOINT`.
+const TRACES_PATH: &str = "/v1/traces";
+
+/// Build the tracer provider and the `tracing` layer feeding it. The caller
+/// keeps the provider and shuts it down on exit so buffered spans flush.
+pub fn layer<S>(
+    endpoint: &str,
+    sample_ratio: f64,
+) -> Result<(OpenTelemetryLayer<S, SdkTracer>, SdkTracerProvider)>
+where
+    S: tracing::Subscriber + for<'span> LookupSpan<'span>,
+{
+    let exporter = SpanExporter::builder()
+        .with_http()
+        .with_endpoint(traces_url(endpoint))
+        .build()
+        .context("build OTLP span exporter")?;
+
+    let resource = Resource::builder()
+        .with_service_name(build_info::NAME)
+        .with_attribute(KeyValue::new("service.version", build_info::VERSION))
+        .build();
+
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_sampler(Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(
+            sample_ratio,
+        ))))
+        .with_resource(resource)
+        .build();
+
+    let tracer = provider.tracer(build_info::NAME);
+    Ok((tracing_opentelemetry::layer().with_tracer(tracer), provider))
+}
+
+fn traces_url(endpoint: &str) -> String {
+    format!("{}{TRACES_PATH}", endpoint.trim_end_matches('/'))
+}
+
+/// Root span for one Telegram update. Handler spans nest under it, so every
+/// log line and exported span of the update shares its `trace_id`.
+pub fn update_span(update: &Update) -> Span {
+    let span = tracing::info_span!(
+        "update",
+        update_id = update.id.0,
+        chat_id = update.chat().map(|c| c.id.0),
+        user_id = update.from().map(|u| u.id.0),
+        trace_id = Empty,
+    );
+    record_trace_id(&span);
+    span
+}
+
+/// Fill the span's `trace_id` field with its OpenTelemetry trace id, so the
+/// JSON log lines can be joined against the exported trace. No-op when
+/// export is off (the span has no valid OTel context).
+pub fn record_trace_id(span: &Span) {
+    let context = span.context();
+    let otel_span = context.span();
+    let span_context = otel_span.span_context();
+    if span_context.is_valid() {
+        span.record("trace_id", display(span_context.trace_id()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn traces_url_appends_signal_path_once() {
+        assert_eq!(
+            traces_url("http://collector:4318"),
+            "http://collector:4318/v1/traces"
+        );
+        assert_eq!(
+            traces_url("http://collector:4318/"),
+            "http://collector:4318/v1/traces"
+        );
+    }
+}
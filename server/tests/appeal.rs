@@ -0,0 +1,151 @@
+//! `models::appeal` against a real `appeals` table: drafts only for a
+//! standing ban, submit → pending, one open appeal per chat, the
+//! first-decision-wins guard, and the cooldown after a denial. `#[ignore]`-gated because it needs Postgres on
+//! `localhost:5432`.
+
+use sqlx::PgPool;
+use uuid::Uuid;
+use vixen_server::models::appeal::{self, AppealStatus, OpenDraft};
+
+const CHAT_ID: i64 = -1001234567890;
+const USER_ID: i64 = 4242;
+const MODERATOR_ID: i64 = 7;
+
+async fn seed_chat(pool: &PgPool, chat_id: i64) {
+    sqlx::query("INSERT INTO chats (chat_id) VALUES ($1) ON CONFLICT DO NOTHING")
+        .bind(chat_id)
+        .execute(pool)
+        .await
+        .expect("seed chats");
+}
+
+async fn ledger(pool: &PgPool, action: &str) -> Uuid {
+    sqlx::query_scalar(
+        "INSERT INTO moderation_actions (chat_id, target_user_id, action, actor_kind, reason)
+         VALUES ($1, $2, $3, 'bot', '{\"matched_rules\":[\"cas\"]}')
+         RETURNING id",
+    )
+    .bind(CHAT_ID)
+    .bind(USER_ID)
+    .bind(action)
+    .fetch_one(pool)
+    .await
+    .expect("insert moderation_actions")
+}
+
+#[sqlx::test(migrations = "./migrations")]
+#[ignore = "requires postgres"]
+async fn test_appeal_requires_standing_ban(pool: PgPool) {
+    seed_chat(&pool, CHAT_ID).await;
+    assert_eq!(
+        appeal::open_draft(&pool, CHAT_ID, USER_ID).await.unwrap(),
+        OpenDraft::NotBanned
+    );
+
+    ledger(&pool, "ban").await;
+    // Telegram-side unban after the ban: nothing left to appeal.
+    sqlx::query(
+        "INSERT INTO moderation_actions
+             (chat_id, target_user_id, action, actor_kind, created_at)
+         VALUES ($1, $2, 'unban', 'moderator', NOW() + INTERVAL '1 second')",
+    )
+    .bind(CHAT_ID)
+    .bind(USER_ID)
+    .execute(&pool)
+    .await
+    .unwrap();
+    assert_eq!(
+        appeal::open_draft(&pool, CHAT_ID, USER_ID).await.unwrap(),
+        OpenDraft::NotBanned
+    );
+}
+
+#[sqlx::test(migrations = "./migrations")]
+#[ignore = "requires postgres"]
+async fn test_appeal_submit_and_decide_once(pool: PgPool) {
+    seed_chat(&pool, CHAT_ID).await;
+    let ban_id = ledger(&pool, "ban").await;
+
+    // No draft yet → a stray DM is ignored.
+    assert!(
+        appeal::submit(&pool, USER_ID, "hello")
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    let OpenDraft::Opened(first) = appeal::open_draft(&pool, CHAT_ID, USER_ID).await.unwrap()
+    else {
+        panic!("expected a draft");
+    };
+    // Re-opening replaces the draft instead of conflicting.
+    let OpenDraft::Opened(draft) = appeal::open_draft(&pool, CHAT_ID, USER_ID).await.unwrap()
+    else {
+        panic!("expected a draft");
+    };
+    assert_ne!(first, draft);
+
+    let submitted = appeal::submit(&pool, USER_ID, "I'm not a spammer")
+        .await
+        .unwrap()
+        .expect("draft is live");
+    assert_eq!(submitted.id, draft);
+    assert_eq!(submitted.status, AppealStatus::Pending);
+    assert_eq!(submitted.action_id, Some(ban_id));
+    assert!(submitted.submitted_at.is_some());
+    assert_eq!(
+        appeal::open_draft(&pool, CHAT_ID, USER_ID).await.unwrap(),
+        OpenDraft::AlreadyPending
+    );
+
+    assert_eq!(appeal::chat_of(&pool, draft).await.unwrap(), Some(CHAT_ID));
+    let decided = appeal::decide(&pool, draft, true, MODERATOR_ID)
+        .await
+        .unwrap()
+        .expect("pending appeal");
+    assert_eq!(decided.status, AppealStatus::Approved);
+    assert_eq!(decided.decided_by, Some(MODERATOR_ID));
+    // A second press (another moderator, or a stale DM copy) is a no-op.
+    assert!(
+        appeal::decide(&pool, draft, false, 8)
+            .await
+            .unwrap()
+            .is_none()
+    );
+    assert_eq!(
+        appeal::ban_reason(&pool, ban_id).await.unwrap().as_deref(),
+        Some(r#"{"matched_rules":["cas"]}"#)
+    );
+}
+
+#[sqlx::test(migrations = "./migrations")]
+#[ignore = "requires postgres"]
+async fn test_appeal_denial_starts_a_cooldown(pool: PgPool) {
+    seed_chat(&pool, CHAT_ID).await;
+    ledger(&pool, "ban").await;
+
+    let OpenDraft::Opened(id) = appeal::open_draft(&pool, CHAT_ID, USER_ID).await.unwrap() else {
+        panic!("expected a draft");
+    };
+    appeal::submit(&pool, USER_ID, "please").await.unwrap();
+    appeal::decide(&pool, id, false, MODERATOR_ID)
+        .await
+        .unwrap()
+        .expect("pending appeal");
+    assert_eq!(
+        appeal::open_draft(&pool, CHAT_ID, USER_ID).await.unwrap(),
+        OpenDraft::CoolingDown(appeal::DENIAL_COOLDOWN_HOURS)
+    );
+
+    // Once the cooldown has passed, the same ban can be appealed again.
+    sqlx::query("UPDATE appeals SET decided_at = NOW() - make_interval(hours => $2) WHERE id = $1")
+        .bind(id)
+        .bind(appeal::DENIAL_COOLDOWN_HOURS as i32)
+        .execute(&pool)
+        .await
+        .unwrap();
+    assert!(matches!(
+        appeal::open_draft(&pool, CHAT_ID, USER_ID).await.unwrap(),
+        OpenDraft::Opened(_)
+    ));
+}
@@ -0,0 +1,278 @@
+//! Audit log over real `moderation_actions` rows: every filter of
+//! `services::audit_log`, keyset pages that don't skip or repeat rows
+//! sharing a `created_at`, the capped export walk, and
+//! `GET /chats/{chat_id}/actions` with its cursor and CSV / NDJSON
+//! downloads.
+//!
+//! `#[ignore]`-gated: requires Postgres (and Redis for the HTTP test) on
+//! `localhost`.
+
+mod common;
+
+use std::collections::HashSet;
+
+use axum::body::{Body, Bytes};
+use axum::http::header::{AUTHORIZATION, CONTENT_DISPOSITION, CONTENT_TYPE};
+use axum::http::{Request, StatusCode};
+use axum::response::Response;
+use chrono::{Duration, Utc};
+use common::fake_bot_api::FakeBotApi;
+use common::*;
+use futures::TryStreamExt;
+use sqlx::PgPool;
+use tower::ServiceExt;
+use uuid::Uuid;
+use vixen_server::api::build_router;
+use vixen_server::services::audit_log::{self, AuditEntry, AuditFilter};
+
+const REDIS_URL: &str = "redis://localhost:6379/8";
+const MODERATOR: i64 = 4901;
+const VIEWER: i64 = 4902;
+
+async fn record(
+    pool: &PgPool,
+    chat_id: i64,
+    target: i64,
+    action: &str,
+    actor_user_id: Option<i64>,
+    reason: &str,
+    minutes_ago: i32,
+) {
+    let actor_kind = if actor_user_id.is_some() {
+        "moderator"
+    } else {
+        "bot"
+    };
+    sqlx::query(
+        "INSERT INTO moderation_actions
+             (chat_id, target_user_id, action, actor_kind, actor_user_id, reason, created_at)
+         VALUES ($1, $2, $3, $4, $5, $6, NOW() - make_interval(mins => $7))",
+    )
+    .bind(chat_id)
+    .bind(target)
+    .bind(action)
+    .bind(actor_kind)
+    .bind(actor_user_id)
+    .bind(reason)
+    .bind(minutes_ago)
+    .execute(pool)
+    .await
+    .expect("insert moderation_actions");
+}
+
+async fn body(response: Response) -> Bytes {
+    axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("response body")
+}
+
+fn spam(rule: &str) -> String {
+    format!(r#"{{"matched_rules":["{rule}"],"score":1.0,"excerpt":"Buy NOW"}}"#)
+}
+
+async fn targets(pool: &PgPool, chat_id: i64, filter: AuditFilter) -> Vec<i64> {
+    audit_log::page(pool, chat_id, &filter, None, 100)
+        .await
+        .unwrap()
+        .iter()
+        .map(|e| e.target_user_id)
+        .collect()
+}
+
+#[sqlx::test(migrations = "./migrations")]
+#[ignore = "requires postgres"]
+async fn audit_log_filters(pool: PgPool) {
+    let chat_id = unique_chat_id();
+    seed_chat(&pool, chat_id).await;
+    record(&pool, chat_id, 1, "ban", None, &spam("cas"), 50).await;
+    record(&pool, chat_id, 2, "ban", None, &spam("xxh3_dedup"), 40).await;
+    record(&pool, chat_id, 3, "delete", None, &spam("ngram"), 30).await;
+    // Free text that merely looks like JSON.
+    record(&pool, chat_id, 4, "ban", Some(MODERATOR), "{Crypto}", 20).await;
+    record(&pool, chat_id, 5, "verify", Some(MODERATOR), "manual", 10).await;
+    record(&pool, chat_id, 6, "captcha_expired", None, "timeout", 600).await;
+
+    // Newest first.
+    assert_eq!(
+        targets(&pool, chat_id, AuditFilter::default()).await,
+        vec![5, 4, 3, 2, 1, 6]
+    );
+    let bans = AuditFilter {
+        action: Some("ban".into()),
+        ..Default::default()
+    };
+    assert_eq!(targets(&pool, chat_id, bans).await, vec![4, 2, 1]);
+    let by_moderator = AuditFilter {
+        actor_kind: Some("moderator".into()),
+        actor_user_id: Some(MODERATOR),
+        ..Default::default()
+    };
+    assert_eq!(targets(&pool, chat_id, by_moderator).await, vec![5, 4]);
+    let one_target = AuditFilter {
+        target_user_id: Some(3),
+        ..Default::default()
+    };
+    assert_eq!(targets(&pool, chat_id, one_target).await, vec![3]);
+    // Case-insensitive, over free text and verdict JSON alike.
+    let reason = AuditFilter {
+        reason: Some("crypto".into()),
+        ..Default::default()
+    };
+    assert_eq!(targets(&pool, chat_id, reason).await, vec![4]);
+    let excerpt = AuditFilter {
+        reason: Some("buy now".into()),
+        ..Default::default()
+    };
+    assert_eq!(targets(&pool, chat_id, excerpt).await, vec![3, 2, 1]);
+    // Plain-text reasons, `{`-prefixed or not, are skipped by the rule
+    // filter, not a cast error.
+    let rule = AuditFilter {
+        rule: Some("xxh3_dedup".into()),
+        ..Default::default()
+    };
+    assert_eq!(targets(&pool, chat_id, rule).await, vec![2]);
+    let window = AuditFilter {
+        from: Some(Utc::now() - Duration::minutes(45)),
+        to: Some(Utc::now() - Duration::minutes(15)),
+        ..Default::default()
+    };
+    assert_eq!(targets(&pool, chat_id, window).await, vec![4, 3, 2]);
+
+    let other_chat = unique_chat_id();
+    seed_chat(&pool, other_chat).await;
+    assert!(
+        targets(&pool, other_chat, AuditFilter::default())
+            .await
+            .is_empty()
+    );
+}
+
+#[sqlx::test(migrations = "./migrations")]
+#[ignore = "requires postgres"]
+async fn audit_log_pages_ties_and_export(pool: PgPool) {
+    let chat_id = unique_chat_id();
+    seed_chat(&pool, chat_id).await;
+    // Seven rows in one statement share `created_at`; only `id` orders them.
+    sqlx::query(
+        "INSERT INTO moderation_actions (chat_id, target_user_id, action, actor_kind)
+         SELECT $1, g, 'ban', 'bot' FROM generate_series(1, 7) AS g",
+    )
+    .bind(chat_id)
+    .execute(&pool)
+    .await
+    .unwrap();
+    let filter = AuditFilter::default();
+
+    let mut seen = Vec::new();
+    let mut after = None;
+    loop {
+        let page = audit_log::page(&pool, chat_id, &filter, after, 3)
+            .await
+            .unwrap();
+        seen.extend(page.iter().map(|e| e.id));
+        match page.last() {
+            Some(last) if page.len() == 3 => after = Some(last.cursor()),
+            _ => break,
+        }
+    }
+    assert_eq!(seen.len(), 7);
+    assert_eq!(seen.iter().collect::<HashSet<&Uuid>>().len(), 7);
+
+    let batches: Vec<Vec<AuditEntry>> = audit_log::export(pool.clone(), chat_id, filter, None)
+        .try_collect()
+        .await
+        .unwrap();
+    let exported: Vec<Uuid> = batches.into_iter().flatten().map(|e| e.id).collect();
+    assert_eq!(exported, seen, "export walks the same order");
+}
+
+#[sqlx::test(migrations = "./migrations")]
+#[ignore = "requires postgres + redis"]
+async fn actions_route_pages_and_exports(pool: PgPool) {
+    let chat_id = unique_chat_id();
+    seed_chat(&pool, chat_id).await;
+    seed_moderator_role(&pool, chat_id, VIEWER, "viewer").await;
+    record(&pool, chat_id, 1, "ban", None, &spam("cas"), 30).await;
+    record(
+        &pool,
+        chat_id,
+        2,
+        "ban",
+        Some(MODERATOR),
+        "=cmd, \"quoted\"",
+        20,
+    )
+    .await;
+    record(&pool, chat_id, 3, "unban", Some(MODERATOR), "appeal", 10).await;
+    let redis = fresh_redis(REDIS_URL).await;
+    let state = make_state(pool.clone(), redis, FakeBotApi::start().await.bot()).await;
+    let router = build_router(state);
+    let get = |query: String| {
+        let router = router.clone();
+        async move {
+            let request = Request::get(format!("/api/v1/chats/{chat_id}/actions{query}"))
+                .header(AUTHORIZATION, bearer(VIEWER, vec![chat_id]))
+                .body(Body::empty())
+                .unwrap();
+            router.oneshot(request).await.unwrap()
+        }
+    };
+
+    let response = get("?action=ban&limit=1".into()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let json: serde_json::Value = serde_json::from_slice(&body(response).await).unwrap();
+    let page = &json["data"];
+    assert_eq!(page["items"][0]["target_user_id"], 2);
+    assert_eq!(page["has_more"], true);
+    let cursor = page["cursor"].as_str().unwrap().to_owned();
+
+    let response = get(format!("?action=ban&limit=1&cursor={cursor}")).await;
+    let json: serde_json::Value = serde_json::from_slice(&body(response).await).unwrap();
+    let page = &json["data"];
+    assert_eq!(page["items"][0]["target_user_id"], 1);
+    assert_eq!(page["has_more"], false);
+    assert!(page["cursor"].is_null());
+
+    let response = get("?actor_kind=moderator&format=csv".into()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(
+        response.headers()[CONTENT_TYPE]
+            .to_str()
+            .unwrap()
+            .starts_with("text/csv")
+    );
+    assert!(
+        response.headers()[CONTENT_DISPOSITION]
+            .to_str()
+            .unwrap()
+            .starts_with("attachment")
+    );
+    let csv = String::from_utf8(body(response).await.to_vec()).unwrap();
+    let lines: Vec<&str> = csv.split("\r\n").filter(|l| !l.is_empty()).collect();
+    assert_eq!(lines.len(), 3, "{csv}");
+    assert!(lines[0].starts_with("id,created_at,action"));
+    assert!(lines[1].contains(",unban,3,moderator,"));
+    assert!(
+        lines[2].ends_with(",\"'=cmd, \"\"quoted\"\"\""),
+        "{}",
+        lines[2]
+    );
+
+    let response = get("?rule=cas&format=ndjson".into()).await;
+    let ndjson = body(response).await;
+    let rows: Vec<serde_json::Value> = ndjson
+        .split(|b| *b == b'\n')
+        .filter(|l| !l.is_empty())
+        .map(|l| serde_json::from_slice(l).unwrap())
+        .collect();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0]["target_user_id"], 1);
+
+    for bad in ["?action=nuke", "?cursor=nope", "?from=yesterday"] {
+        assert_eq!(
+            get(bad.into()).await.status(),
+            StatusCode::BAD_REQUEST,
+            "{bad}"
+        );
+    }
+}
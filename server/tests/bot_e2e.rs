@@ -0,0 +1,231 @@
+//! End-to-end: the real `build_dispatcher` polling loop and background jobs
+//! against `common::fake_bot_api`, an in-process Bot API server. Updates go
+//! in through `getUpdates`; assertions read the recorded API calls and the
+//! database.
+//!
+//! `#[ignore]`-gated: requires Postgres + Redis on `localhost`.
+
+mod common;
+
+use std::time::{Duration, Instant};
+
+use common::fake_bot_api::{FakeBotApi, Fault, text_update};
+use common::*;
+use sqlx::PgPool;
+use teloxide::dispatching::ShutdownToken;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use vixen_server::api::AppState;
+use vixen_server::jobs::captcha_expiry;
+use vixen_server::telegram::{WatchedChats, build_dispatcher};
+
+const REDIS_URL: &str = "redis://localhost:6379/8";
+const SPAM: &str = "Привет всем, предлагаю быстрый заработок без вложений в нашей команде";
+const POSTER: u64 = 9001;
+const NEWCOMER: u64 = 9002;
+
+struct Running {
+    api: FakeBotApi,
+    state: AppState,
+    chat_id: i64,
+    shutdown: ShutdownToken,
+    dispatcher: JoinHandle<()>,
+}
+
+impl Running {
+    async fn start(pool: &PgPool) -> Self {
+        let api = FakeBotApi::start().await;
+        let chat_id = unique_chat_id();
+        seed_chat(pool, chat_id).await;
+        let redis = fresh_redis(REDIS_URL).await;
+        let bot = api.bot();
+        let state = make_state(pool.clone(), redis, bot.clone()).await;
+
+        let mut dispatcher = build_dispatcher(bot, WatchedChats::new([chat_id]), state.clone());
+        let shutdown = dispatcher.shutdown_token();
+        let dispatcher = tokio::spawn(async move { dispatcher.dispatch().await });
+        Self {
+            api,
+            state,
+            chat_id,
+            shutdown,
+            dispatcher,
+        }
+    }
+
+    async fn stop(self) {
+        self.shutdown
+            .shutdown()
+            .expect("dispatcher is running")
+            .await;
+        self.dispatcher.await.expect("dispatcher task");
+    }
+}
+
+async fn ledger_count(pool: &PgPool, chat_id: i64, action: &str) -> i64 {
+    sqlx::query_scalar("SELECT COUNT(*) FROM moderation_actions WHERE chat_id = $1 AND action = $2")
+        .bind(chat_id)
+        .bind(action)
+        .fetch_one(pool)
+        .await
+        .expect("count moderation_actions")
+}
+
+#[sqlx::test(migrations = "./migrations")]
+#[ignore = "requires postgres + redis"]
+async fn captcha_is_posted_then_swept_by_the_expiry_job(pool: PgPool) {
+    let run = Running::start(&pool).await;
+    run.api
+        .push_update(text_update(run.chat_id, POSTER, 10, "hello everyone"));
+
+    let photos = run.api.wait_for("sendPhoto", 1).await;
+    assert_eq!(photos[0].int("chat_id"), Some(run.chat_id));
+    assert!(
+        photos[0].params["photo"]
+            .as_str()
+            .is_some_and(|p| p.starts_with("<file captcha.webp")),
+        "captcha image uploaded: {:?}",
+        photos[0].params
+    );
+    assert!(photos[0].params["reply_markup"]["inline_keyboard"].is_array());
+    let deleted = run.api.wait_for("deleteMessage", 1).await;
+    assert_eq!(deleted[0].int("message_id"), Some(10));
+
+    // The handler stores the photo's message id once sendPhoto returns.
+    let mut photo_id: Option<i32> = None;
+    for _ in 0..100 {
+        photo_id = sqlx::query_scalar(
+            "SELECT telegram_message_id FROM captcha_challenges WHERE chat_id = $1 AND user_id = $2",
+        )
+        .bind(run.chat_id)
+        .bind(POSTER as i64)
+        .fetch_optional(&pool)
+        .await
+        .unwrap()
+        .flatten();
+        if photo_id.is_some() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    let photo_id = photo_id.expect("challenge row with the photo's message id");
+
+    sqlx::query(
+        "UPDATE captcha_challenges SET expires_at = NOW() - INTERVAL '1 second' WHERE chat_id = $1",
+    )
+    .bind(run.chat_id)
+    .execute(&pool)
+    .await
+    .unwrap();
+    let cancel = CancellationToken::new();
+    // First tick fires immediately.
+    let job = tokio::spawn(captcha_expiry::run(
+        run.api.bot(),
+        run.state.clone(),
+        cancel.clone(),
+    ));
+    let deleted = run.api.wait_for("deleteMessage", 2).await;
+    assert_eq!(deleted[1].int("message_id"), Some(i64::from(photo_id)));
+    cancel.cancel();
+    job.await.unwrap().unwrap();
+
+    assert_eq!(ledger_count(&pool, run.chat_id, "captcha_expired").await, 1);
+    run.stop().await;
+}
+
+#[sqlx::test(migrations = "./migrations")]
+#[ignore = "requires postgres + redis"]
+async fn spam_from_verified_user_is_deleted_and_ledgered(pool: PgPool) {
+    let run = Running::start(&pool).await;
+    seed_verified(&pool, run.chat_id, POSTER as i64).await;
+
+    run.api
+        .push_update(text_update(run.chat_id, POSTER, 20, SPAM));
+    let deleted = run.api.wait_for("deleteMessage", 1).await;
+    assert_eq!(deleted[0].int("message_id"), Some(20));
+    assert_eq!(deleted[0].status, 200);
+
+    // Sequencing point: updates of one chat are handled in order, so once
+    // the next message's captcha is out the spam handler has committed.
+    run.api
+        .push_update(text_update(run.chat_id, NEWCOMER, 21, "hi, new here"));
+    run.api.wait_for("sendPhoto", 1).await;
+
+    assert_eq!(ledger_count(&pool, run.chat_id, "delete").await, 1);
+    run.stop().await;
+}
+
+#[sqlx::test(migrations = "./migrations")]
+#[ignore = "requires postgres + redis"]
+async fn forbidden_delete_still_records_the_action(pool: PgPool) {
+    let run = Running::start(&pool).await;
+    seed_verified(&pool, run.chat_id, POSTER as i64).await;
+    run.api.fail_next(
+        "deleteMessage",
+        Fault::Forbidden("Forbidden: bot was kicked from the supergroup chat".into()),
+    );
+
+    run.api
+        .push_update(text_update(run.chat_id, POSTER, 30, SPAM));
+    let deleted = run.api.wait_for("deleteMessage", 1).await;
+    assert_eq!(deleted[0].status, 403);
+
+    run.api
+        .push_update(text_update(run.chat_id, NEWCOMER, 31, "hi, new here"));
+    run.api.wait_for("sendPhoto", 1).await;
+
+    // 403 is non-fatal: the ledger keeps the intent.
+    assert_eq!(ledger_count(&pool, run.chat_id, "delete").await, 1);
+    run.stop().await;
+}
+
+#[sqlx::test(migrations = "./migrations")]
+#[ignore = "requires postgres + redis"]
+async fn rate_limited_delete_is_retried_after_retry_after(pool: PgPool) {
+    let run = Running::start(&pool).await;
+    seed_verified(&pool, run.chat_id, POSTER as i64).await;
+    run.api.fail_next("deleteMessage", Fault::RetryAfter(1));
+
+    let started = Instant::now();
+    run.api
+        .push_update(text_update(run.chat_id, POSTER, 40, SPAM));
+    let deleted = run.api.wait_for("deleteMessage", 2).await;
+    assert_eq!(deleted[0].status, 429);
+    assert_eq!(deleted[1].status, 200);
+    assert_eq!(deleted[1].int("message_id"), Some(40));
+    assert!(
+        started.elapsed() >= Duration::from_secs(1),
+        "retry waited out retry_after"
+    );
+
+    run.api
+        .push_update(text_update(run.chat_id, NEWCOMER, 41, "hi, new here"));
+    run.api.wait_for("sendPhoto", 1).await;
+
+    assert_eq!(ledger_count(&pool, run.chat_id, "delete").await, 1);
+    run.stop().await;
+}
+
+#[sqlx::test(migrations = "./migrations")]
+#[ignore = "requires postgres + redis"]
+async fn persistent_rate_limit_rolls_the_action_back(pool: PgPool) {
+    let run = Running::start(&pool).await;
+    seed_verified(&pool, run.chat_id, POSTER as i64).await;
+    for _ in 0..3 {
+        run.api.fail_next("deleteMessage", Fault::RetryAfter(1));
+    }
+
+    run.api
+        .push_update(text_update(run.chat_id, POSTER, 50, SPAM));
+    let deleted = run.api.wait_for("deleteMessage", 3).await;
+    assert!(deleted.iter().all(|c| c.status == 429));
+
+    run.api
+        .push_update(text_update(run.chat_id, NEWCOMER, 51, "hi, new here"));
+    run.api.wait_for("sendPhoto", 1).await;
+
+    // Out of retries, the 429 is fatal for `ModerationService::apply`: no
+    // ledger row, so a later retry can apply it.
+    assert_eq!(ledger_count(&pool, run.chat_id, "delete").await, 0);
+    run.stop().await;
+}
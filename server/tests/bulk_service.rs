@@ -0,0 +1,203 @@
+//! `services::bulk_service` against real `moderation_actions` /
+//! `bulk_operations` tables: the filter (rule in `reason`, time range,
+//! actor), one target per user, the parent + items written by `create`, and
+//! the one-running-operation-per-chat rule.
+//! `#[ignore]`-gated because it needs Postgres on `localhost:5432`.
+
+use chrono::{Duration, Utc};
+use sqlx::PgPool;
+use vixen_server::services::bulk_service::{self, BulkFilter, Inverse};
+
+const CHAT_ID: i64 = -1001234567890;
+const MODERATOR_ID: i64 = 7;
+
+async fn seed_chat(pool: &PgPool) {
+    sqlx::query("INSERT INTO chats (chat_id) VALUES ($1) ON CONFLICT DO NOTHING")
+        .bind(CHAT_ID)
+        .execute(pool)
+        .await
+        .expect("seed chats");
+}
+
+async fn ban(pool: &PgPool, user_id: i64, rule: &str, minutes_ago: i64) {
+    sqlx::query(
+        "INSERT INTO moderation_actions
+             (chat_id, target_user_id, action, actor_kind, reason, created_at)
+         VALUES ($1, $2, 'ban', 'bot', $3, NOW() - make_interval(mins => $4::INT))",
+    )
+    .bind(CHAT_ID)
+    .bind(user_id)
+    .bind(format!(r#"{{"matched_rules":["{rule}"],"score":1.0}}"#))
+    .bind(minutes_ago as i32)
+    .execute(pool)
+    .await
+    .expect("insert moderation_actions");
+}
+
+#[sqlx::test(migrations = "./migrations")]
+#[ignore = "requires postgres"]
+async fn test_bulk_service_filter_by_rule_and_window(pool: PgPool) {
+    seed_chat(&pool).await;
+    ban(&pool, 1, "cas", 30).await;
+    ban(&pool, 1, "cas", 20).await;
+    ban(&pool, 2, "cas", 10).await;
+    ban(&pool, 3, "phrase_match", 10).await;
+    ban(&pool, 4, "cas", 600).await;
+    // Moderator bans carry a plain-text reason, which may well look like
+    // JSON; the rule filter must skip them instead of failing the cast.
+    sqlx::query(
+        "INSERT INTO moderation_actions (chat_id, target_user_id, action, actor_kind, reason)
+         VALUES ($1, 5, 'ban', 'moderator', '{spam} manual')",
+    )
+    .bind(CHAT_ID)
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let filter = BulkFilter {
+        action: "ban".into(),
+        rule: Some("cas".into()),
+        from: Some(Utc::now() - Duration::hours(1)),
+        ..Default::default()
+    };
+    let targets = bulk_service::matching_targets(&pool, CHAT_ID, &filter)
+        .await
+        .unwrap();
+    let mut users: Vec<i64> = targets.iter().map(|t| t.user_id).collect();
+    users.sort();
+    assert_eq!(users, vec![1, 2]);
+
+    let all_bans = BulkFilter {
+        action: "ban".into(),
+        ..Default::default()
+    };
+    assert_eq!(
+        bulk_service::matching_targets(&pool, CHAT_ID, &all_bans)
+            .await
+            .unwrap()
+            .len(),
+        5
+    );
+    let by_moderator = BulkFilter {
+        action: "ban".into(),
+        actor_kind: Some("moderator".into()),
+        ..Default::default()
+    };
+    let targets = bulk_service::matching_targets(&pool, CHAT_ID, &by_moderator)
+        .await
+        .unwrap();
+    assert_eq!(targets.len(), 1);
+    assert_eq!(targets[0].user_id, 5);
+}
+
+#[sqlx::test(migrations = "./migrations")]
+#[ignore = "requires postgres"]
+async fn test_bulk_service_create_and_get(pool: PgPool) {
+    seed_chat(&pool).await;
+    ban(&pool, 1, "cas", 5).await;
+    ban(&pool, 2, "cas", 5).await;
+    let filter = BulkFilter {
+        action: "ban".into(),
+        rule: Some("cas".into()),
+        ..Default::default()
+    };
+    let targets = bulk_service::matching_targets(&pool, CHAT_ID, &filter)
+        .await
+        .unwrap();
+
+    assert!(
+        bulk_service::running_for(&pool, CHAT_ID)
+            .await
+            .unwrap()
+            .is_none()
+    );
+    let id = bulk_service::create(
+        &pool,
+        CHAT_ID,
+        MODERATOR_ID,
+        Inverse::Unban,
+        &filter,
+        &targets,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        bulk_service::running_for(&pool, CHAT_ID).await.unwrap(),
+        Some(id)
+    );
+
+    let op = bulk_service::get(&pool, CHAT_ID, id)
+        .await
+        .unwrap()
+        .expect("operation exists");
+    assert_eq!(op.inverse, Inverse::Unban);
+    assert_eq!(op.status, "running");
+    assert_eq!(op.total, 2);
+    assert_eq!(op.filter.rule.as_deref(), Some("cas"));
+    assert_eq!(op.items.len(), 2);
+    assert!(op.items.iter().all(|i| i.outcome == "pending"));
+    assert!(op.items.iter().all(|i| i.source_action_id.is_some()));
+
+    // Scoped to the chat it was created in.
+    assert!(bulk_service::get(&pool, -100, id).await.unwrap().is_none());
+}
+
+#[sqlx::test(migrations = "./migrations")]
+#[ignore = "requires postgres"]
+async fn test_bulk_service_one_running_per_chat(pool: PgPool) {
+    seed_chat(&pool).await;
+    ban(&pool, 1, "cas", 5).await;
+    let filter = BulkFilter {
+        action: "ban".into(),
+        ..Default::default()
+    };
+    let targets = bulk_service::matching_targets(&pool, CHAT_ID, &filter)
+        .await
+        .unwrap();
+    let create = || {
+        bulk_service::create(
+            &pool,
+            CHAT_ID,
+            MODERATOR_ID,
+            Inverse::Unban,
+            &filter,
+            &targets,
+        )
+    };
+
+    let first = create().await.unwrap();
+    let err = create().await.expect_err("second running operation");
+    assert!(err.is::<bulk_service::AlreadyRunning>(), "{err:#}");
+
+    // Recent progress keeps it running, even if it started long ago.
+    sqlx::query("UPDATE bulk_operations SET created_at = NOW() - INTERVAL '1 hour' WHERE id = $1")
+        .bind(first)
+        .execute(&pool)
+        .await
+        .unwrap();
+    assert_eq!(
+        bulk_service::running_for(&pool, CHAT_ID).await.unwrap(),
+        Some(first)
+    );
+
+    // No progress past STALE_AFTER: the process that ran it is gone.
+    sqlx::query(
+        "UPDATE bulk_operation_items SET updated_at = NOW() - INTERVAL '1 hour'
+         WHERE operation_id = $1",
+    )
+    .bind(first)
+    .execute(&pool)
+    .await
+    .unwrap();
+    assert_eq!(
+        bulk_service::running_for(&pool, CHAT_ID).await.unwrap(),
+        None
+    );
+    let op = bulk_service::get(&pool, CHAT_ID, first)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(op.status, "failed");
+    assert!(op.finished_at.is_some());
+    create().await.expect("the chat is free again");
+}
@@ -357,11 +357,14 @@ async fn verify_manual_writes_ledger(pool: PgPool) {
     let svc = make_service(pool.clone());
     let _ = svc.issue_challenge(CHAT_ID, USER_ID).await.unwrap();
 
-    let outcome = svc.verify_manual(CHAT_ID, USER_ID, 555).await.unwrap();
+    let (outcome, action_id) = svc
+        .verify_manual(CHAT_ID, USER_ID, Some(555))
+        .await
+        .unwrap();
     assert_eq!(outcome, Outcome::Solved);
 
     let row = sqlx::query!(
-        r#"SELECT actor_kind, actor_user_id FROM moderation_actions
+        r#"SELECT id, actor_kind, actor_user_id FROM moderation_actions
            WHERE chat_id = $1 AND target_user_id = $2 AND action = 'verify'"#,
         CHAT_ID,
         USER_ID,
@@ -371,10 +374,14 @@ async fn verify_manual_writes_ledger(pool: PgPool) {
     .unwrap();
     assert_eq!(row.actor_kind, "moderator");
     assert_eq!(row.actor_user_id, Some(555));
+    assert_eq!(action_id, Some(row.id));
 
     // Repeat — idempotent.
-    let again = svc.verify_manual(CHAT_ID, USER_ID, 555).await.unwrap();
-    assert_eq!(again, Outcome::AlreadyVerified);
+    let again = svc
+        .verify_manual(CHAT_ID, USER_ID, Some(555))
+        .await
+        .unwrap();
+    assert_eq!(again, (Outcome::AlreadyVerified, None));
 }
 
 // ── helpers ───────────────────────────────────────────────────────────────
@@ -0,0 +1,114 @@
+//! `models::chat_config` get/set as used by `server config get|set`.
+//! `#[ignore]`-gated because it needs Postgres on `localhost:5432`.
+
+use sqlx::PgPool;
+use vixen_server::database::ensure_watched_chats;
+use vixen_server::models::chat_config;
+
+const CHAT_ID: i64 = -1001234567890;
+const UNKNOWN_CHAT_ID: i64 = -1009876543210;
+
+#[sqlx::test(migrations = "./migrations")]
+#[ignore = "requires postgres"]
+async fn set_casts_text_to_the_column_type(pool: PgPool) {
+    ensure_watched_chats(&pool, &[CHAT_ID]).await.unwrap();
+
+    for (field, value) in [
+        ("captcha_attempts", "3"),
+        ("captcha_enabled", "false"),
+        ("spam_threshold", "2.5"),
+        ("timezone", "Europe/Berlin"),
+        ("log_chat_id", "-1005555"),
+    ] {
+        assert!(
+            chat_config::set(&pool, CHAT_ID, field, Some(value))
+                .await
+                .unwrap()
+        );
+        let got = chat_config::get(&pool, CHAT_ID, field).await.unwrap();
+        assert_eq!(got, Some(Some(value.to_owned())), "{field}");
+    }
+
+    chat_config::set(&pool, CHAT_ID, "log_chat_id", None)
+        .await
+        .unwrap();
+    assert_eq!(
+        chat_config::get(&pool, CHAT_ID, "log_chat_id")
+            .await
+            .unwrap(),
+        Some(None)
+    );
+}
+
+#[sqlx::test(migrations = "./migrations")]
+#[ignore = "requires postgres"]
+async fn set_rejects_invalid_values(pool: PgPool) {
+    ensure_watched_chats(&pool, &[CHAT_ID]).await.unwrap();
+
+    // CHECK (report_hour BETWEEN 0 AND 23).
+    assert!(
+        chat_config::set(&pool, CHAT_ID, "report_hour", Some("24"))
+            .await
+            .is_err()
+    );
+    // Not a boolean.
+    assert!(
+        chat_config::set(&pool, CHAT_ID, "spam_enabled", Some("maybe"))
+            .await
+            .is_err()
+    );
+    // VARCHAR(64): too long is an error, not a silent truncation.
+    let long_model = "m".repeat(65);
+    assert!(
+        chat_config::set(&pool, CHAT_ID, "openai_model", Some(&long_model))
+            .await
+            .is_err()
+    );
+    assert!(
+        chat_config::set(&pool, CHAT_ID, "timezone", Some("Mars/Olympus"))
+            .await
+            .is_err()
+    );
+    // NOT NULL.
+    assert!(
+        chat_config::set(&pool, CHAT_ID, "captcha_attempts", None)
+            .await
+            .is_err()
+    );
+
+    assert_eq!(
+        chat_config::get(&pool, CHAT_ID, "report_hour")
+            .await
+            .unwrap(),
+        Some(Some("17".to_owned())),
+        "failed writes leave the default in place"
+    );
+}
+
+#[sqlx::test(migrations = "./migrations")]
+#[ignore = "requires postgres"]
+async fn missing_row_and_sealed_field(pool: PgPool) {
+    ensure_watched_chats(&pool, &[CHAT_ID]).await.unwrap();
+
+    assert_eq!(
+        chat_config::get(&pool, UNKNOWN_CHAT_ID, "language")
+            .await
+            .unwrap(),
+        None
+    );
+    assert!(
+        !chat_config::set(&pool, UNKNOWN_CHAT_ID, "language", Some("en"))
+            .await
+            .unwrap()
+    );
+    assert!(
+        chat_config::get(&pool, CHAT_ID, "openai_api_key")
+            .await
+            .is_err()
+    );
+    assert!(
+        chat_config::set(&pool, CHAT_ID, "openai_api_key", Some("sk-test"))
+            .await
+            .is_err()
+    );
+}
@@ -0,0 +1,129 @@
+//! `models::chat_moderator` writers/readers + the `ModerationService` role
+//! cache. `#[ignore]`-gated because it needs Postgres on `localhost:5432`.
+
+use sqlx::PgPool;
+use teloxide::Bot;
+use vixen_server::models::chat_moderator::{self, ModeratorRole};
+use vixen_server::services::live_feed::LiveFeed;
+use vixen_server::services::moderation_service::ModerationService;
+use vixen_server::services::outbound::{Limits, OutboundQueue};
+
+const CHAT_ID: i64 = -1001234567890;
+const OTHER_CHAT_ID: i64 = -1009876543210;
+const OWNER_ID: i64 = 1;
+const USER_ID: i64 = 4242;
+
+async fn seed_chat(pool: &PgPool, chat_id: i64) {
+    sqlx::query("INSERT INTO chats (chat_id) VALUES ($1) ON CONFLICT DO NOTHING")
+        .bind(chat_id)
+        .execute(pool)
+        .await
+        .expect("seed chats");
+}
+
+#[sqlx::test(migrations = "./migrations")]
+#[ignore = "requires postgres"]
+async fn test_chat_moderator_grant_sets_role_and_granted_by(pool: PgPool) {
+    seed_chat(&pool, CHAT_ID).await;
+
+    chat_moderator::grant(&pool, CHAT_ID, USER_ID, ModeratorRole::Viewer, OWNER_ID)
+        .await
+        .unwrap();
+    assert_eq!(
+        chat_moderator::role_of(&pool, CHAT_ID, USER_ID)
+            .await
+            .unwrap(),
+        Some(ModeratorRole::Viewer)
+    );
+
+    // Re-grant overwrites role + granted_by instead of conflicting.
+    chat_moderator::grant(&pool, CHAT_ID, USER_ID, ModeratorRole::Admin, 7)
+        .await
+        .unwrap();
+    let list = chat_moderator::list(&pool, CHAT_ID).await.unwrap();
+    assert_eq!(list.len(), 1);
+    assert_eq!(list[0].role, ModeratorRole::Admin);
+    assert_eq!(list[0].granted_by, Some(7));
+}
+
+#[sqlx::test(migrations = "./migrations")]
+#[ignore = "requires postgres"]
+async fn test_chat_moderator_legacy_rows_default_to_moderator(pool: PgPool) {
+    seed_chat(&pool, CHAT_ID).await;
+    sqlx::query("INSERT INTO chat_moderators (chat_id, user_id) VALUES ($1, $2)")
+        .bind(CHAT_ID)
+        .bind(USER_ID)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        chat_moderator::role_of(&pool, CHAT_ID, USER_ID)
+            .await
+            .unwrap(),
+        Some(ModeratorRole::Moderator)
+    );
+}
+
+#[sqlx::test(migrations = "./migrations")]
+#[ignore = "requires postgres"]
+async fn test_chat_moderator_list_orders_by_role_and_chats_for(pool: PgPool) {
+    seed_chat(&pool, CHAT_ID).await;
+    seed_chat(&pool, OTHER_CHAT_ID).await;
+    chat_moderator::grant(&pool, CHAT_ID, 10, ModeratorRole::Viewer, OWNER_ID)
+        .await
+        .unwrap();
+    chat_moderator::grant(&pool, CHAT_ID, OWNER_ID, ModeratorRole::Owner, OWNER_ID)
+        .await
+        .unwrap();
+    chat_moderator::grant(&pool, OTHER_CHAT_ID, 10, ModeratorRole::Moderator, OWNER_ID)
+        .await
+        .unwrap();
+
+    let roles: Vec<ModeratorRole> = chat_moderator::list(&pool, CHAT_ID)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|m| m.role)
+        .collect();
+    assert_eq!(roles, vec![ModeratorRole::Owner, ModeratorRole::Viewer]);
+
+    let chats = chat_moderator::chats_for(&pool, 10).await.unwrap();
+    assert_eq!(chats, vec![OTHER_CHAT_ID, CHAT_ID]);
+}
+
+#[sqlx::test(migrations = "./migrations")]
+#[ignore = "requires postgres"]
+async fn test_moderation_role_cache_invalidated_after_revoke(pool: PgPool) {
+    seed_chat(&pool, CHAT_ID).await;
+    let svc = ModerationService::new(
+        pool.clone(),
+        Bot::new("1:test"),
+        OutboundQueue::new(Limits::default()),
+        LiveFeed::disabled(),
+    );
+
+    chat_moderator::grant(&pool, CHAT_ID, USER_ID, ModeratorRole::Moderator, OWNER_ID)
+        .await
+        .unwrap();
+    assert_eq!(
+        svc.role(CHAT_ID, USER_ID).await.unwrap(),
+        Some(ModeratorRole::Moderator)
+    );
+
+    assert!(
+        chat_moderator::revoke(&pool, CHAT_ID, USER_ID)
+            .await
+            .unwrap()
+    );
+    // Still cached until invalidated.
+    assert!(svc.is_moderator(CHAT_ID, USER_ID).await.unwrap());
+    svc.invalidate_moderator(CHAT_ID, USER_ID).await;
+    assert_eq!(svc.role(CHAT_ID, USER_ID).await.unwrap(), None);
+
+    assert!(
+        !chat_moderator::revoke(&pool, CHAT_ID, USER_ID)
+            .await
+            .unwrap()
+    );
+}
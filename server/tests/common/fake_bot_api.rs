@@ -0,0 +1,419 @@
+//! In-process fake of the Telegram Bot API, for end-to-end tests that run
+//! the real `build_dispatcher` polling loop and background jobs against a
+//! `Bot` pointed at it (`Bot::set_api_url`).
+//!
+//! - Every request is recorded as a [`Call`]: the method and its params as
+//!   JSON. Multipart uploads (`sendPhoto`) keep their text fields and
+//!   replace file parts with `"<file NAME, N bytes>"`.
+//! - [`FakeBotApi::push_update`] queues an update for `getUpdates`. The
+//!   server only polls, so there is no webhook delivery path.
+//! - [`FakeBotApi::fail_next`] makes the next call to a method fail with a
+//!   429 `retry_after` or a 403, as Telegram reports them.
+//! - [`FakeBotApi::stub`] overrides a method's `result` (e.g.
+//!   `getChatAdministrators`). Otherwise send/edit methods echo a plausible
+//!   `Message` and everything else returns `true`.
+//!
+//! `teloxide_tests::MockBot` stays the tool for single-handler tests; this
+//! one is for the dispatcher, polling and job wiring it cannot reach.
+
+use std::collections::{HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicI32, AtomicI64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use axum::Router;
+use axum::body::Bytes;
+use axum::extract::{FromRequest, Multipart, Path, Request, State};
+use axum::http::{StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use serde_json::{Value, json};
+use teloxide::Bot;
+use tokio_util::sync::CancellationToken;
+
+/// Token the fake accepts; any token works, this one just looks real.
+pub const TOKEN: &str = "1234567890:QWERTYUIOPASDFGHJKLZXCVBNMQWERTYUIO";
+/// User id of the bot itself in `getMe` and in sent messages.
+pub const BOT_USER_ID: i64 = 1_234_567_890;
+
+/// Longest a `getUpdates` call blocks when the queue is empty. Short, so a
+/// dispatcher shutdown never waits on a long poll.
+const LONG_POLL_CAP: Duration = Duration::from_millis(500);
+const POLL_STEP: Duration = Duration::from_millis(20);
+/// Default deadline for [`FakeBotApi::wait_for`].
+const WAIT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// One recorded Bot API request.
+#[derive(Debug, Clone)]
+pub struct Call {
+    pub method: String,
+    pub params: Value,
+    /// HTTP status the fake answered with.
+    pub status: u16,
+}
+
+impl Call {
+    /// `params[key]` as i64, accepting the string form multipart sends.
+    pub fn int(&self, key: &str) -> Option<i64> {
+        match &self.params[key] {
+            Value::Number(n) => n.as_i64(),
+            Value::String(s) => s.parse().ok(),
+            _ => None,
+        }
+    }
+}
+
+/// Injected failure for the next call to one method.
+#[derive(Debug, Clone)]
+pub enum Fault {
+    /// 429 with `parameters.retry_after`.
+    RetryAfter(u32),
+    /// 403 with this description, e.g. `"Forbidden: bot was kicked from the
+    /// supergroup chat"`.
+    Forbidden(String),
+    /// 400 with this description.
+    BadRequest(String),
+}
+
+#[derive(Default)]
+struct Inner {
+    calls: Mutex<Vec<Call>>,
+    updates: Mutex<VecDeque<Value>>,
+    faults: Mutex<HashMap<String, VecDeque<Fault>>>,
+    stubs: Mutex<HashMap<String, Value>>,
+    next_update_id: AtomicI64,
+    next_message_id: AtomicI32,
+}
+
+pub struct FakeBotApi {
+    addr: SocketAddr,
+    inner: Arc<Inner>,
+    shutdown: CancellationToken,
+}
+
+impl FakeBotApi {
+    /// Bind an ephemeral local port and start serving.
+    pub async fn start() -> Self {
+        let inner = Arc::new(Inner {
+            next_update_id: AtomicI64::new(1),
+            next_message_id: AtomicI32::new(1_000),
+            ..Inner::default()
+        });
+        let app = Router::new()
+            .route("/:token/:method", post(handle))
+            .with_state(inner.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind fake bot api");
+        let addr = listener.local_addr().expect("fake bot api addr");
+        let shutdown = CancellationToken::new();
+        let stop = shutdown.clone();
+        tokio::spawn(async move {
+            axum::serve(listener, app)
+                .with_graceful_shutdown(async move { stop.cancelled().await })
+                .await
+                .expect("fake bot api serve");
+        });
+        Self {
+            addr,
+            inner,
+            shutdown,
+        }
+    }
+
+    /// A `Bot` that talks to this server.
+    pub fn bot(&self) -> Bot {
+        let url = format!("http://{}/", self.addr).parse().expect("api url");
+        Bot::new(TOKEN).set_api_url(url)
+    }
+
+    /// Queue an update for `getUpdates`; `update_id` is assigned. Returns it.
+    pub fn push_update(&self, mut update: Value) -> i64 {
+        let id = self.inner.next_update_id.fetch_add(1, Ordering::Relaxed);
+        update["update_id"] = json!(id);
+        self.inner.updates.lock().unwrap().push_back(update);
+        id
+    }
+
+    /// Fail the next call to `method`. Queued faults are consumed in order.
+    pub fn fail_next(&self, method: &str, fault: Fault) {
+        self.inner
+            .faults
+            .lock()
+            .unwrap()
+            .entry(method.to_owned())
+            .or_default()
+            .push_back(fault);
+    }
+
+    /// Answer every call to `method` with this `result`.
+    pub fn stub(&self, method: &str, result: Value) {
+        self.inner
+            .stubs
+            .lock()
+            .unwrap()
+            .insert(method.to_owned(), result);
+    }
+
+    pub fn calls(&self) -> Vec<Call> {
+        self.inner.calls.lock().unwrap().clone()
+    }
+
+    pub fn calls_to(&self, method: &str) -> Vec<Call> {
+        self.calls()
+            .into_iter()
+            .filter(|c| c.method == method)
+            .collect()
+    }
+
+    /// Wait until at least `count` calls to `method` were recorded; panics
+    /// after 10s with the full call log.
+    pub async fn wait_for(&self, method: &str, count: usize) -> Vec<Call> {
+        let deadline = tokio::time::Instant::now() + WAIT_TIMEOUT;
+        loop {
+            let calls = self.calls_to(method);
+            if calls.len() >= count {
+                return calls;
+            }
+            if tokio::time::Instant::now() >= deadline {
+                let seen: Vec<_> = self.calls().into_iter().map(|c| c.method).collect();
+                panic!("timed out waiting for {count}× {method}; calls so far: {seen:?}");
+            }
+            tokio::time::sleep(POLL_STEP).await;
+        }
+    }
+}
+
+impl Drop for FakeBotApi {
+    fn drop(&mut self) {
+        self.shutdown.cancel();
+    }
+}
+
+async fn handle(
+    State(inner): State<Arc<Inner>>,
+    Path((_token, method)): Path<(String, String)>,
+    req: Request,
+) -> Response {
+    let params = read_params(req).await;
+
+    let fault = inner
+        .faults
+        .lock()
+        .unwrap()
+        .get_mut(&method)
+        .and_then(VecDeque::pop_front);
+    let (status, body) = match fault {
+        Some(fault) => fault_body(&fault),
+        None => {
+            let result = if method == "getUpdates" {
+                get_updates(&inner, &params).await
+            } else {
+                result_for(&inner, &method, &params)
+            };
+            (StatusCode::OK, json!({ "ok": true, "result": result }))
+        }
+    };
+
+    // `getUpdates` is the polling heartbeat; keep it out of the log unless
+    // it carried something, so assertions on "all calls" stay readable.
+    let polled_nothing = method == "getUpdates" && body["result"] == json!([]);
+    if !polled_nothing {
+        inner.calls.lock().unwrap().push(Call {
+            method,
+            params,
+            status: status.as_u16(),
+        });
+    }
+    (status, axum::Json(body)).into_response()
+}
+
+async fn read_params(req: Request) -> Value {
+    let is_multipart = req
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("multipart/form-data"));
+    if !is_multipart {
+        let bytes = Bytes::from_request(req, &()).await.unwrap_or_default();
+        return serde_json::from_slice(&bytes).unwrap_or(json!({}));
+    }
+
+    let mut multipart = Multipart::from_request(req, &()).await.expect("multipart");
+    let mut params = serde_json::Map::new();
+    while let Some(field) = multipart.next_field().await.expect("multipart field") {
+        let name = field.name().unwrap_or_default().to_owned();
+        let file_name = field.file_name().map(str::to_owned);
+        let data = field.bytes().await.expect("multipart bytes");
+        let value = match file_name {
+            Some(file) => json!(format!("<file {file}, {} bytes>", data.len())),
+            None => {
+                let text = String::from_utf8_lossy(&data).into_owned();
+                // Structured params (reply_markup, …) arrive JSON-encoded.
+                serde_json::from_str(&text).unwrap_or(Value::String(text))
+            }
+        };
+        params.insert(name, value);
+    }
+    Value::Object(params)
+}
+
+fn fault_body(fault: &Fault) -> (StatusCode, Value) {
+    match fault {
+        Fault::RetryAfter(secs) => (
+            StatusCode::TOO_MANY_REQUESTS,
+            json!({
+                "ok": false,
+                "error_code": 429,
+                "description": format!("Too Many Requests: retry after {secs}"),
+                "parameters": { "retry_after": secs },
+            }),
+        ),
+        Fault::Forbidden(description) => (
+            StatusCode::FORBIDDEN,
+            json!({ "ok": false, "error_code": 403, "description": description }),
+        ),
+        Fault::BadRequest(description) => (
+            StatusCode::BAD_REQUEST,
+            json!({ "ok": false, "error_code": 400, "description": description }),
+        ),
+    }
+}
+
+/// Long poll: hand out queued updates with `update_id >= offset`, dropping
+/// the acknowledged ones, or `[]` after [`LONG_POLL_CAP`].
+async fn get_updates(inner: &Inner, params: &Value) -> Value {
+    let offset = params["offset"].as_i64().unwrap_or(0);
+    let deadline = tokio::time::Instant::now() + LONG_POLL_CAP;
+    loop {
+        {
+            let mut queue = inner.updates.lock().unwrap();
+            queue.retain(|u| u["update_id"].as_i64().unwrap_or(0) >= offset);
+            if !queue.is_empty() {
+                return Value::Array(queue.iter().cloned().collect());
+            }
+        }
+        if tokio::time::Instant::now() >= deadline {
+            return json!([]);
+        }
+        tokio::time::sleep(POLL_STEP).await;
+    }
+}
+
+fn result_for(inner: &Inner, method: &str, params: &Value) -> Value {
+    if let Some(stub) = inner.stubs.lock().unwrap().get(method) {
+        return stub.clone();
+    }
+    match method {
+        "getMe" => json!({
+            "id": BOT_USER_ID,
+            "is_bot": true,
+            "first_name": "Vixen",
+            "username": "vixen_test_bot",
+            "can_join_groups": true,
+            "can_read_all_group_messages": true,
+            "supports_inline_queries": false,
+            "can_connect_to_business": false,
+        }),
+        "getChatAdministrators" => json!([]),
+        "sendMessage" | "sendPhoto" | "editMessageText" | "editMessageCaption" => {
+            let message_id = params["message_id"].as_i64().unwrap_or_else(|| {
+                i64::from(inner.next_message_id.fetch_add(1, Ordering::Relaxed))
+            });
+            sent_message(method, message_id, params)
+        }
+        _ => json!(true),
+    }
+}
+
+/// A `Message` from the bot, shaped enough for teloxide to deserialize.
+fn sent_message(method: &str, message_id: i64, params: &Value) -> Value {
+    let chat_id = match &params["chat_id"] {
+        Value::Number(n) => n.as_i64().unwrap_or(0),
+        Value::String(s) => s.parse().unwrap_or(0),
+        _ => 0,
+    };
+    let mut message = json!({
+        "message_id": message_id,
+        "date": chrono::Utc::now().timestamp(),
+        "chat": chat(chat_id),
+        "from": {
+            "id": BOT_USER_ID,
+            "is_bot": true,
+            "first_name": "Vixen",
+            "username": "vixen_test_bot",
+        },
+    });
+    if method == "sendPhoto" {
+        message["photo"] = json!([{
+            "file_id": format!("photo-{message_id}"),
+            "file_unique_id": format!("uphoto-{message_id}"),
+            "width": 320,
+            "height": 160,
+        }]);
+        if let Some(caption) = params.get("caption") {
+            message["caption"] = caption.clone();
+        }
+    } else {
+        message["text"] = params
+            .get("text")
+            .or_else(|| params.get("caption"))
+            .cloned()
+            .unwrap_or(json!(""));
+    }
+    message
+}
+
+/// Supergroup for negative ids, private chat otherwise.
+pub fn chat(chat_id: i64) -> Value {
+    if chat_id < 0 {
+        json!({ "id": chat_id, "type": "supergroup", "title": "Test chat" })
+    } else {
+        json!({ "id": chat_id, "type": "private", "first_name": "Test" })
+    }
+}
+
+/// A `message` update with plain text from a regular user. `update_id` is
+/// set by [`FakeBotApi::push_update`].
+pub fn text_update(chat_id: i64, user_id: u64, message_id: i32, text: &str) -> Value {
+    json!({
+        "message": {
+            "message_id": message_id,
+            "date": chrono::Utc::now().timestamp(),
+            "chat": chat(chat_id),
+            "from": {
+                "id": user_id,
+                "is_bot": false,
+                "first_name": "Tester",
+                "language_code": "en",
+            },
+            "text": text,
+        }
+    })
+}
+
+/// A `callback_query` update: `user_id` pressed a button carrying `data`
+/// under the bot's message `message_id`.
+pub fn callback_update(chat_id: i64, user_id: u64, message_id: i32, data: &str) -> Value {
+    json!({
+        "callback_query": {
+            "id": format!("cb-{message_id}-{data}"),
+            "from": {
+                "id": user_id,
+                "is_bot": false,
+                "first_name": "Tester",
+                "language_code": "en",
+            },
+            "chat_instance": "test",
+            "data": data,
+            "message": {
+                "message_id": message_id,
+                "date": chrono::Utc::now().timestamp(),
+                "chat": chat(chat_id),
+                "from": { "id": BOT_USER_ID, "is_bot": true, "first_name": "Vixen" },
+                "text": "captcha",
+            },
+        }
+    })
+}
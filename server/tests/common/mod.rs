@@ -12,30 +12,45 @@
 
 #![allow(dead_code)]
 
+pub mod fake_bot_api;
+pub mod redis_proxy;
+pub mod webhook_receiver;
+
 use std::sync::Arc;
 
 use sqlx::PgPool;
 use teloxide::Bot;
+use tokio_util::sync::CancellationToken;
 
 use vixen_server::api::AppState;
 use vixen_server::config::Config;
 use vixen_server::database::{Database, Redis};
+use vixen_server::services::api_keys::ApiKeyCipher;
+use vixen_server::services::auth_service::{self, TgIdentity};
 use vixen_server::services::captcha::{CaptchaService, CaptchaState, Fonts};
 use vixen_server::services::cas_client::CasClient;
+use vixen_server::services::live_feed::LiveFeed;
+use vixen_server::services::llm::LlmProviders;
 use vixen_server::services::moderation_service::ModerationService;
-use vixen_server::services::openai_client::OpenAiClient;
+use vixen_server::services::outbound::{Limits, OutboundQueue};
+use vixen_server::services::public_report::PublicReportService;
 use vixen_server::services::report_service::ReportService;
 use vixen_server::services::spam::service::SpamService;
 use vixen_server::services::summary_service::SummaryService;
+use vixen_server::services::user_info::UserInfoCache;
 
 /// Default supergroup ID used across handler tests.
 pub const CHAT_ID: i64 = -1001234567890;
 /// Default Telegram user ID for the actor under test.
 pub const USER_ID: u64 = 4242;
 
+/// HS256 secret in [`test_config`], for dashboard tokens from [`bearer`].
+pub const JWT_SECRET: &str = "abcdefghij1234567890ABCDEFGHIJ12";
+
 /// Build a `Config` from clap with the four required flags filled. Used to
 /// satisfy `AppState`'s `Arc<Config>` field — handler tests don't read any
-/// config value, but the field must exist.
+/// config value, but the field must exist. Dashboard auth is on, signed with
+/// [`JWT_SECRET`].
 pub fn test_config() -> Config {
     use clap::Parser;
     Config::try_parse_from([
@@ -44,10 +59,25 @@ pub fn test_config() -> Config {
         "--database-url=postgresql://x:x@localhost/x",
         "--redis-url=redis://localhost:6379",
         "--chats=-1001234567890",
+        "--secrets-key=AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8=",
+        format!("--jwt-secret={JWT_SECRET}").as_str(),
     ])
     .expect("parse test config")
 }
 
+/// `Authorization` header value for a dashboard caller, as
+/// `POST /auth/telegram/login` would mint it.
+pub fn bearer(user_id: i64, chat_ids: Vec<i64>) -> String {
+    let tg = TgIdentity {
+        id: user_id,
+        first_name: "Test".into(),
+        last_name: None,
+        username: None,
+    };
+    let token = auth_service::mint(tg, chat_ids, JWT_SECRET, 3600).expect("mint test token");
+    format!("Bearer {token}")
+}
+
 /// Connect to Redis on the given URL. **Does NOT FLUSHDB** — flushing is
 /// racy when multiple in-file tests run concurrently against the same DB.
 /// Tests should use [`unique_chat_id`] so their per-chat keys
@@ -117,6 +147,21 @@ pub async fn seed_moderator(pool: &PgPool, chat_id: i64, user_id: i64) {
     .expect("seed chat_moderators");
 }
 
+/// Like [`seed_moderator`] but with an explicit `chat_moderators.role`
+/// (`owner` / `admin` / `moderator` / `viewer`).
+pub async fn seed_moderator_role(pool: &PgPool, chat_id: i64, user_id: i64, role: &str) {
+    sqlx::query(
+        "INSERT INTO chat_moderators (chat_id, user_id, granted_by, role) VALUES ($1, $2, $2, $3)
+         ON CONFLICT (chat_id, user_id) DO UPDATE SET role = EXCLUDED.role",
+    )
+    .bind(chat_id)
+    .bind(user_id)
+    .bind(role)
+    .execute(pool)
+    .await
+    .expect("seed chat_moderators with role");
+}
+
 /// Assemble a full `AppState` around the MockBot's `Bot`. The tests pass this
 /// in as a dptree dep so the real handler endpoints can run unchanged.
 pub async fn make_state(pool: PgPool, redis: Arc<Redis>, bot: Bot) -> AppState {
@@ -127,14 +172,28 @@ pub async fn make_state(pool: PgPool, redis: Arc<Redis>, bot: Bot) -> AppState {
     // `seed_chat` forces) — the spam pipeline short-circuits before the HTTP
     // call. Any string accepted here.
     let cas = CasClient::new(redis.clone(), "http://localhost:0".to_string());
-    let spam = Arc::new(SpamService::new(pool.clone(), cas));
-    let moderation = ModerationService::new(pool.clone(), bot);
+    let config = Arc::new(test_config());
+    let llm = Arc::new(LlmProviders::new("http://localhost:0".to_string()));
+    let api_keys = Arc::new(ApiKeyCipher::from_config(&config).expect("test secrets key"));
+    let spam = Arc::new(SpamService::new(
+        pool.clone(),
+        cas,
+        llm.clone(),
+        api_keys.clone(),
+    ));
+    let outbound = OutboundQueue::new(Limits::default());
+    // Publish-only: events land in the Redis resume buffer, where
+    // `LiveFeed::replay` reads them back.
+    let live = LiveFeed::new(redis.clone(), CancellationToken::new());
+    let moderation =
+        ModerationService::new(pool.clone(), bot.clone(), outbound.clone(), live.clone());
     let reports = Arc::new(ReportService::new(pool.clone()));
-    let openai = Arc::new(OpenAiClient::new("http://localhost:0".to_string()));
-    let summary = SummaryService::new(pool.clone(), openai);
+    let summary = SummaryService::new(pool.clone(), llm, api_keys.clone());
+    let public_reports = PublicReportService::new(reports.clone(), config.chats.clone());
+    let user_info = UserInfoCache::new(pool.clone());
 
     AppState {
-        config: Arc::new(test_config()),
+        config,
         db: Arc::new(Database::from_pool(pool)),
         redis,
         captcha,
@@ -143,5 +202,11 @@ pub async fn make_state(pool: PgPool, redis: Arc<Redis>, bot: Bot) -> AppState {
         moderation,
         reports,
         summary,
+        api_keys,
+        public_reports,
+        bot,
+        outbound,
+        live,
+        user_info,
     }
 }
@@ -0,0 +1,97 @@
+//! TCP proxy in front of the test Redis, so a test can kill Redis mid-run
+//! and bring it back without touching the shared server.
+//!
+//! [`RedisProxy::kill`] drops every proxied connection and closes new ones
+//! on accept until [`RedisProxy::revive`] — what the bot sees when Redis
+//! crashes. Point `Redis::connect` at [`RedisProxy::url`].
+
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use tokio::net::{TcpListener, TcpStream};
+use tokio_util::sync::CancellationToken;
+
+/// The docker compose Redis.
+pub const UPSTREAM: &str = "127.0.0.1:6379";
+
+pub struct RedisProxy {
+    addr: SocketAddr,
+    inner: Arc<Inner>,
+    shutdown: CancellationToken,
+}
+
+struct Inner {
+    alive: AtomicBool,
+    /// Cancelled by `kill`; every proxied connection holds a child.
+    connections: Mutex<CancellationToken>,
+}
+
+impl RedisProxy {
+    pub async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind proxy");
+        let addr = listener.local_addr().expect("proxy addr");
+        let inner = Arc::new(Inner {
+            alive: AtomicBool::new(true),
+            connections: Mutex::new(CancellationToken::new()),
+        });
+        let shutdown = CancellationToken::new();
+        tokio::spawn(accept_loop(listener, inner.clone(), shutdown.clone()));
+        Self {
+            addr,
+            inner,
+            shutdown,
+        }
+    }
+
+    /// `redis://` URL through the proxy, on database `db`.
+    pub fn url(&self, db: u8) -> String {
+        format!("redis://{}/{db}", self.addr)
+    }
+
+    /// Reset every open connection and refuse new ones.
+    pub fn kill(&self) {
+        self.inner.alive.store(false, Ordering::SeqCst);
+        let mut connections = self.inner.connections.lock().unwrap();
+        connections.cancel();
+        *connections = CancellationToken::new();
+    }
+
+    /// Accept connections again.
+    pub fn revive(&self) {
+        self.inner.alive.store(true, Ordering::SeqCst);
+    }
+}
+
+impl Drop for RedisProxy {
+    fn drop(&mut self) {
+        self.shutdown.cancel();
+        self.kill();
+    }
+}
+
+async fn accept_loop(listener: TcpListener, inner: Arc<Inner>, shutdown: CancellationToken) {
+    loop {
+        let mut client = tokio::select! {
+            _ = shutdown.cancelled() => return,
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => stream,
+                Err(_) => continue,
+            },
+        };
+        if !inner.alive.load(Ordering::SeqCst) {
+            drop(client);
+            continue;
+        }
+        let killed = inner.connections.lock().unwrap().child_token();
+        tokio::spawn(async move {
+            let Ok(mut upstream) = TcpStream::connect(UPSTREAM).await else {
+                return;
+            };
+            tokio::select! {
+                _ = killed.cancelled() => {}
+                _ = tokio::io::copy_bidirectional(&mut client, &mut upstream) => {}
+            }
+        });
+    }
+}
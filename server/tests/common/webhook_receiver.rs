@@ -0,0 +1,102 @@
+//! Local HTTP consumer for `services::webhooks` deliveries: records every
+//! POST to `/hook` and answers 200 unless a status was queued with
+//! [`WebhookReceiver::respond_with`].
+
+use std::collections::VecDeque;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use axum::Router;
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::post;
+use tokio_util::sync::CancellationToken;
+
+/// One received POST.
+#[derive(Debug, Clone)]
+pub struct Received {
+    pub headers: HeaderMap,
+    pub body: Bytes,
+}
+
+impl Received {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).and_then(|v| v.to_str().ok())
+    }
+
+    pub fn json(&self) -> serde_json::Value {
+        serde_json::from_slice(&self.body).expect("webhook body is JSON")
+    }
+}
+
+#[derive(Default)]
+struct Inner {
+    received: Mutex<Vec<Received>>,
+    statuses: Mutex<VecDeque<StatusCode>>,
+}
+
+pub struct WebhookReceiver {
+    addr: SocketAddr,
+    inner: Arc<Inner>,
+    shutdown: CancellationToken,
+}
+
+impl WebhookReceiver {
+    pub async fn start() -> Self {
+        let inner = Arc::new(Inner::default());
+        let app = Router::new()
+            .route("/hook", post(receive))
+            .with_state(inner.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind webhook receiver");
+        let addr = listener.local_addr().expect("webhook receiver addr");
+        let shutdown = CancellationToken::new();
+        let stop = shutdown.clone();
+        tokio::spawn(async move {
+            axum::serve(listener, app)
+                .with_graceful_shutdown(async move { stop.cancelled().await })
+                .await
+                .expect("webhook receiver serve");
+        });
+        Self {
+            addr,
+            inner,
+            shutdown,
+        }
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}/hook", self.addr)
+    }
+
+    /// Answer the next POST with `status`. Queued statuses are used in order.
+    pub fn respond_with(&self, status: StatusCode) {
+        self.inner.statuses.lock().unwrap().push_back(status);
+    }
+
+    pub fn received(&self) -> Vec<Received> {
+        self.inner.received.lock().unwrap().clone()
+    }
+}
+
+impl Drop for WebhookReceiver {
+    fn drop(&mut self) {
+        self.shutdown.cancel();
+    }
+}
+
+async fn receive(State(inner): State<Arc<Inner>>, headers: HeaderMap, body: Bytes) -> StatusCode {
+    inner
+        .received
+        .lock()
+        .unwrap()
+        .push(Received { headers, body });
+    inner
+        .statuses
+        .lock()
+        .unwrap()
+        .pop_front()
+        .unwrap_or(StatusCode::OK)
+}
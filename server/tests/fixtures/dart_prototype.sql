@@ -0,0 +1,43 @@
+-- Minimal copy of the Dart prototype's SQLite schema: the two tables
+-- `migrate-from-sqlite` reads, plus one it ignores. Timestamps are unix
+-- seconds, as the prototype wrote them. `update_at` values are relative to
+-- the load time (see tests/sqlite_import.rs) so the retention cut-off is
+-- stable.
+
+CREATE TABLE verified (
+    user_id     INTEGER NOT NULL,
+    chat_id     INTEGER NOT NULL,
+    name        TEXT,
+    verified_at INTEGER NOT NULL,
+    PRIMARY KEY (chat_id, user_id)
+);
+
+CREATE TABLE deleted_message_hash (
+    hash      INTEGER NOT NULL PRIMARY KEY,
+    length    INTEGER NOT NULL,
+    message   TEXT,
+    count     INTEGER NOT NULL DEFAULT 1,
+    update_at INTEGER NOT NULL
+);
+
+CREATE TABLE captcha (
+    user_id INTEGER NOT NULL,
+    chat_id INTEGER NOT NULL,
+    digits  TEXT NOT NULL
+);
+
+-- Watched chat -1001 (two users), unwatched chat -1002.
+INSERT INTO verified (user_id, chat_id, name, verified_at) VALUES
+    (101, -1001, 'Alice', 1714000000),
+    (102, -1001, 'Bob',   1714003600),
+    (201, -1002, 'Carol', 1714000000);
+
+-- Two spellings of one spam text (merge), one more spam text, a short
+-- sample, a NULL sample and a sample older than the 14-day retention.
+INSERT INTO deleted_message_hash (hash, length, message, count, update_at) VALUES
+    (1, 80, 'Earn $500 a day from home, message me for the details of this amazing offer', 3, strftime('%s', 'now', '-2 days')),
+    (2, 80, 'EARN $500 A DAY FROM HOME, MESSAGE ME FOR THE DETAILS OF THIS AMAZING OFFER', 2, strftime('%s', 'now', '-1 days')),
+    (3, 70, 'Crypto signals group with guaranteed profit every single week, join today', 1, strftime('%s', 'now', '-3 days')),
+    (4, 7,  'buy now', 5, strftime('%s', 'now', '-1 days')),
+    (5, 0,  NULL, 1, strftime('%s', 'now', '-1 days')),
+    (6, 75, 'Old spam campaign that ran a long time ago and should have expired already', 1, strftime('%s', 'now', '-60 days'));
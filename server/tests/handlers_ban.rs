@@ -49,7 +49,7 @@ fn cmd_message(chat_id: i64, sender_id: u64, text: &str) -> MockMessageText {
     MockMessageText::new()
         .text(text)
         .chat(MockSupergroupChat::new().id(chat_id).build())
-        .from(MockUser::new().id(sender_id).build())
+        .from(MockUser::new().id(sender_id).language_code("en").build())
 }
 
 fn reply_to_message(
@@ -77,7 +77,7 @@ fn callback(
         .id(message_id)
         .build();
     MockCallbackQuery::new()
-        .from(MockUser::new().id(presser_id).build())
+        .from(MockUser::new().id(presser_id).language_code("en").build())
         .message(msg)
         .data(data_for(&short_id(challenge_id), op))
 }
@@ -0,0 +1,166 @@
+//! Handler-level tests for the role gate in `commands::dispatch` and the
+//! `/mod grant|revoke` command. Same MockBot wiring as `handlers_ban.rs`.
+//!
+//! `#[ignore]`-gated: requires Postgres + Redis on `localhost`.
+
+mod common;
+
+use std::sync::Arc;
+
+use common::*;
+use sqlx::PgPool;
+use teloxide::dispatching::UpdateHandler;
+use teloxide::dptree;
+use teloxide::prelude::*;
+use teloxide_tests::{MockBot, MockMessageText, MockSupergroupChat, MockUser};
+use vixen_server::api::AppState;
+use vixen_server::telegram::commands::Command;
+use vixen_server::telegram::handlers::commands as command_handler;
+
+const REDIS_URL: &str = "redis://localhost:6379/13";
+const ACTOR_ID: u64 = 7777;
+const TARGET_ID: u64 = 1212;
+
+fn handler() -> UpdateHandler<Box<dyn std::error::Error + Send + Sync + 'static>> {
+    Update::filter_message().branch(dptree::entry().filter_command::<Command>().endpoint(
+        |bot: Bot, msg: Message, state: AppState, cmd: Command| async move {
+            command_handler::dispatch(bot, msg, state, cmd)
+                .await
+                .map_err(|e| -> Box<dyn std::error::Error + Send + Sync> { e.into() })
+        },
+    ))
+}
+
+fn cmd_message(chat_id: i64, sender_id: u64, text: &str) -> MockMessageText {
+    MockMessageText::new()
+        .text(text)
+        .chat(MockSupergroupChat::new().id(chat_id).build())
+        .from(MockUser::new().id(sender_id).language_code("en").build())
+}
+
+async fn role_of(pool: &PgPool, chat_id: i64, user_id: u64) -> Option<(String, Option<i64>)> {
+    sqlx::query_as(
+        "SELECT role, granted_by FROM chat_moderators WHERE chat_id = $1 AND user_id = $2",
+    )
+    .bind(chat_id)
+    .bind(user_id as i64)
+    .fetch_optional(pool)
+    .await
+    .expect("select chat_moderators")
+}
+
+async fn run(pool: &PgPool, chat_id: i64, sender_id: u64, text: &str) -> Vec<String> {
+    let redis = fresh_redis(REDIS_URL).await;
+    let mock = MockBot::new(cmd_message(chat_id, sender_id, text), handler());
+    let state = make_state(pool.clone(), Arc::clone(&redis), mock.bot.clone()).await;
+    mock.dependencies(dptree::deps![state]);
+    mock.dispatch().await;
+    mock.get_responses()
+        .sent_messages_text
+        .iter()
+        .map(|m| m.message.text().unwrap_or("").to_string())
+        .collect()
+}
+
+#[sqlx::test(migrations = "./migrations")]
+#[ignore = "requires postgres + redis"]
+async fn test_mod_viewer_cannot_ban(pool: PgPool) {
+    let chat_id = unique_chat_id();
+    seed_chat(&pool, chat_id).await;
+    seed_moderator_role(&pool, chat_id, ACTOR_ID as i64, "viewer").await;
+
+    let texts = run(&pool, chat_id, ACTOR_ID, &format!("/ban {TARGET_ID}")).await;
+    assert!(
+        texts.iter().any(|t| t.contains("Your role (viewer)")),
+        "expected role rejection, got: {texts:?}"
+    );
+    let bans: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM moderation_actions")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(bans, 0);
+}
+
+#[sqlx::test(migrations = "./migrations")]
+#[ignore = "requires postgres + redis"]
+async fn test_mod_admin_grants_moderator_with_granted_by(pool: PgPool) {
+    let chat_id = unique_chat_id();
+    seed_chat(&pool, chat_id).await;
+    seed_moderator_role(&pool, chat_id, ACTOR_ID as i64, "admin").await;
+
+    let texts = run(&pool, chat_id, ACTOR_ID, &format!("/mod grant {TARGET_ID}")).await;
+    assert!(
+        texts.iter().any(|t| t.contains("is now moderator")),
+        "got: {texts:?}"
+    );
+    assert_eq!(
+        role_of(&pool, chat_id, TARGET_ID).await,
+        Some(("moderator".to_string(), Some(ACTOR_ID as i64)))
+    );
+
+    run(
+        &pool,
+        chat_id,
+        ACTOR_ID,
+        &format!("/mod revoke {TARGET_ID}"),
+    )
+    .await;
+    assert_eq!(role_of(&pool, chat_id, TARGET_ID).await, None);
+}
+
+#[sqlx::test(migrations = "./migrations")]
+#[ignore = "requires postgres + redis"]
+async fn test_mod_admin_cannot_grant_admin_or_touch_owner(pool: PgPool) {
+    let chat_id = unique_chat_id();
+    seed_chat(&pool, chat_id).await;
+    seed_moderator_role(&pool, chat_id, ACTOR_ID as i64, "admin").await;
+    seed_moderator_role(&pool, chat_id, TARGET_ID as i64, "owner").await;
+
+    let texts = run(&pool, chat_id, ACTOR_ID, "/mod grant 3131 admin").await;
+    assert!(
+        texts.iter().any(|t| t.contains("can't grant admin")),
+        "got: {texts:?}"
+    );
+    assert_eq!(role_of(&pool, chat_id, 3131).await, None);
+
+    let texts = run(
+        &pool,
+        chat_id,
+        ACTOR_ID,
+        &format!("/mod revoke {TARGET_ID}"),
+    )
+    .await;
+    assert!(
+        texts
+            .iter()
+            .any(|t| t.contains("can't change a user with role owner")),
+        "got: {texts:?}"
+    );
+    assert_eq!(
+        role_of(&pool, chat_id, TARGET_ID).await.map(|r| r.0),
+        Some("owner".to_string())
+    );
+}
+
+#[sqlx::test(migrations = "./migrations")]
+#[ignore = "requires postgres + redis"]
+async fn test_mod_moderator_cannot_manage(pool: PgPool) {
+    let chat_id = unique_chat_id();
+    seed_chat(&pool, chat_id).await;
+    seed_moderator(&pool, chat_id, ACTOR_ID as i64).await;
+
+    let texts = run(
+        &pool,
+        chat_id,
+        ACTOR_ID,
+        &format!("/mod grant {TARGET_ID} viewer"),
+    )
+    .await;
+    assert!(
+        texts
+            .iter()
+            .any(|t| t.contains("Your role (moderator) can't run /mod")),
+        "got: {texts:?}"
+    );
+    assert_eq!(role_of(&pool, chat_id, TARGET_ID).await, None);
+}
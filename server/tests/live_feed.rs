@@ -0,0 +1,298 @@
+//! Live feed end to end: events published through `LiveFeed` — directly,
+//! and by `ModerationService::apply` from another (publish-only) feed, as
+//! another replica would — reach `GET /chats/{chat_id}/events` over Redis
+//! pub/sub, filtered by `?types=`; a reconnect with `Last-Event-ID` replays
+//! what it missed from the resume buffer.
+//!
+//! `#[ignore]`-gated: requires Postgres + Redis on `localhost`.
+
+mod common;
+
+use std::time::Duration;
+
+use axum::Router;
+use axum::body::{Body, BodyDataStream};
+use axum::http::header::{AUTHORIZATION, CONTENT_TYPE};
+use axum::http::{Request, StatusCode};
+use axum::response::Response;
+use chrono::Utc;
+use common::fake_bot_api::FakeBotApi;
+use common::*;
+use futures::StreamExt;
+use sqlx::PgPool;
+use tokio_util::sync::CancellationToken;
+use tower::ServiceExt;
+use vixen_server::api::build_router;
+use vixen_server::models::moderation_action::{ActorKind, PublicReason};
+use vixen_server::services::live_feed::{LiveEventKind, LiveFeed};
+use vixen_server::services::moderation_service::{Action, ApplyContext};
+
+const REDIS_URL: &str = "redis://localhost:6379/7";
+const VIEWER: i64 = 4801;
+const TARGET: i64 = 4802;
+const MODERATOR: i64 = 4803;
+
+#[derive(Debug)]
+struct Frame {
+    id: Option<String>,
+    event: String,
+    data: serde_json::Value,
+}
+
+async fn open(
+    router: &Router,
+    chat_id: i64,
+    query: &str,
+    auth: Option<String>,
+    last_event_id: Option<&str>,
+) -> Response {
+    let mut request = Request::get(format!("/api/v1/chats/{chat_id}/events{query}"));
+    if let Some(auth) = auth {
+        request = request.header(AUTHORIZATION, auth);
+    }
+    if let Some(id) = last_event_id {
+        request = request.header("Last-Event-ID", id);
+    }
+    router
+        .clone()
+        .oneshot(request.body(Body::empty()).unwrap())
+        .await
+        .unwrap()
+}
+
+/// Read until `n` events have arrived, skipping keep-alive comments.
+async fn read_frames(body: &mut BodyDataStream, n: usize) -> Vec<Frame> {
+    let mut buffer = String::new();
+    let mut frames = Vec::new();
+    while frames.len() < n {
+        let chunk = tokio::time::timeout(Duration::from_secs(5), body.next())
+            .await
+            .expect("an event within 5s")
+            .expect("stream still open")
+            .expect("body chunk");
+        buffer.push_str(std::str::from_utf8(&chunk).unwrap());
+        while let Some(end) = buffer.find("\n\n") {
+            let raw: String = buffer.drain(..end + 2).collect();
+            frames.extend(parse_frame(&raw));
+        }
+    }
+    frames
+}
+
+fn parse_frame(raw: &str) -> Option<Frame> {
+    let mut id = None;
+    let mut event = None;
+    let mut data = None;
+    for line in raw.lines() {
+        let Some((field, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.strip_prefix(' ').unwrap_or(value);
+        match field {
+            "id" => id = Some(value.to_owned()),
+            "event" => event = Some(value.to_owned()),
+            "data" => data = Some(serde_json::from_str(value).expect("JSON data")),
+            _ => {}
+        }
+    }
+    Some(Frame {
+        id,
+        event: event?,
+        data: data?,
+    })
+}
+
+#[sqlx::test(migrations = "./migrations")]
+#[ignore = "requires postgres + redis"]
+async fn events_stream_filters_and_resumes(pool: PgPool) {
+    let api = FakeBotApi::start().await;
+    let chat_id = unique_chat_id();
+    let other_chat = unique_chat_id();
+    seed_chat(&pool, chat_id).await;
+    seed_moderator_role(&pool, chat_id, VIEWER, "viewer").await;
+    let redis = fresh_redis(REDIS_URL).await;
+    let mut state = make_state(pool.clone(), redis.clone(), api.bot()).await;
+    // The serving feed; `state.moderation` keeps the publish-only one.
+    let shutdown = CancellationToken::new();
+    let (live, relay) = LiveFeed::start(redis, shutdown.clone());
+    state.live = live.clone();
+    let router = build_router(state.clone());
+    // Let the relay PSUBSCRIBE before anything is published.
+    tokio::time::sleep(Duration::from_millis(300)).await;
+
+    let auth = || Some(bearer(VIEWER, vec![chat_id]));
+    let response = open(
+        &router,
+        chat_id,
+        "?types=captcha.solved,moderation.action",
+        auth(),
+        None,
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()[CONTENT_TYPE].to_str().unwrap(),
+        "text/event-stream"
+    );
+    let mut body = response.into_body().into_data_stream();
+
+    live.publish(
+        chat_id,
+        LiveEventKind::CaptchaIssued {
+            user_id: TARGET,
+            expires_at: Utc::now(),
+        },
+    )
+    .await;
+    live.publish(other_chat, LiveEventKind::CaptchaSolved { user_id: TARGET })
+        .await;
+    live.publish(chat_id, LiveEventKind::CaptchaSolved { user_id: TARGET })
+        .await;
+    let ctx = ApplyContext {
+        chat_id,
+        target_user_id: TARGET,
+        message_id: None,
+        actor_kind: ActorKind::Moderator,
+        actor_user_id: Some(MODERATOR),
+    };
+    let ban = Action::Ban {
+        reason: "raid".into(),
+        until: None,
+    };
+    state.moderation.apply(ban, ctx).await.unwrap();
+
+    let frames = read_frames(&mut body, 2).await;
+    assert_eq!(frames[0].event, "captcha.solved");
+    assert_eq!(frames[0].data["chat_id"], chat_id);
+    assert_eq!(frames[0].data["user_id"], TARGET);
+    assert_eq!(frames[1].event, "moderation.action");
+    assert_eq!(frames[1].data["action"], "ban");
+    assert_eq!(frames[1].data["actor_user_id"], MODERATOR);
+    assert_eq!(frames[1].data["reason"], "raid");
+    assert!(frames.iter().all(|f| f.id.is_some()), "{frames:?}");
+    drop(body);
+
+    // Reconnect after the first event: the ban is replayed, the earlier
+    // `captcha.issued` is not.
+    let resumed = open(&router, chat_id, "", auth(), frames[0].id.as_deref()).await;
+    assert_eq!(resumed.status(), StatusCode::OK);
+    let mut body = resumed.into_body().into_data_stream();
+    let replayed = read_frames(&mut body, 1).await;
+    assert_eq!(replayed[0].event, "moderation.action");
+    assert_eq!(replayed[0].id, frames[1].id);
+
+    // Shutdown ends open streams so the HTTP server can drain.
+    shutdown.cancel();
+    let end = tokio::time::timeout(Duration::from_secs(2), body.next())
+        .await
+        .expect("stream ends on shutdown");
+    assert!(end.is_none());
+    relay.await.unwrap();
+}
+
+#[sqlx::test(migrations = "./migrations")]
+#[ignore = "requires postgres + redis"]
+async fn events_never_carry_the_spam_excerpt(pool: PgPool) {
+    let api = FakeBotApi::start().await;
+    let chat_id = unique_chat_id();
+    seed_chat(&pool, chat_id).await;
+    seed_moderator_role(&pool, chat_id, VIEWER, "viewer").await;
+    let redis = fresh_redis(REDIS_URL).await;
+    let mut state = make_state(pool.clone(), redis.clone(), api.bot()).await;
+    let shutdown = CancellationToken::new();
+    let (live, relay) = LiveFeed::start(redis, shutdown.clone());
+    state.live = live.clone();
+    let router = build_router(state.clone());
+    tokio::time::sleep(Duration::from_millis(300)).await;
+
+    let response = open(
+        &router,
+        chat_id,
+        "",
+        Some(bearer(VIEWER, vec![chat_id])),
+        None,
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let mut body = response.into_body().into_data_stream();
+
+    let verdict = serde_json::json!({
+        "matched_rules": ["ngram"],
+        "score": 3.5,
+        "excerpt": "cheap followers, DM me",
+    });
+    let PublicReason {
+        matched_rules,
+        score,
+        ..
+    } = PublicReason::from_verdict(&verdict);
+    live.publish(
+        chat_id,
+        LiveEventKind::SpamVerdict {
+            user_id: TARGET,
+            message_id: 311,
+            verdict: "ban".into(),
+            matched_rules,
+            score,
+        },
+    )
+    .await;
+    let ctx = ApplyContext {
+        chat_id,
+        target_user_id: TARGET,
+        message_id: Some(311),
+        actor_kind: ActorKind::Bot,
+        actor_user_id: None,
+    };
+    let ban = Action::Ban {
+        reason: verdict.to_string(),
+        until: None,
+    };
+    state.moderation.apply(ban, ctx).await.unwrap();
+
+    let frames = read_frames(&mut body, 2).await;
+    assert_eq!(frames[0].event, "spam.verdict");
+    assert_eq!(frames[1].event, "moderation.action");
+    for frame in &frames {
+        assert_eq!(frame.data["matched_rules"], serde_json::json!(["ngram"]));
+        assert_eq!(frame.data["score"], 3.5);
+        assert!(frame.data.get("excerpt").is_none(), "{frame:?}");
+        assert!(!frame.data.to_string().contains("DM me"), "{frame:?}");
+    }
+    assert!(frames[1].data["reason"].is_null());
+
+    shutdown.cancel();
+    drop(body);
+    relay.await.unwrap();
+}
+
+#[sqlx::test(migrations = "./migrations")]
+#[ignore = "requires postgres + redis"]
+async fn events_stream_rejects_bad_requests(pool: PgPool) {
+    let chat_id = unique_chat_id();
+    seed_chat(&pool, chat_id).await;
+    seed_moderator_role(&pool, chat_id, VIEWER, "viewer").await;
+    let redis = fresh_redis(REDIS_URL).await;
+    let state = make_state(pool.clone(), redis, FakeBotApi::start().await.bot()).await;
+    let router = build_router(state);
+    let auth = |user_id| Some(bearer(user_id, vec![chat_id]));
+
+    let status = |response: Response| response.status();
+    assert_eq!(
+        status(open(&router, chat_id, "", None, None).await),
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(
+        status(open(&router, chat_id, "", auth(TARGET), None).await),
+        StatusCode::FORBIDDEN,
+        "no role in the chat"
+    );
+    assert_eq!(
+        status(open(&router, chat_id, "?types=captcha", auth(VIEWER), None).await),
+        StatusCode::BAD_REQUEST
+    );
+    assert_eq!(
+        status(open(&router, chat_id, "", auth(VIEWER), Some("yesterday")).await),
+        StatusCode::BAD_REQUEST
+    );
+}
@@ -0,0 +1,191 @@
+//! `services::llm` providers against a `wiremock` server: wire format,
+//! provider-specific token accounting and the shared retry loop. No
+//! database needed.
+
+use serde_json::json;
+use vixen_server::services::llm::{
+    self, ChatMessage, ChatRequest, ChatRole, LlmProviders, ProviderKind, ProviderSettings,
+};
+use wiremock::matchers::{body_partial_json, header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn request() -> ChatRequest {
+    ChatRequest {
+        model: "llama3.1:8b".into(),
+        messages: vec![
+            ChatMessage {
+                role: ChatRole::System,
+                content: "Summarise.".into(),
+            },
+            ChatMessage {
+                role: ChatRole::User,
+                content: "- first message\n- second message\n".into(),
+            },
+        ],
+        max_tokens: 500,
+    }
+}
+
+fn settings(kind: ProviderKind, base_url: &str, api_key: Option<&str>) -> ProviderSettings {
+    ProviderSettings {
+        kind,
+        base_url: Some(base_url.to_string()),
+        api_key: api_key.map(str::to_string),
+    }
+}
+
+#[tokio::test]
+async fn ollama_counts_prompt_and_eval_tokens() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/api/chat"))
+        .and(body_partial_json(json!({
+            "model": "llama3.1:8b",
+            "stream": false,
+            "options": {"num_predict": 500}
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "message": {"role": "assistant", "content": "• summary"},
+            "done": true,
+            "prompt_eval_count": 61,
+            "eval_count": 17
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let providers = LlmProviders::new("http://localhost:0".into());
+    let provider = providers
+        .for_chat(&settings(ProviderKind::Ollama, &server.uri(), None))
+        .unwrap();
+    let completion = llm::chat(provider.as_ref(), &request()).await.unwrap();
+    assert_eq!(completion.content, "• summary");
+    assert_eq!(completion.total_tokens, 78);
+}
+
+#[tokio::test]
+async fn openai_compatible_sends_key_only_when_set() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/chat/completions"))
+        .and(header("authorization", "Bearer local-key"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "choices": [{"message": {"content": "ok"}}],
+            "usage": {"prompt_tokens": 40, "completion_tokens": 2}
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let providers = LlmProviders::new("http://localhost:0".into());
+    let provider = providers
+        .for_chat(&settings(
+            ProviderKind::OpenAiCompatible,
+            &server.uri(),
+            Some("local-key"),
+        ))
+        .unwrap();
+    let completion = llm::chat(provider.as_ref(), &request()).await.unwrap();
+    // No `total_tokens` — summed from the parts.
+    assert_eq!(completion.total_tokens, 42);
+}
+
+#[tokio::test]
+async fn openai_compatible_estimates_tokens_without_usage() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/chat/completions"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "choices": [{"message": {"content": "done"}}]
+        })))
+        .mount(&server)
+        .await;
+
+    let providers = LlmProviders::new("http://localhost:0".into());
+    let provider = providers
+        .for_chat(&settings(
+            ProviderKind::OpenAiCompatible,
+            &server.uri(),
+            None,
+        ))
+        .unwrap();
+    let completion = llm::chat(provider.as_ref(), &request()).await.unwrap();
+    assert!(completion.total_tokens > 0, "budget must still move");
+}
+
+#[tokio::test]
+async fn hosted_openai_ignores_chat_base_url() {
+    let hosted = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/chat/completions"))
+        .and(header("authorization", "Bearer sk-test"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "choices": [{"message": {"content": "hosted"}}],
+            "usage": {"total_tokens": 9}
+        })))
+        .expect(1)
+        .mount(&hosted)
+        .await;
+
+    let providers = LlmProviders::new(hosted.uri());
+    let provider = providers
+        .for_chat(&settings(
+            ProviderKind::OpenAi,
+            "http://localhost:0",
+            Some("sk-test"),
+        ))
+        .unwrap();
+    let completion = llm::chat(provider.as_ref(), &request()).await.unwrap();
+    assert_eq!(completion.content, "hosted");
+}
+
+#[tokio::test]
+async fn ollama_retries_server_errors() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/api/chat"))
+        .respond_with(
+            ResponseTemplate::new(503)
+                .insert_header("retry-after", "0")
+                .set_body_string("model loading"),
+        )
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/api/chat"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "message": {"role": "assistant", "content": "warm now"},
+            "done": true,
+            "prompt_eval_count": 5,
+            "eval_count": 5
+        })))
+        .mount(&server)
+        .await;
+
+    let providers = LlmProviders::new("http://localhost:0".into());
+    let provider = providers
+        .for_chat(&settings(ProviderKind::Ollama, &server.uri(), None))
+        .unwrap();
+    let completion = llm::chat(provider.as_ref(), &request()).await.unwrap();
+    assert_eq!(completion.content, "warm now");
+    assert_eq!(completion.total_tokens, 10);
+}
+
+#[tokio::test]
+async fn non_retryable_status_fails_after_one_attempt() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/api/chat"))
+        .respond_with(ResponseTemplate::new(404).set_body_string("model not found"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let providers = LlmProviders::new("http://localhost:0".into());
+    let provider = providers
+        .for_chat(&settings(ProviderKind::Ollama, &server.uri(), None))
+        .unwrap();
+    let err = llm::chat(provider.as_ref(), &request()).await.unwrap_err();
+    assert!(format!("{err:#}").contains("model not found"));
+}
@@ -0,0 +1,126 @@
+//! `services::mod_log` outbox queries against a real `moderation_actions`
+//! table: claiming pending rows only for chats with a log channel, the skip
+//! pass for the rest, and `mark_logged`, which also strip the spam excerpt.
+//! `#[ignore]`-gated because it needs Postgres on `localhost:5432`.
+
+use sqlx::PgPool;
+use vixen_server::services::mod_log;
+
+const LOGGED_CHAT_ID: i64 = -1001234567890;
+const SILENT_CHAT_ID: i64 = -1009876543210;
+const LOG_CHANNEL_ID: i64 = -1005555555555;
+
+async fn seed_chat(pool: &PgPool, chat_id: i64, log_chat_id: Option<i64>) {
+    sqlx::query("INSERT INTO chats (chat_id) VALUES ($1) ON CONFLICT DO NOTHING")
+        .bind(chat_id)
+        .execute(pool)
+        .await
+        .expect("seed chats");
+    sqlx::query("INSERT INTO chat_config (chat_id, log_chat_id) VALUES ($1, $2)")
+        .bind(chat_id)
+        .bind(log_chat_id)
+        .execute(pool)
+        .await
+        .expect("seed chat_config");
+}
+
+async fn insert_action(pool: &PgPool, chat_id: i64, target: i64, action: &str) {
+    sqlx::query(
+        "INSERT INTO moderation_actions (chat_id, target_user_id, action, actor_kind, reason)
+         VALUES ($1, $2, $3, 'bot', 'test')",
+    )
+    .bind(chat_id)
+    .bind(target)
+    .bind(action)
+    .execute(pool)
+    .await
+    .expect("insert moderation_actions");
+}
+
+#[sqlx::test(migrations = "./migrations")]
+#[ignore = "requires postgres"]
+async fn test_mod_log_claims_skip_unconfigured_and_drain(pool: PgPool) {
+    seed_chat(&pool, LOGGED_CHAT_ID, Some(LOG_CHANNEL_ID)).await;
+    seed_chat(&pool, SILENT_CHAT_ID, None).await;
+    insert_action(&pool, LOGGED_CHAT_ID, 1, "ban").await;
+    insert_action(&pool, LOGGED_CHAT_ID, 2, "captcha_failed").await;
+    insert_action(&pool, SILENT_CHAT_ID, 3, "ban").await;
+
+    assert_eq!(mod_log::skip_unconfigured(&pool).await.unwrap(), 1);
+    assert_eq!(mod_log::skip_unconfigured(&pool).await.unwrap(), 0);
+
+    let pending = mod_log::claim_pending(&pool, 50).await.unwrap();
+    let got: Vec<(i64, i64, &str)> = pending
+        .iter()
+        .map(|e| (e.log_chat_id, e.target_user_id, e.action.as_str()))
+        .collect();
+    assert_eq!(
+        got,
+        vec![
+            (LOG_CHANNEL_ID, 1, "ban"),
+            (LOG_CHANNEL_ID, 2, "captcha_failed"),
+        ]
+    );
+
+    // A concurrent pass (another replica) gets nothing while the lease runs.
+    assert!(mod_log::claim_pending(&pool, 50).await.unwrap().is_empty());
+
+    mod_log::mark_logged(&pool, pending[0].id).await.unwrap();
+    mod_log::release(&pool, &[pending[1].id]).await.unwrap();
+    let rest = mod_log::claim_pending(&pool, 50).await.unwrap();
+    assert_eq!(rest.len(), 1);
+    assert_eq!(rest[0].target_user_id, 2);
+
+    assert_eq!(
+        mod_log::log_chat_for(&pool, LOGGED_CHAT_ID).await.unwrap(),
+        Some(LOG_CHANNEL_ID)
+    );
+    assert_eq!(
+        mod_log::log_chat_for(&pool, SILENT_CHAT_ID).await.unwrap(),
+        None
+    );
+}
+
+#[sqlx::test(migrations = "./migrations")]
+#[ignore = "requires postgres"]
+async fn test_mod_log_stamping_strips_the_excerpt(pool: PgPool) {
+    seed_chat(&pool, LOGGED_CHAT_ID, Some(LOG_CHANNEL_ID)).await;
+    seed_chat(&pool, SILENT_CHAT_ID, None).await;
+    let verdict = r#"{"matched_rules":["ngram"],"score":2.0,"excerpt":"buy now"}"#;
+    for (chat_id, target, reason) in [
+        (LOGGED_CHAT_ID, 1, verdict),
+        (SILENT_CHAT_ID, 2, verdict),
+        (LOGGED_CHAT_ID, 3, "{not json} but free text"),
+    ] {
+        sqlx::query(
+            "INSERT INTO moderation_actions (chat_id, target_user_id, action, actor_kind, reason)
+             VALUES ($1, $2, 'ban', 'bot', $3)",
+        )
+        .bind(chat_id)
+        .bind(target)
+        .bind(reason)
+        .execute(&pool)
+        .await
+        .unwrap();
+    }
+
+    mod_log::skip_unconfigured(&pool).await.unwrap();
+    let pending = mod_log::claim_pending(&pool, 50).await.unwrap();
+    // The log entry itself still quotes the message.
+    assert!(pending[0].reason.as_deref().unwrap().contains("buy now"));
+    for entry in &pending {
+        mod_log::mark_logged(&pool, entry.id).await.unwrap();
+    }
+
+    let reasons: Vec<String> =
+        sqlx::query_scalar("SELECT reason FROM moderation_actions ORDER BY target_user_id")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+    for stripped in &reasons[..2] {
+        let json: serde_json::Value = serde_json::from_str(stripped).unwrap();
+        assert_eq!(json["matched_rules"][0], "ngram");
+        assert!(json.get("excerpt").is_none(), "{stripped}");
+    }
+    assert_eq!(reasons[2], "{not json} but free text");
+}
@@ -16,7 +16,9 @@ use teloxide::prelude::*;
 use teloxide_tests::{MockBot, MockMessageText};
 use vixen_server::models::daily_stats::{self, Metric};
 use vixen_server::models::moderation_action::ActorKind;
+use vixen_server::services::live_feed::LiveFeed;
 use vixen_server::services::moderation_service::{Action, ApplyContext, ModerationService};
+use vixen_server::services::outbound::{Limits, OutboundQueue};
 
 const CHAT_ID: i64 = -1001234567890;
 const USER_ID: i64 = 9999;
@@ -38,7 +40,12 @@ struct Trigger {
 
 fn handler() -> UpdateHandler<Box<dyn std::error::Error + Send + Sync + 'static>> {
     dptree::entry().endpoint(|bot: Bot, trigger: Arc<Trigger>| async move {
-        let svc = ModerationService::new(trigger.pool.clone(), bot);
+        let svc = ModerationService::new(
+            trigger.pool.clone(),
+            bot,
+            OutboundQueue::new(Limits::default()),
+            LiveFeed::disabled(),
+        );
         let _ = svc.apply(trigger.action.clone(), trigger.ctx).await;
         Ok::<_, Box<dyn std::error::Error + Send + Sync>>(())
     })
@@ -236,7 +243,12 @@ async fn applied_delete_bumps_messages_deleted_counter(pool: PgPool) {
     let h: UpdateHandler<Box<dyn std::error::Error + Send + Sync + 'static>> =
         Update::filter_message().endpoint(
             |bot: Bot, msg: Message, t: Arc<DeleteTrigger>| async move {
-                let svc = ModerationService::new(t.pool.clone(), bot);
+                let svc = ModerationService::new(
+                    t.pool.clone(),
+                    bot,
+                    OutboundQueue::new(Limits::default()),
+                    LiveFeed::disabled(),
+                );
                 let _ = svc
                     .apply(
                         Action::Delete {
@@ -0,0 +1,225 @@
+//! `services::moderator_sync` against a real `chat_moderators` table: the
+//! full-snapshot reconcile, single-member promotions / demotions, the
+//! manual-grant rule, and the `role_grant` / `role_revoke` ledger rows.
+//! `#[ignore]`-gated because it needs Postgres on `localhost:5432`.
+
+use sqlx::PgPool;
+use teloxide::Bot;
+use teloxide::types::{Administrator, ChatMember, ChatMemberKind, Owner};
+use teloxide_tests::MockUser;
+use vixen_server::models::chat_moderator::{self, ModeratorRole, RoleSource};
+use vixen_server::services::live_feed::LiveFeed;
+use vixen_server::services::moderation_service::ModerationService;
+use vixen_server::services::moderator_sync::{self, RoleChange};
+use vixen_server::services::outbound::{Limits, OutboundQueue};
+
+const CHAT_ID: i64 = -1001234567890;
+const OWNER_ID: i64 = 1;
+const ADMIN_ID: i64 = 2;
+const MANUAL_ID: i64 = 3;
+
+async fn seed_chat(pool: &PgPool, chat_id: i64) {
+    sqlx::query("INSERT INTO chats (chat_id) VALUES ($1) ON CONFLICT DO NOTHING")
+        .bind(chat_id)
+        .execute(pool)
+        .await
+        .expect("seed chats");
+}
+
+fn owner_kind() -> ChatMemberKind {
+    ChatMemberKind::Owner(Owner {
+        custom_title: None,
+        is_anonymous: false,
+    })
+}
+
+fn admin_kind() -> ChatMemberKind {
+    ChatMemberKind::Administrator(Administrator {
+        custom_title: None,
+        is_anonymous: false,
+        can_be_edited: false,
+        can_manage_chat: true,
+        can_change_info: false,
+        can_post_messages: false,
+        can_edit_messages: false,
+        can_delete_messages: true,
+        can_post_stories: false,
+        can_edit_stories: false,
+        can_delete_stories: false,
+        can_manage_video_chats: false,
+        can_invite_users: false,
+        can_restrict_members: true,
+        can_pin_messages: false,
+        can_manage_topics: false,
+        can_promote_members: false,
+    })
+}
+
+fn chat_member(id: i64, kind: ChatMemberKind, is_bot: bool) -> ChatMember {
+    ChatMember {
+        user: MockUser::new().id(id as u64).is_bot(is_bot).build(),
+        kind,
+    }
+}
+
+async fn ledger(pool: &PgPool, action: &str) -> Vec<(i64, Option<String>)> {
+    sqlx::query_as(
+        "SELECT target_user_id, reason FROM moderation_actions
+         WHERE chat_id = $1 AND action = $2 AND actor_kind = 'bot'
+         ORDER BY target_user_id",
+    )
+    .bind(CHAT_ID)
+    .bind(action)
+    .fetch_all(pool)
+    .await
+    .unwrap()
+}
+
+#[sqlx::test(migrations = "./migrations")]
+#[ignore = "requires postgres"]
+async fn test_moderator_sync_chat_mirrors_admins_and_skips_bots(pool: PgPool) {
+    seed_chat(&pool, CHAT_ID).await;
+    let svc = ModerationService::new(
+        pool.clone(),
+        Bot::new("1:test"),
+        OutboundQueue::new(Limits::default()),
+        LiveFeed::disabled(),
+    );
+    let admins = [
+        chat_member(OWNER_ID, owner_kind(), false),
+        chat_member(ADMIN_ID, admin_kind(), false),
+        chat_member(999, admin_kind(), true),
+    ];
+
+    let changes = moderator_sync::sync_chat(&pool, &svc, CHAT_ID, &admins)
+        .await
+        .unwrap();
+    assert_eq!(changes.len(), 2);
+
+    let rows = chat_moderator::list(&pool, CHAT_ID).await.unwrap();
+    let got: Vec<(i64, ModeratorRole, RoleSource)> =
+        rows.iter().map(|m| (m.user_id, m.role, m.source)).collect();
+    assert_eq!(
+        got,
+        vec![
+            (OWNER_ID, ModeratorRole::Owner, RoleSource::Telegram),
+            (ADMIN_ID, ModeratorRole::Admin, RoleSource::Telegram),
+        ]
+    );
+    assert_eq!(
+        ledger(&pool, "role_grant").await,
+        vec![
+            (OWNER_ID, Some("owner".to_string())),
+            (ADMIN_ID, Some("admin".to_string())),
+        ]
+    );
+
+    // Same snapshot again: nothing to do, nothing ledgered.
+    let again = moderator_sync::sync_chat(&pool, &svc, CHAT_ID, &admins)
+        .await
+        .unwrap();
+    assert!(again.is_empty());
+    assert_eq!(ledger(&pool, "role_grant").await.len(), 2);
+}
+
+#[sqlx::test(migrations = "./migrations")]
+#[ignore = "requires postgres"]
+async fn test_moderator_sync_chat_revokes_demoted_but_keeps_manual(pool: PgPool) {
+    seed_chat(&pool, CHAT_ID).await;
+    let svc = ModerationService::new(
+        pool.clone(),
+        Bot::new("1:test"),
+        OutboundQueue::new(Limits::default()),
+        LiveFeed::disabled(),
+    );
+    let before = [
+        chat_member(OWNER_ID, owner_kind(), false),
+        chat_member(ADMIN_ID, admin_kind(), false),
+        chat_member(MANUAL_ID, admin_kind(), false),
+    ];
+    moderator_sync::sync_chat(&pool, &svc, CHAT_ID, &before)
+        .await
+        .unwrap();
+    // An admin downgrades MANUAL_ID to viewer with /mod; that row is now
+    // manual and must survive both the demotion and the re-promotion below.
+    chat_moderator::grant(&pool, CHAT_ID, MANUAL_ID, ModeratorRole::Viewer, OWNER_ID)
+        .await
+        .unwrap();
+
+    let after = [chat_member(OWNER_ID, owner_kind(), false)];
+    let changes = moderator_sync::sync_chat(&pool, &svc, CHAT_ID, &after)
+        .await
+        .unwrap();
+    assert_eq!(
+        changes,
+        vec![RoleChange::Revoke {
+            user_id: ADMIN_ID,
+            role: ModeratorRole::Admin,
+        }]
+    );
+    assert_eq!(
+        chat_moderator::role_of(&pool, CHAT_ID, ADMIN_ID)
+            .await
+            .unwrap(),
+        None
+    );
+    assert_eq!(
+        chat_moderator::role_of(&pool, CHAT_ID, MANUAL_ID)
+            .await
+            .unwrap(),
+        Some(ModeratorRole::Viewer)
+    );
+    assert_eq!(
+        ledger(&pool, "role_revoke").await,
+        vec![(ADMIN_ID, Some("admin".to_string()))]
+    );
+}
+
+#[sqlx::test(migrations = "./migrations")]
+#[ignore = "requires postgres"]
+async fn test_moderator_sync_member_promotion_and_demotion_invalidate_cache(pool: PgPool) {
+    seed_chat(&pool, CHAT_ID).await;
+    let svc = ModerationService::new(
+        pool.clone(),
+        Bot::new("1:test"),
+        OutboundQueue::new(Limits::default()),
+        LiveFeed::disabled(),
+    );
+    // Prime the cache with "no role".
+    assert_eq!(svc.role(CHAT_ID, ADMIN_ID).await.unwrap(), None);
+
+    let change = moderator_sync::sync_member(&pool, &svc, CHAT_ID, ADMIN_ID, &admin_kind())
+        .await
+        .unwrap();
+    assert_eq!(
+        change,
+        Some(RoleChange::Grant {
+            user_id: ADMIN_ID,
+            role: ModeratorRole::Admin,
+        })
+    );
+    assert_eq!(
+        svc.role(CHAT_ID, ADMIN_ID).await.unwrap(),
+        Some(ModeratorRole::Admin)
+    );
+
+    let change =
+        moderator_sync::sync_member(&pool, &svc, CHAT_ID, ADMIN_ID, &ChatMemberKind::Member)
+            .await
+            .unwrap();
+    assert_eq!(
+        change,
+        Some(RoleChange::Revoke {
+            user_id: ADMIN_ID,
+            role: ModeratorRole::Admin,
+        })
+    );
+    assert_eq!(svc.role(CHAT_ID, ADMIN_ID).await.unwrap(), None);
+
+    // A member who never was an admin is a no-op.
+    let change =
+        moderator_sync::sync_member(&pool, &svc, CHAT_ID, MANUAL_ID, &ChatMemberKind::Left)
+            .await
+            .unwrap();
+    assert_eq!(change, None);
+}
@@ -0,0 +1,39 @@
+//! OTLP export smoke test against a wiremock stand-in for the collector.
+//! Installs the global subscriber, so it lives alone in its own test binary.
+//!
+//! `multi_thread`: dropping the guard blocks this worker until the batch
+//! processor's thread has POSTed the spans, and the mock server has to keep
+//! answering on another worker meanwhile.
+
+use tracing::field::Empty;
+use vixen_server::telemetry::{self, otel};
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn spans_reach_the_collector_on_shutdown() {
+    let collector = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/traces"))
+        .and(header("content-type", "application/x-protobuf"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&collector)
+        .await;
+
+    let log_dir = tempfile::tempdir().expect("tempdir");
+    let guard = telemetry::init("info", log_dir.path(), Some(&collector.uri()), 1.0);
+
+    let root = tracing::info_span!("update", update_id = 1, trace_id = Empty);
+    otel::record_trace_id(&root);
+    root.in_scope(|| {
+        let _child = tracing::info_span!("inspect", chat_id = -1001).entered();
+        tracing::info!("spam verdict");
+    });
+    drop(root);
+
+    // Shutdown flushes the batch processor.
+    drop(guard);
+
+    let posts = collector.received_requests().await.expect("recording on");
+    assert!(!posts.is_empty(), "no OTLP export reached the collector");
+}
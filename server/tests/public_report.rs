@@ -0,0 +1,116 @@
+//! `services::public_report` against live Postgres: the slug + opt-in +
+//! watched gate, the last-complete-day window and redaction of spam
+//! samples before they reach the public page.
+
+#![cfg(unix)]
+
+mod common;
+use common::*;
+
+use std::sync::Arc;
+
+use chrono::{Duration, Utc};
+use sqlx::PgPool;
+use vixen_server::services::public_report::PublicReportService;
+use vixen_server::services::report_service::ReportService;
+
+const SLUG: &str = "vixen-public-test";
+
+async fn seed_public_chat(pool: &PgPool, chat_id: i64, opted_in: bool) {
+    seed_chat(pool, chat_id).await;
+    sqlx::query("UPDATE chats SET slug = $2 WHERE chat_id = $1")
+        .bind(chat_id)
+        .bind(SLUG)
+        .execute(pool)
+        .await
+        .expect("set slug");
+    sqlx::query("UPDATE chat_config SET public_report = $2 WHERE chat_id = $1")
+        .bind(chat_id)
+        .bind(opted_in)
+        .execute(pool)
+        .await
+        .expect("set public_report");
+}
+
+fn service(pool: &PgPool, watched: Vec<i64>) -> Arc<PublicReportService> {
+    PublicReportService::new(Arc::new(ReportService::new(pool.clone())), watched)
+}
+
+#[sqlx::test(migrations = "./migrations")]
+#[ignore = "requires postgres"]
+async fn test_public_report_requires_opt_in_and_watched_chat(pool: PgPool) {
+    let chat_id = unique_chat_id();
+    seed_public_chat(&pool, chat_id, false).await;
+    assert!(
+        service(&pool, vec![chat_id])
+            .get(SLUG)
+            .await
+            .unwrap()
+            .is_none(),
+        "not opted in"
+    );
+
+    seed_public_chat(&pool, chat_id, true).await;
+    assert!(
+        service(&pool, vec![]).get(SLUG).await.unwrap().is_none(),
+        "not watched"
+    );
+    assert!(
+        service(&pool, vec![chat_id])
+            .get("no-such-chat")
+            .await
+            .unwrap()
+            .is_none()
+    );
+    assert!(
+        service(&pool, vec![chat_id])
+            .get(SLUG)
+            .await
+            .unwrap()
+            .is_some()
+    );
+}
+
+#[sqlx::test(migrations = "./migrations")]
+#[ignore = "requires postgres"]
+async fn test_public_report_covers_yesterday_and_redacts(pool: PgPool) {
+    let chat_id = unique_chat_id();
+    seed_public_chat(&pool, chat_id, true).await;
+
+    // Chat timezone defaults to UTC: the public page covers yesterday (UTC).
+    let yesterday = Utc::now().date_naive() - Duration::days(1);
+    let seen = yesterday.and_hms_opt(12, 0, 0).unwrap().and_utc();
+    sqlx::query(
+        "INSERT INTO spam_messages (xxh3_hash, sample_body, hit_count, last_seen)
+         VALUES ($1, 'join https://scam.example now, ask @scammer_bot', 4, $2)",
+    )
+    .bind(chat_id)
+    .bind(seen)
+    .execute(&pool)
+    .await
+    .unwrap();
+    sqlx::query(
+        "INSERT INTO spam_messages_per_chat (chat_id, xxh3_hash, hit_count, last_seen)
+         VALUES ($1, $1, 4, $2)",
+    )
+    .bind(chat_id)
+    .bind(seen)
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let report = service(&pool, vec![chat_id])
+        .get(SLUG)
+        .await
+        .unwrap()
+        .expect("public report");
+    assert_eq!(report.date, yesterday);
+    assert_eq!(report.data.chat_id, chat_id);
+    assert_eq!(report.data.top_phrases.len(), 1);
+    assert_eq!(
+        report.data.top_phrases[0].text,
+        "join [link] now, ask [user]"
+    );
+    assert!(!report.chart_webp.is_empty());
+    assert_eq!(report.version.len(), 16);
+}
@@ -0,0 +1,174 @@
+//! Redis killed mid-run (`common::redis_proxy`): commands fail fast, the
+//! bot keeps gating and solving captchas from Postgres, `/health` reports
+//! `degraded`, and the probe brings Redis back with the invalidations made
+//! during the outage applied.
+//!
+//! `#[ignore]`-gated: requires Postgres + Redis on `localhost`.
+
+mod common;
+
+use std::time::{Duration, Instant};
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use common::fake_bot_api::{FakeBotApi, callback_update, text_update};
+use common::redis_proxy::RedisProxy;
+use common::*;
+use serde_json::json;
+use sqlx::PgPool;
+use uuid::Uuid;
+use vixen_server::api::routes_health;
+use vixen_server::database::{Redis, RedisError};
+use vixen_server::services::captcha::keyboard::data_for;
+use vixen_server::services::captcha::{CaptchaState, short_id, solution_for};
+use vixen_server::telegram::{WatchedChats, build_dispatcher};
+
+const DB: u8 = 7;
+const NEWCOMER: u64 = 9101;
+const VERIFIED: u64 = 9102;
+const ADMIN: u64 = 9103;
+const OTHER: u64 = 9104;
+
+async fn proxied() -> (RedisProxy, std::sync::Arc<Redis>) {
+    let proxy = RedisProxy::start().await;
+    let redis = fresh_redis(&proxy.url(DB)).await;
+    (proxy, redis)
+}
+
+#[tokio::test]
+#[ignore = "requires redis"]
+async fn outage_fails_fast_and_the_probe_replays_invalidations() {
+    let (proxy, redis) = proxied().await;
+    let state = CaptchaState::new(redis.clone());
+    let chat_id = unique_chat_id();
+    state.mark_verified(chat_id, 1).await.unwrap();
+    assert!(state.is_verified_cached(chat_id, 1).await.unwrap());
+
+    proxy.kill();
+    assert!(state.is_verified_cached(chat_id, 1).await.is_err());
+    assert!(!redis.is_available());
+
+    let started = Instant::now();
+    let err = redis.query::<Option<String>>(&redis::Cmd::get("k")).await;
+    assert!(matches!(err, Err(RedisError::Unavailable)), "{err:?}");
+    assert!(started.elapsed() < Duration::from_millis(50));
+
+    // Un-verified during the outage: deferred, not an error.
+    state.clear_verified(chat_id, 1).await.unwrap();
+    assert!(!redis.probe().await);
+
+    proxy.revive();
+    assert!(redis.probe().await);
+    assert!(redis.is_available());
+    assert!(
+        !state.is_verified_cached(chat_id, 1).await.unwrap(),
+        "deferred DEL replayed before Redis was marked available"
+    );
+}
+
+#[sqlx::test(migrations = "./migrations")]
+#[ignore = "requires postgres + redis"]
+async fn bot_gates_and_solves_captchas_while_redis_is_down(pool: PgPool) {
+    let api = FakeBotApi::start().await;
+    let chat_id = unique_chat_id();
+    seed_chat(&pool, chat_id).await;
+    seed_verified(&pool, chat_id, VERIFIED as i64).await;
+    let (proxy, redis) = proxied().await;
+    let state = make_state(pool.clone(), redis.clone(), api.bot()).await;
+    let mut dispatcher = build_dispatcher(api.bot(), WatchedChats::new([chat_id]), state.clone());
+    let shutdown = dispatcher.shutdown_token();
+    let dispatcher = tokio::spawn(async move { dispatcher.dispatch().await });
+
+    api.stub(
+        "getChatAdministrators",
+        json!([{
+            "status": "administrator",
+            "user": { "id": ADMIN, "is_bot": false, "first_name": "Admin" },
+            "is_anonymous": false,
+            "can_be_edited": false,
+            "can_manage_chat": true,
+            "can_change_info": true,
+            "can_delete_messages": true,
+            "can_manage_video_chats": true,
+            "can_invite_users": true,
+            "can_restrict_members": true,
+            "can_promote_members": false,
+        }]),
+    );
+
+    proxy.kill();
+    assert!(!redis.probe().await, "a failed PING marks Redis down");
+    let (status, body) = routes_health::health(State(state.clone())).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body.status, "degraded");
+    assert_eq!(body.checks.redis, "down");
+
+    // Verified user and Telegram admin pass; the newcomer gets a captcha.
+    api.push_update(text_update(chat_id, VERIFIED, 10, "hello"));
+    api.push_update(text_update(chat_id, ADMIN, 11, "hello"));
+    api.push_update(text_update(chat_id, NEWCOMER, 12, "hello"));
+    api.wait_for("sendPhoto", 1).await;
+    let deleted = api.wait_for("deleteMessage", 1).await;
+    assert_eq!(deleted[0].int("message_id"), Some(12));
+
+    let (challenge_id, photo_id) = wait_for_photo_id(&pool, chat_id, NEWCOMER as i64).await;
+    let short = short_id(challenge_id);
+    // Meta is rebuilt from the challenge row, digits go to its `input`.
+    for (i, digit) in solution_for(challenge_id).chars().enumerate() {
+        let data = data_for(&short, &digit.to_string());
+        api.push_update(callback_update(chat_id, NEWCOMER, photo_id, &data));
+        if i < 3 {
+            api.wait_for("editMessageCaption", i + 1).await;
+        }
+    }
+    let deleted = api.wait_for("deleteMessage", 2).await;
+    assert_eq!(deleted[1].int("message_id"), Some(i64::from(photo_id)));
+    let verified: bool = sqlx::query_scalar(
+        "SELECT EXISTS(SELECT 1 FROM verified_users WHERE chat_id = $1 AND user_id = $2)",
+    )
+    .bind(chat_id)
+    .bind(NEWCOMER as i64)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert!(verified, "captcha solved from Postgres alone");
+
+    // A follow-up captcha proves the earlier messages were handled.
+    api.push_update(text_update(chat_id, OTHER, 13, "hello"));
+    api.wait_for("sendPhoto", 2).await;
+    let deleted: Vec<_> = api
+        .calls_to("deleteMessage")
+        .iter()
+        .filter_map(|c| c.int("message_id"))
+        .collect();
+    assert!(!deleted.contains(&10), "verified user's message kept");
+    assert!(!deleted.contains(&11), "admin's message kept");
+    // Non-admins' messages reused the in-process admin list.
+    assert_eq!(api.calls_to("getChatAdministrators").len(), 1);
+
+    proxy.revive();
+    assert!(redis.probe().await);
+    let (_, body) = routes_health::health(State(state.clone())).await;
+    assert_eq!(body.status, "ok");
+
+    shutdown.shutdown().expect("dispatcher is running").await;
+    dispatcher.await.expect("dispatcher task");
+}
+
+async fn wait_for_photo_id(pool: &PgPool, chat_id: i64, user_id: i64) -> (Uuid, i32) {
+    for _ in 0..100 {
+        let row: Option<(Uuid, Option<i32>)> = sqlx::query_as(
+            "SELECT id, telegram_message_id FROM captcha_challenges WHERE chat_id = $1 AND user_id = $2",
+        )
+        .bind(chat_id)
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await
+        .unwrap();
+        if let Some((id, Some(message_id))) = row {
+            return (id, message_id);
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("challenge row with the photo's message id");
+}
@@ -11,6 +11,7 @@ use common::*;
 
 use chrono::NaiveDate;
 use sqlx::PgPool;
+use vixen_server::models::ReportPeriod::{Daily, Weekly};
 use vixen_server::models::report_message::{self, ReportKind};
 
 #[sqlx::test(migrations = "./migrations")]
@@ -20,14 +21,14 @@ async fn record_inserts_both_kinds(pool: PgPool) {
     seed_chat(&pool, chat_id).await;
     let date = NaiveDate::from_ymd_opt(2026, 5, 3).unwrap();
 
-    report_message::record(&pool, chat_id, date, ReportKind::Text, 100)
+    report_message::record(&pool, chat_id, date, Daily, ReportKind::Text, 100)
         .await
         .unwrap();
-    report_message::record(&pool, chat_id, date, ReportKind::Photo, 101)
+    report_message::record(&pool, chat_id, date, Daily, ReportKind::Photo, 101)
         .await
         .unwrap();
 
-    let prior = report_message::prior_today(&pool, chat_id, date)
+    let prior = report_message::prior_today(&pool, chat_id, date, Daily)
         .await
         .unwrap();
     assert_eq!(prior.len(), 2);
@@ -53,14 +54,14 @@ async fn record_upserts_on_same_kind(pool: PgPool) {
     seed_chat(&pool, chat_id).await;
     let date = NaiveDate::from_ymd_opt(2026, 5, 3).unwrap();
 
-    report_message::record(&pool, chat_id, date, ReportKind::Text, 100)
+    report_message::record(&pool, chat_id, date, Daily, ReportKind::Text, 100)
         .await
         .unwrap();
-    report_message::record(&pool, chat_id, date, ReportKind::Text, 200)
+    report_message::record(&pool, chat_id, date, Daily, ReportKind::Text, 200)
         .await
         .unwrap();
 
-    let prior = report_message::prior_today(&pool, chat_id, date)
+    let prior = report_message::prior_today(&pool, chat_id, date, Daily)
         .await
         .unwrap();
     assert_eq!(prior.len(), 1);
@@ -75,7 +76,7 @@ async fn prior_today_is_empty_when_no_rows(pool: PgPool) {
     seed_chat(&pool, chat_id).await;
     let date = NaiveDate::from_ymd_opt(2026, 5, 3).unwrap();
 
-    let prior = report_message::prior_today(&pool, chat_id, date)
+    let prior = report_message::prior_today(&pool, chat_id, date, Daily)
         .await
         .unwrap();
     assert!(prior.is_empty());
@@ -89,28 +90,28 @@ async fn delete_for_day_drops_only_target_date(pool: PgPool) {
     let day1 = NaiveDate::from_ymd_opt(2026, 5, 3).unwrap();
     let day2 = NaiveDate::from_ymd_opt(2026, 5, 4).unwrap();
 
-    report_message::record(&pool, chat_id, day1, ReportKind::Text, 1)
+    report_message::record(&pool, chat_id, day1, Daily, ReportKind::Text, 1)
         .await
         .unwrap();
-    report_message::record(&pool, chat_id, day1, ReportKind::Photo, 2)
+    report_message::record(&pool, chat_id, day1, Daily, ReportKind::Photo, 2)
         .await
         .unwrap();
-    report_message::record(&pool, chat_id, day2, ReportKind::Text, 3)
+    report_message::record(&pool, chat_id, day2, Daily, ReportKind::Text, 3)
         .await
         .unwrap();
 
-    report_message::delete_for_day(&pool, chat_id, day1)
+    report_message::delete_for_day(&pool, chat_id, day1, Daily)
         .await
         .unwrap();
 
     assert!(
-        report_message::prior_today(&pool, chat_id, day1)
+        report_message::prior_today(&pool, chat_id, day1, Daily)
             .await
             .unwrap()
             .is_empty()
     );
     assert_eq!(
-        report_message::prior_today(&pool, chat_id, day2)
+        report_message::prior_today(&pool, chat_id, day2, Daily)
             .await
             .unwrap()
             .len(),
@@ -129,27 +130,27 @@ async fn already_posted_today_requires_both_kinds(pool: PgPool) {
     let date = NaiveDate::from_ymd_opt(2026, 5, 3).unwrap();
 
     assert!(
-        !report_message::already_posted_today(&pool, chat_id, date)
+        !report_message::already_posted_today(&pool, chat_id, date, Daily)
             .await
             .unwrap(),
         "no rows → not posted"
     );
 
-    report_message::record(&pool, chat_id, date, ReportKind::Text, 99)
+    report_message::record(&pool, chat_id, date, Daily, ReportKind::Text, 99)
         .await
         .unwrap();
     assert!(
-        !report_message::already_posted_today(&pool, chat_id, date)
+        !report_message::already_posted_today(&pool, chat_id, date, Daily)
             .await
             .unwrap(),
         "text only → still not posted (retry photo on next tick)"
     );
 
-    report_message::record(&pool, chat_id, date, ReportKind::Photo, 100)
+    report_message::record(&pool, chat_id, date, Daily, ReportKind::Photo, 100)
         .await
         .unwrap();
     assert!(
-        report_message::already_posted_today(&pool, chat_id, date)
+        report_message::already_posted_today(&pool, chat_id, date, Daily)
             .await
             .unwrap(),
         "both rows → posted"
@@ -165,21 +166,63 @@ async fn distinct_chats_dont_alias(pool: PgPool) {
     seed_chat(&pool, chat_b).await;
     let date = NaiveDate::from_ymd_opt(2026, 5, 3).unwrap();
 
-    report_message::record(&pool, chat_a, date, ReportKind::Text, 1)
+    report_message::record(&pool, chat_a, date, Daily, ReportKind::Text, 1)
         .await
         .unwrap();
 
     assert_eq!(
-        report_message::prior_today(&pool, chat_a, date)
+        report_message::prior_today(&pool, chat_a, date, Daily)
             .await
             .unwrap()
             .len(),
         1
     );
     assert!(
-        report_message::prior_today(&pool, chat_b, date)
+        report_message::prior_today(&pool, chat_b, date, Daily)
             .await
             .unwrap()
             .is_empty()
     );
 }
+
+#[sqlx::test(migrations = "./migrations")]
+#[ignore = "requires postgres"]
+async fn periods_dont_alias(pool: PgPool) {
+    // A weekly rollup posted on the daily report's day keeps its own pair:
+    // redoing the daily report must not delete the weekly messages, and the
+    // daily gate must not count them.
+    let chat_id = unique_chat_id();
+    seed_chat(&pool, chat_id).await;
+    let date = NaiveDate::from_ymd_opt(2026, 5, 4).unwrap();
+
+    report_message::record(&pool, chat_id, date, Weekly, ReportKind::Text, 10)
+        .await
+        .unwrap();
+    report_message::record(&pool, chat_id, date, Weekly, ReportKind::Photo, 11)
+        .await
+        .unwrap();
+    assert!(
+        report_message::already_posted_today(&pool, chat_id, date, Weekly)
+            .await
+            .unwrap()
+    );
+    assert!(
+        !report_message::already_posted_today(&pool, chat_id, date, Daily)
+            .await
+            .unwrap()
+    );
+
+    report_message::record(&pool, chat_id, date, Daily, ReportKind::Text, 12)
+        .await
+        .unwrap();
+    report_message::delete_for_day(&pool, chat_id, date, Daily)
+        .await
+        .unwrap();
+    assert_eq!(
+        report_message::prior_today(&pool, chat_id, date, Weekly)
+            .await
+            .unwrap()
+            .len(),
+        2
+    );
+}
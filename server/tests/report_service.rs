@@ -9,7 +9,8 @@ use common::*;
 
 use chrono::{Duration, Utc};
 use sqlx::PgPool;
-use vixen_server::services::report_service::ReportService;
+use vixen_server::models::ReportPeriod;
+use vixen_server::services::report_service::{ReportService, period_window_local};
 
 #[sqlx::test(migrations = "./migrations")]
 #[ignore = "requires postgres"]
@@ -118,3 +119,75 @@ async fn aggregate_with_no_data_returns_zeros(pool: PgPool) {
         assert_eq!(p.messages, 0);
     }
 }
+
+#[sqlx::test(migrations = "./migrations")]
+#[ignore = "requires postgres"]
+async fn aggregate_rollup_compares_with_previous_week(pool: PgPool) {
+    let chat_id = unique_chat_id();
+    seed_chat(&pool, chat_id).await;
+
+    // This week: 70 messages, previous week: 50. Captcha only this week.
+    sqlx::query(
+        r#"
+        INSERT INTO daily_stats (chat_id, date, kind, value) VALUES
+            ($1, CURRENT_DATE,                      'messages_seen',  70),
+            ($1, CURRENT_DATE - 9,                  'messages_seen',  50),
+            ($1, CURRENT_DATE,                      'captcha_issued', 4),
+            ($1, CURRENT_DATE,                      'captcha_solved', 3)
+        "#,
+    )
+    .bind(chat_id)
+    .execute(&pool)
+    .await
+    .unwrap();
+    sqlx::query(
+        r#"
+        INSERT INTO moderation_actions (chat_id, target_user_id, action, actor_kind, reason)
+        VALUES
+            ($1, 1001, 'ban', 'bot', '{"matched_rules":["cas"]}'),
+            ($1, 1002, 'ban', 'bot', '{"matched_rules":["cas","ngram"]}'),
+            ($1, 1003, 'ban', 'moderator', 'raid'),
+            ($1, 1004, 'ban', 'moderator', NULL),
+            -- Free text that merely looks like JSON must not fail the query.
+            ($1, 1005, 'ban', 'moderator', '{spam} flood')
+        "#,
+    )
+    .bind(chat_id)
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let today = Utc::now().date_naive();
+    let window = period_window_local(ReportPeriod::Weekly, today, chrono_tz::UTC);
+    let service = ReportService::new(pool.clone());
+    let report = service
+        .aggregate_rollup(chat_id, ReportPeriod::Weekly, window)
+        .await
+        .unwrap();
+
+    assert_eq!(report.period(), ReportPeriod::Weekly);
+    assert_eq!(report.messages_seen, 70);
+    assert_eq!(report.users_banned, 5);
+    let rollup = report.rollup.expect("rollup data");
+    assert_eq!(rollup.previous.messages_seen, 50);
+    assert_eq!(rollup.previous.users_banned, 0);
+
+    assert_eq!(rollup.history.len(), 30);
+    assert_eq!(rollup.history.last().unwrap().date, today);
+    assert_eq!(rollup.history.last().unwrap().messages, 70);
+
+    let reasons: Vec<(&str, i64)> = rollup
+        .top_ban_reasons
+        .iter()
+        .map(|r| (r.reason.as_str(), r.count))
+        .collect();
+    assert_eq!(reasons[0], ("cas", 2));
+    assert!(reasons.contains(&("ngram", 1)));
+    assert!(reasons.contains(&("raid", 1)));
+    assert!(reasons.contains(&("manual", 1)));
+    assert!(reasons.contains(&("{spam} flood", 1)));
+
+    assert_eq!(rollup.captcha_trend.len(), 4);
+    let this_week = rollup.captcha_trend.last().unwrap();
+    assert_eq!((this_week.issued, this_week.solved), (4, 3));
+}
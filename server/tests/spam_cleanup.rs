@@ -55,3 +55,26 @@ async fn prune_is_idempotent_when_empty(pool: PgPool) {
     let pruned = spam_cleanup::prune_expired(&pool, 14).await.unwrap();
     assert_eq!(pruned, 0);
 }
+
+#[sqlx::test(migrations = "./migrations")]
+#[ignore = "requires postgres"]
+async fn prune_drops_aged_summary_chunks(pool: PgPool) {
+    seed_chat(&pool, -100).await;
+    for days_old in [1, 20] {
+        sqlx::query(
+            "INSERT INTO summary_chunks
+                 (chat_id, hour_start, language, model, message_count, summary, tokens_used)
+             VALUES (-100, date_trunc('hour', NOW()) - make_interval(days => $1::int),
+                     'en', 'gpt-4o-mini', 3, '- topic', 40)",
+        )
+        .bind(days_old)
+        .execute(&pool)
+        .await
+        .expect("seed summary_chunks");
+    }
+
+    let pruned = spam_cleanup::prune_summary_chunks(&pool, 14)
+        .await
+        .expect("prune");
+    assert_eq!(pruned, 1, "only the 20d chunk should go");
+}
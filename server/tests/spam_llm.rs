@@ -0,0 +1,175 @@
+//! Opt-in LLM step of `SpamService::inspect`, with the chat's provider
+//! pointed at a `wiremock` Ollama. Each case routes every long message into
+//! the step (`spam_llm_min_score = 0`, unreachable `spam_threshold`).
+
+use std::sync::Arc;
+
+use chrono::Utc;
+use serde_json::json;
+use sqlx::PgPool;
+use teloxide::types::Message;
+use teloxide_tests::{MockMessageText, MockSupergroupChat, MockUser};
+use vixen_server::database::Redis;
+use vixen_server::models::daily_stats::{self, Metric};
+use vixen_server::services::api_keys::ApiKeyCipher;
+use vixen_server::services::cas_client::CasClient;
+use vixen_server::services::llm::LlmProviders;
+use vixen_server::services::spam::service::{SpamService, Verdict};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const REDIS_URL: &str = "redis://localhost:6379/14";
+const TEXT: &str = "Hi all, I am hiring remote assistants, message me privately to learn more";
+
+async fn seed_llm_chat(pool: &PgPool, chat_id: i64, base_url: &str, budget: i32) {
+    sqlx::query("INSERT INTO chats (chat_id) VALUES ($1) ON CONFLICT DO NOTHING")
+        .bind(chat_id)
+        .execute(pool)
+        .await
+        .expect("seed chats");
+    sqlx::query(
+        r#"
+        INSERT INTO chat_config (chat_id, cas_enabled, spam_threshold, spam_llm_enabled,
+                                 spam_llm_min_score, llm_provider, llm_base_url,
+                                 openai_model, summary_token_budget)
+        VALUES ($1, FALSE, 100, TRUE, 0, 'ollama', $2, 'llama3.1:8b', $3)
+        "#,
+    )
+    .bind(chat_id)
+    .bind(base_url)
+    .bind(budget)
+    .execute(pool)
+    .await
+    .expect("seed chat_config");
+}
+
+async fn make_service(pool: PgPool) -> SpamService {
+    let redis = Arc::new(Redis::connect(REDIS_URL).await.expect("redis connect"));
+    let cas = CasClient::new(redis, "http://localhost:0".to_string());
+    // Hosted-OpenAI URL unused — every chat here is on `ollama`.
+    let llm = Arc::new(LlmProviders::new("http://localhost:0".to_string()));
+    let api_keys = Arc::new(ApiKeyCipher::new(None, None).unwrap());
+    SpamService::new(pool, cas, llm, api_keys)
+}
+
+fn message(chat_id: i64, message_id: i32, text: &str) -> Message {
+    let chat = MockSupergroupChat::new().id(chat_id).build();
+    let user = MockUser::new().id(4242).build();
+    MockMessageText::new()
+        .text(text)
+        .chat(chat)
+        .from(user)
+        .id(message_id)
+        .build()
+}
+
+fn ollama_reply(content: &str, tokens: u32) -> ResponseTemplate {
+    ResponseTemplate::new(200).set_body_json(json!({
+        "message": {"role": "assistant", "content": content},
+        "done": true,
+        "prompt_eval_count": tokens - 10,
+        "eval_count": 10
+    }))
+}
+
+async fn tokens_used(pool: &PgPool, chat_id: i64) -> i64 {
+    daily_stats::get(
+        pool,
+        chat_id,
+        Utc::now().date_naive(),
+        Metric::OpenaiTokensUsed,
+    )
+    .await
+    .unwrap()
+}
+
+#[sqlx::test(migrations = "./migrations")]
+#[ignore = "requires postgres + redis"]
+async fn confident_spam_is_deleted_not_banned(pool: PgPool) {
+    let chat_id = -100_700_000_001;
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/api/chat"))
+        .respond_with(ollama_reply(r#"{"spam": true, "confidence": 0.95}"#, 120))
+        .expect(1) // the repeat below is served from the verdict cache
+        .mount(&server)
+        .await;
+    seed_llm_chat(&pool, chat_id, &server.uri(), 50_000).await;
+    let service = make_service(pool.clone()).await;
+
+    for message_id in [1, 2] {
+        let verdict = service.inspect(&message(chat_id, message_id, TEXT)).await;
+        match verdict.unwrap() {
+            Verdict::Delete { reason_json } => {
+                assert_eq!(reason_json["matched_rules"], json!(["llm"]));
+                assert_eq!(reason_json["llm_provider"], "ollama");
+            }
+            other => panic!("expected Delete, got {other:?}"),
+        }
+    }
+
+    // Not recorded for dedup — a dedup hit would ban the next copy.
+    let recorded: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM spam_messages")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(recorded, 0);
+    // Reservation reconciled to the provider's count; the cache hit is free.
+    assert_eq!(tokens_used(&pool, chat_id).await, 120);
+}
+
+#[sqlx::test(migrations = "./migrations")]
+#[ignore = "requires postgres + redis"]
+async fn low_confidence_spam_is_allowed(pool: PgPool) {
+    let chat_id = -100_700_000_002;
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/api/chat"))
+        .respond_with(ollama_reply(r#"{"spam": true, "confidence": 0.55}"#, 100))
+        .mount(&server)
+        .await;
+    seed_llm_chat(&pool, chat_id, &server.uri(), 50_000).await;
+    let service = make_service(pool).await;
+
+    let verdict = service.inspect(&message(chat_id, 1, TEXT)).await.unwrap();
+    assert_eq!(verdict, Verdict::Allow);
+}
+
+#[sqlx::test(migrations = "./migrations")]
+#[ignore = "requires postgres + redis"]
+async fn exhausted_budget_skips_the_call(pool: PgPool) {
+    let chat_id = -100_700_000_003;
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/api/chat"))
+        .respond_with(ollama_reply(r#"{"spam": true, "confidence": 0.99}"#, 100))
+        .expect(0)
+        .mount(&server)
+        .await;
+    seed_llm_chat(&pool, chat_id, &server.uri(), 100).await;
+    daily_stats::increment(&pool, chat_id, Metric::OpenaiTokensUsed, 100)
+        .await
+        .unwrap();
+    let service = make_service(pool).await;
+
+    let verdict = service.inspect(&message(chat_id, 1, TEXT)).await.unwrap();
+    assert_eq!(verdict, Verdict::Allow);
+}
+
+#[sqlx::test(migrations = "./migrations")]
+#[ignore = "requires postgres + redis"]
+async fn provider_failure_fails_open_and_refunds(pool: PgPool) {
+    let chat_id = -100_700_000_004;
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/api/chat"))
+        .respond_with(ResponseTemplate::new(500).insert_header("retry-after", "0"))
+        .mount(&server)
+        .await;
+    seed_llm_chat(&pool, chat_id, &server.uri(), 50_000).await;
+    let service = make_service(pool.clone()).await;
+
+    let verdict = service.inspect(&message(chat_id, 1, TEXT)).await.unwrap();
+    assert_eq!(verdict, Verdict::Allow);
+    assert_eq!(tokens_used(&pool, chat_id).await, 0);
+}
@@ -18,7 +18,9 @@ use sqlx::PgPool;
 use teloxide::types::Message;
 use teloxide_tests::{MockMessageText, MockSupergroupChat, MockUser};
 use vixen_server::database::Redis;
+use vixen_server::services::api_keys::ApiKeyCipher;
 use vixen_server::services::cas_client::CasClient;
+use vixen_server::services::llm::LlmProviders;
 use vixen_server::services::spam::service::{SpamService, Verdict};
 
 const CHAT_ID: i64 = -1001234567890;
@@ -102,7 +104,10 @@ async fn make_service(pool: PgPool) -> SpamService {
     // Base URL is unused once cas_enabled is FALSE in chat_config — the
     // CAS branch never runs, so we can pass any string.
     let cas = CasClient::new(redis, "http://localhost:0".to_string());
-    SpamService::new(pool, cas)
+    // `spam_llm_enabled` defaults to FALSE, so the LLM step never runs here.
+    let llm = Arc::new(LlmProviders::new("http://localhost:0".to_string()));
+    let api_keys = Arc::new(ApiKeyCipher::new(None, None).unwrap());
+    SpamService::new(pool, cas, llm, api_keys)
 }
 
 /// Wipe the global `spam_messages` table between samples. The table has no
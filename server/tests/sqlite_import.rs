@@ -0,0 +1,114 @@
+//! `migrate-from-sqlite` against `fixtures/dart_prototype.sql`, loaded into
+//! a temporary SQLite file per test.
+
+use std::path::PathBuf;
+
+use sqlx::PgPool;
+use tempfile::TempDir;
+use vixen_server::database::{Database, ensure_watched_chats};
+use vixen_server::services::spam::normalize;
+use vixen_server::services::sqlite_import::{self, ImportPlan};
+use xxhash_rust::xxh3::xxh3_64;
+
+const WATCHED: i64 = -1001;
+const MERGED_SPAM: &str =
+    "Earn $500 a day from home, message me for the details of this amazing offer";
+
+fn fixture_db() -> (TempDir, PathBuf) {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("vixen.db");
+    let conn = rusqlite::Connection::open(&path).expect("create sqlite");
+    conn.execute_batch(include_str!("fixtures/dart_prototype.sql"))
+        .expect("load fixture");
+    (dir, path)
+}
+
+fn fixture_plan() -> ImportPlan {
+    let (_dir, path) = fixture_db();
+    let legacy = sqlite_import::read(&path).expect("read fixture");
+    sqlite_import::plan(&legacy, &[WATCHED], 14, chrono::Utc::now())
+}
+
+#[test]
+fn plan_maps_fixture_rows() {
+    let plan = fixture_plan();
+    assert_eq!(plan.verified.len(), 2);
+    assert!(plan.verified.iter().all(|r| r.chat_id == WATCHED));
+    assert_eq!(plan.verified[0].verified_at.timestamp(), 1_714_000_000);
+
+    assert_eq!(plan.spam.len(), 2);
+    let expected = xxh3_64(normalize::normalize(MERGED_SPAM).as_bytes()) as i64;
+    let merged = plan
+        .spam
+        .iter()
+        .find(|r| r.xxh3_hash == expected)
+        .expect("merged spam row");
+    assert_eq!(merged.hit_count, 5);
+    assert_eq!(merged.sample_body, MERGED_SPAM);
+
+    let s = plan.skipped;
+    assert_eq!(s.unwatched_chat, 1);
+    assert_eq!(s.merged, 1);
+    assert_eq!(s.short_sample, 2);
+    assert_eq!(s.expired, 1);
+}
+
+#[test]
+fn read_rejects_a_file_without_prototype_tables() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("empty.db");
+    rusqlite::Connection::open(&path).unwrap();
+    assert!(sqlite_import::read(&path).is_err());
+}
+
+#[sqlx::test(migrations = "./migrations")]
+#[ignore = "requires postgres"]
+async fn import_is_idempotent_and_dry_run_writes_nothing(pool: PgPool) {
+    ensure_watched_chats(&pool, &[WATCHED]).await.unwrap();
+    let plan = fixture_plan();
+
+    let dry = sqlite_import::apply(&pool, &plan, true).await.unwrap();
+    assert_eq!(dry.verified_users.inserted, 2);
+    assert_eq!(dry.spam_messages.inserted, 2);
+    let rows: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM spam_messages")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(rows, 0, "dry run must not write");
+
+    let first = sqlite_import::apply(&pool, &plan, false).await.unwrap();
+    assert_eq!(first.verified_users.inserted, 2);
+    assert_eq!(first.spam_messages.inserted, 2);
+
+    let second = sqlite_import::apply(&pool, &plan, false).await.unwrap();
+    assert_eq!(second.verified_users.inserted, 0);
+    assert_eq!(second.verified_users.existing, 2);
+    assert_eq!(second.spam_messages.inserted, 0);
+    assert_eq!(second.spam_messages.existing, 2);
+
+    // The imported key is what the live pipeline looks up.
+    let hash = xxh3_64(normalize::normalize(MERGED_SPAM).as_bytes()) as i64;
+    let hit_count: i64 =
+        sqlx::query_scalar("SELECT hit_count FROM spam_messages WHERE xxh3_hash = $1")
+            .bind(hash)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(hit_count, 5);
+}
+
+#[sqlx::test(migrations = false)]
+#[ignore = "requires postgres"]
+async fn dry_run_schema_check_is_read_only(pool: PgPool) {
+    // The dry run refuses a schema that is behind instead of migrating it.
+    let db = Database::from_pool(pool.clone());
+    assert!(!db.pending_migrations().await.unwrap().is_empty());
+    let tracked: bool = sqlx::query_scalar("SELECT to_regclass('_sqlx_migrations') IS NOT NULL")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert!(!tracked, "the check must not create _sqlx_migrations");
+
+    db.migrate().await.unwrap();
+    assert_eq!(db.pending_migrations().await.unwrap(), Vec::<i64>::new());
+}
@@ -0,0 +1,232 @@
+//! Verified / banned users lists over real rows: profiles written by
+//! `UserInfoCache::observe`, search by id / username / name, ban state
+//! from the latest ban-or-unban ledger row, keyset pages, and the
+//! `/chats/{chat_id}/moderation/*` routes with a fake Bot API behind the
+//! ban button.
+//!
+//! `#[ignore]`-gated: requires Postgres (and Redis for the HTTP test) on
+//! `localhost`.
+
+mod common;
+
+use axum::body::Body;
+use axum::http::header::{AUTHORIZATION, CONTENT_TYPE};
+use axum::http::{Request, StatusCode};
+use common::fake_bot_api::{FakeBotApi, Fault};
+use common::*;
+use serde_json::{Value, json};
+use sqlx::PgPool;
+use teloxide_tests::MockUser;
+use tower::ServiceExt;
+use vixen_server::api::build_router;
+use vixen_server::services::user_info::{UserInfoCache, UserProfile};
+use vixen_server::services::user_lists;
+
+const REDIS_URL: &str = "redis://localhost:6379/9";
+const MODERATOR: i64 = 5001;
+const VIEWER: i64 = 5002;
+
+async fn observe(cache: &UserInfoCache, id: u64, username: &str, first_name: &str) {
+    let mut user = MockUser::new().id(id).build();
+    user.username = Some(username.to_owned());
+    user.first_name = first_name.to_owned();
+    user.last_name = None;
+    cache.observe(&user).await.expect("observe");
+}
+
+async fn record(pool: &PgPool, chat_id: i64, target: i64, action: &str, minutes_ago: i32) {
+    sqlx::query(
+        "INSERT INTO moderation_actions
+             (chat_id, target_user_id, action, actor_kind, actor_user_id, reason, created_at)
+         VALUES ($1, $2, $3, 'moderator', $4, 'raid', NOW() - make_interval(mins => $5))",
+    )
+    .bind(chat_id)
+    .bind(target)
+    .bind(action)
+    .bind(MODERATOR)
+    .bind(minutes_ago)
+    .execute(pool)
+    .await
+    .expect("insert moderation_actions");
+}
+
+async fn verified_ids(pool: &PgPool, chat_id: i64, q: Option<&str>) -> Vec<i64> {
+    user_lists::verified(pool, chat_id, q, None, 100)
+        .await
+        .unwrap()
+        .iter()
+        .map(|u| u.user_id)
+        .collect()
+}
+
+#[sqlx::test(migrations = "./migrations")]
+#[ignore = "requires postgres"]
+async fn profiles_are_upserted_and_joined(pool: PgPool) {
+    let chat_id = unique_chat_id();
+    seed_chat(&pool, chat_id).await;
+    let cache = UserInfoCache::new(pool.clone());
+    observe(&cache, 11, "alice_w", "Alice").await;
+    observe(&cache, 12, "bob", "Robert").await;
+    // Renames overwrite the row.
+    observe(&cache, 12, "bobby", "Robert").await;
+    let mut bot = MockUser::new().id(13).build();
+    bot.is_bot = true;
+    cache.observe(&bot).await.unwrap();
+
+    assert_eq!(
+        cache.get(12).await.unwrap(),
+        Some(UserProfile {
+            username: Some("bobby".into()),
+            first_name: "Robert".into(),
+            last_name: None,
+        })
+    );
+    assert_eq!(cache.get(13).await.unwrap(), None, "bots are skipped");
+
+    for user_id in [11, 12, 14] {
+        seed_verified(&pool, chat_id, user_id).await;
+    }
+    let all = user_lists::verified(&pool, chat_id, None, None, 100)
+        .await
+        .unwrap();
+    assert_eq!(all.len(), 3);
+    let unknown = all.iter().find(|u| u.user_id == 14).unwrap();
+    assert_eq!(unknown.username, None, "never observed");
+
+    assert_eq!(verified_ids(&pool, chat_id, Some("@BOBBY")).await, vec![12]);
+    assert_eq!(verified_ids(&pool, chat_id, Some("alice")).await, vec![11]);
+    assert_eq!(verified_ids(&pool, chat_id, Some("14")).await, vec![14]);
+    // Ids match exactly, not as a substring.
+    assert!(verified_ids(&pool, chat_id, Some("1")).await.is_empty());
+}
+
+#[sqlx::test(migrations = "./migrations")]
+#[ignore = "requires postgres"]
+async fn banned_is_the_latest_ban_state(pool: PgPool) {
+    let chat_id = unique_chat_id();
+    seed_chat(&pool, chat_id).await;
+    record(&pool, chat_id, 21, "ban", 50).await;
+    record(&pool, chat_id, 22, "ban", 40).await;
+    record(&pool, chat_id, 22, "unban", 30).await;
+    record(&pool, chat_id, 23, "ban", 20).await;
+    record(&pool, chat_id, 23, "delete", 10).await;
+    record(&pool, chat_id, 24, "unban", 5).await;
+
+    let banned = user_lists::banned(&pool, chat_id, None, None, 100)
+        .await
+        .unwrap();
+    let ids: Vec<i64> = banned.iter().map(|b| b.user_id).collect();
+    assert_eq!(
+        ids,
+        vec![23, 21],
+        "newest ban first; unbanned users drop out"
+    );
+    assert_eq!(banned[0].actor_kind, "moderator");
+    assert_eq!(banned[0].actor_user_id, Some(MODERATOR));
+    assert_eq!(banned[0].reason.as_deref(), Some("raid"));
+
+    let first = user_lists::banned(&pool, chat_id, None, None, 1)
+        .await
+        .unwrap();
+    let rest = user_lists::banned(&pool, chat_id, None, Some(first[0].cursor()), 1)
+        .await
+        .unwrap();
+    assert_eq!(rest[0].user_id, 21);
+
+    let other_chat = unique_chat_id();
+    seed_chat(&pool, other_chat).await;
+    assert!(
+        user_lists::banned(&pool, other_chat, None, None, 100)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[sqlx::test(migrations = "./migrations")]
+#[ignore = "requires postgres + redis"]
+async fn moderation_routes_list_and_mutate(pool: PgPool) {
+    let chat_id = unique_chat_id();
+    seed_chat(&pool, chat_id).await;
+    seed_moderator_role(&pool, chat_id, MODERATOR, "moderator").await;
+    seed_moderator_role(&pool, chat_id, VIEWER, "viewer").await;
+    let redis = fresh_redis(REDIS_URL).await;
+    let api = FakeBotApi::start().await;
+    let state = make_state(pool.clone(), redis, api.bot()).await;
+    observe(&state.user_info, 31, "carol", "Carol").await;
+    let router = build_router(state);
+    let call = |method: &str, path: &str, actor: i64, body: Option<Value>| {
+        let router = router.clone();
+        let builder = Request::builder()
+            .method(method)
+            .uri(format!("/api/v1/chats/{chat_id}/moderation/{path}"))
+            .header(AUTHORIZATION, bearer(actor, vec![chat_id]));
+        let request = match body {
+            Some(body) => builder
+                .header(CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string())),
+            None => builder.body(Body::empty()),
+        }
+        .unwrap();
+        async move {
+            let response = router.oneshot(request).await.unwrap();
+            let status = response.status();
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            (status, serde_json::from_slice::<Value>(&bytes).unwrap())
+        }
+    };
+
+    let (status, json) = call("POST", "verify", MODERATOR, Some(json!({"user_id": 31}))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["data"]["applied"], true);
+    assert_eq!(json["data"]["user"]["username"], "carol");
+    let (_, json) = call("POST", "verify", MODERATOR, Some(json!({"user_id": 31}))).await;
+    assert_eq!(json["data"]["applied"], false, "already verified");
+
+    let (status, json) = call("GET", "verified?q=%40car", VIEWER, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["data"]["items"][0]["user_id"], 31);
+    assert_eq!(json["data"]["has_more"], false);
+
+    // Viewers read; moderators act.
+    let (status, _) = call("POST", "ban", VIEWER, Some(json!({"user_id": 32}))).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = call("POST", "ban", MODERATOR, Some(json!({"user_id": 0}))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    // Role holders are only acted on from strictly above, as with `/mod`.
+    let (status, json) = call("POST", "ban", MODERATOR, Some(json!({"user_id": VIEWER}))).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(json["error"]["code"], "FORBIDDEN");
+    assert!(api.calls_to("banChatMember").is_empty());
+
+    api.fail_next(
+        "banChatMember",
+        Fault::BadRequest("Bad Request: nope".into()),
+    );
+    let (status, json) = call("POST", "ban", MODERATOR, Some(json!({"user_id": 32}))).await;
+    assert_eq!(status, StatusCode::BAD_GATEWAY);
+    assert_eq!(json["error"]["code"], "BOT_API_ERROR");
+
+    let (status, json) = call("POST", "ban", MODERATOR, Some(json!({"user_id": 32}))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["data"]["applied"], true);
+    assert!(json["data"]["user"].is_null());
+    let (_, json) = call("GET", "banned", VIEWER, None).await;
+    let item = &json["data"]["items"][0];
+    assert_eq!(item["user_id"], 32);
+    assert_eq!(item["actor_user_id"], MODERATOR);
+    assert_eq!(item["reason"], "manual ban (no reason)");
+
+    let (_, json) = call("POST", "unban", MODERATOR, Some(json!({"user_id": 32}))).await;
+    assert_eq!(json["data"]["applied"], true);
+    let (_, json) = call("GET", "banned", VIEWER, None).await;
+    assert_eq!(json["data"]["items"], json!([]));
+    assert_eq!(api.calls_to("unbanChatMember").len(), 1);
+
+    let (_, json) = call("POST", "unverify", MODERATOR, Some(json!({"user_id": 31}))).await;
+    assert_eq!(json["data"]["applied"], true);
+    let (status, _) = call("GET", "verified?cursor=nope", VIEWER, None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
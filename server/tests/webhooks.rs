@@ -0,0 +1,337 @@
+//! Outbox + webhook delivery end to end: events are written with the
+//! action's transaction (and rolled back with it), fanned out per endpoint,
+//! POSTed signed to a local receiver (`common::webhook_receiver`), retried
+//! with backoff, dead-lettered and re-driven.
+//!
+//! `#[ignore]`-gated: requires Postgres on `localhost:5432`.
+
+mod common;
+
+use std::sync::Arc;
+
+use axum::http::StatusCode;
+use common::fake_bot_api::{FakeBotApi, Fault};
+use common::webhook_receiver::WebhookReceiver;
+use common::{seed_chat, test_config, unique_chat_id};
+use sqlx::PgPool;
+use uuid::Uuid;
+use vixen_server::config::SecretsKey;
+use vixen_server::jobs::webhooks::http_client;
+use vixen_server::models::moderation_action::ActorKind;
+use vixen_server::services::api_keys::{self, ApiKeyCipher};
+use vixen_server::services::captcha::{CaptchaService, Fonts};
+use vixen_server::services::live_feed::LiveFeed;
+use vixen_server::services::moderation_service::{Action, ApplyContext, ModerationService};
+use vixen_server::services::outbound::{Limits, OutboundQueue};
+use vixen_server::services::webhooks::{
+    self, CreateOutcome, DELIVERY_HEADER, DeliveryOutcome, EVENT_HEADER, MAX_ATTEMPTS,
+    SIGNATURE_HEADER,
+};
+
+const MODERATOR: i64 = 7001;
+const TARGET: i64 = 7002;
+
+fn cipher() -> Arc<ApiKeyCipher> {
+    Arc::new(ApiKeyCipher::from_config(&test_config()).expect("test secrets key"))
+}
+
+async fn endpoint(pool: &PgPool, chat_id: i64, url: &str) -> (Uuid, String) {
+    match webhooks::create_endpoint(pool, &cipher(), chat_id, url, MODERATOR)
+        .await
+        .unwrap()
+    {
+        CreateOutcome::Created(c) => (c.endpoint.id, c.secret),
+        other => panic!("endpoint not created: {other:?}"),
+    }
+}
+
+async fn outbox_types(pool: &PgPool, chat_id: i64) -> Vec<String> {
+    sqlx::query_scalar(
+        "SELECT event_type FROM outbox_events WHERE chat_id = $1 ORDER BY created_at",
+    )
+    .bind(chat_id)
+    .fetch_all(pool)
+    .await
+    .unwrap()
+}
+
+async fn delivery_state(pool: &PgPool, endpoint_id: Uuid) -> (String, i32, Option<String>) {
+    sqlx::query_as(
+        "SELECT status, attempts, last_error FROM webhook_deliveries WHERE endpoint_id = $1",
+    )
+    .bind(endpoint_id)
+    .fetch_one(pool)
+    .await
+    .unwrap()
+}
+
+#[sqlx::test(migrations = "./migrations")]
+#[ignore = "requires postgres"]
+async fn manual_verify_is_delivered_signed(pool: PgPool) {
+    let receiver = WebhookReceiver::start().await;
+    let chat_id = unique_chat_id();
+    seed_chat(&pool, chat_id).await;
+    let (endpoint_id, secret) = endpoint(&pool, chat_id, &receiver.url()).await;
+    let captcha = CaptchaService::new(pool.clone(), Fonts::load().expect("fonts"));
+
+    captcha
+        .verify_manual(chat_id, TARGET, Some(MODERATOR))
+        .await
+        .unwrap();
+    assert_eq!(outbox_types(&pool, chat_id).await, vec!["user.verified"]);
+
+    assert_eq!(webhooks::fan_out(&pool, 50).await.unwrap(), 1);
+    assert_eq!(webhooks::fan_out(&pool, 50).await.unwrap(), 0);
+    let due = webhooks::claim_due(&pool, 50).await.unwrap();
+    assert_eq!(due.len(), 1);
+    // Claimed rows are leased away from a concurrent pass.
+    assert!(webhooks::claim_due(&pool, 50).await.unwrap().is_empty());
+
+    // The secret is stored sealed, never as the `whsec_` value.
+    assert_ne!(due[0].secret, secret);
+    let outcome = webhooks::deliver(&pool, &http_client(true).unwrap(), &cipher(), &due[0])
+        .await
+        .unwrap();
+    assert_eq!(outcome, DeliveryOutcome::Delivered);
+    assert_eq!(
+        delivery_state(&pool, endpoint_id).await,
+        ("delivered".into(), 1, None)
+    );
+
+    let received = receiver.received();
+    assert_eq!(received.len(), 1);
+    let post = &received[0];
+    assert_eq!(post.header(EVENT_HEADER), Some("user.verified"));
+    assert_eq!(
+        post.header(DELIVERY_HEADER),
+        Some(due[0].event_id.to_string().as_str())
+    );
+    let body = post.json();
+    assert_eq!(body["type"], "user.verified");
+    assert_eq!(body["chat_id"], chat_id);
+    assert_eq!(body["user_id"], TARGET);
+    assert_eq!(body["actor_kind"], "moderator");
+    assert_eq!(body["actor_user_id"], MODERATOR);
+    assert!(body["action_id"].is_string(), "{body}");
+
+    let signature = post.header(SIGNATURE_HEADER).expect("signed");
+    let timestamp: i64 = signature
+        .strip_prefix("t=")
+        .and_then(|s| s.split(',').next())
+        .and_then(|t| t.parse().ok())
+        .expect("t=<unix secs>");
+    assert_eq!(signature, webhooks::sign(&secret, timestamp, &post.body));
+}
+
+#[sqlx::test(migrations = "./migrations")]
+#[ignore = "requires postgres"]
+async fn ban_event_commits_and_rolls_back_with_the_action(pool: PgPool) {
+    let api = FakeBotApi::start().await;
+    let chat_id = unique_chat_id();
+    seed_chat(&pool, chat_id).await;
+    let moderation = ModerationService::new(
+        pool.clone(),
+        api.bot(),
+        OutboundQueue::new(Limits::default()),
+        LiveFeed::disabled(),
+    );
+    let ctx = ApplyContext {
+        chat_id,
+        target_user_id: TARGET,
+        message_id: None,
+        actor_kind: ActorKind::Moderator,
+        actor_user_id: Some(MODERATOR),
+    };
+    let ban = || Action::Ban {
+        reason: "raid".into(),
+        until: None,
+    };
+
+    // A fatal Bot API error rolls back the ledger row and the event.
+    api.fail_next(
+        "banChatMember",
+        Fault::BadRequest("Bad Request: nope".into()),
+    );
+    assert!(moderation.apply(ban(), ctx).await.is_err());
+    assert!(outbox_types(&pool, chat_id).await.is_empty());
+
+    moderation.apply(ban(), ctx).await.unwrap();
+    // Already in effect: no second event.
+    moderation.apply(ban(), ctx).await.unwrap();
+    moderation.apply(Action::Unban, ctx).await.unwrap();
+    assert_eq!(
+        outbox_types(&pool, chat_id).await,
+        vec!["user.banned", "user.unbanned"]
+    );
+    let payload: serde_json::Value = sqlx::query_scalar(
+        "SELECT payload FROM outbox_events WHERE chat_id = $1 AND event_type = 'user.banned'",
+    )
+    .bind(chat_id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(payload["reason"], "raid");
+
+    // Deletes stay internal.
+    let delete = ApplyContext {
+        message_id: Some(55),
+        actor_kind: ActorKind::Bot,
+        actor_user_id: None,
+        ..ctx
+    };
+    moderation
+        .apply(
+            Action::Delete {
+                reason: "spam".into(),
+            },
+            delete,
+        )
+        .await
+        .unwrap();
+    assert_eq!(outbox_types(&pool, chat_id).await.len(), 2);
+}
+
+#[sqlx::test(migrations = "./migrations")]
+#[ignore = "requires postgres"]
+async fn failures_back_off_dead_letter_and_redrive(pool: PgPool) {
+    let receiver = WebhookReceiver::start().await;
+    let chat_id = unique_chat_id();
+    let silent_chat = unique_chat_id();
+    seed_chat(&pool, chat_id).await;
+    seed_chat(&pool, silent_chat).await;
+    let (endpoint_id, _) = endpoint(&pool, chat_id, &receiver.url()).await;
+    let captcha = CaptchaService::new(pool.clone(), Fonts::load().expect("fonts"));
+    let http = http_client(true).unwrap();
+
+    captcha.verify_manual(chat_id, TARGET, None).await.unwrap();
+    // A chat without endpoints is stamped without deliveries.
+    captcha
+        .verify_manual(silent_chat, TARGET, None)
+        .await
+        .unwrap();
+    assert_eq!(webhooks::fan_out(&pool, 50).await.unwrap(), 2);
+
+    receiver.respond_with(StatusCode::INTERNAL_SERVER_ERROR);
+    let due = webhooks::claim_due(&pool, 50).await.unwrap();
+    assert_eq!(due.len(), 1);
+    let outcome = webhooks::deliver(&pool, &http, &cipher(), &due[0])
+        .await
+        .unwrap();
+    assert_eq!(outcome, DeliveryOutcome::Retry(webhooks::backoff(1)));
+    assert_eq!(
+        delivery_state(&pool, endpoint_id).await,
+        ("pending".into(), 1, Some("HTTP 500".into()))
+    );
+    assert!(
+        webhooks::claim_due(&pool, 50).await.unwrap().is_empty(),
+        "not due before the backoff"
+    );
+
+    // Skip ahead to the last attempt.
+    sqlx::query(
+        "UPDATE webhook_deliveries SET attempts = $2, next_attempt_at = NOW() WHERE endpoint_id = $1",
+    )
+    .bind(endpoint_id)
+    .bind(MAX_ATTEMPTS - 1)
+    .execute(&pool)
+    .await
+    .unwrap();
+    receiver.respond_with(StatusCode::SERVICE_UNAVAILABLE);
+    let due = webhooks::claim_due(&pool, 50).await.unwrap();
+    let outcome = webhooks::deliver(&pool, &http, &cipher(), &due[0])
+        .await
+        .unwrap();
+    assert_eq!(outcome, DeliveryOutcome::Dead);
+    let listed = webhooks::list_endpoints(&pool, chat_id).await.unwrap();
+    assert_eq!((listed[0].pending, listed[0].dead), (0, 1));
+    assert!(webhooks::claim_due(&pool, 50).await.unwrap().is_empty());
+
+    assert_eq!(
+        webhooks::redrive(&pool, silent_chat, endpoint_id)
+            .await
+            .unwrap(),
+        None,
+        "endpoint belongs to another chat"
+    );
+    assert_eq!(
+        webhooks::redrive(&pool, chat_id, endpoint_id)
+            .await
+            .unwrap(),
+        Some(1)
+    );
+    let due = webhooks::claim_due(&pool, 50).await.unwrap();
+    let outcome = webhooks::deliver(&pool, &http, &cipher(), &due[0])
+        .await
+        .unwrap();
+    assert_eq!(outcome, DeliveryOutcome::Delivered);
+    assert_eq!(receiver.received().len(), 3);
+    // Same event id on every attempt, so the consumer can dedup.
+    let ids: Vec<_> = receiver
+        .received()
+        .iter()
+        .map(|r| r.header(DELIVERY_HEADER).map(str::to_owned))
+        .collect();
+    assert!(ids.windows(2).all(|w| w[0] == w[1]), "{ids:?}");
+}
+
+#[sqlx::test(migrations = "./migrations")]
+#[ignore = "requires postgres"]
+async fn endpoints_are_unique_per_chat_and_capped(pool: PgPool) {
+    let chat_id = unique_chat_id();
+    seed_chat(&pool, chat_id).await;
+    let url = |n: i64| format!("https://consumer.example.com/hook/{n}");
+
+    endpoint(&pool, chat_id, &url(0)).await;
+    assert!(matches!(
+        webhooks::create_endpoint(&pool, &cipher(), chat_id, &url(0), MODERATOR)
+            .await
+            .unwrap(),
+        CreateOutcome::Duplicate
+    ));
+    for n in 1..webhooks::MAX_ENDPOINTS_PER_CHAT {
+        endpoint(&pool, chat_id, &url(n)).await;
+    }
+    assert!(matches!(
+        webhooks::create_endpoint(&pool, &cipher(), chat_id, &url(99), MODERATOR)
+            .await
+            .unwrap(),
+        CreateOutcome::LimitReached
+    ));
+
+    let first = webhooks::list_endpoints(&pool, chat_id).await.unwrap()[0].id;
+    assert!(
+        webhooks::delete_endpoint(&pool, chat_id, first)
+            .await
+            .unwrap()
+    );
+    assert!(
+        !webhooks::delete_endpoint(&pool, chat_id, first)
+            .await
+            .unwrap()
+    );
+}
+
+#[sqlx::test(migrations = "./migrations")]
+#[ignore = "requires postgres"]
+async fn secrets_key_rotation_rewraps_endpoint_secrets(pool: PgPool) {
+    let chat_id = unique_chat_id();
+    seed_chat(&pool, chat_id).await;
+    let (endpoint_id, secret) = endpoint(&pool, chat_id, "https://consumer.example.com/h").await;
+
+    let old = test_config().secrets_key.expect("test secrets key");
+    let new = SecretsKey::new("ICEiIyQlJicoKSorLC0uLzAxMjM0NTY3ODk6Ozw9Pj8=");
+    let rotating = ApiKeyCipher::new(Some(&new), Some(&old)).unwrap();
+    let report = api_keys::rotate(&pool, &rotating).await.unwrap();
+    assert_eq!(report.rewrapped, 1);
+
+    let stored: String = sqlx::query_scalar("SELECT secret FROM webhook_endpoints WHERE id = $1")
+        .bind(endpoint_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    let new_only = ApiKeyCipher::new(Some(&new), None).unwrap();
+    assert_eq!(
+        new_only.open_webhook_secret(endpoint_id, &stored).unwrap(),
+        secret
+    );
+}